@@ -0,0 +1,79 @@
+//! Performance benchmarks for parsing, diagnostics and completion on large stylesheets
+//!
+//! The internals are reached through the test utilities, run the benchmarks with:
+//!
+//! ```text
+//! cargo bench --features test-utils
+//! ```
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use tower_lsp::lsp_types::Position;
+use unity_code_native::test_utils::{generate_stylesheet, StyleSheetFixture};
+use unity_code_native::UssAnalyzer;
+
+/// Number of rules of the generated stylesheets
+const RULE_COUNTS: &[usize] = &[100, 500, 2_000];
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for &rule_count in RULE_COUNTS {
+        let mut fixture = StyleSheetFixture::new(generate_stylesheet(rule_count));
+        group.bench_function(BenchmarkId::from_parameter(rule_count), |b| b.iter(|| black_box(fixture.parse())));
+    }
+    group.finish();
+}
+
+fn bench_diagnostics(c: &mut Criterion) {
+    let mut group = c.benchmark_group("diagnostics");
+    for &rule_count in RULE_COUNTS {
+        let fixture = StyleSheetFixture::new(generate_stylesheet(rule_count));
+        group.bench_function(BenchmarkId::from_parameter(rule_count), |b| b.iter(|| black_box(fixture.diagnostics())));
+    }
+    group.finish();
+}
+
+fn bench_new_provider_diagnostics(c: &mut Criterion) {
+    // a new provider per request, e.g. for inline styles, only pays for the tables the first time
+    let mut group = c.benchmark_group("new_provider_diagnostics");
+    for &rule_count in RULE_COUNTS {
+        let fixture = StyleSheetFixture::new(generate_stylesheet(rule_count));
+        group.bench_function(BenchmarkId::from_parameter(rule_count), |b| {
+            b.iter(|| black_box(fixture.diagnostics_with_new_provider()))
+        });
+    }
+    group.finish();
+}
+
+fn bench_completion(c: &mut Criterion) {
+    let mut group = c.benchmark_group("completion");
+    for &rule_count in RULE_COUNTS {
+        // complete a property value in the last rule, the worst case for tree traversal
+        let mut content = generate_stylesheet(rule_count);
+        content.push_str(".last {\n    flex-direction: ");
+        let position = Position::new(content.lines().count() as u32 - 1, 20);
+        let fixture = StyleSheetFixture::new(content);
+        group.bench_function(BenchmarkId::from_parameter(rule_count), |b| b.iter(|| black_box(fixture.complete(position))));
+    }
+    group.finish();
+}
+
+fn bench_analyze(c: &mut Criterion) {
+    // parsing and diagnostics through the public API, as other tools use them
+    let mut group = c.benchmark_group("analyze");
+    for &rule_count in RULE_COUNTS {
+        let content = generate_stylesheet(rule_count);
+        let mut analyzer = UssAnalyzer::new();
+        group.bench_function(BenchmarkId::from_parameter(rule_count), |b| b.iter(|| black_box(analyzer.analyze(&content))));
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    // the analysis of the largest stylesheets takes seconds, the default of 100 samples would take hours
+    config = Criterion::default().sample_size(10);
+    targets = bench_parse, bench_diagnostics, bench_new_provider_diagnostics, bench_completion, bench_analyze
+}
+criterion_main!(benches);
@@ -0,0 +1,131 @@
+//! Startup of the `unity_code_native` binary
+//!
+//! Subcommands run and exit. Given a project path, the UDP server that monitors the Unity Editor and the
+//! USS language server run side by side.
+
+use std::path::PathBuf;
+use std::process;
+use std::sync::Arc;
+
+use log::{error, info};
+
+use crate::cli;
+use crate::cs::docs_manager::CsDocsManager;
+use crate::cs::custom_elements::CustomElementScanner;
+use crate::language::request_timing;
+use crate::logging;
+use crate::monitor;
+use crate::server::Server;
+use crate::unity_project_manager::UnityProjectManager;
+use crate::uss::server::start_uss_language_server;
+use crate::uxml_schema_manager::UxmlSchemaManager;
+
+/// Run the `unity_code_native` binary with its command line arguments, the first one is the program name
+///
+/// Runs a subcommand if one is given, otherwise monitors the Unity project and serves USS language
+/// features until the process exits.
+pub async fn run(args: Vec<String>) {
+    if let Some(exit_code) = cli::run_subcommand(args.get(1..).unwrap_or_default()).await {
+        process::exit(exit_code);
+    }
+
+    if args.len() < 2 {
+        // Use eprintln for usage info since logger isn't initialized yet
+        eprintln!("Usage: {} <project_path>", args[0]);
+        eprintln!("  <project_path>: Start Unity monitor server with USS Language Server");
+        eprintln!("Example: {} F:\\projects\\unity\\MyProject", args[0]);
+        eprintln!("Note: Both UDP server and USS Language Server run concurrently.");
+        cli::print_subcommand_usage(&args[0]);
+        process::exit(1);
+    }
+
+    // Initialize file logging for combined mode
+    if let Err(e) = logging::init_logger() {
+        eprintln!("Failed to initialize logger: {}", e);
+        process::exit(1);
+    }
+    request_timing::init();
+    
+    // Log startup information
+    info!("Unity Code Native starting with both UDP server and USS Language Server");
+    info!("Command line arguments: {:?}", args);
+
+    let target_project_path = monitor::normalize_path(&args[1]);
+    info!("Monitoring project path: {}", target_project_path);
+    
+    // Create Unity project manager instance
+    let unity_project_manager = UnityProjectManager::new(PathBuf::from(&target_project_path));
+    match unity_project_manager.detect_unity_version() {
+        Ok(version) => info!("Detected Unity version: {}", version),
+        Err(e) => info!("Unity project detection failed: {}", e),
+    }
+    
+    // Create UXML schema manager once for the entire application
+    let mut uxml_schema_manager = UxmlSchemaManager::new(PathBuf::from(&target_project_path).join("UIElementsSchema"));
+    uxml_schema_manager.set_custom_elements(CustomElementScanner::new(PathBuf::from(&target_project_path)));
+    uxml_schema_manager.set_unity_version(unity_project_manager.get_parsed_unity_version());
+    info!("UXML schema manager created");
+
+    // Docs are compiled by the UDP server, the USS Language Server shows the compiled ones in hover
+    let docs_manager = match CsDocsManager::new(PathBuf::from(&target_project_path)) {
+        Ok(manager) => Arc::new(tokio::sync::Mutex::new(manager)),
+        Err(e) => {
+            error!("Failed to create docs manager: {}", e);
+            process::exit(1);
+        }
+    };
+
+    // Start UDP server first
+    let target_project_path_clone = target_project_path.clone();
+    let udp_docs_manager = docs_manager.clone();
+    let udp_server_task = async move {
+        match Server::new(target_project_path_clone, udp_docs_manager).await {
+            Ok(mut server) => {
+                info!("UDP server started successfully");
+                server.run().await;
+                Ok(())
+            }
+            Err(e) => {
+                error!("Failed to create UDP server: {}", e);
+                Err(e)
+            }
+        }
+    };
+    
+    // Start USS Language Server concurrently
+    let project_path_for_lsp = PathBuf::from(&target_project_path);
+    let lsp_docs_manager = docs_manager.clone();
+    let lsp_server_task = async move {
+        info!("Starting USS Language Server (will handle LSP requests when connected)");
+        if let Err(e) = start_uss_language_server(project_path_for_lsp, Arc::new(tokio::sync::Mutex::new(uxml_schema_manager)), lsp_docs_manager).await {
+            error!("USS Language Server error: {:?}", e);
+        }
+        info!("USS Language Server stopped");
+    };
+    
+    // Run both servers concurrently - if either stops, continue with the other
+    tokio::select! {
+        result = udp_server_task => {
+            if let Err(_) = result {
+                error!("UDP server failed, shutting down");
+                process::exit(1);
+            }
+            info!("UDP server stopped");
+        }
+        _ = lsp_server_task => {
+             info!("LSP server task completed, UDP server continues running");
+             // Continue running UDP server even if LSP server stops
+             match Server::new(target_project_path, docs_manager).await {
+                 Ok(mut server) => {
+                     server.run().await;
+                 }
+                 Err(e) => {
+                     error!("Failed to restart UDP server: {}", e);
+                     process::exit(1);
+                 }
+             }
+         }
+    }
+    
+    info!("Unity Code Native shutting down");
+}
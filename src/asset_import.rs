@@ -0,0 +1,109 @@
+//! Assets to Import After a Save
+//!
+//! Unity imports changed files when its window gains focus, so after saving a style sheet users switch to
+//! Unity to see the change. Instead, an editor can ask Unity to import just the saved files (through its
+//! connection to the Editor, e.g. `AssetDatabase.ImportAsset`). This server has no connection to the Editor,
+//! it tells the editor which of the saved files Unity should import, see [`MessageType::GetAssetsToImport`].
+//!
+//! C# scripts are left to Hot Reload for Unity when it is enabled, importing them would recompile the scripts
+//! and reload the domain, which is what Hot Reload avoids.
+//!
+//! [`MessageType::GetAssetsToImport`]: crate::protocol::MessageType::GetAssetsToImport
+
+use std::path::Path;
+
+use crate::protocol::{AssetsToImportResponse, SkippedAsset};
+
+/// Extensions of the files that are imported, lower case
+const IMPORTED_EXTENSIONS: [&str; 4] = ["uss", "tss", "uxml", "cs"];
+
+/// The assets Unity should import after files were saved
+///
+/// `paths` are absolute paths of the saved files, the assets are paths relative to the project with `/`,
+/// e.g. `Assets/UI/Main.uss`. `unity_process_id` is 0 if Unity is not running.
+pub fn assets_to_import(
+    project_root: &Path,
+    paths: &[String],
+    unity_process_id: u32,
+    is_hot_reload_enabled: bool,
+) -> AssetsToImportResponse {
+    let mut response = AssetsToImportResponse {
+        unity_process_id,
+        ..Default::default()
+    };
+    let project_root = std::fs::canonicalize(project_root).unwrap_or_else(|_| project_root.to_path_buf());
+
+    for path in paths {
+        let mut skip = |reason: &str| {
+            response.skipped.push(SkippedAsset {
+                path: path.clone(),
+                reason: reason.to_string(),
+            })
+        };
+
+        let Ok(file_path) = std::fs::canonicalize(path) else {
+            skip("File not found");
+            continue;
+        };
+        let Some(asset_path) = file_path
+            .strip_prefix(&project_root)
+            .ok()
+            .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+            .filter(|relative| relative.starts_with("Assets/") || relative.starts_with("Packages/"))
+        else {
+            skip("Not an asset of the project");
+            continue;
+        };
+
+        let extension = file_path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+        if !IMPORTED_EXTENSIONS.contains(&extension.as_str()) {
+            skip("Not a script, style sheet or UXML document");
+        } else if extension == "cs" && is_hot_reload_enabled {
+            skip("Applied by Hot Reload for Unity");
+        } else if !response.asset_paths.contains(&asset_path) {
+            response.asset_paths.push(asset_path);
+        }
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_assets_to_import() {
+        let project = TempDir::new().unwrap();
+        let ui = project.path().join("Assets").join("UI");
+        std::fs::create_dir_all(&ui).unwrap();
+        for file in ["Main.uss", "Main.uxml", "Player.cs", "notes.txt"] {
+            std::fs::write(ui.join(file), "").unwrap();
+        }
+        std::fs::write(project.path().join("README.cs"), "").unwrap();
+        let path = |relative: &str| project.path().join(relative).to_string_lossy().to_string();
+
+        let paths = [
+            path("Assets/UI/Main.uss"),
+            path("Assets/UI/Main.uxml"),
+            path("Assets/UI/Player.cs"),
+            path("Assets/UI/notes.txt"),
+            path("README.cs"),
+            path("Assets/UI/Deleted.uss"),
+            path("Assets/UI/Main.uss"),
+        ];
+        let response = assets_to_import(project.path(), &paths, 42, false);
+        assert_eq!(response.unity_process_id, 42);
+        assert_eq!(response.asset_paths, vec!["Assets/UI/Main.uss", "Assets/UI/Main.uxml", "Assets/UI/Player.cs"]);
+        let reasons: Vec<_> = response.skipped.iter().map(|s| s.reason.as_str()).collect();
+        assert_eq!(
+            reasons,
+            vec!["Not a script, style sheet or UXML document", "Not an asset of the project", "File not found"]
+        );
+
+        let response = assets_to_import(project.path(), &paths[..3], 42, true);
+        assert_eq!(response.asset_paths, vec!["Assets/UI/Main.uss", "Assets/UI/Main.uxml"]);
+        assert_eq!(response.skipped[0].path, paths[2]);
+        assert_eq!(response.skipped[0].reason, "Applied by Hot Reload for Unity");
+    }
+}
@@ -0,0 +1,594 @@
+//! Batch Check
+//!
+//! Checks USS and UXML files without an editor, for the `check` subcommand in CI pipelines.
+//! Files get the diagnostics the language server reports when they are opened: the project's
+//! `UnityCode.toml` applies, ignored files are skipped and diagnostics in the baseline are not reported.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+
+use futures::future::join_all;
+use serde::Serialize;
+use thiserror::Error;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Range, Url};
+
+use crate::config::{ConfigError, UnityCodeConfig};
+use crate::dir_changed::{DirChangedError, FileChanges};
+use crate::language::asset_url::{
+    create_project_url_with_normalization, project_url_to_path, project_url_to_relative_path,
+};
+use crate::language::project_packages::ProjectPackages;
+use crate::language::panic_guard::panic_message;
+use crate::language::worker_pool::{WorkError, WorkerPool};
+use crate::language::tree_utils::byte_to_position;
+use crate::unity_asset_database::AssetPathResolver;
+use crate::unity_project_manager::UnityProjectManager;
+use crate::uss::baseline::{BaselineError, DiagnosticsBaseline};
+use crate::uss::constants::PROJECT_SCHEME;
+use crate::uss::definitions::UssDefinitions;
+use crate::uss::diagnostics::UssDiagnostics;
+use crate::uss::diagnostics_snapshot::DiagnosticsSnapshot;
+use crate::uss::profile::detect_profile;
+use crate::uss::document::UssDocument;
+use crate::uss::import_graph::UssImportGraph;
+use crate::uss::parser::UssParser;
+use crate::uss::settings::UssSettings;
+use crate::uss::unused_variables::UnusedVariableScope;
+use crate::uxml::parser::{find_syntax_error, parse_uxml_elements};
+use crate::uxml::templates::TemplateResolver;
+
+/// Diagnostic code of UXML documents that aren't well formed XML
+pub const UXML_SYNTAX_ERROR_CODE: &str = "syntax-error";
+
+/// How the results are printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CheckFormat {
+    /// One line per diagnostic, `path:line:column: severity[code]: message`
+    #[default]
+    Text,
+    /// A [`CheckReport`]
+    Json,
+}
+
+impl FromStr for CheckFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(CheckFormat::Text),
+            "json" => Ok(CheckFormat::Json),
+            _ => Err(format!("Unknown check format '{}', expected 'text' or 'json'", s)),
+        }
+    }
+}
+
+/// Errors that stop a check before any file is checked
+#[derive(Debug, Error)]
+pub enum CheckError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Baseline(#[from] BaselineError),
+}
+
+/// Diagnostics of a checked file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileReport {
+    /// Project relative path, e.g. `Assets/UI/Main.uss`, or the path as given for files outside the project
+    pub path: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Result of checking files
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckReport {
+    /// Checked files, including the ones without diagnostics
+    pub files: Vec<FileReport>,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+impl CheckReport {
+    pub fn add(&mut self, file: FileReport) {
+        self.files.push(file);
+        self.count();
+    }
+
+    /// Replace the diagnostics of a file, `None` if it is no longer checked, e.g. because it was deleted
+    pub fn update(&mut self, path: &str, file: Option<FileReport>) {
+        self.files.retain(|f| f.path != path);
+        self.files.extend(file);
+        self.files.sort_by(|a, b| a.path.cmp(&b.path));
+        self.count();
+    }
+
+    /// e.g. `2 errors, 1 warnings in 5 files`
+    pub fn summary(&self) -> String {
+        format!("{} errors, {} warnings in {} files", self.errors, self.warnings, self.files.len())
+    }
+
+    fn count(&mut self) {
+        let severities = self.files.iter().flat_map(|file| &file.diagnostics).map(|d| d.severity);
+        let (mut errors, mut warnings) = (0, 0);
+        for severity in severities {
+            match severity {
+                Some(DiagnosticSeverity::ERROR) => errors += 1,
+                Some(DiagnosticSeverity::WARNING) => warnings += 1,
+                _ => {}
+            }
+        }
+        self.errors = errors;
+        self.warnings = warnings;
+    }
+}
+
+impl fmt::Display for FileReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for diagnostic in &self.diagnostics {
+            let severity = match diagnostic.severity {
+                Some(DiagnosticSeverity::ERROR) => "error",
+                Some(DiagnosticSeverity::WARNING) => "warning",
+                Some(DiagnosticSeverity::INFORMATION) => "info",
+                _ => "hint",
+            };
+            let code = match &diagnostic.code {
+                Some(NumberOrString::String(code)) => format!("[{}]", code),
+                Some(NumberOrString::Number(code)) => format!("[{}]", code),
+                None => String::new(),
+            };
+            // editors and CI annotations count lines and columns from 1
+            writeln!(
+                f,
+                "{}:{}:{}: {}{}: {}",
+                self.path,
+                diagnostic.range.start.line + 1,
+                diagnostic.range.start.character + 1,
+                severity,
+                code,
+                diagnostic.message
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for file in &self.files {
+            write!(f, "{}", file)?;
+        }
+        write!(f, "{}", self.summary())
+    }
+}
+
+/// Checks files of a project
+pub struct Checker {
+    project_root: PathBuf,
+    config: UnityCodeConfig,
+    baseline: Option<DiagnosticsBaseline>,
+    diagnostics: UssDiagnostics,
+    /// Built once when the first style sheet is checked, then only read, so files are checked at the same time
+    import_graph: OnceLock<UssImportGraph>,
+    packages: ProjectPackages,
+    /// Built when the first missing asset is looked for
+    asset_paths: OnceLock<Arc<AssetPathResolver>>,
+}
+
+impl Checker {
+    /// Create a checker with the project's configuration and baseline, which must be valid
+    pub fn new(project_root: PathBuf) -> Result<Self, CheckError> {
+        let baseline = DiagnosticsBaseline::load(&project_root)?;
+        Self::with_baseline(project_root, baseline).map_err(Into::into)
+    }
+
+    /// Create a checker with the project's configuration, which must be valid, reporting diagnostics in the baseline
+    pub fn without_baseline(project_root: PathBuf) -> Result<Self, ConfigError> {
+        Self::with_baseline(project_root, None)
+    }
+
+    fn with_baseline(project_root: PathBuf, baseline: Option<DiagnosticsBaseline>) -> Result<Self, ConfigError> {
+        let config = UnityCodeConfig::load(&project_root)?.unwrap_or_default();
+
+        let mut settings = UssSettings::default();
+        config.apply_to(&mut settings);
+        let mut diagnostics = UssDiagnostics::new();
+        diagnostics.set_settings(settings.diagnostics);
+        diagnostics.set_unity_version(UnityProjectManager::new(project_root.clone()).get_parsed_unity_version());
+
+        Ok(Self {
+            packages: ProjectPackages::find(&project_root),
+            project_root,
+            config,
+            baseline,
+            diagnostics,
+            import_graph: OnceLock::new(),
+            asset_paths: OnceLock::new(),
+        })
+    }
+
+    /// Check files at the same time on a [`WorkerPool`], the results are in the order of `paths`
+    pub async fn check_files(self: &Arc<Self>, paths: &[PathBuf]) -> Vec<io::Result<Option<FileReport>>> {
+        let pool = WorkerPool::per_core();
+        let checks = paths.iter().map(|path| {
+            let (checker, path) = (self.clone(), path.clone());
+            pool.run(path.clone(), move || checker.check_file(&path))
+        });
+
+        join_all(checks)
+            .await
+            .into_iter()
+            .map(|result| match result {
+                Ok(result) => result,
+                Err(WorkError::Panicked(e)) if e.is_panic() => Err(io::Error::other(format!(
+                    "the check failed unexpectedly: {}",
+                    panic_message(e.into_panic().as_ref())
+                ))),
+                Err(WorkError::Panicked(e)) => Err(io::Error::other(format!("the check failed unexpectedly: {}", e))),
+                // a path given more than once is checked for its last occurrence
+                Err(WorkError::Superseded) => Err(io::Error::other("the file is checked more than once")),
+            })
+            .collect()
+    }
+
+    /// Check a USS, TSS or UXML file, `None` for other files and files the configuration ignores
+    pub fn check_file(&self, path: &Path) -> io::Result<Option<FileReport>> {
+        let Some((relative_path, content, mut diagnostics)) = self.diagnose(path)? else {
+            return Ok(None);
+        };
+
+        if let (Some(baseline), Some(relative_path)) = (&self.baseline, &relative_path) {
+            baseline.filter(relative_path, &mut diagnostics, &content);
+        }
+
+        Ok(Some(FileReport {
+            path: relative_path.unwrap_or_else(|| path.display().to_string()),
+            diagnostics,
+        }))
+    }
+
+    /// Record the diagnostics of the files in the project as a baseline, the existing baseline is not applied
+    ///
+    /// Files the configuration ignores and files that can't be read are left out. Reads the files, async code
+    /// should call it with `spawn_blocking`.
+    pub fn baseline_of(&self, paths: &[PathBuf]) -> DiagnosticsBaseline {
+        let mut baseline = DiagnosticsBaseline::default();
+        for path in paths {
+            match self.diagnose(path) {
+                Ok(Some((Some(relative_path), content, diagnostics))) => {
+                    baseline.set_file(&relative_path, &diagnostics, &content)
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("Failed to read {}: {}", path.display(), e),
+            }
+        }
+        baseline
+    }
+
+    /// Project relative path, content and diagnostics of a file, before the baseline is applied
+    fn diagnose(&self, path: &Path) -> io::Result<Option<(Option<String>, String, Vec<Diagnostic>)>> {
+        let extension = path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase());
+        let is_style_sheet = matches!(extension.as_deref(), Some("uss") | Some("tss"));
+        if !is_style_sheet && extension.as_deref() != Some("uxml") {
+            return Ok(None);
+        }
+
+        let project_url = create_project_url_with_normalization(path, &self.project_root).ok();
+        let relative_path = project_url.as_ref().and_then(project_url_to_relative_path);
+        if relative_path.as_deref().is_some_and(|path| self.config.is_ignored(path)) {
+            return Ok(None);
+        }
+
+        let content = std::fs::read_to_string(path)?;
+        let diagnostics = if is_style_sheet {
+            self.check_style_sheet(path, project_url, &content)
+        } else {
+            self.check_uxml(path, &content)
+        };
+        Ok(Some((relative_path, content, diagnostics)))
+    }
+
+    /// How a file is named in reports, its project relative path if it is in the project
+    pub fn report_path(&self, path: &Path) -> String {
+        let relative = create_project_url_with_normalization(path, &self.project_root)
+            .ok()
+            .and_then(|url| project_url_to_relative_path(&url))
+            // deleted files can't be made canonical
+            .or_else(|| {
+                let root = std::fs::canonicalize(&self.project_root).ok()?;
+                let relative = path.strip_prefix(root).ok()?;
+                Some(relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/"))
+            });
+        relative.unwrap_or_else(|| path.display().to_string())
+    }
+
+    /// Diagnostics of a style sheet, analyzed like open documents of the language server
+    fn check_style_sheet(&self, path: &Path, project_url: Option<Url>, content: &str) -> Vec<Diagnostic> {
+        let Ok(uri) = Url::from_file_path(path) else {
+            return Vec::new();
+        };
+        let mut document = UssDocument::new(uri, content.to_string(), 0, UssDefinitions::shared());
+        document.parse(&mut UssParser::default());
+        document.extract_variables_with_source_url(project_url.as_ref());
+        let Some(tree) = document.tree().cloned() else {
+            return Vec::new();
+        };
+
+        let mut imports = Vec::new();
+        let mut import_diagnostics = Vec::new();
+        let mut importers = Vec::new();
+        if let Some(project_url) = &project_url {
+            let import_graph = self.import_graph.get_or_init(|| {
+                let mut graph = UssImportGraph::new(self.project_root.clone());
+                graph.refresh();
+                graph
+            });
+            // the graph has the files as they are on disk, but not every file, e.g. themes, so the
+            // file is checked with its own imports
+            imports = import_graph.extract_imports(&tree, content, project_url);
+            import_diagnostics = import_graph.import_diagnostics_of(project_url, &imports);
+            self.diagnostics.settings().apply(&mut import_diagnostics);
+            if self.diagnostics.settings().unused_variables == UnusedVariableScope::Importers {
+                importers = import_graph.importers_of(project_url);
+            }
+        }
+
+        let profile = project_url
+            .as_ref()
+            .and_then(project_url_to_relative_path)
+            .and_then(|path| detect_profile(&path, &self.config));
+        let snapshot = DiagnosticsSnapshot {
+            analyzer: self.diagnostics.clone(),
+            tree,
+            content: content.to_string(),
+            variable_resolver: document.variable_resolver.clone(),
+            project_url,
+            project_root: self.project_root.clone(),
+            imports,
+            import_diagnostics,
+            importers,
+            open_importers: Vec::new(),
+            asset_paths: self
+                .asset_paths
+                .get_or_init(|| Arc::new(AssetPathResolver::new(&self.project_root)))
+                .clone(),
+            profile,
+        };
+        let (mut diagnostics, suppressions) = snapshot.analyze(None);
+        suppressions.apply(&mut diagnostics);
+        diagnostics
+    }
+
+    fn check_uxml(&self, path: &Path, content: &str) -> Vec<Diagnostic> {
+        if let Some((offset, message)) = find_syntax_error(content) {
+            let position = byte_to_position(offset.min(content.len()), content);
+            return vec![Diagnostic {
+                range: Range::new(position, position),
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(NumberOrString::String(UXML_SYNTAX_ERROR_CODE.to_string())),
+                source: Some("uxml".to_string()),
+                message,
+                ..Default::default()
+            }];
+        }
+
+        let resolver = TemplateResolver {
+            document_path: path,
+            project_root: &self.project_root,
+            packages: Some(&self.packages),
+        };
+        resolver.diagnostics(&parse_uxml_elements(content))
+    }
+}
+
+/// Files to check: the files given and the USS, TSS and UXML files in the directories given, in path order
+pub fn collect_files(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            collect_files_in_dir(path, &mut files);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    files.sort();
+    files.dedup();
+    files
+}
+
+fn collect_files_in_dir(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files_in_dir(&path, files);
+        } else if path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| matches!(e.to_ascii_lowercase().as_str(), "uss" | "tss" | "uxml"))
+        {
+            files.push(path);
+        }
+    }
+}
+
+/// Changed files to check again, for the `--watch` option
+///
+/// Folders given are watched recursively, files given are watched in their folder.
+pub struct CheckWatcher {
+    changes: FileChanges,
+    /// Canonical paths of the folders and files given
+    roots: Vec<PathBuf>,
+}
+
+impl CheckWatcher {
+    pub fn new(paths: &[PathBuf]) -> Result<Self, DirChangedError> {
+        let mut changes = FileChanges::new(&["uss", "tss", "uxml"])?;
+        let mut roots = Vec::new();
+        for path in paths {
+            let Ok(root) = std::fs::canonicalize(path) else {
+                continue;
+            };
+            if root.is_dir() {
+                changes.watch(&root, true)?;
+            } else if let Some(dir) = root.parent() {
+                changes.watch(dir, false)?;
+            }
+            roots.push(root);
+        }
+        Ok(Self { changes, roots })
+    }
+
+    /// Files changed, created or deleted since the last call, in path order
+    pub fn take_changed_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = self
+            .changes
+            .take_changes()
+            .into_iter()
+            .filter(|path| self.roots.iter().any(|root| path.starts_with(root)))
+            .collect();
+        files.sort();
+        files
+    }
+}
+
+/// The Unity project a path is in, the closest folder with `ProjectSettings/ProjectVersion.txt`
+pub fn find_project_root(path: &Path) -> Option<PathBuf> {
+    let path = std::fs::canonicalize(path).ok()?;
+    path.ancestors()
+        .find(|dir| UnityProjectManager::new(dir.to_path_buf()).is_valid_unity_project())
+        .map(Path::to_path_buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uss::baseline::BaselineSummary;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn project() -> TempDir {
+        let project = TempDir::new().unwrap();
+        fs::create_dir_all(project.path().join("ProjectSettings")).unwrap();
+        fs::write(
+            project.path().join("ProjectSettings/ProjectVersion.txt"),
+            "m_EditorVersion: 6000.0.51f1\n",
+        )
+        .unwrap();
+        fs::create_dir_all(project.path().join("Assets/UI/Generated")).unwrap();
+        fs::write(project.path().join("Assets/UI/Main.uss"), ".a { colr: red; }\n.b { color: red; }\n").unwrap();
+        fs::write(project.path().join("Assets/UI/Main.uxml"), "<ui:UXML>\n  <ui:Button>\n</ui:UXML>\n").unwrap();
+        fs::write(project.path().join("Assets/UI/Generated/Out.uss"), ".a { colr: red; }\n").unwrap();
+        fs::write(project.path().join("Assets/UI/readme.txt"), "").unwrap();
+        fs::write(project.path().join("UnityCode.toml"), "ignore = [\"Assets/UI/Generated/**\"]\n").unwrap();
+        project
+    }
+
+    fn check(project: &Path) -> CheckReport {
+        let checker = Checker::new(project.to_path_buf()).unwrap();
+        let mut report = CheckReport::default();
+        for file in collect_files(&[project.join("Assets")]) {
+            if let Some(file) = checker.check_file(&file).unwrap() {
+                report.add(file);
+            }
+        }
+        report
+    }
+
+    #[test]
+    fn test_check_project() {
+        let project = project();
+        assert_eq!(find_project_root(&project.path().join("Assets/UI")), Some(fs::canonicalize(project.path()).unwrap()));
+
+        let report = check(project.path());
+        let paths: Vec<_> = report.files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, vec!["Assets/UI/Main.uss", "Assets/UI/Main.uxml"]);
+        assert_eq!(report.errors, 2);
+
+        let uxml = &report.files[1].diagnostics[0];
+        assert_eq!(uxml.code, Some(NumberOrString::String(UXML_SYNTAX_ERROR_CODE.to_string())));
+        let text = report.to_string();
+        assert!(text.starts_with("Assets/UI/Main.uss:1:6: error[unknown-property]"), "{}", text);
+        assert!(text.ends_with("2 errors, 0 warnings in 2 files"), "{}", text);
+
+        // diagnostics in the baseline are not reported, ignored style sheets aren't recorded
+        let style_sheets = collect_files(&[project.path().join("Assets/UI/Main.uss"), project.path().join("Assets/UI/Generated")]);
+        let baseline = Checker::without_baseline(project.path().to_path_buf()).unwrap().baseline_of(&style_sheets);
+        assert_eq!(baseline.summary(), BaselineSummary { files: 1, diagnostics: 1 });
+        baseline.save(project.path()).unwrap();
+        assert_eq!(check(project.path()).errors, 1);
+
+        // the baseline is recorded again with the diagnostics it suppresses
+        let baseline = Checker::without_baseline(project.path().to_path_buf()).unwrap().baseline_of(&style_sheets);
+        assert_eq!(baseline.summary().diagnostics, 1);
+    }
+
+    #[test]
+    fn test_missing_assets() {
+        let project = project();
+        let root = project.path();
+        fs::create_dir_all(root.join("Packages/com.example.ui/Resources/Icons")).unwrap();
+        fs::write(root.join("Packages/com.example.ui/package.json"), r#"{ "name": "com.example.ui" }"#).unwrap();
+        fs::write(root.join("Packages/com.example.ui/Resources/Icons/star.png"), "").unwrap();
+        fs::write(
+            root.join("Assets/UI/Main.uss"),
+            ".a { background-image: resource(\"Icons/star\"); }\n.b { background-image: resource(\"Icons/moon\"); }\n",
+        )
+        .unwrap();
+
+        let report = check(root);
+        let missing: Vec<_> = report.files[0]
+            .diagnostics
+            .iter()
+            .filter(|d| d.code == Some(NumberOrString::String("asset-not-found".to_string())))
+            .collect();
+        assert_eq!(missing.len(), 1, "{:?}", report.files[0].diagnostics);
+        assert_eq!(missing[0].range.start.line, 1);
+        assert!(missing[0].message.ends_with("Icons/moon"), "{}", missing[0].message);
+    }
+
+    #[tokio::test]
+    async fn test_check_files_concurrently() {
+        let project = project();
+        for i in 0..20 {
+            fs::write(project.path().join(format!("Assets/UI/Extra{:02}.uss", i)), ".a { colr: red; }\n").unwrap();
+        }
+        let checker = Arc::new(Checker::new(project.path().to_path_buf()).unwrap());
+        let files = collect_files(&[project.path().join("Assets")]);
+
+        let results = checker.check_files(&files).await;
+        assert_eq!(results.len(), files.len());
+        // the same reports as checking one file after another, in the same order
+        for (file, result) in files.iter().zip(results) {
+            assert_eq!(result.unwrap(), checker.check_file(file).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_update_report() {
+        let error = Diagnostic {
+            severity: Some(DiagnosticSeverity::ERROR),
+            message: "error".to_string(),
+            ..Default::default()
+        };
+        let file = |path: &str, diagnostics: Vec<Diagnostic>| FileReport { path: path.to_string(), diagnostics };
+
+        let mut report = CheckReport::default();
+        report.add(file("b.uss", vec![error.clone()]));
+        report.update("a.uss", Some(file("a.uss", vec![error.clone(), error.clone()])));
+        assert_eq!(report.errors, 3);
+        assert_eq!(report.files[0].path, "a.uss");
+
+        report.update("b.uss", Some(file("b.uss", Vec::new())));
+        assert_eq!(report.errors, 2);
+        report.update("a.uss", None);
+        assert_eq!(report.summary(), "0 errors, 0 warnings in 1 files");
+    }
+}
@@ -0,0 +1,334 @@
+//! Command line subcommands
+//!
+//! Without a subcommand the program runs the servers for a project, subcommands do one job and exit:
+//!
+//! ```text
+//! unity_code_native export-docs <project_path> <assembly_name> [--format markdown|html] [--output <dir>] [--include-non-public]
+//! unity_code_native protocol-schema [--output <file>]
+//! unity_code_native theme-report <project_path> [<file>...] [--output <file>]
+//! unity_code_native url-references <project_path> [<file>...] [--output <file>]
+//! unity_code_native check <path>... [--project <project_path>] [--format text|json] [--watch]
+//! ```
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::check::{collect_files, find_project_root, CheckFormat, CheckReport, CheckWatcher, Checker, FileReport};
+use crate::cs::docs_export::{export_docs, ExportFormat, ExportOptions};
+use crate::cs::docs_manager::CsDocsManager;
+use crate::monitor::normalize_path;
+use crate::protocol::protocol_schema;
+use crate::language::asset_url::create_project_url_with_normalization;
+use crate::uss::import_graph::{find_style_sheets, find_theme_style_sheets};
+use crate::uss::parser::UssParser;
+use crate::uss::theme_report::ThemeReport;
+use crate::uss::url_references::{UrlReferenceResolver, UrlReferencesReport};
+
+const EXPORT_DOCS_COMMAND: &str = "export-docs";
+const PROTOCOL_SCHEMA_COMMAND: &str = "protocol-schema";
+const THEME_REPORT_COMMAND: &str = "theme-report";
+const URL_REFERENCES_COMMAND: &str = "url-references";
+const CHECK_COMMAND: &str = "check";
+
+/// How often `check --watch` looks for changed files, editors write files in several steps
+const WATCH_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Run the subcommand given in `args` (without the program name)
+///
+/// Returns the exit code, or `None` if `args` are not a subcommand.
+pub async fn run_subcommand(args: &[String]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+    match command.as_str() {
+        EXPORT_DOCS_COMMAND => Some(exit_code(run_export_docs(rest).await)),
+        PROTOCOL_SCHEMA_COMMAND => Some(exit_code(run_protocol_schema(rest))),
+        THEME_REPORT_COMMAND => Some(exit_code(run_theme_report(rest))),
+        URL_REFERENCES_COMMAND => Some(exit_code(run_url_references(rest))),
+        CHECK_COMMAND => Some(exit_code(run_check(rest).await)),
+        _ => None,
+    }
+}
+
+/// Print usage of the subcommands
+pub fn print_subcommand_usage(program: &str) {
+    eprintln!(
+        "       {} {} <project_path> <assembly_name> [--format markdown|html] [--output <dir>] [--include-non-public]",
+        program, EXPORT_DOCS_COMMAND
+    );
+    eprintln!("  Export the documentation of an assembly of the project as static pages");
+    eprintln!("       {} {} [--output <file>]", program, PROTOCOL_SCHEMA_COMMAND);
+    eprintln!("  Print the JSON schema of the UDP messaging protocol");
+    eprintln!("       {} {} <project_path> [<file>...] [--output <file>]", program, THEME_REPORT_COMMAND);
+    eprintln!("  Report where theme variables are defined and overridden as JSON, for all themes if no files are given");
+    eprintln!("       {} {} <project_path> [<file>...] [--output <file>]", program, URL_REFERENCES_COMMAND);
+    eprintln!("  Report the assets style sheets reference as JSON, exits with 1 if any are missing");
+    eprintln!("       {} {} <path>... [--project <project_path>] [--format text|json] [--watch]", program, CHECK_COMMAND);
+    eprintln!("  Check USS and UXML files or folders, exits with 1 if there are errors");
+    eprintln!("  With --watch, check changed files again until stopped");
+}
+
+fn exit_code(result: Result<(), String>) -> i32 {
+    match result {
+        Ok(()) => 0,
+        Err(message) => {
+            eprintln!("Error: {}", message);
+            1
+        }
+    }
+}
+
+async fn run_export_docs(args: &[String]) -> Result<(), String> {
+    let mut positional = Vec::new();
+    let mut format = ExportFormat::Markdown;
+    let mut output = None;
+    let mut include_non_public = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                let value = args.next().ok_or("--format needs a value")?;
+                format = value.parse()?;
+            }
+            "--output" => output = Some(PathBuf::from(args.next().ok_or("--output needs a value")?)),
+            "--include-non-public" => include_non_public = true,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ => positional.push(arg.as_str()),
+        }
+    }
+
+    let [project_path, assembly_name] = positional[..] else {
+        return Err(format!("{} needs a project path and an assembly name", EXPORT_DOCS_COMMAND));
+    };
+
+    let project_root = PathBuf::from(normalize_path(project_path));
+    let mut docs_manager = CsDocsManager::new(project_root).map_err(|e| e.to_string())?;
+    let docs = docs_manager
+        .get_docs_for_assembly(assembly_name)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Assembly '{}' not found in the project", assembly_name))?;
+
+    let output = output.unwrap_or_else(|| PathBuf::from(format!("{}-docs", assembly_name)));
+    let options = ExportOptions {
+        format,
+        include_non_public,
+    };
+    let written = export_docs(&docs, &output, &options).map_err(|e| e.to_string())?;
+
+    println!("Exported {} pages to {}", written.len(), output.display());
+    Ok(())
+}
+
+fn run_protocol_schema(args: &[String]) -> Result<(), String> {
+    let output = match args {
+        [] => None,
+        [option, path] if option == "--output" => Some(PathBuf::from(path)),
+        _ => return Err(format!("Usage: {} [--output <file>]", PROTOCOL_SCHEMA_COMMAND)),
+    };
+
+    let schema = serde_json::to_string_pretty(&protocol_schema()).map_err(|e| e.to_string())?;
+    match output {
+        Some(path) => std::fs::write(&path, schema).map_err(|e| format!("Failed to write {}: {}", path.display(), e)),
+        None => {
+            println!("{}", schema);
+            Ok(())
+        }
+    }
+}
+
+fn run_theme_report(args: &[String]) -> Result<(), String> {
+    let mut positional = Vec::new();
+    let mut output = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => output = Some(PathBuf::from(args.next().ok_or("--output needs a value")?)),
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ => positional.push(arg.as_str()),
+        }
+    }
+
+    let Some((project_path, files)) = positional.split_first() else {
+        return Err(format!("{} needs a project path", THEME_REPORT_COMMAND));
+    };
+
+    let project_root = PathBuf::from(normalize_path(project_path));
+    let report = if files.is_empty() {
+        ThemeReport::for_project(&project_root)
+    } else {
+        // relative paths are relative to the project, like in the editor
+        let files: Vec<PathBuf> = files.iter().map(|file| project_root.join(file)).collect();
+        ThemeReport::build(&project_root, &files)
+    };
+
+    let json = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+    match output {
+        Some(path) => std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e)),
+        None => {
+            println!("{}", json);
+            Ok(())
+        }
+    }
+}
+
+fn run_url_references(args: &[String]) -> Result<(), String> {
+    let mut positional = Vec::new();
+    let mut output = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => output = Some(PathBuf::from(args.next().ok_or("--output needs a value")?)),
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ => positional.push(arg.as_str()),
+        }
+    }
+
+    let Some((project_path, files)) = positional.split_first() else {
+        return Err(format!("{} needs a project path", URL_REFERENCES_COMMAND));
+    };
+
+    let project_root = PathBuf::from(normalize_path(project_path));
+    let files: Vec<PathBuf> = if files.is_empty() {
+        find_style_sheets(&project_root).into_iter().chain(find_theme_style_sheets(&project_root)).collect()
+    } else {
+        // relative paths are relative to the project, like in the editor
+        files.iter().map(|file| project_root.join(file)).collect()
+    };
+
+    let resolver = UrlReferenceResolver::new(project_root.clone());
+    let mut parser = UssParser::default();
+    let mut report = UrlReferencesReport::default();
+    for path in files {
+        let content =
+            std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let source_url = create_project_url_with_normalization(&path, &project_root)
+            .map_err(|e| format!("{} is not in the project: {}", path.display(), e.message))?;
+        let tree = parser.parse(&content, None).ok_or_else(|| format!("Failed to parse {}", path.display()))?;
+        report.add(resolver.file_references(&tree, &content, &source_url));
+    }
+
+    let json = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+    match output {
+        Some(path) => std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?,
+        None => println!("{}", json),
+    }
+    if report.unresolved > 0 {
+        return Err(format!("{} references are missing or invalid", report.unresolved));
+    }
+    Ok(())
+}
+
+async fn run_check(args: &[String]) -> Result<(), String> {
+    let mut paths = Vec::new();
+    let mut project = None;
+    let mut format = CheckFormat::default();
+    let mut watch = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => format = args.next().ok_or("--format needs a value")?.parse()?,
+            "--project" => project = Some(PathBuf::from(normalize_path(args.next().ok_or("--project needs a value")?))),
+            "--watch" => watch = true,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ => paths.push(PathBuf::from(normalize_path(arg))),
+        }
+    }
+
+    if paths.is_empty() {
+        return Err(format!("{} needs files or folders to check", CHECK_COMMAND));
+    }
+    // without --project, the files are checked in the project they are in
+    let project_root = project
+        .or_else(|| find_project_root(&paths[0]))
+        .ok_or_else(|| format!("{} is not in a Unity project, use --project", paths[0].display()))?;
+
+    let checker = Arc::new(Checker::new(project_root).map_err(|e| e.to_string())?);
+    let mut report = CheckReport::default();
+    let files = collect_files(&paths);
+    for (path, result) in files.iter().zip(checker.check_files(&files).await) {
+        match result {
+            Ok(Some(file)) => report.add(file),
+            Ok(None) => {}
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        }
+    }
+
+    match format {
+        CheckFormat::Text => println!("{}", report),
+        CheckFormat::Json if watch => println!("{}", serde_json::to_string(&report).map_err(|e| e.to_string())?),
+        CheckFormat::Json => println!("{}", serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?),
+    }
+
+    if watch {
+        return watch_check(&paths, checker, report, format).await;
+    }
+    if report.errors > 0 {
+        return Err(format!("{} errors found", report.errors));
+    }
+    Ok(())
+}
+
+/// Check changed files again until the process is stopped, printing the diagnostics of each changed file
+///
+/// The JSON format prints one line per change, the report with only the changed files and the totals of all files.
+/// Deleted files are in it without diagnostics.
+async fn watch_check(paths: &[PathBuf], checker: Arc<Checker>, mut report: CheckReport, format: CheckFormat) -> Result<(), String> {
+    let watcher = CheckWatcher::new(paths).map_err(|e| e.to_string())?;
+    if format == CheckFormat::Text {
+        println!("Watching for changes, press Ctrl+C to stop");
+    }
+
+    loop {
+        tokio::time::sleep(WATCH_INTERVAL).await;
+        let changed = watcher.take_changed_files();
+        if changed.is_empty() {
+            continue;
+        }
+
+        let mut changed_files = Vec::new();
+        for (path, result) in changed.iter().zip(checker.check_files(&changed).await) {
+            let file = match result {
+                Ok(file) => file,
+                // deleted files are no longer reported
+                Err(_) if !path.exists() => None,
+                Err(e) => {
+                    eprintln!("Failed to read {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let file_path = file.as_ref().map(|f| f.path.clone()).unwrap_or_else(|| checker.report_path(path));
+            report.update(&file_path, file.clone());
+            if file.is_some() || !path.exists() {
+                let is_deleted = file.is_none();
+                changed_files.push((file.unwrap_or(FileReport { path: file_path, diagnostics: Vec::new() }), is_deleted));
+            }
+        }
+        if changed_files.is_empty() {
+            continue;
+        }
+
+        match format {
+            CheckFormat::Text => {
+                for (file, is_deleted) in &changed_files {
+                    if *is_deleted {
+                        println!("{}: deleted", file.path);
+                    } else if file.diagnostics.is_empty() {
+                        println!("{}: no problems", file.path);
+                    } else {
+                        print!("{}", file);
+                    }
+                }
+                println!("{}", report.summary());
+            }
+            CheckFormat::Json => {
+                let files = changed_files.into_iter().map(|(file, _)| file).collect();
+                let update = CheckReport { files, errors: report.errors, warnings: report.warnings };
+                println!("{}", serde_json::to_string(&update).map_err(|e| e.to_string())?);
+            }
+        }
+    }
+}
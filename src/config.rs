@@ -0,0 +1,487 @@
+//! Project Configuration File
+//!
+//! `UnityCode.toml` in the project root configures the tools for everyone working on the project, e.g.
+//!
+//! ```toml
+//! # project relative paths of files that are not checked
+//! ignore = ["Assets/ThirdParty/**", "Assets/**/*.generated.uss"]
+//!
+//! [format]
+//! enabled = true
+//! declaration-order = "grouped"
+//!
+//! # diagnostics turned on or off by code
+//! [lint]
+//! unused-variable = false
+//!
+//! [diagnostics]
+//! unused-variables = "importers"
+//! mode = "strict-unity"
+//!
+//! [diagnostics.severity]
+//! duplicate-property = "error"
+//!
+//! # style sheets checked as editor or runtime style sheets
+//! [profiles]
+//! editor = ["Assets/Tools/**"]
+//! runtime = ["Assets/UI/**"]
+//!
+//! # assemblies whose C# documentation is compiled
+//! [docs]
+//! exclude = ["*.Generated"]
+//! include = ["Unity.InputSystem"]
+//! packages = false
+//! ```
+//!
+//! The file is loaded at startup and again when it changes or with the [`RELOAD_CONFIG_COMMAND`] command.
+//! What the file sets takes precedence over the editor's settings.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::uss::organize::DeclarationOrder;
+use crate::uss::profile::UssProfile;
+use crate::uss::settings::{SeverityLevel, UssSettings};
+use crate::uss::unity_compatibility::ValidationMode;
+use crate::uss::unused_variables::UnusedVariableScope;
+
+/// Name of the configuration file in the project root
+pub const CONFIG_FILE_NAME: &str = "UnityCode.toml";
+
+/// Command that reloads the configuration file, returns the loaded configuration
+pub const RELOAD_CONFIG_COMMAND: &str = "unityCode/reloadConfig";
+
+/// Minimum time between two checks of the file for changes
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Errors of loading the configuration file
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Invalid {CONFIG_FILE_NAME}: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("Invalid {CONFIG_FILE_NAME}: {}", .0.join("; "))]
+    Invalid(Vec<String>),
+}
+
+/// Content of `UnityCode.toml`, missing entries have their default values
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct UnityCodeConfig {
+    /// Project relative paths of files that get no diagnostics, `*` matches within a folder and `**` any number of folders
+    pub ignore: Vec<String>,
+    pub format: FormatConfig,
+    /// Diagnostics turned on or off by code, e.g. `unused-variable = false`
+    pub lint: BTreeMap<String, bool>,
+    pub diagnostics: DiagnosticsConfig,
+    pub profiles: ProfilesConfig,
+    pub docs: DocsConfig,
+}
+
+/// The `[format]` section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct FormatConfig {
+    /// Whether documents can be formatted
+    pub enabled: bool,
+    /// Order used by the organize declarations code action, `alphabetical` or `grouped`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub declaration_order: Option<String>,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            declaration_order: None,
+        }
+    }
+}
+
+/// The `[diagnostics]` section
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct DiagnosticsConfig {
+    /// Severity by diagnostic code, `error`, `warning`, `info`, `hint` or `off`
+    pub severity: BTreeMap<String, String>,
+    /// Where references are searched for unused variables, `document` or `importers`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unused_variables: Option<String>,
+    /// Which rules values are validated with, `standard` or `strict-unity`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+}
+
+/// The `[profiles]` section, project relative paths of style sheets with a profile, see [`crate::uss::profile`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ProfilesConfig {
+    /// Style sheets used in the Editor
+    pub editor: Vec<String>,
+    /// Style sheets used in the game
+    pub runtime: Vec<String>,
+}
+
+/// The `[docs]` section, which assemblies get C# documentation
+///
+/// Patterns match assembly names, `*` matches any characters and `?` one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct DocsConfig {
+    /// Assemblies that get no documentation, e.g. large generated code
+    pub exclude: Vec<String>,
+    /// Assemblies that get documentation even if excluded or a package assembly with `packages = false`
+    pub include: Vec<String>,
+    /// Whether package assemblies get documentation
+    pub packages: bool,
+}
+
+impl Default for DocsConfig {
+    fn default() -> Self {
+        Self {
+            exclude: Vec::new(),
+            include: Vec::new(),
+            packages: true,
+        }
+    }
+}
+
+impl DocsConfig {
+    /// Whether the documentation of an assembly is compiled, `is_user_code` is false for package assemblies
+    pub fn includes_assembly(&self, assembly_name: &str, is_user_code: bool) -> bool {
+        let matches = |patterns: &[String]| patterns.iter().any(|pattern| path_matches(pattern, assembly_name));
+        if matches(&self.include) {
+            return true;
+        }
+        (is_user_code || self.packages) && !matches(&self.exclude)
+    }
+}
+
+impl UnityCodeConfig {
+    /// Path of the configuration file of a project
+    pub fn path(project_root: &Path) -> PathBuf {
+        project_root.join(CONFIG_FILE_NAME)
+    }
+
+    /// Parse and validate the content of a configuration file
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(text)?;
+        let problems = config.validate();
+        if problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+
+    /// Load the configuration file of a project, `None` if the project doesn't have one
+    pub fn load(project_root: &Path) -> Result<Option<Self>, ConfigError> {
+        let path = Self::path(project_root);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text).map(Some),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(source) => Err(ConfigError::Io { path, source }),
+        }
+    }
+
+    /// Problems of values that are well formed TOML but not valid settings
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        let path_patterns = [
+            ("ignore", &self.ignore),
+            ("profiles.editor", &self.profiles.editor),
+            ("profiles.runtime", &self.profiles.runtime),
+        ];
+        for (key, patterns) in path_patterns {
+            for pattern in patterns {
+                if pattern.is_empty() || pattern.starts_with('/') || pattern.contains('\\') {
+                    problems.push(format!(
+                        "{} pattern '{}' must be a project relative path with '/' separators",
+                        key, pattern
+                    ));
+                }
+            }
+        }
+        for (key, patterns) in [("exclude", &self.docs.exclude), ("include", &self.docs.include)] {
+            for pattern in patterns {
+                if pattern.is_empty() || pattern.contains('/') || pattern.contains('\\') {
+                    problems.push(format!("docs.{} pattern '{}' must be an assembly name", key, pattern));
+                }
+            }
+        }
+        if let Some(order) = &self.format.declaration_order {
+            if DeclarationOrder::from_name(order).is_none() {
+                problems.push(format!("format.declaration-order '{}' must be 'alphabetical' or 'grouped'", order));
+            }
+        }
+        for (code, level) in &self.diagnostics.severity {
+            if SeverityLevel::from_name(level).is_none() {
+                problems.push(format!(
+                    "diagnostics.severity.{} '{}' must be 'error', 'warning', 'info', 'hint' or 'off'",
+                    code, level
+                ));
+            }
+        }
+        if let Some(scope) = &self.diagnostics.unused_variables {
+            if UnusedVariableScope::from_name(scope).is_none() {
+                problems.push(format!("diagnostics.unused-variables '{}' must be 'document' or 'importers'", scope));
+            }
+        }
+        if let Some(mode) = &self.diagnostics.mode {
+            if ValidationMode::from_name(mode).is_none() {
+                problems.push(format!("diagnostics.mode '{}' must be 'standard' or 'strict-unity'", mode));
+            }
+        }
+
+        problems
+    }
+
+    /// Override editor settings with the ones set in this configuration
+    ///
+    /// A code turned off in `[lint]` is off whatever its severity, a code turned on gets its
+    /// severity from `[diagnostics.severity]` or its default one.
+    pub fn apply_to(&self, settings: &mut UssSettings) {
+        settings.format_enabled = self.format.enabled;
+        if let Some(order) = self.format.declaration_order.as_deref().and_then(DeclarationOrder::from_name) {
+            settings.declaration_order = order;
+        }
+
+        let diagnostics = &mut settings.diagnostics;
+        for (code, level) in &self.diagnostics.severity {
+            if let Some(level) = SeverityLevel::from_name(level) {
+                diagnostics.severity_overrides.insert(code.clone(), level);
+            }
+        }
+        for (code, enabled) in &self.lint {
+            if !enabled {
+                diagnostics.severity_overrides.insert(code.clone(), SeverityLevel::Off);
+            } else if diagnostics.severity_overrides.get(code) == Some(&SeverityLevel::Off) {
+                diagnostics.severity_overrides.remove(code);
+            }
+        }
+        if let Some(scope) = self.diagnostics.unused_variables.as_deref().and_then(UnusedVariableScope::from_name) {
+            diagnostics.unused_variables = scope;
+        }
+        if let Some(mode) = self.diagnostics.mode.as_deref().and_then(ValidationMode::from_name) {
+            diagnostics.mode = mode;
+        }
+    }
+
+    /// Whether a file gets no diagnostics, by its project relative path (e.g. `Assets/UI/Main.uss`)
+    pub fn is_ignored(&self, relative_path: &str) -> bool {
+        self.ignore.iter().any(|pattern| path_matches(pattern, relative_path))
+    }
+
+    /// Profile the `[profiles]` section sets for a file by its project relative path, editor patterns first
+    pub fn profile_of(&self, relative_path: &str) -> Option<UssProfile> {
+        let matches = |patterns: &[String]| patterns.iter().any(|pattern| path_matches(pattern, relative_path));
+        if matches(&self.profiles.editor) {
+            Some(UssProfile::Editor)
+        } else if matches(&self.profiles.runtime) {
+            Some(UssProfile::Runtime)
+        } else {
+            None
+        }
+    }
+}
+
+/// Match a path against a glob pattern, `*` and `?` don't match `/`, `**` matches any number of folders
+pub fn path_matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    segments_match(&pattern, &path)
+}
+
+fn segments_match(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skipped| segments_match(rest, &path[skipped..])),
+        Some((segment, rest)) => match path.split_first() {
+            Some((name, path_rest)) => segment_matches(segment.as_bytes(), name.as_bytes()) && segments_match(rest, path_rest),
+            None => false,
+        },
+    }
+}
+
+fn segment_matches(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skipped| segment_matches(rest, &name[skipped..])),
+        Some((b'?', rest)) => !name.is_empty() && segment_matches(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && segment_matches(rest, &name[1..]),
+    }
+}
+
+/// Detects changes of the configuration file by its modification time, checked at most every 2 seconds
+#[derive(Debug)]
+pub struct ConfigFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    last_check: Option<Instant>,
+}
+
+impl ConfigFile {
+    pub fn new(project_root: &Path) -> Self {
+        Self {
+            path: UnityCodeConfig::path(project_root),
+            modified: None,
+            last_check: None,
+        }
+    }
+
+    /// Remember the current state of the file, after it's loaded
+    pub fn mark_loaded(&mut self) {
+        self.modified = self.read_modified();
+        self.last_check = Some(Instant::now());
+    }
+
+    /// Whether the file was created, changed or deleted since it was last loaded
+    pub fn has_changed(&mut self) -> bool {
+        if self.last_check.is_some_and(|time| time.elapsed() < CHECK_INTERVAL) {
+            return false;
+        }
+        self.last_check = Some(Instant::now());
+        self.read_modified() != self.modified
+    }
+
+    fn read_modified(&self) -> Option<SystemTime> {
+        std::fs::metadata(&self.path).and_then(|m| m.modified()).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const CONFIG: &str = r#"
+ignore = ["Assets/ThirdParty/**", "Assets/**/*.generated.uss"]
+
+[format]
+enabled = false
+declaration-order = "grouped"
+
+[lint]
+unused-variable = false
+duplicate-property = true
+
+[diagnostics]
+unused-variables = "importers"
+mode = "strict-unity"
+
+[diagnostics.severity]
+unknown-property = "hint"
+
+[docs]
+exclude = ["*.Generated", "Game.Tests"]
+include = ["Game.Tests", "Unity.InputSystem"]
+packages = false
+"#;
+
+    #[test]
+    fn test_parse() {
+        let config = UnityCodeConfig::parse(CONFIG).unwrap();
+        assert_eq!(config.ignore.len(), 2);
+        assert!(!config.format.enabled);
+        assert_eq!(config.lint["unused-variable"], false);
+        assert_eq!(config.diagnostics.severity["unknown-property"], "hint");
+        assert!(!config.docs.packages);
+
+        // everything is optional
+        assert_eq!(UnityCodeConfig::parse("").unwrap(), UnityCodeConfig::default());
+        assert!(UnityCodeConfig::default().format.enabled);
+        assert!(UnityCodeConfig::default().docs.packages);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(matches!(UnityCodeConfig::parse("[format]\nindent = 4"), Err(ConfigError::Parse(_))));
+        assert!(matches!(UnityCodeConfig::parse("ignore = 'Assets'"), Err(ConfigError::Parse(_))));
+
+        let error = UnityCodeConfig::parse(
+            "ignore = ['/Assets']\n[format]\ndeclaration-order = 'random'\n[diagnostics]\nmode = 'lenient'\n[diagnostics.severity]\nunknown-property = 'loud'\n[docs]\nexclude = ['Library/Game']",
+        )
+        .unwrap_err();
+        let ConfigError::Invalid(problems) = &error else {
+            panic!("Expected validation problems, got {}", error);
+        };
+        assert_eq!(problems.len(), 5);
+        assert!(error.to_string().contains("unknown-property 'loud'"), "{}", error);
+    }
+
+    #[test]
+    fn test_apply_to_settings() {
+        let config = UnityCodeConfig::parse(CONFIG).unwrap();
+        let mut settings = UssSettings::default();
+        settings.diagnostics.severity_overrides.insert("duplicate-property".to_string(), SeverityLevel::Off);
+        settings.diagnostics.severity_overrides.insert("unknown-property".to_string(), SeverityLevel::Error);
+        config.apply_to(&mut settings);
+
+        assert!(!settings.format_enabled);
+        assert_eq!(settings.declaration_order, DeclarationOrder::Grouped);
+        let overrides = &settings.diagnostics.severity_overrides;
+        assert_eq!(overrides["unused-variable"], SeverityLevel::Off);
+        assert_eq!(overrides["unknown-property"], SeverityLevel::Hint);
+        // turned on by the project although turned off in the editor
+        assert!(!overrides.contains_key("duplicate-property"));
+        assert_eq!(settings.diagnostics.unused_variables, UnusedVariableScope::Importers);
+        assert_eq!(settings.diagnostics.mode, ValidationMode::StrictUnity);
+    }
+
+    #[test]
+    fn test_ignored_paths() {
+        let config = UnityCodeConfig::parse(CONFIG).unwrap();
+        assert!(config.is_ignored("Assets/ThirdParty/Lib/Styles/a.uss"));
+        assert!(config.is_ignored("Assets/a.generated.uss"));
+        assert!(config.is_ignored("Assets/UI/Menus/b.generated.uss"));
+        assert!(!config.is_ignored("Assets/UI/Main.uss"));
+        assert!(!config.is_ignored("Packages/ThirdParty/a.uss"));
+
+        assert!(path_matches("Assets/UI/?.uss", "Assets/UI/a.uss"));
+        assert!(!path_matches("Assets/*.uss", "Assets/UI/a.uss"));
+    }
+
+    #[test]
+    fn test_docs_assemblies() {
+        let docs = UnityCodeConfig::parse(CONFIG).unwrap().docs;
+        assert!(docs.includes_assembly("Game", true));
+        assert!(!docs.includes_assembly("Game.Protocol.Generated", true));
+        // included although excluded
+        assert!(docs.includes_assembly("Game.Tests", true));
+        // package assemblies only if included
+        assert!(!docs.includes_assembly("Unity.Mathematics", false));
+        assert!(docs.includes_assembly("Unity.InputSystem", false));
+
+        assert!(DocsConfig::default().includes_assembly("Unity.Mathematics", false));
+    }
+
+    #[test]
+    fn test_load_and_detect_changes() {
+        let dir = TempDir::new().unwrap();
+        assert!(UnityCodeConfig::load(dir.path()).unwrap().is_none());
+
+        let mut file = ConfigFile::new(dir.path());
+        file.mark_loaded();
+        // checks are throttled
+        std::fs::write(UnityCodeConfig::path(dir.path()), CONFIG).unwrap();
+        assert!(!file.has_changed());
+        file.last_check = None;
+        assert!(file.has_changed());
+
+        file.mark_loaded();
+        file.last_check = None;
+        assert!(!file.has_changed());
+        assert!(!UnityCodeConfig::load(dir.path()).unwrap().unwrap().format.enabled);
+    }
+}
@@ -0,0 +1,300 @@
+//! C# compile errors from the Unity Editor log
+//!
+//! Unity writes the errors and warnings of script compilation to its Editor log, one per line:
+//!
+//! ```text
+//! Assets/Scripts/Player.cs(12,17): error CS0103: The name 'speed' does not exist in the current context
+//! ```
+//!
+//! [`CompileErrorWatcher`] follows the log as it grows and keeps the diagnostics of the latest compilation,
+//! keyed by file path, so editors without a C# language server can still show compile errors.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range};
+
+use crate::editor_log::EditorLogReader;
+
+/// Source of the diagnostics
+const DIAGNOSTIC_SOURCE: &str = "unity";
+
+/// Lines that start a script compilation, the diagnostics of the previous compilation are cleared
+const COMPILATION_START_MARKERS: &[&str] = &[
+    "Requested script compilation because",
+    "- Starting script compilation",
+];
+
+/// `path(line,column): error|warning CODE: message`
+static COMPILER_MESSAGE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?P<path>.+?\.cs)\((?P<line>\d+),(?P<column>\d+)\): (?P<severity>error|warning) (?P<code>\w+): (?P<message>.*)$")
+        .unwrap()
+});
+
+/// Diagnostics of the latest compilation, sent to clients
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompileErrors {
+    /// Whether the latest compilation has errors, warnings alone don't fail a compilation
+    #[serde(rename = "HasErrors")]
+    pub has_errors: bool,
+    /// Diagnostics were left out so the message fits in a datagram, see [`CompileErrors::payload`]
+    #[serde(rename = "Truncated")]
+    pub truncated: bool,
+    /// Diagnostics by absolute file path
+    #[serde(rename = "Files")]
+    pub files: BTreeMap<String, Vec<Diagnostic>>,
+}
+
+impl CompileErrors {
+    /// Payload of a message with the diagnostics, at most `max_size` bytes
+    ///
+    /// If they don't fit warnings are left out, then the errors of the files that come last.
+    pub fn payload(&self, max_size: usize) -> serde_json::Result<String> {
+        let json = serde_json::to_string(self)?;
+        if json.len() <= max_size {
+            return Ok(json);
+        }
+
+        let mut truncated = CompileErrors { has_errors: self.has_errors, truncated: true, files: BTreeMap::new() };
+        let mut remaining = max_size.saturating_sub(serde_json::to_string(&truncated)?.len());
+        for (path, diagnostics) in &self.files {
+            let errors: Vec<Diagnostic> = diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.severity == Some(DiagnosticSeverity::ERROR))
+                .cloned()
+                .collect();
+            if errors.is_empty() {
+                continue;
+            }
+            // the path, a colon, the diagnostics and a comma
+            let size = serde_json::to_string(path)?.len() + serde_json::to_string(&errors)?.len() + 2;
+            if size > remaining {
+                break;
+            }
+            remaining -= size;
+            truncated.files.insert(path.clone(), errors);
+        }
+        serde_json::to_string(&truncated)
+    }
+
+    fn add(&mut self, path: String, diagnostic: Diagnostic) {
+        if diagnostic.severity == Some(DiagnosticSeverity::ERROR) {
+            self.has_errors = true;
+        }
+
+        let diagnostics = self.files.entry(path).or_default();
+        // Unity repeats compiler messages in the log, once from the compiler and once when it shows them in the console
+        if !diagnostics.contains(&diagnostic) {
+            diagnostics.push(diagnostic);
+        }
+    }
+}
+
+/// Parse a compiler message line of the Editor log
+///
+/// Returns the file path, resolved against the project root if it's relative, and the diagnostic.
+pub fn parse_compiler_message(line: &str, project_root: &Path) -> Option<(String, Diagnostic)> {
+    let captures = COMPILER_MESSAGE_REGEX.captures(line.trim_end())?;
+
+    // positions in compiler messages are 1-based
+    let line_number = captures["line"].parse::<u32>().ok()?.saturating_sub(1);
+    let column = captures["column"].parse::<u32>().ok()?.saturating_sub(1);
+    let position = Position::new(line_number, column);
+
+    let severity = match &captures["severity"] {
+        "error" => DiagnosticSeverity::ERROR,
+        _ => DiagnosticSeverity::WARNING,
+    };
+
+    let path = Path::new(&captures["path"]);
+    let path = if path.is_absolute() { path.to_path_buf() } else { project_root.join(path) };
+
+    let diagnostic = Diagnostic {
+        range: Range::new(position, position),
+        severity: Some(severity),
+        code: Some(NumberOrString::String(captures["code"].to_string())),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        message: captures["message"].to_string(),
+        ..Default::default()
+    };
+
+    Some((path.to_string_lossy().replace('\\', "/"), diagnostic))
+}
+
+/// Path of the Editor log, Unity writes it to a fixed location per platform unless it's started with `-logFile`
+pub fn default_editor_log_path() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        Some(dirs::data_local_dir()?.join("Unity").join("Editor").join("Editor.log"))
+    } else if cfg!(target_os = "macos") {
+        Some(dirs::home_dir()?.join("Library").join("Logs").join("Unity").join("Editor.log"))
+    } else {
+        Some(dirs::config_dir()?.join("unity3d").join("Editor.log"))
+    }
+}
+
+/// Follows the Editor log and collects the diagnostics of the latest compilation
+pub struct CompileErrorWatcher {
+    project_root: PathBuf,
+    log: EditorLogReader,
+    errors: CompileErrors,
+}
+
+impl CompileErrorWatcher {
+    pub fn new(project_root: PathBuf, log_path: PathBuf) -> Self {
+        Self {
+            project_root,
+            log: EditorLogReader::new(log_path),
+            errors: CompileErrors::default(),
+        }
+    }
+
+    /// Diagnostics of the latest compilation
+    pub fn errors(&self) -> &CompileErrors {
+        &self.errors
+    }
+
+    /// Read what was added to the log since the last update
+    ///
+    /// Returns `true` if the diagnostics changed. When the log got shorter Unity was restarted,
+    /// it's read again from the start.
+    pub fn update(&mut self) -> bool {
+        let old_errors = self.errors.clone();
+        match self.log.read_lines() {
+            Ok(new) => {
+                if new.restarted {
+                    log::info!("Editor log was truncated, reading it from the start");
+                    self.errors = CompileErrors::default();
+                }
+                self.process(&new.lines);
+                old_errors != self.errors
+            }
+            Err(e) => {
+                if e.kind() != io::ErrorKind::NotFound {
+                    log::warn!("Failed to read Editor log {}: {}", self.log.log_path().display(), e);
+                }
+                false
+            }
+        }
+    }
+
+    fn process(&mut self, lines: &[String]) {
+        for line in lines {
+            if COMPILATION_START_MARKERS.iter().any(|marker| line.contains(marker)) {
+                self.errors = CompileErrors::default();
+            } else if let Some((path, diagnostic)) = parse_compiler_message(line, &self.project_root) {
+                self.errors.add(path, diagnostic);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn append(path: &Path, text: &str) {
+        let mut file = OpenOptions::new().create(true).append(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_parse_compiler_message() {
+        let root = Path::new("/projects/game");
+        let (path, diagnostic) = parse_compiler_message(
+            "Assets/Scripts/Player.cs(12,17): error CS0103: The name 'speed' does not exist in the current context",
+            root,
+        )
+        .unwrap();
+
+        assert_eq!(path, "/projects/game/Assets/Scripts/Player.cs");
+        assert_eq!(diagnostic.range.start, Position::new(11, 16));
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+        assert_eq!(diagnostic.code, Some(NumberOrString::String("CS0103".to_string())));
+        assert_eq!(diagnostic.message, "The name 'speed' does not exist in the current context");
+
+        let (path, diagnostic) =
+            parse_compiler_message("Assets\\Scripts\\Enemy.cs(3,5): warning CS0168: The variable 'e' is declared but never used\r", root)
+                .unwrap();
+        assert!(path.ends_with("Assets/Scripts/Enemy.cs"));
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+
+        assert!(parse_compiler_message("Refreshing native plugins compatible for Editor in 1.23 ms", root).is_none());
+    }
+
+    #[test]
+    fn test_watcher_follows_compilations() {
+        let dir = TempDir::new().unwrap();
+        let log_path = dir.path().join("Editor.log");
+        let mut watcher = CompileErrorWatcher::new(dir.path().to_path_buf(), log_path.clone());
+
+        // no log yet
+        assert!(!watcher.update());
+
+        append(&log_path, "[ScriptCompilation] Requested script compilation because: Assetdatabase observed changes\n");
+        append(&log_path, "Assets/A.cs(1,1): error CS1002: ; expected\nAssets/A.cs(1,1): error CS1002: ; expected\n");
+        // the second message is still being written
+        append(&log_path, "Assets/B.cs(2,3): warning CS0168: unused");
+        assert!(watcher.update());
+        assert!(watcher.errors().has_errors);
+        assert_eq!(watcher.errors().files.len(), 1, "Duplicates are merged, partial lines are kept");
+
+        append(&log_path, "\n");
+        assert!(watcher.update());
+        assert_eq!(watcher.errors().files.len(), 2);
+
+        assert!(!watcher.update(), "Nothing changed");
+
+        // a successful compilation clears the errors
+        append(&log_path, "[ScriptCompilation] Requested script compilation because: Assetdatabase observed changes\n");
+        append(&log_path, "*** Tundra build success (3.20 seconds), 9 items updated, 862 evaluated\n");
+        assert!(watcher.update());
+        assert_eq!(watcher.errors(), &CompileErrors::default());
+    }
+
+    #[test]
+    fn test_payload_fits_in_max_size() {
+        let root = Path::new("/projects/game");
+        let mut errors = CompileErrors::default();
+        for i in 0..50 {
+            for line in [
+                format!("Assets/Scripts/File{:02}.cs(1,1): error CS1002: ; expected", i),
+                format!("Assets/Scripts/File{:02}.cs(2,1): warning CS0168: The variable 'e' is declared but never used", i),
+            ] {
+                let (path, diagnostic) = parse_compiler_message(&line, root).unwrap();
+                errors.add(path, diagnostic);
+            }
+        }
+
+        let json = errors.payload(100_000).unwrap();
+        assert_eq!(serde_json::from_str::<CompileErrors>(&json).unwrap(), errors);
+
+        let json = errors.payload(4000).unwrap();
+        assert!(json.len() <= 4000);
+        let payload: CompileErrors = serde_json::from_str(&json).unwrap();
+        assert!(payload.truncated && payload.has_errors);
+        assert!(!payload.files.is_empty() && payload.files.len() < 50);
+        assert!(payload.files.values().flatten().all(|d| d.severity == Some(DiagnosticSeverity::ERROR)));
+        assert!(payload.files.contains_key("/projects/game/Assets/Scripts/File00.cs"));
+    }
+
+    #[test]
+    fn test_watcher_restarts_when_log_is_truncated() {
+        let dir = TempDir::new().unwrap();
+        let log_path = dir.path().join("Editor.log");
+        let mut watcher = CompileErrorWatcher::new(dir.path().to_path_buf(), log_path.clone());
+
+        append(&log_path, "some output of the previous session\nAssets/A.cs(1,1): error CS1002: ; expected\n");
+        assert!(watcher.update());
+
+        std::fs::write(&log_path, "new session\n").unwrap();
+        assert!(watcher.update());
+        assert!(watcher.errors().files.is_empty());
+    }
+}
@@ -3,43 +3,38 @@
 //! This module contains standalone functions for normalizing C# type and member names
 //! when processing syntax trees for documentation compilation.
 
-use tree_sitter::Node;
+use tree_sitter::{Node, Point};
 use super::constants::*;
 
 /// Normalize a type name from a tree-sitter node
-/// Returns the fully qualified type name including namespace
+/// Returns the fully qualified type name including namespace, nested types include their containing types,
+/// e.g. `MyNamespace.Outer.Inner`
 pub fn normalize_type_name(node: Node, source: &str) -> Option<String> {
-    // For a class name node, we need to traverse up to find the full namespace path
+    // For a type name node, we need to traverse up to find the full namespace path
     let class_name = node.utf8_text(source.as_bytes()).ok()?.to_string();
     
-    // Find the class declaration that contains this name node
+    // Find the type declaration that contains this name node
     let mut current = node.parent();
     while let Some(parent) = current {
-        if parent.kind() == CLASS_DECLARATION {
-            // Found the class, now traverse up to collect namespace hierarchy
-            let mut namespaces = Vec::new();
-            let mut namespace_parent = parent.parent();
+        if is_type_declaration(parent.kind()) {
+            // Found the type, now traverse up to collect containing types and the namespace hierarchy
+            let mut names = vec![class_name];
+            let mut outer = parent.parent();
             
-            while let Some(ns_parent) = namespace_parent {
-                if ns_parent.kind() == NAMESPACE_DECLARATION {
-                    if let Some(ns_name_node) = ns_parent.child_by_field_name(NAME_FIELD) {
-                        if let Ok(ns_name) = ns_name_node.utf8_text(source.as_bytes()) {
-                            namespaces.push(ns_name.to_string());
+            while let Some(outer_node) = outer {
+                if outer_node.kind() == NAMESPACE_DECLARATION || is_type_declaration(outer_node.kind()) {
+                    if let Some(name_node) = outer_node.child_by_field_name(NAME_FIELD) {
+                        if let Ok(name) = name_node.utf8_text(source.as_bytes()) {
+                            names.push(name.to_string());
                         }
                     }
                 }
-                namespace_parent = ns_parent.parent();
+                outer = outer_node.parent();
             }
             
             // Reverse to get correct order (outermost to innermost)
-            namespaces.reverse();
-            
-            // Combine namespace and class name
-            if namespaces.is_empty() {
-                return Some(class_name);
-            } else {
-                return Some(format!("{}.{}", namespaces.join("."), class_name));
-            }
+            names.reverse();
+            return Some(names.join("."));
         }
         current = parent.parent();
     }
@@ -48,6 +43,37 @@ pub fn normalize_type_name(node: Node, source: &str) -> Option<String> {
     Some(class_name)
 }
 
+/// Whether a node kind declares a type
+pub fn is_type_declaration(kind: &str) -> bool {
+    matches!(kind, CLASS_DECLARATION | INTERFACE_DECLARATION | STRUCT_DECLARATION | ENUM_DECLARATION)
+}
+
+/// Names of the type parameters of a type or method declaration, e.g. `T` of `class Pool<T>`
+pub fn declared_type_parameters(node: Node, source: &str) -> Vec<String> {
+    // methods have a field for them, types don't
+    let type_parameters = node.child_by_field_name(TYPE_PARAMETERS_FIELD).or_else(|| {
+        let mut cursor = node.walk();
+        let list = node.children(&mut cursor).find(|child| child.kind() == TYPE_PARAMETER_LIST);
+        list
+    });
+    let Some(type_parameters) = type_parameters else {
+        return Vec::new();
+    };
+    let mut cursor = type_parameters.walk();
+    type_parameters
+        .children(&mut cursor)
+        .filter(|child| child.kind() == TYPE_PARAMETER)
+        .filter_map(|parameter| {
+            let name = parameter.child_by_field_name(NAME_FIELD).or_else(|| {
+                let mut cursor = parameter.walk();
+                let identifier = parameter.children(&mut cursor).find(|child| child.kind() == IDENTIFIER);
+                identifier
+            })?;
+            name.utf8_text(source.as_bytes()).ok().map(str::to_string)
+        })
+        .collect()
+}
+
 /// Normalize a member name from a tree-sitter node
 pub fn normalize_member_name(node: Node, source: &str) -> Option<String> {
     match node.kind() {
@@ -326,6 +352,67 @@ pub fn normalize_symbol_name(named: &str) -> String {
     result
 }
 
+/// Type and member declared around a position of a C# source file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnclosingSymbol {
+    /// Fully qualified type name, as in documentation assemblies
+    pub type_name: String,
+    /// Normalized member name (methods include parameter types), `None` outside of members
+    pub member_name: Option<String>,
+}
+
+impl EnclosingSymbol {
+    /// Full symbol name, e.g. `MyNamespace.Player.Move(float)`
+    pub fn symbol_name(&self) -> String {
+        match &self.member_name {
+            Some(member) => format!("{}.{}", self.type_name, member),
+            None => self.type_name.clone(),
+        }
+    }
+}
+
+/// Find the innermost type declaration containing `point`, and the member of that type containing it
+///
+/// Names are normalized the same way as when documentation is compiled, so they can be used to look up docs.
+pub fn find_enclosing_symbol(root: Node, source: &str, point: Point) -> Option<EnclosingSymbol> {
+    let mut current = root.descendant_for_point_range(point, point);
+    let mut member_name = None;
+
+    while let Some(node) = current {
+        match node.kind() {
+            METHOD_DECLARATION | CONSTRUCTOR_DECLARATION | DESTRUCTOR_DECLARATION | PROPERTY_DECLARATION
+            | FIELD_DECLARATION | EVENT_DECLARATION
+                if member_name.is_none() =>
+            {
+                member_name = normalize_member_name(node, source);
+            }
+            CLASS_DECLARATION | INTERFACE_DECLARATION | STRUCT_DECLARATION | ENUM_DECLARATION => {
+                let name_node = node.child_by_field_name(NAME_FIELD)?;
+                return Some(EnclosingSymbol {
+                    type_name: normalize_type_name(name_node, source)?,
+                    member_name,
+                });
+            }
+            _ => {}
+        }
+        current = node.parent();
+    }
+
+    None
+}
+
+/// Convert a 0-based line and character column to a tree-sitter point (byte column)
+///
+/// Columns past the end of the line are clamped to the end of the line.
+pub fn line_column_to_point(source: &str, line: u32, column: u32) -> Option<Point> {
+    let line_text = source.split('\n').nth(line as usize)?;
+    let byte_column = line_text
+        .char_indices()
+        .nth(column as usize)
+        .map_or(line_text.len(), |(byte, _)| byte);
+    Some(Point::new(line as usize, byte_column))
+}
+
 #[cfg(test)]
 #[path="compile_utils_tests.rs"]
 mod tests;
\ No newline at end of file
@@ -329,3 +329,37 @@ fn test_nested_namespace_normalization() {
         panic!("Could not find method node");
     }
 }
+
+#[test]
+fn test_find_enclosing_symbol() {
+    let mut parser = Parser::new();
+    parser.set_language(&tree_sitter_c_sharp::LANGUAGE.into()).unwrap();
+
+    let source = r#"namespace Game
+{
+    public class Player
+    {
+        private float speed;
+
+        public void Move(float delta)
+        {
+            var x = delta * speed;
+        }
+    }
+}
+"#;
+    let tree = parser.parse(source, None).unwrap();
+    let find = |line, column| {
+        let point = line_column_to_point(source, line, column).unwrap();
+        find_enclosing_symbol(tree.root_node(), source, point)
+    };
+
+    let symbol = find(8, 20).unwrap();
+    assert_eq!(symbol.type_name, "Game.Player");
+    assert_eq!(symbol.member_name.as_deref(), Some("Move(float)"));
+    assert_eq!(symbol.symbol_name(), "Game.Player.Move(float)");
+
+    assert_eq!(find(4, 23).unwrap().symbol_name(), "Game.Player.speed");
+    assert_eq!(find(3, 5).unwrap().symbol_name(), "Game.Player");
+    assert_eq!(find(0, 0), None);
+}
@@ -134,6 +134,16 @@ pub const TYPE_FIELD: &str = "type";
 /// Used with `node.child_by_field_name(TYPE_PARAMETERS_FIELD)`
 pub const TYPE_PARAMETERS_FIELD: &str = "type_parameters";
 
+/// Tree-sitter node kind for a type parameter of a generic type or method
+///
+/// Example: `T` in `class Pool<T>`
+pub const TYPE_PARAMETER: &str = "type_parameter";
+
+/// Tree-sitter node kind for the type parameters of a generic type or method
+///
+/// Example: `<TKey, TValue>` in `class Cache<TKey, TValue>`
+pub const TYPE_PARAMETER_LIST: &str = "type_parameter_list";
+
 /// Tree-sitter field name for accessing parameters
 /// 
 /// Used with `node.child_by_field_name(PARAMETERS_FIELD)`
@@ -0,0 +1,381 @@
+//! Custom Visual Elements of User Code
+//!
+//! Custom elements are only in the UXML schema after Unity regenerates it, so an element that was just
+//! written would be reported as unknown. This module finds them in the C# sources of the user assemblies:
+//! - classes with the `[UxmlElement]` attribute (Unity 6), `[UxmlElement("name")]` changes the UXML name
+//! - classes with a nested `UxmlFactory<T>` class (older versions)
+//!
+//! Sources are listed from the `.csproj` files, like for documentation, and only the files that changed
+//! are scanned again.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use tree_sitter::{Node, Parser};
+
+use super::constants::*;
+use super::source_utils::{find_user_assemblies, get_assembly_source_files};
+use crate::dir_changed::{FileChanges, BULK_CHANGE_THRESHOLD};
+
+/// Tree-sitter node kind for file scoped namespaces, e.g. `namespace MyGame.UI;`
+const FILE_SCOPED_NAMESPACE_DECLARATION: &str = "file_scoped_namespace_declaration";
+
+/// A custom visual element declared in C#
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomElement {
+    /// Name of the element in UXML and USS, the class name unless `[UxmlElement("name")]` sets one
+    pub name: String,
+    /// Namespace of the class, empty in the global namespace
+    pub namespace: String,
+    /// Source file that declares the element
+    pub path: PathBuf,
+}
+
+impl CustomElement {
+    /// The fully qualified name, e.g. `MyGame.UI.HealthBar`
+    pub fn fully_qualified_name(&self) -> String {
+        if self.namespace.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}.{}", self.namespace, self.name)
+        }
+    }
+}
+
+/// Find the custom visual elements declared in a C# source
+pub fn find_custom_elements(parser: &mut Parser, source: &str, path: &Path) -> Vec<CustomElement> {
+    // most sources don't declare elements, don't parse them
+    if !source.contains("UxmlElement") && !source.contains("UxmlFactory") {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(source, None) else {
+        return Vec::new();
+    };
+
+    let mut elements = Vec::new();
+    collect_elements(tree.root_node(), source, path, &mut Vec::new(), &mut elements);
+    elements
+}
+
+fn collect_elements(
+    node: Node,
+    source: &str,
+    path: &Path,
+    namespaces: &mut Vec<String>,
+    elements: &mut Vec<CustomElement>,
+) {
+    let text = |node: Node| node.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+
+    let mut cursor = node.walk();
+    let mut file_scoped_namespace = false;
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            NAMESPACE_DECLARATION => {
+                namespaces.push(child.child_by_field_name(NAME_FIELD).map(text).unwrap_or_default());
+                collect_elements(child, source, path, namespaces, elements);
+                namespaces.pop();
+            }
+            FILE_SCOPED_NAMESPACE_DECLARATION => {
+                // the declarations that follow it are its siblings
+                namespaces.push(child.child_by_field_name(NAME_FIELD).map(text).unwrap_or_default());
+                file_scoped_namespace = true;
+            }
+            CLASS_DECLARATION => {
+                if let Some(name) = element_name(child, source) {
+                    elements.push(CustomElement {
+                        name,
+                        namespace: namespaces.join("."),
+                        path: path.to_path_buf(),
+                    });
+                }
+                // nested classes can be elements too
+                if let Some(body) = child.child_by_field_name(BODY_FIELD) {
+                    collect_elements(body, source, path, namespaces, elements);
+                }
+            }
+            DECLARATION_LIST => collect_elements(child, source, path, namespaces, elements),
+            _ => {}
+        }
+    }
+    if file_scoped_namespace {
+        namespaces.pop();
+    }
+}
+
+/// UXML name of a class if it is a custom element
+fn element_name(class: Node, source: &str) -> Option<String> {
+    let text = |node: Node| node.utf8_text(source.as_bytes()).unwrap_or("");
+    let class_name = text(class.child_by_field_name(NAME_FIELD)?).to_string();
+
+    let mut cursor = class.walk();
+    for child in class.children(&mut cursor) {
+        if child.kind() != "attribute_list" {
+            continue;
+        }
+        let mut attributes = child.walk();
+        for attribute in child.children(&mut attributes).filter(|n| n.kind() == "attribute") {
+            let name = attribute.child_by_field_name(NAME_FIELD).map(text).unwrap_or("");
+            let name = name.rsplit('.').next().unwrap_or(name);
+            if name == "UxmlElement" || name == "UxmlElementAttribute" {
+                return Some(attribute_string_argument(attribute, source).unwrap_or(class_name));
+            }
+        }
+    }
+
+    let has_factory = class.child_by_field_name(BODY_FIELD).is_some_and(|body| {
+        let mut cursor = body.walk();
+        let has_factory = body
+            .children(&mut cursor)
+            .any(|member| member.kind() == CLASS_DECLARATION && derives_from_factory(member, source));
+        has_factory
+    });
+    has_factory.then_some(class_name)
+}
+
+/// The string of `[UxmlElement("name")]`, `None` without one
+fn attribute_string_argument(attribute: Node, source: &str) -> Option<String> {
+    let mut cursor = attribute.walk();
+    let arguments = attribute.children(&mut cursor).find(|n| n.kind() == "attribute_argument_list")?;
+    let mut cursor = arguments.walk();
+    let argument = arguments.children(&mut cursor).find(|n| n.kind() == "attribute_argument")?;
+    let literal = argument.child(0).filter(|n| n.kind() == "string_literal")?;
+    let value = literal.utf8_text(source.as_bytes()).ok()?.trim_matches('"');
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Whether a class derives from `UxmlFactory<...>`
+fn derives_from_factory(class: Node, source: &str) -> bool {
+    let mut cursor = class.walk();
+    let Some(bases) = class.children(&mut cursor).find(|n| n.kind() == "base_list") else {
+        return false;
+    };
+    let mut cursor = bases.walk();
+    let derives = bases.children(&mut cursor).any(|base| {
+        let generic = match base.kind() {
+            "generic_name" => Some(base),
+            // e.g. `UnityEngine.UIElements.UxmlFactory<T>`
+            QUALIFIED_NAME => base.child_by_field_name(NAME_FIELD).filter(|n| n.kind() == "generic_name"),
+            _ => None,
+        };
+        generic
+            .and_then(|generic| generic.child(0))
+            .is_some_and(|name| name.utf8_text(source.as_bytes()) == Ok("UxmlFactory"))
+    });
+    derives
+}
+
+#[derive(Debug)]
+struct ScannedFile {
+    last_modified: SystemTime,
+    elements: Vec<CustomElement>,
+}
+
+/// Custom elements of the user code of a project, kept up to date as sources change
+pub struct CustomElementScanner {
+    unity_project_root: PathBuf,
+    parser: Parser,
+    files: HashMap<PathBuf, ScannedFile>,
+    /// Changes of sources and `.csproj` files, `None` if they can't be watched
+    source_changes: Option<FileChanges>,
+    /// Whether the sources were listed from the `.csproj` files
+    is_listed: bool,
+}
+
+impl CustomElementScanner {
+    pub fn new(unity_project_root: PathBuf) -> Self {
+        let mut parser = Parser::new();
+        if let Err(e) = parser.set_language(&tree_sitter_c_sharp::LANGUAGE.into()) {
+            log::error!("Failed to load the C# grammar: {}", e);
+        }
+
+        let source_changes = FileChanges::new(&["cs", "csproj"])
+            .map(|mut changes| {
+                // .csproj files are only in the project root, sources of user code are in Assets and embedded packages
+                let watches = [
+                    (unity_project_root.clone(), false),
+                    (unity_project_root.join("Assets"), true),
+                    (unity_project_root.join("Packages"), true),
+                ];
+                for (dir, recursive) in watches {
+                    if dir.is_dir() {
+                        if let Err(e) = changes.watch(&dir, recursive) {
+                            log::warn!("Failed to watch {}: {}", dir.display(), e);
+                        }
+                    }
+                }
+                changes
+            })
+            .map_err(|e| log::warn!("Failed to create source watcher: {}", e))
+            .ok();
+
+        Self {
+            unity_project_root,
+            parser,
+            files: HashMap::new(),
+            source_changes,
+            is_listed: false,
+        }
+    }
+
+    /// Scan the sources that changed since the last call, the first call scans all sources
+    ///
+    /// Returns whether the custom elements may have changed.
+    pub async fn refresh(&mut self) -> bool {
+        let changed = self.source_changes.as_ref().map(|changes| changes.take_changes()).unwrap_or_default();
+        // after many changes at once, e.g. switching branches, listing all sources is one pass
+        let relist = !self.is_listed
+            || changed.len() >= BULK_CHANGE_THRESHOLD
+            || changed.iter().any(|path| path.extension().is_some_and(|e| e == "csproj"));
+
+        if relist {
+            self.is_listed = true;
+            let sources = self.list_sources().await;
+            self.files.retain(|path, _| sources.contains(path));
+            for path in sources {
+                self.scan_file(path).await;
+            }
+            return true;
+        }
+
+        for path in &changed {
+            if path.is_file() {
+                self.scan_file(path.clone()).await;
+            } else {
+                self.files.remove(path);
+            }
+        }
+        !changed.is_empty()
+    }
+
+    /// Scan all sources again on the next refresh
+    pub fn invalidate(&mut self) {
+        self.is_listed = false;
+        self.files.clear();
+    }
+
+    /// The custom elements of all scanned sources, sorted by fully qualified name
+    pub fn elements(&self) -> Vec<&CustomElement> {
+        let mut elements: Vec<_> = self.files.values().flat_map(|file| &file.elements).collect();
+        elements.sort_by_key(|element| element.fully_qualified_name());
+        elements
+    }
+
+    /// Sources of the user assemblies
+    async fn list_sources(&self) -> Vec<PathBuf> {
+        let assemblies = match find_user_assemblies(&self.unity_project_root).await {
+            Ok(assemblies) => assemblies,
+            Err(e) => {
+                log::warn!("Failed to find user assemblies: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let mut sources = Vec::new();
+        for assembly in assemblies {
+            match get_assembly_source_files(&assembly, &self.unity_project_root).await {
+                Ok(files) => sources.extend(files),
+                Err(e) => log::warn!("Failed to get the sources of {}: {}", assembly.name, e),
+            }
+        }
+        sources
+    }
+
+    /// Scan a source unless it didn't change since it was last scanned
+    async fn scan_file(&mut self, path: PathBuf) {
+        let Ok(last_modified) = tokio::fs::metadata(&path).await.and_then(|m| m.modified()) else {
+            self.files.remove(&path);
+            return;
+        };
+        if self.files.get(&path).is_some_and(|file| file.last_modified == last_modified) {
+            return;
+        }
+        let Ok(source) = tokio::fs::read_to_string(&path).await else {
+            self.files.remove(&path);
+            return;
+        };
+
+        let elements = find_custom_elements(&mut self.parser, &source, &path);
+        self.files.insert(path, ScannedFile { last_modified, elements });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn parser() -> Parser {
+        let mut parser = Parser::new();
+        parser.set_language(&tree_sitter_c_sharp::LANGUAGE.into()).unwrap();
+        parser
+    }
+
+    fn names(source: &str) -> Vec<String> {
+        find_custom_elements(&mut parser(), source, Path::new("Test.cs"))
+            .iter()
+            .map(|element| element.fully_qualified_name())
+            .collect()
+    }
+
+    #[test]
+    fn test_find_custom_elements() {
+        let source = r#"
+using UnityEngine.UIElements;
+
+namespace MyGame.UI
+{
+    [UxmlElement]
+    public partial class HealthBar : VisualElement { }
+
+    [UnityEngine.UIElements.UxmlElement("mana-bar")]
+    public partial class ManaBar : VisualElement { }
+
+    public class Legacy : VisualElement
+    {
+        public new class UxmlFactory : UxmlFactory<Legacy, UxmlTraits> { }
+    }
+
+    public class Outer
+    {
+        [UxmlElement]
+        public partial class Inner : VisualElement { }
+    }
+
+    [Serializable]
+    public class NotAnElement { }
+}
+"#;
+        assert_eq!(
+            names(source),
+            vec!["MyGame.UI.HealthBar", "MyGame.UI.mana-bar", "MyGame.UI.Legacy", "MyGame.UI.Inner"]
+        );
+
+        let file_scoped = "namespace MyGame;\n[UxmlElement]\npartial class Gauge : VisualElement { }";
+        assert_eq!(names(file_scoped), vec!["MyGame.Gauge"]);
+        assert_eq!(names("[UxmlElement] partial class Global : VisualElement { }"), vec!["Global"]);
+        assert!(names("public class Player : MonoBehaviour { }").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_scanner() {
+        let project = TempDir::new().unwrap();
+        let scripts = project.path().join("Assets").join("Scripts");
+        std::fs::create_dir_all(&scripts).unwrap();
+        std::fs::write(scripts.join("HealthBar.cs"), "[UxmlElement] partial class HealthBar : VisualElement { }").unwrap();
+        std::fs::write(scripts.join("Player.cs"), "class Player : MonoBehaviour { }").unwrap();
+        std::fs::write(
+            project.path().join("Assembly-CSharp.csproj"),
+            r#"<Project><PropertyGroup><AssemblyName>Assembly-CSharp</AssemblyName></PropertyGroup>
+<ItemGroup><Compile Include="Assets/Scripts/HealthBar.cs" /><Compile Include="Assets/Scripts/Player.cs" /></ItemGroup></Project>"#,
+        )
+        .unwrap();
+
+        let mut scanner = CustomElementScanner::new(project.path().to_path_buf());
+        assert!(scanner.refresh().await);
+        let elements = scanner.elements();
+        assert_eq!(elements.len(), 1);
+        assert_eq!(elements[0].name, "HealthBar");
+        assert_eq!(elements[0].path, scripts.join("HealthBar.cs"));
+    }
+}
@@ -4,19 +4,25 @@
 //! It uses tree-sitter to parse C# source files and extract XML documentation comments.
 
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 use serde::{Deserialize, Serialize};
 use tokio::fs;
 use tree_sitter::{Parser, Node};
 use crate::cs::source_utils::{extract_compile_items, find_cs_files_in_dir};
+use crate::language::progress::ProgressCounter;
 use crate::language::tree_utils::has_error_nodes;
+use crate::protocol::{DocsCompileReport, FailedSourceFile};
 
 use super::source_assembly::SourceAssembly;
-use super::compile_utils::{normalize_type_name, normalize_member_name};
+use super::compile_utils::{declared_type_parameters, is_type_declaration, normalize_type_name, normalize_member_name};
 use super::constants::*;
 use super::error::{CsResult, CsError, IoContext};
 
 /// Current version of the DocsAssembly data structure
-pub const DOCS_ASSEMBLY_VERSION: u32 = 1;
+pub const DOCS_ASSEMBLY_VERSION: u32 = 3;
+
+/// Most failed files listed in a compile report, the others are only counted
+const MAX_FAILED_FILES: usize = 100;
 
 /// Represents XML documentation for a C# member
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,12 +33,18 @@ pub struct MemberDoc {
     pub xml_doc: String,
     /// Whether this member is public
     pub is_public: bool,
+    /// Source file declaring the member
+    #[serde(default)]
+    pub source_file: Option<PathBuf>,
+    /// 0-based line of the member's name in the source file
+    #[serde(default)]
+    pub line: Option<u32>,
 }
 
 /// Represents XML documentation for a C# type
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TypeDoc {
-    /// Fully qualified name of the type
+    /// Fully qualified name of the type, without its type parameters, nested types include their containing types
     pub name: String,
     /// The XML documentation content for the type itself
     pub xml_doc: String,
@@ -42,6 +54,15 @@ pub struct TypeDoc {
     pub members: std::collections::HashMap<String, MemberDoc>,
     /// Using namespaces from the source file containing this type
     pub using_namespaces: Vec<String>,
+    /// Names of the type parameters of a generic type, e.g. `T` of `Pool<T>`
+    #[serde(default)]
+    pub type_parameters: Vec<String>,
+    /// Source file declaring the type, the first one found for partial types
+    #[serde(default)]
+    pub source_file: Option<PathBuf>,
+    /// 0-based line of the type's name in the source file
+    #[serde(default)]
+    pub line: Option<u32>,
 }
 
 /// Represents the complete documentation assembly
@@ -85,53 +106,100 @@ impl DocsCompiler {
     
     /// Compile documentation for a source assembly
     pub async fn compile_assembly(&mut self, assembly: &SourceAssembly, unity_project_root: &Path, include_non_public: bool) -> CsResult<DocsAssembly> {
+        let (docs_assembly, _) = self
+            .compile_assembly_with_report(assembly, unity_project_root, include_non_public, &ProgressCounter::new())
+            .await?;
+        Ok(docs_assembly)
+    }
+
+    /// Compile documentation for a source assembly, counting the compiled files in `progress`
+    ///
+    /// Also returns a report of the compilation, with the files that failed to parse.
+    pub async fn compile_assembly_with_report(
+        &mut self,
+        assembly: &SourceAssembly,
+        unity_project_root: &Path,
+        include_non_public: bool,
+        progress: &ProgressCounter,
+    ) -> CsResult<(DocsAssembly, DocsCompileReport)> {
+        let start = Instant::now();
         let source_files = self.get_assembly_source_files(assembly, unity_project_root).await?;
         let mut merged_types: std::collections::HashMap<String, TypeDoc> = std::collections::HashMap::new();
+        let mut report = DocsCompileReport {
+            assembly_name: assembly.name.clone(),
+            files_total: source_files.len(),
+            ..Default::default()
+        };
+        progress.add_total(source_files.len());
         
         for source_file in source_files {
             let full_path = unity_project_root.join(&source_file);
+            let file_result = self.extract_docs_from_file(&full_path, include_non_public).await;
+            progress.advance();
             // If any error occured in a file, we can ignore that
             // We're compiling docs, not an executable, so it doesn't matter, just extract the correct stuff
-            if let Ok(file_types) = self.extract_docs_from_file(&full_path, include_non_public).await{
-                // Incrementally merge types from this file
-                for type_doc in file_types {
-                    if let Some(existing_type) = merged_types.get_mut(&type_doc.name) {
-                        // Merge members from this partial class into the existing one
-                        existing_type.members.extend(type_doc.members);
-                        
-                        // Merge XML documentation (combine if both exist)
-                        if !type_doc.xml_doc.is_empty() {
-                            if existing_type.xml_doc.is_empty() {
-                                existing_type.xml_doc = type_doc.xml_doc;
-                            } else {
-                                // Combine XML docs with a separator
-                                existing_type.xml_doc = format!("{} {}", existing_type.xml_doc, type_doc.xml_doc);
-                            }
+            // It is still reported, so users know why the docs of a file are missing
+            let file_types = match file_result {
+                Ok(file_types) => file_types,
+                Err(e) => {
+                    let reason = match e {
+                        CsError::Parse { message, .. } => message,
+                        e => e.to_string(),
+                    };
+                    if report.failed_files.len() < MAX_FAILED_FILES {
+                        report.failed_files.push(FailedSourceFile { path: full_path.to_string_lossy().to_string(), reason });
+                    } else {
+                        report.failed_files_truncated += 1;
+                    }
+                    continue;
+                }
+            };
+            report.files_compiled += 1;
+            // Incrementally merge types from this file
+            for type_doc in file_types {
+                if let Some(existing_type) = merged_types.get_mut(&type_doc.name) {
+                    // Merge members from this partial class into the existing one
+                    existing_type.members.extend(type_doc.members);
+                    
+                    // Merge XML documentation (combine if both exist)
+                    if !type_doc.xml_doc.is_empty() {
+                        if existing_type.xml_doc.is_empty() {
+                            existing_type.xml_doc = type_doc.xml_doc;
+                        } else {
+                            // Combine XML docs with a separator
+                            existing_type.xml_doc = format!("{} {}", existing_type.xml_doc, type_doc.xml_doc);
                         }
-                        
-                        // Keep the most permissive visibility (if any part is public, the whole type is public)
-                        existing_type.is_public = existing_type.is_public || type_doc.is_public;
-                        
-                        // Merge using namespaces (combine and deduplicate)
-                        for using_ns in type_doc.using_namespaces {
-                            if !existing_type.using_namespaces.contains(&using_ns) {
-                                existing_type.using_namespaces.push(using_ns);
-                            }
+                    }
+                    
+                    if existing_type.type_parameters.is_empty() {
+                        existing_type.type_parameters = type_doc.type_parameters;
+                    }
+
+                    // Keep the most permissive visibility (if any part is public, the whole type is public)
+                    existing_type.is_public = existing_type.is_public || type_doc.is_public;
+                    
+                    // Merge using namespaces (combine and deduplicate)
+                    for using_ns in type_doc.using_namespaces {
+                        if !existing_type.using_namespaces.contains(&using_ns) {
+                            existing_type.using_namespaces.push(using_ns);
                         }
-                    } else {
-                        // First occurrence of this type
-                        merged_types.insert(type_doc.name.clone(), type_doc);
                     }
+                } else {
+                    // First occurrence of this type
+                    merged_types.insert(type_doc.name.clone(), type_doc);
                 }
             }
         }
         
-        Ok(DocsAssembly {
+        report.type_count = merged_types.len();
+        report.duration_ms = start.elapsed().as_millis() as u64;
+        let docs_assembly = DocsAssembly {
             version: DOCS_ASSEMBLY_VERSION,
             assembly_name: assembly.name.clone(),
             is_user_code: assembly.is_user_code,
             types: merged_types,
-        })
+        };
+        Ok((docs_assembly, report))
     }
     
     /// Extract documentation from a single C# source file
@@ -157,7 +225,13 @@ impl DocsCompiler {
         
         let mut types = Vec::new();
         self.extract_types_from_node(tree.root_node(), &content, include_non_public, &mut types, String::new(), &using_namespaces)?;
-        
+
+        for type_doc in &mut types {
+            type_doc.source_file = Some(file_path.to_path_buf());
+            for member_doc in type_doc.members.values_mut() {
+                member_doc.source_file = Some(file_path.to_path_buf());
+            }
+        }
         Ok(types)
     }
     
@@ -198,6 +272,15 @@ impl DocsCompiler {
                 // Extract type documentation
                 if let Some(type_doc) = self.extract_type_doc(node, source, include_non_public, &namespace_prefix, using_namespaces)? {
                     types.push(type_doc);
+
+                    // Nested types are named after their containing type, e.g. `MyNamespace.Outer.Inner`
+                    if let Some(body) = node.child_by_field_name(BODY_FIELD) {
+                        for child in body.children(&mut body.walk()) {
+                            if is_type_declaration(child.kind()) {
+                                self.extract_types_from_node(child, source, include_non_public, types, namespace_prefix.clone(), using_namespaces)?;
+                            }
+                        }
+                    }
                 }
             },
             _ => {
@@ -269,6 +352,9 @@ impl DocsCompiler {
             is_public,
             members,
             using_namespaces: using_namespaces.to_vec(),
+            type_parameters: declared_type_parameters(node, source),
+            source_file: None,
+            line: Some(name_node.start_position().row as u32),
         }))
     }
     
@@ -305,6 +391,8 @@ impl DocsCompiler {
                     name,
                     xml_doc,
                     is_public,
+                    source_file: None,
+                    line: Some(member_name_line(node) as u32),
                 }))
             },
             _ => Ok(None),
@@ -424,6 +512,18 @@ impl DocsCompiler {
     }
 }
 
+/// Row of the name of a member declaration, fields and events declare their names in a variable declarator
+fn member_name_line(node: Node) -> usize {
+    let name = node.child_by_field_name(NAME_FIELD).or_else(|| {
+        let mut cursor = node.walk();
+        let declaration = node.children(&mut cursor).find(|child| child.kind() == VARIABLE_DECLARATION)?;
+        let mut cursor = declaration.walk();
+        let declarator = declaration.children(&mut cursor).find(|child| child.kind() == VARIABLE_DECLARATOR);
+        declarator
+    });
+    name.unwrap_or(node).start_position().row
+}
+
 #[cfg(test)]
 #[path ="docs_compiler_tests.rs"]
 mod tests;
\ No newline at end of file
@@ -176,8 +176,6 @@ async fn test_compile_assembly_csharp() {
         assert!(!pm.is_public, "ProcessPrivately should not be public");
     }
 
-    // Note: Nested classes are not supported by the doc compiler as documented
-
     // Verify private class is included for user code
     let private_class = docs_assembly
         .types
@@ -0,0 +1,731 @@
+//! Documentation export
+//!
+//! Turns a compiled [`DocsAssembly`] into static pages, one per type plus an index,
+//! so the API docs of a project can be published without other tools.
+//! The XML doc comments are converted to Markdown or HTML: the common tags
+//! (`summary`, `remarks`, `param`, `typeparam`, `returns`, `value`, `exception`, `example`)
+//! become sections, inline tags (`c`, `code`, `see`, `paramref`, `para`) are formatted.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use quick_xml::escape::{escape, resolve_predefined_entity};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use super::docs_compiler::{DocsAssembly, MemberDoc, TypeDoc};
+use super::error::{CsError, CsResult, IoContext};
+
+/// Format of exported documentation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Ok(ExportFormat::Markdown),
+            "html" => Ok(ExportFormat::Html),
+            _ => Err(format!("Unknown export format '{}', expected 'markdown' or 'html'", s)),
+        }
+    }
+}
+
+/// Inline content of a doc comment section
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inline {
+    Text(String),
+    /// `<c>`, `<paramref>`, `<typeparamref>` and `<see langword>`
+    Code(String),
+    /// `<code>`, kept as is
+    CodeBlock(String),
+    /// `<see cref>` and `<seealso cref>`, the name without the `T:`/`M:` prefix
+    Reference(String),
+    /// `<para>` boundaries
+    ParagraphBreak,
+}
+
+/// A named entry of a doc comment, e.g. a `<param>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedSection {
+    pub name: String,
+    pub content: Vec<Inline>,
+}
+
+/// The sections of an XML doc comment
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DocComment {
+    pub summary: Vec<Inline>,
+    pub remarks: Vec<Inline>,
+    pub type_params: Vec<NamedSection>,
+    pub params: Vec<NamedSection>,
+    pub returns: Vec<Inline>,
+    pub value: Vec<Inline>,
+    pub exceptions: Vec<NamedSection>,
+    pub example: Vec<Inline>,
+    /// Set when the docs are inherited with `<inheritdoc>`, the referenced symbol if any
+    pub inherit_doc: Option<String>,
+}
+
+/// Which section of the comment the content goes to
+enum Section {
+    None,
+    Summary,
+    Remarks,
+    TypeParam,
+    Param,
+    Returns,
+    Value,
+    Exception,
+    Example,
+}
+
+impl DocComment {
+    /// Parse an XML doc comment, as stored in a [`DocsAssembly`]
+    ///
+    /// Text outside of known sections is ignored, malformed XML stops the parsing but keeps what was parsed.
+    pub fn parse(xml: &str) -> Self {
+        let mut comment = DocComment::default();
+        let mut reader = Reader::from_str(xml);
+        let mut section = Section::None;
+        // set while in a `<code>` or `<c>` element, their text is collected verbatim
+        let mut code: Option<(bool, String)> = None;
+
+        loop {
+            let event = match reader.read_event() {
+                Ok(Event::Eof) => break,
+                Ok(event) => event,
+                Err(e) => {
+                    log::debug!("Stopped parsing doc comment: {}", e);
+                    break;
+                }
+            };
+
+            match event {
+                Event::Start(e) => {
+                    let name = local_name(&e);
+                    if let Some(new_section) = section_for(&name) {
+                        section = new_section;
+                        comment.start_section(&section, &e);
+                    } else {
+                        match name.as_str() {
+                            "c" => code = Some((false, String::new())),
+                            "code" => code = Some((true, String::new())),
+                            "para" | "item" => comment.push(&section, Inline::ParagraphBreak),
+                            _ => {}
+                        }
+                    }
+                }
+                Event::Empty(e) => {
+                    let name = local_name(&e);
+                    match name.as_str() {
+                        "inheritdoc" => comment.inherit_doc = Some(attribute(&e, "cref").map(clean_cref).unwrap_or_default()),
+                        "see" | "seealso" => {
+                            if let Some(cref) = attribute(&e, "cref") {
+                                comment.push(&section, Inline::Reference(clean_cref(cref)));
+                            } else if let Some(word) = attribute(&e, "langword") {
+                                comment.push(&section, Inline::Code(word));
+                            } else if let Some(href) = attribute(&e, "href") {
+                                comment.push(&section, Inline::Text(href));
+                            }
+                        }
+                        "paramref" | "typeparamref" => {
+                            if let Some(name) = attribute(&e, "name") {
+                                comment.push(&section, Inline::Code(name));
+                            }
+                        }
+                        "para" | "br" => comment.push(&section, Inline::ParagraphBreak),
+                        _ => {
+                            // empty sections like `<returns/>`
+                            if section_for(&name).is_some() {
+                                section = Section::None;
+                            }
+                        }
+                    }
+                }
+                Event::End(e) => {
+                    let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                    match name.as_str() {
+                        "c" | "code" => {
+                            if let Some((is_block, text)) = code.take() {
+                                let inline = if is_block {
+                                    Inline::CodeBlock(trim_code_block(&text))
+                                } else {
+                                    Inline::Code(text.trim().to_string())
+                                };
+                                comment.push(&section, inline);
+                            }
+                        }
+                        "para" => comment.push(&section, Inline::ParagraphBreak),
+                        _ if section_for(&name).is_some() => section = Section::None,
+                        _ => {}
+                    }
+                }
+                Event::Text(e) => {
+                    let text = e.decode().unwrap_or_default();
+                    match code.as_mut() {
+                        Some((_, code_text)) => code_text.push_str(&text),
+                        None => comment.push(&section, Inline::Text(text.into_owned())),
+                    }
+                }
+                Event::CData(e) => {
+                    let text = String::from_utf8_lossy(&e).to_string();
+                    match code.as_mut() {
+                        Some((_, code_text)) => code_text.push_str(&text),
+                        None => comment.push(&section, Inline::Text(text)),
+                    }
+                }
+                Event::GeneralRef(e) => {
+                    let name = e.decode().unwrap_or_default();
+                    let text = match e.resolve_char_ref() {
+                        Ok(Some(c)) => c.to_string(),
+                        _ => resolve_predefined_entity(&name).unwrap_or_default().to_string(),
+                    };
+                    match code.as_mut() {
+                        Some((_, code_text)) => code_text.push_str(&text),
+                        None => comment.push(&section, Inline::Text(text)),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        comment
+    }
+
+    fn start_section(&mut self, section: &Section, e: &BytesStart) {
+        let named = |attribute_name| NamedSection {
+            name: attribute(e, attribute_name).map(clean_cref).unwrap_or_default(),
+            content: Vec::new(),
+        };
+        match section {
+            Section::TypeParam => self.type_params.push(named("name")),
+            Section::Param => self.params.push(named("name")),
+            Section::Exception => self.exceptions.push(named("cref")),
+            _ => {}
+        }
+    }
+
+    fn push(&mut self, section: &Section, inline: Inline) {
+        let target = match section {
+            Section::None => return,
+            Section::Summary => &mut self.summary,
+            Section::Remarks => &mut self.remarks,
+            Section::Returns => &mut self.returns,
+            Section::Value => &mut self.value,
+            Section::Example => &mut self.example,
+            Section::TypeParam => match self.type_params.last_mut() {
+                Some(named) => &mut named.content,
+                None => return,
+            },
+            Section::Param => match self.params.last_mut() {
+                Some(named) => &mut named.content,
+                None => return,
+            },
+            Section::Exception => match self.exceptions.last_mut() {
+                Some(named) => &mut named.content,
+                None => return,
+            },
+        };
+        target.push(inline);
+    }
+}
+
+fn section_for(tag: &str) -> Option<Section> {
+    match tag {
+        "summary" => Some(Section::Summary),
+        "remarks" => Some(Section::Remarks),
+        "typeparam" => Some(Section::TypeParam),
+        "param" => Some(Section::Param),
+        "returns" => Some(Section::Returns),
+        "value" => Some(Section::Value),
+        "exception" => Some(Section::Exception),
+        "example" => Some(Section::Example),
+        _ => None,
+    }
+}
+
+fn local_name(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.local_name().as_ref()).to_string()
+}
+
+fn attribute(e: &BytesStart, name: &str) -> Option<String> {
+    let attribute = e.try_get_attribute(name).ok()??;
+    Some(attribute.unescape_value().ok()?.into_owned())
+}
+
+/// Remove the kind prefix of a cref, `T:UnityEngine.Vector3` -> `UnityEngine.Vector3`
+fn clean_cref(cref: String) -> String {
+    match cref.split_once(':') {
+        Some((prefix, name)) if prefix.len() == 1 => name.to_string(),
+        _ => cref,
+    }
+}
+
+/// Remove the blank lines around a code block
+fn trim_code_block(text: &str) -> String {
+    text.trim_matches('\n').trim_end().to_string()
+}
+
+/// Options of a documentation export
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Export non-public types and members, if the assembly has them
+    pub include_non_public: bool,
+}
+
+/// Write the documentation of an assembly to `output_dir`, one page per type and an index
+///
+/// Returns the written files.
+pub fn export_docs(docs: &DocsAssembly, output_dir: &Path, options: &ExportOptions) -> CsResult<Vec<PathBuf>> {
+    fs::create_dir_all(output_dir).with_io_context("Failed to create output directory")?;
+
+    let mut types: Vec<&TypeDoc> = docs
+        .types
+        .values()
+        .filter(|t| options.include_non_public || t.is_public)
+        .collect();
+    types.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if types.is_empty() {
+        return Err(CsError::NoDocumentationAvailable {
+            assembly: docs.assembly_name.clone(),
+        });
+    }
+
+    let mut written = Vec::new();
+    for type_doc in &types {
+        let path = output_dir.join(page_file_name(&type_doc.name, options.format));
+        let page = render_type_page(type_doc, options);
+        fs::write(&path, page).with_io_context("Failed to write type page")?;
+        written.push(path);
+    }
+
+    let index_path = output_dir.join(format!("index.{}", options.format.extension()));
+    fs::write(&index_path, render_index(docs, &types, options.format)).with_io_context("Failed to write index page")?;
+    written.push(index_path);
+
+    Ok(written)
+}
+
+/// File name of the page of a type, characters of generic names that are not valid in file names are replaced
+fn page_file_name(type_name: &str, format: ExportFormat) -> String {
+    let name: String = type_name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    format!("{}.{}", name, format.extension())
+}
+
+fn render_index(docs: &DocsAssembly, types: &[&TypeDoc], format: ExportFormat) -> String {
+    let mut out = String::new();
+    match format {
+        ExportFormat::Markdown => {
+            let _ = writeln!(out, "# {}\n", docs.assembly_name);
+            for type_doc in types {
+                let summary = render_inline_text(&DocComment::parse(&type_doc.xml_doc).summary, format);
+                let _ = write!(out, "- [{}]({})", type_doc.name, page_file_name(&type_doc.name, format));
+                if !summary.is_empty() {
+                    let _ = write!(out, ": {}", summary);
+                }
+                out.push('\n');
+            }
+        }
+        ExportFormat::Html => {
+            out.push_str(&html_header(&docs.assembly_name));
+            let _ = writeln!(out, "<h1>{}</h1>\n<ul>", escape(&docs.assembly_name));
+            for type_doc in types {
+                let summary = render_inline_text(&DocComment::parse(&type_doc.xml_doc).summary, format);
+                let _ = write!(
+                    out,
+                    "<li><a href=\"{}\">{}</a>",
+                    escape(page_file_name(&type_doc.name, format)),
+                    escape(&type_doc.name)
+                );
+                if !summary.is_empty() {
+                    let _ = write!(out, ": {}", summary);
+                }
+                out.push_str("</li>\n");
+            }
+            out.push_str("</ul>\n</body>\n</html>\n");
+        }
+    }
+    out
+}
+
+fn render_type_page(type_doc: &TypeDoc, options: &ExportOptions) -> String {
+    let format = options.format;
+    let mut members: Vec<&MemberDoc> = type_doc
+        .members
+        .values()
+        .filter(|m| options.include_non_public || m.is_public)
+        .collect();
+    members.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut out = String::new();
+    if format == ExportFormat::Html {
+        out.push_str(&html_header(&type_doc.name));
+    }
+
+    out.push_str(&heading(1, &type_doc.name, format));
+    render_comment(&mut out, &DocComment::parse(&type_doc.xml_doc), format);
+
+    if !members.is_empty() {
+        out.push_str(&heading(2, "Members", format));
+        for member in members {
+            out.push_str(&heading(3, &member.name, format));
+            render_comment(&mut out, &DocComment::parse(&member.xml_doc), format);
+        }
+    }
+
+    if format == ExportFormat::Html {
+        out.push_str("</body>\n</html>\n");
+    }
+    out
+}
+
+fn html_header(title: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n",
+        escape(title)
+    )
+}
+
+fn heading(level: usize, text: &str, format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Markdown => format!("{} {}\n\n", "#".repeat(level), text),
+        ExportFormat::Html => format!("<h{level}>{}</h{level}>\n", escape(text)),
+    }
+}
+
+/// Small heading of a part of a comment, e.g. "Parameters"
+fn label(text: &str, format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Markdown => format!("**{}**\n\n", text),
+        ExportFormat::Html => format!("<p><strong>{}</strong></p>\n", escape(text)),
+    }
+}
+
+fn render_comment(out: &mut String, comment: &DocComment, format: ExportFormat) {
+    if let Some(cref) = &comment.inherit_doc {
+        let text = if cref.is_empty() {
+            "Documentation is inherited.".to_string()
+        } else {
+            format!("Documentation is inherited from {}.", code_span(cref, format))
+        };
+        out.push_str(&paragraph(&text, format));
+    }
+
+    out.push_str(&render_blocks(&comment.summary, format));
+
+    let named_sections = [
+        ("Type Parameters", &comment.type_params),
+        ("Parameters", &comment.params),
+        ("Exceptions", &comment.exceptions),
+    ];
+    for (title, sections) in named_sections {
+        if sections.is_empty() {
+            continue;
+        }
+        out.push_str(&label(title, format));
+        out.push_str(&render_named_list(sections, format));
+    }
+
+    let sections = [
+        ("Returns", &comment.returns),
+        ("Value", &comment.value),
+        ("Remarks", &comment.remarks),
+        ("Example", &comment.example),
+    ];
+    for (title, content) in sections {
+        if content.is_empty() {
+            continue;
+        }
+        out.push_str(&label(title, format));
+        out.push_str(&render_blocks(content, format));
+    }
+}
+
+fn render_named_list(sections: &[NamedSection], format: ExportFormat) -> String {
+    let mut out = String::new();
+    match format {
+        ExportFormat::Markdown => {
+            for section in sections {
+                let _ = writeln!(out, "- `{}`: {}", section.name, render_inline_text(&section.content, format));
+            }
+            out.push('\n');
+        }
+        ExportFormat::Html => {
+            out.push_str("<ul>\n");
+            for section in sections {
+                let _ = writeln!(
+                    out,
+                    "<li><code>{}</code>: {}</li>",
+                    escape(&section.name),
+                    render_inline_text(&section.content, format)
+                );
+            }
+            out.push_str("</ul>\n");
+        }
+    }
+    out
+}
+
+/// Render content as paragraphs and code blocks
+fn render_blocks(content: &[Inline], format: ExportFormat) -> String {
+    let mut out = String::new();
+    let mut current: Vec<Inline> = Vec::new();
+
+    let mut flush = |current: &mut Vec<Inline>, out: &mut String| {
+        let text = render_inline_text(current, format);
+        if !text.is_empty() {
+            out.push_str(&paragraph(&text, format));
+        }
+        current.clear();
+    };
+
+    for inline in content {
+        match inline {
+            Inline::ParagraphBreak => flush(&mut current, &mut out),
+            Inline::CodeBlock(code) => {
+                flush(&mut current, &mut out);
+                match format {
+                    ExportFormat::Markdown => {
+                        let _ = write!(out, "```csharp\n{}\n```\n\n", code);
+                    }
+                    ExportFormat::Html => {
+                        let _ = writeln!(out, "<pre><code>{}</code></pre>", escape(code.as_str()));
+                    }
+                }
+            }
+            other => current.push(other.clone()),
+        }
+    }
+    flush(&mut current, &mut out);
+    out
+}
+
+fn paragraph(text: &str, format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Markdown => format!("{}\n\n", text),
+        ExportFormat::Html => format!("<p>{}</p>\n", text),
+    }
+}
+
+fn code_span(text: &str, format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Markdown => format!("`{}`", text),
+        ExportFormat::Html => format!("<code>{}</code>", escape(text)),
+    }
+}
+
+/// Render inline content on one line, whitespace of text is collapsed like in XML docs viewers
+///
+/// Code spans are separated from the text around them as in the XML, e.g. no space is added before the
+/// period that follows a `<paramref>`.
+fn render_inline_text(content: &[Inline], format: ExportFormat) -> String {
+    let mut out = String::new();
+    // whitespace since the last token, written as one space before the next token
+    let mut space = false;
+    let mut push = |out: &mut String, token: &str, space: &mut bool| {
+        if *space && !out.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(token);
+        *space = false;
+    };
+
+    for inline in content {
+        match inline {
+            Inline::Text(text) => {
+                space |= text.starts_with(char::is_whitespace);
+                for (index, word) in text.split_whitespace().enumerate() {
+                    space |= index > 0;
+                    push(&mut out, &escape_text(word, format), &mut space);
+                }
+                space |= text.ends_with(char::is_whitespace);
+            }
+            Inline::Code(code) | Inline::Reference(code) => push(&mut out, &code_span(code, format), &mut space),
+            Inline::CodeBlock(code) => {
+                space = true;
+                push(&mut out, &code_span(code, format), &mut space);
+                space = true;
+            }
+            Inline::ParagraphBreak => space = true,
+        }
+    }
+    out
+}
+
+/// Escape text so it shows as written, instead of being taken as markup
+fn escape_text(text: &str, format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Markdown => {
+            let mut escaped = String::with_capacity(text.len());
+            for c in text.chars() {
+                if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|') {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+            }
+            escaped
+        }
+        ExportFormat::Html => escape(text).into_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cs::docs_compiler::DOCS_ASSEMBLY_VERSION;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    const PLAYER_DOC: &str = "<summary>\nMoves the player by <paramref name=\"delta\"/>.\n</summary>\n<param name=\"delta\">Offset in <see cref=\"T:UnityEngine.Vector3\"/> units</param>\n<returns><c>true</c> if the player moved</returns>\n<example>\n<code>\nplayer.Move(Vector3.up);\n</code>\n</example>";
+
+    fn test_assembly() -> DocsAssembly {
+        let mut members = HashMap::new();
+        members.insert(
+            "Move(UnityEngine.Vector3)".to_string(),
+            MemberDoc {
+                name: "Move(UnityEngine.Vector3)".to_string(),
+                xml_doc: PLAYER_DOC.to_string(),
+                is_public: true,
+                source_file: None,
+                line: None,
+            },
+        );
+        members.insert(
+            "secret".to_string(),
+            MemberDoc {
+                name: "secret".to_string(),
+                xml_doc: "<summary>Hidden</summary>".to_string(),
+                is_public: false,
+                source_file: None,
+                line: None,
+            },
+        );
+
+        let mut types = HashMap::new();
+        types.insert(
+            "Game.Player".to_string(),
+            TypeDoc {
+                name: "Game.Player".to_string(),
+                xml_doc: "<summary>The player &amp; its state.</summary>".to_string(),
+                is_public: true,
+                members,
+                using_namespaces: Vec::new(),
+                type_parameters: Vec::new(),
+                source_file: None,
+                line: None,
+            },
+        );
+        types.insert(
+            "Game.Internal".to_string(),
+            TypeDoc {
+                name: "Game.Internal".to_string(),
+                xml_doc: String::new(),
+                is_public: false,
+                members: HashMap::new(),
+                using_namespaces: Vec::new(),
+                type_parameters: Vec::new(),
+                source_file: None,
+                line: None,
+            },
+        );
+
+        DocsAssembly {
+            version: DOCS_ASSEMBLY_VERSION,
+            assembly_name: "Assembly-CSharp".to_string(),
+            is_user_code: true,
+            types,
+        }
+    }
+
+    #[test]
+    fn test_parse_doc_comment() {
+        let comment = DocComment::parse(PLAYER_DOC);
+        assert_eq!(
+            render_inline_text(&comment.summary, ExportFormat::Markdown),
+            "Moves the player by `delta`."
+        );
+        assert_eq!(comment.params.len(), 1);
+        assert_eq!(comment.params[0].name, "delta");
+        assert_eq!(
+            render_inline_text(&comment.params[0].content, ExportFormat::Markdown),
+            "Offset in `UnityEngine.Vector3` units"
+        );
+        assert_eq!(comment.returns[0], Inline::Code("true".to_string()));
+        assert!(comment.example.contains(&Inline::CodeBlock("player.Move(Vector3.up);".to_string())));
+
+        // text is kept as written, and markdown in it is escaped
+        let comment = DocComment::parse(
+            "<summary>Targets .NET, see <see cref=\"T:Game.Pool\"/> , a *fast* pool_size [0, 1].</summary>",
+        );
+        assert_eq!(
+            render_inline_text(&comment.summary, ExportFormat::Markdown),
+            "Targets .NET, see `Game.Pool` , a \\*fast\\* pool\\_size \\[0, 1\\]."
+        );
+
+        let comment = DocComment::parse("<inheritdoc cref=\"M:Base.Run\"/>");
+        assert_eq!(comment.inherit_doc.as_deref(), Some("Base.Run"));
+    }
+
+    #[test]
+    fn test_export_markdown() {
+        let dir = TempDir::new().unwrap();
+        let options = ExportOptions {
+            format: ExportFormat::Markdown,
+            include_non_public: false,
+        };
+        let written = export_docs(&test_assembly(), dir.path(), &options).unwrap();
+        assert_eq!(written.len(), 2, "Non-public types are skipped: {:?}", written);
+
+        let page = fs::read_to_string(dir.path().join("Game.Player.md")).unwrap();
+        assert!(page.starts_with("# Game.Player\n\nThe player & its state.\n"), "{}", page);
+        assert!(page.contains("### Move(UnityEngine.Vector3)"));
+        assert!(page.contains("- `delta`: Offset in `UnityEngine.Vector3` units"));
+        assert!(page.contains("```csharp\nplayer.Move(Vector3.up);\n```"));
+        assert!(!page.contains("secret"));
+
+        let index = fs::read_to_string(dir.path().join("index.md")).unwrap();
+        assert!(index.contains("- [Game.Player](Game.Player.md): The player & its state."));
+    }
+
+    #[test]
+    fn test_export_html_escapes_content() {
+        let dir = TempDir::new().unwrap();
+        let options = ExportOptions {
+            format: ExportFormat::Html,
+            include_non_public: true,
+        };
+        let written = export_docs(&test_assembly(), dir.path(), &options).unwrap();
+        assert_eq!(written.len(), 3);
+
+        let page = fs::read_to_string(dir.path().join("Game.Player.html")).unwrap();
+        assert!(page.contains("<p>The player &amp; its state.</p>"), "{}", page);
+        assert!(page.contains("<h3>secret</h3>"));
+        assert!(page.contains("<pre><code>player.Move(Vector3.up);</code></pre>"));
+    }
+
+    #[test]
+    fn test_page_file_name() {
+        assert_eq!(page_file_name("Game.Pool<T>", ExportFormat::Markdown), "Game.Pool_T_.md");
+    }
+}
@@ -5,21 +5,30 @@
 
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime};
 use serde::{Deserialize, Serialize};
 use tokio::fs;
 use regex::Regex;
 
 use crate::cs::source_utils::{normalize_path_for_comparison, parse_csproj_file};
-use crate::cs::xml_doc_utils::merge_xml_docs;
+use crate::cs::xml_doc_utils::{merge_xml_docs, summary_to_markdown};
 use crate::cs::{
     assembly_manager::AssemblyManager, 
     package_manager::UnityPackageManager, 
     source_assembly::SourceAssembly, 
     source_utils::get_assembly_source_files,
-    docs_compiler::{DocsCompiler, DocsAssembly, DOCS_ASSEMBLY_VERSION}
+    docs_compiler::{DocsCompiler, DocsAssembly, MemberDoc, TypeDoc, DOCS_ASSEMBLY_VERSION}
 };
-use crate::cs::compile_utils::normalize_symbol_name;
+use crate::config::{ConfigFile, DocsConfig, UnityCodeConfig};
+use crate::cs::symbol_names::{
+    canonical_member_name, canonical_type_name, member_base_name, split_type_and_member, type_parameter_names,
+};
+use crate::dir_changed::{FileChanges, BULK_CHANGE_THRESHOLD};
+use crate::language::progress::ProgressCounter;
+use crate::protocol::{DocsCompileProgress, DocsCompileReport};
+use crate::unity_project_manager::{UnityProjectManager, DEFAULT_DOCS_VERSION};
+use crate::cs::compile_utils::{find_enclosing_symbol, line_column_to_point, normalize_symbol_name};
 use crate::cs::error::{CsResult, CsError, IoContext, JsonContext};
 
 /// Cached documentation assembly with timestamp
@@ -38,6 +47,151 @@ struct CsprojCacheEntry {
     last_modified: SystemTime,
 }
 
+/// The assembly whose documentation is being compiled and the reports of past compilations, shared with the
+/// server so it can report them without waiting for the [`CsDocsManager`]
+///
+/// Requests wait for the compilation, the server polls this meanwhile to tell clients what it is doing.
+/// After the compilation the progress of the last assembly stays, marked as finished.
+#[derive(Debug, Clone, Default)]
+pub struct DocsCompileStatus {
+    current: Arc<Mutex<Option<(String, ProgressCounter, bool)>>>,
+    /// Latest compilation of each assembly compiled since the manager was created
+    reports: Arc<Mutex<HashMap<String, DocsCompileReport>>>,
+}
+
+impl DocsCompileStatus {
+    fn begin(&self, assembly_name: &str, counter: ProgressCounter) {
+        if let Ok(mut current) = self.current.lock() {
+            *current = Some((assembly_name.to_string(), counter, false));
+        }
+    }
+
+    fn finish(&self) {
+        if let Ok(mut current) = self.current.lock() {
+            if let Some((_, _, finished)) = current.as_mut() {
+                *finished = true;
+            }
+        }
+    }
+
+    fn add_report(&self, report: DocsCompileReport) {
+        if let Ok(mut reports) = self.reports.lock() {
+            reports.insert(report.assembly_name.clone(), report);
+        }
+    }
+
+    fn retain_reports(&self, mut keep: impl FnMut(&str) -> bool) {
+        if let Ok(mut reports) = self.reports.lock() {
+            reports.retain(|name, _| keep(name));
+        }
+    }
+
+    /// Reports of the latest compilation of assemblies sorted by name, only the one of `assembly_name` if set
+    pub fn reports(&self, assembly_name: Option<&str>) -> Vec<DocsCompileReport> {
+        let Ok(reports) = self.reports.lock() else {
+            return Vec::new();
+        };
+        let mut reports: Vec<DocsCompileReport> = reports
+            .values()
+            .filter(|report| assembly_name.is_none_or(|name| report.assembly_name == name))
+            .cloned()
+            .collect();
+        reports.sort_by(|a, b| a.assembly_name.cmp(&b.assembly_name));
+        reports
+    }
+
+    /// Progress of the current or last compilation, `None` if nothing was compiled yet
+    pub fn progress(&self) -> Option<DocsCompileProgress> {
+        let current = self.current.lock().ok()?;
+        let (assembly_name, counter, finished) = current.as_ref()?;
+        Some(DocsCompileProgress {
+            assembly_name: assembly_name.clone(),
+            files_done: counter.done(),
+            files_total: counter.total(),
+            finished: *finished,
+        })
+    }
+}
+
+/// Compilation of the documentation of one assembly, run without holding the [`CsDocsManager`]
+///
+/// Created by [`CsDocsManager::next_prewarm`], the result is stored by [`CsDocsManager::finish_prewarm`].
+#[derive(Debug)]
+pub struct DocsCompileJob {
+    assembly: SourceAssembly,
+    unity_project_root: PathBuf,
+    status: DocsCompileStatus,
+}
+
+impl DocsCompileJob {
+    /// Compile the documentation, reporting progress and the result to the status of the manager that created the job
+    pub async fn compile(self) -> CompiledDocs {
+        let assembly_name = self.assembly.name.clone();
+        let counter = ProgressCounter::new();
+        let start = Instant::now();
+        self.status.begin(&assembly_name, counter.clone());
+        let compiled = match DocsCompiler::new() {
+            Ok(mut docs_compiler) => {
+                docs_compiler
+                    .compile_assembly_with_report(
+                        &self.assembly,
+                        &self.unity_project_root,
+                        self.assembly.is_user_code,
+                        &counter,
+                    )
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        self.status.finish();
+
+        let docs = match compiled {
+            Ok((docs_assembly, report)) => {
+                if !report.failed_files.is_empty() {
+                    log::warn!(
+                        "{} of {} files of {} failed to parse, their docs are missing",
+                        report.failed_files.len() + report.failed_files_truncated,
+                        report.files_total,
+                        assembly_name
+                    );
+                }
+                self.status.add_report(report);
+                Ok(docs_assembly)
+            }
+            Err(e) => {
+                self.status.add_report(DocsCompileReport {
+                    assembly_name: assembly_name.clone(),
+                    duration_ms: start.elapsed().as_millis() as u64,
+                    error_message: Some(e.to_string()),
+                    ..Default::default()
+                });
+                Err(e)
+            }
+        };
+        CompiledDocs { assembly_name, docs }
+    }
+}
+
+/// Documentation compiled by a [`DocsCompileJob`]
+#[derive(Debug)]
+pub struct CompiledDocs {
+    assembly_name: String,
+    docs: CsResult<DocsAssembly>,
+}
+
+impl CompiledDocs {
+    /// Name of the compiled assembly
+    pub fn assembly_name(&self) -> &str {
+        &self.assembly_name
+    }
+}
+
+/// Documentation of an assembly that is either cached or needs to be compiled
+enum DocsLookup {
+    Cached(DocsAssembly),
+    Compile(DocsCompileJob),
+}
+
 /// Enriched documentation result that includes inheritance information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocResult {
@@ -53,6 +207,139 @@ pub struct DocResult {
     pub inherited_from_member_name: Option<String>,
     /// Whether this documentation was resolved through inheritdoc
     pub is_inherited: bool,
+    /// Source file declaring the symbol, `None` for symbols without source in the project
+    pub source_file_path: Option<PathBuf>,
+    /// 0-based line of the symbol's name in the source file
+    pub line: Option<u32>,
+    /// Scripting Reference page of engine symbols, which have no source in the project
+    pub documentation_url: Option<String>,
+}
+
+impl DocResult {
+    /// Name of the symbol the documentation was found for, type and member
+    pub fn symbol_name(&self) -> String {
+        match &self.source_member_name {
+            Some(member_name) => format!("{}.{}", self.source_type_name, member_name),
+            None => self.source_type_name.clone(),
+        }
+    }
+
+    /// Name of the symbol the documentation was inherited from, `None` if it isn't inherited
+    pub fn inherited_from_symbol_name(&self) -> Option<String> {
+        if !self.is_inherited {
+            return None;
+        }
+        let type_name = self.inherited_from_type_name.as_ref()?;
+        Some(match &self.inherited_from_member_name {
+            Some(member_name) => format!("{}.{}", type_name, member_name),
+            None => type_name.clone(),
+        })
+    }
+}
+
+/// Namespaces of the engine, their types are documented in the Scripting Reference
+const ENGINE_NAMESPACES: [&str; 2] = ["UnityEngine.", "UnityEditor."];
+
+/// Scripting Reference page of a symbol of the engine, `None` for other symbols
+///
+/// The page of a type is named after the type without `UnityEngine.` or `UnityEditor.`, e.g.
+/// `UIElements.VisualElement.html`. Methods are `Transform.Rotate.html`, properties and fields are
+/// `Transform-position.html`.
+pub fn engine_docs_url(symbol_name: &str, docs_version: Option<&str>) -> Option<String> {
+    let symbol_name = ENGINE_NAMESPACES.iter().find_map(|namespace| symbol_name.strip_prefix(namespace))?;
+    let page = match split_symbol_name(symbol_name) {
+        (type_name, Some(member)) => match member.split_once('(') {
+            Some((method, _)) => format!("{}.{}", type_name, method),
+            None => format!("{}-{}", type_name, member),
+        },
+        (type_name, None) => type_name.to_string(),
+    };
+    Some(format!(
+        "https://docs.unity3d.com/{}/Documentation/ScriptReference/{}.html",
+        docs_version.unwrap_or(DEFAULT_DOCS_VERSION),
+        page
+    ))
+}
+
+/// The symbol declared at a position of a source file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceSymbol {
+    /// Full symbol name, type and member, as accepted by [`CsDocsManager::get_docs_for_symbol`]
+    pub symbol_name: String,
+    /// Fully qualified type name
+    pub type_name: String,
+    /// Normalized member name, `None` if the position is not in a member
+    pub member_name: Option<String>,
+    /// Assembly that compiles the source file, if it is user code of a known assembly
+    pub assembly_name: Option<String>,
+}
+
+/// Type and member of a symbol name, parameters of methods stay with the member
+///
+/// Without parameters a member can't be told from a nested type, a lower case name is taken as a member.
+fn split_symbol_name(symbol_name: &str) -> (&str, Option<&str>) {
+    let path_end = symbol_name.find('(').unwrap_or(symbol_name.len());
+    match symbol_name[..path_end].rfind('.') {
+        Some(dot)
+            if path_end < symbol_name.len()
+                || symbol_name[dot + 1..].starts_with(|c: char| c.is_ascii_lowercase()) =>
+        {
+            (&symbol_name[..dot], Some(&symbol_name[dot + 1..]))
+        }
+        _ => (symbol_name, None),
+    }
+}
+
+/// Find a type by its name in any form, e.g. ``MyGame.Pool`1`` or `MyGame.Outer+Inner`, see [`canonical_type_name`]
+fn find_type<'a>(docs_assembly: &'a DocsAssembly, type_name: &str) -> Option<&'a TypeDoc> {
+    if let Some(type_doc) = docs_assembly.types.get(type_name) {
+        return Some(type_doc);
+    }
+    let canonical = canonical_type_name(type_name);
+    docs_assembly.types.iter().find(|(name, _)| canonical_type_name(name) == canonical).map(|(_, type_doc)| type_doc)
+}
+
+/// Find a type by its name without namespace and containing types, e.g. `Enemy` for `MyGame.Enemy`
+///
+/// Names with a namespace are only found by [`find_type`], the first type by full name wins if several match.
+fn find_type_by_simple_name<'a>(docs_assembly: &'a DocsAssembly, type_name: &str) -> Option<&'a TypeDoc> {
+    if type_name.contains('.') {
+        return None;
+    }
+    docs_assembly
+        .types
+        .values()
+        .filter(|type_doc| canonical_type_name(&type_doc.name).rsplit('.').next() == Some(type_name))
+        .min_by(|a, b| a.name.cmp(&b.name))
+}
+
+/// Find a member of a type by its name in any form, see [`canonical_member_name`]
+///
+/// `type_name` is the type as named in the query, type parameters named there take precedence over the
+/// names in the declaration, e.g. `Pool<U>.Push(U)` finds `Push(T)` of `Pool<T>`.
+fn find_member<'a>(type_doc: &'a TypeDoc, type_name: &str, member_name: &str) -> Option<&'a MemberDoc> {
+    if let Some(member_doc) = type_doc.members.get(member_name) {
+        return Some(member_doc);
+    }
+    let query_type_parameters = type_parameter_names(type_name);
+    let type_parameters = if query_type_parameters.is_empty() { &type_doc.type_parameters } else { &query_type_parameters };
+    let canonical = canonical_member_name(member_name, type_parameters);
+    type_doc
+        .members
+        .iter()
+        .find(|(name, _)| canonical_member_name(name, &type_doc.type_parameters) == canonical)
+        .map(|(_, member_doc)| member_doc)
+}
+
+/// The `[docs]` section of the project's `UnityCode.toml`, `previous` if the file is invalid
+fn load_docs_config(unity_project_root: &Path, previous: &DocsConfig) -> DocsConfig {
+    match UnityCodeConfig::load(unity_project_root) {
+        Ok(config) => config.map(|config| config.docs).unwrap_or_default(),
+        Err(e) => {
+            log::warn!("Failed to load docs configuration: {}", e);
+            previous.clone()
+        }
+    }
 }
 
 /// Main CS documentation manager
@@ -62,13 +349,27 @@ pub struct CsDocsManager {
     assemblies: HashMap<String, SourceAssembly>,
     package_manager: UnityPackageManager,
     assembly_manager: AssemblyManager,
-    docs_compiler: DocsCompiler,
     /// In-memory cache of compiled documentation assemblies
     docs_cache: HashMap<String, CachedDocsAssembly>,
     /// Directory where JSON documentation files are stored
     docs_assemblies_dir: PathBuf,
     /// Unified cache for .csproj files - includes assembly metadata and source files
     csproj_cache: HashMap<PathBuf, CsprojCacheEntry>,
+    /// Watcher for changes of .cs and .csproj files, see [`CsDocsManager::watch_sources`]
+    source_changes: Option<FileChanges>,
+    /// Assemblies whose docs were invalidated by source changes and should be compiled again
+    pending_prewarm: HashSet<String>,
+    /// Assemblies taken by [`CsDocsManager::next_prewarm`] whose documentation is being compiled
+    prewarming: HashSet<String>,
+    /// Progress and reports of the compilation of documentation, see [`CsDocsManager::compile_status`]
+    compile_status: DocsCompileStatus,
+    /// Which assemblies get documentation, the `[docs]` section of `UnityCode.toml`
+    docs_config: DocsConfig,
+    config_file: ConfigFile,
+    /// Assemblies found by the last discovery that are excluded by `docs_config`
+    excluded_assemblies: HashSet<String>,
+    /// Reads the Unity version of the project for links to the Scripting Reference, it caches the version
+    unity_project_manager: UnityProjectManager,
 }
 
 impl CsDocsManager {
@@ -76,18 +377,30 @@ impl CsDocsManager {
     pub fn new(unity_project_root: PathBuf) -> CsResult<Self> {
         let package_manager = UnityPackageManager::new(unity_project_root.clone());
         let assembly_manager = AssemblyManager::new(unity_project_root.clone());
-        let docs_compiler = DocsCompiler::new()?;
+        // fail early if the C# grammar can't be loaded, each compilation creates its own compiler
+        DocsCompiler::new()?;
         let docs_assemblies_dir = unity_project_root.join("Library").join("UnityCode").join("DocAssemblies");
+        let unity_project_manager = UnityProjectManager::new(unity_project_root.clone());
+        let mut config_file = ConfigFile::new(&unity_project_root);
+        let docs_config = load_docs_config(&unity_project_root, &DocsConfig::default());
+        config_file.mark_loaded();
         
         Ok(Self {
             unity_project_root,
             assemblies: HashMap::new(),
             package_manager,
             assembly_manager,
-            docs_compiler,
             docs_cache: HashMap::new(),
             docs_assemblies_dir,
             csproj_cache: HashMap::new(),
+            source_changes: None,
+            pending_prewarm: HashSet::new(),
+            prewarming: HashSet::new(),
+            compile_status: DocsCompileStatus::default(),
+            docs_config,
+            config_file,
+            excluded_assemblies: HashSet::new(),
+            unity_project_manager,
         })
     }
 
@@ -123,18 +436,269 @@ impl CsDocsManager {
         let docs_assembly = self.get_docs_for_assembly(&target_assembly_name).await?;
         
         // Search for the symbol in the documentation
+        let normalized_symbol_name = normalize_symbol_name(symbol_name);
         if let Some(docs) = docs_assembly {
-            let normalized_symbol_name = normalize_symbol_name(symbol_name);
             self.find_symbol_with_inheritdoc(&docs, normalized_symbol_name.as_str())
                 .ok_or_else(|| CsError::SymbolNotFound { 
                     symbol: normalized_symbol_name.clone(), 
                     assembly: target_assembly_name.clone() 
                 })
+        } else if let Some(documentation_url) = engine_docs_url(&normalized_symbol_name, self.docs_version().as_deref()) {
+            // engine assemblies have no source, their documentation is online
+            let (type_name, member_name) = split_symbol_name(&normalized_symbol_name);
+            Ok(DocResult {
+                xml_doc: String::new(),
+                source_type_name: type_name.to_string(),
+                source_member_name: member_name.map(str::to_string),
+                inherited_from_type_name: None,
+                inherited_from_member_name: None,
+                is_inherited: false,
+                source_file_path: None,
+                line: None,
+                documentation_url: Some(documentation_url),
+            })
         } else {
             Err(CsError::NoDocumentationAvailable { assembly: target_assembly_name })
         }
     }
     
+    /// Status of the compilation of documentation, a handle that can be polled while a request waits for it
+    pub fn compile_status(&self) -> DocsCompileStatus {
+        self.compile_status.clone()
+    }
+
+    /// Version of the Scripting Reference for the project, e.g. `6000.0`
+    pub fn docs_version(&self) -> Option<String> {
+        self.unity_project_manager.get_unity_version_for_docs()
+    }
+
+    /// The project manager reading the Unity version, clones share its cached version
+    pub fn unity_project_manager(&self) -> &UnityProjectManager {
+        &self.unity_project_manager
+    }
+
+    /// Watch .csproj files and C# sources of the project, so cached data is invalidated when they change
+    ///
+    /// Changes are applied by [`CsDocsManager::process_source_changes`].
+    pub fn watch_sources(&mut self) {
+        let mut changes = match FileChanges::new(&["cs", "csproj"]) {
+            Ok(changes) => changes,
+            Err(e) => {
+                log::warn!("Failed to create source watcher: {}", e);
+                return;
+            }
+        };
+
+        // .csproj files are only in the project root, sources of user code are in Assets and embedded packages
+        let watches = [
+            (self.unity_project_root.clone(), false),
+            (self.unity_project_root.join("Assets"), true),
+            (self.unity_project_root.join("Packages"), true),
+        ];
+        for (dir, recursive) in watches {
+            if dir.is_dir() {
+                if let Err(e) = changes.watch(&dir, recursive) {
+                    log::warn!("Failed to watch {}: {}", dir.display(), e);
+                }
+            }
+        }
+
+        self.source_changes = Some(changes);
+    }
+
+    /// Invalidate cached data of the sources changed since the last call
+    ///
+    /// Returns the names of the affected assemblies. Their documentation is compiled again
+    /// in the background by [`CsDocsManager::next_prewarm`] if it was compiled before.
+    /// When many sources changed at once, e.g. after switching branches, all user code is invalidated.
+    pub async fn process_source_changes(&mut self) -> Vec<String> {
+        let changed = match &self.source_changes {
+            Some(changes) => changes.take_changes(),
+            None => return Vec::new(),
+        };
+        if changed.is_empty() {
+            return Vec::new();
+        }
+        if changed.len() >= BULK_CHANGE_THRESHOLD {
+            log::info!("{} sources changed at once, invalidating all user code", changed.len());
+            return self.invalidate_all().await;
+        }
+        self.invalidate_paths(&changed).await
+    }
+
+    /// Invalidate the .csproj cache and documentation of all user code assemblies in one pass
+    ///
+    /// Unlike [`CsDocsManager::invalidate_paths`] this doesn't look up the assembly of each file,
+    /// the .csproj files are parsed again when documentation is next requested.
+    pub async fn invalidate_all(&mut self) -> Vec<String> {
+        let mut affected: HashSet<String> =
+            self.csproj_cache.drain().map(|(_, entry)| entry.assembly.name).collect();
+        affected.extend(
+            self.assemblies
+                .values()
+                .filter(|assembly| assembly.is_user_code)
+                .map(|assembly| assembly.name.clone()),
+        );
+        self.invalidate_assemblies(affected).await
+    }
+
+    /// Invalidate the .csproj cache entries and documentation of the assemblies the paths belong to
+    async fn invalidate_paths(&mut self, paths: &[PathBuf]) -> Vec<String> {
+        let mut affected = HashSet::new();
+
+        for path in paths {
+            let normalized = normalize_path_for_comparison(path);
+            if path.extension().and_then(|s| s.to_str()) == Some("csproj") {
+                let key = self
+                    .csproj_cache
+                    .keys()
+                    .find(|key| normalize_path_for_comparison(key) == normalized)
+                    .cloned();
+                if let Some(entry) = key.and_then(|key| self.csproj_cache.remove(&key)) {
+                    affected.insert(entry.assembly.name);
+                }
+            } else {
+                for entry in self.csproj_cache.values() {
+                    if entry.source_files.contains(&normalized) {
+                        affected.insert(entry.assembly.name.clone());
+                    }
+                }
+            }
+        }
+
+        self.invalidate_assemblies(affected).await
+    }
+
+    /// Drop the documentation of the assemblies, the ones that were compiled are compiled again
+    async fn invalidate_assemblies(&mut self, affected: HashSet<String>) -> Vec<String> {
+        for assembly_name in &affected {
+            // the JSON file would still be valid until Unity compiles the assembly again
+            let json_path = self.get_docs_json_path(assembly_name);
+            let had_json = fs::remove_file(&json_path).await.is_ok();
+            let was_compiled = self.docs_cache.remove(assembly_name).is_some() || had_json;
+            if was_compiled || self.prewarming.contains(assembly_name) {
+                self.pending_prewarm.insert(assembly_name.clone());
+            }
+        }
+
+        let mut affected: Vec<String> = affected.into_iter().collect();
+        affected.sort();
+        affected
+    }
+
+    /// Take the next assembly invalidated by source changes whose documentation should be compiled again
+    ///
+    /// The job compiles without the manager so requests for docs are handled meanwhile, pass its result to
+    /// [`CsDocsManager::finish_prewarm`]. Returns `None` if no pending assembly needs to be compiled.
+    pub async fn next_prewarm(&mut self) -> Option<DocsCompileJob> {
+        loop {
+            let assembly_name = self.pending_prewarm.iter().next()?.clone();
+            self.pending_prewarm.remove(&assembly_name);
+
+            match self.lookup_docs(&assembly_name).await {
+                Ok(Some(DocsLookup::Compile(job))) => {
+                    self.prewarming.insert(assembly_name);
+                    return Some(job);
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("Failed to compile docs for {}: {}", assembly_name, e),
+            }
+        }
+    }
+
+    /// Store the documentation compiled by a job of [`CsDocsManager::next_prewarm`]
+    ///
+    /// The documentation is dropped if the assembly was invalidated again while it compiled,
+    /// it is pending to be compiled again then.
+    pub async fn finish_prewarm(&mut self, compiled: CompiledDocs) {
+        self.prewarming.remove(&compiled.assembly_name);
+        if self.pending_prewarm.contains(&compiled.assembly_name) {
+            log::debug!("Sources of {} changed while its docs compiled, dropping them", compiled.assembly_name);
+            return;
+        }
+        let assembly_name = compiled.assembly_name.clone();
+        if let Err(e) = self.store_compiled_docs(compiled).await {
+            log::warn!("Failed to compile docs for {}: {}", assembly_name, e);
+        }
+    }
+
+    /// Get the type and member declared at a position of a C# source file
+    ///
+    /// `line` and `column` are 0-based, the column counts characters. The returned names are
+    /// normalized like compiled documentation, so clients don't need to build symbol names themselves.
+    pub async fn get_symbol_at_position(
+        &mut self,
+        source_file_path: &Path,
+        line: u32,
+        column: u32,
+    ) -> CsResult<SourceSymbol> {
+        let content = fs::read_to_string(source_file_path).await
+            .with_io_context("Failed to read source file")?;
+
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_c_sharp::LANGUAGE.into())
+            .map_err(|e| CsError::TreeSitterLanguage { message: e.to_string() })?;
+        let tree = parser.parse(&content, None)
+            .ok_or_else(|| CsError::Parse {
+                file: source_file_path.to_path_buf(),
+                message: "Failed to parse C# file".to_string(),
+            })?;
+
+        let no_symbol = || CsError::NoSymbolAtPosition {
+            file: source_file_path.to_path_buf(),
+            line,
+            column,
+        };
+        let point = line_column_to_point(&content, line, column).ok_or_else(no_symbol)?;
+        let symbol = find_enclosing_symbol(tree.root_node(), &content, point).ok_or_else(no_symbol)?;
+
+        // the symbol is useful even if the project's assemblies can't be read
+        let assembly_name = self.find_assembly_for_source_file(source_file_path).await.ok().flatten();
+
+        Ok(SourceSymbol {
+            symbol_name: symbol.symbol_name(),
+            type_name: symbol.type_name,
+            member_name: symbol.member_name,
+            assembly_name,
+        })
+    }
+
+    /// Get documentation for a type of user code from the docs compiled so far, without knowing its assembly
+    ///
+    /// Searches the compiled user assemblies, e.g. for a custom visual element named in a style sheet.
+    /// Never compiles or reads docs, so it's cheap enough for hover. Returns `None` if no compiled
+    /// user assembly declares the type.
+    pub fn compiled_docs_for_type(&self, type_name: &str) -> Option<DocResult> {
+        let mut user_assemblies: Vec<&DocsAssembly> = self.docs_cache.values()
+            .map(|cached| &cached.docs)
+            .filter(|docs_assembly| docs_assembly.is_user_code)
+            .collect();
+        user_assemblies.sort_by(|a, b| a.assembly_name.cmp(&b.assembly_name));
+
+        let docs_assembly = user_assemblies.into_iter().find(|docs_assembly| find_type(docs_assembly, type_name).is_some())?;
+        self.find_symbol_with_inheritdoc(docs_assembly, type_name)
+    }
+
+    /// Summary of a method from the docs compiled so far as markdown, the first documented overload
+    ///
+    /// `type_name` is fully qualified, or a simple name without namespace that is looked up in all namespaces.
+    /// Searches all compiled assemblies and never compiles or reads docs, like [`CsDocsManager::compiled_docs_for_type`].
+    pub fn compiled_method_summary(&self, type_name: &str, method_name: &str) -> Option<String> {
+        let mut assemblies: Vec<&DocsAssembly> = self.docs_cache.values().map(|cached| &cached.docs).collect();
+        assemblies.sort_by(|a, b| a.assembly_name.cmp(&b.assembly_name));
+
+        assemblies.into_iter().find_map(|docs_assembly| {
+            let type_doc = find_type(docs_assembly, type_name).or_else(|| find_type_by_simple_name(docs_assembly, type_name))?;
+            let mut members: Vec<&String> =
+                type_doc.members.keys().filter(|member| member_base_name(member) == method_name).collect();
+            members.sort();
+            members.into_iter().find_map(|member| {
+                let docs = self.find_symbol_with_inheritdoc(docs_assembly, &format!("{}.{}", type_doc.name, member))?;
+                summary_to_markdown(&docs.xml_doc)
+            })
+        })
+    }
+
     /// Find assembly name that contains the given source file path
     async fn find_assembly_for_source_file(&mut self, source_file_path: &Path) -> CsResult<Option<String>> {
         // Ensure assemblies are discovered
@@ -157,7 +721,23 @@ impl CsDocsManager {
 
     
     /// Get documentation for a specific assembly, using cache when possible
-    async fn get_docs_for_assembly(&mut self, assembly_name: &str) -> CsResult<Option<DocsAssembly>> {
+    ///
+    /// Returns `None` if the project has no assembly with that name.
+    pub async fn get_docs_for_assembly(&mut self, assembly_name: &str) -> CsResult<Option<DocsAssembly>> {
+        match self.lookup_docs(assembly_name).await? {
+            None => Ok(None),
+            Some(DocsLookup::Cached(docs_assembly)) => Ok(Some(docs_assembly)),
+            Some(DocsLookup::Compile(job)) => {
+                let compiled = job.compile().await;
+                self.store_compiled_docs(compiled).await.map(Some)
+            }
+        }
+    }
+
+    /// Cached documentation of an assembly, or the job to compile it if the cache is missing or outdated
+    ///
+    /// Returns `None` if the project has no assembly with that name.
+    async fn lookup_docs(&mut self, assembly_name: &str) -> CsResult<Option<DocsLookup>> {
         // Check if we need to update assemblies
         self.discover_assemblies().await?;
         self.assembly_manager.update().await?;
@@ -165,26 +745,33 @@ impl CsDocsManager {
         // Check if assembly exists
         let source_assembly = match self.assemblies.get(assembly_name) {
             Some(assembly) => assembly.clone(),
+            None if self.excluded_assemblies.contains(assembly_name) => {
+                return Err(CsError::AssemblyExcluded { assembly: assembly_name.to_string() });
+            }
             None => return Ok(None),
         };
         
         // Check if we can use cached documentation
         if let Some(cached_docs) = self.get_cached_docs(assembly_name).await? {
-            return Ok(Some(cached_docs));
+            return Ok(Some(DocsLookup::Cached(cached_docs)));
         }
         
         // Need to compile documentation
-        let include_non_public = source_assembly.is_user_code;
-        let docs_assembly = self.docs_compiler.compile_assembly(
-            &source_assembly, 
-            &self.unity_project_root, 
-            include_non_public
-        ).await?;
+        Ok(Some(DocsLookup::Compile(DocsCompileJob {
+            assembly: source_assembly,
+            unity_project_root: self.unity_project_root.clone(),
+            status: self.compile_status.clone(),
+        })))
+    }
+
+    /// Cache the compiled documentation
+    async fn store_compiled_docs(&mut self, compiled: CompiledDocs) -> CsResult<DocsAssembly> {
+        let docs_assembly = compiled.docs?;
         
         // Cache the compiled documentation
         self.cache_docs(&docs_assembly).await?;
         
-        Ok(Some(docs_assembly))
+        Ok(docs_assembly)
     }
     
     /// Check if cached documentation is available and up-to-date
@@ -297,8 +884,8 @@ impl CsDocsManager {
     
     /// Find symbol documentation with options for parameter omission
     fn find_symbol_basic_with_options(&self, docs_assembly: &DocsAssembly, symbol_name: &str, allow_parameter_omission: bool) -> Option<DocResult> {
-        // Try exact type match
-        if let Some(type_doc) = docs_assembly.types.get(symbol_name) {
+        // Try type match
+        if let Some(type_doc) = find_type(docs_assembly, symbol_name) {
             if !type_doc.xml_doc.trim().is_empty() {
                 return Some(DocResult {
                     xml_doc: type_doc.xml_doc.clone(),
@@ -307,18 +894,18 @@ impl CsDocsManager {
                     inherited_from_type_name: None,
                     inherited_from_member_name: None,
                     is_inherited: false,
+                    source_file_path: type_doc.source_file.clone(),
+                    line: type_doc.line,
+                    documentation_url: None,
                 });
             }
         }
         
         // Try to extract type name from fully qualified symbol name
-        if let Some(last_dot) = symbol_name.rfind('.') {
-            let type_name = &symbol_name[..last_dot];
-            let member_name = &symbol_name[last_dot + 1..];
-            
-            if let Some(type_doc) = docs_assembly.types.get(type_name) {
-                // First try exact match
-                if let Some(member_doc) = type_doc.members.get(member_name) {
+        if let Some((type_name, member_name)) = split_type_and_member(symbol_name) {
+            if let Some(type_doc) = find_type(docs_assembly, type_name) {
+                // First try matching the full member name
+                if let Some(member_doc) = find_member(type_doc, type_name, member_name) {
                     if !member_doc.xml_doc.trim().is_empty() {
                         return Some(DocResult {
                             xml_doc: member_doc.xml_doc.clone(),
@@ -327,21 +914,19 @@ impl CsDocsManager {
                             inherited_from_type_name: None,
                             inherited_from_member_name: None,
                             is_inherited: false,
+                            source_file_path: member_doc.source_file.clone(),
+                            line: member_doc.line,
+                            documentation_url: None,
                         });
                     }
                 }
                 
                 // If allow_parameter_omission is true and the search symbol doesn't have parameters, try parameter omission
                 if allow_parameter_omission && !member_name.contains('(') {
-                    // Find all members that start with this method name (without parameters)
+                    // Find all members that have this method name (without type parameters and parameters)
+                    let base_name = member_base_name(member_name);
                     let matching_members: Vec<_> = type_doc.members.iter()
-                        .filter(|(name, _)| {
-                            if let Some(paren_pos) = name.find('(') {
-                                &name[..paren_pos] == member_name
-                            } else {
-                                name.as_str() == member_name
-                            }
-                        })
+                        .filter(|(name, _)| member_base_name(name) == base_name)
                         .collect();
                     
                     // If there's exactly one match, use it
@@ -355,6 +940,9 @@ impl CsDocsManager {
                                 inherited_from_type_name: None,
                                 inherited_from_member_name: None,
                                 is_inherited: false,
+                                source_file_path: member_doc.source_file.clone(),
+                                line: member_doc.line,
+                                documentation_url: None,
                             });
                         }
                     }
@@ -397,6 +985,9 @@ impl CsDocsManager {
                         inherited_from_type_name: Some(target_result.source_type_name),
                         inherited_from_member_name: target_result.source_member_name,
                         is_inherited: true,
+                        source_file_path: original_result.source_file_path.clone(),
+                        line: original_result.line,
+                        documentation_url: None,
                     });
                 }
             }
@@ -437,32 +1028,88 @@ impl CsDocsManager {
     }
 
     /// Find all assemblies and their source files
+    ///
+    /// Assemblies excluded by the `[docs]` section of `UnityCode.toml` are left out like assemblies
+    /// that don't exist. When the assemblies changed, the documentation of the ones that no longer
+    /// exist or are excluded is removed.
     async fn discover_assemblies(&mut self) -> CsResult<()> {
+        if self.config_file.has_changed() {
+            self.docs_config = load_docs_config(&self.unity_project_root, &self.docs_config);
+            self.config_file.mark_loaded();
+        }
+
         // Clear existing assemblies
-        self.assemblies.clear();
+        let previous: HashSet<String> = self.assemblies.drain().map(|(name, _)| name).collect();
+        self.excluded_assemblies.clear();
 
         // Find user code assemblies from .csproj files
-        let user_assemblies = self.find_user_assemblies().await?;
-        
-        for assembly in user_assemblies {
-            self.assemblies.insert(assembly.name.clone(), assembly);
-        }
+        let mut assemblies = self.find_user_assemblies().await?;
 
         // Find package assemblies using the package manager
         self.package_manager.update().await?;
         
         for package in self.package_manager.get_packages() {
-            for assembly in package.assemblies {
+            assemblies.extend(package.assemblies);
+        }
+
+        // package assemblies replace user code assemblies of the same name
+        for assembly in assemblies {
+            if self.docs_config.includes_assembly(&assembly.name, assembly.is_user_code) {
+                self.excluded_assemblies.remove(&assembly.name);
                 self.assemblies.insert(assembly.name.clone(), assembly);
+            } else {
+                self.assemblies.remove(&assembly.name);
+                self.excluded_assemblies.insert(assembly.name);
             }
         }
 
+        // the first discovery also finds the ones removed while the server wasn't running
+        if previous.len() != self.assemblies.len() || previous.iter().any(|name| !self.assemblies.contains_key(name)) {
+            self.remove_stale_docs().await;
+        }
+
         Ok(())
     }
+
+    /// Remove the documentation of assemblies that no longer exist, e.g. after an asmdef was deleted or renamed,
+    /// or that are excluded
+    ///
+    /// Both the JSON files and the in-memory cache are removed. Returns the names of the removed assemblies.
+    async fn remove_stale_docs(&mut self) -> Vec<String> {
+        let assemblies = &self.assemblies;
+        let mut removed: Vec<String> =
+            self.docs_cache.keys().filter(|name| !assemblies.contains_key(*name)).cloned().collect();
+        self.docs_cache.retain(|name, _| assemblies.contains_key(name));
+        self.pending_prewarm.retain(|name| assemblies.contains_key(name));
+        self.compile_status.retain_reports(|name| assemblies.contains_key(name));
+
+        if let Ok(mut entries) = fs::read_dir(&self.docs_assemblies_dir).await {
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let path = entry.path();
+                if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                    continue;
+                }
+                let Some(assembly_name) = path.file_stem().and_then(|s| s.to_str()) else {
+                    continue;
+                };
+                if !self.assemblies.contains_key(assembly_name) && fs::remove_file(&path).await.is_ok() {
+                    removed.push(assembly_name.to_string());
+                }
+            }
+        }
+
+        removed.sort();
+        removed.dedup();
+        if !removed.is_empty() {
+            log::info!("Removed docs of assemblies that no longer exist or are excluded: {}", removed.join(", "));
+        }
+        removed
+    }
     
     /// Find user code assemblies from .csproj files in the project root with efficient caching
     async fn find_user_assemblies(&mut self) -> CsResult<Vec<SourceAssembly>> {
         let mut all_assemblies = Vec::new();
+        let mut found_csproj_files = HashSet::new();
         
         // Read all .csproj files in the project root
         let mut entries = fs::read_dir(&self.unity_project_root).await
@@ -471,6 +1118,8 @@ impl CsDocsManager {
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
             if path.extension().and_then(|s| s.to_str()) == Some("csproj") {
+                found_csproj_files.insert(path.clone());
+
                 // Check if we can use cached data
                 let metadata = fs::metadata(&path).await
                     .with_io_context("Failed to get .csproj file metadata")?;
@@ -520,6 +1169,9 @@ impl CsDocsManager {
                 }
             }
         }
+
+        // deleted .csproj files, e.g. of a removed asmdef
+        self.csproj_cache.retain(|path, _| found_csproj_files.contains(path));
         
         Ok(all_assemblies)
     }
@@ -1,7 +1,7 @@
 use std::path::{Path, PathBuf};
 
 use crate::{
-    cs::{docs_manager::CsDocsManager, source_utils::normalize_path_for_comparison},
+    cs::{docs_manager::*, error::CsError, source_utils::normalize_path_for_comparison},
     test_utils::get_unity_project_root,
 };
 
@@ -371,3 +371,420 @@ async fn test_inheritdoc_resolution() {
     assert!(doc_result.xml_doc.contains("return from Add5"));
     assert!(doc_result.xml_doc.contains("remarks from Add7"));
 }
+
+#[tokio::test]
+async fn test_get_symbol_at_position() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let source_path = temp_dir.path().join("Player.cs");
+    std::fs::write(
+        &source_path,
+        "namespace Game {\n    public class Player {\n        public void Jump(int height) {\n            height++;\n        }\n    }\n}\n",
+    )
+    .unwrap();
+
+    let mut manager = CsDocsManager::new(temp_dir.path().to_path_buf()).unwrap();
+    let symbol = manager.get_symbol_at_position(&source_path, 3, 14).await.unwrap();
+    assert_eq!(symbol.symbol_name, "Game.Player.Jump(int)");
+    assert_eq!(symbol.type_name, "Game.Player");
+    assert_eq!(symbol.member_name.as_deref(), Some("Jump(int)"));
+    assert_eq!(symbol.assembly_name, None);
+
+    assert!(matches!(
+        manager.get_symbol_at_position(&source_path, 0, 0).await,
+        Err(CsError::NoSymbolAtPosition { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_invalidate_changed_sources() {
+    use std::collections::{HashMap, HashSet};
+    use std::time::SystemTime;
+
+    use crate::cs::docs_compiler::{DocsAssembly, DOCS_ASSEMBLY_VERSION};
+    use crate::cs::source_assembly::SourceAssembly;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path().to_path_buf();
+    let csproj_path = root.join("Game.csproj");
+    let source_path = root.join("Assets").join("Player.cs");
+
+    let mut manager = CsDocsManager::new(root.clone()).unwrap();
+    manager.csproj_cache.insert(
+        csproj_path.clone(),
+        CsprojCacheEntry {
+            assembly: SourceAssembly {
+                name: "Game".to_string(),
+                is_user_code: true,
+                source_location: csproj_path.clone(),
+            },
+            source_files: HashSet::from([normalize_path_for_comparison(&source_path)]),
+            last_modified: SystemTime::now(),
+        },
+    );
+    manager.docs_cache.insert(
+        "Game".to_string(),
+        CachedDocsAssembly {
+            docs: DocsAssembly {
+                version: DOCS_ASSEMBLY_VERSION,
+                assembly_name: "Game".to_string(),
+                is_user_code: true,
+                types: HashMap::new(),
+            },
+            cached_at: SystemTime::now(),
+        },
+    );
+
+    // unrelated files don't invalidate anything
+    assert!(manager.invalidate_paths(&[root.join("Assets").join("Other.cs")]).await.is_empty());
+
+    assert_eq!(manager.invalidate_paths(&[source_path]).await, vec!["Game".to_string()]);
+    assert!(manager.docs_cache.is_empty());
+    assert!(manager.pending_prewarm.contains("Game"));
+
+    assert_eq!(manager.invalidate_paths(&[csproj_path.clone()]).await, vec!["Game".to_string()]);
+    assert!(manager.csproj_cache.is_empty());
+
+    // the assembly doesn't exist in the temp project, prewarming just consumes it
+    assert!(manager.next_prewarm().await.is_none());
+    assert!(manager.pending_prewarm.is_empty());
+
+    // after a bulk change, all user code is invalidated without looking up the changed files
+    manager.csproj_cache.insert(
+        csproj_path.clone(),
+        CsprojCacheEntry {
+            assembly: SourceAssembly {
+                name: "Game".to_string(),
+                is_user_code: true,
+                source_location: csproj_path.clone(),
+            },
+            source_files: HashSet::new(),
+            last_modified: SystemTime::now(),
+        },
+    );
+    std::fs::create_dir_all(&manager.docs_assemblies_dir).unwrap();
+    std::fs::write(manager.get_docs_json_path("Game"), "{}").unwrap();
+    assert_eq!(manager.invalidate_all().await, vec!["Game".to_string()]);
+    assert!(manager.csproj_cache.is_empty());
+    assert!(!manager.get_docs_json_path("Game").exists());
+    assert!(manager.pending_prewarm.contains("Game"));
+
+    // docs of an assembly invalidated while they compile in the background are dropped
+    manager.pending_prewarm.clear();
+    manager.prewarming.insert("Game".to_string());
+    manager.invalidate_assemblies(HashSet::from(["Game".to_string()])).await;
+    assert!(manager.pending_prewarm.contains("Game"));
+    manager
+        .finish_prewarm(CompiledDocs {
+            assembly_name: "Game".to_string(),
+            docs: Ok(DocsAssembly {
+                version: DOCS_ASSEMBLY_VERSION,
+                assembly_name: "Game".to_string(),
+                is_user_code: true,
+                types: HashMap::new(),
+            }),
+        })
+        .await;
+    assert!(manager.prewarming.is_empty());
+    assert!(manager.docs_cache.is_empty());
+    assert!(manager.pending_prewarm.contains("Game"));
+}
+
+#[tokio::test]
+async fn test_compiled_docs_for_type() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    std::fs::create_dir_all(root.join("Assets")).unwrap();
+    std::fs::write(
+        root.join("Assets").join("HealthBar.cs"),
+        "namespace MyGame.UI {\n    /// <summary>Shows the health of the player</summary>\n    [UxmlElement]\n    public partial class HealthBar : VisualElement { }\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("Assembly-CSharp.csproj"),
+        r#"<Project><AssemblyName>Assembly-CSharp</AssemblyName><Compile Include="Assets/HealthBar.cs" /></Project>"#,
+    )
+    .unwrap();
+
+    let mut manager = CsDocsManager::new(root.to_path_buf()).unwrap();
+    // docs are never compiled for the type
+    assert!(manager.compiled_docs_for_type("MyGame.UI.HealthBar").is_none());
+
+    manager.get_docs_for_assembly("Assembly-CSharp").await.unwrap().unwrap();
+    let docs = manager.compiled_docs_for_type("MyGame.UI.HealthBar").unwrap();
+    assert!(docs.xml_doc.contains("Shows the health of the player"));
+    assert!(docs.source_file_path.as_ref().is_some_and(|path| path.ends_with("Assets/HealthBar.cs")));
+    assert_eq!(docs.line, Some(3));
+    assert_eq!(docs.documentation_url, None);
+    assert!(manager.compiled_docs_for_type("MyGame.UI.ManaBar").is_none());
+
+    // engine assemblies have no source, their symbols link the Scripting Reference instead
+    let docs = manager
+        .get_docs_for_symbol("UnityEngine.Transform.Rotate(UnityEngine.Vector3)", Some("UnityEngine.CoreModule"), None)
+        .await
+        .unwrap();
+    assert!(docs.xml_doc.is_empty());
+    assert_eq!(docs.source_type_name, "UnityEngine.Transform");
+    assert_eq!(docs.source_member_name.as_deref(), Some("Rotate(Vector3)"));
+    assert_eq!(
+        docs.documentation_url.as_deref(),
+        Some("https://docs.unity3d.com/6000.0/Documentation/ScriptReference/Transform.Rotate.html")
+    );
+    assert!(manager.get_docs_for_symbol("Other.Type", Some("Other"), None).await.is_err());
+}
+
+#[tokio::test]
+async fn test_compiled_method_summary() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    std::fs::create_dir_all(root.join("Assets")).unwrap();
+    std::fs::write(
+        root.join("Assets").join("Enemy.cs"),
+        "namespace MyGame {\n    public class Enemy : MonoBehaviour {\n        /// <summary>Registers the <c>Enemy</c></summary>\n        protected virtual void Awake() { }\n        protected void Update() { }\n    }\n}\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("Assembly-CSharp.csproj"),
+        r#"<Project><AssemblyName>Assembly-CSharp</AssemblyName><Compile Include="Assets/Enemy.cs" /></Project>"#,
+    )
+    .unwrap();
+
+    let mut manager = CsDocsManager::new(root.to_path_buf()).unwrap();
+    assert_eq!(manager.compiled_method_summary("Enemy", "Awake"), None);
+
+    manager.get_docs_for_assembly("Assembly-CSharp").await.unwrap().unwrap();
+    assert_eq!(manager.compiled_method_summary("MyGame.Enemy", "Awake").as_deref(), Some("Registers the `Enemy`"));
+    // classes named in source files have no namespace
+    assert_eq!(manager.compiled_method_summary("Enemy", "Awake").as_deref(), Some("Registers the `Enemy`"));
+    assert_eq!(manager.compiled_method_summary("Other.Enemy", "Awake"), None);
+    // undocumented methods fall back to the summaries of Unity messages
+    assert_eq!(manager.compiled_method_summary("Enemy", "Update"), None);
+}
+
+#[tokio::test]
+async fn test_remove_stale_docs() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    std::fs::create_dir_all(root.join("Assets")).unwrap();
+    std::fs::write(root.join("Assets").join("Player.cs"), "namespace Game { public class Player { } }\n").unwrap();
+    let write_csproj = |assembly_name: &str| {
+        std::fs::write(
+            root.join(format!("{}.csproj", assembly_name)),
+            format!(
+                r#"<Project><AssemblyName>{}</AssemblyName><Compile Include="Assets/Player.cs" /></Project>"#,
+                assembly_name
+            ),
+        )
+        .unwrap();
+    };
+    write_csproj("Game");
+    write_csproj("Game.Tools");
+
+    let mut manager = CsDocsManager::new(root.to_path_buf()).unwrap();
+    // docs of an assembly that was removed while the server wasn't running
+    std::fs::create_dir_all(&manager.docs_assemblies_dir).unwrap();
+    std::fs::write(manager.get_docs_json_path("Old"), "{}").unwrap();
+
+    assert!(manager.get_docs_for_assembly("Game").await.unwrap().is_some());
+    assert!(manager.get_docs_for_assembly("Game.Tools").await.unwrap().is_some());
+    assert!(!manager.get_docs_json_path("Old").exists());
+    assert!(manager.get_docs_json_path("Game").exists());
+    assert!(manager.get_docs_json_path("Game.Tools").exists());
+
+    // renaming the asmdef renames the .csproj and the assembly
+    std::fs::rename(root.join("Game.csproj"), root.join("Game.Runtime.csproj")).unwrap();
+    write_csproj("Game.Runtime");
+    assert!(manager.get_docs_for_assembly("Game.Runtime").await.unwrap().is_some());
+    assert!(!manager.get_docs_json_path("Game").exists());
+    assert!(!manager.docs_cache.contains_key("Game"));
+    assert!(!manager.csproj_cache.contains_key(&root.join("Game.csproj")));
+    assert!(manager.docs_cache.contains_key("Game.Runtime"));
+
+    // deleting an asmdef deletes its .csproj
+    std::fs::remove_file(root.join("Game.Tools.csproj")).unwrap();
+    assert!(manager.get_docs_for_assembly("Game.Tools").await.unwrap().is_none());
+    assert!(!manager.get_docs_json_path("Game.Tools").exists());
+    assert!(!manager.docs_cache.contains_key("Game.Tools"));
+    assert!(manager.get_docs_json_path("Game.Runtime").exists());
+
+    // the assembly can be added back after it was removed
+    write_csproj("Game.Tools");
+    assert!(manager.get_docs_for_assembly("Game.Tools").await.unwrap().is_some());
+    assert!(manager.get_docs_json_path("Game.Tools").exists());
+}
+
+#[tokio::test]
+async fn test_excluded_assemblies() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    std::fs::create_dir_all(root.join("Assets")).unwrap();
+    std::fs::write(root.join("Assets").join("Player.cs"), "namespace Game { public class Player { } }\n").unwrap();
+    for assembly_name in ["Game", "Game.Generated"] {
+        std::fs::write(
+            root.join(format!("{}.csproj", assembly_name)),
+            format!(
+                r#"<Project><AssemblyName>{}</AssemblyName><Compile Include="Assets/Player.cs" /></Project>"#,
+                assembly_name
+            ),
+        )
+        .unwrap();
+    }
+
+    let mut manager = CsDocsManager::new(root.to_path_buf()).unwrap();
+    assert!(manager.get_docs_for_assembly("Game.Generated").await.unwrap().is_some());
+    assert!(manager.get_docs_json_path("Game.Generated").exists());
+
+    // excluding the assembly removes its docs, the changed file is picked up by the next discovery
+    std::fs::write(root.join("UnityCode.toml"), "[docs]\nexclude = [\"*.Generated\"]\n").unwrap();
+    manager.config_file = ConfigFile::new(root);
+    assert!(matches!(
+        manager.get_docs_for_assembly("Game.Generated").await,
+        Err(CsError::AssemblyExcluded { .. })
+    ));
+    assert!(!manager.get_docs_json_path("Game.Generated").exists());
+    assert!(!manager.docs_cache.contains_key("Game.Generated"));
+    assert!(manager.get_docs_for_assembly("Game").await.unwrap().is_some());
+    assert!(manager.get_docs_for_assembly("Missing").await.unwrap().is_none());
+
+    // an included assembly gets docs although it's excluded
+    std::fs::write(root.join("UnityCode.toml"), "[docs]\nexclude = [\"Game*\"]\ninclude = [\"Game.Generated\"]\n").unwrap();
+    manager.config_file = ConfigFile::new(root);
+    assert!(manager.get_docs_for_assembly("Game.Generated").await.unwrap().is_some());
+    assert!(matches!(manager.get_docs_for_assembly("Game").await, Err(CsError::AssemblyExcluded { .. })));
+}
+
+#[tokio::test]
+async fn test_compile_reports() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    std::fs::create_dir_all(root.join("Assets")).unwrap();
+    std::fs::write(root.join("Assets").join("Player.cs"), "namespace Game { public class Player { } }\n").unwrap();
+    std::fs::write(root.join("Assets").join("Broken.cs"), "namespace Game { public class Broken { \n").unwrap();
+    std::fs::write(
+        root.join("Game.csproj"),
+        r#"<Project><AssemblyName>Game</AssemblyName><Compile Include="Assets/Player.cs" /><Compile Include="Assets/Broken.cs" /></Project>"#,
+    )
+    .unwrap();
+
+    let mut manager = CsDocsManager::new(root.to_path_buf()).unwrap();
+    let status = manager.compile_status();
+    assert_eq!(status.progress(), None);
+    assert!(status.reports(None).is_empty());
+
+    let docs = manager.get_docs_for_assembly("Game").await.unwrap().unwrap();
+    assert!(docs.types.contains_key("Game.Player"));
+
+    let reports = status.reports(None);
+    assert_eq!(reports.len(), 1);
+    let report = &reports[0];
+    assert_eq!(report.assembly_name, "Game");
+    assert_eq!((report.files_total, report.files_compiled, report.type_count), (2, 1, 1));
+    assert_eq!(report.error_message, None);
+    assert_eq!(report.failed_files.len(), 1);
+    assert!(report.failed_files[0].path.ends_with("Broken.cs"));
+    assert_eq!(report.failed_files[0].reason, "There are syntax errors in C# file");
+    assert!(status.reports(Some("Other")).is_empty());
+
+    // the status is shared with the server, the last compilation stays finished
+    let progress = status.progress().unwrap();
+    assert_eq!(progress.assembly_name, "Game");
+    assert_eq!((progress.files_done, progress.files_total, progress.finished), (2, 2, true));
+}
+
+#[tokio::test]
+async fn test_generic_and_nested_symbol_names() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let root = temp_dir.path();
+    std::fs::create_dir_all(root.join("Assets")).unwrap();
+    std::fs::write(
+        root.join("Assets").join("Pool.cs"),
+        r#"using System.Collections.Generic;
+namespace Game {
+    /// <summary>pool</summary>
+    public class Pool<T> {
+        /// <summary>push</summary>
+        public void Push(T item) { }
+        /// <summary>push many</summary>
+        public void PushAll(List<T> items, ref int count) { }
+        /// <summary>convert</summary>
+        public U Convert<U>(T item, U fallback) { return fallback; }
+
+        /// <summary>slot</summary>
+        public struct Slot {
+            /// <summary>index</summary>
+            public int Index;
+            /// <summary>swap</summary>
+            public void Swap(Slot other) { }
+        }
+    }
+
+    /// <summary>point</summary>
+    public struct Point { }
+}
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("Game.csproj"),
+        r#"<Project><AssemblyName>Game</AssemblyName><Compile Include="Assets/Pool.cs" /></Project>"#,
+    )
+    .unwrap();
+
+    let mut manager = CsDocsManager::new(root.to_path_buf()).unwrap();
+    let cases = [
+        // generic types
+        ("Game.Pool", "pool"),
+        ("Game.Pool<T>", "pool"),
+        ("Game.Pool{T}", "pool"),
+        ("Game.Pool`1", "pool"),
+        // members of generic types, by type parameter name or position
+        ("Game.Pool.Push(T)", "push"),
+        ("Game.Pool<T>.Push(T)", "push"),
+        ("Game.Pool<TItem>.Push(TItem)", "push"),
+        ("Game.Pool`1.Push(`0)", "push"),
+        ("Game.Pool{T}.PushAll(System.Collections.Generic.List{T}, ref System.Int32)", "push many"),
+        ("Game.Pool`1.PushAll(System.Collections.Generic.List{`0},System.Int32@)", "push many"),
+        // generic methods
+        ("Game.Pool.Convert<U>(T, U)", "convert"),
+        ("Game.Pool{T}.Convert{V}(T, V)", "convert"),
+        ("Game.Pool`1.Convert``1(`0,``0)", "convert"),
+        // nested types and their members
+        ("Game.Pool.Slot", "slot"),
+        ("Game.Pool+Slot", "slot"),
+        ("Game.Pool`1+Slot", "slot"),
+        ("Game.Pool<T>.Slot.Index", "index"),
+        ("Game.Pool`1+Slot.Swap(Game.Pool`1+Slot)", "swap"),
+        ("Game.Pool.Slot.Swap(Slot)", "swap"),
+        // structs are named with their namespace too
+        ("Game.Point", "point"),
+    ];
+    for (symbol, summary) in cases {
+        let docs = manager
+            .get_docs_for_symbol(symbol, Some("Game"), None)
+            .await
+            .unwrap_or_else(|e| panic!("{}: {}", symbol, e));
+        assert!(docs.xml_doc.contains(&format!("<summary>{}</summary>", summary)), "{}: {}", symbol, docs.xml_doc);
+    }
+
+    for symbol in ["Game.Pool.Push(int)", "Game.Pool.Convert(T, T)", "Game.Slot", "Game.Pool`1.Convert``2(`0,``0)"] {
+        assert!(manager.get_docs_for_symbol(symbol, Some("Game"), None).await.is_err(), "{}", symbol);
+    }
+    assert!(manager.compiled_docs_for_type("Game.Pool+Slot").is_some());
+}
+
+#[test]
+fn test_engine_docs_url() {
+    let url = |symbol: &str| engine_docs_url(symbol, Some("2022.3"));
+    assert_eq!(
+        url("UnityEngine.UIElements.VisualElement").as_deref(),
+        Some("https://docs.unity3d.com/2022.3/Documentation/ScriptReference/UIElements.VisualElement.html")
+    );
+    assert_eq!(
+        url("UnityEngine.Transform.position").as_deref(),
+        Some("https://docs.unity3d.com/2022.3/Documentation/ScriptReference/Transform-position.html")
+    );
+    assert_eq!(
+        url("UnityEditor.EditorWindow.GetWindow(System.Type)").as_deref(),
+        Some("https://docs.unity3d.com/2022.3/Documentation/ScriptReference/EditorWindow.GetWindow.html")
+    );
+    assert_eq!(url("Unity.Mathematics.math"), None);
+}
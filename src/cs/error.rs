@@ -73,9 +73,17 @@ pub enum CsError {
     #[error("No assembly specified")]
     NoAssemblySpecified,
 
+    /// No type or member declared at a source position
+    #[error("No symbol at {file:?}:{line}:{column}")]
+    NoSymbolAtPosition { file: PathBuf, line: u32, column: u32 },
+
     /// No documentation available error
     #[error("No documentation available for assembly '{assembly}'")]
     NoDocumentationAvailable { assembly: String },
+
+    /// Assembly excluded from documentation by the project configuration
+    #[error("Docs of assembly '{assembly}' are excluded by UnityCode.toml")]
+    AssemblyExcluded { assembly: String },
 }
 
 /// Result type alias for CS operations
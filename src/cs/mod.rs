@@ -13,10 +13,15 @@ pub mod docs_manager;
 pub mod package_manager;
 pub mod compile_utils;
 pub mod docs_compiler;
+pub mod docs_export;
 pub mod source_assembly;
 pub mod xml_doc_utils;
 pub mod constants;
 pub mod error;
+pub mod compile_errors;
+pub mod custom_elements;
+pub mod unity_messages;
+pub mod symbol_names;
 
 // Re-export common types
 pub use error::{CsError, CsResult};
\ No newline at end of file
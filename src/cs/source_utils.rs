@@ -98,7 +98,7 @@ pub async fn get_assembly_source_files(assembly: &SourceAssembly, unity_project_
 }
 
 /// Recursively find all .cs files in a directory and return absolute paths
-pub fn find_cs_files_in_dir<'a>(dir: &'a Path, unity_project_root: &'a Path) -> std::pin::Pin<Box<dyn std::future::Future<Output = CsResult<Vec<PathBuf>>> + 'a>> {
+pub fn find_cs_files_in_dir<'a>(dir: &'a Path, unity_project_root: &'a Path) -> std::pin::Pin<Box<dyn std::future::Future<Output = CsResult<Vec<PathBuf>>> + Send + 'a>> {
     Box::pin(async move {
         let mut cs_files = Vec::new();
         
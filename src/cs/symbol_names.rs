@@ -0,0 +1,319 @@
+//! Symbol Name Canonicalization
+//!
+//! The same symbol is named differently depending on where the name comes from. Docs assemblies store names
+//! as written in C#, e.g. `Pool.Push(T)` and `Add<T>(T, int)`. Documentation IDs and reflection name them
+//! ``Pool`1.Push(`0)`` and ``Add``1(``0, System.Int32)``, with `Outer+Inner` for nested types, and crefs use
+//! `Add{T}(T, int)`. Both the stored names and the names looked up are turned into a canonical form, so any of
+//! them finds the same docs.
+//!
+//! In the canonical form:
+//! - Nested types are separated from their containing type by `.`
+//! - Types have no generic parameters, docs assemblies store a generic type under its name without them
+//! - Methods end with their number of type parameters, e.g. ``Add``1``
+//! - Parameter types are simple names with C# keywords for primitives, and type parameters are positional,
+//!   `` `0 `` for the first of the type, ``` ``0 ``` for the first of the method
+//! - `ref`, `out` and `in` parameters are all `ref`, overloads can't differ only by them
+
+/// CLR names of primitive types, as they are written after their namespace is removed
+const PRIMITIVE_TYPES: [(&str, &str); 16] = [
+    ("Boolean", "bool"),
+    ("Byte", "byte"),
+    ("SByte", "sbyte"),
+    ("Char", "char"),
+    ("Decimal", "decimal"),
+    ("Double", "double"),
+    ("Single", "float"),
+    ("Int32", "int"),
+    ("UInt32", "uint"),
+    ("Int64", "long"),
+    ("UInt64", "ulong"),
+    ("Int16", "short"),
+    ("UInt16", "ushort"),
+    ("Object", "object"),
+    ("String", "string"),
+    ("Void", "void"),
+];
+
+/// Canonical name of a type, e.g. ``MyGame.Pool`1+Slot`` and `MyGame.Pool<T>.Slot` are `MyGame.Pool.Slot`
+pub fn canonical_type_name(name: &str) -> String {
+    split_top_level(name.trim(), &['.', '+', '/'])
+        .into_iter()
+        .map(|segment| generic_parts(segment).0)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Canonical name of a member, `type_parameters` are the names of the type parameters of its type
+///
+/// E.g. in `Pool<T>`, `Add<U>(T, List<U>, ref System.Int32)` is ``Add``1(`0, List<``0>, ref int)``.
+pub fn canonical_member_name(member: &str, type_parameters: &[String]) -> String {
+    let member = member.trim();
+    let paren = find_top_level(member, '(');
+    let (name, method_parameters, arity) = generic_parts(&member[..paren.unwrap_or(member.len())]);
+
+    let mut result = name.to_string();
+    if arity > 0 {
+        result.push_str(&format!("``{}", arity));
+    }
+    if let Some(paren) = paren {
+        let end = member.rfind(')').filter(|end| *end > paren).unwrap_or(member.len());
+        let parameters: Vec<String> = split_top_level(&member[paren + 1..end], &[','])
+            .into_iter()
+            .filter(|parameter| !parameter.trim().is_empty())
+            .map(|parameter| canonical_parameter(parameter, &method_parameters, type_parameters))
+            .collect();
+        result.push('(');
+        result.push_str(&parameters.join(", "));
+        result.push(')');
+    }
+    result
+}
+
+/// Name of a member without its type parameters and parameters, e.g. `Add` of `Add<T>(T, int)`
+pub fn member_base_name(member: &str) -> &str {
+    let member = member.trim();
+    let paren = find_top_level(member, '(').unwrap_or(member.len());
+    generic_parts(&member[..paren]).0
+}
+
+/// Split a symbol into its type and member at the last `.` or `+` outside of generics and parameters
+///
+/// `None` if the symbol has no separator, e.g. a type without namespace.
+pub fn split_type_and_member(symbol: &str) -> Option<(&str, &str)> {
+    let end = find_top_level(symbol, '(').unwrap_or(symbol.len());
+    let mut depth = 0;
+    let mut split = None;
+    for (index, ch) in symbol[..end].char_indices() {
+        match ch {
+            '<' | '{' | '[' => depth += 1,
+            '>' | '}' | ']' => depth -= 1,
+            '.' | '+' if depth == 0 => split = Some(index),
+            _ => {}
+        }
+    }
+    split.map(|index| (&symbol[..index], &symbol[index + 1..]))
+}
+
+/// Names of the type parameters a type name declares, e.g. `T` of `MyGame.Pool<T>`, empty for ``Pool`1``
+pub fn type_parameter_names(type_name: &str) -> Vec<String> {
+    let segments = split_top_level(type_name.trim(), &['.', '+', '/']);
+    segments.last().map(|segment| generic_parts(segment).1).unwrap_or_default()
+}
+
+fn canonical_parameter(parameter: &str, method_parameters: &[String], type_parameters: &[String]) -> String {
+    let mut parameter = parameter.trim();
+    let mut is_ref = false;
+    loop {
+        if let Some(rest) = ["ref ", "out ", "in "].iter().find_map(|modifier| parameter.strip_prefix(modifier)) {
+            is_ref = true;
+            parameter = rest.trim_start();
+        } else if let Some(rest) = ["params ", "this ", "scoped "].iter().find_map(|modifier| parameter.strip_prefix(modifier)) {
+            parameter = rest.trim_start();
+        } else {
+            break;
+        }
+    }
+    // documentation IDs mark ref and out parameters with `@`
+    if let Some(rest) = parameter.strip_suffix('@') {
+        is_ref = true;
+        parameter = rest;
+    }
+
+    let type_name = canonical_type_reference(parameter, method_parameters, type_parameters);
+    if is_ref {
+        format!("ref {}", type_name)
+    } else {
+        type_name
+    }
+}
+
+/// Canonical name of a type used by a parameter, these keep their generic arguments
+fn canonical_type_reference(type_name: &str, method_parameters: &[String], type_parameters: &[String]) -> String {
+    let type_name = type_name.trim();
+    for suffix in ["[]", "?", "*"] {
+        if let Some(element) = type_name.strip_suffix(suffix) {
+            return format!("{}{}", canonical_type_reference(element, method_parameters, type_parameters), suffix);
+        }
+    }
+    if type_name.ends_with(']') {
+        // multidimensional arrays, e.g. `int[,]`
+        if let Some(start) = type_name.rfind('[') {
+            let element = canonical_type_reference(&type_name[..start], method_parameters, type_parameters);
+            return format!("{}{}", element, &type_name[start..]);
+        }
+    }
+    if type_name.starts_with('`') {
+        return type_name.to_string();
+    }
+    if let Some(index) = method_parameters.iter().position(|name| name == type_name) {
+        return format!("``{}", index);
+    }
+    if let Some(index) = type_parameters.iter().position(|name| name == type_name) {
+        return format!("`{}", index);
+    }
+
+    // without namespace and containing types, e.g. `Inner` of ``MyGame.Outer`1+Inner``
+    let segments = split_top_level(type_name, &['.', '+', '/']);
+    let (base, arguments, _) = generic_parts(segments.last().copied().unwrap_or(type_name));
+    let simple = primitive_keyword(base);
+    if arguments.is_empty() {
+        return simple;
+    }
+    let arguments: Vec<String> = arguments
+        .iter()
+        .map(|argument| canonical_type_reference(argument, method_parameters, type_parameters))
+        .collect();
+    format!("{}<{}>", simple, arguments.join(", "))
+}
+
+/// C# keyword of a primitive type named by its CLR name, other names are kept
+fn primitive_keyword(name: &str) -> String {
+    PRIMITIVE_TYPES
+        .iter()
+        .find(|(clr_name, _)| *clr_name == name)
+        .map_or(name, |(_, keyword)| keyword)
+        .to_string()
+}
+
+/// Name, generic arguments and arity of a name with generics, e.g. `Dictionary<K, V>` or ``Dictionary`2``
+///
+/// Arguments are only known when they are written out, not for the arity form.
+fn generic_parts(segment: &str) -> (&str, Vec<String>, usize) {
+    let segment = segment.trim();
+    let Some(start) = segment.find(['<', '{', '`']) else {
+        return (segment, Vec::new(), 0);
+    };
+    let name = &segment[..start];
+    let rest = &segment[start..];
+
+    if rest.starts_with('`') {
+        let digits: String = rest.trim_start_matches('`').chars().take_while(|c| c.is_ascii_digit()).collect();
+        return (name, Vec::new(), digits.parse().unwrap_or(0));
+    }
+    let end = rest.rfind(['>', '}']).unwrap_or(rest.len());
+    let arguments: Vec<String> = split_top_level(&rest[1..end.max(1)], &[','])
+        .into_iter()
+        .map(|argument| argument.trim().to_string())
+        .filter(|argument| !argument.is_empty())
+        .collect();
+    let arity = arguments.len();
+    (name, arguments, arity)
+}
+
+/// Split at separators outside of brackets
+fn split_top_level<'a>(text: &'a str, separators: &[char]) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, ch) in text.char_indices() {
+        match ch {
+            '<' | '{' | '[' | '(' => depth += 1,
+            '>' | '}' | ']' | ')' => depth -= 1,
+            _ if depth == 0 && separators.contains(&ch) => {
+                parts.push(&text[start..index]);
+                start = index + ch.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// Byte index of the first `target` outside of generic brackets
+fn find_top_level(text: &str, target: char) -> Option<usize> {
+    let mut depth = 0;
+    for (index, ch) in text.char_indices() {
+        match ch {
+            _ if ch == target && depth == 0 => return Some(index),
+            '<' | '{' | '[' => depth += 1,
+            '>' | '}' | ']' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_canonical_type_name() {
+        let cases = [
+            ("MyGame.Player", "MyGame.Player"),
+            ("MyGame.Pool<T>", "MyGame.Pool"),
+            ("MyGame.Pool{T}", "MyGame.Pool"),
+            ("MyGame.Pool`1", "MyGame.Pool"),
+            ("MyGame.Pool<int>", "MyGame.Pool"),
+            ("MyGame.Cache<TKey, TValue>", "MyGame.Cache"),
+            ("System.Collections.Generic.List`1[[System.Int32, mscorlib]]", "System.Collections.Generic.List"),
+            ("MyGame.Outer+Inner", "MyGame.Outer.Inner"),
+            ("MyGame.Outer/Inner", "MyGame.Outer.Inner"),
+            ("MyGame.Outer.Inner", "MyGame.Outer.Inner"),
+            ("MyGame.Outer`1+Inner`2", "MyGame.Outer.Inner"),
+            ("MyGame.Outer<T>.Inner<U, V>", "MyGame.Outer.Inner"),
+            (" Player ", "Player"),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(canonical_type_name(name), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_canonical_member_name() {
+        let none: &[String] = &[];
+        let pool = names(&["T"]);
+        let cases: [(&str, &[String], &str); 22] = [
+            ("position", none, "position"),
+            ("Jump()", none, "Jump()"),
+            ("Move(float, float)", none, "Move(float, float)"),
+            ("Move(System.Single,System.Single)", none, "Move(float, float)"),
+            ("Move(Single, Single)", none, "Move(float, float)"),
+            ("Rotate(UnityEngine.Vector3)", none, "Rotate(Vector3)"),
+            ("Add<T>(T, int, int)", none, "Add``1(``0, int, int)"),
+            ("Add{T}(T, int, int)", none, "Add``1(``0, int, int)"),
+            ("Add``1(``0,System.Int32,System.Int32)", none, "Add``1(``0, int, int)"),
+            ("Add<U>(U, int, int)", none, "Add``1(``0, int, int)"),
+            ("Convert<TIn, TOut>(TIn)", none, "Convert``2(``0)"),
+            ("Push(T)", &pool, "Push(`0)"),
+            ("Push(`0)", &pool, "Push(`0)"),
+            ("Push(`0)", none, "Push(`0)"),
+            ("Merge<U>(T, U)", &pool, "Merge``1(`0, ``0)"),
+            ("Fill(List<T>)", &pool, "Fill(List<`0>)"),
+            ("Fill(System.Collections.Generic.List{`0})", &pool, "Fill(List<`0>)"),
+            ("Map(Dictionary<string, List<T>>)", &pool, "Map(Dictionary<string, List<`0>>)"),
+            ("Swap(ref int, out int, in System.Int32)", none, "Swap(ref int, ref int, ref int)"),
+            ("Swap(System.Int32@, System.Int32@, System.Int32)", none, "Swap(ref int, ref int, int)"),
+            ("Sum(params int[], int?)", none, "Sum(int[], int?)"),
+            ("Attach(MyGame.Outer+Inner, T[,])", &pool, "Attach(Inner, `0[,])"),
+        ];
+        for (member, type_parameters, expected) in cases {
+            assert_eq!(canonical_member_name(member, type_parameters), expected, "{}", member);
+        }
+    }
+
+    #[test]
+    fn test_split_type_and_member() {
+        let cases = [
+            ("MyGame.Player.Jump()", Some(("MyGame.Player", "Jump()"))),
+            ("MyGame.Player.Move(UnityEngine.Vector3)", Some(("MyGame.Player", "Move(UnityEngine.Vector3)"))),
+            ("MyGame.Pool<MyGame.Item>.Push(MyGame.Item)", Some(("MyGame.Pool<MyGame.Item>", "Push(MyGame.Item)"))),
+            ("MyGame.Outer+Inner", Some(("MyGame.Outer", "Inner"))),
+            ("MyGame.Pool{T}.Add{U}(T, U)", Some(("MyGame.Pool{T}", "Add{U}(T, U)"))),
+            ("Player", None),
+        ];
+        for (symbol, expected) in cases {
+            assert_eq!(split_type_and_member(symbol), expected, "{}", symbol);
+        }
+
+        assert_eq!(member_base_name("GetGenericValue<T>(T)"), "GetGenericValue");
+        assert_eq!(member_base_name("Add``1(``0)"), "Add");
+        assert_eq!(type_parameter_names("MyGame.Cache<TKey, TValue>"), names(&["TKey", "TValue"]));
+        assert!(type_parameter_names("MyGame.Pool`1").is_empty());
+    }
+}
@@ -0,0 +1,544 @@
+//! Unity Messages
+//!
+//! Unity calls methods with certain names on scripts, e.g. `Awake` or `OnCollisionEnter`, without them being
+//! declared by a base class. C# tooling doesn't know about them, so editors without Unity support can't
+//! complete them. Which messages a class gets depends on what it derives from, e.g. `MonoBehaviour` or
+//! `EditorWindow`, see [`MessageType::GetUnityMessages`].
+//!
+//! Summaries of the messages come from the compiled documentation when it documents them, e.g. a base class
+//! of the project that declares `Awake`, the summaries here are the fallback.
+//!
+//! [`MessageType::GetUnityMessages`]: crate::protocol::MessageType::GetUnityMessages
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use tree_sitter::{Node, Parser, Point};
+
+use crate::cs::compile_utils::line_column_to_point;
+use crate::cs::constants::*;
+use crate::protocol::{UnityMessageInfo, UnityMessagesRequest, UnityMessagesResponse};
+use crate::unity_project_manager::DEFAULT_DOCS_VERSION;
+
+/// Base classes are followed at most this deep within a source file
+const MAX_BASE_DEPTH: usize = 8;
+
+/// A method Unity calls on scripts
+#[derive(Debug)]
+pub struct UnityMessage {
+    pub name: &'static str,
+    /// Declaration of the method without body, e.g. `void OnCollisionEnter(Collision collision)`
+    pub signature: &'static str,
+    /// Summary used when the compiled documentation doesn't document the message
+    pub summary: &'static str,
+}
+
+/// A Unity class scripts derive from and the messages it adds
+struct UnityBaseType {
+    name: &'static str,
+    namespace: &'static str,
+    /// Base class with messages, its messages are also sent to this class
+    base: Option<&'static str>,
+    messages: &'static [UnityMessage],
+}
+
+const fn message(name: &'static str, signature: &'static str, summary: &'static str) -> UnityMessage {
+    UnityMessage { name, signature, summary }
+}
+
+const SCRIPTABLE_OBJECT_MESSAGES: &[UnityMessage] = &[
+    message("Awake", "void Awake()", "Called when the instance is created or loaded."),
+    message("OnEnable", "void OnEnable()", "Called when the object is loaded or enabled."),
+    message("OnDisable", "void OnDisable()", "Called when the object goes out of scope or is disabled."),
+    message("OnDestroy", "void OnDestroy()", "Called when the object is destroyed."),
+    message(
+        "OnValidate",
+        "void OnValidate()",
+        "Called in the Editor when the script is loaded or a value changes in the Inspector.",
+    ),
+    message("Reset", "void Reset()", "Called in the Editor when the object is created or its Reset command is used."),
+];
+
+const MONO_BEHAVIOUR_MESSAGES: &[UnityMessage] = &[
+    message("Awake", "void Awake()", "Called when the script instance is being loaded."),
+    message("Start", "void Start()", "Called on the frame the script is enabled, before any of the Update methods."),
+    message("Update", "void Update()", "Called every frame while the behaviour is enabled."),
+    message("FixedUpdate", "void FixedUpdate()", "Called every fixed frame-rate frame, use it for physics."),
+    message("LateUpdate", "void LateUpdate()", "Called every frame after all Update methods have been called."),
+    message("OnEnable", "void OnEnable()", "Called when the object becomes enabled and active."),
+    message("OnDisable", "void OnDisable()", "Called when the behaviour becomes disabled or inactive."),
+    message("OnDestroy", "void OnDestroy()", "Called when the behaviour is destroyed."),
+    message(
+        "OnValidate",
+        "void OnValidate()",
+        "Called in the Editor when the script is loaded or a value changes in the Inspector.",
+    ),
+    message("Reset", "void Reset()", "Called in the Editor when the component is added or its Reset command is used."),
+    message(
+        "OnApplicationFocus",
+        "void OnApplicationFocus(bool hasFocus)",
+        "Called when the application gains or loses focus.",
+    ),
+    message(
+        "OnApplicationPause",
+        "void OnApplicationPause(bool pauseStatus)",
+        "Called when the application pauses or resumes.",
+    ),
+    message("OnApplicationQuit", "void OnApplicationQuit()", "Called before the application quits."),
+    message(
+        "OnCollisionEnter",
+        "void OnCollisionEnter(Collision collision)",
+        "Called when this collider or rigidbody begins touching another rigidbody or collider.",
+    ),
+    message(
+        "OnCollisionStay",
+        "void OnCollisionStay(Collision collision)",
+        "Called once per frame for every collider or rigidbody that touches another rigidbody or collider.",
+    ),
+    message(
+        "OnCollisionExit",
+        "void OnCollisionExit(Collision collision)",
+        "Called when this collider or rigidbody stops touching another rigidbody or collider.",
+    ),
+    message(
+        "OnCollisionEnter2D",
+        "void OnCollisionEnter2D(Collision2D collision)",
+        "Called when a 2D collider of this object begins touching another 2D collider.",
+    ),
+    message(
+        "OnCollisionStay2D",
+        "void OnCollisionStay2D(Collision2D collision)",
+        "Called each frame a 2D collider of this object touches another 2D collider.",
+    ),
+    message(
+        "OnCollisionExit2D",
+        "void OnCollisionExit2D(Collision2D collision)",
+        "Called when a 2D collider of this object stops touching another 2D collider.",
+    ),
+    message("OnTriggerEnter", "void OnTriggerEnter(Collider other)", "Called when a collider enters the trigger."),
+    message(
+        "OnTriggerStay",
+        "void OnTriggerStay(Collider other)",
+        "Called once per physics update for every collider touching the trigger.",
+    ),
+    message("OnTriggerExit", "void OnTriggerExit(Collider other)", "Called when a collider stops touching the trigger."),
+    message(
+        "OnTriggerEnter2D",
+        "void OnTriggerEnter2D(Collider2D other)",
+        "Called when a 2D collider enters the 2D trigger.",
+    ),
+    message(
+        "OnTriggerStay2D",
+        "void OnTriggerStay2D(Collider2D other)",
+        "Called each frame a 2D collider touches the 2D trigger.",
+    ),
+    message(
+        "OnTriggerExit2D",
+        "void OnTriggerExit2D(Collider2D other)",
+        "Called when a 2D collider stops touching the 2D trigger.",
+    ),
+    message(
+        "OnControllerColliderHit",
+        "void OnControllerColliderHit(ControllerColliderHit hit)",
+        "Called when the character controller hits a collider while moving.",
+    ),
+    message(
+        "OnParticleCollision",
+        "void OnParticleCollision(GameObject other)",
+        "Called when a particle hits a collider.",
+    ),
+    message("OnJointBreak", "void OnJointBreak(float breakForce)", "Called when a joint attached to the same object breaks."),
+    message(
+        "OnBecameVisible",
+        "void OnBecameVisible()",
+        "Called when the renderer became visible by any camera.",
+    ),
+    message(
+        "OnBecameInvisible",
+        "void OnBecameInvisible()",
+        "Called when the renderer is no longer visible by any camera.",
+    ),
+    message("OnMouseDown", "void OnMouseDown()", "Called when the user presses the mouse button over the collider."),
+    message("OnMouseUp", "void OnMouseUp()", "Called when the user releases the mouse button."),
+    message("OnMouseEnter", "void OnMouseEnter()", "Called when the mouse enters the collider."),
+    message("OnMouseOver", "void OnMouseOver()", "Called every frame while the mouse is over the collider."),
+    message("OnMouseExit", "void OnMouseExit()", "Called when the mouse is not over the collider anymore."),
+    message(
+        "OnMouseDrag",
+        "void OnMouseDrag()",
+        "Called when the user clicked on the collider and is still holding down the mouse.",
+    ),
+    message("OnAnimatorMove", "void OnAnimatorMove()", "Callback for processing animation movements for modifying root motion."),
+    message("OnAnimatorIK", "void OnAnimatorIK(int layerIndex)", "Callback for setting up animation IK (inverse kinematics)."),
+    message(
+        "OnTransformParentChanged",
+        "void OnTransformParentChanged()",
+        "Called when the parent of the transform changed.",
+    ),
+    message(
+        "OnTransformChildrenChanged",
+        "void OnTransformChildrenChanged()",
+        "Called when the list of children of the transform changed.",
+    ),
+    message(
+        "OnRectTransformDimensionsChange",
+        "void OnRectTransformDimensionsChange()",
+        "Called when the dimensions of the RectTransform changed.",
+    ),
+    message("OnRenderObject", "void OnRenderObject()", "Called after the camera has rendered the scene."),
+    message("OnGUI", "void OnGUI()", "Called for rendering and handling IMGUI events."),
+    message(
+        "OnDrawGizmos",
+        "void OnDrawGizmos()",
+        "Implement to draw gizmos that are pickable and always drawn.",
+    ),
+    message(
+        "OnDrawGizmosSelected",
+        "void OnDrawGizmosSelected()",
+        "Implement to draw gizmos only if the object is selected.",
+    ),
+];
+
+const EDITOR_MESSAGES: &[UnityMessage] = &[
+    message(
+        "CreateInspectorGUI",
+        "public override VisualElement CreateInspectorGUI()",
+        "Implement to make a custom UI Toolkit inspector.",
+    ),
+    message("OnInspectorGUI", "public override void OnInspectorGUI()", "Implement to make a custom IMGUI inspector."),
+    message(
+        "OnSceneGUI",
+        "void OnSceneGUI()",
+        "Enables the Editor to handle an event in the Scene view.",
+    ),
+    message(
+        "HasPreviewGUI",
+        "public override bool HasPreviewGUI()",
+        "Implement to tell whether the inspected object has a preview.",
+    ),
+    message(
+        "OnPreviewGUI",
+        "public override void OnPreviewGUI(Rect r, GUIStyle background)",
+        "Implement to make a custom preview for the preview area of the inspector.",
+    ),
+];
+
+const EDITOR_WINDOW_MESSAGES: &[UnityMessage] = &[
+    message("CreateGUI", "void CreateGUI()", "Called when the root visual element of the window is ready to be populated."),
+    message("OnGUI", "void OnGUI()", "Implement your own IMGUI editor GUI here."),
+    message("Update", "void Update()", "Called multiple times per second on all visible windows."),
+    message("OnFocus", "void OnFocus()", "Called when the window gets keyboard focus."),
+    message("OnLostFocus", "void OnLostFocus()", "Called when the window loses keyboard focus."),
+    message("OnBecameVisible", "void OnBecameVisible()", "Called when the window becomes visible."),
+    message("OnBecameInvisible", "void OnBecameInvisible()", "Called when the window becomes invisible."),
+    message(
+        "OnHierarchyChange",
+        "void OnHierarchyChange()",
+        "Called when an object or group of objects in the hierarchy changes.",
+    ),
+    message("OnProjectChange", "void OnProjectChange()", "Called whenever the project has changed."),
+    message("OnSelectionChange", "void OnSelectionChange()", "Called whenever the selection has changed."),
+    message("OnInspectorUpdate", "void OnInspectorUpdate()", "Called 10 times per second to give the inspector a chance to update."),
+];
+
+const BASE_TYPES: &[UnityBaseType] = &[
+    UnityBaseType {
+        name: "ScriptableObject",
+        namespace: "UnityEngine",
+        base: None,
+        messages: SCRIPTABLE_OBJECT_MESSAGES,
+    },
+    UnityBaseType { name: "MonoBehaviour", namespace: "UnityEngine", base: None, messages: MONO_BEHAVIOUR_MESSAGES },
+    UnityBaseType { name: "Editor", namespace: "UnityEditor", base: Some("ScriptableObject"), messages: EDITOR_MESSAGES },
+    UnityBaseType {
+        name: "EditorWindow",
+        namespace: "UnityEditor",
+        base: Some("ScriptableObject"),
+        messages: EDITOR_WINDOW_MESSAGES,
+    },
+];
+
+/// Messages of a class derived from a Unity class, with the class that documents each
+///
+/// The name can be qualified, e.g. `UnityEngine.MonoBehaviour`. Returns `None` if the class has no messages.
+pub fn messages_of(base_type_name: &str) -> Option<Vec<(&'static str, &'static UnityMessage)>> {
+    let mut base_type = find_base_type(simple_name(base_type_name))?;
+    let mut messages: Vec<(&'static str, &'static UnityMessage)> = Vec::new();
+    loop {
+        for message in base_type.messages {
+            // the message of the derived class is the one documented for it
+            if !messages.iter().any(|(_, m)| m.name == message.name) {
+                messages.push((base_type.name, message));
+            }
+        }
+        match base_type.base.and_then(find_base_type) {
+            Some(base) => base_type = base,
+            None => return Some(messages),
+        }
+    }
+}
+
+/// Page of a message in the scripting reference, e.g. `MonoBehaviour.Awake.html`
+pub fn documentation_url(owner: &str, message: &UnityMessage, docs_version: Option<&str>) -> String {
+    format!(
+        "https://docs.unity3d.com/{}/Documentation/ScriptReference/{}.{}.html",
+        docs_version.unwrap_or(DEFAULT_DOCS_VERSION),
+        owner,
+        message.name
+    )
+}
+
+/// Fully qualified name of a Unity class with messages, e.g. `UnityEngine.MonoBehaviour`
+fn qualified_name(base_type_name: &str) -> Option<String> {
+    let base_type = find_base_type(simple_name(base_type_name))?;
+    Some(format!("{}.{}", base_type.namespace, base_type.name))
+}
+
+fn find_base_type(name: &str) -> Option<&'static UnityBaseType> {
+    BASE_TYPES.iter().find(|base_type| base_type.name == name)
+}
+
+/// Name without namespace and type arguments, e.g. `Editor` of `UnityEditor.Editor`
+fn simple_name(type_name: &str) -> &str {
+    let name = type_name.split('<').next().unwrap_or(type_name).trim();
+    name.rsplit('.').next().unwrap_or(name)
+}
+
+/// A class declared in a source file
+#[derive(Debug, PartialEq)]
+pub struct SourceClass {
+    pub name: String,
+    /// Names in the base list, as written
+    pub base_types: Vec<String>,
+    /// Names of the methods the class declares
+    pub methods: HashSet<String>,
+}
+
+fn source_class(class: Node, source: &str) -> Option<SourceClass> {
+    let text = |node: Node| node.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+    let name = text(class.child_by_field_name(NAME_FIELD)?);
+
+    let mut cursor = class.walk();
+    let base_types = class
+        .children(&mut cursor)
+        .find(|n| n.kind() == "base_list")
+        .map(|bases| {
+            let mut cursor = bases.walk();
+            bases.named_children(&mut cursor).map(text).collect()
+        })
+        .unwrap_or_default();
+
+    let methods = class
+        .child_by_field_name(BODY_FIELD)
+        .map(|body| {
+            let mut cursor = body.walk();
+            body.named_children(&mut cursor)
+                .filter(|member| member.kind() == METHOD_DECLARATION)
+                .filter_map(|member| member.child_by_field_name(NAME_FIELD).map(text))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(SourceClass { name, base_types, methods })
+}
+
+/// The innermost class declaration containing `point`
+pub fn class_at_point(root: Node, source: &str, point: Point) -> Option<SourceClass> {
+    let mut current = root.descendant_for_point_range(point, point);
+    while let Some(node) = current {
+        if node.kind() == CLASS_DECLARATION {
+            return source_class(node, source);
+        }
+        current = node.parent();
+    }
+    None
+}
+
+/// The Unity class with messages a class derives from, following base classes declared in the same source
+///
+/// Also returns the names of the classes in between, nearest first, e.g. `Enemy` for `Goblin : Enemy`.
+pub fn unity_base_type(root: Node, source: &str, class: &SourceClass) -> Option<(&'static str, Vec<String>)> {
+    let mut bases = class.base_types.clone();
+    let mut classes_between = Vec::new();
+    for _ in 0..MAX_BASE_DEPTH {
+        if let Some(base_type) = bases.iter().find_map(|base| find_base_type(simple_name(base))) {
+            return Some((base_type.name, classes_between));
+        }
+        // only the first base can be a class, the others are interfaces
+        let base = simple_name(bases.first()?).to_string();
+        bases = find_class(root, source, &base)?.base_types;
+        classes_between.push(base);
+    }
+    None
+}
+
+fn find_class(node: Node, source: &str, name: &str) -> Option<SourceClass> {
+    if node.kind() == CLASS_DECLARATION
+        && node.child_by_field_name(NAME_FIELD).and_then(|n| n.utf8_text(source.as_bytes()).ok()) == Some(name)
+    {
+        return source_class(node, source);
+    }
+    let mut cursor = node.walk();
+    let found = node.named_children(&mut cursor).find_map(|child| find_class(child, source, name));
+    found
+}
+
+/// Answer a request for the messages of a class
+///
+/// The class is either named by the request or found at a position of a source file, then the messages the
+/// class already declares are left out.
+///
+/// `summary_of` looks up the summary of a method of a type in the compiled documentation, e.g. `Awake` of
+/// `UnityEngine.MonoBehaviour`. The classes between the class and the Unity class are asked first, the
+/// summaries of [`UnityMessage`] are used for the messages it doesn't find.
+pub fn unity_messages(
+    request: &UnityMessagesRequest,
+    docs_version: Option<&str>,
+    summary_of: impl Fn(&str, &str) -> Option<String>,
+) -> UnityMessagesResponse {
+    let failure = |message: String| UnityMessagesResponse {
+        error_message: Some(message),
+        ..Default::default()
+    };
+
+    let (base_type_name, declared, classes_between) = match (&request.base_type_name, &request.source_file_path) {
+        (Some(name), _) => (name.clone(), HashSet::new(), Vec::new()),
+        (None, Some(path)) => {
+            let (class, base_type) = match class_in_file(Path::new(path), request.line, request.column) {
+                Ok(class) => class,
+                Err(message) => return failure(message),
+            };
+            let Some((base_type, classes_between)) = base_type else {
+                return failure(format!("Class {} doesn't derive from a Unity class with messages", class.name));
+            };
+            (base_type.to_string(), class.methods, classes_between)
+        }
+        (None, None) => return failure("Either BaseTypeName or SourceFilePath must be provided".to_string()),
+    };
+
+    let Some(messages) = messages_of(&base_type_name) else {
+        return failure(format!("{} is not a Unity class with messages", base_type_name));
+    };
+    UnityMessagesResponse {
+        success: true,
+        base_type_name: Some(simple_name(&base_type_name).to_string()),
+        messages: messages
+            .into_iter()
+            .filter(|(_, message)| !declared.contains(message.name))
+            .map(|(owner, message)| {
+                let summary = classes_between
+                    .iter()
+                    .cloned()
+                    .chain(qualified_name(owner))
+                    .find_map(|type_name| summary_of(&type_name, message.name))
+                    .unwrap_or_else(|| message.summary.to_string());
+                UnityMessageInfo {
+                    name: message.name.to_string(),
+                    signature: message.signature.to_string(),
+                    summary,
+                    documentation_url: documentation_url(owner, message, docs_version),
+                }
+            })
+            .collect(),
+        error_message: None,
+    }
+}
+
+/// The class at a position of a source file, and the Unity class it derives from, see [`unity_base_type`]
+fn class_in_file(path: &Path, line: u32, column: u32) -> Result<(SourceClass, Option<(&'static str, Vec<String>)>), String> {
+    let source = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_c_sharp::LANGUAGE.into())
+        .map_err(|e| format!("Failed to load the C# grammar: {}", e))?;
+    let tree = parser.parse(&source, None).ok_or_else(|| format!("Failed to parse {}", path.display()))?;
+
+    let no_class = || format!("No class at {}:{}:{}", path.display(), line + 1, column + 1);
+    let point = line_column_to_point(&source, line, column).ok_or_else(no_class)?;
+    let class = class_at_point(tree.root_node(), &source, point).ok_or_else(no_class)?;
+    let base_type = unity_base_type(tree.root_node(), &source, &class);
+    Ok((class, base_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_of_base_types() {
+        let names = |base_type: &str| -> Vec<&str> {
+            messages_of(base_type).unwrap().iter().map(|(_, message)| message.name).collect()
+        };
+
+        let behaviour = names("UnityEngine.MonoBehaviour");
+        assert!(behaviour.contains(&"OnCollisionEnter"));
+        assert!(!behaviour.contains(&"OnInspectorGUI"));
+
+        // messages of the base class are included once, documented by the derived class if it has them
+        let editor = messages_of("UnityEditor.Editor").unwrap();
+        assert!(editor.iter().any(|(owner, m)| *owner == "Editor" && m.name == "OnInspectorGUI"));
+        assert!(editor.iter().any(|(owner, m)| *owner == "ScriptableObject" && m.name == "OnEnable"));
+        let window = names("EditorWindow");
+        assert_eq!(window.iter().filter(|name| **name == "OnBecameVisible").count(), 1);
+        assert!(window.contains(&"CreateGUI") && window.contains(&"OnDestroy"));
+
+        assert!(messages_of("VisualElement").is_none());
+
+        let (owner, awake) = behaviour_message("Awake");
+        assert_eq!(
+            documentation_url(owner, awake, Some("2022.3")),
+            "https://docs.unity3d.com/2022.3/Documentation/ScriptReference/MonoBehaviour.Awake.html"
+        );
+    }
+
+    fn behaviour_message(name: &str) -> (&'static str, &'static UnityMessage) {
+        messages_of("MonoBehaviour").unwrap().into_iter().find(|(_, m)| m.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_messages_of_class_in_source() {
+        let source = "using UnityEngine;\n\
+            public abstract class Enemy : MonoBehaviour, IDamageable { }\n\
+            public class Goblin : Enemy\n\
+            {\n    void Awake() { }\n    void Attack() { }\n}\n\
+            public class Plain { }\n";
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("Goblin.cs");
+        std::fs::write(&path, source).unwrap();
+        let request = |line: u32| UnityMessagesRequest {
+            base_type_name: None,
+            source_file_path: Some(path.to_string_lossy().to_string()),
+            line,
+            column: 5,
+        };
+
+        let response = unity_messages(&request(5), None, |_, _| None);
+        assert!(response.success, "{:?}", response.error_message);
+        assert_eq!(response.base_type_name.as_deref(), Some("MonoBehaviour"));
+        let names: Vec<&str> = response.messages.iter().map(|m| m.name.as_str()).collect();
+        assert!(names.contains(&"Start"));
+        // already declared by the class
+        assert!(!names.contains(&"Awake"));
+
+        let response = unity_messages(&request(7), None, |_, _| None);
+        assert!(!response.success);
+        assert_eq!(
+            response.error_message.as_deref(),
+            Some("Class Plain doesn't derive from a Unity class with messages")
+        );
+
+        let by_name = UnityMessagesRequest { base_type_name: Some("ScriptableObject".to_string()), ..request(0) };
+        assert_eq!(unity_messages(&by_name, None, |_, _| None).messages.len(), SCRIPTABLE_OBJECT_MESSAGES.len());
+
+        // summaries of the compiled documentation, the classes in between first, then the Unity class
+        let response = unity_messages(&request(5), None, |type_name, method_name| match (type_name, method_name) {
+            ("Enemy", "Start") => Some("Starts the enemy".to_string()),
+            ("UnityEngine.MonoBehaviour", "Start" | "Update") => Some(format!("{} of the engine", method_name)),
+            _ => None,
+        });
+        let summary = |name: &str| response.messages.iter().find(|m| m.name == name).unwrap().summary.as_str();
+        assert_eq!(summary("Start"), "Starts the enemy");
+        assert_eq!(summary("Update"), "Update of the engine");
+        assert_eq!(summary("FixedUpdate"), behaviour_message("FixedUpdate").1.summary);
+    }
+}
@@ -195,6 +195,28 @@ fn extract_tag_content(xml: &str, tag_name: &str) -> Option<String> {
     None
 }
 
+/// The `<summary>` of XML documentation as markdown, `None` without one
+///
+/// `<see cref="..."/>` and `<c>` become code, `<para>` starts a paragraph, and other tags are removed.
+pub fn summary_to_markdown(xml_doc: &str) -> Option<String> {
+    let summary = extract_tag_content(xml_doc, "summary")?;
+
+    let see = Regex::new(r#"<see\s+(?:cref|langword|href)="(?:[A-Z]:)?([^"]*)"\s*/>"#).expect("Failed to compile see regex");
+    let text = see.replace_all(&summary, |captures: &regex::Captures| format!("`{}`", &captures[1]));
+    let text = text.replace("<c>", "`").replace("</c>", "`").replace("<para>", "\n\n").replace("</para>", "\n\n");
+    let tags = Regex::new(r"<[^>]+>").expect("Failed to compile tag regex");
+    let text = tags.replace_all(&text, "");
+
+    // the source indentation and line breaks are not meaningful, paragraphs are
+    let paragraphs: Vec<String> = text
+        .split("\n\n")
+        .map(|paragraph| paragraph.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect();
+    let markdown = paragraphs.join("\n\n").replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&");
+    (!markdown.is_empty()).then_some(markdown)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -276,4 +298,15 @@ mod tests {
         let add4_result = merge_xml_docs(add4_original, add5_target).unwrap();
         assert_eq!(add4_result, add5_target);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_summary_to_markdown() {
+        let xml = "<summary>\n A bar that shows the <see cref=\"T:MyGame.Player\"/> health,\n use <c>value</c> to set it.\n <para>Values &lt; 0 are clamped.</para>\n </summary>\n<remarks>Not shown</remarks>";
+        assert_eq!(
+            summary_to_markdown(xml).as_deref(),
+            Some("A bar that shows the `MyGame.Player` health, use `value` to set it.\n\nValues < 0 are clamped.")
+        );
+        assert_eq!(summary_to_markdown("<remarks>No summary</remarks>"), None);
+        assert_eq!(summary_to_markdown("<summary> </summary>"), None);
+    }
+}
@@ -1,10 +1,17 @@
-use std::path::Path;
-use std::sync::{atomic::{AtomicU64, Ordering}, Arc};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{atomic::{AtomicU64, Ordering}, Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 use notify::{Watcher, RecursiveMode, Event as NotifyEvent, EventKind};
 use std::sync::mpsc;
 use thiserror::Error;
 
+/// Number of files changed at once from which caches are rebuilt in one pass instead of per file
+///
+/// Switching branches or pulling changes thousands of files within a second, looking up what each of them
+/// affects is then slower than starting over.
+pub const BULK_CHANGE_THRESHOLD: usize = 200;
+
 /// Errors that can occur during directory change monitoring
 #[derive(Error, Debug)]
 pub enum DirChangedError {
@@ -140,3 +147,59 @@ impl DirChanged {
         Ok((watcher, rx))
     }
 }
+
+/// Collects the paths of changed files under watched directories
+///
+/// Unlike [`DirChanged`] this keeps which files changed, so caches can invalidate only the affected entries.
+#[derive(Debug)]
+pub struct FileChanges {
+    /// Paths changed since the last [`FileChanges::take_changes`]
+    changed: Arc<Mutex<HashSet<PathBuf>>>,
+    watcher: notify::RecommendedWatcher,
+}
+
+impl FileChanges {
+    /// Creates a watcher for files with one of the given extensions (e.g. `["cs", "csproj"]`)
+    ///
+    /// Nothing is watched until [`FileChanges::watch`] is called.
+    pub fn new(extensions: &[&str]) -> Result<Self, DirChangedError> {
+        let changed: Arc<Mutex<HashSet<PathBuf>>> = Arc::new(Mutex::new(HashSet::new()));
+        let extensions: Vec<String> = extensions.iter().map(|s| s.to_string()).collect();
+
+        let changed_clone = changed.clone();
+        let watcher = notify::recommended_watcher(move |res: Result<NotifyEvent, notify::Error>| {
+            let Ok(event) = res else {
+                return;
+            };
+            if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+                return;
+            }
+
+            let relevant = event.paths.into_iter().filter(|path| {
+                path.extension()
+                    .and_then(|s| s.to_str())
+                    .is_some_and(|ext| extensions.iter().any(|e| e == ext))
+            });
+            if let Ok(mut changed) = changed_clone.lock() {
+                changed.extend(relevant);
+            }
+        })?;
+
+        Ok(Self { changed, watcher })
+    }
+
+    /// Start watching a directory
+    pub fn watch(&mut self, directory: &Path, recursive: bool) -> Result<(), DirChangedError> {
+        let mode = if recursive { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive };
+        self.watcher.watch(directory, mode)?;
+        Ok(())
+    }
+
+    /// Take the paths changed since the last call
+    pub fn take_changes(&self) -> Vec<PathBuf> {
+        match self.changed.lock() {
+            Ok(mut changed) => changed.drain().collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Following the Unity Editor log
+//!
+//! Unity writes what it does to its Editor log, e.g. script compilation and asset imports. [`EditorLogReader`]
+//! reads the lines added since it last read, so the log can be followed while Unity runs.
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Most bytes read from the log in one read, the log can be huge when we first read it
+const MAX_READ_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Lines added to the log since the previous read
+#[derive(Debug, Default)]
+pub struct EditorLogLines {
+    /// The log got shorter, Unity was restarted and the lines are read from the start of the new log
+    pub restarted: bool,
+    pub lines: Vec<String>,
+}
+
+/// Reads the Editor log as it grows
+pub struct EditorLogReader {
+    log_path: PathBuf,
+    /// How far the log was read
+    offset: u64,
+    /// Incomplete last line of the previous read
+    partial_line: String,
+}
+
+impl EditorLogReader {
+    pub fn new(log_path: PathBuf) -> Self {
+        Self {
+            log_path,
+            offset: 0,
+            partial_line: String::new(),
+        }
+    }
+
+    pub fn log_path(&self) -> &Path {
+        &self.log_path
+    }
+
+    /// Skip what the log has now, the next read only returns lines written after this
+    pub fn skip_to_end(&mut self) {
+        self.offset = std::fs::metadata(&self.log_path).map(|m| m.len()).unwrap_or(0);
+        self.partial_line.clear();
+    }
+
+    /// Complete lines written since the previous read, an incomplete last line is returned once it's complete
+    ///
+    /// Only the last [`MAX_READ_SIZE`] bytes are read if more were written.
+    pub fn read_lines(&mut self) -> io::Result<EditorLogLines> {
+        let mut file = File::open(&self.log_path)?;
+        let len = file.metadata()?.len();
+
+        let restarted = len < self.offset;
+        if restarted {
+            self.offset = 0;
+            self.partial_line.clear();
+        }
+
+        if len - self.offset > MAX_READ_SIZE {
+            self.offset = len - MAX_READ_SIZE;
+            self.partial_line.clear();
+        }
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut bytes = Vec::new();
+        file.take(len - self.offset).read_to_end(&mut bytes)?;
+        self.offset += bytes.len() as u64;
+
+        let content = std::mem::take(&mut self.partial_line) + &String::from_utf8_lossy(&bytes);
+        let (complete, partial) = match content.rfind('\n') {
+            Some(end) => content.split_at(end + 1),
+            None => ("", content.as_str()),
+        };
+        let lines = complete.lines().map(str::to_string).collect();
+        self.partial_line = partial.to_string();
+
+        Ok(EditorLogLines { restarted, lines })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn append(path: &Path, text: &str) {
+        let mut file = OpenOptions::new().create(true).append(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_read_lines() {
+        let dir = TempDir::new().unwrap();
+        let log_path = dir.path().join("Editor.log");
+        let mut reader = EditorLogReader::new(log_path.clone());
+        assert_eq!(reader.read_lines().unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        append(&log_path, "old line\n");
+        reader.skip_to_end();
+        append(&log_path, "first\r\nsecond");
+        assert_eq!(reader.read_lines().unwrap().lines, vec!["first"]);
+        append(&log_path, " line\n");
+        assert_eq!(reader.read_lines().unwrap().lines, vec!["second line"]);
+        assert!(reader.read_lines().unwrap().lines.is_empty());
+
+        std::fs::write(&log_path, "new\n").unwrap();
+        let new = reader.read_lines().unwrap();
+        assert!(new.restarted);
+        assert_eq!(new.lines, vec!["new"]);
+    }
+}
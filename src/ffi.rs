@@ -0,0 +1,394 @@
+//! C API
+//!
+//! With the `ffi` feature, the library can be built as a native library that a Unity Editor plugin loads
+//! with `DllImport`, so editor features call into the USS engine in-process instead of over UDP:
+//!
+//! ```text
+//! cargo rustc --lib --release --features ffi --crate-type cdylib
+//! ```
+//!
+//! The documentation of C# symbols is compiled on the first request for an assembly, the compiled
+//! documentation is kept for later requests of the same project.
+//!
+//! Every function takes a JSON request as a null-terminated UTF-8 string and returns a JSON response, that
+//! must be freed with [`free_uss_string`]. A request that can't be handled returns `{"error": "..."}`. See
+//! `docs/NativeLibrary.md` for the requests and responses.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::catch_unwind;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Diagnostic, Range};
+use url::Url;
+
+use crate::cs::docs_manager::CsDocsManager;
+use crate::unity_project_manager::DEFAULT_DOCS_VERSION;
+use crate::uss::analyzer::UssAnalyzer;
+use crate::uss::definitions::UssDefinitions;
+use crate::uss::url_references::UrlReferenceKind;
+
+/// Documentation managers by project root, they keep the compiled documentation between requests
+static DOCS_MANAGERS: LazyLock<Mutex<HashMap<PathBuf, CsDocsManager>>> = LazyLock::new(Default::default);
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AnalyzeRequest {
+    text: String,
+    /// Unity version to check compatibility with, e.g. `6000.0`
+    #[serde(default)]
+    unity_version: Option<String>,
+    /// URL of the style sheet, e.g. `project:///Assets/UI/Main.uss`, relative URLs are resolved against it
+    #[serde(default)]
+    source_url: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AnalyzeResponse {
+    diagnostics: Vec<Diagnostic>,
+    asset_references: Vec<AssetReference>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AssetReference {
+    kind: UrlReferenceKind,
+    /// The reference as written
+    text: String,
+    /// The URL it resolves to
+    url: String,
+    range: Range,
+}
+
+#[derive(Debug, Deserialize)]
+struct FormatRequest {
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+struct FormatResponse {
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CsDocsRequest {
+    /// Root of the Unity project
+    project_path: String,
+    /// Full symbol name including namespace and type, methods include parameter types
+    symbol_name: String,
+    /// Assembly to search, required without `source_file_path`
+    #[serde(default)]
+    assembly_name: Option<String>,
+    /// Source file of user code, the symbol is searched in the assembly that compiles it
+    #[serde(default)]
+    source_file_path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CsDocsResponse {
+    /// XML documentation, empty for engine symbols which are documented online
+    documentation: String,
+    found_symbol_name: String,
+    inherited_from_symbol_name: Option<String>,
+    source_file_path: Option<String>,
+    /// 0-based line of the symbol's name in the source file
+    line: Option<u32>,
+    /// Scripting Reference page of engine symbols
+    documentation_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UssDocsRequest {
+    /// A property, `:pseudo-class`, `function()`, keyword or unit
+    symbol: String,
+    /// Property a keyword is a value of, to document what the keyword means for it
+    #[serde(default)]
+    property: Option<String>,
+    /// Unity version of the documentation links, e.g. `2022.3`
+    #[serde(default)]
+    unity_version: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct UssDocsResponse {
+    /// Markdown documentation, `None` if the symbol isn't known
+    documentation: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// Analyze a style sheet, returns its diagnostics and the assets it references
+///
+/// Request: `{"text": "...", "unityVersion": "6000.0", "sourceUrl": "project:///Assets/UI/Main.uss"}`, only
+/// `text` is required.
+///
+/// # Safety
+///
+/// `request` must be null or a valid null-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn analyze_uss(request: *const c_char) -> *mut c_char {
+    // SAFETY: guaranteed by the caller
+    unsafe { call(request, analyze) }
+}
+
+/// Format a style sheet
+///
+/// Request: `{"text": "..."}`, returns `{"text": "..."}` with the formatted style sheet, or an error if it
+/// has syntax errors.
+///
+/// # Safety
+///
+/// `request` must be null or a valid null-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn format_uss(request: *const c_char) -> *mut c_char {
+    // SAFETY: guaranteed by the caller
+    unsafe { call(request, format) }
+}
+
+/// Documentation of a C# symbol of a Unity project, like [`MessageType::GetSymbolDocs`]
+///
+/// Request: `{"projectPath": "...", "symbolName": "MyGame.Player.Jump(float)", "assemblyName": "Assembly-CSharp"}`,
+/// `assemblyName` or `sourceFilePath` is required. Compiles the documentation of the assembly if it isn't yet.
+///
+/// # Safety
+///
+/// `request` must be null or a valid null-terminated string.
+///
+/// [`MessageType::GetSymbolDocs`]: crate::protocol::MessageType::GetSymbolDocs
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn get_docs_for_symbol(request: *const c_char) -> *mut c_char {
+    // SAFETY: guaranteed by the caller
+    unsafe { call(request, cs_docs_for_symbol) }
+}
+
+/// Markdown documentation of a USS property, pseudo-class, function, keyword or unit, as shown by hover
+///
+/// Request: `{"symbol": "flex-direction", "property": "...", "unityVersion": "2022.3"}`, only `symbol` is
+/// required. Returns `{"documentation": null}` if the symbol isn't known.
+///
+/// # Safety
+///
+/// `request` must be null or a valid null-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn get_uss_docs_for_symbol(request: *const c_char) -> *mut c_char {
+    // SAFETY: guaranteed by the caller
+    unsafe { call(request, uss_docs_for_symbol) }
+}
+
+/// Free a string returned by the functions of this module
+///
+/// # Safety
+///
+/// `response` must be null or a string returned by this module that wasn't freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn free_uss_string(response: *mut c_char) {
+    if !response.is_null() {
+        // SAFETY: the string was created with `CString::into_raw`
+        drop(unsafe { CString::from_raw(response) });
+    }
+}
+
+/// Handle a request, panics are returned as errors, they must not unwind into the caller
+unsafe fn call(request: *const c_char, handler: fn(&str) -> Result<String, String>) -> *mut c_char {
+    let response = if request.is_null() {
+        Err("The request is null".to_string())
+    } else {
+        // SAFETY: guaranteed by the caller
+        match unsafe { CStr::from_ptr(request) }.to_str() {
+            Ok(request) => catch_unwind(|| handler(request)).unwrap_or_else(|_| Err("Internal error".to_string())),
+            Err(_) => Err("The request is not valid UTF-8".to_string()),
+        }
+    };
+    let json = response.unwrap_or_else(|error| {
+        serde_json::to_string(&ErrorResponse { error }).unwrap_or_default()
+    });
+    // JSON escapes control characters, so it has no null bytes
+    CString::new(json).unwrap_or_default().into_raw()
+}
+
+fn analyze(request: &str) -> Result<String, String> {
+    let request: AnalyzeRequest = parse_request(request)?;
+    let source_url = match &request.source_url {
+        Some(url) => Some(Url::parse(url).map_err(|e| format!("Invalid source URL: {}", e))?),
+        None => None,
+    };
+
+    let mut analyzer = UssAnalyzer::new();
+    if let Some(version) = &request.unity_version {
+        analyzer = analyzer.with_unity_version(version);
+    }
+    let result = analyzer
+        .analyze_with_source_url(&request.text, source_url.as_ref())
+        .ok_or("Failed to parse the style sheet")?;
+
+    let asset_references = result
+        .asset_references
+        .into_iter()
+        .map(|reference| AssetReference {
+            kind: reference.kind,
+            text: reference.text,
+            url: reference.url.to_string(),
+            range: reference.range,
+        })
+        .collect();
+    to_json(&AnalyzeResponse { diagnostics: result.diagnostics, asset_references })
+}
+
+fn format(request: &str) -> Result<String, String> {
+    let request: FormatRequest = parse_request(request)?;
+    let text = UssAnalyzer::new().format(&request.text)?;
+    to_json(&FormatResponse { text })
+}
+
+fn cs_docs_for_symbol(request: &str) -> Result<String, String> {
+    let request: CsDocsRequest = parse_request(request)?;
+    if request.assembly_name.is_none() && request.source_file_path.is_none() {
+        return Err("Either assemblyName or sourceFilePath must be provided".to_string());
+    }
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("Failed to start the runtime: {}", e))?;
+
+    // a request that panicked poisons the lock, later requests still use the manager
+    let mut managers = DOCS_MANAGERS.lock().unwrap_or_else(|e| e.into_inner());
+    let manager = match managers.entry(PathBuf::from(&request.project_path)) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => {
+            let manager = CsDocsManager::new(entry.key().clone()).map_err(|e| e.to_string())?;
+            entry.insert(manager)
+        }
+    };
+    let source_file_path = request.source_file_path.map(PathBuf::from);
+    let docs = runtime
+        .block_on(manager.get_docs_for_symbol(
+            &request.symbol_name,
+            request.assembly_name.as_deref(),
+            source_file_path.as_deref(),
+        ))
+        .map_err(|e| e.to_string())?;
+
+    to_json(&CsDocsResponse {
+        found_symbol_name: docs.symbol_name(),
+        inherited_from_symbol_name: docs.inherited_from_symbol_name(),
+        documentation: docs.xml_doc,
+        source_file_path: docs.source_file_path.map(|path| path.to_string_lossy().to_string()),
+        line: docs.line,
+        documentation_url: docs.documentation_url,
+    })
+}
+
+fn uss_docs_for_symbol(request: &str) -> Result<String, String> {
+    let request: UssDocsRequest = parse_request(request)?;
+    let version = request.unity_version.as_deref().unwrap_or(DEFAULT_DOCS_VERSION);
+    let documentation = symbol_documentation(UssDefinitions::shared(), &request.symbol, request.property.as_deref(), version);
+    to_json(&UssDocsResponse { documentation })
+}
+
+/// Documentation of a symbol, names that are both a property and a keyword or function are documented as the property
+fn symbol_documentation(definitions: &UssDefinitions, symbol: &str, property: Option<&str>, unity_version: &str) -> Option<String> {
+    let symbol = symbol.trim();
+    if let Some(pseudo_class) = symbol.strip_prefix(':') {
+        return Some(definitions.get_pseudo_class_info(pseudo_class)?.create_hover_documentation(unity_version));
+    }
+    if let Some(function) = symbol.strip_suffix("()") {
+        return Some(definitions.get_function_info(function)?.create_documentation());
+    }
+
+    if let Some(info) = definitions.get_property_info(symbol) {
+        return Some(info.create_documentation(symbol, unity_version));
+    }
+    if let Some(info) = definitions.get_function_info(symbol) {
+        return Some(info.create_documentation());
+    }
+    if let Some(property) = property {
+        if let Some(content) = definitions.keyword_documentation(symbol, property) {
+            return Some(content);
+        }
+    }
+    if let Some(info) = definitions.get_keyword_info(symbol) {
+        return Some(info.create_documentation(None));
+    }
+    Some(definitions.get_unit_info(symbol)?.create_documentation())
+}
+
+fn parse_request<'a, T: Deserialize<'a>>(request: &'a str) -> Result<T, String> {
+    serde_json::from_str(request).map_err(|e| format!("Invalid request: {}", e))
+}
+
+fn to_json<T: Serialize>(response: &T) -> Result<String, String> {
+    serde_json::to_string(response).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(function: unsafe extern "C" fn(*const c_char) -> *mut c_char, request: &str) -> serde_json::Value {
+        let request = CString::new(request).unwrap();
+        unsafe {
+            let response = function(request.as_ptr());
+            let json = CStr::from_ptr(response).to_str().unwrap().to_string();
+            free_uss_string(response);
+            serde_json::from_str(&json).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_analyze_uss() {
+        let response = request(
+            analyze_uss,
+            r#"{"text": ".title { colr: red; background-image: url(\"star.png\"); }", "sourceUrl": "project:///Assets/UI/Main.uss"}"#,
+        );
+        assert_eq!(response["diagnostics"].as_array().unwrap().len(), 1);
+        assert_eq!(response["assetReferences"][0]["url"], "project:///Assets/UI/star.png");
+        assert_eq!(response["assetReferences"][0]["kind"], "url");
+
+        assert!(request(analyze_uss, "{}")["error"].as_str().unwrap().starts_with("Invalid request"));
+        assert!(request(analyze_uss, r#"{"text": "", "sourceUrl": "Main.uss"}"#)["error"].is_string());
+        let response = unsafe { analyze_uss(std::ptr::null()) };
+        assert!(unsafe { CStr::from_ptr(response) }.to_str().unwrap().contains("null"));
+        unsafe { free_uss_string(response) };
+    }
+
+    #[test]
+    fn test_get_docs_for_symbol() {
+        let project_path = serde_json::to_string(&crate::test_utils::get_unity_project_root()).unwrap();
+        let response = request(
+            get_docs_for_symbol,
+            &format!(
+                r#"{{"projectPath": {}, "symbolName": "UnityEngine.Transform.position", "assemblyName": "UnityEngine.CoreModule"}}"#,
+                project_path
+            ),
+        );
+        assert_eq!(response["foundSymbolName"], "UnityEngine.Transform.position");
+        assert!(response["documentationUrl"].as_str().unwrap().ends_with("/ScriptReference/Transform-position.html"));
+
+        let response = request(get_docs_for_symbol, &format!(r#"{{"projectPath": {}, "symbolName": "TestClass"}}"#, project_path));
+        assert!(response["error"].as_str().unwrap().contains("assemblyName"));
+    }
+
+    #[test]
+    fn test_get_uss_docs_for_symbol() {
+        let docs = |symbol: &str| request(get_uss_docs_for_symbol, &format!(r#"{{"symbol": "{}"}}"#, symbol))["documentation"].clone();
+
+        assert!(docs("flex-direction").as_str().unwrap().contains("flex-direction"));
+        assert!(docs(":hover").as_str().unwrap().starts_with("### Pseudo Class :hover"));
+        assert!(docs("rgb()").as_str().unwrap().starts_with("### Function rgb()"));
+        assert!(docs("px").as_str().unwrap().starts_with("### Unit px"));
+        assert!(docs("unknown-symbol").is_null());
+
+        let response = request(get_uss_docs_for_symbol, r#"{"symbol": "row", "property": "flex-direction"}"#);
+        assert!(response["documentation"].as_str().unwrap().contains("**Property `flex-direction`:**"));
+    }
+}
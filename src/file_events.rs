@@ -0,0 +1,293 @@
+//! File Change Events
+//!
+//! Clients can subscribe to changes of the files in `Assets` and `Packages` of the project, see
+//! [`MessageType::FileChanges`]. Large operations, e.g. switching branches, change thousands of files within
+//! a second, so changes are not sent as they happen. They are collected per client until no file changed for
+//! the debounce time of its subscription, and only the paths that match its patterns are kept.
+//!
+//! Events of the same path in a batch are coalesced into the one change they add up to, e.g. a file that was
+//! created and then modified is created, a file that was created and then deleted is left out.
+//!
+//! [`MessageType::FileChanges`]: crate::protocol::MessageType::FileChanges
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event as NotifyEvent, EventKind, RecursiveMode, Watcher};
+
+use crate::config::path_matches;
+use crate::dir_changed::DirChangedError;
+use crate::protocol::{FileChange, FileChangeKind, FileChangesSubscription};
+
+/// Time without changes before a batch is sent, if the subscription doesn't set one
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Range of the debounce time a subscription can set
+const MIN_DEBOUNCE: Duration = Duration::from_millis(50);
+const MAX_DEBOUNCE: Duration = Duration::from_secs(5);
+
+/// A batch is sent at the latest this long after its first change, even if files keep changing
+const MAX_BATCH_DELAY: Duration = Duration::from_secs(2);
+
+/// Changes per message, so a message stays well below the size of a UDP datagram
+pub const MAX_CHANGES_PER_MESSAGE: usize = 200;
+
+/// The change a path had in a batch after another event, `None` if they cancel out
+pub fn coalesce(previous: Option<FileChangeKind>, next: FileChangeKind) -> Option<FileChangeKind> {
+    match (previous, next) {
+        (None, next) => Some(next),
+        (Some(FileChangeKind::Created), FileChangeKind::Deleted) => None,
+        (Some(FileChangeKind::Created), _) => Some(FileChangeKind::Created),
+        (Some(_), FileChangeKind::Deleted) => Some(FileChangeKind::Deleted),
+        // deleted and created again, e.g. saved by replacing the file, is a modification
+        (Some(_), _) => Some(FileChangeKind::Modified),
+    }
+}
+
+/// File changes of a client that are not yet sent
+#[derive(Debug)]
+pub struct FileSubscription {
+    patterns: Vec<String>,
+    debounce: Duration,
+    /// Coalesced changes by path
+    pending: BTreeMap<String, FileChangeKind>,
+    first_change: Option<Instant>,
+    last_change: Option<Instant>,
+}
+
+impl FileSubscription {
+    pub fn new(request: &FileChangesSubscription) -> Self {
+        Self {
+            patterns: request.patterns.clone(),
+            debounce: request
+                .debounce_ms
+                .map_or(DEFAULT_DEBOUNCE, Duration::from_millis)
+                .clamp(MIN_DEBOUNCE, MAX_DEBOUNCE),
+            pending: BTreeMap::new(),
+            first_change: None,
+            last_change: None,
+        }
+    }
+
+    /// Add a change of a path relative to the project, ignored if it doesn't match the patterns
+    pub fn push(&mut self, path: &str, kind: FileChangeKind, now: Instant) {
+        if !self.patterns.iter().any(|pattern| path_matches(pattern, path)) {
+            return;
+        }
+        match coalesce(self.pending.get(path).copied(), kind) {
+            Some(kind) => {
+                self.pending.insert(path.to_string(), kind);
+            }
+            None => {
+                self.pending.remove(path);
+            }
+        }
+        self.first_change.get_or_insert(now);
+        self.last_change = Some(now);
+    }
+
+    /// Take the pending changes if the batch is complete, empty if it isn't or changes cancelled out
+    pub fn take_ready(&mut self, now: Instant) -> Vec<FileChange> {
+        let (Some(first_change), Some(last_change)) = (self.first_change, self.last_change) else {
+            return Vec::new();
+        };
+        if now.duration_since(last_change) < self.debounce
+            && now.duration_since(first_change) < MAX_BATCH_DELAY.max(self.debounce)
+        {
+            return Vec::new();
+        }
+
+        self.first_change = None;
+        self.last_change = None;
+        std::mem::take(&mut self.pending)
+            .into_iter()
+            .map(|(path, kind)| FileChange { path, kind })
+            .collect()
+    }
+}
+
+/// Collects the changes of files in `Assets` and `Packages` of a project
+pub struct ProjectFileWatcher {
+    /// Changes since the last [`ProjectFileWatcher::take_events`], paths are relative to the project
+    events: Arc<Mutex<Vec<(String, FileChangeKind)>>>,
+    _watcher: notify::RecommendedWatcher,
+}
+
+impl ProjectFileWatcher {
+    pub fn new(project_root: &Path) -> Result<Self, DirChangedError> {
+        // watchers can report paths with symbolic links resolved, they are made relative to the resolved root
+        let project_root = std::fs::canonicalize(project_root).unwrap_or_else(|_| project_root.to_path_buf());
+        let events: Arc<Mutex<Vec<(String, FileChangeKind)>>> = Arc::new(Mutex::new(Vec::new()));
+
+        let events_clone = events.clone();
+        let root = project_root.clone();
+        let mut watcher = notify::recommended_watcher(move |res: Result<NotifyEvent, notify::Error>| {
+            let Ok(event) = res else {
+                return;
+            };
+            let changes = event_changes(event)
+                .into_iter()
+                .filter_map(|(path, kind)| relative_path(&root, &path).map(|path| (path, kind)));
+            if let Ok(mut events) = events_clone.lock() {
+                events.extend(changes);
+            }
+        })?;
+
+        for dir in [project_root.join("Assets"), project_root.join("Packages")] {
+            if dir.is_dir() {
+                if let Err(e) = watcher.watch(&dir, RecursiveMode::Recursive) {
+                    log::warn!("Failed to watch {}: {}", dir.display(), e);
+                }
+            }
+        }
+
+        Ok(Self { events, _watcher: watcher })
+    }
+
+    /// Take the changes since the last call, in the order they happened
+    pub fn take_events(&self) -> Vec<(String, FileChangeKind)> {
+        match self.events.lock() {
+            Ok(mut events) => std::mem::take(&mut *events),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+/// Path relative to the project with `/`, e.g. `Assets/UI/Main.uss`
+pub fn relative_path(project_root: &Path, path: &Path) -> Option<String> {
+    path.strip_prefix(project_root)
+        .ok()
+        .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+}
+
+/// Changes of a watcher event, a rename is a deletion of the old path and a creation of the new one
+fn event_changes(event: NotifyEvent) -> Vec<(PathBuf, FileChangeKind)> {
+    let kind = match event.kind {
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => FileChangeKind::Created,
+        EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => FileChangeKind::Deleted,
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+            let mut paths = event.paths.into_iter();
+            let Some(from) = paths.next() else {
+                return Vec::new();
+            };
+            return std::iter::once((from, FileChangeKind::Deleted))
+                .chain(paths.map(|to| (to, FileChangeKind::Created)))
+                .collect();
+        }
+        // the platform doesn't tell which side of the rename the path is
+        EventKind::Modify(ModifyKind::Name(_)) => {
+            return event
+                .paths
+                .into_iter()
+                .map(|path| {
+                    let kind = if path.exists() { FileChangeKind::Created } else { FileChangeKind::Deleted };
+                    (path, kind)
+                })
+                .collect();
+        }
+        EventKind::Modify(_) => FileChangeKind::Modified,
+        _ => return Vec::new(),
+    };
+    event.paths.into_iter().map(|path| (path, kind)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, DataChange};
+
+    fn subscription(patterns: &[&str], debounce_ms: Option<u64>) -> FileSubscription {
+        FileSubscription::new(&FileChangesSubscription {
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            debounce_ms,
+        })
+    }
+
+    fn change(path: &str, kind: FileChangeKind) -> FileChange {
+        FileChange { path: path.to_string(), kind }
+    }
+
+    #[test]
+    fn test_coalesce() {
+        use FileChangeKind::*;
+        assert_eq!(coalesce(None, Modified), Some(Modified));
+        assert_eq!(coalesce(Some(Created), Modified), Some(Created));
+        assert_eq!(coalesce(Some(Created), Deleted), None);
+        assert_eq!(coalesce(Some(Modified), Deleted), Some(Deleted));
+        assert_eq!(coalesce(Some(Deleted), Created), Some(Modified));
+        assert_eq!(coalesce(Some(Modified), Modified), Some(Modified));
+    }
+
+    #[test]
+    fn test_batching_and_filters() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut ui = subscription(&["Assets/UI/**"], None);
+        let mut scripts = subscription(&["**/*.cs"], Some(100));
+
+        let events = [
+            ("Assets/UI/Main.uss", FileChangeKind::Modified),
+            ("Assets/UI/Temp.uss", FileChangeKind::Created),
+            ("Assets/Scripts/Player.cs", FileChangeKind::Created),
+            ("Assets/UI/Temp.uss", FileChangeKind::Modified),
+            ("Assets/Scripts/Player.cs", FileChangeKind::Modified),
+            ("Assets/UI/Temp.uss", FileChangeKind::Deleted),
+            ("Packages/com.example/Runtime/Example.cs", FileChangeKind::Deleted),
+        ];
+        for (path, kind) in events {
+            ui.push(path, kind, at(0));
+            scripts.push(path, kind, at(0));
+        }
+
+        // waits for the debounce time of each subscription
+        assert!(ui.take_ready(at(150)).is_empty());
+        assert_eq!(
+            scripts.take_ready(at(150)),
+            vec![
+                change("Assets/Scripts/Player.cs", FileChangeKind::Created),
+                change("Packages/com.example/Runtime/Example.cs", FileChangeKind::Deleted),
+            ]
+        );
+        assert_eq!(ui.take_ready(at(200)), vec![change("Assets/UI/Main.uss", FileChangeKind::Modified)]);
+        assert!(ui.take_ready(at(1000)).is_empty());
+
+        // files that keep changing are still sent after the maximum delay
+        for ms in (0..=2000).step_by(100) {
+            ui.push("Assets/UI/Main.uss", FileChangeKind::Modified, at(1000 + ms));
+            let ready = ui.take_ready(at(1000 + ms));
+            assert_eq!(ready.is_empty(), ms < 2000, "at {}ms", ms);
+        }
+    }
+
+    #[test]
+    fn test_event_changes() {
+        let path = |name: &str| PathBuf::from("/project/Assets").join(name);
+        let changes = |kind, paths: &[&str]| {
+            let event = paths.iter().fold(NotifyEvent::new(kind), |event, name| event.add_path(path(name)));
+            event_changes(event)
+        };
+
+        assert_eq!(
+            changes(EventKind::Create(CreateKind::File), &["a.uss"]),
+            vec![(path("a.uss"), FileChangeKind::Created)]
+        );
+        assert_eq!(
+            changes(EventKind::Modify(ModifyKind::Data(DataChange::Content)), &["a.uss"]),
+            vec![(path("a.uss"), FileChangeKind::Modified)]
+        );
+        assert_eq!(
+            changes(EventKind::Modify(ModifyKind::Name(RenameMode::Both)), &["a.uss", "b.uss"]),
+            vec![(path("a.uss"), FileChangeKind::Deleted), (path("b.uss"), FileChangeKind::Created)]
+        );
+        assert!(changes(EventKind::Access(notify::event::AccessKind::Any), &["a.uss"]).is_empty());
+
+        assert_eq!(
+            relative_path(Path::new("/project"), &path("UI/a.uss")),
+            Some("Assets/UI/a.uss".to_string())
+        );
+        assert_eq!(relative_path(Path::new("/other"), &path("a.uss")), None);
+    }
+}
@@ -0,0 +1,241 @@
+//! Asset Import Durations
+//!
+//! After [`MessageType::GetAssetsToImport`] the editor asks Unity to import the saved files, and the changes
+//! only show up in Unity once they are imported. Unity writes a line to its Editor log for each asset it
+//! imports, with the time the import took:
+//!
+//! ```text
+//! Start importing Assets/UI/Main.uss using Guid(3f0c...) Importer(-1,0000...)  -> (artifact id: 'b6c0...') in 0.0123 seconds
+//! ```
+//!
+//! [`ImportTracker`] follows the log while assets it was told about are waiting to be imported, and records
+//! how long it took from the request until Unity imported each of them. The latest imports are kept for
+//! [`MessageType::GetAssetImports`], slow ones are also sent to clients, so users know why changes take a
+//! while to show up. Assets Unity doesn't import within [`PENDING_TIMEOUT`] are recorded as timed out and
+//! no longer waited for, e.g. the editor has no connection to Unity and Unity imports them when it gains focus.
+//!
+//! [`MessageType::GetAssetsToImport`]: crate::protocol::MessageType::GetAssetsToImport
+//! [`MessageType::GetAssetImports`]: crate::protocol::MessageType::GetAssetImports
+
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use regex::Regex;
+
+use crate::editor_log::EditorLogReader;
+use crate::protocol::AssetImport;
+
+/// Imports that take longer from the request are slow
+pub const SLOW_IMPORT_THRESHOLD: Duration = Duration::from_secs(2);
+
+/// Time after which an asset that wasn't imported is no longer waited for
+const PENDING_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Number of imports kept
+const MAX_IMPORTS: usize = 100;
+
+/// `Start importing <path> using Guid(...) ...`, newer Unity versions end the line with `in <seconds> seconds`
+static IMPORT_LINE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^Start importing (?P<path>.+?) using Guid\(\w*\)(?:.*\bin (?P<seconds>\d+(?:\.\d+)?) seconds)?").unwrap()
+});
+
+/// Parse an import line of the Editor log, returns the asset path and the time the import took
+pub fn parse_import_line(line: &str) -> Option<(String, Option<Duration>)> {
+    let captures = IMPORT_LINE_REGEX.captures(line.trim_end())?;
+    let seconds = captures.name("seconds").and_then(|s| s.as_str().parse::<f64>().ok());
+    Some((captures["path"].to_string(), seconds.map(Duration::from_secs_f64)))
+}
+
+/// Follows the Editor log while assets are waiting to be imported, and keeps the latest imports
+pub struct ImportTracker {
+    log: EditorLogReader,
+    /// When each asset waiting to be imported was requested, by path relative to the project
+    pending: HashMap<String, Instant>,
+    /// Latest imports, oldest first
+    imports: VecDeque<AssetImport>,
+}
+
+impl ImportTracker {
+    pub fn new(log_path: PathBuf) -> Self {
+        Self {
+            log: EditorLogReader::new(log_path),
+            pending: HashMap::new(),
+            imports: VecDeque::new(),
+        }
+    }
+
+    /// Whether assets are waiting to be imported
+    pub fn is_tracking(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Wait for Unity to import the assets, `asset_paths` are relative to the project
+    ///
+    /// An asset that is already waiting is timed from this request, Unity imports its latest version.
+    pub fn track(&mut self, asset_paths: &[String], now: Instant) {
+        if asset_paths.is_empty() {
+            return;
+        }
+        // lines written before the request are imports of earlier changes
+        if self.pending.is_empty() {
+            self.log.skip_to_end();
+        }
+        for path in asset_paths {
+            self.pending.insert(path.clone(), now);
+        }
+    }
+
+    /// Read the imports Unity logged since the last update, returns the slow ones and the ones that timed out
+    ///
+    /// Reads the Editor log, async code should call it with `spawn_blocking`.
+    pub fn update(&mut self, now: Instant) -> Vec<AssetImport> {
+        if self.pending.is_empty() {
+            return Vec::new();
+        }
+
+        let mut slow = Vec::new();
+        match self.log.read_lines() {
+            Ok(new) => {
+                for (path, import_time) in new.lines.iter().filter_map(|line| parse_import_line(line)) {
+                    let Some(requested) = self.pending.remove(&path) else {
+                        continue;
+                    };
+                    let import = self.record(path, now.duration_since(requested), import_time, false);
+                    if import.is_slow {
+                        slow.push(import);
+                    }
+                }
+            }
+            Err(e) => {
+                if e.kind() != io::ErrorKind::NotFound {
+                    log::warn!("Failed to read Editor log {}: {}", self.log.log_path().display(), e);
+                }
+            }
+        }
+
+        let mut timed_out: Vec<(String, Instant)> = self
+            .pending
+            .iter()
+            .filter(|(_, requested)| now.duration_since(**requested) >= PENDING_TIMEOUT)
+            .map(|(path, requested)| (path.clone(), *requested))
+            .collect();
+        timed_out.sort();
+        for (path, requested) in timed_out {
+            log::debug!("{} was not imported within {:?}, stop waiting", path, PENDING_TIMEOUT);
+            self.pending.remove(&path);
+            slow.push(self.record(path, now.duration_since(requested), None, true));
+        }
+        slow
+    }
+
+    /// Latest imports, oldest first
+    pub fn imports(&self) -> impl Iterator<Item = &AssetImport> {
+        self.imports.iter()
+    }
+
+    fn record(&mut self, asset_path: String, duration: Duration, import_time: Option<Duration>, timed_out: bool) -> AssetImport {
+        let import = AssetImport {
+            asset_path,
+            duration_ms: duration.as_millis() as u64,
+            import_ms: import_time.map(|time| time.as_millis() as u64),
+            is_slow: duration > SLOW_IMPORT_THRESHOLD,
+            timed_out,
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or_default(),
+        };
+        if self.imports.len() == MAX_IMPORTS {
+            self.imports.pop_front();
+        }
+        self.imports.push_back(import.clone());
+        import
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn append(path: &Path, text: &str) {
+        let mut file = OpenOptions::new().create(true).append(true).open(path).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    fn import_line(path: &str, seconds: &str) -> String {
+        format!(
+            "Start importing {} using Guid(3f0c5e1a8b7d4e2f9a6b1c0d2e3f4a5b) Importer(-1,00000000000000000000000000000000)  -> (artifact id: 'b6c0a1d2e3f4a5b6c7d8e9f0a1b2c3d4') in {} seconds\n",
+            path, seconds
+        )
+    }
+
+    #[test]
+    fn test_parse_import_line() {
+        assert_eq!(
+            parse_import_line(&import_line("Assets/UI/Main Menu.uss", "0.0123")),
+            Some(("Assets/UI/Main Menu.uss".to_string(), Some(Duration::from_secs_f64(0.0123))))
+        );
+        assert_eq!(
+            parse_import_line("Start importing Assets/UI/Main.uxml using Guid(3f0c5e1a) Importer(-1,00000000000000000000000000000000)\r"),
+            Some(("Assets/UI/Main.uxml".to_string(), None))
+        );
+        assert_eq!(parse_import_line("Asset Pipeline Refresh (id=2c4b): Total: 0.045 seconds"), None);
+    }
+
+    #[test]
+    fn test_track_imports() {
+        let dir = TempDir::new().unwrap();
+        let log_path = dir.path().join("Editor.log");
+        let mut tracker = ImportTracker::new(log_path.clone());
+        let start = Instant::now();
+
+        // imported before the request
+        append(&log_path, &import_line("Assets/UI/Main.uss", "0.01"));
+        tracker.track(&["Assets/UI/Main.uss".to_string(), "Assets/UI/Main.uxml".to_string()], start);
+        assert!(tracker.update(start).is_empty());
+        assert_eq!(tracker.imports().count(), 0);
+
+        append(&log_path, &import_line("Assets/UI/Main.uss", "0.02"));
+        append(&log_path, &import_line("Assets/Other.uss", "0.01"));
+        assert!(tracker.update(start + Duration::from_millis(300)).is_empty());
+        append(&log_path, &import_line("Assets/UI/Main.uxml", "1.5"));
+        let slow = tracker.update(start + Duration::from_secs(3));
+        assert_eq!(slow.len(), 1);
+        assert_eq!(slow[0].asset_path, "Assets/UI/Main.uxml");
+        assert_eq!(slow[0].duration_ms, 3000);
+        assert_eq!(slow[0].import_ms, Some(1500));
+        assert!(!tracker.is_tracking());
+
+        let imports: Vec<_> = tracker.imports().map(|i| (i.asset_path.as_str(), i.duration_ms, i.is_slow)).collect();
+        assert_eq!(imports, vec![("Assets/UI/Main.uss", 300, false), ("Assets/UI/Main.uxml", 3000, true)]);
+    }
+
+    #[test]
+    fn test_record_assets_not_imported() {
+        let dir = TempDir::new().unwrap();
+        let log_path = dir.path().join("Editor.log");
+        let mut tracker = ImportTracker::new(log_path.clone());
+        let start = Instant::now();
+
+        tracker.track(&["Assets/UI/Main.uss".to_string()], start);
+        assert!(tracker.update(start + Duration::from_secs(10)).is_empty());
+        assert!(tracker.is_tracking());
+        let timed_out = tracker.update(start + PENDING_TIMEOUT);
+        assert_eq!(timed_out.len(), 1);
+        assert_eq!(timed_out[0].asset_path, "Assets/UI/Main.uss");
+        assert_eq!(timed_out[0].duration_ms, PENDING_TIMEOUT.as_millis() as u64);
+        assert_eq!(timed_out[0].import_ms, None);
+        assert!(timed_out[0].is_slow && timed_out[0].timed_out);
+        assert!(!tracker.is_tracking());
+        assert_eq!(tracker.imports().count(), 1);
+
+        // an import after the timeout isn't timed
+        append(&log_path, &import_line("Assets/UI/Main.uss", "0.01"));
+        assert!(tracker.update(start + PENDING_TIMEOUT + Duration::from_secs(1)).is_empty());
+        assert_eq!(tracker.imports().count(), 1);
+    }
+}
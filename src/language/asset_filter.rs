@@ -0,0 +1,66 @@
+//! Asset Filters
+//!
+//! Properties that reference assets only accept some types of assets, e.g. `background-image` takes a
+//! texture, a sprite or a vector image, but not a style sheet. An [`AssetFilter`] tells the accepted
+//! types of assets from their file extensions.
+
+/// Types of assets accepted somewhere, by file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssetFilter {
+    /// What the assets are, e.g. `texture`, used in messages
+    pub description: &'static str,
+    /// Accepted file extensions, lower case without the dot
+    pub extensions: &'static [&'static str],
+}
+
+/// Textures, sprites (in textures or `.asset` files), vector images and render textures
+pub const IMAGE_ASSETS: AssetFilter = AssetFilter {
+    description: "texture, sprite or vector image",
+    extensions: &[
+        "png", "jpg", "jpeg", "tga", "psd", "gif", "bmp", "tif", "tiff", "exr", "hdr", "iff", "pict", "svg",
+        "rendertexture", "asset",
+    ],
+};
+
+/// Textures for `cursor`, a custom cursor is a `Texture2D`
+pub const CURSOR_ASSETS: AssetFilter = AssetFilter {
+    description: "texture",
+    extensions: &["png", "jpg", "jpeg", "tga", "psd", "gif", "bmp", "tif", "tiff", "exr", "hdr", "iff", "pict"],
+};
+
+/// Fonts, and font assets or font definitions in `.asset` files
+pub const FONT_ASSETS: AssetFilter = AssetFilter {
+    description: "font",
+    extensions: &["ttf", "otf", "ttc", "dfont", "asset"],
+};
+
+impl AssetFilter {
+    /// Whether a file name or path has one of the accepted extensions (case-insensitive)
+    pub fn accepts(&self, file_name: &str) -> bool {
+        file_extension(file_name)
+            .is_some_and(|extension| self.extensions.iter().any(|e| e.eq_ignore_ascii_case(extension)))
+    }
+}
+
+/// Extension of a file name or path, `None` without one
+pub fn file_extension(file_name: &str) -> Option<&str> {
+    let name = file_name.rsplit('/').next().unwrap_or(file_name);
+    name.rsplit_once('.').map(|(_, extension)| extension).filter(|e| !e.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepts() {
+        assert!(IMAGE_ASSETS.accepts("icon.PNG"));
+        assert!(IMAGE_ASSETS.accepts("Assets/UI/atlas.asset"));
+        assert!(!IMAGE_ASSETS.accepts("Assets/UI/main.uss"));
+        assert!(FONT_ASSETS.accepts("Assets/Fonts/Inter.otf"));
+        assert!(!FONT_ASSETS.accepts("Assets/Fonts.d/Inter"));
+        assert!(!FONT_ASSETS.accepts("readme"));
+        assert_eq!(file_extension("Assets/a.b/c"), None);
+        assert_eq!(file_extension("c.tar.gz"), Some("gz"));
+    }
+}
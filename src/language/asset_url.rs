@@ -228,6 +228,48 @@ pub fn project_url_to_relative_path(url: &Url) -> Option<String>{
 }
 
 
+/// Path of `target` written the way `original` was written in the file of `referrer`
+///
+/// Used to update references to a file that was moved: a relative path stays relative to the referrer,
+/// an absolute path stays absolute and a project scheme URL keeps its form.
+/// Returns `None` if `referrer` or `target` isn't a project URL.
+pub fn rewrite_reference_path(original: &str, referrer: &Url, target: &Url) -> Option<String> {
+    let target_path = project_url_to_relative_path(target)?;
+    if let Some(rest) = original.strip_prefix("project:") {
+        let slashes = if rest.starts_with("///") { "///" } else { "/" };
+        return Some(format!("project:{}{}", slashes, target_path));
+    }
+    if original.starts_with('/') {
+        return Some(format!("/{}", target_path));
+    }
+
+    let relative = relative_reference_path(referrer, target)?;
+    if original.starts_with("./") && !relative.starts_with("..") {
+        return Some(format!("./{}", relative));
+    }
+    Some(relative)
+}
+
+/// Path of `target` relative to the directory of `referrer`, e.g. `../Images/icon.png`
+///
+/// Returns `None` if `referrer` or `target` isn't a project URL.
+pub fn relative_reference_path(referrer: &Url, target: &Url) -> Option<String> {
+    let target_path = project_url_to_relative_path(target)?;
+    let referrer_path = project_url_to_relative_path(referrer)?;
+    let mut referrer_dir: Vec<&str> = referrer_path.split('/').collect();
+    referrer_dir.pop();
+    let target_parts: Vec<&str> = target_path.split('/').collect();
+    let common = referrer_dir
+        .iter()
+        .zip(&target_parts)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut parts = vec![".."; referrer_dir.len() - common];
+    parts.extend(&target_parts[common..]);
+    Some(parts.join("/"))
+}
+
 /// Creates a project scheme URL from normalized file path and project root path
 ///
 /// Converts a file system path to a Unity project scheme URL.
@@ -282,6 +324,8 @@ pub fn create_project_url(normalized_file_path: &std::path::Path, normalized_pro
 /// For better performance when dealing with multiple paths, consider normalizing paths
 /// once and using `create_project_url` directly.
 ///
+/// A file that doesn't exist, e.g. one that was just deleted, is normalized through its directory.
+///
 /// # Arguments
 /// * `file_path` - The absolute file system path to the file
 /// * `project_root` - The absolute path to the Unity project root directory
@@ -292,6 +336,10 @@ pub fn create_project_url(normalized_file_path: &std::path::Path, normalized_pro
 pub fn create_project_url_with_normalization(file_path: &std::path::Path, project_root: &std::path::Path) -> Result<Url, AssetValidationError> {
     // Canonicalize both paths to handle case-insensitive comparison and resolve symlinks
     let canonical_file_path = std::fs::canonicalize(file_path)
+        .or_else(|e| match (file_path.parent(), file_path.file_name()) {
+            (Some(dir), Some(name)) if !file_path.exists() => std::fs::canonicalize(dir).map(|dir| dir.join(name)),
+            _ => Err(e),
+        })
         .map_err(|e| AssetValidationError::new(format!("Failed to canonicalize file path: {}", e)))?;
     let canonical_project_root = std::fs::canonicalize(project_root)
         .map_err(|e| AssetValidationError::new(format!("Failed to canonicalize project root: {}", e)))?;
@@ -539,6 +587,10 @@ mod tests {
         assert!(result.is_ok());
         let url = result.unwrap();
         assert_eq!(url.as_str(), "project:/Assets/test.uss");
+
+        // a deleted file
+        let deleted = create_project_url_with_normalization(&assets_dir.join("deleted.uss"), project_root);
+        assert_eq!(deleted.unwrap().as_str(), "project:/Assets/deleted.uss");
         
         // On Windows, test with different case in project root path
         #[cfg(windows)]
@@ -653,4 +705,20 @@ mod tests {
         let path = project_url_to_relative_path(&Url::parse("project:///Assets/hello.txt").unwrap());
         assert_eq!(path.unwrap(), "Assets/hello.txt");
     }
+
+    #[test]
+    fn test_rewrite_reference_path() {
+        let referrer = Url::parse("project:/Assets/UI/Main/main.uss").unwrap();
+        let target = Url::parse("project:/Assets/UI/Shared/common.uss").unwrap();
+        let rewrite = |original| rewrite_reference_path(original, &referrer, &target).unwrap();
+
+        assert_eq!(rewrite("common.uss"), "../Shared/common.uss");
+        assert_eq!(rewrite("/Assets/UI/Main/common.uss"), "/Assets/UI/Shared/common.uss");
+        assert_eq!(rewrite("project:///Assets/common.uss"), "project:///Assets/UI/Shared/common.uss");
+        assert_eq!(rewrite("project:/Assets/common.uss"), "project:/Assets/UI/Shared/common.uss");
+
+        let nested = Url::parse("project:/Assets/UI/Main/Parts/common.uss").unwrap();
+        assert_eq!(rewrite_reference_path("./common.uss", &referrer, &nested).unwrap(), "./Parts/common.uss");
+        assert_eq!(rewrite_reference_path("common.uss", &referrer, &Url::parse("https://example.com/a.uss").unwrap()), None);
+    }
 }
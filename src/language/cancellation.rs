@@ -0,0 +1,113 @@
+//! Request cancellation
+//!
+//! tower-lsp handles `$/cancelRequest` by dropping the future of the cancelled request.
+//! That stops the request at its next `.await`, but not work it started on another thread
+//! (e.g. with `spawn_blocking`). Such work takes a [`CancellationToken`] and checks it regularly,
+//! the request holds a [`CancelOnDrop`] guard that cancels the token when the request is dropped.
+//!
+//! Work that stays on the runtime, e.g. the docs compilation, which awaits the read of every source
+//! file, already stops with the dropped future and doesn't need a token.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use thiserror::Error;
+
+/// Error returned by operations that stopped because they were cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("Operation was cancelled")]
+pub struct Cancelled;
+
+/// Shared flag telling an operation to stop
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// `Err(Cancelled)` if the token was cancelled, to stop an operation with `?`
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() { Err(Cancelled) } else { Ok(()) }
+    }
+
+    /// Guard that cancels the token when dropped, unless it is disarmed
+    pub fn cancel_on_drop(&self) -> CancelOnDrop {
+        CancelOnDrop {
+            token: Some(self.clone()),
+        }
+    }
+}
+
+/// Cancels its token when dropped, see [`CancellationToken::cancel_on_drop`]
+pub struct CancelOnDrop {
+    token: Option<CancellationToken>,
+}
+
+impl CancelOnDrop {
+    /// Don't cancel the token on drop, for when the operation completed
+    pub fn disarm(mut self) {
+        self.token = None;
+    }
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        if let Some(token) = &self.token {
+            token.cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_on_drop() {
+        let token = CancellationToken::new();
+        assert_eq!(token.check(), Ok(()));
+
+        token.cancel_on_drop().disarm();
+        assert!(!token.is_cancelled());
+
+        drop(token.cancel_on_drop());
+        assert_eq!(token.check(), Err(Cancelled));
+    }
+
+    #[tokio::test]
+    async fn test_dropped_request_cancels_blocking_work() {
+        let token = CancellationToken::new();
+        let (started_tx, started_rx) = std::sync::mpsc::channel();
+
+        let worker_token = token.clone();
+        let worker = std::thread::spawn(move || {
+            started_tx.send(()).unwrap();
+            while !worker_token.is_cancelled() {
+                std::thread::yield_now();
+            }
+        });
+
+        let request = async {
+            let _guard = token.cancel_on_drop();
+            std::future::pending::<()>().await;
+        };
+        started_rx.recv().unwrap();
+        // dropping the request without completing it, like tower-lsp does on cancellation
+        let _ = tokio::time::timeout(std::time::Duration::from_millis(10), request).await;
+
+        worker.join().unwrap();
+        assert!(token.is_cancelled());
+    }
+}
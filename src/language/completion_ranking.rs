@@ -0,0 +1,237 @@
+//! Completion Ranking
+//!
+//! Matches and ranks completion candidates against the text typed so far, so large
+//! candidate sets (e.g. hundreds of UXML element names) can be cut down to the most
+//! relevant items. Matches are ranked by how the query matches a candidate, best first:
+//! - prefix: `But` matches `Button`
+//! - camel hump: `PrB` and `pro-ba` match `ProgressBar` and `progress-bar`, each part of the query
+//!   is a prefix of a word of the candidate, words can be skipped except the first one
+//! - fuzzy: the characters of the query appear in order anywhere in the candidate
+//!
+//! All matching is case-insensitive.
+
+use tower_lsp::lsp_types::CompletionItem;
+
+/// How a query matches a candidate, in order of relevance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MatchKind {
+    Prefix,
+    CamelHump,
+    Fuzzy,
+}
+
+/// Match a query against a candidate, `None` if it doesn't match at all
+///
+/// An empty query matches everything as a prefix.
+pub fn match_kind(query: &str, candidate: &str) -> Option<MatchKind> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    let lower: Vec<char> = candidate.chars().flat_map(char::to_lowercase).collect();
+
+    if lower.starts_with(&query) {
+        return Some(MatchKind::Prefix);
+    }
+
+    let words = split_words(candidate);
+    if !words.is_empty() && matches_words(&strip_separators(&query), &words, true) {
+        return Some(MatchKind::CamelHump);
+    }
+
+    if is_subsequence(&query, &lower) {
+        return Some(MatchKind::Fuzzy);
+    }
+
+    None
+}
+
+/// A candidate that matched a query
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankedCandidate<T> {
+    pub kind: MatchKind,
+    pub value: T,
+}
+
+/// Rank candidates by how well they match the query, dropping the ones that don't match
+///
+/// Candidates are ordered by match kind, then shorter names first, then alphabetically,
+/// so the order is stable no matter the order of the input.
+pub fn rank<T>(
+    query: &str,
+    candidates: impl IntoIterator<Item = T>,
+    name: impl Fn(&T) -> &str,
+) -> Vec<RankedCandidate<T>> {
+    let mut ranked: Vec<RankedCandidate<T>> = candidates
+        .into_iter()
+        .filter_map(|value| {
+            let kind = match_kind(query, name(&value))?;
+            Some(RankedCandidate { kind, value })
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| {
+        let (a_name, b_name) = (name(&a.value), name(&b.value));
+        a.kind
+            .cmp(&b.kind)
+            .then(a_name.len().cmp(&b_name.len()))
+            .then(a_name.cmp(b_name))
+    });
+    ranked
+}
+
+/// Filter and rank completion items against the query, by their filter text or label
+///
+/// The sort text of the items is set from their rank, so clients show the best matches first.
+/// Items that don't start with the query get the query prepended to their filter text,
+/// otherwise clients that only do prefix filtering would hide them.
+pub fn rank_items(query: &str, items: Vec<CompletionItem>) -> Vec<CompletionItem> {
+    let ranked = rank(query, items, |item| item.filter_text.as_deref().unwrap_or(&item.label));
+
+    ranked
+        .into_iter()
+        .enumerate()
+        .map(|(index, candidate)| {
+            let mut item = candidate.value;
+            item.sort_text = Some(format!("{:05}", index));
+            if candidate.kind != MatchKind::Prefix {
+                let name = item.filter_text.take().unwrap_or_else(|| item.label.clone());
+                item.filter_text = Some(format!("{}{}", query, name));
+            }
+            item
+        })
+        .collect()
+}
+
+/// Keep at most `max_items` of ranked completion items
+///
+/// Returns `true` if items were dropped, the list should then be reported as incomplete so the client
+/// asks again (and the candidates are ranked again) when the user types more.
+pub fn limit_ranked_items(items: &mut Vec<CompletionItem>, max_items: usize) -> bool {
+    let is_incomplete = items.len() > max_items;
+    items.truncate(max_items);
+    is_incomplete
+}
+
+/// Split a name into lower case words, at case changes, digits and separators (`-`, `_`, `.`, space)
+///
+/// `ProgressBar` -> `progress`, `bar`; `-unity-font` -> `unity`, `font`; `UIElement` -> `ui`, `element`
+fn split_words(name: &str) -> Vec<Vec<char>> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words: Vec<Vec<char>> = Vec::new();
+    let mut current: Vec<char> = Vec::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if matches!(c, '-' | '_' | '.' | ' ') {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1);
+        let starts_word = match prev {
+            Some(p) if c.is_uppercase() => {
+                // `aB` or the `E` of `UIElement`
+                p.is_lowercase() || p.is_ascii_digit() || (p.is_uppercase() && next.is_some_and(|n| n.is_lowercase()))
+            }
+            Some(p) => c.is_ascii_digit() != p.is_ascii_digit(),
+            None => false,
+        };
+        if starts_word && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        current.extend(c.to_lowercase());
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Remove separators from a query, word boundaries of the query are found by [`matches_words`]
+fn strip_separators(query: &[char]) -> Vec<char> {
+    query.iter().copied().filter(|c| !matches!(c, '-' | '_' | '.' | ' ')).collect()
+}
+
+/// Whether the query can be consumed by prefixes of words, in order
+///
+/// When `anchored` the query must start at the first word.
+fn matches_words(query: &[char], words: &[Vec<char>], anchored: bool) -> bool {
+    if query.is_empty() {
+        return true;
+    }
+
+    let candidates = if anchored { &words[..words.len().min(1)] } else { words };
+    for (i, word) in candidates.iter().enumerate() {
+        let common = word.iter().zip(query).take_while(|(w, q)| w == q).count();
+        // try the longest prefix first, fall back to shorter ones
+        for len in (1..=common).rev() {
+            if matches_words(&query[len..], &words[i + 1..], false) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn is_subsequence(query: &[char], candidate: &[char]) -> bool {
+    let mut candidate = candidate.iter();
+    query.iter().all(|q| candidate.any(|c| c == q))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_kind() {
+        assert_eq!(match_kind("", "Button"), Some(MatchKind::Prefix));
+        assert_eq!(match_kind("but", "Button"), Some(MatchKind::Prefix));
+        assert_eq!(match_kind("PB", "ProgressBar"), Some(MatchKind::CamelHump));
+        assert_eq!(match_kind("ProBa", "ProgressBar"), Some(MatchKind::CamelHump));
+        assert_eq!(match_kind("pro-ba", "progress-bar"), Some(MatchKind::CamelHump));
+        assert_eq!(match_kind("ufn", "-unity-font"), Some(MatchKind::Fuzzy));
+        assert_eq!(match_kind("uf", "-unity-font"), Some(MatchKind::CamelHump));
+        assert_eq!(match_kind("ue", "UIElement"), Some(MatchKind::CamelHump));
+        // the first word can't be skipped
+        assert_eq!(match_kind("bu", "RadioButton"), Some(MatchKind::Fuzzy));
+        assert_eq!(match_kind("ogb", "ProgressBar"), Some(MatchKind::Fuzzy));
+        assert_eq!(match_kind("xyz", "ProgressBar"), None);
+    }
+
+    #[test]
+    fn test_rank() {
+        let candidates = ["TextField", "Toggle", "Button", "TwoPaneSplitView", "RadioButton", "TextElement"];
+        let ranked: Vec<&str> = rank("tf", candidates, |c| c).into_iter().map(|r| r.value).collect();
+        assert_eq!(ranked, vec!["TextField"]);
+
+        let ranked: Vec<&str> = rank("te", candidates, |c| c).into_iter().map(|r| r.value).collect();
+        assert_eq!(ranked, vec!["TextField", "TextElement", "Toggle", "TwoPaneSplitView"]);
+
+        let ranked: Vec<(MatchKind, &str)> =
+            rank("bu", candidates, |c| c).into_iter().map(|r| (r.kind, r.value)).collect();
+        assert_eq!(ranked, vec![(MatchKind::Prefix, "Button"), (MatchKind::Fuzzy, "RadioButton")]);
+    }
+
+    #[test]
+    fn test_rank_items() {
+        let items = ["background-color", "color", "border-color", "width"]
+            .into_iter()
+            .map(|label| CompletionItem::new_simple(label.to_string(), String::new()))
+            .collect();
+
+        let ranked = rank_items("bc", items);
+        let labels: Vec<&str> = ranked.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, vec!["border-color", "background-color"]);
+        assert_eq!(ranked[0].sort_text.as_deref(), Some("00000"));
+        assert_eq!(ranked[0].filter_text.as_deref(), Some("bcborder-color"));
+    }
+
+    #[test]
+    fn test_limit_ranked_items() {
+        let mut items: Vec<CompletionItem> = (0..5).map(|i| CompletionItem::new_simple(i.to_string(), String::new())).collect();
+        assert!(!limit_ranked_items(&mut items, 5));
+        assert!(limit_ranked_items(&mut items, 3));
+        assert_eq!(items.len(), 3);
+    }
+}
@@ -0,0 +1,308 @@
+//! Font Assets
+//!
+//! `-unity-font-definition` takes a font asset, a `.asset` file created by UI Toolkit or TextMeshPro,
+//! which is hard to find by its file name alone. [`FontAssetIndex`] finds the font assets under `Assets`
+//! so they can be offered by the family and style of their font, e.g. `Inter Bold`.
+//!
+//! Font assets are recognized from their YAML header, they are MonoBehaviours with face info
+//! (`m_FaceInfo` in UI Toolkit and recent TextMeshPro, `m_fontInfo` in older TextMeshPro). Assets saved
+//! in binary form are not recognized.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use urlencoding::encode;
+
+/// File ID of the main object of a ScriptableObject asset
+const MAIN_OBJECT_FILE_ID: i64 = 11400000;
+
+/// Lines read from the start of an asset to find its face info, the glyph tables come after it
+const MAX_HEADER_LINES: usize = 200;
+
+/// How long the found font assets are used before the folders are scanned again
+const SCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A font asset in the project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FontAssetInfo {
+    /// Project relative path, e.g. `Assets/Fonts/Inter SDF.asset`
+    pub path: String,
+    /// GUID from the `.meta` file, `None` if it has none yet
+    pub guid: Option<String>,
+    /// Name of the asset, `m_Name`
+    pub name: String,
+    /// Family name of the font, e.g. `Inter`
+    pub family: Option<String>,
+    /// Style name of the font, e.g. `Bold`
+    pub style: Option<String>,
+}
+
+impl FontAssetInfo {
+    /// Friendly name of the font asset: family and style of its font, or its name if they are unknown
+    pub fn label(&self) -> String {
+        match (&self.family, &self.style) {
+            (Some(family), Some(style)) => format!("{} {}", family, style),
+            (Some(family), None) => family.clone(),
+            _ => self.name.clone(),
+        }
+    }
+
+    /// URL of the asset as Unity writes it, with the GUID when it is known
+    pub fn url(&self) -> String {
+        let path = self.path.split('/').map(|segment| encode(segment).into_owned()).collect::<Vec<_>>().join("/");
+        match &self.guid {
+            Some(guid) => format!(
+                "project:///{}?fileID={}&guid={}&type=2#{}",
+                path,
+                MAIN_OBJECT_FILE_ID,
+                guid,
+                encode(&self.name)
+            ),
+            None => format!("project:///{}", path),
+        }
+    }
+}
+
+/// Read the name, family and style of a font asset, `None` if the content is not a font asset
+///
+/// Returns `(name, family, style)`.
+pub fn parse_font_asset(reader: impl BufRead) -> Option<(String, Option<String>, Option<String>)> {
+    let mut lines = reader.lines().map_while(Result::ok).take(MAX_HEADER_LINES);
+    if !lines.next()?.starts_with("%YAML") {
+        return None;
+    }
+
+    let mut is_mono_behaviour = false;
+    let mut has_face_info = false;
+    let mut name = None;
+    let mut family = None;
+    let mut style = None;
+    for line in lines {
+        let line = line.trim();
+        if line.starts_with("--- ") {
+            // the first object is the asset, later ones are sub assets such as the atlas texture
+            if is_mono_behaviour {
+                break;
+            }
+            continue;
+        }
+        match line.split_once(':').map(|(key, value)| (key, unquote(value.trim()))) {
+            Some(("MonoBehaviour", _)) => is_mono_behaviour = true,
+            Some(("m_FaceInfo" | "m_fontInfo", _)) => has_face_info = true,
+            Some(("m_Name", value)) if name.is_none() => name = Some(value.to_string()),
+            // `Name` is the family in the face info of older TextMeshPro assets
+            Some(("m_FamilyName" | "Name", value)) if has_face_info && family.is_none() && !value.is_empty() => {
+                family = Some(value.to_string())
+            }
+            Some(("m_StyleName", value)) if has_face_info && style.is_none() && !value.is_empty() => {
+                style = Some(value.to_string())
+            }
+            _ => {}
+        }
+        if family.is_some() && style.is_some() {
+            break;
+        }
+    }
+
+    if is_mono_behaviour && has_face_info {
+        Some((name.unwrap_or_default(), family, style))
+    } else {
+        None
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value)
+}
+
+/// GUID in a `.meta` file
+fn read_guid(meta_path: &Path) -> Option<String> {
+    let file = File::open(meta_path).ok()?;
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .find_map(|line| line.strip_prefix("guid:").map(|guid| guid.trim().to_string()))
+}
+
+/// The font assets of a project, assets are only read again when they change
+#[derive(Debug)]
+pub struct FontAssetIndex {
+    project_root: PathBuf,
+    cache: Mutex<FontAssetCache>,
+}
+
+#[derive(Debug, Default)]
+struct FontAssetCache {
+    scanned_at: Option<Instant>,
+    /// Every `.asset` file found, with its modification time and the font asset if it is one
+    files: HashMap<PathBuf, (Option<SystemTime>, Option<FontAssetInfo>)>,
+    assets: Vec<FontAssetInfo>,
+}
+
+impl FontAssetIndex {
+    pub fn new(project_root: &Path) -> Self {
+        Self {
+            project_root: project_root.to_path_buf(),
+            cache: Mutex::new(FontAssetCache::default()),
+        }
+    }
+
+    /// The font assets under `Assets` found by the last [`refresh`](Self::refresh), sorted by label
+    pub fn font_assets(&self) -> Vec<FontAssetInfo> {
+        self.cache.lock().map(|cache| cache.assets.clone()).unwrap_or_default()
+    }
+
+    /// Whether the folders should be scanned again before the font assets are used
+    pub fn is_stale(&self) -> bool {
+        self.cache
+            .lock()
+            .map(|cache| !cache.scanned_at.is_some_and(|at| at.elapsed() < SCAN_INTERVAL))
+            .unwrap_or(true)
+    }
+
+    /// Scan `Assets` for font assets if the last scan is stale
+    ///
+    /// Walks the folders and reads the changed assets, async code should call it with `spawn_blocking`.
+    pub fn refresh(&self) {
+        if !self.is_stale() {
+            return;
+        }
+        let mut previous = self.cache.lock().map(|mut cache| std::mem::take(&mut cache.files)).unwrap_or_default();
+
+        let mut found = Vec::new();
+        collect_asset_files(&self.project_root.join("Assets"), &mut found);
+
+        let mut files = HashMap::new();
+        for path in found {
+            let modified = std::fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+            let info = match previous.remove(&path) {
+                Some((previous, info)) if previous == modified && modified.is_some() => info,
+                _ => self.read_font_asset(&path),
+            };
+            files.insert(path, (modified, info));
+        }
+
+        let mut assets: Vec<FontAssetInfo> = files.values().filter_map(|(_, info)| info.clone()).collect();
+        assets.sort_by(|a, b| a.label().cmp(&b.label()).then_with(|| a.path.cmp(&b.path)));
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.files = files;
+            cache.assets = assets;
+            cache.scanned_at = Some(Instant::now());
+        }
+    }
+
+    /// Scan for font assets again on the next refresh, e.g. after an asset was created or deleted
+    ///
+    /// Assets that didn't change are not read again.
+    pub fn invalidate(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.scanned_at = None;
+        }
+    }
+
+    fn read_font_asset(&self, path: &Path) -> Option<FontAssetInfo> {
+        let file = File::open(path).ok()?;
+        let (name, family, style) = parse_font_asset(BufReader::new(file))?;
+        let relative = path.strip_prefix(&self.project_root).ok()?;
+        let relative = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+        let mut meta_path = path.as_os_str().to_owned();
+        meta_path.push(".meta");
+        Some(FontAssetInfo {
+            path: relative,
+            guid: read_guid(Path::new(&meta_path)),
+            name,
+            family,
+            style,
+        })
+    }
+}
+
+fn collect_asset_files(dir: &Path, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_asset_files(&path, found);
+        } else if path.extension().and_then(|e| e.to_str()) == Some("asset") {
+            found.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const UI_TOOLKIT_FONT_ASSET: &str = "%YAML 1.1
+%TAG !u! tag:unity3d.com,2011:
+--- !u!114 &11400000
+MonoBehaviour:
+  m_ObjectHideFlags: 0
+  m_Script: {fileID: 11500000, guid: 4f2d3cbc2a8b4d1aa1b21bd3ebb6d1f4, type: 3}
+  m_Name: Inter SDF
+  m_EditorClassIdentifier:
+  m_Version: 1.1.0
+  m_FaceInfo:
+    m_FaceIndex: 0
+    m_FamilyName: Inter
+    m_StyleName: Bold
+    m_PointSize: 90
+";
+
+    #[test]
+    fn test_parse_font_asset() {
+        let (name, family, style) = parse_font_asset(UI_TOOLKIT_FONT_ASSET.as_bytes()).unwrap();
+        assert_eq!(name, "Inter SDF");
+        assert_eq!(family.as_deref(), Some("Inter"));
+        assert_eq!(style.as_deref(), Some("Bold"));
+
+        let legacy = "%YAML 1.1\n--- !u!114 &11400000\nMonoBehaviour:\n  m_Name: LiberationSans SDF\n  m_fontInfo:\n    Name: Liberation Sans\n    PointSize: 86\n";
+        let (name, family, style) = parse_font_asset(legacy.as_bytes()).unwrap();
+        assert_eq!(name, "LiberationSans SDF");
+        assert_eq!(family.as_deref(), Some("Liberation Sans"));
+        assert_eq!(style, None);
+
+        let panel_settings = "%YAML 1.1\n--- !u!114 &11400000\nMonoBehaviour:\n  m_Name: PanelSettings\n  m_ScaleMode: 1\n";
+        assert_eq!(parse_font_asset(panel_settings.as_bytes()), None);
+        assert_eq!(parse_font_asset(&b"binary"[..]), None);
+    }
+
+    #[test]
+    fn test_font_asset_index() {
+        let project = TempDir::new().unwrap();
+        let fonts = project.path().join("Assets").join("Fonts");
+        std::fs::create_dir_all(&fonts).unwrap();
+        std::fs::write(fonts.join("Inter SDF.asset"), UI_TOOLKIT_FONT_ASSET).unwrap();
+        std::fs::write(
+            fonts.join("Inter SDF.asset.meta"),
+            "fileFormatVersion: 2\nguid: 8f586378b4e144a9851e7b34d9b748ee\nNativeFormatImporter:\n",
+        )
+        .unwrap();
+        std::fs::write(fonts.join("Other.asset"), "%YAML 1.1\n--- !u!114 &1\nMonoBehaviour:\n  m_Name: Other\n").unwrap();
+
+        let index = FontAssetIndex::new(project.path());
+        assert!(index.is_stale() && index.font_assets().is_empty());
+        index.refresh();
+        assert!(!index.is_stale());
+        let assets = index.font_assets();
+        assert_eq!(assets.len(), 1);
+        assert_eq!(assets[0].path, "Assets/Fonts/Inter SDF.asset");
+        assert_eq!(assets[0].label(), "Inter Bold");
+        assert_eq!(
+            assets[0].url(),
+            "project:///Assets/Fonts/Inter%20SDF.asset?fileID=11400000&guid=8f586378b4e144a9851e7b34d9b748ee&type=2#Inter%20SDF"
+        );
+    }
+}
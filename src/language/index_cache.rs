@@ -0,0 +1,119 @@
+//! Index Cache
+//!
+//! Indexes of a project (UXML elements, USS imports) are saved in `Library/UnityCode/Index`, so a restarted
+//! server starts with the files indexed in the last session. Indexed files keep their modification time,
+//! so the next refresh only parses the files that changed since.
+//!
+//! Cache files are JSON with the [`INDEX_CACHE_VERSION`] they were written with. A cache of another
+//! version or one that can't be read is deleted, the index is then built from scratch.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Version of the cache format, caches of other versions are discarded
+///
+/// Increase it when the serialized form of an index changes.
+pub const INDEX_CACHE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile<T> {
+    version: u32,
+    index: T,
+}
+
+/// Only the version, so a cache of another version isn't read further
+#[derive(Deserialize)]
+struct CacheHeader {
+    version: u32,
+}
+
+/// Path of the cache of an index, e.g. `Library/UnityCode/Index/uxml_elements.json`
+pub fn cache_path(project_root: &Path, name: &str) -> PathBuf {
+    project_root
+        .join("Library")
+        .join("UnityCode")
+        .join("Index")
+        .join(format!("{}.json", name))
+}
+
+/// Read the cache of an index, `None` if there is none or it was discarded
+pub fn load_index<T: DeserializeOwned>(project_root: &Path, name: &str) -> Option<T> {
+    let path = cache_path(project_root, name);
+    let bytes = match fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            log::warn!("Failed to read index cache {}: {}", path.display(), e);
+            return None;
+        }
+    };
+
+    let discarded = match serde_json::from_slice::<CacheHeader>(&bytes) {
+        Ok(header) if header.version != INDEX_CACHE_VERSION => format!("it has version {}", header.version),
+        Ok(_) => match serde_json::from_slice::<CacheFile<T>>(&bytes) {
+            Ok(cache) => return Some(cache.index),
+            Err(e) => e.to_string(),
+        },
+        Err(e) => e.to_string(),
+    };
+    log::info!("Discarding index cache {}, {}", path.display(), discarded);
+    if let Err(e) = fs::remove_file(&path) {
+        log::warn!("Failed to delete index cache {}: {}", path.display(), e);
+    }
+    None
+}
+
+/// Write the cache of an index
+///
+/// The cache is written to a temporary file first, so a server stopped while writing leaves the previous cache.
+pub fn save_index<T: Serialize>(project_root: &Path, name: &str, index: &T) -> io::Result<()> {
+    let path = cache_path(project_root, name);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let cache = CacheFile { version: INDEX_CACHE_VERSION, index };
+    let json = serde_json::to_vec(&cache).map_err(io::Error::other)?;
+    let temporary = path.with_extension("json.tmp");
+    fs::write(&temporary, json)?;
+    fs::rename(&temporary, &path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_save_and_load() {
+        let project = TempDir::new().unwrap();
+        assert_eq!(load_index::<HashMap<String, u32>>(project.path(), "test"), None);
+
+        let index = HashMap::from([("Assets/a.uss".to_string(), 3)]);
+        save_index(project.path(), "test", &index).unwrap();
+        assert!(project.path().join("Library/UnityCode/Index/test.json").exists());
+        assert_eq!(load_index(project.path(), "test"), Some(index));
+    }
+
+    #[test]
+    fn test_discard_invalid_cache() {
+        let project = TempDir::new().unwrap();
+        let path = cache_path(project.path(), "test");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+        // corrupted, e.g. by a full disk
+        fs::write(&path, "{\"version\": 1, \"index\": {\"Assets/a.u").unwrap();
+        assert_eq!(load_index::<HashMap<String, u32>>(project.path(), "test"), None);
+        assert!(!path.exists());
+
+        // another version
+        fs::write(&path, "{\"version\": 0, \"index\": [1, 2]}").unwrap();
+        assert_eq!(load_index::<HashMap<String, u32>>(project.path(), "test"), None);
+        assert!(!path.exists());
+    }
+}
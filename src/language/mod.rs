@@ -5,6 +5,19 @@ pub mod asset_url;
 pub mod tree_utils;
 pub mod url_completion;
 pub mod tree_printer;
+pub mod request_timing;
+pub mod completion_ranking;
+pub mod panic_guard;
+pub mod cancellation;
+pub mod workspace_edit;
+pub mod progress;
+pub mod spelling;
+pub mod project_packages;
+pub mod asset_filter;
+pub mod font_assets;
+pub mod index_cache;
+pub mod project_scan;
+pub mod worker_pool;
 
 #[cfg(test)]
 mod url_completion_tests;
\ No newline at end of file
@@ -0,0 +1,67 @@
+//! Panic isolation for language server requests
+//!
+//! A panic while handling one request must not take down the whole server, so request
+//! handlers are wrapped with [`catch_unwind`], which turns a panic into an error that the
+//! server reports for that request only.
+
+use std::any::Any;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Future returned by [`catch_unwind`]
+pub struct CatchUnwind<F: Future> {
+    future: Pin<Box<F>>,
+}
+
+/// Run a future, catching panics while it is polled
+///
+/// Resolves to the output of the future, or the panic message if it panicked.
+/// Shared state the future touched may be left half updated, callers are responsible for
+/// recovering it (e.g. clearing poisoned mutexes).
+pub fn catch_unwind<F: Future>(future: F) -> CatchUnwind<F> {
+    CatchUnwind {
+        future: Box::pin(future),
+    }
+}
+
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = Result<F::Output, String>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match panic::catch_unwind(AssertUnwindSafe(|| self.future.as_mut().poll(cx))) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(payload) => Poll::Ready(Err(panic_message(payload.as_ref()))),
+        }
+    }
+}
+
+/// Message of a panic payload, for payloads created by `panic!` with a message
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_catch_unwind() {
+        assert_eq!(catch_unwind(async { 42 }).await, Ok(42));
+
+        let result = catch_unwind(async {
+            tokio::task::yield_now().await;
+            panic!("bad request {}", 1);
+        })
+        .await;
+        assert_eq!(result, Err::<(), _>("bad request 1".to_string()));
+    }
+}
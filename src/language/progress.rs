@@ -0,0 +1,178 @@
+//! Work Done Progress
+//!
+//! Long operations, like indexing the files of a large project, report their progress to the editor with
+//! `window/workDoneProgress` so it can show e.g. "Indexing Unity project 43%".
+//!
+//! The work usually runs on another thread (with `spawn_blocking`) and counts what it has done in a
+//! [`ProgressCounter`], while a [`ProgressReporter`] on the runtime sends the count to the client
+//! every [`REPORT_INTERVAL`] until the work completes.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tower_lsp::lsp_types::notification::Progress;
+use tower_lsp::lsp_types::request::WorkDoneProgressCreate;
+use tower_lsp::lsp_types::{
+    NumberOrString, ProgressParams, ProgressParamsValue, ProgressToken, WorkDoneProgress, WorkDoneProgressBegin,
+    WorkDoneProgressCreateParams, WorkDoneProgressEnd, WorkDoneProgressReport,
+};
+use tower_lsp::Client;
+
+/// Time between two progress reports
+pub const REPORT_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Items done out of a total, shared between the thread that does the work and the reporter
+///
+/// The total can grow while the work runs, e.g. when files found during the work are added.
+#[derive(Debug, Clone, Default)]
+pub struct ProgressCounter {
+    done: Arc<AtomicUsize>,
+    total: Arc<AtomicUsize>,
+}
+
+impl ProgressCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add items to do
+    pub fn add_total(&self, count: usize) {
+        self.total.fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Count an item as done
+    pub fn advance(&self) {
+        self.done.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn done(&self) -> usize {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    pub fn total(&self) -> usize {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// Percentage done, 0 while there is nothing to do and never more than 100
+    pub fn percentage(&self) -> u32 {
+        let total = self.total();
+        if total == 0 {
+            return 0;
+        }
+        (self.done().min(total) * 100 / total) as u32
+    }
+}
+
+/// Reports the progress of an operation to the client
+///
+/// Nothing is sent if the client doesn't support work done progress, or fails to create the progress token.
+pub struct ProgressReporter {
+    client: Client,
+    token: Option<ProgressToken>,
+    last_percentage: Option<u32>,
+}
+
+impl ProgressReporter {
+    /// Start reporting an operation, `supported` is whether the client supports work done progress
+    pub async fn begin(client: &Client, supported: bool, title: &str) -> Self {
+        let mut reporter = Self {
+            client: client.clone(),
+            token: None,
+            last_percentage: None,
+        };
+        if !supported {
+            return reporter;
+        }
+
+        static NEXT_TOKEN: AtomicU64 = AtomicU64::new(1);
+        let token = NumberOrString::String(format!("unityCode/progress/{}", NEXT_TOKEN.fetch_add(1, Ordering::Relaxed)));
+        let created = client
+            .send_request::<WorkDoneProgressCreate>(WorkDoneProgressCreateParams { token: token.clone() })
+            .await;
+        if let Err(e) = created {
+            log::debug!("Client refused to create progress {}: {}", title, e);
+            return reporter;
+        }
+
+        reporter.token = Some(token);
+        reporter
+            .send(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                title: title.to_string(),
+                cancellable: Some(false),
+                message: None,
+                percentage: Some(0),
+            }))
+            .await;
+        reporter
+    }
+
+    /// Report the progress of a counter, nothing is sent if the percentage didn't change
+    pub async fn report(&mut self, counter: &ProgressCounter, message: Option<String>) {
+        let percentage = counter.percentage();
+        if self.last_percentage == Some(percentage) {
+            return;
+        }
+        self.last_percentage = Some(percentage);
+        self.send(WorkDoneProgress::Report(WorkDoneProgressReport {
+            cancellable: Some(false),
+            message,
+            percentage: Some(percentage),
+        }))
+        .await;
+    }
+
+    /// Run `work`, reporting the progress of `counter` until it completes
+    pub async fn track<F: Future>(&mut self, counter: &ProgressCounter, work: F) -> F::Output {
+        let mut work = std::pin::pin!(work);
+        loop {
+            tokio::select! {
+                output = &mut work => {
+                    self.report(counter, None).await;
+                    return output;
+                }
+                _ = tokio::time::sleep(REPORT_INTERVAL) => self.report(counter, None).await,
+            }
+        }
+    }
+
+    /// Finish reporting
+    pub async fn end(self, message: Option<String>) {
+        self.send(WorkDoneProgress::End(WorkDoneProgressEnd { message })).await;
+    }
+
+    async fn send(&self, progress: WorkDoneProgress) {
+        if let Some(token) = &self.token {
+            self.client
+                .send_notification::<Progress>(ProgressParams {
+                    token: token.clone(),
+                    value: ProgressParamsValue::WorkDone(progress),
+                })
+                .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_counter() {
+        let counter = ProgressCounter::new();
+        assert_eq!(counter.percentage(), 0);
+
+        counter.add_total(3);
+        let shared = counter.clone();
+        shared.advance();
+        assert_eq!(counter.done(), 1);
+        assert_eq!(counter.percentage(), 33);
+
+        shared.advance();
+        shared.advance();
+        shared.advance();
+        // more done than expected, e.g. an item that wasn't counted in the total
+        assert_eq!(counter.percentage(), 100);
+    }
+}
@@ -0,0 +1,188 @@
+//! Project Packages
+//!
+//! Assets of packages are referenced as `project:///Packages/<package-name>/...`, but the package
+//! is not necessarily in the `Packages` folder under that name:
+//! - Embedded packages are folders in `Packages`, with any folder name
+//! - Local packages are referenced by a `file:` path in `Packages/manifest.json`
+//! - Other packages (registry, git) are in `Library/PackageCache/<package-name>@<version>`
+//!
+//! [`ProjectPackages`] finds the folder of every package from its `package.json`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+/// Where a package comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageSource {
+    /// A folder in `Packages`
+    Embedded,
+    /// A `file:` dependency of the manifest, outside of the project
+    Local,
+    /// Installed by the package manager in `Library/PackageCache`
+    Cache,
+}
+
+/// A package of the project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectPackage {
+    /// Package name, e.g. `com.unity.ugui`, the folder of the package in project URLs
+    pub name: String,
+    /// Name displayed in the package manager, e.g. `Unity UI`
+    pub display_name: Option<String>,
+    /// Folder of the package on disk
+    pub path: PathBuf,
+    pub source: PackageSource,
+}
+
+/// The fields of `package.json` we use
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageJson {
+    name: String,
+    display_name: Option<String>,
+}
+
+/// The fields of `Packages/manifest.json` we use
+#[derive(Debug, Deserialize, Default)]
+struct Manifest {
+    #[serde(default)]
+    dependencies: HashMap<String, String>,
+}
+
+/// The packages of a project by name
+#[derive(Debug, Clone, Default)]
+pub struct ProjectPackages {
+    packages: HashMap<String, ProjectPackage>,
+}
+
+impl ProjectPackages {
+    /// Find the packages of a project, reading the manifest and the `package.json` of every package
+    ///
+    /// If packages have the same name, embedded packages win over local packages, which win over
+    /// packages in the cache, like in Unity.
+    pub fn find(project_root: &Path) -> Self {
+        let mut packages = HashMap::new();
+        let packages_dir = project_root.join("Packages");
+
+        for path in sub_directories(&packages_dir) {
+            add_package(&mut packages, path, PackageSource::Embedded);
+        }
+
+        let manifest = std::fs::read_to_string(packages_dir.join("manifest.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str::<Manifest>(&content).ok())
+            .unwrap_or_default();
+        for reference in manifest.dependencies.values() {
+            if let Some(path) = reference.strip_prefix("file:") {
+                // relative paths are relative to the Packages folder
+                add_package(&mut packages, packages_dir.join(path), PackageSource::Local);
+            }
+        }
+
+        for path in sub_directories(&project_root.join("Library").join("PackageCache")) {
+            add_package(&mut packages, path, PackageSource::Cache);
+        }
+
+        Self { packages }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ProjectPackage> {
+        self.packages.get(name)
+    }
+
+    /// The packages sorted by name
+    pub fn sorted(&self) -> Vec<&ProjectPackage> {
+        let mut packages: Vec<_> = self.packages.values().collect();
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+        packages
+    }
+
+    /// Path on disk of a path relative to the project, e.g. `Packages/com.unity.ugui/Runtime`
+    ///
+    /// `None` if the path is not in a package of the project.
+    pub fn resolve(&self, relative_path: &str) -> Option<PathBuf> {
+        let mut segments = relative_path.trim_start_matches('/').splitn(3, '/');
+        if segments.next() != Some("Packages") {
+            return None;
+        }
+        let package = self.get(segments.next()?)?;
+        match segments.next() {
+            Some(rest) if !rest.is_empty() => Some(package.path.join(rest)),
+            _ => Some(package.path.clone()),
+        }
+    }
+}
+
+fn sub_directories(path: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return Vec::new();
+    };
+    entries.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()).collect()
+}
+
+fn add_package(packages: &mut HashMap<String, ProjectPackage>, path: PathBuf, source: PackageSource) {
+    let Some(package_json) = std::fs::read_to_string(path.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<PackageJson>(&content).ok())
+    else {
+        return;
+    };
+
+    packages.entry(package_json.name.clone()).or_insert(ProjectPackage {
+        name: package_json.name,
+        display_name: package_json.display_name,
+        path,
+        source,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_package(path: &Path, json: &str) {
+        std::fs::create_dir_all(path).unwrap();
+        std::fs::write(path.join("package.json"), json).unwrap();
+    }
+
+    #[test]
+    fn test_find_packages() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("Project");
+        write_package(
+            &project.join("Packages").join("MyTools"),
+            r#"{ "name": "com.me.tools", "displayName": "My Tools" }"#,
+        );
+        write_package(&temp.path().join("Shared"), r#"{ "name": "com.me.shared" }"#);
+        std::fs::write(
+            project.join("Packages").join("manifest.json"),
+            r#"{ "dependencies": { "com.me.shared": "file:../../Shared", "com.unity.ugui": "2.0.0" } }"#,
+        )
+        .unwrap();
+        let cache = project.join("Library").join("PackageCache");
+        write_package(&cache.join("com.unity.ugui@abc123"), r#"{ "name": "com.unity.ugui", "displayName": "Unity UI" }"#);
+        // embedded packages override the cache
+        write_package(&cache.join("com.me.tools@1.0.0"), r#"{ "name": "com.me.tools" }"#);
+
+        let packages = ProjectPackages::find(&project);
+        let names: Vec<_> = packages.sorted().iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["com.me.shared", "com.me.tools", "com.unity.ugui"]);
+
+        let tools = packages.get("com.me.tools").unwrap();
+        assert_eq!(tools.source, PackageSource::Embedded);
+        assert_eq!(tools.display_name.as_deref(), Some("My Tools"));
+        assert_eq!(packages.get("com.me.shared").unwrap().source, PackageSource::Local);
+        assert_eq!(packages.get("com.unity.ugui").unwrap().source, PackageSource::Cache);
+
+        assert_eq!(
+            packages.resolve("Packages/com.unity.ugui/Runtime/UI"),
+            Some(cache.join("com.unity.ugui@abc123").join("Runtime/UI"))
+        );
+        assert_eq!(packages.resolve("Packages/com.me.tools"), Some(project.join("Packages").join("MyTools")));
+        assert_eq!(packages.resolve("Packages/com.missing/a.uss"), None);
+        assert_eq!(packages.resolve("Assets/a.uss"), None);
+    }
+}
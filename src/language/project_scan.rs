@@ -0,0 +1,72 @@
+//! Project Scans
+//!
+//! Indexes of project files (the USS import graph, the UXML usage index and the theme report) find their
+//! files by walking the scanned directories of the project, and are scanned again when they are used
+//! after [`REFRESH_INTERVAL`]. A scan reads the file system, the language server scans without holding
+//! its state and only takes the lock to store the result.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Minimum time between two scans of the project directories
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Directories (relative to the project root) that are scanned for project files
+pub const SCAN_DIRECTORIES: &[&str] = &["Assets", "Packages"];
+
+/// When an index was last scanned
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScanTimer {
+    last_scan: Option<Instant>,
+}
+
+impl ScanTimer {
+    /// Whether the last scan is older than the refresh interval, or there was none
+    pub fn is_stale(&self) -> bool {
+        self.last_scan.map_or(true, |last| last.elapsed() >= REFRESH_INTERVAL)
+    }
+
+    /// Whether the project was scanned at least once
+    pub fn is_scanned(&self) -> bool {
+        self.last_scan.is_some()
+    }
+
+    pub fn mark_scanned(&mut self) {
+        self.last_scan = Some(Instant::now());
+    }
+
+    /// Scan again on next use
+    pub fn invalidate(&mut self) {
+        self.last_scan = None;
+    }
+}
+
+/// All files with the extension in the scanned directories of a project
+pub fn find_files(project_root: &Path, extension: &str) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    for dir in SCAN_DIRECTORIES {
+        collect_files(&project_root.join(dir), extension, &mut found);
+    }
+    found
+}
+
+/// Whether a path is in one of the scanned directories of a project
+pub fn is_in_scanned_directory(project_root: &Path, path: &Path) -> bool {
+    SCAN_DIRECTORIES.iter().any(|dir| path.starts_with(project_root.join(dir)))
+}
+
+fn collect_files(dir: &Path, extension: &str, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, extension, found);
+        } else if path.extension().and_then(|e| e.to_str()) == Some(extension) {
+            found.push(path);
+        }
+    }
+}
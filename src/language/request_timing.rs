@@ -0,0 +1,328 @@
+//! Request timing
+//!
+//! Counts and latencies of the requests handled since the process started, by request type: LSP requests
+//! by handler name (e.g. `completion`, `hover`, `diagnostic` for the diagnostics of a document) and UDP
+//! messages by message type (e.g. `udp/GetUnityState`). The [`METRICS_COMMAND`] LSP command returns them,
+//! so performance data can be attached to bug reports without profiling tools.
+//!
+//! Latencies are kept in a histogram with fixed buckets, so memory doesn't grow with the number of requests.
+//! With the `requestTiming` setting the latencies of the recent requests are kept too and their percentiles
+//! are logged periodically, so performance regressions in big projects show up in the log file.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// LSP command that returns the metrics, see [`MetricsReport`]
+pub const METRICS_COMMAND: &str = "unityCode/metrics";
+
+/// Upper bounds of the latency buckets in milliseconds, slower requests are in a last bucket without bound
+const BUCKET_BOUNDS_MS: [u64; 12] = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1000, 2000, 5000];
+
+/// Number of most recent samples kept per request, percentiles are computed from these
+const MAX_SAMPLES: usize = 1000;
+
+/// Percentiles are logged every time a request has been handled this many times
+const LOG_INTERVAL: u64 = 100;
+
+static TIMINGS: LazyLock<Mutex<RequestTimings>> = LazyLock::new(|| Mutex::new(RequestTimings::new()));
+
+/// Latency summary of a request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatencySummary {
+    /// Total number of recorded requests
+    pub count: u64,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+/// Count and latencies of one request type
+#[derive(Debug, Clone, Default)]
+struct RequestStats {
+    count: u64,
+    failures: u64,
+    total: Duration,
+    max: Duration,
+    /// Requests per bucket of [`BUCKET_BOUNDS_MS`], and the last one for slower requests
+    buckets: [u64; BUCKET_BOUNDS_MS.len() + 1],
+    /// Most recent latencies, only kept while logging is enabled
+    samples: VecDeque<Duration>,
+}
+
+impl RequestStats {
+    fn record(&mut self, duration: Duration, succeeded: bool, keep_sample: bool) {
+        self.count += 1;
+        if !succeeded {
+            self.failures += 1;
+        }
+        self.total += duration;
+        self.max = self.max.max(duration);
+        let millis = duration.as_secs_f64() * 1000.0;
+        let bucket = BUCKET_BOUNDS_MS
+            .iter()
+            .position(|&bound| millis <= bound as f64)
+            .unwrap_or(BUCKET_BOUNDS_MS.len());
+        self.buckets[bucket] += 1;
+
+        if keep_sample {
+            if self.samples.len() == MAX_SAMPLES {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(duration);
+        }
+    }
+
+    fn report(&self) -> RequestReport {
+        let mean = if self.count == 0 { 0.0 } else { self.total.as_secs_f64() * 1000.0 / self.count as f64 };
+        RequestReport {
+            count: self.count,
+            failures: self.failures,
+            mean_ms: mean,
+            max_ms: self.max.as_secs_f64() * 1000.0,
+            histogram: self
+                .buckets
+                .iter()
+                .enumerate()
+                .filter(|(_, count)| **count > 0)
+                .map(|(i, count)| HistogramBucket {
+                    up_to_ms: BUCKET_BOUNDS_MS.get(i).copied(),
+                    count: *count,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Recorded latencies by request name
+#[derive(Debug)]
+pub struct RequestTimings {
+    started: Instant,
+    /// Whether recent latencies are kept and their percentiles logged
+    logging: bool,
+    requests: BTreeMap<String, RequestStats>,
+}
+
+/// Metrics as returned by [`METRICS_COMMAND`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsReport {
+    pub uptime_seconds: u64,
+    /// By request type
+    pub requests: BTreeMap<String, RequestReport>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestReport {
+    pub count: u64,
+    /// Requests that returned an error or panicked
+    pub failures: u64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+    /// Buckets with at least one request
+    pub histogram: Vec<HistogramBucket>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramBucket {
+    /// Latency upper bound of the bucket, `None` for requests slower than all bounds
+    pub up_to_ms: Option<u64>,
+    pub count: u64,
+}
+
+impl RequestTimings {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            logging: false,
+            requests: BTreeMap::new(),
+        }
+    }
+
+    /// Enable or disable logging of percentiles, disabling drops the recent latencies
+    pub fn set_logging(&mut self, enabled: bool) {
+        self.logging = enabled;
+        if !enabled {
+            for stats in self.requests.values_mut() {
+                stats.samples = VecDeque::new();
+            }
+        }
+    }
+
+    /// Record a handled request, percentiles are logged every [`LOG_INTERVAL`] requests while logging is enabled
+    pub fn record(&mut self, request: &str, duration: Duration, succeeded: bool) {
+        if !self.requests.contains_key(request) {
+            self.requests.insert(request.to_string(), RequestStats::default());
+        }
+        let Some(stats) = self.requests.get_mut(request) else {
+            return;
+        };
+        stats.record(duration, succeeded, self.logging);
+        if self.logging && stats.count % LOG_INTERVAL == 0 {
+            self.log_summary(request);
+        }
+    }
+
+    /// Latency summary of the recent requests of a type, `None` if none were recorded while logging
+    pub fn summary(&self, request: &str) -> Option<LatencySummary> {
+        let stats = self.requests.get(request).filter(|stats| !stats.samples.is_empty())?;
+        let mut sorted: Vec<Duration> = stats.samples.iter().copied().collect();
+        sorted.sort();
+
+        Some(LatencySummary {
+            count: stats.count,
+            p50: percentile(&sorted, 50),
+            p90: percentile(&sorted, 90),
+            p99: percentile(&sorted, 99),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+
+    /// Log the latency summary of a request
+    pub fn log_summary(&self, request: &str) {
+        if let Some(summary) = self.summary(request) {
+            log::info!(
+                "[timing] {}: count={} p50={:.2?} p90={:.2?} p99={:.2?} max={:.2?}",
+                request,
+                summary.count,
+                summary.p50,
+                summary.p90,
+                summary.p99,
+                summary.max
+            );
+        }
+    }
+
+    /// Log the latency summaries of all requests
+    pub fn log_all_summaries(&self) {
+        for request in self.requests.keys() {
+            self.log_summary(request);
+        }
+    }
+
+    pub fn report(&self) -> MetricsReport {
+        MetricsReport {
+            uptime_seconds: self.started.elapsed().as_secs(),
+            requests: self.requests.iter().map(|(name, stats)| (name.clone(), stats.report())).collect(),
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// Start counting the uptime, otherwise it starts with the first request
+pub fn init() {
+    LazyLock::force(&TIMINGS);
+}
+
+/// Record a handled request in the timings of the process
+pub fn record(request: &str, duration: Duration, succeeded: bool) {
+    if let Ok(mut timings) = TIMINGS.lock() {
+        timings.record(request, duration, succeeded);
+    }
+}
+
+/// Enable or disable logging of the percentiles of the process, see [`RequestTimings::set_logging`]
+pub fn set_logging(enabled: bool) {
+    if let Ok(mut timings) = TIMINGS.lock() {
+        timings.set_logging(enabled);
+    }
+}
+
+/// Log the latency summaries of all requests of the process
+pub fn log_all_summaries() {
+    if let Ok(timings) = TIMINGS.lock() {
+        timings.log_all_summaries();
+    }
+}
+
+/// Metrics of the process
+pub fn report() -> MetricsReport {
+    match TIMINGS.lock() {
+        Ok(timings) => timings.report(),
+        Err(e) => e.into_inner().report(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_requests() {
+        let mut timings = RequestTimings::new();
+        timings.record("completion", Duration::from_micros(500), true);
+        timings.record("completion", Duration::from_millis(3), true);
+        timings.record("completion", Duration::from_millis(8), false);
+        timings.record("hover", Duration::from_secs(10), true);
+
+        let report = timings.report();
+        let completion = &report.requests["completion"];
+        assert_eq!(completion.count, 3);
+        assert_eq!(completion.failures, 1);
+        assert_eq!(completion.max_ms, 8.0);
+        assert!((completion.mean_ms - 3.833).abs() < 0.001);
+        let buckets: Vec<_> = completion.histogram.iter().map(|b| (b.up_to_ms, b.count)).collect();
+        assert_eq!(buckets, vec![(Some(1), 1), (Some(5), 1), (Some(10), 1)]);
+
+        let hover = &report.requests["hover"];
+        assert_eq!(hover.histogram.len(), 1);
+        assert_eq!(hover.histogram[0].up_to_ms, None);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["requests"]["completion"]["histogram"][0]["upToMs"], 1);
+        assert!(json["uptimeSeconds"].is_u64());
+    }
+
+    #[test]
+    fn test_no_samples_without_logging() {
+        let mut timings = RequestTimings::new();
+        timings.record("hover", Duration::from_millis(1), true);
+        assert_eq!(timings.summary("hover"), None);
+        assert_eq!(timings.report().requests["hover"].count, 1);
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let mut timings = RequestTimings::new();
+        timings.set_logging(true);
+        for ms in (1..=100).rev() {
+            timings.record("completion", Duration::from_millis(ms), true);
+        }
+
+        let summary = timings.summary("completion").unwrap();
+        assert_eq!(summary.count, 100);
+        assert_eq!(summary.p50, Duration::from_millis(50));
+        assert_eq!(summary.p90, Duration::from_millis(90));
+        assert_eq!(summary.p99, Duration::from_millis(99));
+        assert_eq!(summary.max, Duration::from_millis(100));
+
+        timings.set_logging(false);
+        assert_eq!(timings.summary("completion"), None);
+    }
+
+    #[test]
+    fn test_samples_are_bounded() {
+        let mut timings = RequestTimings::new();
+        timings.set_logging(true);
+        for _ in 0..MAX_SAMPLES {
+            timings.record("hover", Duration::from_secs(1), true);
+        }
+        for _ in 0..MAX_SAMPLES {
+            timings.record("hover", Duration::from_millis(1), true);
+        }
+
+        let summary = timings.summary("hover").unwrap();
+        assert_eq!(summary.count, 2 * MAX_SAMPLES as u64);
+        assert_eq!(summary.max, Duration::from_millis(1));
+    }
+}
@@ -0,0 +1,74 @@
+//! Spelling Suggestions
+//!
+//! Finds the closest of a set of known words to a misspelled one, for "did you mean" messages and quick fixes.
+
+/// Largest edit distance of a suggestion
+const MAX_DISTANCE: usize = 2;
+
+/// Edit distance between two words, counting insertions, deletions, substitutions and swaps of adjacent characters
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // distances[i][j] is the distance between a[..i] and b[..j]
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+/// The candidate closest to `word` (ignoring case), `None` if none is close enough to be a likely typo
+///
+/// Of candidates at the same distance the first one wins.
+pub fn closest_match<'a>(word: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let word = word.to_lowercase();
+    // short words are at a small distance of many other words
+    let max_distance = MAX_DISTANCE.min(word.chars().count().saturating_sub(1) / 2).max(1);
+
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(&word, &candidate.to_lowercase()), *candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("flex", "flex"), 0);
+        assert_eq!(edit_distance("flx", "flex"), 1);
+        assert_eq!(edit_distance("rwo", "row"), 1);
+        assert_eq!(edit_distance("", "row"), 3);
+        assert_eq!(edit_distance("column", "row"), 5);
+    }
+
+    #[test]
+    fn test_closest_match() {
+        let keywords = ["row", "row-reverse", "column", "column-reverse"];
+        assert_eq!(closest_match("colum", &keywords), Some("column"));
+        assert_eq!(closest_match("Column", &keywords), Some("column"));
+        assert_eq!(closest_match("rwo", &keywords), Some("row"));
+        assert_eq!(closest_match("grid", &keywords), None);
+    }
+}
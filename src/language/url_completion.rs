@@ -2,14 +2,21 @@
 //!
 //! This module provides auto-completion for Unity project URLs in USS and UXML files.
 //! It supports path completion for project assets and query parameter completion for assets with subassets.
+//! Paths under `Packages/` are completed with the packages of the project, wherever they are on disk.
+//! Font assets of the project can also be offered by their font name, see [`FontAssetIndex`].
 
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 use tower_lsp::lsp_types::*;
 use url::Url;
 use log;
 use urlencoding::encode;
 
-use crate::language::asset_url::{validate_url, project_url_to_path};
+use crate::language::asset_filter::AssetFilter;
+use crate::language::font_assets::FontAssetIndex;
+use crate::language::asset_url::{validate_url, project_url_to_path, project_url_to_relative_path};
+use crate::language::project_packages::ProjectPackages;
 use crate::unity_asset_database::{UnityAssetDatabase, AssetDatabaseError};
 
 /// Error type for URL completion operations
@@ -55,8 +62,15 @@ pub enum UrlCompletionContext {
 pub struct UrlCompletionProvider {
     /// Reference to the Unity asset database
     asset_database: UnityAssetDatabase,
+    /// Packages of the project, with the modification times of the files they were found from
+    packages: Mutex<Option<(PackagesStamp, Arc<ProjectPackages>)>>,
+    /// Font assets of the project, refreshed by the server without holding its state
+    font_assets: Arc<FontAssetIndex>,
 }
 
+/// Modification times of the package manifest, lock file and `Packages` folder
+type PackagesStamp = [Option<SystemTime>; 3];
+
 impl UrlCompletionProvider {
     /// Create a new URL completion provider
     ///
@@ -65,9 +79,49 @@ impl UrlCompletionProvider {
     pub fn new(project_root: &Path) -> Self {
         Self {
             asset_database: UnityAssetDatabase::new(project_root),
+            packages: Mutex::new(None),
+            font_assets: Arc::new(FontAssetIndex::new(project_root)),
         }
     }
 
+    /// Look for font assets again on the next refresh, see [`FontAssetIndex::invalidate`]
+    pub fn invalidate_font_assets(&self) {
+        self.font_assets.invalidate();
+    }
+
+    /// Font assets offered by [`complete_font_assets`](Self::complete_font_assets)
+    pub fn font_asset_index(&self) -> Arc<FontAssetIndex> {
+        self.font_assets.clone()
+    }
+
+    /// Completion items for the font assets of the project, labeled with the name of their font
+    ///
+    /// Uses the font assets found by the last refresh of the [`font_asset_index`](Self::font_asset_index).
+    /// An item replaces the whole URL in `range` with the URL of the font asset.
+    pub fn complete_font_assets(&self, range: Range) -> Vec<CompletionItem> {
+        self.font_assets
+            .font_assets()
+            .into_iter()
+            .map(|asset| {
+                let label = asset.label();
+                CompletionItem {
+                    label_details: Some(CompletionItemLabelDetails {
+                        detail: None,
+                        description: Some(asset.path.clone()),
+                    }),
+                    kind: Some(CompletionItemKind::FILE),
+                    detail: Some(format!("Font asset: {}", asset.path)),
+                    text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(range, asset.url()))),
+                    insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+                    filter_text: Some(label.clone()),
+                    sort_text: Some(format!("00_{}", label)),
+                    label,
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
     /// Provide completion items for a URL string
     ///
     /// # Arguments
@@ -86,8 +140,20 @@ impl UrlCompletionProvider {
         cursor_position: usize,
         base_url: Option<&Url>,
     ) -> Vec<CompletionItem> {
+        self.complete_url_with_filter(url_string, cursor_position, base_url, None)
+    }
 
-        
+    /// Provide completion items for a URL string, like [`Self::complete_url`], where only some types of assets
+    /// are accepted
+    ///
+    /// Files that the filter doesn't accept are not suggested, directories always are.
+    pub fn complete_url_with_filter(
+        &self,
+        url_string: &str,
+        cursor_position: usize,
+        base_url: Option<&Url>,
+        filter: Option<&AssetFilter>,
+    ) -> Vec<CompletionItem> {
         let context = match self.analyze_completion_context(url_string, cursor_position, base_url) {
             Ok(context) => context,
             Err(err) => {
@@ -98,7 +164,7 @@ impl UrlCompletionProvider {
         
         let result = match context {
             UrlCompletionContext::Path { partial_path, base_url } => {
-                self.complete_path(&partial_path, base_url.as_ref()).unwrap_or_default()
+                self.complete_path(&partial_path, base_url.as_ref(), filter).unwrap_or_default()
             }
             UrlCompletionContext::Query { asset_url } => {
                 self.complete_query(&asset_url).unwrap_or_default()
@@ -173,11 +239,12 @@ impl UrlCompletionProvider {
         })
     }
 
-    /// Complete the path portion of a URL
+    /// Complete the path portion of a URL, with only the files accepted by `filter` if any
     pub fn complete_path(
         &self,
         partial_path: &str,
         base_url: Option<&Url>,
+        filter: Option<&AssetFilter>,
     ) -> Result<Vec<CompletionItem>, UrlCompletionError> {
 
         
@@ -195,8 +262,13 @@ impl UrlCompletionProvider {
             }
         };
         
+        // Packages are folders of Packages in URLs, but they can be anywhere on disk
+        if directory_path == self.asset_database.project_root().join("Packages") {
+            return Ok(self.complete_package_names(&filename_prefix));
+        }
+
         // List directory contents
-        let entries = match self.list_directory_entries(&directory_path, &filename_prefix) {
+        let entries = match self.list_directory_entries(&directory_path, &filename_prefix, filter) {
             Ok(entries) => entries,
             Err(err) => {
                 log::warn!("Failed to list directory entries: {}", err);
@@ -321,7 +393,7 @@ impl UrlCompletionProvider {
             let project_root = self.asset_database.project_root();
             
             // Get the directory from the base URL
-            if let Some(base_path) = project_url_to_path(project_root, base) {
+            if let Some(base_path) = self.url_to_path(base) {
                 let base_dir = if base_path.is_dir() {
                     base_path
                 } else {
@@ -352,9 +424,7 @@ impl UrlCompletionProvider {
         };
 
         // Convert URL to file system path
-        let project_root = self.asset_database.project_root();
-        
-        if let Some(_) = project_url_to_path(project_root, &resolved_url) {
+        if let Some(_) = self.url_to_path(&resolved_url) {
             let path_str = resolved_url.path();
             
             // Find the last '/' to separate directory and filename
@@ -366,7 +436,7 @@ impl UrlCompletionProvider {
                 let directory_url_str = format!("project:{}", directory_part);
                 
                 if let Ok(directory_url) = Url::parse(&directory_url_str) {
-                    if let Some(directory_path) = project_url_to_path(&project_root, &directory_url) {
+                    if let Some(directory_path) = self.url_to_path(&directory_url) {
                         return Ok((directory_path, filename_part.to_string()));
                     } else {
                         log::warn!("Failed to convert directory URL to path: {}", directory_url);
@@ -399,8 +469,7 @@ impl UrlCompletionProvider {
             
             // Try to resolve the directory part
             if let Ok(validation_result) = validate_url(directory_part, base_url) {
-                let project_root = self.asset_database.project_root();
-                if let Some(directory_path) = project_url_to_path(project_root, &validation_result.url) {
+                if let Some(directory_path) = self.url_to_path(&validation_result.url) {
                     return Ok((directory_path, filename_part.to_string()));
                 }
             }
@@ -409,11 +478,64 @@ impl UrlCompletionProvider {
         Err(UrlCompletionError::new("Fallback path extraction failed"))
     }
 
+    /// Path on disk of a project URL, paths in packages are resolved to the folder of the package
+    fn url_to_path(&self, url: &Url) -> Option<PathBuf> {
+        let package_path = project_url_to_relative_path(url).and_then(|path| self.packages().resolve(&path));
+        package_path.or_else(|| project_url_to_path(self.asset_database.project_root(), url))
+    }
+
+    /// Packages of the project, found again when the manifest, the lock file or the `Packages` folder changed
+    fn packages(&self) -> Arc<ProjectPackages> {
+        let project_root = self.asset_database.project_root();
+        let packages_dir = project_root.join("Packages");
+        let modified = |path: PathBuf| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let stamp = [
+            modified(packages_dir.join("manifest.json")),
+            modified(packages_dir.join("packages-lock.json")),
+            modified(packages_dir),
+        ];
+
+        let Ok(mut cache) = self.packages.lock() else {
+            return Arc::new(ProjectPackages::find(project_root));
+        };
+        match cache.as_ref() {
+            Some((cached_stamp, packages)) if *cached_stamp == stamp => packages.clone(),
+            _ => {
+                let packages = Arc::new(ProjectPackages::find(project_root));
+                *cache = Some((stamp, packages.clone()));
+                packages
+            }
+        }
+    }
+
+    /// Complete the names of the packages of the project that start with a prefix (case-insensitive)
+    fn complete_package_names(&self, prefix: &str) -> Vec<CompletionItem> {
+        let prefix_lower = prefix.to_lowercase();
+        self.packages()
+            .sorted()
+            .into_iter()
+            .filter(|package| {
+                let name_lower = package.name.to_lowercase();
+                name_lower.starts_with(&prefix_lower) && name_lower != prefix_lower
+            })
+            .map(|package| CompletionItem {
+                label: package.name.clone(),
+                kind: Some(CompletionItemKind::FOLDER),
+                detail: Some(package.display_name.clone().unwrap_or_else(|| "Package".to_string())),
+                insert_text: Some(encode(&package.name).to_string()),
+                insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+                sort_text: Some(format!("0_{}", package.name)),
+                ..Default::default()
+            })
+            .collect()
+    }
+
     /// List entries in a directory that match the given prefix
     fn list_directory_entries(
         &self,
         directory_path: &Path,
         filename_prefix: &str,
+        filter: Option<&AssetFilter>,
     ) -> Result<Vec<DirectoryEntry>, UrlCompletionError> {
         log::debug!("Listing directory entries in: '{}' with prefix: '{}'", directory_path.display(), filename_prefix);
         
@@ -454,6 +576,14 @@ impl UrlCompletionProvider {
                                 continue;
                             }
                         }
+
+                        // Skip files of types that are not accepted
+                        if let Some(filter) = filter {
+                            if !filter.accepts(&file_name_str) && !entry.path().is_dir() {
+                                filtered_out += 1;
+                                continue;
+                            }
+                        }
                         
                         // Filter by prefix (case-insensitive) but exclude exact matches
                         let filename_lower = file_name_str.to_lowercase();
@@ -472,7 +602,7 @@ impl UrlCompletionProvider {
                         }
                     }
                 }
-                log::debug!("Directory scan complete: {} total files, {} .meta files skipped, {} filtered out by prefix/root filter, {} entries added", 
+                log::debug!("Directory scan complete: {} total files, {} .meta files skipped, {} filtered out by prefix/root/type filter, {} entries added", 
                            total_files, skipped_meta, filtered_out, entries.len());
             }
             Err(e) => {
@@ -84,7 +84,7 @@ fn test_no_completion_without_slash() {
     let project_root = get_unity_project_root();
     let provider = UrlCompletionProvider::new(&project_root);
 
-    let result = provider.complete_path("project", None).unwrap();
+    let result = provider.complete_path("project", None, None).unwrap();
     assert!(result.is_empty());
 }
 
@@ -392,3 +392,50 @@ fn test_no_completion_for_exact_matches() {
         "Should not return case-insensitive exact match"
     );
 }
+
+#[test]
+fn test_complete_package_paths() {
+    let temp_dir = TempDir::new().unwrap();
+    let project_root = temp_dir.path();
+
+    // an embedded package in a folder with another name, and a package in the cache
+    let embedded = project_root.join("Packages").join("MyTools");
+    fs::create_dir_all(embedded.join("Styles")).unwrap();
+    fs::write(embedded.join("package.json"), r#"{ "name": "com.me.tools", "displayName": "My Tools" }"#).unwrap();
+    File::create(embedded.join("Styles").join("tools.uss")).unwrap();
+    File::create(project_root.join("Packages").join("manifest.json")).unwrap();
+    let cached = project_root.join("Library").join("PackageCache").join("com.unity.ugui@1a2b3c");
+    fs::create_dir_all(&cached).unwrap();
+    fs::write(cached.join("package.json"), r#"{ "name": "com.unity.ugui" }"#).unwrap();
+
+    let provider = UrlCompletionProvider::new(project_root);
+
+    let completions = provider.complete_url("project:/Packages/", "project:/Packages/".len(), None);
+    let labels: Vec<_> = completions.iter().map(|c| c.label.as_str()).collect();
+    assert_eq!(labels, vec!["com.me.tools", "com.unity.ugui"]);
+    assert_eq!(completions[0].detail.as_deref(), Some("My Tools"));
+    assert_eq!(completions[1].detail.as_deref(), Some("Package"));
+
+    let completions = provider.complete_url("project:/Packages/com.u", "project:/Packages/com.u".len(), None);
+    assert_eq!(completions.len(), 1);
+    assert_eq!(completions[0].label, "com.unity.ugui");
+
+    // paths in a package are completed from its folder
+    let completions = provider.complete_url("project:/Packages/com.me.tools/", "project:/Packages/com.me.tools/".len(), None);
+    let labels: Vec<_> = completions.iter().map(|c| c.label.as_str()).collect();
+    assert_eq!(labels, vec!["Styles", "package.json"]);
+
+    let completions = provider.complete_url(
+        "project:/Packages/com.me.tools/Styles/t",
+        "project:/Packages/com.me.tools/Styles/t".len(),
+        None,
+    );
+    assert_eq!(completions.len(), 1);
+    assert_eq!(completions[0].label, "tools.uss");
+
+    // relative to a style sheet of the package
+    let base_url = url::Url::parse("project:/Packages/com.me.tools/Styles/main.uss").unwrap();
+    let completions = provider.complete_url("to", 2, Some(&base_url));
+    assert_eq!(completions.len(), 1);
+    assert_eq!(completions[0].label, "tools.uss");
+}
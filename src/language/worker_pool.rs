@@ -0,0 +1,168 @@
+//! Worker Pool
+//!
+//! Analyzing a document is CPU bound work, and the language server runs on a single threaded runtime,
+//! so analysis done on the runtime blocks every other request. [`WorkerPool`] runs it on blocking threads
+//! instead, at most a fixed number at a time, so checking hundreds of files doesn't start hundreds of threads.
+//!
+//! Work is submitted with a key, usually the document it is for. Work that is still waiting for a worker
+//! when newer work for the same key is submitted is dropped, only the latest is done. Work that already
+//! started is finished. Work that panics is reported as [`WorkError::Panicked`], so callers can tell it
+//! apart from work that was superseded and report the panic.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::Semaphore;
+use tokio::task::JoinError;
+
+/// Why work run on a [`WorkerPool`] has no result
+#[derive(Debug)]
+pub enum WorkError {
+    /// Newer work for the same key was submitted before this started
+    Superseded,
+    /// The work panicked, the payload is in the error
+    Panicked(JoinError),
+}
+
+/// Runs work on blocking threads, at most `workers` at a time, with the latest work per key
+#[derive(Debug)]
+pub struct WorkerPool<K> {
+    workers: Arc<Semaphore>,
+    /// Work waiting for a worker, the number of the latest work submitted per key
+    waiting: Arc<Mutex<HashMap<K, u64>>>,
+    submitted: AtomicU64,
+}
+
+impl<K: Clone + Eq + Hash> WorkerPool<K> {
+    /// Create a pool with at most `workers` running at a time, at least 1
+    pub fn new(workers: usize) -> Self {
+        Self {
+            workers: Arc::new(Semaphore::new(workers.max(1))),
+            waiting: Arc::new(Mutex::new(HashMap::new())),
+            submitted: AtomicU64::new(0),
+        }
+    }
+
+    /// Create a pool with a worker per CPU core
+    pub fn per_core() -> Self {
+        Self::new(std::thread::available_parallelism().map_or(4, |n| n.get()))
+    }
+
+    /// Run work once a worker is free
+    ///
+    /// Fails with [`WorkError::Superseded`] if newer work for the same key was submitted before this started.
+    pub async fn run<T, F>(&self, key: K, work: F) -> Result<T, WorkError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let number = self.submitted.fetch_add(1, Ordering::Relaxed);
+        self.waiting.lock().unwrap_or_else(|e| e.into_inner()).insert(key.clone(), number);
+        let waiting = WaitingEntry { waiting: &self.waiting, key, number };
+
+        // the semaphore is never closed
+        let worker = self.workers.clone().acquire_owned().await.map_err(|_| WorkError::Superseded)?;
+        if !waiting.take() {
+            return Err(WorkError::Superseded);
+        }
+
+        tokio::task::spawn_blocking(move || {
+            let _worker = worker;
+            work()
+        })
+        .await
+        .map_err(WorkError::Panicked)
+    }
+}
+
+/// Removes work that is no longer waiting, also when the caller stops waiting for it
+struct WaitingEntry<'a, K: Eq + Hash> {
+    waiting: &'a Mutex<HashMap<K, u64>>,
+    key: K,
+    number: u64,
+}
+
+impl<K: Eq + Hash> WaitingEntry<'_, K> {
+    /// Remove the entry, false if newer work for the key replaced it
+    fn take(&self) -> bool {
+        let mut waiting = self.waiting.lock().unwrap_or_else(|e| e.into_inner());
+        if waiting.get(&self.key) != Some(&self.number) {
+            return false;
+        }
+        waiting.remove(&self.key);
+        true
+    }
+}
+
+impl<K: Eq + Hash> Drop for WaitingEntry<'_, K> {
+    fn drop(&mut self) {
+        self.take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_bounded_workers() {
+        let pool = Arc::new(WorkerPool::new(2));
+        let running = Arc::new(AtomicUsize::new(0));
+        let most_running = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let pool = pool.clone();
+                let running = running.clone();
+                let most_running = most_running.clone();
+                tokio::spawn(async move {
+                    pool.run(i, move || {
+                        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        most_running.fetch_max(now, Ordering::SeqCst);
+                        std::thread::sleep(Duration::from_millis(20));
+                        running.fetch_sub(1, Ordering::SeqCst);
+                        i * 10
+                    })
+                    .await
+                    .ok()
+                })
+            })
+            .collect();
+        let mut results = Vec::new();
+        for handle in handles {
+            results.push(handle.await.unwrap());
+        }
+
+        assert_eq!(results, (0..8).map(|i| Some(i * 10)).collect::<Vec<_>>());
+        assert_eq!(most_running.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_latest_work_per_key() {
+        let pool = WorkerPool::new(1);
+
+        // the worker is busy, so both documents wait and only the latest of "a" is done
+        let (busy, first, second, other) = tokio::join!(
+            pool.run("busy", || std::thread::sleep(Duration::from_millis(20))),
+            pool.run("a", || 1),
+            pool.run("a", || 2),
+            pool.run("b", || 3),
+        );
+        assert!(busy.is_ok());
+        assert!(matches!(first, Err(WorkError::Superseded)));
+        assert_eq!(second.ok(), Some(2));
+        assert_eq!(other.ok(), Some(3));
+        assert!(pool.waiting.lock().unwrap().is_empty());
+
+        // panicking work keeps its payload, the worker is free again
+        match pool.run("a", || -> i32 { panic!("failed") }).await {
+            Err(WorkError::Panicked(e)) => assert_eq!(e.into_panic().downcast_ref::<&str>(), Some(&"failed")),
+            other => panic!("expected a panic, got {:?}", other),
+        }
+        assert_eq!(pool.run("a", || 4).await.ok(), Some(4));
+    }
+}
@@ -0,0 +1,151 @@
+//! Versioned Workspace Edits
+//!
+//! Edits are computed from the content of a document at some version. If the document changes before the
+//! client applies them, their ranges point at other text and applying them corrupts the file. So every edit
+//! we send carries the version of the document it was computed for, and the client rejects it if the
+//! document has changed since.
+//!
+//! Clients that don't support `documentChanges` get plain `changes` without versions, see [`without_versions`].
+
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{
+    DocumentChanges, OneOf, OptionalVersionedTextDocumentIdentifier, TextDocumentEdit, TextEdit, Url, WorkspaceEdit,
+};
+
+/// Identifier of a document at a version, `None` for a document that is not open in the client (its content on disk)
+pub fn versioned_document(uri: &Url, version: Option<i32>) -> OptionalVersionedTextDocumentIdentifier {
+    OptionalVersionedTextDocumentIdentifier {
+        uri: uri.clone(),
+        version,
+    }
+}
+
+/// Builds a [`WorkspaceEdit`] of versioned document edits
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceEditBuilder {
+    document_edits: Vec<TextDocumentEdit>,
+}
+
+impl WorkspaceEditBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add edits to a document, edits to a document that already has some are merged with them
+    pub fn edit(mut self, document: &OptionalVersionedTextDocumentIdentifier, edits: Vec<TextEdit>) -> Self {
+        let edits = edits.into_iter().map(OneOf::Left);
+        match self.document_edits.iter_mut().find(|e| e.text_document.uri == document.uri) {
+            Some(existing) => existing.edits.extend(edits),
+            None => self.document_edits.push(TextDocumentEdit {
+                text_document: document.clone(),
+                edits: edits.collect(),
+            }),
+        }
+        self
+    }
+
+    /// Whether no edits were added
+    pub fn is_empty(&self) -> bool {
+        self.document_edits.iter().all(|e| e.edits.is_empty())
+    }
+
+    pub fn build(self) -> WorkspaceEdit {
+        WorkspaceEdit {
+            document_changes: Some(DocumentChanges::Edits(self.document_edits)),
+            ..Default::default()
+        }
+    }
+}
+
+/// A workspace edit with the edits of a single document
+pub fn document_edit(document: &OptionalVersionedTextDocumentIdentifier, edits: Vec<TextEdit>) -> WorkspaceEdit {
+    WorkspaceEditBuilder::new().edit(document, edits).build()
+}
+
+/// The text edits of a document in a workspace edit, from either `documentChanges` or `changes`
+pub fn text_edits<'a>(edit: &'a WorkspaceEdit, uri: &Url) -> Vec<&'a TextEdit> {
+    let mut result = Vec::new();
+    if let Some(DocumentChanges::Edits(document_edits)) = &edit.document_changes {
+        for document_edit in document_edits.iter().filter(|e| &e.text_document.uri == uri) {
+            result.extend(document_edit.edits.iter().map(|e| match e {
+                OneOf::Left(edit) => edit,
+                OneOf::Right(annotated) => &annotated.text_edit,
+            }));
+        }
+    }
+    if let Some(edits) = edit.changes.as_ref().and_then(|changes| changes.get(uri)) {
+        result.extend(edits);
+    }
+    result
+}
+
+/// Convert document edits to `changes` without versions, for clients that don't support `documentChanges`
+pub fn without_versions(edit: WorkspaceEdit) -> WorkspaceEdit {
+    let Some(DocumentChanges::Edits(document_edits)) = edit.document_changes else {
+        return edit;
+    };
+
+    let mut changes: HashMap<Url, Vec<TextEdit>> = edit.changes.unwrap_or_default();
+    for document_edit in document_edits {
+        changes.entry(document_edit.text_document.uri).or_default().extend(document_edit.edits.into_iter().map(
+            |e| match e {
+                OneOf::Left(edit) => edit,
+                OneOf::Right(annotated) => annotated.text_edit,
+            },
+        ));
+    }
+
+    WorkspaceEdit {
+        changes: Some(changes),
+        document_changes: None,
+        change_annotations: edit.change_annotations,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::{Position, Range};
+
+    fn text_edit(line: u32, text: &str) -> TextEdit {
+        TextEdit {
+            range: Range::new(Position::new(line, 0), Position::new(line, 1)),
+            new_text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_build_versioned_edit() {
+        let a = Url::parse("file:///project/Assets/a.uss").unwrap();
+        let b = Url::parse("file:///project/Assets/b.uss").unwrap();
+
+        let builder = WorkspaceEditBuilder::new()
+            .edit(&versioned_document(&a, Some(3)), vec![text_edit(0, "x")])
+            .edit(&versioned_document(&b, None), vec![text_edit(1, "y")])
+            .edit(&versioned_document(&a, Some(3)), vec![text_edit(2, "z")]);
+        assert!(!builder.is_empty());
+        let edit = builder.build();
+
+        let Some(DocumentChanges::Edits(document_edits)) = &edit.document_changes else {
+            panic!("Expected document edits");
+        };
+        assert_eq!(document_edits.len(), 2);
+        assert_eq!(document_edits[0].text_document.version, Some(3));
+        assert_eq!(document_edits[1].text_document.version, None);
+        assert_eq!(text_edits(&edit, &a).len(), 2);
+        assert_eq!(text_edits(&edit, &b)[0].new_text, "y");
+
+        assert!(WorkspaceEditBuilder::new().is_empty());
+    }
+
+    #[test]
+    fn test_without_versions() {
+        let uri = Url::parse("file:///project/Assets/a.uss").unwrap();
+        let edit = without_versions(document_edit(&versioned_document(&uri, Some(7)), vec![text_edit(0, "x")]));
+
+        assert!(edit.document_changes.is_none());
+        assert_eq!(edit.changes.as_ref().unwrap()[&uri].len(), 1);
+        assert_eq!(text_edits(&edit, &uri)[0].new_text, "x");
+    }
+}
@@ -0,0 +1,39 @@
+//! Unity Code Native
+//!
+//! The language server and Unity Editor monitor are in the `unity_code_native` binary, which only calls
+//! [`run`]. The USS engine is also a library, so other tools (linters, build steps, asset pipelines) can
+//! analyze style sheets without running the language server, start with [`UssAnalyzer`].
+//!
+//! Only [`uss`], [`uxml_schema_manager`] and [`unity_asset_database`] are public.
+
+// Allow warnings, so we don't see so many warnings everytime we run tests or build
+// We will clean up warnings once in a while
+#![allow(warnings)]
+mod app;
+mod asset_import;
+mod check;
+mod cli;
+mod config;
+mod editor_log;
+mod file_events;
+mod import_timing;
+mod logging;
+mod monitor;
+mod protocol;
+mod server;
+mod style_patches;
+mod unity_project_manager;
+pub mod unity_asset_database;
+pub mod uxml_schema_manager;
+mod dir_changed;
+pub mod uss;
+mod uxml;
+mod language;
+mod cs;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+
+pub use app::run;
+pub use uss::{AnalysisResult, UssAnalyzer};
@@ -1,77 +1,305 @@
-use std::fs::{self, OpenOptions};
+//! File logging
+//!
+//! Log records are written to a file in local app data, one line per record with the time,
+//! level and subsystem (the module path without the crate name, e.g. `uss::server`).
+//!
+//! Levels can be set per subsystem with a filter like `uss=debug,cs=info,warn`: the most specific
+//! matching subsystem wins, a bare level applies to everything else. The filter is read from the
+//! `UNITY_CODE_LOG` environment variable at startup and can be changed at runtime with the
+//! [`SET_LOG_LEVEL_COMMAND`] LSP command.
+//!
+//! The log file is rotated when it grows over [`MAX_LOG_FILE_SIZE`], keeping [`MAX_LOG_BACKUPS`] old files.
+
+use std::fs::{self, File, OpenOptions};
 use std::io::{self, Write};
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Mutex, RwLock};
+
 use log::{LevelFilter, Log, Metadata, Record};
+use thiserror::Error;
 
-struct FileLogger {
-    file: Mutex<std::fs::File>,
+/// LSP command that changes the log filter, its argument is a filter string like `uss=debug,info`
+pub const SET_LOG_LEVEL_COMMAND: &str = "unityCode.setLogLevel";
+
+/// Environment variable with the initial log filter
+const LOG_FILTER_ENV: &str = "UNITY_CODE_LOG";
+
+/// The log file is rotated when it gets bigger than this
+const MAX_LOG_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Number of rotated log files kept (`.1` is the most recent)
+const MAX_LOG_BACKUPS: usize = 3;
+
+/// Crate name that is removed from targets, so filters can use the subsystem name
+const CRATE_PREFIX: &str = concat!(env!("CARGO_CRATE_NAME"), "::");
+
+/// Current filter, used by the logger
+static LOG_FILTER: RwLock<LogFilter> = RwLock::new(LogFilter::new(LevelFilter::Info));
+
+/// Error parsing a log filter
+#[derive(Debug, Error, PartialEq)]
+pub enum LogFilterError {
+    #[error("Invalid log level '{0}'")]
+    InvalidLevel(String),
+    #[error("Invalid log filter directive '{0}'")]
+    InvalidDirective(String),
 }
 
-impl FileLogger {
-    fn new(file_path: PathBuf) -> io::Result<Self> {
+/// Log levels by subsystem
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFilter {
+    /// Level for subsystems without a directive
+    default: LevelFilter,
+    /// Subsystem (e.g. `uss` or `uss::server`) and its level
+    directives: Vec<(String, LevelFilter)>,
+}
+
+impl LogFilter {
+    pub const fn new(default: LevelFilter) -> Self {
+        Self {
+            default,
+            directives: Vec::new(),
+        }
+    }
+
+    /// Level for a log target (module path)
+    pub fn level_for(&self, target: &str) -> LevelFilter {
+        let subsystem = target.strip_prefix(CRATE_PREFIX).unwrap_or(target);
+        self.directives
+            .iter()
+            .filter(|(name, _)| {
+                subsystem == name || subsystem.strip_prefix(name.as_str()).is_some_and(|rest| rest.starts_with("::"))
+            })
+            .max_by_key(|(name, _)| name.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    /// Most verbose level of all subsystems
+    pub fn max_level(&self) -> LevelFilter {
+        self.directives
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+impl FromStr for LogFilter {
+    type Err = LogFilterError;
+
+    /// Parse a filter like `uss=debug,cs::compile=trace,warn`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_level =
+            |level: &str| LevelFilter::from_str(level.trim()).map_err(|_| LogFilterError::InvalidLevel(level.trim().to_string()));
+
+        let mut filter = LogFilter::new(LevelFilter::Info);
+        for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((name, level)) => {
+                    let name = name.trim();
+                    if name.is_empty() {
+                        return Err(LogFilterError::InvalidDirective(directive.to_string()));
+                    }
+                    filter.directives.push((name.to_string(), parse_level(level)?));
+                }
+                None => filter.default = parse_level(directive)?,
+            }
+        }
+        Ok(filter)
+    }
+}
+
+/// Replace the log filter
+pub fn set_log_filter(filter: LogFilter) {
+    log::set_max_level(filter.max_level());
+    if let Ok(mut current) = LOG_FILTER.write() {
+        *current = filter;
+    }
+}
+
+/// A log file that is rotated when it gets too big
+struct RotatingFile {
+    path: PathBuf,
+    /// `None` only while rotating, the file must be closed to be renamed on Windows
+    file: Option<File>,
+    size: u64,
+    max_size: u64,
+}
+
+impl RotatingFile {
+    /// Open the log file, overwriting previous logs
+    fn create(path: PathBuf, max_size: u64) -> io::Result<Self> {
         // Create parent directories if they don't exist
-        if let Some(parent) = file_path.parent() {
+        if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
-        
+
         let file = OpenOptions::new()
             .create(true)
             .write(true)
             .truncate(true)
-            .open(file_path)?;
-        
-        Ok(FileLogger {
-            file: Mutex::new(file),
+            .open(&path)?;
+
+        Ok(Self {
+            path,
+            file: Some(file),
+            size: 0,
+            max_size,
         })
     }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+        file.write_all(line.as_bytes())?;
+        file.flush()?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Shift `log.1` to `log.2` etc, move the current file to `log.1` and start a new file
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file = None;
+        for i in (1..MAX_LOG_BACKUPS).rev() {
+            let from = backup_path(&self.path, i);
+            if from.exists() {
+                fs::rename(&from, backup_path(&self.path, i + 1))?;
+            }
+        }
+        fs::rename(&self.path, backup_path(&self.path, 1))?;
+
+        *self = Self::create(self.path.clone(), self.max_size)?;
+        Ok(())
+    }
+}
+
+fn backup_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}", index));
+    path.with_file_name(name)
+}
+
+struct FileLogger {
+    file: Mutex<RotatingFile>,
 }
 
 impl Log for FileLogger {
-    fn enabled(&self, _metadata: &Metadata) -> bool {
-        true
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        LOG_FILTER
+            .read()
+            .map(|filter| metadata.level() <= filter.level_for(metadata.target()))
+            .unwrap_or(true)
     }
 
     fn log(&self, record: &Record) {
         if self.enabled(record.metadata()) {
+            let line = format_record(record);
             if let Ok(mut file) = self.file.lock() {
-                let _ = writeln!(
-                    file,
-                    "[{}] [{}] {}",
-                    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S%.3f"),
-                    record.level(),
-                    record.args()
-                );
-                let _ = file.flush();
+                let _ = file.write_line(&line);
             }
         }
     }
 
     fn flush(&self) {
         if let Ok(mut file) = self.file.lock() {
-            let _ = file.flush();
+            if let Some(file) = file.file.as_mut() {
+                let _ = file.flush();
+            }
         }
     }
 }
 
+fn format_record(record: &Record) -> String {
+    let target = record.target();
+    format!(
+        "[{}] [{}] [{}] {}\n",
+        chrono::Utc::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+        record.level(),
+        target.strip_prefix(CRATE_PREFIX).unwrap_or(target),
+        record.args()
+    )
+}
+
 /// Get the platform-specific log file path
 fn get_log_file_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
     let data_dir = dirs::data_local_dir()
         .ok_or("Could not determine local data directory")?;
-    
+
     let unity_code_dir = data_dir.join("UnityCode");
     let log_file_path = unity_code_dir.join("unity_code_native.log");
-    
+
     Ok(log_file_path)
 }
 
-/// Initialize the logger to write to a single file in local app data, overwriting previous logs
+/// Initialize the logger to write to a file in local app data, overwriting previous logs
+///
+/// The filter is read from the `UNITY_CODE_LOG` environment variable, `info` if it is not set.
 pub fn init_logger() -> Result<(), Box<dyn std::error::Error>> {
     let log_file_path = get_log_file_path()?;
-    let logger = FileLogger::new(log_file_path)?;
-    
-    log::set_boxed_logger(Box::new(logger))
-        .map(|()| log::set_max_level(LevelFilter::Info))?;
-    
+    let logger = FileLogger {
+        file: Mutex::new(RotatingFile::create(log_file_path, MAX_LOG_FILE_SIZE)?),
+    };
+
+    log::set_boxed_logger(Box::new(logger))?;
+
+    let filter = match std::env::var(LOG_FILTER_ENV) {
+        Ok(value) => value.parse().unwrap_or_else(|e| {
+            eprintln!("Ignoring {}: {}", LOG_FILTER_ENV, e);
+            LogFilter::new(LevelFilter::Info)
+        }),
+        Err(_) => LogFilter::new(LevelFilter::Info),
+    };
+    set_log_filter(filter);
+
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_filter() {
+        let filter: LogFilter = "uss=debug, cs::compile=trace,warn".parse().unwrap();
+        assert_eq!(filter.level_for("unity_code_native::uss::server"), LevelFilter::Debug);
+        assert_eq!(filter.level_for("unity_code_native::cs::compile"), LevelFilter::Trace);
+        assert_eq!(filter.level_for("unity_code_native::cs::docs"), LevelFilter::Warn);
+        // prefix must match whole module names
+        assert_eq!(filter.level_for("unity_code_native::ussx"), LevelFilter::Warn);
+        assert_eq!(filter.max_level(), LevelFilter::Trace);
+
+        assert_eq!("".parse::<LogFilter>().unwrap(), LogFilter::new(LevelFilter::Info));
+        assert_eq!("uss=loud".parse::<LogFilter>(), Err(LogFilterError::InvalidLevel("loud".to_string())));
+        assert_eq!("=debug".parse::<LogFilter>(), Err(LogFilterError::InvalidDirective("=debug".to_string())));
+    }
+
+    #[test]
+    fn test_most_specific_directive_wins() {
+        let filter: LogFilter = "uss::server=off,uss=debug".parse().unwrap();
+        assert_eq!(filter.level_for("unity_code_native::uss::server"), LevelFilter::Off);
+        assert_eq!(filter.level_for("unity_code_native::uss::completion"), LevelFilter::Debug);
+        assert_eq!(filter.level_for("unity_code_native::monitor"), LevelFilter::Info);
+    }
+
+    #[test]
+    fn test_rotation() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("test.log");
+        let mut file = RotatingFile::create(path.clone(), 10).unwrap();
+
+        for i in 0..5 {
+            file.write_line(&format!("line {}\n", i)).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "line 4\n");
+        assert_eq!(fs::read_to_string(backup_path(&path, 1)).unwrap(), "line 3\n");
+        assert_eq!(fs::read_to_string(backup_path(&path, MAX_LOG_BACKUPS)).unwrap(), "line 1\n");
+        assert!(!backup_path(&path, MAX_LOG_BACKUPS + 1).exists());
+    }
+}
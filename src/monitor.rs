@@ -1,13 +1,41 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
+use serde::Deserialize;
 use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
 use log::info;
 
+use crate::protocol::EditorEventKind;
+
 pub(crate) struct ProcessMonitor {
     pub(crate) system: System,
     pub(crate) target_project_path: String,
     pub(crate) unity_pid: Option<Pid>,
     pub(crate) hot_reload_pid: Option<Pid>,
+    /// Whether the Unity Editor has a focused window, only known on Windows
+    pub(crate) is_focused: bool,
+    /// Modification time of `Library/EditorInstance.json` when it was last seen, `None` if it didn't exist
+    editor_instance_modified: Option<SystemTime>,
+}
+
+/// `Library/EditorInstance.json`, written by Unity when it opens the project and deleted when it quits
+#[derive(Debug, Deserialize, PartialEq)]
+pub(crate) struct EditorInstance {
+    pub(crate) process_id: u32,
+    #[serde(default)]
+    pub(crate) version: Option<String>,
+}
+
+impl EditorInstance {
+    pub(crate) fn path(project_path: &Path) -> PathBuf {
+        project_path.join("Library").join("EditorInstance.json")
+    }
+
+    /// Read the editor instance of a project, `None` if Unity didn't write one (or it quit)
+    pub(crate) fn read(project_path: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path(project_path)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
 }
 
 impl ProcessMonitor {
@@ -17,9 +45,37 @@ impl ProcessMonitor {
             target_project_path,
             unity_pid: None,
             hot_reload_pid: None,
+            is_focused: false,
+            editor_instance_modified: None,
         }
     }
 
+    /// Whether `Library/EditorInstance.json` was created or changed since the last call, e.g. Unity opened the project
+    ///
+    /// Checking it is much cheaper than scanning processes, so it can be done often.
+    pub(crate) fn editor_instance_changed(&mut self) -> bool {
+        let path = EditorInstance::path(Path::new(&self.target_project_path));
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        if modified == self.editor_instance_modified {
+            return false;
+        }
+        self.editor_instance_modified = modified;
+        modified.is_some()
+    }
+
+    /// Update whether the Unity Editor is focused, returns the new focus if it changed
+    pub(crate) fn update_focus(&mut self) -> Option<bool> {
+        let is_focused = match (self.unity_pid, foreground_process_id()) {
+            (Some(unity_pid), Some(foreground_pid)) => unity_pid.as_u32() == foreground_pid,
+            _ => false,
+        };
+        if is_focused == self.is_focused {
+            return None;
+        }
+        self.is_focused = is_focused;
+        Some(is_focused)
+    }
+
     /**
      *  refresh the processes we detected
      *
@@ -44,7 +100,7 @@ impl ProcessMonitor {
             .refresh_processes_specifics(ProcessesToUpdate::All, true, process_refresh_kind());
         let normalized_project_path = normalize_path(&self.target_project_path.as_str());
 
-        if self.unity_pid().is_none() {
+        if self.unity_pid().is_none() && !self.detect_unity_from_editor_instance(&normalized_project_path) {
             self.detect_unity_process(&normalized_project_path);
         }
 
@@ -74,6 +130,23 @@ impl ProcessMonitor {
         self.set_hot_reload_pid(found_hot_reload_pid);
     }
 
+    /// Detect Unity from the process written in `Library/EditorInstance.json`, returns true if found
+    fn detect_unity_from_editor_instance(&mut self, normalized_project_path: &String) -> bool {
+        let Some(instance) = EditorInstance::read(Path::new(&self.target_project_path)) else {
+            return false;
+        };
+        // the file is left behind if Unity crashes, and the process id can be reused
+        let pid = Pid::from_u32(instance.process_id);
+        let is_unity = self.system.process(pid).is_some_and(|process| {
+            self.is_valid_unity_process(process)
+                && extract_unity_project_path(process).is_some_and(|path| normalize_path(&path) == *normalized_project_path)
+        });
+        if is_unity {
+            self.set_unity_pid(Some(pid));
+        }
+        is_unity
+    }
+
     fn detect_unity_process(&mut self, normalized_project_path: &String) {
         let mut found_unity_pid = None::<Pid>;
         for (pid, process) in self.system.processes() {
@@ -170,6 +243,7 @@ impl ProcessMonitor {
         }
         if unity_pid.is_none() {
             info!("Unity process is closed");
+            self.is_focused = false;
         } else {
             info!("Unity process detected, id = {:?}", unity_pid);
         }
@@ -177,6 +251,52 @@ impl ProcessMonitor {
     }
 }
 
+/// Lifecycle events of a change of the Unity process, from `old_pid` to `new_pid`
+pub(crate) fn lifecycle_events(old_pid: Option<Pid>, new_pid: Option<Pid>) -> Vec<(EditorEventKind, Pid)> {
+    if old_pid == new_pid {
+        return Vec::new();
+    }
+    let mut events = Vec::new();
+    if let Some(pid) = old_pid {
+        events.push((EditorEventKind::Quit, pid));
+    }
+    if let Some(pid) = new_pid {
+        events.push((EditorEventKind::Started, pid));
+    }
+    events
+}
+
+/// Process that owns the foreground window, `None` if unknown
+#[cfg(target_os = "windows")]
+fn foreground_process_id() -> Option<u32> {
+    use std::ffi::c_void;
+
+    #[link(name = "user32")]
+    unsafe extern "system" {
+        fn GetForegroundWindow() -> *mut c_void;
+        fn GetWindowThreadProcessId(window: *mut c_void, process_id: *mut u32) -> u32;
+    }
+
+    // SAFETY: both functions accept any window handle, including null, and only write the process id
+    unsafe {
+        let window = GetForegroundWindow();
+        if window.is_null() {
+            return None;
+        }
+        let mut process_id = 0u32;
+        GetWindowThreadProcessId(window, &mut process_id);
+        (process_id != 0).then_some(process_id)
+    }
+}
+
+/// Process that owns the foreground window, `None` if unknown
+///
+/// Only implemented on Windows, other platforms need a window system specific API.
+#[cfg(not(target_os = "windows"))]
+fn foreground_process_id() -> Option<u32> {
+    None
+}
+
 pub(crate) fn get_unity_name() -> &'static str {
     #[cfg(target_os = "windows")]
     {
@@ -316,6 +436,44 @@ mod tests {
         // 3. The ProcessRefreshKind configuration works as expected
     }
 
+    #[test]
+    fn test_lifecycle_events() {
+        let (a, b) = (Pid::from_u32(10), Pid::from_u32(20));
+        assert!(lifecycle_events(Some(a), Some(a)).is_empty());
+        assert_eq!(lifecycle_events(None, Some(a)), vec![(EditorEventKind::Started, a)]);
+        assert_eq!(lifecycle_events(Some(a), None), vec![(EditorEventKind::Quit, a)]);
+        // restarted before we noticed
+        assert_eq!(
+            lifecycle_events(Some(a), Some(b)),
+            vec![(EditorEventKind::Quit, a), (EditorEventKind::Started, b)]
+        );
+    }
+
+    #[test]
+    fn test_editor_instance() {
+        let project = tempfile::TempDir::new().unwrap();
+        let mut monitor = ProcessMonitor::new(project.path().to_string_lossy().to_string());
+        assert!(EditorInstance::read(project.path()).is_none());
+        assert!(!monitor.editor_instance_changed());
+
+        std::fs::create_dir_all(project.path().join("Library")).unwrap();
+        std::fs::write(
+            EditorInstance::path(project.path()),
+            r#"{ "process_id": 1234, "version": "6000.0.51f1", "app_path": "C:/Unity/Editor/Unity.exe" }"#,
+        )
+        .unwrap();
+        let instance = EditorInstance::read(project.path()).unwrap();
+        assert_eq!(instance.process_id, 1234);
+        assert_eq!(instance.version.as_deref(), Some("6000.0.51f1"));
+        assert!(monitor.editor_instance_changed());
+        assert!(!monitor.editor_instance_changed());
+
+        // not a Unity process of the project
+        monitor.update(false);
+        assert_eq!(monitor.unity_pid(), None);
+        assert_eq!(monitor.update_focus(), None);
+    }
+
     #[test]
     fn test_process_refresh_kind_memory_info() {
         // Create a system and refresh with our specific ProcessRefreshKind
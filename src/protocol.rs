@@ -0,0 +1,1262 @@
+//! Messaging protocol of the UDP server
+//!
+//! Every message, in both directions, is a 9 byte header followed by a JSON payload:
+//!
+//! ```text
+//! u8 message type | u32 request id | u32 payload length | payload (UTF-8 JSON, can be empty)
+//! ```
+//!
+//! Integers are little endian. The request id of a response is the id of the request,
+//! messages the server sends on its own (e.g. when Unity state changes) have request id 0.
+//!
+//! The payload types are defined here, [`protocol_schema`] describes them as a JSON schema for client authors,
+//! see also `docs/MessagingProtocol.md`.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+/// Version of the protocol
+///
+/// Increased when a message changes in a way that breaks existing clients, adding message types
+/// or optional fields doesn't change it. Clients check it with [`MessageType::GetProtocolVersion`].
+///
+/// - 2: CompileErrors and DocsCompileReports are truncated to fit in a datagram, with the required
+///   `Truncated` field, slow asset imports have the required `TimedOut` field, and invalid
+///   FileChanges and StylePatches subscriptions are rejected instead of replacing the previous one
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Size of the message header, type, request id and payload length
+pub const HEADER_SIZE: usize = 9;
+
+/// Largest payload of a message that fits in a UDP datagram, which carries at most 65507 bytes over IPv4
+pub const MAX_PAYLOAD_SIZE: usize = 65507 - HEADER_SIZE;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(u8)]
+pub enum MessageType {
+    None = 0,
+    GetUnityState = 1,
+    GetSymbolDocs = 2,
+    GetCompileErrors = 3,
+    GetSymbolAtPosition = 4,
+    GetProtocolVersion = 5,
+    /// Sent by the server when the Unity Editor of the project starts, gains or loses focus, or quits
+    EditorEvent = 6,
+    /// Which of the files an editor saved Unity should import, see [`crate::asset_import`]
+    GetAssetsToImport = 7,
+    /// Subscribe to changes of project files, the server then sends the changes in batches, see [`crate::file_events`]
+    FileChanges = 8,
+    /// Methods Unity calls on a class, e.g. `Awake`, see [`crate::cs::unity_messages`]
+    GetUnityMessages = 9,
+    /// Sent by the server while documentation of an assembly is compiled, see [`crate::cs::docs_manager`]
+    DocsCompileProgress = 10,
+    /// Reports of the latest documentation compilation of assemblies, with the files that failed to parse
+    GetDocsCompileReports = 11,
+    /// Subscribe to style changes of saved style sheets, the server then sends them as patches, see [`crate::style_patches`]
+    StylePatches = 12,
+    /// Sent by the server when Unity was slow to import an asset after GetAssetsToImport, see [`crate::import_timing`]
+    SlowAssetImport = 13,
+    /// The latest imports of assets after GetAssetsToImport, with how long they took
+    GetAssetImports = 14,
+}
+
+impl MessageType {
+    pub const ALL: [MessageType; 15] = [
+        MessageType::None,
+        MessageType::GetUnityState,
+        MessageType::GetSymbolDocs,
+        MessageType::GetCompileErrors,
+        MessageType::GetSymbolAtPosition,
+        MessageType::GetProtocolVersion,
+        MessageType::EditorEvent,
+        MessageType::GetAssetsToImport,
+        MessageType::FileChanges,
+        MessageType::GetUnityMessages,
+        MessageType::DocsCompileProgress,
+        MessageType::GetDocsCompileReports,
+        MessageType::StylePatches,
+        MessageType::SlowAssetImport,
+        MessageType::GetAssetImports,
+    ];
+}
+
+impl From<u8> for MessageType {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => MessageType::None,
+            1 => MessageType::GetUnityState,
+            2 => MessageType::GetSymbolDocs,
+            3 => MessageType::GetCompileErrors,
+            4 => MessageType::GetSymbolAtPosition,
+            5 => MessageType::GetProtocolVersion,
+            6 => MessageType::EditorEvent,
+            7 => MessageType::GetAssetsToImport,
+            8 => MessageType::FileChanges,
+            9 => MessageType::GetUnityMessages,
+            10 => MessageType::DocsCompileProgress,
+            11 => MessageType::GetDocsCompileReports,
+            12 => MessageType::StylePatches,
+            13 => MessageType::SlowAssetImport,
+            14 => MessageType::GetAssetImports,
+            _ => MessageType::None,
+        }
+    }
+}
+
+/// Errors of decoding a message
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ProtocolError {
+    #[error("Message is too short: {0} bytes")]
+    TooShort(usize),
+    #[error("Payload length mismatch: header says {expected} bytes, got {actual}")]
+    PayloadLengthMismatch { expected: usize, actual: usize },
+    #[error("Payload is not valid UTF-8")]
+    InvalidUtf8,
+}
+
+/// A decoded message
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message<'a> {
+    pub message_type: MessageType,
+    pub request_id: u32,
+    pub payload: &'a str,
+}
+
+impl<'a> Message<'a> {
+    pub fn decode(data: &'a [u8]) -> Result<Self, ProtocolError> {
+        if data.len() < HEADER_SIZE {
+            return Err(ProtocolError::TooShort(data.len()));
+        }
+
+        let message_type = MessageType::from(data[0]);
+        let request_id = u32::from_le_bytes([data[1], data[2], data[3], data[4]]);
+        let payload_length = u32::from_le_bytes([data[5], data[6], data[7], data[8]]) as usize;
+
+        let payload = data
+            .get(HEADER_SIZE..HEADER_SIZE + payload_length)
+            .ok_or(ProtocolError::PayloadLengthMismatch {
+                expected: payload_length,
+                actual: data.len() - HEADER_SIZE,
+            })?;
+        let payload = std::str::from_utf8(payload).map_err(|_| ProtocolError::InvalidUtf8)?;
+
+        Ok(Self {
+            message_type,
+            request_id,
+            payload,
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let payload_bytes = self.payload.as_bytes();
+
+        let mut data = Vec::with_capacity(HEADER_SIZE + payload_bytes.len());
+        data.push(self.message_type as u8);
+        data.extend_from_slice(&self.request_id.to_le_bytes());
+        data.extend_from_slice(&(payload_bytes.len() as u32).to_le_bytes());
+        data.extend_from_slice(payload_bytes);
+        data
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct ProcessState {
+    #[serde(rename = "UnityProcessId")]
+    pub unity_process_id: u32,
+    #[serde(rename = "IsHotReloadEnabled")]
+    pub is_hot_reload_enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SymbolDocsRequest {
+    #[serde(rename = "SymbolName")]
+    pub symbol_name: String,
+    #[serde(rename = "AssemblyName")]
+    pub assembly_name: Option<String>,
+    #[serde(rename = "SourceFilePath")]
+    pub source_file_path: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SymbolDocsResponse {
+    #[serde(rename = "Success")]
+    pub success: bool,
+    #[serde(rename = "Documentation")]
+    pub documentation: Option<String>,
+    #[serde(rename = "ErrorMessage")]
+    pub error_message: Option<String>,
+    #[serde(rename = "FoundSymbolName")]
+    pub found_symbol_name: Option<String>,
+    #[serde(rename = "InheritedFromSymbolName")]
+    pub inherited_from_symbol_name: Option<String>,
+    /// Source file declaring the symbol, for "Go to source"
+    #[serde(rename = "SourceFilePath", default)]
+    pub source_file_path: Option<String>,
+    /// 0-based line of the symbol's name in the source file
+    #[serde(rename = "Line", default)]
+    pub line: Option<u32>,
+    /// Scripting Reference page of engine symbols, which have no source in the project
+    #[serde(rename = "DocumentationUrl", default)]
+    pub documentation_url: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SymbolAtPositionRequest {
+    #[serde(rename = "SourceFilePath")]
+    pub source_file_path: String,
+    /// 0-based line
+    #[serde(rename = "Line")]
+    pub line: u32,
+    /// 0-based column, in characters
+    #[serde(rename = "Column")]
+    pub column: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SymbolAtPositionResponse {
+    #[serde(rename = "Success")]
+    pub success: bool,
+    #[serde(rename = "SymbolName")]
+    pub symbol_name: Option<String>,
+    #[serde(rename = "TypeName")]
+    pub type_name: Option<String>,
+    #[serde(rename = "MemberName")]
+    pub member_name: Option<String>,
+    #[serde(rename = "AssemblyName")]
+    pub assembly_name: Option<String>,
+    #[serde(rename = "ErrorMessage")]
+    pub error_message: Option<String>,
+}
+
+/// Lifecycle events of the Unity Editor of the project
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EditorEventKind {
+    Started,
+    Focused,
+    Unfocused,
+    Quit,
+}
+
+impl EditorEventKind {
+    pub const ALL: [EditorEventKind; 4] = [
+        EditorEventKind::Started,
+        EditorEventKind::Focused,
+        EditorEventKind::Unfocused,
+        EditorEventKind::Quit,
+    ];
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct EditorEvent {
+    #[serde(rename = "Event")]
+    pub event: EditorEventKind,
+    /// Process of the Editor, for `Quit` the process that quit
+    #[serde(rename = "UnityProcessId")]
+    pub unity_process_id: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AssetsToImportRequest {
+    /// Absolute paths of the saved files
+    #[serde(rename = "Paths")]
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct AssetsToImportResponse {
+    /// 0 if Unity is not running, then there is nothing to import into
+    #[serde(rename = "UnityProcessId")]
+    pub unity_process_id: u32,
+    /// Paths of the assets to import, relative to the project, e.g. `Assets/UI/Main.uss`
+    #[serde(rename = "AssetPaths")]
+    pub asset_paths: Vec<String>,
+    #[serde(rename = "Skipped")]
+    pub skipped: Vec<SkippedAsset>,
+    /// Why the request was rejected, nothing is imported then
+    #[serde(rename = "ErrorMessage", default)]
+    pub error_message: Option<String>,
+}
+
+/// A saved file that Unity doesn't need to import
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct SkippedAsset {
+    #[serde(rename = "Path")]
+    pub path: String,
+    #[serde(rename = "Reason")]
+    pub reason: String,
+}
+
+/// An import of an asset after GetAssetsToImport
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AssetImport {
+    /// Path relative to the project, e.g. `Assets/UI/Main.uss`
+    #[serde(rename = "AssetPath")]
+    pub asset_path: String,
+    /// Time from the request until Unity imported the asset, in milliseconds
+    #[serde(rename = "DurationMs")]
+    pub duration_ms: u64,
+    /// Time Unity spent importing the asset, `None` if its version doesn't log it
+    #[serde(rename = "ImportMs")]
+    pub import_ms: Option<u64>,
+    #[serde(rename = "IsSlow")]
+    pub is_slow: bool,
+    /// Unity didn't import the asset in time, it stopped being waited for after `DurationMs`
+    #[serde(rename = "TimedOut")]
+    pub timed_out: bool,
+    /// When the import was seen, milliseconds since the Unix epoch
+    #[serde(rename = "Timestamp")]
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AssetImportsResponse {
+    /// The latest imports, oldest first
+    #[serde(rename = "Imports")]
+    pub imports: Vec<AssetImport>,
+    /// Imports that take longer are slow
+    #[serde(rename = "SlowThresholdMs")]
+    pub slow_threshold_ms: u64,
+}
+
+/// Which file changes a client is sent
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FileChangesSubscription {
+    /// Glob patterns of the paths relative to the project, e.g. `Assets/UI/**`, empty to unsubscribe
+    #[serde(rename = "Patterns")]
+    pub patterns: Vec<String>,
+    /// Time without changes before a batch is sent, in milliseconds
+    #[serde(rename = "DebounceMs", default)]
+    pub debounce_ms: Option<u64>,
+}
+
+/// What happened to a file within a batch
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FileChangeKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+impl FileChangeKind {
+    pub const ALL: [FileChangeKind; 3] = [FileChangeKind::Created, FileChangeKind::Modified, FileChangeKind::Deleted];
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileChange {
+    /// Path relative to the project, e.g. `Assets/UI/Main.uss`
+    #[serde(rename = "Path")]
+    pub path: String,
+    #[serde(rename = "Kind")]
+    pub kind: FileChangeKind,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct FileChangesBatch {
+    #[serde(rename = "Changes")]
+    pub changes: Vec<FileChange>,
+    /// Why a subscription was rejected, the previous subscription of the client is kept then
+    #[serde(rename = "ErrorMessage", default)]
+    pub error_message: Option<String>,
+}
+
+/// How a rule of a style sheet changed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RulePatchKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl RulePatchKind {
+    pub const ALL: [RulePatchKind; 3] = [RulePatchKind::Added, RulePatchKind::Removed, RulePatchKind::Changed];
+}
+
+/// A declaration to set or remove in a rule
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeclarationPatch {
+    #[serde(rename = "Property")]
+    pub property: String,
+    /// `None` if the declaration was removed
+    #[serde(rename = "Value")]
+    pub value: Option<String>,
+    #[serde(rename = "Important")]
+    pub important: bool,
+}
+
+/// A rule that was added, removed or has changed declarations
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RulePatch {
+    /// Selectors of the rule, separated by `, `
+    #[serde(rename = "Selector")]
+    pub selector: String,
+    #[serde(rename = "Kind")]
+    pub kind: RulePatchKind,
+    /// All declarations of an added or removed rule, the changed ones of a changed rule
+    #[serde(rename = "Declarations")]
+    pub declarations: Vec<DeclarationPatch>,
+}
+
+/// Style changes of a saved style sheet since it was last sent
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct StylePatch {
+    /// Path relative to the project, e.g. `Assets/UI/Main.uss`
+    #[serde(rename = "Path")]
+    pub path: String,
+    #[serde(rename = "Rules")]
+    pub rules: Vec<RulePatch>,
+    /// Whether the changes can't be applied as a patch and the style sheet must be imported, e.g. an import changed
+    #[serde(rename = "RequiresImport")]
+    pub requires_import: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct StylePatches {
+    #[serde(rename = "Patches")]
+    pub patches: Vec<StylePatch>,
+    /// Why a subscription was rejected, the previous subscription of the client is kept then
+    #[serde(rename = "ErrorMessage", default)]
+    pub error_message: Option<String>,
+}
+
+/// The class whose Unity messages to get, either by its base class or its position in a source file
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UnityMessagesRequest {
+    /// Unity class the class derives from, e.g. `MonoBehaviour`, takes precedence over the source file
+    #[serde(rename = "BaseTypeName", default)]
+    pub base_type_name: Option<String>,
+    #[serde(rename = "SourceFilePath", default)]
+    pub source_file_path: Option<String>,
+    /// 0-based line of a position in the class
+    #[serde(rename = "Line", default)]
+    pub line: u32,
+    /// 0-based column, in characters
+    #[serde(rename = "Column", default)]
+    pub column: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UnityMessagesResponse {
+    #[serde(rename = "Success")]
+    pub success: bool,
+    /// Unity class the messages are of, e.g. `MonoBehaviour`
+    #[serde(rename = "BaseTypeName")]
+    pub base_type_name: Option<String>,
+    /// Messages the class doesn't declare yet
+    #[serde(rename = "Messages")]
+    pub messages: Vec<UnityMessageInfo>,
+    #[serde(rename = "ErrorMessage")]
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct UnityMessageInfo {
+    #[serde(rename = "Name")]
+    pub name: String,
+    /// Declaration without body, e.g. `void OnCollisionEnter(Collision collision)`
+    #[serde(rename = "Signature")]
+    pub signature: String,
+    #[serde(rename = "Summary")]
+    pub summary: String,
+    #[serde(rename = "DocumentationUrl")]
+    pub documentation_url: String,
+}
+
+/// Files of an assembly whose documentation is being compiled
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DocsCompileProgress {
+    #[serde(rename = "AssemblyName")]
+    pub assembly_name: String,
+    #[serde(rename = "FilesDone")]
+    pub files_done: usize,
+    #[serde(rename = "FilesTotal")]
+    pub files_total: usize,
+    /// Whether the compilation completed, the report then has the details
+    #[serde(rename = "Finished")]
+    pub finished: bool,
+}
+
+/// Which reports to get, the payload can also be empty
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DocsCompileReportsRequest {
+    /// Only the report of this assembly, all reports if not set
+    #[serde(rename = "AssemblyName", default)]
+    pub assembly_name: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DocsCompileReportsResponse {
+    /// Reports of the assemblies compiled since the server started, by assembly name
+    #[serde(rename = "Reports")]
+    pub reports: Vec<DocsCompileReport>,
+}
+
+impl DocsCompileReportsResponse {
+    /// Payload of the response, at most `max_size` bytes unless the reports don't fit without their failed files
+    ///
+    /// If they don't fit failed files are left out, the ones of the reports that come last first,
+    /// and counted in [`DocsCompileReport::failed_files_truncated`].
+    pub fn payload(&self, max_size: usize) -> serde_json::Result<String> {
+        let json = serde_json::to_string(self)?;
+        if json.len() <= max_size {
+            return Ok(json);
+        }
+
+        let mut truncated = DocsCompileReportsResponse {
+            reports: self
+                .reports
+                .iter()
+                .map(|report| DocsCompileReport {
+                    failed_files: Vec::new(),
+                    failed_files_truncated: report.failed_files_truncated + report.failed_files.len(),
+                    ..report.clone()
+                })
+                .collect(),
+        };
+        let mut remaining = max_size.saturating_sub(serde_json::to_string(&truncated)?.len());
+        'reports: for (report, truncated_report) in self.reports.iter().zip(&mut truncated.reports) {
+            for failed_file in &report.failed_files {
+                // the file and a comma
+                let size = serde_json::to_string(failed_file)?.len() + 1;
+                if size > remaining {
+                    break 'reports;
+                }
+                remaining -= size;
+                truncated_report.failed_files.push(failed_file.clone());
+                truncated_report.failed_files_truncated -= 1;
+            }
+        }
+        serde_json::to_string(&truncated)
+    }
+}
+
+/// Latest documentation compilation of an assembly
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DocsCompileReport {
+    #[serde(rename = "AssemblyName")]
+    pub assembly_name: String,
+    #[serde(rename = "FilesTotal")]
+    pub files_total: usize,
+    /// Files whose documentation was extracted
+    #[serde(rename = "FilesCompiled")]
+    pub files_compiled: usize,
+    /// Files that couldn't be read or have syntax errors, their documentation is missing
+    #[serde(rename = "FailedFiles")]
+    pub failed_files: Vec<FailedSourceFile>,
+    /// Failed files left out of `failed_files` to keep the report small
+    #[serde(rename = "FailedFilesTruncated", default)]
+    pub failed_files_truncated: usize,
+    #[serde(rename = "TypeCount")]
+    pub type_count: usize,
+    #[serde(rename = "DurationMs")]
+    pub duration_ms: u64,
+    /// Why the whole assembly failed to compile, e.g. its .csproj can't be read
+    #[serde(rename = "ErrorMessage")]
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FailedSourceFile {
+    /// Absolute path of the source file
+    #[serde(rename = "Path")]
+    pub path: String,
+    #[serde(rename = "Reason")]
+    pub reason: String,
+}
+
+/// Handshake request, the payload can also be empty
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProtocolVersionRequest {
+    /// Protocol version the client was written for
+    #[serde(rename = "ProtocolVersion", default)]
+    pub protocol_version: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ProtocolVersionResponse {
+    #[serde(rename = "ProtocolVersion")]
+    pub protocol_version: u32,
+    /// Version of the server program
+    #[serde(rename = "ServerVersion")]
+    pub server_version: String,
+    /// Whether the client's protocol version is supported, `true` if the client didn't send one
+    #[serde(rename = "IsCompatible")]
+    pub is_compatible: bool,
+}
+
+impl ProtocolVersionResponse {
+    pub fn for_request(request: &ProtocolVersionRequest) -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            server_version: env!("CARGO_PKG_VERSION").to_string(),
+            is_compatible: request.protocol_version.is_none_or(|version| version == PROTOCOL_VERSION),
+        }
+    }
+}
+
+/// Payloads of a message type, by schema definition name
+struct MessageSchema {
+    message_type: MessageType,
+    request: Option<&'static str>,
+    response: Option<&'static str>,
+    description: &'static str,
+}
+
+const MESSAGE_SCHEMAS: [MessageSchema; 15] = [
+    MessageSchema {
+        message_type: MessageType::None,
+        request: None,
+        response: None,
+        description: "Does nothing and has no response, keeps the client alive",
+    },
+    MessageSchema {
+        message_type: MessageType::GetUnityState,
+        request: None,
+        response: Some("ProcessState"),
+        description: "State of the Unity process, also sent when it changes",
+    },
+    MessageSchema {
+        message_type: MessageType::GetSymbolDocs,
+        request: Some("SymbolDocsRequest"),
+        response: Some("SymbolDocsResponse"),
+        description: "XML documentation of a C# symbol",
+    },
+    MessageSchema {
+        message_type: MessageType::GetCompileErrors,
+        request: None,
+        response: Some("CompileErrors"),
+        description: "C# compile errors of the latest compilation, also sent when they change",
+    },
+    MessageSchema {
+        message_type: MessageType::GetSymbolAtPosition,
+        request: Some("SymbolAtPositionRequest"),
+        response: Some("SymbolAtPositionResponse"),
+        description: "C# symbol declared at a position of a source file",
+    },
+    MessageSchema {
+        message_type: MessageType::GetProtocolVersion,
+        request: Some("ProtocolVersionRequest"),
+        response: Some("ProtocolVersionResponse"),
+        description: "Protocol version handshake",
+    },
+    MessageSchema {
+        message_type: MessageType::EditorEvent,
+        request: None,
+        response: Some("EditorEvent"),
+        description: "Only sent by the server, when the Unity Editor of the project starts, gains or loses focus, or quits",
+    },
+    MessageSchema {
+        message_type: MessageType::GetAssetsToImport,
+        request: Some("AssetsToImportRequest"),
+        response: Some("AssetsToImportResponse"),
+        description: "Which of the saved files Unity should import, the editor asks Unity to import them",
+    },
+    MessageSchema {
+        message_type: MessageType::FileChanges,
+        request: Some("FileChangesSubscription"),
+        response: Some("FileChangesBatch"),
+        description: "Subscribe to changes of project files, the response is empty, changes are then sent in batches",
+    },
+    MessageSchema {
+        message_type: MessageType::GetUnityMessages,
+        request: Some("UnityMessagesRequest"),
+        response: Some("UnityMessagesResponse"),
+        description: "Methods Unity calls on a class derived from MonoBehaviour, ScriptableObject, Editor or EditorWindow",
+    },
+    MessageSchema {
+        message_type: MessageType::DocsCompileProgress,
+        request: None,
+        response: Some("DocsCompileProgress"),
+        description: "Only sent by the server, while documentation of an assembly compiles for longer than a moment",
+    },
+    MessageSchema {
+        message_type: MessageType::GetDocsCompileReports,
+        request: Some("DocsCompileReportsRequest"),
+        response: Some("DocsCompileReportsResponse"),
+        description: "Reports of the latest documentation compilation of assemblies, with the files that failed to parse",
+    },
+    MessageSchema {
+        message_type: MessageType::StylePatches,
+        request: Some("FileChangesSubscription"),
+        response: Some("StylePatches"),
+        description: "Subscribe to style changes of saved style sheets, the response is empty, changes are then sent as patches",
+    },
+    MessageSchema {
+        message_type: MessageType::SlowAssetImport,
+        request: None,
+        response: Some("AssetImport"),
+        description: "Only sent by the server, when Unity was slow to import an asset after GetAssetsToImport",
+    },
+    MessageSchema {
+        message_type: MessageType::GetAssetImports,
+        request: None,
+        response: Some("AssetImportsResponse"),
+        description: "The latest imports of assets after GetAssetsToImport, with how long they took",
+    },
+];
+
+fn object_schema(description: &str, properties: &[(&str, Value)], required: &[&str]) -> Value {
+    let properties: serde_json::Map<String, Value> =
+        properties.iter().map(|(name, schema)| (name.to_string(), schema.clone())).collect();
+    json!({
+        "type": "object",
+        "description": description,
+        "properties": properties,
+        "required": required,
+    })
+}
+
+fn nullable(type_name: &str) -> Value {
+    json!({ "type": [type_name, "null"] })
+}
+
+fn definitions() -> BTreeMap<&'static str, Value> {
+    let string = json!({ "type": "string" });
+    let boolean = json!({ "type": "boolean" });
+    let uint = json!({ "type": "integer", "minimum": 0 });
+
+    BTreeMap::from([
+        (
+            "ProcessState",
+            object_schema(
+                "State of the Unity process of the project",
+                &[("UnityProcessId", json!({ "type": "integer", "minimum": 0, "description": "0 if Unity is not running" })), ("IsHotReloadEnabled", boolean.clone())],
+                &["UnityProcessId", "IsHotReloadEnabled"],
+            ),
+        ),
+        (
+            "SymbolDocsRequest",
+            object_schema(
+                "Either AssemblyName or SourceFilePath must be provided, AssemblyName takes precedence",
+                &[("SymbolName", string.clone()), ("AssemblyName", nullable("string")), ("SourceFilePath", nullable("string"))],
+                &["SymbolName"],
+            ),
+        ),
+        (
+            "SymbolDocsResponse",
+            object_schema(
+                "Documentation of a symbol",
+                &[
+                    ("Success", boolean.clone()),
+                    ("Documentation", nullable("string")),
+                    ("ErrorMessage", nullable("string")),
+                    ("FoundSymbolName", nullable("string")),
+                    ("InheritedFromSymbolName", nullable("string")),
+                    ("SourceFilePath", nullable("string")),
+                    ("Line", nullable("integer")),
+                    ("DocumentationUrl", nullable("string")),
+                ],
+                &["Success"],
+            ),
+        ),
+        (
+            "CompileErrors",
+            object_schema(
+                "Diagnostics of the latest compilation",
+                &[
+                    ("HasErrors", boolean.clone()),
+                    ("Truncated", json!({ "type": "boolean", "description": "Diagnostics were left out to fit in a message" })),
+                    (
+                        "Files",
+                        json!({
+                            "type": "object",
+                            "description": "LSP diagnostics by absolute file path",
+                            "additionalProperties": { "type": "array", "items": { "$ref": "#/definitions/Diagnostic" } },
+                        }),
+                    ),
+                ],
+                &["HasErrors", "Truncated", "Files"],
+            ),
+        ),
+        (
+            "Diagnostic",
+            json!({
+                "type": "object",
+                "description": "A diagnostic as defined by the Language Server Protocol, positions are 0-based",
+            }),
+        ),
+        (
+            "SymbolAtPositionRequest",
+            object_schema(
+                "Position in a C# source file, 0-based",
+                &[("SourceFilePath", string.clone()), ("Line", uint.clone()), ("Column", uint.clone())],
+                &["SourceFilePath", "Line", "Column"],
+            ),
+        ),
+        (
+            "SymbolAtPositionResponse",
+            object_schema(
+                "Symbol declared at the position",
+                &[
+                    ("Success", boolean.clone()),
+                    ("SymbolName", nullable("string")),
+                    ("TypeName", nullable("string")),
+                    ("MemberName", nullable("string")),
+                    ("AssemblyName", nullable("string")),
+                    ("ErrorMessage", nullable("string")),
+                ],
+                &["Success"],
+            ),
+        ),
+        (
+            "EditorEvent",
+            object_schema(
+                "Lifecycle event of the Unity Editor of the project",
+                &[
+                    (
+                        "Event",
+                        json!({ "type": "string", "enum": EditorEventKind::ALL.map(|kind| format!("{:?}", kind)) }),
+                    ),
+                    ("UnityProcessId", uint.clone()),
+                ],
+                &["Event", "UnityProcessId"],
+            ),
+        ),
+        (
+            "AssetsToImportRequest",
+            object_schema(
+                "Files an editor saved",
+                &[("Paths", json!({ "type": "array", "items": { "type": "string" }, "description": "Absolute paths" }))],
+                &["Paths"],
+            ),
+        ),
+        (
+            "AssetsToImportResponse",
+            object_schema(
+                "Assets Unity should import",
+                &[
+                    ("UnityProcessId", json!({ "type": "integer", "minimum": 0, "description": "0 if Unity is not running" })),
+                    (
+                        "AssetPaths",
+                        json!({ "type": "array", "items": { "type": "string" }, "description": "Paths relative to the project, e.g. Assets/UI/Main.uss" }),
+                    ),
+                    ("Skipped", json!({ "type": "array", "items": { "$ref": "#/definitions/SkippedAsset" } })),
+                    ("ErrorMessage", nullable("string")),
+                ],
+                &["UnityProcessId", "AssetPaths", "Skipped"],
+            ),
+        ),
+        (
+            "SkippedAsset",
+            object_schema(
+                "A saved file that Unity doesn't need to import",
+                &[("Path", string.clone()), ("Reason", string.clone())],
+                &["Path", "Reason"],
+            ),
+        ),
+        (
+            "AssetImport",
+            object_schema(
+                "An import of an asset after GetAssetsToImport",
+                &[
+                    ("AssetPath", json!({ "type": "string", "description": "Path relative to the project, e.g. Assets/UI/Main.uss" })),
+                    ("DurationMs", json!({ "type": "integer", "minimum": 0, "description": "Time from the request until Unity imported the asset" })),
+                    ("ImportMs", json!({ "type": ["integer", "null"], "description": "Time Unity spent importing the asset" })),
+                    ("IsSlow", boolean.clone()),
+                    ("TimedOut", json!({ "type": "boolean", "description": "Unity didn't import the asset in time" })),
+                    ("Timestamp", json!({ "type": "integer", "minimum": 0, "description": "Milliseconds since the Unix epoch" })),
+                ],
+                &["AssetPath", "DurationMs", "IsSlow", "TimedOut", "Timestamp"],
+            ),
+        ),
+        (
+            "AssetImportsResponse",
+            object_schema(
+                "The latest imports of assets after GetAssetsToImport",
+                &[
+                    ("Imports", json!({ "type": "array", "items": { "$ref": "#/definitions/AssetImport" }, "description": "Oldest first" })),
+                    ("SlowThresholdMs", uint.clone()),
+                ],
+                &["Imports", "SlowThresholdMs"],
+            ),
+        ),
+        (
+            "FileChangesSubscription",
+            object_schema(
+                "Which file changes the client is sent",
+                &[
+                    (
+                        "Patterns",
+                        json!({ "type": "array", "items": { "type": "string" }, "description": "Glob patterns of paths relative to the project, empty to unsubscribe" }),
+                    ),
+                    ("DebounceMs", nullable("integer")),
+                ],
+                &["Patterns"],
+            ),
+        ),
+        (
+            "FileChangesBatch",
+            object_schema(
+                "Coalesced changes of the files matching the subscription",
+                &[
+                    ("Changes", json!({ "type": "array", "items": { "$ref": "#/definitions/FileChange" } })),
+                    ("ErrorMessage", nullable("string")),
+                ],
+                &["Changes"],
+            ),
+        ),
+        (
+            "FileChange",
+            object_schema(
+                "What happened to a file",
+                &[
+                    ("Path", json!({ "type": "string", "description": "Relative to the project, e.g. Assets/UI/Main.uss" })),
+                    ("Kind", json!({ "type": "string", "enum": FileChangeKind::ALL.map(|kind| format!("{:?}", kind)) })),
+                ],
+                &["Path", "Kind"],
+            ),
+        ),
+        (
+            "StylePatches",
+            object_schema(
+                "Style changes of saved style sheets matching the subscription",
+                &[
+                    ("Patches", json!({ "type": "array", "items": { "$ref": "#/definitions/StylePatch" } })),
+                    ("ErrorMessage", nullable("string")),
+                ],
+                &["Patches"],
+            ),
+        ),
+        (
+            "StylePatch",
+            object_schema(
+                "Style changes of a saved style sheet since it was last sent",
+                &[
+                    ("Path", json!({ "type": "string", "description": "Relative to the project, e.g. Assets/UI/Main.uss" })),
+                    ("Rules", json!({ "type": "array", "items": { "$ref": "#/definitions/RulePatch" } })),
+                    (
+                        "RequiresImport",
+                        json!({ "type": "boolean", "description": "The changes can't be applied as a patch, the style sheet must be imported" }),
+                    ),
+                ],
+                &["Path", "Rules", "RequiresImport"],
+            ),
+        ),
+        (
+            "RulePatch",
+            object_schema(
+                "A rule that was added, removed or has changed declarations",
+                &[
+                    ("Selector", json!({ "type": "string", "description": "Selectors of the rule, separated by ', '" })),
+                    ("Kind", json!({ "type": "string", "enum": RulePatchKind::ALL.map(|kind| format!("{:?}", kind)) })),
+                    ("Declarations", json!({ "type": "array", "items": { "$ref": "#/definitions/DeclarationPatch" } })),
+                ],
+                &["Selector", "Kind", "Declarations"],
+            ),
+        ),
+        (
+            "DeclarationPatch",
+            object_schema(
+                "A declaration to set or remove in a rule",
+                &[
+                    ("Property", string.clone()),
+                    ("Value", json!({ "type": ["string", "null"], "description": "null if the declaration was removed" })),
+                    ("Important", boolean.clone()),
+                ],
+                &["Property", "Value", "Important"],
+            ),
+        ),
+        (
+            "UnityMessagesRequest",
+            object_schema(
+                "Either BaseTypeName or SourceFilePath must be provided, BaseTypeName takes precedence",
+                &[
+                    ("BaseTypeName", nullable("string")),
+                    ("SourceFilePath", nullable("string")),
+                    ("Line", json!({ "type": "integer", "minimum": 0, "description": "0-based line in the class" })),
+                    ("Column", json!({ "type": "integer", "minimum": 0, "description": "0-based, in characters" })),
+                ],
+                &[],
+            ),
+        ),
+        (
+            "UnityMessagesResponse",
+            object_schema(
+                "Messages the class doesn't declare yet",
+                &[
+                    ("Success", boolean.clone()),
+                    ("BaseTypeName", nullable("string")),
+                    ("Messages", json!({ "type": "array", "items": { "$ref": "#/definitions/UnityMessageInfo" } })),
+                    ("ErrorMessage", nullable("string")),
+                ],
+                &["Success", "Messages"],
+            ),
+        ),
+        (
+            "UnityMessageInfo",
+            object_schema(
+                "A method Unity calls",
+                &[
+                    ("Name", string.clone()),
+                    ("Signature", json!({ "type": "string", "description": "e.g. void OnCollisionEnter(Collision collision)" })),
+                    ("Summary", string.clone()),
+                    ("DocumentationUrl", string.clone()),
+                ],
+                &["Name", "Signature", "Summary", "DocumentationUrl"],
+            ),
+        ),
+        (
+            "DocsCompileProgress",
+            object_schema(
+                "Files of an assembly whose documentation is being compiled",
+                &[
+                    ("AssemblyName", string.clone()),
+                    ("FilesDone", uint.clone()),
+                    ("FilesTotal", uint.clone()),
+                    ("Finished", boolean.clone()),
+                ],
+                &["AssemblyName", "FilesDone", "FilesTotal", "Finished"],
+            ),
+        ),
+        (
+            "DocsCompileReportsRequest",
+            object_schema(
+                "Which reports to get, the payload can be empty",
+                &[("AssemblyName", json!({ "type": ["string", "null"], "description": "All reports if not set" }))],
+                &[],
+            ),
+        ),
+        (
+            "DocsCompileReportsResponse",
+            object_schema(
+                "Reports of the assemblies compiled since the server started",
+                &[("Reports", json!({ "type": "array", "items": { "$ref": "#/definitions/DocsCompileReport" } }))],
+                &["Reports"],
+            ),
+        ),
+        (
+            "DocsCompileReport",
+            object_schema(
+                "Latest documentation compilation of an assembly",
+                &[
+                    ("AssemblyName", string.clone()),
+                    ("FilesTotal", uint.clone()),
+                    ("FilesCompiled", uint.clone()),
+                    ("FailedFiles", json!({ "type": "array", "items": { "$ref": "#/definitions/FailedSourceFile" } })),
+                    (
+                        "FailedFilesTruncated",
+                        json!({ "type": "integer", "minimum": 0, "description": "Failed files left out of FailedFiles" }),
+                    ),
+                    ("TypeCount", uint.clone()),
+                    ("DurationMs", uint.clone()),
+                    ("ErrorMessage", nullable("string")),
+                ],
+                &[
+                    "AssemblyName",
+                    "FilesTotal",
+                    "FilesCompiled",
+                    "FailedFiles",
+                    "FailedFilesTruncated",
+                    "TypeCount",
+                    "DurationMs",
+                ],
+            ),
+        ),
+        (
+            "FailedSourceFile",
+            object_schema(
+                "A source file whose documentation is missing",
+                &[("Path", json!({ "type": "string", "description": "Absolute path" })), ("Reason", string.clone())],
+                &["Path", "Reason"],
+            ),
+        ),
+        (
+            "ProtocolVersionRequest",
+            object_schema("Protocol version of the client, the payload can be empty", &[("ProtocolVersion", nullable("integer"))], &[]),
+        ),
+        (
+            "ProtocolVersionResponse",
+            object_schema(
+                "Protocol version of the server",
+                &[("ProtocolVersion", uint), ("ServerVersion", string), ("IsCompatible", boolean)],
+                &["ProtocolVersion", "ServerVersion", "IsCompatible"],
+            ),
+        ),
+    ])
+}
+
+/// JSON schema of the message payloads
+///
+/// Message types and their payloads are listed in the `messages` extension of the schema.
+pub fn protocol_schema() -> Value {
+    let messages: Vec<Value> = MESSAGE_SCHEMAS
+        .iter()
+        .map(|message| {
+            let reference = |name: Option<&str>| name.map(|name| json!({ "$ref": format!("#/definitions/{}", name) }));
+            json!({
+                "type": message.message_type as u8,
+                "name": format!("{:?}", message.message_type),
+                "description": message.description,
+                "request": reference(message.request),
+                "response": reference(message.response),
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Unity Code Native messaging protocol",
+        "protocolVersion": PROTOCOL_VERSION,
+        "messages": messages,
+        "definitions": definitions(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cs::compile_errors::CompileErrors;
+
+    #[test]
+    fn test_encode_decode_message() {
+        let message = Message {
+            message_type: MessageType::GetSymbolDocs,
+            request_id: 42,
+            payload: r#"{"SymbolName":"Foo"}"#,
+        };
+        let data = message.encode();
+        assert_eq!(data.len(), HEADER_SIZE + message.payload.len());
+        assert_eq!(Message::decode(&data), Ok(message));
+
+        assert_eq!(Message::decode(&data[..5]), Err(ProtocolError::TooShort(5)));
+        assert_eq!(
+            Message::decode(&data[..12]),
+            Err(ProtocolError::PayloadLengthMismatch { expected: 20, actual: 3 })
+        );
+    }
+
+    #[test]
+    fn test_protocol_version_handshake() {
+        let response = ProtocolVersionResponse::for_request(&serde_json::from_str("{}").unwrap());
+        assert_eq!(response.protocol_version, PROTOCOL_VERSION);
+        assert!(response.is_compatible);
+
+        let request = ProtocolVersionRequest {
+            protocol_version: Some(PROTOCOL_VERSION + 1),
+        };
+        assert!(!ProtocolVersionResponse::for_request(&request).is_compatible);
+    }
+
+    #[test]
+    fn test_docs_compile_reports_payload() {
+        let report = |assembly_name: &str, failed: usize| DocsCompileReport {
+            assembly_name: assembly_name.to_string(),
+            failed_files: (0..failed)
+                .map(|i| FailedSourceFile { path: format!("/Project/Assets/File{}.cs", i), reason: "syntax errors".to_string() })
+                .collect(),
+            ..Default::default()
+        };
+        let response = DocsCompileReportsResponse { reports: vec![report("Game", 2000), report("Tools", 2000)] };
+
+        let json = response.payload(MAX_PAYLOAD_SIZE).unwrap();
+        assert!(json.len() <= MAX_PAYLOAD_SIZE);
+        let truncated: DocsCompileReportsResponse = serde_json::from_str(&json).unwrap();
+        let game = &truncated.reports[0];
+        assert!(!game.failed_files.is_empty());
+        assert_eq!(game.failed_files.len() + game.failed_files_truncated, 2000);
+        assert_eq!(game.failed_files[0], response.reports[0].failed_files[0]);
+        // the reports that come last lose their failed files first
+        assert!(truncated.reports[1].failed_files.is_empty());
+        assert_eq!(truncated.reports[1].failed_files_truncated, 2000);
+
+        let small = DocsCompileReportsResponse { reports: vec![report("Game", 2)] };
+        assert_eq!(small.payload(MAX_PAYLOAD_SIZE).unwrap(), serde_json::to_string(&small).unwrap());
+    }
+
+    /// The schema is written by hand, it must list the same fields as the serialized types
+    #[test]
+    fn test_schema_matches_payload_types() {
+        let samples = [
+            ("ProcessState", serde_json::to_value(ProcessState { unity_process_id: 0, is_hot_reload_enabled: false })),
+            (
+                "SymbolDocsRequest",
+                serde_json::to_value(SymbolDocsRequest { symbol_name: String::new(), assembly_name: None, source_file_path: None }),
+            ),
+            ("SymbolDocsResponse", serde_json::to_value(SymbolDocsResponse::default())),
+            ("CompileErrors", serde_json::to_value(CompileErrors::default())),
+            (
+                "SymbolAtPositionRequest",
+                serde_json::to_value(SymbolAtPositionRequest { source_file_path: String::new(), line: 0, column: 0 }),
+            ),
+            ("SymbolAtPositionResponse", serde_json::to_value(SymbolAtPositionResponse::default())),
+            ("EditorEvent", serde_json::to_value(EditorEvent { event: EditorEventKind::Started, unity_process_id: 1 })),
+            ("AssetsToImportRequest", serde_json::to_value(AssetsToImportRequest::default())),
+            ("AssetsToImportResponse", serde_json::to_value(AssetsToImportResponse::default())),
+            ("SkippedAsset", serde_json::to_value(SkippedAsset { path: String::new(), reason: String::new() })),
+            ("AssetImport", serde_json::to_value(AssetImport::default())),
+            ("AssetImportsResponse", serde_json::to_value(AssetImportsResponse::default())),
+            ("FileChangesSubscription", serde_json::to_value(FileChangesSubscription::default())),
+            ("FileChangesBatch", serde_json::to_value(FileChangesBatch::default())),
+            (
+                "FileChange",
+                serde_json::to_value(FileChange { path: String::new(), kind: FileChangeKind::Created }),
+            ),
+            ("StylePatches", serde_json::to_value(StylePatches::default())),
+            ("StylePatch", serde_json::to_value(StylePatch::default())),
+            (
+                "RulePatch",
+                serde_json::to_value(RulePatch { selector: String::new(), kind: RulePatchKind::Added, declarations: Vec::new() }),
+            ),
+            (
+                "DeclarationPatch",
+                serde_json::to_value(DeclarationPatch { property: String::new(), value: None, important: false }),
+            ),
+            ("UnityMessagesRequest", serde_json::to_value(UnityMessagesRequest::default())),
+            ("UnityMessagesResponse", serde_json::to_value(UnityMessagesResponse::default())),
+            (
+                "UnityMessageInfo",
+                serde_json::to_value(UnityMessageInfo {
+                    name: String::new(),
+                    signature: String::new(),
+                    summary: String::new(),
+                    documentation_url: String::new(),
+                }),
+            ),
+            ("DocsCompileProgress", serde_json::to_value(DocsCompileProgress::default())),
+            ("DocsCompileReportsRequest", serde_json::to_value(DocsCompileReportsRequest::default())),
+            ("DocsCompileReportsResponse", serde_json::to_value(DocsCompileReportsResponse::default())),
+            ("DocsCompileReport", serde_json::to_value(DocsCompileReport::default())),
+            (
+                "FailedSourceFile",
+                serde_json::to_value(FailedSourceFile { path: String::new(), reason: String::new() }),
+            ),
+            ("ProtocolVersionRequest", serde_json::to_value(ProtocolVersionRequest::default())),
+            ("ProtocolVersionResponse", serde_json::to_value(ProtocolVersionResponse::for_request(&Default::default()))),
+        ];
+
+        let schema = protocol_schema();
+        for (name, sample) in samples {
+            let sample = sample.unwrap();
+            let mut fields: Vec<&String> = sample.as_object().unwrap().keys().collect();
+            let mut properties: Vec<&String> =
+                schema["definitions"][name]["properties"].as_object().unwrap().keys().collect();
+            fields.sort();
+            properties.sort();
+            assert_eq!(fields, properties, "Schema of {} is out of date", name);
+        }
+
+        let event_kinds: Vec<_> =
+            EditorEventKind::ALL.iter().map(|kind| serde_json::to_value(kind).unwrap()).collect();
+        assert_eq!(schema["definitions"]["EditorEvent"]["properties"]["Event"]["enum"], json!(event_kinds));
+        let change_kinds: Vec<_> =
+            FileChangeKind::ALL.iter().map(|kind| serde_json::to_value(kind).unwrap()).collect();
+        assert_eq!(schema["definitions"]["FileChange"]["properties"]["Kind"]["enum"], json!(change_kinds));
+        let patch_kinds: Vec<_> =
+            RulePatchKind::ALL.iter().map(|kind| serde_json::to_value(kind).unwrap()).collect();
+        assert_eq!(schema["definitions"]["RulePatch"]["properties"]["Kind"]["enum"], json!(patch_kinds));
+
+        let messages = schema["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), MessageType::ALL.len());
+        for (message, message_type) in messages.iter().zip(MessageType::ALL) {
+            assert_eq!(message["type"], message_type as u8);
+            for payload in ["request", "response"] {
+                if let Some(reference) = message[payload]["$ref"].as_str() {
+                    let name = reference.trim_start_matches("#/definitions/");
+                    assert!(schema["definitions"].get(name).is_some(), "{} is not defined", name);
+                }
+            }
+        }
+    }
+}
@@ -1,75 +1,50 @@
 use std::{
     collections::HashMap,
+    future::Future,
     io,
     path::PathBuf,
+    sync::Arc,
     time::{Duration, Instant},
 };
 
-use serde::{Deserialize, Serialize};
-
 use tokio::{
     net::UdpSocket,
+    sync::Mutex,
+    task::JoinHandle,
     time::interval,
 };
 use log::{debug, error, info, warn};
-use crate::monitor::ProcessMonitor;
-use crate::cs::docs_manager::CsDocsManager;
-
-#[derive(Debug, Clone, Copy, PartialEq)]
-#[repr(u8)]
-pub enum MessageType {
-    None = 0,
-    GetUnityState = 1,
-    GetSymbolDocs = 2,
-}
-
-impl From<u8> for MessageType {
-    fn from(value: u8) -> Self {
-        match value {
-            0 => MessageType::None,
-            1 => MessageType::GetUnityState,
-            2 => MessageType::GetSymbolDocs,
-            _ => MessageType::None,
-        }
-    }
-}
-
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq)]
-pub struct ProcessState {
-    #[serde(rename = "UnityProcessId")]
-    pub unity_process_id: u32,
-    #[serde(rename = "IsHotReloadEnabled")]
-    pub is_hot_reload_enabled: bool,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SymbolDocsRequest {
-    #[serde(rename = "SymbolName")]
-    pub symbol_name: String,
-    #[serde(rename = "AssemblyName")]
-    pub assembly_name: Option<String>,
-    #[serde(rename = "SourceFilePath")]
-    pub source_file_path: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SymbolDocsResponse {
-    #[serde(rename = "Success")]
-    pub success: bool,
-    #[serde(rename = "Documentation")]
-    pub documentation: Option<String>,
-    #[serde(rename = "ErrorMessage")]
-    pub error_message: Option<String>,
-    #[serde(rename = "FoundSymbolName")]
-    pub found_symbol_name: Option<String>,
-    #[serde(rename = "InheritedFromSymbolName")]
-    pub inherited_from_symbol_name: Option<String>,
-}
+use crate::asset_import::assets_to_import;
+use crate::import_timing::{ImportTracker, SLOW_IMPORT_THRESHOLD};
+use crate::file_events::{FileSubscription, ProjectFileWatcher, MAX_CHANGES_PER_MESSAGE};
+use crate::monitor::{lifecycle_events, ProcessMonitor};
+use crate::style_patches::{patch_payload, read_style_sheets, StylePatchSubscription};
+use crate::cs::docs_manager::{CsDocsManager, DocsCompileStatus};
+use crate::cs::compile_errors::{default_editor_log_path, CompileErrorWatcher};
+use crate::cs::unity_messages::unity_messages;
+use crate::language::progress::REPORT_INTERVAL;
+use crate::language::request_timing;
+use crate::unity_project_manager::UnityProjectManager;
+use crate::protocol::{
+    AssetImportsResponse, AssetsToImportRequest, AssetsToImportResponse, DocsCompileProgress, DocsCompileReportsRequest, DocsCompileReportsResponse, EditorEvent, EditorEventKind, FileChange, FileChangesBatch, FileChangesSubscription, Message, MessageType, MAX_PAYLOAD_SIZE, ProcessState, ProtocolVersionRequest, ProtocolVersionResponse, StylePatch, StylePatches, SymbolAtPositionRequest,
+    SymbolAtPositionResponse, SymbolDocsRequest, SymbolDocsResponse, UnityMessagesRequest, UnityMessagesResponse,
+};
 
 /// Time interval for periodic detect Unity when Unity is not yet detected
 /// Note that it takes 30 seconds or more to start Unity Editor, so we don't need to detect Unity too frequently
 const DETECT_UNITY_INTERVAL: Duration = Duration::from_secs(10);
 
+/// Time interval for applying C# source changes to the docs cache
+/// Saving a file can cause several change events, so they are batched
+const DOCS_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Time interval for sending file changes to subscribed clients, changes are sent after their debounce time
+const FILE_CHANGES_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Time interval for reading asset imports from the Editor log, only while imports are pending
+/// Short, the durations of the imports are measured when they are read
+const IMPORT_TRACKING_INTERVAL: Duration = Duration::from_millis(100);
+
 /// Time interval for cleaning up inactive clients
 const CLEANUP_INTERVAL: Duration = Duration::from_secs(5);
 
@@ -77,20 +52,45 @@ const CLEANUP_INTERVAL: Duration = Duration::from_secs(5);
 /// Typically this will not do all processes refresh, only refresh processes that we care about and already detected
 const MONITOR_INTERVAL: Duration = Duration::from_millis(500);
 
+/// Maximum size of a message, requests with many paths can be large
+const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
 struct ClientInfo {
     last_message_time: Instant,
+    /// File changes the client subscribed to
+    file_changes: Option<FileSubscription>,
+    /// Style sheets the client is sent the style changes of
+    style_patches: Option<StylePatchSubscription>,
 }
 
 pub struct Server {
-    socket: UdpSocket,
+    socket: Arc<UdpSocket>,
+    project_path: PathBuf,
     clients: HashMap<std::net::SocketAddr, ClientInfo>,
     monitor: ProcessMonitor,
     last_monitor_update: Instant,
-    docs_manager: CsDocsManager,
+    /// Shared with the task that compiles documentation in the background
+    docs_manager: Arc<Mutex<CsDocsManager>>,
+    /// Progress of the documentation the docs manager compiles, broadcast while a compilation takes a while,
+    /// and the reports of past compilations
+    docs_compile_status: DocsCompileStatus,
+    /// Task applying source changes to the docs and compiling invalidated docs, see [`Server::refresh_docs`]
+    docs_refresh: Option<JoinHandle<()>>,
+    /// Reads the Unity version of the project, shares its cached version with the docs manager
+    unity_project: UnityProjectManager,
+    /// Compile errors from the Editor log, `None` if the log location is unknown on this platform
+    compile_errors: Option<CompileErrorWatcher>,
+    /// Imports of the assets clients were told to import, `None` if the Editor log location is unknown
+    import_tracker: Option<ImportTracker>,
+    /// Editor lifecycle events not yet broadcast
+    pending_editor_events: Vec<EditorEvent>,
+    /// Watches project files while a client is subscribed to their changes or style patches
+    file_watcher: Option<ProjectFileWatcher>,
 }
 
 impl Server {
-    pub async fn new(project_path: String) -> io::Result<Self> {
+    /// `docs_manager` is shared with the USS language server, which reads the docs compiled here
+    pub async fn new(project_path: String, docs_manager: Arc<Mutex<CsDocsManager>>) -> io::Result<Self> {
         let pid = std::process::id();
         let port = 50000 + (pid % 1000);
         let addr = format!("127.0.0.1:{}", port);
@@ -100,22 +100,37 @@ impl Server {
         info!("Server listening on {}", addr);
 
         let unity_project_root = PathBuf::from(&project_path);
-        let docs_manager = CsDocsManager::new(unity_project_root)
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("Failed to create docs manager: {}", e)))?;
+        let (docs_compile_status, unity_project) = {
+            let mut docs_manager = docs_manager.lock().await;
+            docs_manager.watch_sources();
+            (docs_manager.compile_status(), docs_manager.unity_project_manager().clone())
+        };
 
         Ok(Server {
-            socket,
+            socket: Arc::new(socket),
+            project_path: unity_project_root.clone(),
             clients: HashMap::new(),
             monitor: ProcessMonitor::new(project_path),
             last_monitor_update: Instant::now() - DETECT_UNITY_INTERVAL, // we want to update immediately
             docs_manager,
+            docs_compile_status,
+            docs_refresh: None,
+            unity_project,
+            compile_errors: default_editor_log_path()
+                .map(|log_path| CompileErrorWatcher::new(unity_project_root, log_path)),
+            import_tracker: default_editor_log_path().map(ImportTracker::new),
+            pending_editor_events: Vec::new(),
+            file_watcher: None,
         })
     }
 
     pub async fn run(&mut self) {
-        let mut buffer = [0u8; 1024];
+        let mut buffer = vec![0u8; MAX_MESSAGE_SIZE];
         let mut cleanup_interval = interval(CLEANUP_INTERVAL);
         let mut monitor_interval = interval(MONITOR_INTERVAL);
+        let mut docs_refresh_interval = interval(DOCS_REFRESH_INTERVAL);
+        let mut file_changes_interval = interval(FILE_CHANGES_INTERVAL);
+        let mut import_tracking_interval = interval(IMPORT_TRACKING_INTERVAL);
 
         loop {
             tokio::select! {
@@ -136,22 +151,86 @@ impl Server {
                     self.cleanup_inactive_clients();
                 }
                 
+                // Send batches of file changes whose debounce time passed
+                _ = file_changes_interval.tick() => {
+                    self.send_file_changes().await;
+                }
+
+                // Time the imports of assets clients were told to import
+                _ = import_tracking_interval.tick() => {
+                    self.update_import_tracking().await;
+                }
+
+                // Invalidate docs of changed sources, and compile them again one assembly at a time
+                // so requests are not blocked for long
+                _ = docs_refresh_interval.tick() => {
+                    self.refresh_docs();
+                }
+
                 // Monitor Unity processes
                 _ = monitor_interval.tick() => {
                     // check if unity is already detected or DETECT_UNITY_INTERVAL is reached
                     // this will make detect new Unity instance slow and find out Unity shutdown fast
-                    if self.last_monitor_update.elapsed() >= DETECT_UNITY_INTERVAL || self.monitor.unity_pid().is_some() {
+                    // unless Unity writes its EditorInstance.json, which is cheap to check
+                    if self.last_monitor_update.elapsed() >= DETECT_UNITY_INTERVAL
+                        || self.monitor.unity_pid().is_some()
+                        || self.monitor.editor_instance_changed()
+                    {
                         // only checks unity
                         if self.monitor_update(false) {
                             info!("state changed to {:?}, broadcast to clients", self.get_process_state());
                             self.broadcast_state().await;
                         }
                     }
+
+                    if let (Some(unity_pid), Some(is_focused)) = (self.monitor.unity_pid(), self.monitor.update_focus()) {
+                        let event = if is_focused { EditorEventKind::Focused } else { EditorEventKind::Unfocused };
+                        self.pending_editor_events.push(EditorEvent { event, unity_process_id: unity_pid.as_u32() });
+                    }
+                    self.broadcast_editor_events().await;
+
+                    // the Editor log is shared by all Unity instances, only follow it while Unity runs our project
+                    if self.monitor.unity_pid().is_some() && self.update_compile_errors() {
+                        info!("compile errors changed, broadcast to clients");
+                        self.broadcast_compile_errors().await;
+                    }
                 }
             }
         }
     }
 
+    /// Apply source changes to the docs and compile the docs of one invalidated assembly, in a task so
+    /// messages are handled while it compiles
+    ///
+    /// Does nothing while the previous refresh is running. The docs manager is only locked to pick the assembly
+    /// and to store its docs, requests for docs are handled while it compiles.
+    fn refresh_docs(&mut self) {
+        if self.docs_refresh.as_ref().is_some_and(|task| !task.is_finished()) {
+            return;
+        }
+        let docs_manager = self.docs_manager.clone();
+        let socket = self.socket.clone();
+        let clients: Vec<std::net::SocketAddr> = self.clients.keys().copied().collect();
+        let status = self.docs_compile_status.clone();
+        self.docs_refresh = Some(tokio::spawn(async move {
+            let job = {
+                let mut docs_manager = docs_manager.lock().await;
+                let affected = docs_manager.process_source_changes().await;
+                if !affected.is_empty() {
+                    info!("sources changed, invalidated docs of {:?}", affected);
+                }
+                docs_manager.next_prewarm().await
+            };
+            let Some(job) = job else {
+                return;
+            };
+            let compiled = track_docs_compile(&socket, &clients, &status, job.compile()).await;
+            let assembly_name = compiled.assembly_name().to_string();
+            docs_manager.lock().await.finish_prewarm(compiled).await;
+            debug!("compiled docs of {} in the background", assembly_name);
+        }));
+    }
+
     /** 
      * update the process monitor
      * 
@@ -161,11 +240,15 @@ impl Server {
         let start = Instant::now();
 
         let old_state = self.get_process_state();
+        let old_unity_pid = self.monitor.unity_pid();
         
         self.monitor.update(is_full);
         self.last_monitor_update = Instant::now();
         
         let new_state = self.get_process_state();
+        for (event, pid) in lifecycle_events(old_unity_pid, self.monitor.unity_pid()) {
+            self.pending_editor_events.push(EditorEvent { event, unity_process_id: pid.as_u32() });
+        }
 
         #[cfg(debug_assertions)]{
             debug!("monitor update took: {:?}, is_full:{}, state is:{:?}", start.elapsed(), is_full, new_state);
@@ -174,162 +257,518 @@ impl Server {
     }
 
     async fn handle_message(&mut self, data: &[u8], addr: std::net::SocketAddr) {
-        if data.len() < 9 {
-            warn!("Invalid message format: too short");
-            return;
-        }
+        let message = match Message::decode(data) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("Invalid message format: {}", e);
+                return;
+            }
+        };
 
         // Update client last message time
-        self.clients.insert(
-            addr,
-            ClientInfo {
+        self.clients
+            .entry(addr)
+            .and_modify(|client| client.last_message_time = Instant::now())
+            .or_insert_with(|| ClientInfo {
                 last_message_time: Instant::now(),
-            },
-        );
+                file_changes: None,
+                style_patches: None,
+            });
 
-        let message_type = MessageType::from(data[0]);
-        let request_id = u32::from_le_bytes([data[1], data[2], data[3], data[4]]);
-        let payload_length = u32::from_le_bytes([data[5], data[6], data[7], data[8]]) as usize;
+        let Message {
+            message_type,
+            request_id,
+            payload,
+        } = message;
 
-        if data.len() < 9 + payload_length {
-            warn!("Invalid message format: payload length mismatch");
-            return;
+        let start = Instant::now();
+        let succeeded = match message_type {
+            // editor events, compile progress and slow imports are only sent by the server
+            MessageType::None | MessageType::EditorEvent | MessageType::DocsCompileProgress | MessageType::SlowAssetImport => true,
+            MessageType::GetUnityState => self.handle_get_unity_state(addr, request_id).await,
+            MessageType::GetSymbolDocs => self.handle_get_symbol_docs(addr, request_id, payload).await,
+            MessageType::GetSymbolAtPosition => self.handle_get_symbol_at_position(addr, request_id, payload).await,
+            MessageType::GetCompileErrors => {
+                self.update_compile_errors();
+                self.send_compile_errors(addr, request_id).await
+            }
+            MessageType::GetProtocolVersion => self.handle_get_protocol_version(addr, request_id, payload).await,
+            MessageType::GetAssetsToImport => self.handle_get_assets_to_import(addr, request_id, payload).await,
+            MessageType::FileChanges => self.handle_subscribe_file_changes(addr, request_id, payload).await,
+            MessageType::GetUnityMessages => self.handle_get_unity_messages(addr, request_id, payload).await,
+            MessageType::GetDocsCompileReports => self.handle_get_docs_compile_reports(addr, request_id, payload).await,
+            MessageType::StylePatches => self.handle_subscribe_style_patches(addr, request_id, payload).await,
+            MessageType::GetAssetImports => self.handle_get_asset_imports(addr, request_id).await,
+        };
+        // a request fails if it was invalid, its lookup failed or the response couldn't be sent
+        request_timing::record(&format!("udp/{:?}", message_type), start.elapsed(), succeeded);
+    }
+
+    async fn handle_get_unity_state(&mut self, addr: std::net::SocketAddr, request_id: u32) -> bool {
+        // Always update monitor when state is requested(full check)
+        let _changed = self.monitor_update(true);
+
+        let sent = self.send_state(addr, request_id).await;
+        self.broadcast_editor_events().await;
+        sent
+    }
+
+    async fn handle_get_symbol_docs(&mut self, addr: std::net::SocketAddr, request_id: u32, payload: &str) -> bool {
+        let failure = |message: String| SymbolDocsResponse {
+            error_message: Some(message),
+            ..Default::default()
+        };
+
+        let response = if payload.is_empty() {
+            failure("Empty request payload".to_string())
+        } else {
+            match serde_json::from_str::<SymbolDocsRequest>(payload) {
+                Ok(request) if request.assembly_name.is_none() && request.source_file_path.is_none() => {
+                    failure("Either AssemblyName or SourceFilePath must be provided".to_string())
+                }
+                Ok(request) => {
+                    let source_file_path = request.source_file_path.as_ref().map(PathBuf::from);
+                    // progress is also sent while waiting for a compilation in the background
+                    let docs = async {
+                        self.docs_manager
+                            .lock()
+                            .await
+                            .get_docs_for_symbol(&request.symbol_name, request.assembly_name.as_deref(), source_file_path.as_deref())
+                            .await
+                    };
+                    let clients: Vec<std::net::SocketAddr> = self.clients.keys().copied().collect();
+                    match track_docs_compile(&self.socket, &clients, &self.docs_compile_status, docs).await {
+                        Ok(doc_result) => SymbolDocsResponse {
+                            success: true,
+                            found_symbol_name: Some(doc_result.symbol_name()),
+                            inherited_from_symbol_name: doc_result.inherited_from_symbol_name(),
+                            documentation: Some(doc_result.xml_doc),
+                            source_file_path: doc_result.source_file_path.map(|path| path.to_string_lossy().to_string()),
+                            line: doc_result.line,
+                            documentation_url: doc_result.documentation_url,
+                            ..Default::default()
+                        },
+                        Err(e) => failure(e.to_string()),
+                    }
+                }
+                Err(e) => failure(format!("Invalid request format: {}", e)),
+            }
+        };
+
+        // Send response
+        match serde_json::to_string(&response) {
+            Ok(json) => self.send_response(MessageType::GetSymbolDocs, request_id, &json, addr).await && response.success,
+            Err(e) => {
+                error!("Error serializing SymbolDocsResponse: {}", e);
+                false
+            }
         }
+    }
+
+    async fn handle_get_symbol_at_position(&mut self, addr: std::net::SocketAddr, request_id: u32, payload: &str) -> bool {
+        let failure = |message: String| SymbolAtPositionResponse {
+            error_message: Some(message),
+            ..Default::default()
+        };
 
-        let payload = if payload_length > 0 {
-            match std::str::from_utf8(&data[9..9 + payload_length]) {
-                Ok(s) => s,
-                Err(_) => {
-                    warn!("Invalid UTF-8 in payload");
-                    return;
+        let response = match serde_json::from_str::<SymbolAtPositionRequest>(payload) {
+            Ok(request) => {
+                let path = PathBuf::from(&request.source_file_path);
+                let symbol = self.docs_manager.lock().await.get_symbol_at_position(&path, request.line, request.column).await;
+                match symbol {
+                    Ok(symbol) => SymbolAtPositionResponse {
+                        success: true,
+                        symbol_name: Some(symbol.symbol_name),
+                        type_name: Some(symbol.type_name),
+                        member_name: symbol.member_name,
+                        assembly_name: symbol.assembly_name,
+                        error_message: None,
+                    },
+                    Err(e) => failure(e.to_string()),
                 }
             }
+            Err(e) => failure(format!("Invalid request format: {}", e)),
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => self.send_response(MessageType::GetSymbolAtPosition, request_id, &json, addr).await && response.success,
+            Err(e) => {
+                error!("Error serializing SymbolAtPositionResponse: {}", e);
+                false
+            }
+        }
+    }
+
+    async fn handle_get_docs_compile_reports(&mut self, addr: std::net::SocketAddr, request_id: u32, payload: &str) -> bool {
+        let (request, valid) = if payload.is_empty() {
+            (DocsCompileReportsRequest::default(), true)
         } else {
-            ""
+            match serde_json::from_str::<DocsCompileReportsRequest>(payload) {
+                Ok(request) => (request, true),
+                Err(e) => {
+                    warn!("Invalid docs compile reports request: {}", e);
+                    (DocsCompileReportsRequest::default(), false)
+                }
+            }
         };
 
-        match message_type {
-            MessageType::None => {
-                // Do nothing
+        // the reports are kept apart from the docs manager, they can be read while docs compile
+        let response = DocsCompileReportsResponse {
+            reports: self.docs_compile_status.reports(request.assembly_name.as_deref()),
+        };
+        match response.payload(MAX_PAYLOAD_SIZE) {
+            Ok(json) => self.send_response(MessageType::GetDocsCompileReports, request_id, &json, addr).await && valid,
+            Err(e) => {
+                error!("Error serializing DocsCompileReportsResponse: {}", e);
+                false
             }
-            MessageType::GetUnityState => {
-                self.handle_get_unity_state(addr, request_id).await;
+        }
+    }
+
+    async fn handle_get_protocol_version(&mut self, addr: std::net::SocketAddr, request_id: u32, payload: &str) -> bool {
+        let (request, valid) = if payload.is_empty() {
+            (ProtocolVersionRequest::default(), true)
+        } else {
+            match serde_json::from_str::<ProtocolVersionRequest>(payload) {
+                Ok(request) => (request, true),
+                Err(e) => {
+                    warn!("Invalid protocol version request: {}", e);
+                    (ProtocolVersionRequest::default(), false)
+                }
             }
-            MessageType::GetSymbolDocs => {
-                self.handle_get_symbol_docs(addr, request_id, payload).await;
+        };
+
+        let response = ProtocolVersionResponse::for_request(&request);
+        if !response.is_compatible {
+            warn!("client {} uses protocol version {:?}, server uses {}", addr, request.protocol_version, response.protocol_version);
+        }
+
+        match serde_json::to_string(&response) {
+            Ok(json) => self.send_response(MessageType::GetProtocolVersion, request_id, &json, addr).await && valid,
+            Err(e) => {
+                error!("Error serializing ProtocolVersionResponse: {}", e);
+                false
             }
         }
     }
 
-    async fn handle_get_unity_state(&mut self, addr: std::net::SocketAddr, request_id: u32) {
-        // Always update monitor when state is requested(full check)
-        let _changed = self.monitor_update(true);
+    async fn handle_get_assets_to_import(&mut self, addr: std::net::SocketAddr, request_id: u32, payload: &str) -> bool {
+        let request = match serde_json::from_str::<AssetsToImportRequest>(payload) {
+            Ok(request) => request,
+            Err(e) => {
+                warn!("Invalid assets to import request: {}", e);
+                let response = AssetsToImportResponse {
+                    error_message: Some(format!("Invalid request format: {}", e)),
+                    ..Default::default()
+                };
+                match serde_json::to_string(&response) {
+                    Ok(json) => {
+                        self.send_response(MessageType::GetAssetsToImport, request_id, &json, addr).await;
+                    }
+                    Err(e) => error!("Error serializing AssetsToImportResponse: {}", e),
+                }
+                return false;
+            }
+        };
+
+        // C# scripts depend on whether Hot Reload is enabled, which is only detected on a full update
+        self.monitor_update(true);
+        let state = self.get_process_state();
+        let response = assets_to_import(
+            &self.project_path,
+            &request.paths,
+            state.unity_process_id,
+            state.is_hot_reload_enabled,
+        );
+        if response.unity_process_id != 0 {
+            if let Some(tracker) = self.import_tracker.as_mut() {
+                tracker.track(&response.asset_paths, Instant::now());
+            }
+        }
 
-        self.send_state(addr, request_id).await;
+        let sent = match serde_json::to_string(&response) {
+            Ok(json) => self.send_response(MessageType::GetAssetsToImport, request_id, &json, addr).await,
+            Err(e) => {
+                error!("Error serializing AssetsToImportResponse: {}", e);
+                false
+            }
+        };
+        self.broadcast_editor_events().await;
+        sent
     }
 
-    async fn handle_get_symbol_docs(&mut self, addr: std::net::SocketAddr, request_id: u32, payload: &str) {
-        let response = if payload.is_empty() {
-            SymbolDocsResponse {
-                success: false,
-                documentation: None,
-                error_message: Some("Empty request payload".to_string()),
-                found_symbol_name: None,
-                inherited_from_symbol_name: None,
+    async fn handle_get_asset_imports(&mut self, addr: std::net::SocketAddr, request_id: u32) -> bool {
+        let response = AssetImportsResponse {
+            imports: self.import_tracker.iter().flat_map(|tracker| tracker.imports().cloned()).collect(),
+            slow_threshold_ms: SLOW_IMPORT_THRESHOLD.as_millis() as u64,
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => self.send_response(MessageType::GetAssetImports, request_id, &json, addr).await,
+            Err(e) => {
+                error!("Error serializing AssetImportsResponse: {}", e);
+                false
             }
-        } else {
-            match serde_json::from_str::<SymbolDocsRequest>(payload) {
-                Ok(request) => {
-                    // Validate request
-                    if request.assembly_name.is_none() && request.source_file_path.is_none() {
-                        SymbolDocsResponse {
-                            success: false,
-                            documentation: None,
-                            error_message: Some("Either AssemblyName or SourceFilePath must be provided".to_string()),
-                            found_symbol_name: None,
-                            inherited_from_symbol_name: None,
-                        }
-                    } else {
-                        // Convert source file path to PathBuf if provided
-                        let source_file_path = request.source_file_path.as_ref().map(|p| PathBuf::from(p));
-                        
-                        // Call docs manager
-                        match self.docs_manager.get_docs_for_symbol(
-                            &request.symbol_name,
-                            request.assembly_name.as_deref(),
-                            source_file_path.as_deref(),
-                        ).await {
-                            Ok(doc_result) => {
-                                // Construct the found symbol name from source type and member
-                                let found_symbol_name = if let Some(ref member_name) = doc_result.source_member_name {
-                                    format!("{}.{}", doc_result.source_type_name, member_name)
-                                } else {
-                                    doc_result.source_type_name.clone()
-                                };
-                                
-                                // Construct the inherited from symbol name if applicable
-                                 let inherited_from_symbol_name = if doc_result.is_inherited {
-                                     if let (Some(inherited_type), Some(inherited_member)) = 
-                                         (&doc_result.inherited_from_type_name, &doc_result.inherited_from_member_name) {
-                                         Some(format!("{}.{}", inherited_type, inherited_member))
-                                     } else if let Some(inherited_type) = &doc_result.inherited_from_type_name {
-                                         Some(inherited_type.clone())
-                                     } else {
-                                         None
-                                     }
-                                 } else {
-                                     None
-                                 };
-                                
-                                SymbolDocsResponse {
-                                    success: true,
-                                    documentation: Some(doc_result.xml_doc),
-                                    error_message: None,
-                                    found_symbol_name: Some(found_symbol_name),
-                                    inherited_from_symbol_name,
-                                }
-                            },
-                            Err(e) => SymbolDocsResponse {
-                                success: false,
-                                documentation: None,
-                                error_message: Some(e.to_string()),
-                                found_symbol_name: None,
-                                inherited_from_symbol_name: None,
-                            },
-                        }
+        }
+    }
+
+    /// Read the imports Unity logged on a blocking thread, slow ones and ones that timed out are broadcast
+    async fn update_import_tracking(&mut self) {
+        let Some(mut tracker) = self.import_tracker.take_if(|tracker| tracker.is_tracking()) else {
+            return;
+        };
+        let update = tokio::task::spawn_blocking(move || {
+            let slow = tracker.update(Instant::now());
+            (tracker, slow)
+        });
+        let slow = match update.await {
+            Ok((tracker, slow)) => {
+                self.import_tracker = Some(tracker);
+                slow
+            }
+            Err(e) => {
+                error!("Failed to read asset imports: {}", e);
+                self.import_tracker = default_editor_log_path().map(ImportTracker::new);
+                return;
+            }
+        };
+        for import in slow {
+            if import.timed_out {
+                info!("Unity didn't import {} within {} ms, broadcast to clients", import.asset_path, import.duration_ms);
+            } else {
+                info!("Unity took {} ms to import {}, broadcast to clients", import.duration_ms, import.asset_path);
+            }
+            match serde_json::to_string(&import) {
+                Ok(json) => self.broadcast(MessageType::SlowAssetImport, json).await,
+                Err(e) => error!("Error serializing AssetImport: {}", e),
+            }
+        }
+    }
+
+    async fn handle_get_unity_messages(&mut self, addr: std::net::SocketAddr, request_id: u32, payload: &str) -> bool {
+        let response = match serde_json::from_str::<UnityMessagesRequest>(payload) {
+            Ok(request) => {
+                // summaries only come from docs compiled so far, the request never waits for a compilation
+                let docs_manager = self.docs_manager.try_lock().ok();
+                unity_messages(&request, self.unity_project.get_unity_version_for_docs().as_deref(), |type_name, method_name| {
+                    docs_manager.as_ref()?.compiled_method_summary(type_name, method_name)
+                })
+            }
+            Err(e) => UnityMessagesResponse {
+                error_message: Some(format!("Invalid request format: {}", e)),
+                ..Default::default()
+            },
+        };
+
+        match serde_json::to_string(&response) {
+            Ok(json) => self.send_response(MessageType::GetUnityMessages, request_id, &json, addr).await && response.error_message.is_none(),
+            Err(e) => {
+                error!("Error serializing UnityMessagesResponse: {}", e);
+                false
+            }
+        }
+    }
+
+    async fn handle_subscribe_file_changes(&mut self, addr: std::net::SocketAddr, request_id: u32, payload: &str) -> bool {
+        let request = match serde_json::from_str::<FileChangesSubscription>(payload) {
+            Ok(request) => request,
+            Err(e) => {
+                warn!("Invalid file changes subscription: {}", e);
+                let response = FileChangesBatch {
+                    error_message: Some(format!("Invalid request format: {}", e)),
+                    ..Default::default()
+                };
+                match serde_json::to_string(&response) {
+                    Ok(json) => {
+                        self.send_response(MessageType::FileChanges, request_id, &json, addr).await;
                     }
+                    Err(e) => error!("Error serializing FileChangesBatch: {}", e),
                 }
-                Err(e) => SymbolDocsResponse {
-                    success: false,
-                    documentation: None,
-                    error_message: Some(format!("Invalid request format: {}", e)),
-                    found_symbol_name: None,
-                    inherited_from_symbol_name: None,
-                },
+                return false;
             }
         };
 
-        // Send response
+        let subscription = (!request.patterns.is_empty()).then(|| FileSubscription::new(&request));
+        if subscription.is_some() && self.file_watcher.is_none() {
+            match ProjectFileWatcher::new(&self.project_path) {
+                Ok(watcher) => self.file_watcher = Some(watcher),
+                Err(e) => error!("Failed to watch project files: {}", e),
+            }
+        }
+        if let Some(client) = self.clients.get_mut(&addr) {
+            info!("client {} subscribed to file changes of {:?}", addr, request.patterns);
+            client.file_changes = subscription;
+        }
+
+        match serde_json::to_string(&FileChangesBatch::default()) {
+            Ok(json) => self.send_response(MessageType::FileChanges, request_id, &json, addr).await,
+            Err(e) => {
+                error!("Error serializing FileChangesBatch: {}", e);
+                false
+            }
+        }
+    }
+
+    async fn handle_subscribe_style_patches(&mut self, addr: std::net::SocketAddr, request_id: u32, payload: &str) -> bool {
+        let request = match serde_json::from_str::<FileChangesSubscription>(payload) {
+            Ok(request) => request,
+            Err(e) => {
+                warn!("Invalid style patches subscription: {}", e);
+                return self.reject_style_patches(addr, request_id, format!("Invalid request format: {}", e)).await;
+            }
+        };
+
+        let patterns = request.patterns.clone();
+        let subscription = if patterns.is_empty() {
+            None
+        } else {
+            // finds and reads the style sheets matching the patterns, their content is the first version
+            let project_root = self.project_path.clone();
+            match tokio::task::spawn_blocking(move || StylePatchSubscription::new(&request, &project_root)).await {
+                Ok(subscription) => Some(subscription),
+                Err(e) => {
+                    error!("Failed to read the style sheets of the subscription: {}", e);
+                    return self.reject_style_patches(addr, request_id, "Failed to read the style sheets".to_string()).await;
+                }
+            }
+        };
+        if subscription.is_some() && self.file_watcher.is_none() {
+            match ProjectFileWatcher::new(&self.project_path) {
+                Ok(watcher) => self.file_watcher = Some(watcher),
+                Err(e) => error!("Failed to watch project files: {}", e),
+            }
+        }
+        if let Some(client) = self.clients.get_mut(&addr) {
+            info!("client {} subscribed to style patches of {:?}", addr, patterns);
+            client.style_patches = subscription;
+        }
+
+        match serde_json::to_string(&StylePatches::default()) {
+            Ok(json) => self.send_response(MessageType::StylePatches, request_id, &json, addr).await,
+            Err(e) => {
+                error!("Error serializing StylePatches: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Answer a style patches subscription with an error, the previous subscription of the client is kept
+    async fn reject_style_patches(&self, addr: std::net::SocketAddr, request_id: u32, message: String) -> bool {
+        let response = StylePatches { error_message: Some(message), ..Default::default() };
         match serde_json::to_string(&response) {
             Ok(json) => {
-                self.send_response(MessageType::GetSymbolDocs, request_id, &json, addr).await;
+                self.send_response(MessageType::StylePatches, request_id, &json, addr).await;
             }
-            Err(e) => {
-                error!("Error serializing SymbolDocsResponse: {}", e);
+            Err(e) => error!("Error serializing StylePatches: {}", e),
+        }
+        false
+    }
+
+    /// Send the batches of file changes and style patches that are complete, stops watching when no client is subscribed
+    async fn send_file_changes(&mut self) {
+        let Some(watcher) = &self.file_watcher else {
+            return;
+        };
+        let events = watcher.take_events();
+        let now = Instant::now();
+
+        let mut batches = Vec::new();
+        let mut style_changes = Vec::new();
+        for (addr, client) in self.clients.iter_mut() {
+            if let Some(subscription) = client.file_changes.as_mut() {
+                for (path, kind) in &events {
+                    subscription.push(path, *kind, now);
+                }
+                let changes = subscription.take_ready(now);
+                if !changes.is_empty() {
+                    batches.push((*addr, changes));
+                }
+            }
+            if let Some(subscription) = client.style_patches.as_mut() {
+                for (path, kind) in &events {
+                    subscription.push(path, *kind, now);
+                }
+                let changes = subscription.take_ready(now);
+                if !changes.is_empty() {
+                    style_changes.push((*addr, changes));
+                }
+            }
+        }
+        if !self.clients.values().any(|client| client.file_changes.is_some() || client.style_patches.is_some()) {
+            info!("no client is subscribed to file changes, stop watching project files");
+            self.file_watcher = None;
+        }
+
+        for (addr, changes) in batches {
+            debug!("sending {} file changes to {}", changes.len(), addr);
+            for chunk in changes.chunks(MAX_CHANGES_PER_MESSAGE) {
+                match serde_json::to_string(&FileChangesBatch { changes: chunk.to_vec(), error_message: None }) {
+                    Ok(json) => {
+                        self.send_response(MessageType::FileChanges, 0, &json, addr).await;
+                    }
+                    Err(e) => error!("Error serializing FileChangesBatch: {}", e),
+                }
+            }
+        }
+        // one style sheet per message, a message stays small unless many rules are added at once
+        for (addr, ready) in self.style_patches(style_changes).await {
+            debug!("sending style patches of {} style sheets to {}", ready.len(), addr);
+            for patch in ready {
+                match patch_payload(patch, MAX_PAYLOAD_SIZE) {
+                    Ok(json) => {
+                        self.send_response(MessageType::StylePatches, 0, &json, addr).await;
+                    }
+                    Err(e) => error!("Error serializing StylePatches: {}", e),
+                }
             }
         }
     }
 
-    async fn send_state(&mut self, addr: std::net::SocketAddr, request_id: u32) {
+    /// Patches of the style sheets that changed for each client, the style sheets are read on a blocking thread
+    async fn style_patches(
+        &mut self,
+        changes: Vec<(std::net::SocketAddr, Vec<FileChange>)>,
+    ) -> Vec<(std::net::SocketAddr, Vec<StylePatch>)> {
+        if changes.is_empty() {
+            return Vec::new();
+        }
+        let project_root = self.project_path.clone();
+        let read = tokio::task::spawn_blocking(move || {
+            changes
+                .into_iter()
+                .map(|(addr, changes)| (addr, read_style_sheets(&project_root, changes)))
+                .collect::<Vec<_>>()
+        });
+        let contents = match read.await {
+            Ok(contents) => contents,
+            Err(e) => {
+                error!("Failed to read changed style sheets: {}", e);
+                return Vec::new();
+            }
+        };
+
+        contents
+            .into_iter()
+            .filter_map(|(addr, contents)| {
+                let subscription = self.clients.get_mut(&addr)?.style_patches.as_mut()?;
+                let patches: Vec<StylePatch> = contents
+                    .into_iter()
+                    .filter_map(|(path, content)| subscription.patch(&path, content))
+                    .collect();
+                (!patches.is_empty()).then_some((addr, patches))
+            })
+            .collect()
+    }
+
+    async fn send_state(&mut self, addr: std::net::SocketAddr, request_id: u32) -> bool {
         // Return real process state data from monitor
         let state = self.get_process_state();
 
         match serde_json::to_string(&state) {
-            Ok(json) => {
-                self.send_response(MessageType::GetUnityState, request_id, &json, addr).await;
-            }
+            Ok(json) => self.send_response(MessageType::GetUnityState, request_id, &json, addr).await,
             Err(e) => {
                 error!("Error serializing ProcessState: {}", e);
+                false
             }
         }
     }
@@ -358,6 +797,45 @@ impl Server {
         }
     }
 
+    async fn broadcast_editor_events(&mut self) {
+        for event in std::mem::take(&mut self.pending_editor_events) {
+            info!("Unity Editor event {:?}, broadcast to clients", event.event);
+            match serde_json::to_string(&event) {
+                Ok(json) => self.broadcast(MessageType::EditorEvent, json).await,
+                Err(e) => error!("Error serializing EditorEvent: {}", e),
+            }
+        }
+    }
+
+    /// Read new compile errors from the Editor log, returns true if they changed
+    fn update_compile_errors(&mut self) -> bool {
+        self.compile_errors.as_mut().is_some_and(|watcher| watcher.update())
+    }
+
+    fn compile_errors_json(&self) -> Option<String> {
+        let errors = self.compile_errors.as_ref().map(|watcher| watcher.errors().clone()).unwrap_or_default();
+        match errors.payload(MAX_PAYLOAD_SIZE) {
+            Ok(json) => Some(json),
+            Err(e) => {
+                error!("Error serializing CompileErrors: {}", e);
+                None
+            }
+        }
+    }
+
+    async fn send_compile_errors(&mut self, addr: std::net::SocketAddr, request_id: u32) -> bool {
+        match self.compile_errors_json() {
+            Some(json) => self.send_response(MessageType::GetCompileErrors, request_id, &json, addr).await,
+            None => false,
+        }
+    }
+
+    async fn broadcast_compile_errors(&mut self) {
+        if let Some(json) = self.compile_errors_json() {
+            self.broadcast(MessageType::GetCompileErrors, json).await;
+        }
+    }
+
     async fn broadcast(&mut self, message_type: MessageType, json: String) {
         // Send to all connected clients
         let clients: Vec<std::net::SocketAddr> = self.clients.keys().cloned().collect();
@@ -366,19 +844,9 @@ impl Server {
         }
     }
     
-    async fn send_response(&self, message_type: MessageType, request_id: u32, payload: &str, addr: std::net::SocketAddr) {
-        let payload_bytes = payload.as_bytes();
-        let payload_length = payload_bytes.len() as u32;
-
-        let mut response = Vec::with_capacity(9 + payload_bytes.len());
-        response.push(message_type as u8);
-        response.extend_from_slice(&request_id.to_le_bytes());
-        response.extend_from_slice(&payload_length.to_le_bytes());
-        response.extend_from_slice(payload_bytes);
-
-        if let Err(e) = self.socket.send_to(&response, addr).await {
-            error!("Error sending response to {}: {}", addr, e);
-        }
+    /// Send a message to a client, returns false if it couldn't be sent
+    async fn send_response(&self, message_type: MessageType, request_id: u32, payload: &str, addr: std::net::SocketAddr) -> bool {
+        send_message(&self.socket, message_type, request_id, payload, addr).await
     }
 
     fn cleanup_inactive_clients(&mut self) {
@@ -394,3 +862,67 @@ impl Server {
         });
     }
 }
+
+async fn send_message(
+    socket: &UdpSocket,
+    message_type: MessageType,
+    request_id: u32,
+    payload: &str,
+    addr: std::net::SocketAddr,
+) -> bool {
+    let response = Message {
+        message_type,
+        request_id,
+        payload,
+    }
+    .encode();
+
+    match socket.send_to(&response, addr).await {
+        Ok(_) => true,
+        Err(e) => {
+            error!("Error sending response to {}: {}", addr, e);
+            false
+        }
+    }
+}
+
+/// Run `work` of the docs manager, broadcasting the progress of the documentation it compiles
+///
+/// Nothing is sent if no compilation takes longer than [`REPORT_INTERVAL`], once progress was sent
+/// the end of the compilation is sent too. Takes the fields it needs, the work borrows the docs manager.
+async fn track_docs_compile<F: Future>(
+    socket: &UdpSocket,
+    clients: &[std::net::SocketAddr],
+    status: &DocsCompileStatus,
+    work: F,
+) -> F::Output {
+    let mut work = std::pin::pin!(work);
+    let mut last_sent: Option<DocsCompileProgress> = None;
+    loop {
+        let output = tokio::select! {
+            output = &mut work => Some(output),
+            _ = tokio::time::sleep(REPORT_INTERVAL) => None,
+        };
+
+        let progress = status.progress().filter(|progress| {
+            // a compilation that finished before the first report isn't worth telling about
+            last_sent.as_ref() != Some(progress)
+                && (!progress.finished || last_sent.as_ref().is_some_and(|last| last.assembly_name == progress.assembly_name))
+        });
+        if let Some(progress) = progress {
+            match serde_json::to_string(&progress) {
+                Ok(json) => {
+                    for addr in clients {
+                        send_message(socket, MessageType::DocsCompileProgress, 0, &json, *addr).await;
+                    }
+                }
+                Err(e) => error!("Error serializing DocsCompileProgress: {}", e),
+            }
+            last_sent = Some(progress);
+        }
+
+        if let Some(output) = output {
+            return output;
+        }
+    }
+}
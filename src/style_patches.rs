@@ -0,0 +1,266 @@
+//! Live Style Patches
+//!
+//! Clients can subscribe to style changes of saved style sheets, see [`MessageType::StylePatches`]. When a
+//! USS or TSS file is saved, it's compared by rules and declarations with the version last sent (see
+//! [`crate::uss::style_diff`]), and only the changed declarations are sent. The Unity Editor can set them
+//! on the loaded style sheet, so panels restyle right away instead of after the file is imported.
+//!
+//! Changes that can't be applied to the rules alone, e.g. a changed `@import` or rules that moved, are sent
+//! with `RequiresImport`. A style sheet with syntax errors is not sent until they are fixed, Unity would
+//! skip the broken rules when it imports the file.
+//!
+//! [`MessageType::StylePatches`]: crate::protocol::MessageType::StylePatches
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+
+use crate::config::path_matches;
+use crate::file_events::{relative_path, FileSubscription};
+use crate::protocol::{
+    DeclarationPatch, FileChange, FileChangeKind, FileChangesSubscription, RulePatch, RulePatchKind, StylePatch,
+    StylePatches,
+};
+use crate::uss::import_graph::{find_style_sheets, find_theme_style_sheets};
+use crate::uss::parser::UssParser;
+use crate::uss::style_diff::{diff_style_sheets, ChangeKind, StyleDiff};
+
+/// Style sheets a client is sent the style changes of
+#[derive(Debug)]
+pub struct StylePatchSubscription {
+    files: FileSubscription,
+    /// Content of every style sheet as it was last sent, by path relative to the project
+    sent: HashMap<String, String>,
+}
+
+impl StylePatchSubscription {
+    /// Subscription to the style sheets of the project matching the patterns, their current content is the first version
+    ///
+    /// Finds and reads the style sheets, async code should call it with `spawn_blocking`.
+    pub fn new(request: &FileChangesSubscription, project_root: &Path) -> Self {
+        let sent = find_style_sheets(project_root)
+            .into_iter()
+            .chain(find_theme_style_sheets(project_root))
+            .filter_map(|path| {
+                let relative = relative_path(project_root, &path)?;
+                if !request.patterns.iter().any(|pattern| path_matches(pattern, &relative)) {
+                    return None;
+                }
+                Some((relative, std::fs::read_to_string(&path).ok()?))
+            })
+            .collect();
+        Self { files: FileSubscription::new(request), sent }
+    }
+
+    /// Add a change of a path relative to the project, ignored if it isn't a style sheet matching the patterns
+    pub fn push(&mut self, path: &str, kind: FileChangeKind, now: Instant) {
+        if is_style_sheet(path) {
+            self.files.push(path, kind, now);
+        }
+    }
+
+    /// Style sheets that changed, once no style sheet changed for the debounce time
+    ///
+    /// Read them with [`read_style_sheets`], then [`StylePatchSubscription::patch`] them.
+    pub fn take_ready(&mut self, now: Instant) -> Vec<FileChange> {
+        self.files.take_ready(now)
+    }
+
+    /// Patch of a style sheet from the version last sent to `content`, `None` for a deleted style sheet
+    ///
+    /// Returns `None` if nothing changed, or if the new version has syntax errors.
+    pub fn patch(&mut self, path: &str, content: Option<String>) -> Option<StylePatch> {
+        let Some(content) = content else {
+            self.sent.remove(path)?;
+            return Some(StylePatch { path: path.to_string(), rules: Vec::new(), requires_import: true });
+        };
+
+        let mut parser = UssParser::new().ok()?;
+        let tree = parser.parse(&content, None)?;
+        if tree.root_node().has_error() {
+            return None;
+        }
+        let old_content = self.sent.get(path).map(String::as_str);
+        let old_tree = parser.parse(old_content.unwrap_or_default(), None)?;
+        let diff = diff_style_sheets(&old_tree, old_content.unwrap_or_default(), &tree, &content);
+
+        let is_new = old_content.is_none();
+        self.sent.insert(path.to_string(), content);
+        if diff.is_empty() && !is_new {
+            return None;
+        }
+        let mut patch = style_patch(path, &diff);
+        // a new style sheet is used by nothing until Unity imports it
+        patch.requires_import |= is_new;
+        Some(patch)
+    }
+}
+
+/// Content of changed style sheets by path relative to the project, `None` if a style sheet was deleted
+pub fn read_style_sheets(project_root: &Path, changes: Vec<FileChange>) -> Vec<(String, Option<String>)> {
+    changes
+        .into_iter()
+        .map(|change| {
+            let content = match change.kind {
+                FileChangeKind::Deleted => None,
+                _ => std::fs::read_to_string(project_root.join(&change.path)).ok(),
+            };
+            (change.path, content)
+        })
+        .collect()
+}
+
+/// Patch with the changes of a diff, rules of a changed rule only have their changed declarations
+pub fn style_patch(path: &str, diff: &StyleDiff) -> StylePatch {
+    let rules = diff
+        .rules
+        .iter()
+        .map(|rule| RulePatch {
+            selector: rule.selector.clone(),
+            kind: patch_kind(rule.kind),
+            declarations: rule
+                .declarations
+                .iter()
+                .map(|declaration| DeclarationPatch {
+                    property: declaration.property.clone(),
+                    value: declaration.value.clone(),
+                    important: declaration.important,
+                })
+                .collect(),
+        })
+        .collect();
+    StylePatch {
+        path: path.to_string(),
+        rules,
+        requires_import: !diff.imports.is_empty() || diff.reordered,
+    }
+}
+
+/// Payload of a message with a patch, without its rules if it's larger than `max_size`, the style sheet must then be imported
+pub fn patch_payload(mut patch: StylePatch, max_size: usize) -> serde_json::Result<String> {
+    let json = serde_json::to_string(&StylePatches { patches: vec![patch.clone()], error_message: None })?;
+    if json.len() <= max_size {
+        return Ok(json);
+    }
+    patch.rules.clear();
+    patch.requires_import = true;
+    serde_json::to_string(&StylePatches { patches: vec![patch], error_message: None })
+}
+
+fn patch_kind(kind: ChangeKind) -> RulePatchKind {
+    match kind {
+        ChangeKind::Added => RulePatchKind::Added,
+        ChangeKind::Removed => RulePatchKind::Removed,
+        ChangeKind::Changed => RulePatchKind::Changed,
+    }
+}
+
+fn is_style_sheet(path: &str) -> bool {
+    let path = path.to_ascii_lowercase();
+    path.ends_with(".uss") || path.ends_with(".tss")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscription(sent: &[(&str, &str)]) -> StylePatchSubscription {
+        let request = FileChangesSubscription { patterns: vec!["Assets/**".to_string()], debounce_ms: None };
+        StylePatchSubscription {
+            files: FileSubscription::new(&request),
+            sent: sent.iter().map(|(path, content)| (path.to_string(), content.to_string())).collect(),
+        }
+    }
+
+    #[test]
+    fn test_patches() {
+        let path = "Assets/UI/Main.uss";
+        let mut subscription = subscription(&[(path, ".button { color: red; margin: 4px; }")]);
+
+        let patch = subscription.patch(path, Some(".button { color: blue; margin: 4px; }".to_string())).unwrap();
+        assert!(!patch.requires_import);
+        assert_eq!(
+            patch.rules,
+            vec![RulePatch {
+                selector: ".button".to_string(),
+                kind: RulePatchKind::Changed,
+                declarations: vec![DeclarationPatch {
+                    property: "color".to_string(),
+                    value: Some("blue".to_string()),
+                    important: false,
+                }],
+            }]
+        );
+
+        // compared with the version last sent
+        assert!(subscription.patch(path, Some(".button {\n    color: blue;\n    margin: 4px;\n}".to_string())).is_none());
+        let patch = subscription.patch(path, Some(".button { color: blue; }".to_string())).unwrap();
+        assert_eq!(patch.rules[0].declarations[0].value, None);
+
+        // syntax errors are sent once fixed
+        assert!(subscription.patch(path, Some(".button { color: ; } }".to_string())).is_none());
+        let patch = subscription.patch(path, Some(".button { color: blue; }\n.label { color: green; }".to_string())).unwrap();
+        assert_eq!(patch.rules.len(), 1);
+        assert_eq!(patch.rules[0].kind, RulePatchKind::Added);
+
+        let patch = subscription.patch(path, Some("@import \"common.uss\";\n.button { color: blue; }\n.label { color: green; }".to_string())).unwrap();
+        assert!(patch.requires_import);
+        assert!(patch.rules.is_empty());
+
+        let patch = subscription.patch(path, None).unwrap();
+        assert!(patch.requires_import);
+        assert!(subscription.patch(path, None).is_none());
+
+        // new style sheets are imported
+        let patch = subscription.patch(path, Some(".button { color: blue; }".to_string())).unwrap();
+        assert!(patch.requires_import);
+        assert_eq!(patch.rules.len(), 1);
+    }
+
+    #[test]
+    fn test_large_patches_are_imported() {
+        let mut subscription = subscription(&[]);
+        let content: String = (0..100).map(|i| format!(".item-{} {{ color: red; }}\n", i)).collect();
+        let patch = subscription.patch("Assets/UI/Large.uss", Some(content)).unwrap();
+
+        let payload: StylePatches = serde_json::from_str(&patch_payload(patch.clone(), 100_000).unwrap()).unwrap();
+        assert_eq!(payload.patches[0].rules.len(), 100);
+        let payload: StylePatches = serde_json::from_str(&patch_payload(patch, 1000).unwrap()).unwrap();
+        assert!(payload.patches[0].rules.is_empty());
+        assert!(payload.patches[0].requires_import);
+    }
+
+    #[test]
+    fn test_read_style_sheets() {
+        let project = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(project.path().join("Assets/UI")).unwrap();
+        std::fs::write(project.path().join("Assets/UI/Main.uss"), ".button { color: red; }").unwrap();
+
+        let change = |path: &str, kind| FileChange { path: path.to_string(), kind };
+        let contents = read_style_sheets(
+            project.path(),
+            vec![change("Assets/UI/Main.uss", FileChangeKind::Modified), change("Assets/UI/Old.uss", FileChangeKind::Deleted)],
+        );
+        assert_eq!(
+            contents,
+            vec![
+                ("Assets/UI/Main.uss".to_string(), Some(".button { color: red; }".to_string())),
+                ("Assets/UI/Old.uss".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_only_style_sheets() {
+        let start = Instant::now();
+        let mut subscription = subscription(&[]);
+        subscription.push("Assets/UI/Main.uxml", FileChangeKind::Modified, start);
+        subscription.push("Assets/UI/Main.USS", FileChangeKind::Deleted, start);
+        subscription.push("Assets/UI/Default.tss", FileChangeKind::Deleted, start);
+
+        let later = start + std::time::Duration::from_secs(1);
+        let changes = subscription.files.take_ready(later);
+        let paths: Vec<&str> = changes.iter().map(|change| change.path.as_str()).collect();
+        assert_eq!(paths, vec!["Assets/UI/Default.tss", "Assets/UI/Main.USS"]);
+    }
+}
@@ -1,7 +1,21 @@
 //! Test utilities shared across the codebase
+//!
+//! Tests that need a Unity project without a Unity install can build a fake one with
+//! [`UnityProjectBuilder`]: `ProjectSettings/ProjectVersion.txt`, a `UIElementsSchema` folder with the
+//! common built-in elements, sample style sheets and UXML, and `.csproj` files of C# scripts.
 
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tower_lsp::lsp_types::{CompletionItem, Diagnostic, Position};
+use tree_sitter::Tree;
+
+use crate::uss::completion::UssCompletionProvider;
+use crate::uss::diagnostics::UssDiagnostics;
+use crate::uss::parser::UssParser;
 
 /// Helper function to get the project root directory for tests
 /// This looks for the Cargo.toml file to determine the project root
@@ -34,3 +48,374 @@ pub fn get_unity_project_root() -> PathBuf {
 pub fn get_ui_elements_schema_dir() -> PathBuf {
     get_unity_project_root().join("UIElementsSchema")
 }
+
+/// Generate a large synthetic USS stylesheet, used to measure performance on big projects
+///
+/// The output is deterministic and covers the common constructs of real stylesheets:
+/// variables, class/id/type selectors with pseudo-classes and combinators, comments,
+/// `var()` references, `url()`/`resource()` assets, multi-value properties and transitions.
+pub fn generate_stylesheet(rule_count: usize) -> String {
+    const TYPES: &[&str] = &["Button", "Label", "VisualElement", "TextField", "Toggle", "ScrollView"];
+    const PSEUDO_CLASSES: &[&str] = &["", ":hover", ":active", ":focus", ":checked", ":disabled"];
+    const COLORS: &[&str] = &["red", "#FF8800", "rgb(10, 20, 30)", "rgba(0, 0, 0, 0.5)", "var(--color-1)"];
+
+    let mut content = String::from("@import url(\"project:///Assets/UI/Common.uss\");\n\n:root {\n");
+    for i in 0..16 {
+        content.push_str(&format!("    --color-{}: #{:06X};\n", i, (i * 0x0F0F0F) % 0xFFFFFF));
+        content.push_str(&format!("    --spacing-{}: {}px;\n", i, i * 2));
+    }
+    content.push_str("}\n");
+
+    for i in 0..rule_count {
+        let selector = match i % 4 {
+            0 => format!(".class-{}{}", i, PSEUDO_CLASSES[i % PSEUDO_CLASSES.len()]),
+            1 => format!("#element-{} > .child-{}", i, i % 7),
+            2 => format!("{}.class-{} .nested", TYPES[i % TYPES.len()], i % 13),
+            _ => format!(".class-{}, {}{}", i, TYPES[i % TYPES.len()], PSEUDO_CLASSES[(i + 1) % PSEUDO_CLASSES.len()]),
+        };
+
+        content.push('\n');
+        if i % 5 == 0 {
+            content.push_str(&format!("/* Section {} */\n", i / 5));
+        }
+        content.push_str(&selector);
+        content.push_str(" {\n");
+        content.push_str(&format!("    width: {}px;\n", 10 + i % 300));
+        content.push_str(&format!("    height: {}%;\n", i % 100));
+        content.push_str(&format!("    margin: {}px {}px;\n", i % 8, i % 16));
+        content.push_str(&format!("    padding: var(--spacing-{});\n", i % 16));
+        content.push_str(&format!("    color: {};\n", COLORS[i % COLORS.len()]));
+        content.push_str(&format!("    background-color: var(--color-{});\n", i % 16));
+        match i % 3 {
+            0 => content.push_str("    flex-direction: row;\n    align-items: center;\n"),
+            1 => content.push_str(&format!(
+                "    background-image: url(\"project:///Assets/Textures/icon_{}.png\");\n",
+                i % 10
+            )),
+            _ => content.push_str(&format!(
+                "    -unity-font: resource(\"Fonts/font_{}\");\n    -unity-text-align: middle-center;\n",
+                i % 4
+            )),
+        }
+        if i % 4 == 0 {
+            content.push_str("    transition: opacity 0.2s ease-in-out, width 300ms linear;\n");
+        }
+        content.push_str(&format!("    border-width: {}px;\n", i % 4));
+        content.push_str(&format!("    border-radius: {}px {}px;\n", i % 6, i % 3));
+        content.push_str("}\n");
+    }
+
+    content
+}
+
+/// A parsed style sheet, for the benchmarks in `benches/` of internals of the USS engine that aren't public
+pub struct StyleSheetFixture {
+    content: String,
+    tree: Tree,
+    parser: UssParser,
+    diagnostics: UssDiagnostics,
+    completion: UssCompletionProvider,
+}
+
+impl StyleSheetFixture {
+    pub fn new(content: String) -> Self {
+        let mut parser = UssParser::new().expect("the USS grammar loads");
+        let tree = parser.parse(&content, None).expect("parsing without a timeout succeeds");
+        Self {
+            content,
+            tree,
+            parser,
+            diagnostics: UssDiagnostics::new(),
+            completion: UssCompletionProvider::new(),
+        }
+    }
+
+    /// Parse the style sheet again from scratch
+    pub fn parse(&mut self) -> Option<Tree> {
+        self.parser.parse(&self.content, None)
+    }
+
+    /// Diagnostics of the style sheet, the provider is reused like in the language server
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.diagnostics.analyze(&self.tree, &self.content)
+    }
+
+    /// Diagnostics of the style sheet by a new provider, e.g. one per inline style
+    pub fn diagnostics_with_new_provider(&self) -> Vec<Diagnostic> {
+        UssDiagnostics::new().analyze(&self.tree, &self.content)
+    }
+
+    /// Completion at a position, without the project and its UXML schema
+    pub fn complete(&self, position: Position) -> Vec<CompletionItem> {
+        self.completion.complete(&self.tree, &self.content, position, None, None, None)
+    }
+}
+
+/// Unity version of fake projects, unless set with [`UnityProjectBuilder::unity_version`]
+pub const DEFAULT_UNITY_VERSION: &str = "6000.0.51f1";
+
+/// Schema of a few built-in elements, in the form Unity generates `UIElementsSchema/UnityEngine.UIElements.xsd`
+pub const ENGINE_SCHEMA: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<xs:schema xmlns:engine="UnityEngine.UIElements" elementFormDefault="qualified" targetNamespace="UnityEngine.UIElements" xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:simpleType name="VisualElement_picking-mode_Type">
+    <xs:restriction base="xs:string">
+      <xs:enumeration value="Position" />
+      <xs:enumeration value="Ignore" />
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:complexType name="VisualElementType">
+    <xs:sequence minOccurs="0" maxOccurs="unbounded">
+      <xs:element ref="engine:VisualElement" />
+    </xs:sequence>
+    <xs:attribute default="" name="name" type="xs:string" use="optional" />
+    <xs:attribute default="true" name="enabled" type="xs:boolean" use="optional" />
+    <xs:attribute default="Position" name="picking-mode" type="engine:VisualElement_picking-mode_Type" use="optional" />
+    <xs:attribute default="" name="class" type="xs:string" use="optional" />
+    <xs:attribute default="" name="style" type="xs:string" use="optional" />
+    <xs:anyAttribute processContents="lax" />
+  </xs:complexType>
+  <xs:element name="VisualElement" substitutionGroup="engine:VisualElement" type="engine:VisualElementType" />
+  <xs:complexType name="TextElementType">
+    <xs:complexContent mixed="false">
+      <xs:restriction base="engine:VisualElementType">
+        <xs:attribute default="" name="text" type="xs:string" use="optional" />
+      </xs:restriction>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:element name="TextElement" substitutionGroup="engine:VisualElement" type="engine:TextElementType" />
+  <xs:complexType name="LabelType">
+    <xs:complexContent mixed="false">
+      <xs:restriction base="engine:TextElementType" />
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:element name="Label" substitutionGroup="engine:VisualElement" type="engine:LabelType" />
+  <xs:complexType name="ButtonType">
+    <xs:complexContent mixed="false">
+      <xs:restriction base="engine:TextElementType" />
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:element name="Button" substitutionGroup="engine:VisualElement" type="engine:ButtonType" />
+  <xs:complexType name="ToggleType">
+    <xs:complexContent mixed="false">
+      <xs:restriction base="engine:VisualElementType">
+        <xs:attribute default="" name="label" type="xs:string" use="optional" />
+        <xs:attribute default="false" name="value" type="xs:boolean" use="optional" />
+      </xs:restriction>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:element name="Toggle" substitutionGroup="engine:VisualElement" type="engine:ToggleType" />
+</xs:schema>
+"#;
+
+/// Style sheet added by [`UnityProjectBuilder::sample_ui`]
+pub const SAMPLE_USS: &str = r#":root {
+    --accent-color: rgb(58, 121, 187);
+}
+
+.title {
+    font-size: 24px;
+    color: var(--accent-color);
+}
+
+.play-button:hover {
+    background-color: rgb(88, 88, 88);
+}
+"#;
+
+/// UXML added by [`UnityProjectBuilder::sample_ui`], uses [`SAMPLE_USS`]
+pub const SAMPLE_UXML: &str = r#"<ui:UXML xmlns:ui="UnityEngine.UIElements">
+    <Style src="Main.uss" />
+    <ui:Label text="Title" class="title" />
+    <ui:Button text="Play" class="play-button" />
+    <ui:Toggle label="Mute" />
+</ui:UXML>
+"#;
+
+/// Number of fake projects created by the process, for unique folder names
+static PROJECT_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Builds a fake Unity project in a temporary folder
+///
+/// ```ignore
+/// let project = UnityProjectBuilder::new()
+///     .ui_elements_schema()
+///     .file("Assets/UI/Main.uss", ".title { color: red; }")
+///     .build()?;
+/// let manager = UnityProjectManager::new(project.path().to_path_buf());
+/// ```
+#[derive(Debug, Clone)]
+pub struct UnityProjectBuilder {
+    unity_version: String,
+    /// Files to write, by path relative to the project
+    files: Vec<(String, String)>,
+}
+
+impl Default for UnityProjectBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UnityProjectBuilder {
+    /// A project with only `ProjectSettings/ProjectVersion.txt` and empty `Assets` and `Packages` folders
+    pub fn new() -> Self {
+        Self {
+            unity_version: DEFAULT_UNITY_VERSION.to_string(),
+            files: Vec::new(),
+        }
+    }
+
+    /// The Unity version in `ProjectVersion.txt`, e.g. `2022.3.10f1`
+    pub fn unity_version(mut self, version: &str) -> Self {
+        self.unity_version = version.to_string();
+        self
+    }
+
+    /// Add a file, `path` is relative to the project, e.g. `Assets/UI/Main.uss`
+    ///
+    /// A file added again replaces the earlier one.
+    pub fn file(mut self, path: &str, content: &str) -> Self {
+        self.files.retain(|(existing, _)| existing != path);
+        self.files.push((path.to_string(), content.to_string()));
+        self
+    }
+
+    /// Add `UIElementsSchema/UnityEngine.UIElements.xsd` with `VisualElement`, `TextElement`, `Label`, `Button` and `Toggle`
+    pub fn ui_elements_schema(self) -> Self {
+        self.file("UIElementsSchema/UnityEngine.UIElements.xsd", ENGINE_SCHEMA)
+    }
+
+    /// Add [`SAMPLE_USS`] and [`SAMPLE_UXML`] as `Assets/UI/Main.uss` and `Assets/UI/Main.uxml`
+    pub fn sample_ui(self) -> Self {
+        self.file("Assets/UI/Main.uss", SAMPLE_USS).file("Assets/UI/Main.uxml", SAMPLE_UXML)
+    }
+
+    /// Add C# scripts and the `<assembly_name>.csproj` in the project root that compiles them
+    ///
+    /// `scripts` are (path relative to the project, source code).
+    pub fn csproj(mut self, assembly_name: &str, scripts: &[(&str, &str)]) -> Self {
+        let compile_items: String = scripts
+            .iter()
+            .map(|(path, _)| format!("    <Compile Include=\"{}\" />\n", path))
+            .collect();
+        let csproj = format!(
+            "<Project ToolsVersion=\"4.0\" DefaultTargets=\"Build\">\n  <PropertyGroup>\n    <AssemblyName>{}</AssemblyName>\n  </PropertyGroup>\n  <ItemGroup>\n{}  </ItemGroup>\n</Project>\n",
+            assembly_name, compile_items
+        );
+        for (path, source) in scripts {
+            self = self.file(path, source);
+        }
+        self.file(&format!("{}.csproj", assembly_name), &csproj)
+    }
+
+    /// Create the project in a new folder of the system's temporary folder
+    pub fn build(self) -> io::Result<UnityProjectFixture> {
+        let index = PROJECT_COUNT.fetch_add(1, Ordering::Relaxed);
+        let root = env::temp_dir().join(format!("unity_code_fixture_{}_{}", std::process::id(), index));
+        if root.exists() {
+            fs::remove_dir_all(&root)?;
+        }
+        // dropped on errors, which removes what was created
+        let fixture = UnityProjectFixture { root };
+
+        fs::create_dir_all(fixture.root.join("Assets"))?;
+        fs::create_dir_all(fixture.root.join("Packages"))?;
+        fixture.write(
+            "ProjectSettings/ProjectVersion.txt",
+            &format!("m_EditorVersion: {}\nm_EditorVersionWithRevision: {} (0000000000000)\n", self.unity_version, self.unity_version),
+        )?;
+        for (path, content) in &self.files {
+            fixture.write(path, content)?;
+        }
+        Ok(fixture)
+    }
+}
+
+/// A fake Unity project created by [`UnityProjectBuilder`], its folder is removed when it's dropped
+#[derive(Debug)]
+pub struct UnityProjectFixture {
+    root: PathBuf,
+}
+
+impl UnityProjectFixture {
+    /// The project root
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
+    /// Absolute path of a path relative to the project
+    pub fn join(&self, path: &str) -> PathBuf {
+        self.root.join(path)
+    }
+
+    /// Write a file, creating its folders, `path` is relative to the project
+    pub fn write(&self, path: &str, content: &str) -> io::Result<()> {
+        let path = self.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, content)
+    }
+
+    /// Remove a file, `path` is relative to the project
+    pub fn remove(&self, path: &str) -> io::Result<()> {
+        fs::remove_file(self.join(path))
+    }
+}
+
+impl Drop for UnityProjectFixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cs::source_utils::find_user_assemblies;
+    use crate::language::tree_utils::has_error_nodes;
+    use crate::unity_project_manager::UnityProjectManager;
+    use crate::uxml_schema_manager::UxmlSchemaManager;
+
+    #[test]
+    fn test_generated_stylesheet_is_valid() {
+        let content = generate_stylesheet(50);
+        assert_eq!(content, generate_stylesheet(50), "Generator must be deterministic");
+
+        let tree = UssParser::new().unwrap().parse(&content, None).unwrap();
+        assert!(!has_error_nodes(tree.root_node()));
+    }
+
+    #[tokio::test]
+    async fn test_fake_unity_project() {
+        let project = UnityProjectBuilder::new()
+            .unity_version("2022.3.10f1")
+            .ui_elements_schema()
+            .sample_ui()
+            .csproj("Assembly-CSharp", &[("Assets/Scripts/Player.cs", "class Player { }")])
+            .build()
+            .unwrap();
+        let root = project.path().to_path_buf();
+
+        let manager = UnityProjectManager::new(root.clone());
+        assert_eq!(manager.detect_unity_version().unwrap(), "2022.3.10f1");
+
+        let mut schema_manager = UxmlSchemaManager::new(project.join("UIElementsSchema"));
+        schema_manager.update().await.unwrap();
+        let data = schema_manager.get_visual_elements_data();
+        let data = data.lock().unwrap();
+        assert!(data.lookup("UnityEngine.UIElements.Toggle").is_some());
+        assert!(data.derives_from("UnityEngine.UIElements.Button", "UnityEngine.UIElements.TextElement"));
+
+        let content = fs::read_to_string(project.join("Assets/UI/Main.uss")).unwrap();
+        let tree = UssParser::new().unwrap().parse(&content, None).unwrap();
+        assert!(!tree.root_node().has_error());
+
+        let assemblies = find_user_assemblies(&root).await.unwrap();
+        assert_eq!(assemblies.len(), 1);
+        assert_eq!(assemblies[0].name, "Assembly-CSharp");
+
+        drop(project);
+        assert!(!root.exists());
+    }
+}
@@ -1,14 +1,16 @@
 //! Unity Asset Database for querying asset information from meta files
 //!
 //! This module provides the `UnityAssetDatabase` struct that can query asset information
-//! from Unity project meta files, including GUIDs and sprite details for textures.
+//! from Unity project meta files, including GUIDs and sprite details for textures, and the
+//! `AssetPathResolver` struct that finds the files references to assets are loaded from.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use url::Url;
 use serde::{Deserialize, Serialize};
-use crate::language::asset_url::{project_url_to_path};
+use crate::language::asset_url::{project_url_to_path, project_url_to_relative_path};
+use crate::language::project_packages::ProjectPackages;
 
 /// Error type for Unity Asset Database operations
 /// 
@@ -292,6 +294,215 @@ impl UnityAssetDatabase {
     }
 }
 
+/// Folder of `resource()` assets that are named with their extension
+const EDITOR_DEFAULT_RESOURCES: &str = "Assets/Editor Default Resources";
+
+/// Folder of the Addressables group assets, each lists the addresses of its entries
+const ADDRESSABLE_GROUPS: &str = "Assets/AddressableAssetsData/AssetGroups";
+
+/// Finds the files that references to assets are loaded from, the way Unity does
+///
+/// - Project URLs refer to a file in the project or in one of its packages.
+/// - `resource()` names an asset in a `Resources` folder anywhere in `Assets` or in a package, without its
+///   extension, or in `Assets/Editor Default Resources`, with its extension.
+/// - Assets of Addressables groups are loaded by their address, which custom elements can take in place of a
+///   path. An entry for a folder gives an address to all the files in it, e.g. `Icons/star.png` for the
+///   entry `Icons`.
+///
+/// Folders and group assets are scanned when the resolver is created, create a new one when assets are
+/// added or removed.
+#[derive(Debug)]
+pub struct AssetPathResolver {
+    project_root: PathBuf,
+    packages: ProjectPackages,
+    /// `Resources` folders of the project and its packages
+    resource_folders: Vec<PathBuf>,
+    /// Paths of the Addressables entries, by address
+    addresses: HashMap<String, PathBuf>,
+}
+
+impl AssetPathResolver {
+    pub fn new(project_root: &Path) -> Self {
+        let packages = ProjectPackages::find(project_root);
+        let mut resource_folders = Vec::new();
+        find_resource_folders(&project_root.join("Assets"), &mut resource_folders);
+        for package in packages.sorted() {
+            find_resource_folders(&package.path, &mut resource_folders);
+        }
+        let addresses = find_addresses(project_root, &packages);
+
+        Self {
+            project_root: project_root.to_path_buf(),
+            packages,
+            resource_folders,
+            addresses,
+        }
+    }
+
+    /// Gets the project root path
+    pub fn project_root(&self) -> &Path {
+        &self.project_root
+    }
+
+    /// The file of a project URL, in the project or in a package, `None` if it doesn't exist
+    pub fn resolve_project_url(&self, url: &Url) -> Option<PathBuf> {
+        let path = project_url_to_path(&self.project_root, url)?;
+        if path.exists() {
+            return Some(path);
+        }
+        let relative = project_url_to_relative_path(url)?;
+        self.packages.resolve(&relative).filter(|path| path.exists())
+    }
+
+    /// The file of a `resource()`, whose URL is relative to `Assets/Resources`, `None` if it doesn't exist
+    pub fn resolve_resource_url(&self, url: &Url) -> Option<PathBuf> {
+        let relative = project_url_to_relative_path(url)?;
+        self.resolve_resource(relative.strip_prefix("Assets/Resources/")?)
+    }
+
+    /// The file of an asset named like in `Resources.Load`, or with its extension in `Editor Default Resources`
+    pub fn resolve_resource(&self, name: &str) -> Option<PathBuf> {
+        let editor_resource = self.project_root.join(EDITOR_DEFAULT_RESOURCES).join(name);
+        if editor_resource.is_file() {
+            return Some(editor_resource);
+        }
+        self.resource_folders.iter().find_map(|folder| find_without_extension(&folder.join(name)))
+    }
+
+    /// The file of an Addressables address, `None` if no entry has it
+    pub fn resolve_address(&self, address: &str) -> Option<PathBuf> {
+        if let Some(path) = self.addresses.get(address) {
+            return Some(path.clone());
+        }
+        // files in a folder entry are addressed by the folder's address and their path in it
+        let mut prefix = address;
+        while let Some((folder, _)) = prefix.rsplit_once('/') {
+            if let Some(folder_path) = self.addresses.get(folder).filter(|path| path.is_dir()) {
+                let file = folder_path.join(&address[folder.len() + 1..]);
+                return file.is_file().then_some(file);
+            }
+            prefix = folder;
+        }
+        None
+    }
+}
+
+/// A file named like `path` with any extension, other than `.meta`
+fn find_without_extension(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    fs::read_dir(path.parent()?).ok()?.flatten().map(|entry| entry.path()).find(|file| {
+        file.is_file()
+            && file.extension().is_some_and(|e| e != "meta")
+            && file.file_stem().and_then(|s| s.to_str()) == Some(name)
+    })
+}
+
+fn find_resource_folders(dir: &Path, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        if path.file_name().is_some_and(|name| name == "Resources") {
+            found.push(path.clone());
+        }
+        find_resource_folders(&path, found);
+    }
+}
+
+/// Paths of the entries of the Addressables groups of a project, by address
+fn find_addresses(project_root: &Path, packages: &ProjectPackages) -> HashMap<String, PathBuf> {
+    let Ok(groups) = fs::read_dir(project_root.join(ADDRESSABLE_GROUPS)) else {
+        return HashMap::new();
+    };
+    let entries: Vec<(String, String)> = groups
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == "asset"))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .flat_map(|content| parse_group_entries(&content))
+        .collect();
+    if entries.is_empty() {
+        return HashMap::new();
+    }
+
+    let mut guids: HashMap<String, PathBuf> = HashMap::new();
+    let mut wanted: HashSet<&str> = entries.iter().map(|(guid, _)| guid.as_str()).collect();
+    find_guid_paths(&project_root.join("Assets"), &mut wanted, &mut guids);
+    for package in packages.sorted() {
+        find_guid_paths(&package.path, &mut wanted, &mut guids);
+    }
+
+    entries
+        .into_iter()
+        .filter_map(|(guid, address)| Some((address, guids.get(&guid)?.clone())))
+        .collect()
+}
+
+/// GUIDs and addresses of the entries of an Addressables group asset
+///
+/// Group assets are YAML with Unity's tags, the entries are read line by line:
+/// ```text
+///   m_SerializeEntries:
+///   - m_GUID: 5d0c2a0f8c2b4e64f9a5a3c1b7e6d2f1
+///     m_Address: Icons/star.png
+/// ```
+fn parse_group_entries(content: &str) -> Vec<(String, String)> {
+    let mut entries = Vec::new();
+    let mut guid = None;
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(value) = line.strip_prefix("- m_GUID:") {
+            guid = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("m_Address:") {
+            if let Some(guid) = guid.take() {
+                entries.push((guid, unquote(value.trim()).to_string()));
+            }
+        }
+    }
+    entries
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(quote).and_then(|v| v.strip_suffix(quote)) {
+            return inner;
+        }
+    }
+    value
+}
+
+/// Add the paths of the assets whose meta file has one of the `wanted` GUIDs, removing them from `wanted`
+fn find_guid_paths(dir: &Path, wanted: &mut HashSet<&str>, found: &mut HashMap<String, PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if wanted.is_empty() {
+            return;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            find_guid_paths(&path, wanted, found);
+            continue;
+        }
+        if path.extension().is_none_or(|e| e != "meta") {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let guid = content.lines().find_map(|line| line.strip_prefix("guid:")).map(str::trim);
+        if let Some(guid) = guid.filter(|guid| wanted.contains(guid)) {
+            wanted.remove(guid);
+            found.insert(guid.to_string(), path.with_extension(""));
+        }
+    }
+}
+
 #[cfg(test)]
 #[path = "unity_asset_database_tests.rs"]
 mod tests;
\ No newline at end of file
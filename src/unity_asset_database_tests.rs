@@ -109,3 +109,52 @@ fn test_texture_asset_info_for_non_multiple_sprite() {
         }
     }
 }
+
+#[test]
+fn test_asset_path_resolver() {
+    use crate::unity_asset_database::AssetPathResolver;
+    use std::fs;
+
+    let project = tempfile::TempDir::new().unwrap();
+    let root = project.path();
+    for dir in ["Assets/Art/Resources/Icons", "Assets/UI/Addressable/Icons", "Assets/AddressableAssetsData/AssetGroups"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    fs::write(root.join("Assets/Art/Resources/Icons/star.png"), "").unwrap();
+    fs::write(root.join("Assets/Art/Resources/Icons/star.png.meta"), "guid: 11111111111111111111111111111111\n").unwrap();
+    fs::write(root.join("Assets/UI/Addressable/logo.png"), "").unwrap();
+    fs::write(root.join("Assets/UI/Addressable/logo.png.meta"), "fileFormatVersion: 2\nguid: 22222222222222222222222222222222\n").unwrap();
+    fs::write(root.join("Assets/UI/Addressable/Icons.meta"), "guid: 33333333333333333333333333333333\n").unwrap();
+    fs::write(root.join("Assets/UI/Addressable/Icons/moon.png"), "").unwrap();
+    fs::write(
+        root.join("Assets/AddressableAssetsData/AssetGroups/Default Local Group.asset"),
+        r#"%YAML 1.1
+%TAG !u! tag:unity3d.com,2011:
+--- !u!114 &11400000
+MonoBehaviour:
+  m_GroupName: Default Local Group
+  m_SerializeEntries:
+  - m_GUID: 22222222222222222222222222222222
+    m_Address: UI/Logo
+    m_ReadOnly: 0
+  - m_GUID: 33333333333333333333333333333333
+    m_Address: 'Icons'
+    m_ReadOnly: 0
+  - m_GUID: 44444444444444444444444444444444
+    m_Address: Gone
+"#,
+    )
+    .unwrap();
+
+    let resolver = AssetPathResolver::new(root);
+    assert_eq!(resolver.resolve_resource("Icons/star"), Some(root.join("Assets/Art/Resources/Icons/star.png")));
+    assert_eq!(resolver.resolve_resource("Icons/star.png"), None);
+    let url = Url::parse("project:///Assets/Resources/Icons/star").unwrap();
+    assert!(resolver.resolve_resource_url(&url).is_some());
+
+    assert_eq!(resolver.resolve_address("UI/Logo"), Some(root.join("Assets/UI/Addressable/logo.png")));
+    assert_eq!(resolver.resolve_address("Icons/moon.png"), Some(root.join("Assets/UI/Addressable/Icons/moon.png")));
+    assert_eq!(resolver.resolve_address("Icons/sun.png"), None);
+    assert_eq!(resolver.resolve_address("Gone"), None);
+    assert_eq!(resolver.resolve_address("Assets/UI/Addressable/logo.png"), None);
+}
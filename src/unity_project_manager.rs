@@ -3,14 +3,20 @@
 //! This module provides functionality to manage Unity projects and detect
 //! their versions and configurations.
 
+use std::fmt;
 use std::path::PathBuf;
 use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 use serde::Deserialize;
 use url::Url;
 use std::io;
 
 use crate::language::asset_url::create_project_url_with_normalization;
 
+/// Version of the Unity documentation linked to when the project's Unity version isn't known
+pub const DEFAULT_DOCS_VERSION: &str = "6000.0";
+
 /// Represents the structure of Unity's ProjectVersion.txt file
 #[derive(Debug, Deserialize)]
 struct ProjectVersion {
@@ -34,10 +40,42 @@ pub enum UnityProjectError {
     InvalidVersionFormat(String),
 }
 
+/// A Unity version reduced to major and minor numbers (e.g. `2022.3`, `6000.0`)
+///
+/// Ordered by major then minor version, which is what matters for feature availability.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UnityVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl UnityVersion {
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+
+    /// Parse a version string like "6000.0.51f1" or "2022.3", anything after the minor version is ignored
+    pub fn parse(version: &str) -> Option<Self> {
+        let mut parts = version.trim().split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        Some(Self { major, minor })
+    }
+}
+
+impl fmt::Display for UnityVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
 /// Manages Unity project information and provides version detection capabilities.
 #[derive(Debug, Clone)]
 pub struct UnityProjectManager {
     project_path: PathBuf,
+    /// Editor version read from ProjectVersion.txt and the modification time of the file then,
+    /// shared by clones so the file is only read again when Unity changes it
+    version_cache: Arc<Mutex<Option<(SystemTime, String)>>>,
 }
 
 impl UnityProjectManager {
@@ -56,7 +94,10 @@ impl UnityProjectManager {
     /// let manager = UnityProjectManager::new(PathBuf::from("/path/to/unity/project"));
     /// ```
     pub fn new(project_path: PathBuf) -> Self {
-        Self { project_path }
+        Self {
+            project_path,
+            version_cache: Arc::new(Mutex::new(None)),
+        }
     }
 
     /// Checks if the project path is a valid Unity project.
@@ -74,7 +115,7 @@ impl UnityProjectManager {
     /// Detects the Unity version of the project.
     ///
     /// Reads and parses the ProjectSettings/ProjectVersion.txt file to extract
-    /// the Unity editor version.
+    /// the Unity editor version. The version is cached until the file is modified.
     ///
     /// # Returns
     ///
@@ -92,8 +133,15 @@ impl UnityProjectManager {
             .join("ProjectSettings")
             .join("ProjectVersion.txt");
 
-        if !project_version_path.exists() {
-            return Err(UnityProjectError::NotUnityProject);
+        let modified = match fs::metadata(&project_version_path) {
+            Ok(metadata) => metadata.modified()?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Err(UnityProjectError::NotUnityProject),
+            Err(e) => return Err(e.into()),
+        };
+        if let Some((cached_modified, version)) = self.version_cache.lock().ok().and_then(|cache| cache.clone()) {
+            if cached_modified == modified {
+                return Ok(version);
+            }
         }
 
         let content = fs::read_to_string(&project_version_path)?;
@@ -106,6 +154,9 @@ impl UnityProjectManager {
             ));
         }
 
+        if let Ok(mut cache) = self.version_cache.lock() {
+            *cache = Some((modified, project_version.editor_version.clone()));
+        }
         Ok(project_version.editor_version)
     }
 
@@ -156,6 +207,15 @@ impl UnityProjectManager {
         }
     }
 
+    /// Gets the parsed Unity version of the project, used to check feature availability.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the project is invalid or the version can't be parsed.
+    pub fn get_parsed_unity_version(&self) -> Option<UnityVersion> {
+        UnityVersion::parse(&self.detect_unity_version().ok()?)
+    }
+
     /// Returns the project path.
     pub fn project_path(&self) -> &PathBuf {
         &self.project_path
@@ -277,6 +337,36 @@ mod tests {
         assert_eq!(doc_version, None);
     }
 
+    #[test]
+    fn test_parsed_unity_version() {
+        let temp_dir = create_test_unity_project("2022.3.42f1");
+        let manager = UnityProjectManager::new(temp_dir.path().to_path_buf());
+        assert_eq!(manager.get_parsed_unity_version(), Some(UnityVersion::new(2022, 3)));
+
+        assert_eq!(UnityVersion::parse("6000.0"), Some(UnityVersion::new(6000, 0)));
+        assert_eq!(UnityVersion::parse("6000"), None);
+        assert_eq!(UnityVersion::parse("abc.1"), None);
+
+        assert!(UnityVersion::new(2021, 3) < UnityVersion::new(2022, 2));
+        assert!(UnityVersion::new(2022, 10) > UnityVersion::new(2022, 2));
+        assert!(UnityVersion::new(6000, 0) > UnityVersion::new(2023, 3));
+        assert_eq!(UnityVersion::new(2022, 2).to_string(), "2022.2");
+    }
+
+    #[test]
+    fn test_version_cached_until_modified() {
+        let temp_dir = create_test_unity_project("2022.3.42f1");
+        let path = temp_dir.path().join("ProjectSettings").join("ProjectVersion.txt");
+        let manager = UnityProjectManager::new(temp_dir.path().to_path_buf());
+        assert_eq!(manager.get_unity_version(), Some("2022.3.42f1".to_string()));
+
+        // Unity upgraded the project
+        fs::write(&path, "m_EditorVersion: 6000.0.51f1\n").unwrap();
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + std::time::Duration::from_secs(10)).unwrap();
+        assert_eq!(manager.clone().get_unity_version(), Some("6000.0.51f1".to_string()));
+    }
+
     #[test]
     fn test_malformed_yaml() {
         let temp_dir = TempDir::new().unwrap();
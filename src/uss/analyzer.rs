@@ -0,0 +1,150 @@
+//! USS Analyzer
+//!
+//! The entry point for other Rust tools that use the USS engine without the language server. A style
+//! sheet is parsed and analyzed in one call, the result has its diagnostics, the assets it references
+//! and its variables. Formatting and the definitions used by completion are available too.
+//!
+//! ```ignore
+//! use unity_code_native::UssAnalyzer;
+//!
+//! let mut analyzer = UssAnalyzer::new().with_unity_version("6000.0");
+//! let result = analyzer.analyze(".title { colr: red; }").unwrap();
+//! assert_eq!(result.diagnostics.len(), 1);
+//! ```
+//!
+//! Nothing is read from disk, so missing assets and imports are not reported, see
+//! [`AnalysisResult::asset_references`] to check them.
+
+use tower_lsp::lsp_types::Diagnostic;
+use tree_sitter::Tree;
+use url::Url;
+
+use crate::language::tree_utils::position_to_byte_offset;
+use crate::unity_project_manager::UnityVersion;
+use crate::uss::definitions::UssDefinitions;
+use crate::uss::diagnostics::{Suppressions, UssDiagnostics};
+use crate::uss::formatter::UssFormatter;
+use crate::uss::parser::UssParser;
+use crate::uss::settings::DiagnosticSettings;
+use crate::uss::url_function_node::UrlReference;
+use crate::uss::variable_resolver::VariableResolver;
+
+/// Analyzes style sheets, reuse it for many style sheets
+pub struct UssAnalyzer {
+    parser: UssParser,
+    diagnostics: UssDiagnostics,
+    formatter: UssFormatter,
+}
+
+/// Result of [`UssAnalyzer::analyze`]
+#[derive(Debug, Clone)]
+pub struct AnalysisResult {
+    /// Syntax tree of the style sheet
+    pub tree: Tree,
+    /// Diagnostics, without the ones suppressed by comments in the style sheet
+    pub diagnostics: Vec<Diagnostic>,
+    /// Assets referenced with `@import`, `url()` and `resource()`
+    pub asset_references: Vec<UrlReference>,
+    /// Variables declared in the style sheet, with their resolved values
+    pub variables: VariableResolver,
+}
+
+impl Default for UssAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UssAnalyzer {
+    /// Analyzer with the default settings, that doesn't check compatibility with a Unity version
+    pub fn new() -> Self {
+        Self {
+            parser: UssParser::default(),
+            diagnostics: UssDiagnostics::new(),
+            formatter: UssFormatter::new(),
+        }
+    }
+
+    /// Check compatibility with a Unity version, e.g. `2022.3` or `6000.0.51f1`, ignored if it can't be parsed
+    pub fn with_unity_version(mut self, version: &str) -> Self {
+        self.diagnostics.set_unity_version(UnityVersion::parse(version));
+        self
+    }
+
+    /// Set the severities and the diagnostics that are turned off
+    pub fn with_settings(mut self, settings: DiagnosticSettings) -> Self {
+        self.diagnostics.set_settings(settings);
+        self
+    }
+
+    /// The USS properties, keywords, functions and pseudo-classes, as used by completion and hover
+    pub fn definitions(&self) -> &'static UssDefinitions {
+        UssDefinitions::shared()
+    }
+
+    /// Analyze a style sheet, relative URLs in it are not resolved
+    ///
+    /// `None` if it can't be parsed, which only happens if the parser gives up.
+    pub fn analyze(&mut self, text: &str) -> Option<AnalysisResult> {
+        self.analyze_with_source_url(text, None)
+    }
+
+    /// Analyze a style sheet whose URL is `source_url`, e.g. `project:///Assets/UI/Main.uss`,
+    /// relative URLs in it are resolved against it
+    pub fn analyze_with_source_url(&mut self, text: &str, source_url: Option<&Url>) -> Option<AnalysisResult> {
+        let tree = self.parser.parse(text, None)?;
+
+        let mut variables = VariableResolver::new(UssDefinitions::shared());
+        variables.add_variables_from_tree_with_source_url(tree.root_node(), text, source_url);
+        let (mut diagnostics, asset_references) =
+            self.diagnostics
+                .analyze_with_variables_and_classes(&tree, text, source_url, Some(&variables), None);
+        Suppressions::from_tree(&tree, text).apply(&mut diagnostics);
+
+        Some(AnalysisResult {
+            tree,
+            diagnostics,
+            asset_references,
+            variables,
+        })
+    }
+
+    /// The formatted style sheet, an error if it has syntax errors
+    pub fn format(&mut self, text: &str) -> Result<String, String> {
+        let tree = self.parser.parse(text, None).ok_or("Failed to parse the style sheet")?;
+        let mut edits = self.formatter.format_document(text, &tree)?;
+        edits.sort_by_key(|edit| edit.range.start);
+
+        let mut result = text.to_string();
+        for edit in edits.iter().rev() {
+            let start = position_to_byte_offset(text, edit.range.start).ok_or("Invalid edit range")?;
+            let end = position_to_byte_offset(text, edit.range.end).ok_or("Invalid edit range")?;
+            result.replace_range(start..end, &edit.new_text);
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uss::url_references::UrlReferenceKind;
+
+    #[test]
+    fn test_analyze() {
+        let mut analyzer = UssAnalyzer::new().with_unity_version("6000.0");
+        let text = ":root { --accent: red; }\n.title { colr: var(--accent); background-image: url(\"Icons/star.png\"); }\n/* uss-disable-next-line */\n.b { colr: blue; }";
+        let source_url = Url::parse("project:///Assets/UI/Main.uss").unwrap();
+        let result = analyzer.analyze_with_source_url(text, Some(&source_url)).unwrap();
+
+        assert!(!result.tree.root_node().has_error());
+        let lines: Vec<_> = result.diagnostics.iter().map(|d| d.range.start.line).collect();
+        assert_eq!(lines, vec![1], "{:?}", result.diagnostics);
+        assert_eq!(result.asset_references.len(), 1);
+        assert_eq!(result.asset_references[0].kind, UrlReferenceKind::Url);
+        assert_eq!(result.asset_references[0].url.as_str(), "project:///Assets/UI/Icons/star.png");
+        assert!(result.variables.get_variable("accent").is_some());
+
+        assert!(analyzer.definitions().is_predefined_property("color"));
+    }
+}
@@ -0,0 +1,277 @@
+//! Diagnostics Baseline
+//!
+//! Teams that start checking existing style sheets can record their current diagnostics in a baseline,
+//! `UnityCode.baseline.json` in the project root. Diagnostics in the baseline are not reported, new ones
+//! are, so the number of problems can only go down. The baseline is written with
+//! [`UPDATE_BASELINE_COMMAND`], which is also how it is updated after problems are fixed.
+//!
+//! A diagnostic is recorded by its code, its message and the text it covers, not by its position, so
+//! editing other parts of a file doesn't make it new. The same diagnostic appearing more times than
+//! recorded is reported for the extra occurrences.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tower_lsp::lsp_types::{Diagnostic, NumberOrString};
+
+use crate::language::tree_utils::position_to_byte_offset;
+
+/// Name of the baseline file in the project root
+pub const BASELINE_FILE_NAME: &str = "UnityCode.baseline.json";
+
+/// Command that records the current diagnostics of all style sheets as the baseline, returns [`BaselineSummary`]
+pub const UPDATE_BASELINE_COMMAND: &str = "unityCode/updateDiagnosticsBaseline";
+
+/// Version of the file format
+const BASELINE_VERSION: u32 = 1;
+
+/// Longest text of a diagnostic that is recorded, longer texts are cut
+const MAX_TEXT_LENGTH: usize = 120;
+
+/// Errors of reading or writing the baseline file
+#[derive(Debug, Error)]
+pub enum BaselineError {
+    #[error("Failed to access {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Invalid {BASELINE_FILE_NAME}: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("{BASELINE_FILE_NAME} has version {0}, this version only reads version {BASELINE_VERSION}")]
+    UnsupportedVersion(u32),
+}
+
+/// A recorded diagnostic
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    /// Diagnostic code, empty if the diagnostic has none
+    pub code: String,
+    pub message: String,
+    /// Text covered by the diagnostic, with whitespace collapsed
+    pub text: String,
+    /// Number of times the diagnostic appears in the file
+    pub count: usize,
+}
+
+/// Content of the baseline file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiagnosticsBaseline {
+    pub version: u32,
+    /// Recorded diagnostics by project relative path, e.g. `Assets/UI/Main.uss`
+    pub files: BTreeMap<String, Vec<BaselineEntry>>,
+}
+
+impl Default for DiagnosticsBaseline {
+    fn default() -> Self {
+        Self {
+            version: BASELINE_VERSION,
+            files: BTreeMap::new(),
+        }
+    }
+}
+
+/// What [`UPDATE_BASELINE_COMMAND`] recorded
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BaselineSummary {
+    /// Number of files with recorded diagnostics
+    pub files: usize,
+    /// Number of recorded diagnostics
+    pub diagnostics: usize,
+}
+
+impl DiagnosticsBaseline {
+    /// Path of the baseline file of a project
+    pub fn path(project_root: &Path) -> PathBuf {
+        project_root.join(BASELINE_FILE_NAME)
+    }
+
+    /// Load the baseline of a project, `None` if the project doesn't have one
+    pub fn load(project_root: &Path) -> Result<Option<Self>, BaselineError> {
+        let path = Self::path(project_root);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(BaselineError::Io { path, source }),
+        };
+        let baseline: Self = serde_json::from_str(&text)?;
+        if baseline.version != BASELINE_VERSION {
+            return Err(BaselineError::UnsupportedVersion(baseline.version));
+        }
+        Ok(Some(baseline))
+    }
+
+    /// Write the baseline of a project, files are sorted so the file diffs well
+    pub fn save(&self, project_root: &Path) -> Result<(), BaselineError> {
+        let path = Self::path(project_root);
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        std::fs::write(&path, json).map_err(|source| BaselineError::Io { path, source })
+    }
+
+    /// Record the diagnostics of a file, replacing what was recorded for it
+    pub fn set_file(&mut self, path: &str, diagnostics: &[Diagnostic], content: &str) {
+        let mut counts: BTreeMap<(String, String, String), usize> = BTreeMap::new();
+        for diagnostic in diagnostics {
+            *counts.entry(fingerprint(diagnostic, content)).or_default() += 1;
+        }
+
+        if counts.is_empty() {
+            self.files.remove(path);
+            return;
+        }
+        let entries = counts
+            .into_iter()
+            .map(|((code, message, text), count)| BaselineEntry { code, message, text, count })
+            .collect();
+        self.files.insert(path.to_string(), entries);
+    }
+
+    /// Remove the recorded diagnostics of a file, returns how many were removed
+    pub fn filter(&self, path: &str, diagnostics: &mut Vec<Diagnostic>, content: &str) -> usize {
+        let Some(entries) = self.files.get(path) else {
+            return 0;
+        };
+        let mut remaining: BTreeMap<(String, String, String), usize> = entries
+            .iter()
+            .map(|entry| ((entry.code.clone(), entry.message.clone(), entry.text.clone()), entry.count))
+            .collect();
+
+        let before = diagnostics.len();
+        diagnostics.retain(|diagnostic| {
+            match remaining.get_mut(&fingerprint(diagnostic, content)) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            }
+        });
+        before - diagnostics.len()
+    }
+
+    pub fn summary(&self) -> BaselineSummary {
+        BaselineSummary {
+            files: self.files.len(),
+            diagnostics: self.files.values().flatten().map(|entry| entry.count).sum(),
+        }
+    }
+}
+
+/// What identifies a diagnostic in a file: its code, message and text
+fn fingerprint(diagnostic: &Diagnostic, content: &str) -> (String, String, String) {
+    let code = match &diagnostic.code {
+        Some(NumberOrString::String(code)) => code.clone(),
+        Some(NumberOrString::Number(code)) => code.to_string(),
+        None => String::new(),
+    };
+    let start = position_to_byte_offset(content, diagnostic.range.start);
+    let end = position_to_byte_offset(content, diagnostic.range.end);
+    let text = match (start, end) {
+        (Some(start), Some(end)) if start <= end => content[start..end].split_whitespace().collect::<Vec<_>>().join(" "),
+        _ => String::new(),
+    };
+    let text = match text.char_indices().nth(MAX_TEXT_LENGTH) {
+        Some((cut, _)) => text[..cut].to_string(),
+        None => text,
+    };
+    (code, diagnostic.message.clone(), text)
+}
+
+/// The baseline of a project, read again when the file changes
+#[derive(Debug)]
+pub struct BaselineFile {
+    project_root: PathBuf,
+    baseline: Option<DiagnosticsBaseline>,
+    /// Modification time of the file when it was read, `None` if it didn't exist
+    modified: Option<SystemTime>,
+    is_loaded: bool,
+}
+
+impl BaselineFile {
+    pub fn new(project_root: PathBuf) -> Self {
+        Self {
+            project_root,
+            baseline: None,
+            modified: None,
+            is_loaded: false,
+        }
+    }
+
+    /// The current baseline, `None` if the project has none or it is invalid
+    pub fn baseline(&mut self) -> Option<&DiagnosticsBaseline> {
+        let modified = std::fs::metadata(DiagnosticsBaseline::path(&self.project_root))
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if !self.is_loaded || modified != self.modified {
+            self.baseline = DiagnosticsBaseline::load(&self.project_root).unwrap_or_else(|e| {
+                log::warn!("Ignoring the diagnostics baseline: {}", e);
+                None
+            });
+            self.modified = modified;
+            self.is_loaded = true;
+        }
+        self.baseline.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use tower_lsp::lsp_types::{Position, Range};
+
+    fn diagnostic(code: &str, message: &str, line: u32, start: u32, end: u32) -> Diagnostic {
+        Diagnostic {
+            range: Range::new(Position::new(line, start), Position::new(line, end)),
+            code: Some(NumberOrString::String(code.to_string())),
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_filter_baselined_diagnostics() {
+        let content = ".a { colr: red; }\n.b { colr: red; }\n";
+        let unknown = |line| diagnostic("unknown-property", "Unknown property 'colr'", line, 5, 9);
+        let mut baseline = DiagnosticsBaseline::default();
+        baseline.set_file("Assets/a.uss", &[unknown(0), unknown(1)], content);
+        assert_eq!(baseline.summary(), BaselineSummary { files: 1, diagnostics: 2 });
+        assert_eq!(baseline.files["Assets/a.uss"][0].text, "colr");
+
+        // a rule was added before, the recorded diagnostics moved but are still known
+        let content = ".z { }\n.a { colr: red; }\n.b { colr: red; }\n.c { colr: red; width: x; }\n";
+        let mut diagnostics = vec![unknown(1), unknown(2), unknown(3), diagnostic("invalid-value", "Invalid value", 3, 23, 24)];
+        assert_eq!(baseline.filter("Assets/a.uss", &mut diagnostics, content), 2);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].range.start.line, 3);
+
+        // other files are not affected
+        let mut diagnostics = vec![unknown(1)];
+        assert_eq!(baseline.filter("Assets/b.uss", &mut diagnostics, content), 0);
+
+        baseline.set_file("Assets/a.uss", &[], content);
+        assert!(baseline.files.is_empty());
+    }
+
+    #[test]
+    fn test_baseline_file() {
+        let project = TempDir::new().unwrap();
+        let mut file = BaselineFile::new(project.path().to_path_buf());
+        assert!(file.baseline().is_none());
+
+        let mut baseline = DiagnosticsBaseline::default();
+        baseline.set_file("Assets/a.uss", &[diagnostic("unknown-property", "Unknown", 0, 0, 1)], "x");
+        baseline.save(project.path()).unwrap();
+        assert_eq!(file.baseline(), Some(&baseline));
+
+        std::fs::write(DiagnosticsBaseline::path(project.path()), r#"{"version": 99, "files": {}}"#).unwrap();
+        assert!(matches!(DiagnosticsBaseline::load(project.path()), Err(BaselineError::UnsupportedVersion(99))));
+    }
+}
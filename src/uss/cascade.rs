@@ -0,0 +1,296 @@
+//! Declaration Cascade
+//!
+//! Hover of a property in a rule also lists the other rules that set the property for the same elements,
+//! and which declaration wins: the one with the highest specificity, and of equal ones the last. Style
+//! sheets imported with `@import` come before the importing style sheet, depth first in the order they
+//! are written, like in the theme report.
+//!
+//! Which elements a rule applies to is only known at runtime, so rules are compared by the subjects of
+//! their selectors, see [`CompoundSelector::overlaps`]. Rules that only apply in some states, e.g. with
+//! `:hover`, take part with their specificity.
+//!
+//! [`CompoundSelector::overlaps`]: crate::uss::selector::CompoundSelector::overlaps
+
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::path::Path;
+
+use tower_lsp::lsp_types::{Position, Range};
+use tree_sitter::{Node, Tree};
+use url::Url;
+
+use crate::language::asset_url::{project_url_to_path, project_url_to_relative_path};
+use crate::language::tree_utils::{find_node_of_type_at_position, node_to_range};
+use crate::uss::constants::*;
+use crate::uss::import_graph::UssImportGraph;
+use crate::uss::parser::UssParser;
+use crate::uss::rule_matching::rule_declarations;
+use crate::uss::selector::{parse_rule_selectors, rule_selector_texts, ComplexSelector};
+
+/// A declaration of the hovered property in a rule that targets the same elements as the hovered rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct CascadeEntry {
+    /// Style sheet of the rule, `None` for the hovered document
+    pub file: Option<Url>,
+    /// The selector of the rule that targets the same elements, the one with the highest specificity if several do
+    pub selector: String,
+    /// Specificity of the selector as (names, classes and pseudo-classes, types)
+    pub specificity: (u32, u32, u32),
+    pub value: String,
+    /// Range of the declaration
+    pub range: Range,
+    /// Whether this is the hovered declaration
+    pub is_hovered: bool,
+}
+
+/// Declarations of the property whose name is at `position`, the one that wins first
+///
+/// `load_imports` returns the style sheets the document imports with their content, in cascade order,
+/// see [`read_style_sheets`]. It's only called if the position is on the name of a property in a rule,
+/// otherwise the result is empty. Variables are left out, their values are shown by the theme report.
+pub fn declaration_cascade(
+    tree: &Tree,
+    content: &str,
+    position: Position,
+    load_imports: impl FnOnce() -> Vec<(Url, String)>,
+) -> Vec<CascadeEntry> {
+    let Some((property, declaration, rule_set)) = property_at_position(tree, content, position) else {
+        return Vec::new();
+    };
+    let hovered_selectors = parse_rule_selectors(rule_set, content);
+    if hovered_selectors.is_empty() {
+        return Vec::new();
+    }
+    let hovered_range = node_to_range(declaration, content);
+
+    let mut entries = Vec::new();
+    if let Ok(mut parser) = UssParser::new() {
+        for (url, imported_content) in load_imports() {
+            if let Some(imported_tree) = parser.parse(&imported_content, None) {
+                collect_entries(&imported_tree, &imported_content, Some(&url), property, &hovered_selectors, None, &mut entries);
+            }
+        }
+    }
+    collect_entries(tree, content, None, property, &hovered_selectors, Some(hovered_range), &mut entries);
+
+    // stable, so of equal specificities the later one comes first once the order is reversed
+    entries.reverse();
+    entries.sort_by_key(|entry| Reverse(entry.specificity));
+    entries
+}
+
+/// Markdown with the declarations of [`declaration_cascade`], `None` if no other rule sets the property
+pub fn hover_section(entries: &[CascadeEntry]) -> Option<String> {
+    if entries.len() < 2 {
+        return None;
+    }
+
+    let mut content = String::from("**Cascade:**\n");
+    for (index, entry) in entries.iter().enumerate() {
+        let line = entry.range.start.line + 1;
+        let location = match &entry.file {
+            Some(url) => format!("{}:{}", project_url_to_relative_path(url).unwrap_or_else(|| url.to_string()), line),
+            None => format!("line {}", line),
+        };
+        let (names, classes, types) = entry.specificity;
+        content.push_str(&format!(
+            "{}. `{}` `{}`, specificity ({}, {}, {}), {}",
+            index + 1,
+            entry.selector,
+            entry.value,
+            names,
+            classes,
+            types,
+            location
+        ));
+        if index == 0 {
+            content.push_str(" **wins**");
+        }
+        if entry.is_hovered {
+            content.push_str(" (this declaration)");
+        }
+        content.push('\n');
+    }
+    Some(content)
+}
+
+/// Whether the position is on the name of a property in a rule, only then [`declaration_cascade`] has entries
+pub fn is_on_property(tree: &Tree, content: &str, position: Position) -> bool {
+    property_at_position(tree, content, position).is_some()
+}
+
+/// The style sheets a document imports, directly or through other style sheets, in cascade order
+pub fn imported_urls(import_graph: &UssImportGraph, url: &Url) -> Vec<Url> {
+    let mut visited = HashSet::from([url.clone()]);
+    let mut order = Vec::new();
+    collect_imports(import_graph, url, &mut visited, &mut order);
+    order
+}
+
+/// Content of style sheets, the ones without content, e.g. because they aren't open, are read from disk
+///
+/// Style sheets that can't be read are left out. Reads files, async code should call it with `spawn_blocking`.
+pub fn read_style_sheets(project_root: &Path, style_sheets: Vec<(Url, Option<String>)>) -> Vec<(Url, String)> {
+    style_sheets
+        .into_iter()
+        .filter_map(|(url, content)| {
+            let content = content.or_else(|| {
+                project_url_to_path(project_root, &url).and_then(|path| std::fs::read_to_string(path).ok())
+            })?;
+            Some((url, content))
+        })
+        .collect()
+}
+
+fn collect_imports(import_graph: &UssImportGraph, url: &Url, visited: &mut HashSet<Url>, order: &mut Vec<Url>) {
+    for edge in import_graph.imports_of(url) {
+        if edge.url.scheme() == PROJECT_SCHEME && visited.insert(edge.url.clone()) {
+            collect_imports(import_graph, &edge.url, visited, order);
+            order.push(edge.url.clone());
+        }
+    }
+}
+
+/// Name of the property at a position with its declaration and rule set
+fn property_at_position<'a>(tree: &'a Tree, content: &'a str, position: Position) -> Option<(&'a str, Node<'a>, Node<'a>)> {
+    let declaration = find_node_of_type_at_position(tree.root_node(), content, position, NODE_DECLARATION)?;
+    let name_node = declaration.child(0).filter(|n| n.kind() == NODE_PROPERTY_NAME)?;
+    let name_range = node_to_range(name_node, content);
+    if position < name_range.start || position > name_range.end {
+        return None;
+    }
+    let property = name_node.utf8_text(content.as_bytes()).ok()?;
+    if property.starts_with("--") {
+        return None;
+    }
+
+    let rule_set = declaration.parent()?.parent().filter(|n| n.kind() == NODE_RULE_SET)?;
+    Some((property, declaration, rule_set))
+}
+
+/// Add the declarations of `property` in rules of a style sheet that target the same elements, in source order
+fn collect_entries(
+    tree: &Tree,
+    content: &str,
+    file: Option<&Url>,
+    property: &str,
+    hovered_selectors: &[ComplexSelector],
+    hovered_range: Option<Range>,
+    entries: &mut Vec<CascadeEntry>,
+) {
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    for rule_set in root.children(&mut cursor).filter(|n| n.kind() == NODE_RULE_SET) {
+        let best = rule_selector_texts(rule_set, content)
+            .into_iter()
+            .filter_map(|text| Some((text, ComplexSelector::parse(text)?)))
+            .filter(|(_, selector)| {
+                hovered_selectors.iter().any(|hovered| hovered.subject().overlaps(selector.subject()))
+            })
+            .max_by_key(|(_, selector)| selector.specificity());
+        let Some((text, selector)) = best else {
+            continue;
+        };
+
+        for declaration in rule_declarations(rule_set, content) {
+            if declaration.property != property {
+                continue;
+            }
+            entries.push(CascadeEntry {
+                file: file.cloned(),
+                selector: text.trim().to_string(),
+                specificity: selector.specificity(),
+                value: declaration.value,
+                is_hovered: hovered_range == Some(declaration.range),
+                range: declaration.range,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USS: &str = r#"Button {
+    color: red;
+}
+.primary {
+    color: blue;
+    margin: 4px;
+}
+.title {
+    color: white;
+}
+#ok:hover {
+    color: green;
+}
+.primary {
+    color: yellow;
+}
+"#;
+
+    fn cascade(content: &str, position: Position, imports: &[(Url, String)]) -> Vec<CascadeEntry> {
+        let tree = UssParser::new().unwrap().parse(content, None).unwrap();
+        declaration_cascade(&tree, content, position, || imports.to_vec())
+    }
+
+    #[test]
+    fn test_declaration_cascade() {
+        // `color` of the first `.primary` rule
+        let entries = cascade(USS, Position::new(4, 6), &[]);
+        let selectors: Vec<(&str, &str)> = entries.iter().map(|e| (e.selector.as_str(), e.value.as_str())).collect();
+        assert_eq!(selectors, vec![("#ok:hover", "green"), (".primary", "yellow"), (".primary", "blue"), ("Button", "red")]);
+        assert!(entries[2].is_hovered);
+        assert_eq!(entries[2].range.start.line, 4);
+
+        let section = hover_section(&entries).unwrap();
+        assert!(section.contains("1. `#ok:hover` `green`, specificity (1, 1, 0), line 12 **wins**"), "{}", section);
+        assert!(section.contains("3. `.primary` `blue`, specificity (0, 1, 0), line 5 (this declaration)"), "{}", section);
+
+        // only the hovered rule sets `margin`
+        let entries = cascade(USS, Position::new(5, 6), &[]);
+        assert_eq!(entries.len(), 1);
+        assert!(hover_section(&entries).is_none());
+
+        // on the value, not the property
+        assert!(cascade(USS, Position::new(4, 12), &[]).is_empty());
+    }
+
+    #[test]
+    fn test_imported_rules_come_first() {
+        let imported = (
+            Url::parse("project:///Assets/UI/Base.uss").unwrap(),
+            ".primary { color: black; }\n.title { color: gray; }".to_string(),
+        );
+        let content = ".primary { color: blue; }";
+        let entries = cascade(content, Position::new(0, 12), &[imported]);
+
+        // same specificity, the importing style sheet wins
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].is_hovered);
+        assert_eq!(entries[1].file.as_ref().map(Url::path), Some("/Assets/UI/Base.uss"));
+
+        let section = hover_section(&entries).unwrap();
+        assert!(section.contains("`black`, specificity (0, 1, 0), Assets/UI/Base.uss:1"), "{}", section);
+    }
+
+    #[test]
+    fn test_read_style_sheets() {
+        let project = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(project.path().join("Assets/UI")).unwrap();
+        std::fs::write(project.path().join("Assets/UI/Base.uss"), ".saved {}").unwrap();
+        std::fs::write(project.path().join("Assets/UI/Theme.uss"), ".saved {}").unwrap();
+        let url = |path: &str| Url::parse(&format!("project:///Assets/UI/{}", path)).unwrap();
+
+        // open style sheets have unsaved changes, missing ones are left out
+        let style_sheets = read_style_sheets(
+            project.path(),
+            vec![(url("Base.uss"), None), (url("Missing.uss"), None), (url("Theme.uss"), Some(".unsaved {}".to_string()))],
+        );
+        assert_eq!(
+            style_sheets,
+            vec![(url("Base.uss"), ".saved {}".to_string()), (url("Theme.uss"), ".unsaved {}".to_string())]
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! USS Code Lens
+//!
+//! Shows "N usages in UXML" above rules, and the resolved value and usages of the variable above
+//! custom property declarations, e.g. "#ff0000 · 3 usages · 1 override".
+//!
+//! Code lenses are returned unresolved, the counts are computed in the resolve step,
+//! so opening a large file doesn't pay the cost of indexing UXML files upfront.
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{CodeLens, Command, Location, Range, Url};
+use tree_sitter::Node;
+
+use crate::language::tree_utils::{node_to_range, position_to_byte_offset};
+use crate::uss::constants::*;
+use crate::uss::selector::{rule_selector_texts, ComplexSelector};
+use crate::uss::unused_variables::{reference_ranges, VariableLocations};
+use crate::uss::variable_resolver::{VariableResolver, VariableStatus};
+use crate::uxml::usage_index::UxmlUsageIndex;
+
+/// Longest value shown in a variable lens, longer values are cut
+const MAX_VALUE_LENGTH: usize = 40;
+
+/// Command executed when a code lens is clicked, arguments are the document uri,
+/// the lens position and the reference locations
+pub const SHOW_REFERENCES_COMMAND: &str = "uss.showReferences";
+
+/// Data attached to an unresolved code lens
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum CodeLensData {
+    /// Lens above a rule, resolves to the UXML elements matched by its selectors
+    Selector { uri: Url, selectors: Vec<String> },
+    /// Lens above a custom property declaration, resolves to its value and the references of the variable
+    Variable { uri: Url, name: String },
+}
+
+/// USS code lens provider
+pub struct UssCodeLensProvider {}
+
+impl UssCodeLensProvider {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Create unresolved code lenses for all rules and custom property declarations
+    pub fn provide_code_lenses(&self, root: Node, content: &str, uri: &Url) -> Vec<CodeLens> {
+        let mut lenses = Vec::new();
+        self.collect_code_lenses(root, content, uri, &mut lenses);
+        lenses
+    }
+
+    fn collect_code_lenses(&self, node: Node, content: &str, uri: &Url, lenses: &mut Vec<CodeLens>) {
+        match node.kind() {
+            NODE_RULE_SET => {
+                if let Some(selectors) = node.child(0).filter(|n| n.kind() == NODE_SELECTORS) {
+                    let texts = rule_selector_texts(node, content);
+                    if !texts.is_empty() {
+                        lenses.push(unresolved_lens(
+                            node_to_range(selectors, content),
+                            CodeLensData::Selector {
+                                uri: uri.clone(),
+                                selectors: texts.iter().map(|t| t.to_string()).collect(),
+                            },
+                        ));
+                    }
+                }
+            }
+            NODE_DECLARATION => {
+                if let Some(property) = node.child(0).filter(|n| n.kind() == NODE_PROPERTY_NAME) {
+                    if let Ok(name) = property.utf8_text(content.as_bytes()) {
+                        if name.starts_with("--") {
+                            lenses.push(unresolved_lens(
+                                node_to_range(property, content),
+                                CodeLensData::Variable {
+                                    uri: uri.clone(),
+                                    name: name.to_string(),
+                                },
+                            ));
+                        }
+                    }
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        for i in 0..node.child_count() {
+            if let Some(child) = node.child(i) {
+                self.collect_code_lenses(child, content, uri, lenses);
+            }
+        }
+    }
+
+    /// Resolve a code lens by computing its references
+    ///
+    /// `variables` resolves the values of variables of the document, `elsewhere` are the references and
+    /// declarations of the lens variable in other style sheets (the ones that import the document). `usage_index` is used
+    /// as it is, the caller refreshes it when it's stale. Lenses without valid data are returned unchanged.
+    pub fn resolve_code_lens(
+        &self,
+        mut lens: CodeLens,
+        root: Node,
+        content: &str,
+        usage_index: &UxmlUsageIndex,
+        variables: Option<&VariableResolver>,
+        elsewhere: &VariableLocations,
+    ) -> CodeLens {
+        let Some(data) = lens
+            .data
+            .clone()
+            .and_then(|d| serde_json::from_value::<CodeLensData>(d).ok())
+        else {
+            return lens;
+        };
+
+        let (uri, title, locations) = match data {
+            CodeLensData::Selector { uri, selectors } => {
+                let selectors: Vec<ComplexSelector> = selectors
+                    .iter()
+                    .filter_map(|s| ComplexSelector::parse(s))
+                    .collect();
+
+                let locations: Vec<Location> = usage_index
+                    .find_matching_elements(&selectors)
+                    .iter()
+                    .filter_map(|m| m.location())
+                    .collect();
+
+                let title = match locations.len() {
+                    1 => "1 usage in UXML".to_string(),
+                    n => format!("{} usages in UXML", n),
+                };
+                (uri, title, locations)
+            }
+            CodeLensData::Variable { uri, name } => {
+                let mut overrides = Vec::new();
+                self.find_variable_declarations(root, content, &uri, &name, &mut overrides);
+                // the declaration the lens sits on is not an override of itself
+                overrides.retain(|l| l.range != lens.range);
+                overrides.extend_from_slice(&elsewhere.declarations);
+
+                let mut locations: Vec<Location> = reference_ranges(root, content, &name)
+                    .into_iter()
+                    .map(|range| Location { uri: uri.clone(), range })
+                    .collect();
+                locations.extend_from_slice(&elsewhere.references);
+
+                let mut parts = Vec::new();
+                if let Some(value) = self.declaration_value(root, content, &lens.range, &name, variables) {
+                    parts.push(value);
+                }
+                parts.push(match locations.len() {
+                    1 => "1 usage".to_string(),
+                    n => format!("{} usages", n),
+                });
+                match overrides.len() {
+                    0 => {}
+                    1 => parts.push("1 override".to_string()),
+                    n => parts.push(format!("{} overrides", n)),
+                }
+                (uri, parts.join(" · "), locations)
+            }
+        };
+
+        lens.command = Some(Command {
+            title,
+            command: SHOW_REFERENCES_COMMAND.to_string(),
+            arguments: Some(vec![
+                serde_json::json!(uri),
+                serde_json::json!(lens.range.start),
+                serde_json::json!(locations),
+            ]),
+        });
+        lens
+    }
+
+    /// Value of the variable declaration whose property name is at `range`
+    ///
+    /// The resolved value if the variable resolves to a single value, otherwise the value as written,
+    /// since a variable declared several times has a value per declaration.
+    fn declaration_value(
+        &self,
+        root: Node,
+        content: &str,
+        range: &Range,
+        name: &str,
+        variables: Option<&VariableResolver>,
+    ) -> Option<String> {
+        let offset = position_to_byte_offset(content, range.start)?;
+        let declaration = root
+            .descendant_for_byte_range(offset, offset)?
+            .parent()
+            .filter(|n| n.kind() == NODE_DECLARATION)?;
+
+        let resolved = match variables.and_then(|variables| variables.get_variable(name.trim_start_matches("--"))) {
+            Some(VariableStatus::Resolved(values)) => {
+                Some(values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "))
+            }
+            _ => None,
+        };
+        let value = match resolved {
+            Some(value) => value,
+            None => {
+                let mut cursor = declaration.walk();
+                let values: Vec<&str> = declaration
+                    .children(&mut cursor)
+                    .skip_while(|n| n.kind() != NODE_COLON)
+                    .skip(1)
+                    .filter(|n| !matches!(n.kind(), NODE_SEMICOLON | NODE_IMPORTANT | NODE_COMMENT))
+                    .filter_map(|n| n.utf8_text(content.as_bytes()).ok())
+                    .collect();
+                values.join(" ")
+            }
+        };
+
+        if value.is_empty() {
+            None
+        } else if value.chars().count() > MAX_VALUE_LENGTH {
+            Some(format!("{}…", value.chars().take(MAX_VALUE_LENGTH).collect::<String>()))
+        } else {
+            Some(value)
+        }
+    }
+
+    /// Find all declarations of a custom property in the document
+    fn find_variable_declarations(
+        &self,
+        node: Node,
+        content: &str,
+        uri: &Url,
+        name: &str,
+        locations: &mut Vec<Location>,
+    ) {
+        if node.kind() == NODE_DECLARATION {
+            if let Some(property) = node.child(0).filter(|n| n.kind() == NODE_PROPERTY_NAME) {
+                if property.utf8_text(content.as_bytes()) == Ok(name) {
+                    locations.push(Location {
+                        uri: uri.clone(),
+                        range: node_to_range(property, content),
+                    });
+                }
+            }
+            return;
+        }
+
+        for i in 0..node.child_count() {
+            if let Some(child) = node.child(i) {
+                self.find_variable_declarations(child, content, uri, name, locations);
+            }
+        }
+    }
+}
+
+impl Default for UssCodeLensProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn unresolved_lens(range: Range, data: CodeLensData) -> CodeLens {
+    CodeLens {
+        range,
+        command: None,
+        data: serde_json::to_value(data).ok(),
+    }
+}
+
+#[cfg(test)]
+#[path = "code_lens_tests.rs"]
+mod code_lens_tests;
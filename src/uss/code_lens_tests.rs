@@ -0,0 +1,106 @@
+use super::*;
+use crate::test_utils::get_unity_project_root;
+use crate::uss::definitions::UssDefinitions;
+use crate::uss::parser::UssParser;
+
+fn resolve(content: &str, lens_index: usize) -> CodeLens {
+    let mut parser = UssParser::new().unwrap();
+    let tree = parser.parse(content, None).unwrap();
+    let provider = UssCodeLensProvider::new();
+    let uri = Url::parse("file:///test.uss").unwrap();
+    let lenses = provider.provide_code_lenses(tree.root_node(), content, &uri);
+
+    let mut index = UxmlUsageIndex::new(get_unity_project_root());
+    index.refresh();
+    provider.resolve_code_lens(lenses[lens_index].clone(), tree.root_node(), content, &index, None, &VariableLocations::default())
+}
+
+#[test]
+fn test_provide_code_lenses_unresolved() {
+    let content = ":root { --main-color: red; color: var(--main-color); }\n.primary-button, Label { --main-color: blue; }";
+    let mut parser = UssParser::new().unwrap();
+    let tree = parser.parse(content, None).unwrap();
+    let uri = Url::parse("file:///test.uss").unwrap();
+
+    let lenses = UssCodeLensProvider::new().provide_code_lenses(tree.root_node(), content, &uri);
+    assert_eq!(lenses.len(), 4);
+    assert!(lenses.iter().all(|l| l.command.is_none()));
+
+    let data: CodeLensData = serde_json::from_value(lenses[2].data.clone().unwrap()).unwrap();
+    assert_eq!(
+        data,
+        CodeLensData::Selector {
+            uri,
+            selectors: vec![".primary-button".to_string(), "Label".to_string()],
+        }
+    );
+}
+
+#[test]
+fn test_resolve_selector_usages() {
+    let content = ".primary-button, .label { color: red; }";
+    let lens = resolve(content, 0);
+    let command = lens.command.unwrap();
+
+    assert_eq!(command.title, "2 usages in UXML");
+    assert_eq!(command.command, SHOW_REFERENCES_COMMAND);
+
+    let locations: Vec<Location> =
+        serde_json::from_value(command.arguments.unwrap()[2].clone()).unwrap();
+    assert_eq!(locations.len(), 2);
+    assert!(locations.iter().all(|l| l.uri.path().ends_with("MainWindow.uxml")));
+}
+
+#[test]
+fn test_resolve_selector_without_usages() {
+    let lens = resolve(".unused-class { color: red; }", 0);
+    assert_eq!(lens.command.unwrap().title, "0 usages in UXML");
+}
+
+#[test]
+fn test_resolve_variable_overrides() {
+    let content = ":root { --main-color: red; }\n.a { --main-color: blue; }\n.b { --main-color: green; --other: 1px; }";
+
+    let lens = resolve(content, 1);
+    assert_eq!(lens.command.unwrap().title, "red · 0 usages · 2 overrides");
+
+    let lens = resolve(content, 6);
+    assert_eq!(lens.command.unwrap().title, "1px · 0 usages");
+}
+
+#[test]
+fn test_resolve_variable_value_and_usages() {
+    let content = ":root { --size: 4px; --gap: var(--size); }\n.a { margin: var(--gap); padding: var(--gap); }";
+    let mut parser = UssParser::new().unwrap();
+    let tree = parser.parse(content, None).unwrap();
+    let provider = UssCodeLensProvider::new();
+    let uri = Url::parse("file:///test.uss").unwrap();
+    let lenses = provider.provide_code_lenses(tree.root_node(), content, &uri);
+
+    let mut variables = VariableResolver::new(UssDefinitions::shared());
+    variables.add_variables_from_tree(tree.root_node(), content);
+    let importer = Location {
+        uri: Url::parse("file:///importer.uss").unwrap(),
+        range: Range::default(),
+    };
+    let elsewhere = VariableLocations {
+        references: vec![importer.clone()],
+        declarations: vec![importer.clone()],
+    };
+    let index = UxmlUsageIndex::new(get_unity_project_root());
+    let lens = provider.resolve_code_lens(
+        lenses[2].clone(),
+        tree.root_node(),
+        content,
+        &index,
+        Some(&variables),
+        &elsewhere,
+    );
+
+    // the value follows the reference to --size, the importer declares it again
+    let command = lens.command.unwrap();
+    assert_eq!(command.title, "4px · 3 usages · 1 override");
+    let locations: Vec<Location> = serde_json::from_value(command.arguments.unwrap()[2].clone()).unwrap();
+    assert_eq!(locations.len(), 3);
+    assert_eq!(locations[2], importer);
+}
@@ -34,7 +34,11 @@ impl Color {
     pub fn from_hex(hex_value: &str) -> Option<Self> {
         // Remove # if present
         let hex_part = if hex_value.starts_with('#') { &hex_value[1..] } else { hex_value };
-        
+        // the digits are sliced by byte below
+        if !hex_part.is_ascii() {
+            return None;
+        }
+
         match hex_part.len() {
             3 => {
                 // 3-digit hex: #rgb -> #rrggbb
@@ -135,6 +139,9 @@ mod tests {
         assert!(Color::from_hex("#invalid").is_none());
         assert!(Color::from_hex("#ff").is_none());
         assert!(Color::from_hex("#ffff").is_none());
+        // 3 and 6 bytes, but not 3 and 6 digits
+        assert!(Color::from_hex("#é1").is_none());
+        assert!(Color::from_hex("#ffé12").is_none());
     }
 
     #[test]
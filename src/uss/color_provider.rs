@@ -4,40 +4,60 @@
 
 use tower_lsp::lsp_types::{ColorInformation, ColorPresentation, Position, Range, TextEdit, Color as LspColor};
 use tree_sitter::{Node, Tree};
+use crate::language::tree_utils::position_to_byte_offset;
 use crate::uss::definitions::UssDefinitions;
 use crate::uss::value::UssValue;
+use crate::uss::variable_resolver::{VariableResolver, VariableStatus};
 use crate::uss::constants::*;
 
 /// USS color information provider
 pub struct UssColorProvider {
-    definitions: UssDefinitions,
+    definitions: &'static UssDefinitions,
 }
 
 impl UssColorProvider {
     /// Create a new USS color provider
     pub fn new() -> Self {
         Self {
-            definitions: UssDefinitions::new(),
+            definitions: UssDefinitions::shared(),
         }
     }
     
     /// Extract color information from a USS document
     pub fn provide_document_colors(&self, tree: &Tree, content: &str) -> Vec<ColorInformation> {
+        self.provide_document_colors_with_variables(tree, content, None)
+    }
+
+    /// Extract color information from a USS document, including `var()` references whose variable resolves to a color
+    ///
+    /// The color of a variable can't be changed where it's referenced, see [`Self::is_variable_color`].
+    pub fn provide_document_colors_with_variables(
+        &self,
+        tree: &Tree,
+        content: &str,
+        variable_resolver: Option<&VariableResolver>,
+    ) -> Vec<ColorInformation> {
         let mut colors = Vec::new();
         let root = tree.root_node();
         
-        self.walk_node_for_colors(&root, content, &mut colors);
+        self.walk_node_for_colors(&root, content, variable_resolver, &mut colors);
         
         colors
     }
     
     /// Recursively walk the syntax tree to find color values
-    fn walk_node_for_colors(&self, node: &Node, content: &str, colors: &mut Vec<ColorInformation>) {
+    fn walk_node_for_colors(
+        &self,
+        node: &Node,
+        content: &str,
+        variable_resolver: Option<&VariableResolver>,
+        colors: &mut Vec<ColorInformation>,
+    ) {
         let node_type = node.kind();
         
         match node_type {
             NODE_COLOR_VALUE | NODE_PLAIN_VALUE | NODE_CALL_EXPRESSION => {
-                if let Some(color_info) = self.extract_color_from_uss_value(node, content) {
+                if let Some(color_info) = self.extract_color_from_uss_value(node, content, variable_resolver) {
                     colors.push(color_info);
                 }
             }
@@ -45,7 +65,7 @@ impl UssColorProvider {
                 // Recursively process child nodes
                 for i in 0..node.child_count() {
                     if let Some(child) = node.child(i) {
-                        self.walk_node_for_colors(&child, content, colors);
+                        self.walk_node_for_colors(&child, content, variable_resolver, colors);
                     }
                 }
             }
@@ -53,45 +73,58 @@ impl UssColorProvider {
     }
     
     /// Extract color information using UssValue parsing
-    fn extract_color_from_uss_value(&self, node: &Node, content: &str) -> Option<ColorInformation> {
+    fn extract_color_from_uss_value(
+        &self,
+        node: &Node,
+        content: &str,
+        variable_resolver: Option<&VariableResolver>,
+    ) -> Option<ColorInformation> {
         // Try to parse the node as a UssValue
         let uss_value = UssValue::from_node(*node, content, &self.definitions, None).ok()?;
         
-        // Convert UssValue to ColorInformation if it's a color
-        match uss_value {
-            UssValue::Color(uss_color) => {
-                let range = self.node_to_range(node, content)?;
-                Some(ColorInformation {
-                    range,
-                    color: LspColor {
-                        red: uss_color.r as f32 / 255.0,
-                        green: uss_color.g as f32 / 255.0,
-                        blue: uss_color.b as f32 / 255.0,
-                        alpha: uss_color.a,
-                    },
-                })
-            }
+        let color = match uss_value {
+            // a variable whose value is a single color
+            UssValue::VariableReference(name) => match variable_resolver?.get_variable(&name)? {
+                VariableStatus::Resolved(values) if values.len() == 1 => self.to_lsp_color(&values[0])?,
+                _ => return None,
+            },
+            value => self.to_lsp_color(&value)?,
+        };
+        Some(ColorInformation {
+            range: self.node_to_range(node, content)?,
+            color,
+        })
+    }
+
+    /// Color of a value that is a color or a color keyword
+    fn to_lsp_color(&self, value: &UssValue) -> Option<LspColor> {
+        match value {
+            UssValue::Color(uss_color) => Some(LspColor {
+                red: uss_color.r as f32 / 255.0,
+                green: uss_color.g as f32 / 255.0,
+                blue: uss_color.b as f32 / 255.0,
+                alpha: uss_color.a,
+            }),
             UssValue::Identifier(keyword) => {
                 // Check if this identifier is a color keyword
-                if let Some((r, g, b)) = self.definitions.get_color_rgb(&keyword) {
-                    let range = self.node_to_range(node, content)?;
-                    
-                    Some(ColorInformation {
-                        range,
-                        color: LspColor {
-                            red: r as f32 / 255.0,
-                            green: g as f32 / 255.0,
-                            blue: b as f32 / 255.0,
-                            alpha: 1.0,
-                        },
-                    })
-                } else {
-                    None
-                }
+                let (r, g, b) = self.definitions.get_color_rgb(keyword)?;
+                Some(LspColor {
+                    red: r as f32 / 255.0,
+                    green: g as f32 / 255.0,
+                    blue: b as f32 / 255.0,
+                    alpha: 1.0,
+                })
             }
             _ => None,
         }
     }
+
+    /// Whether the color at a range is the color of a variable, from [`Self::provide_document_colors_with_variables`]
+    ///
+    /// The color picker doesn't change those, replacing the reference with a literal would disconnect it from the variable.
+    pub fn is_variable_color(&self, content: &str, range: Range) -> bool {
+        position_to_byte_offset(content, range.start).is_some_and(|start| content[start..].starts_with("var("))
+    }
     
     /// Convert a tree-sitter node to an LSP Range
     fn node_to_range(&self, node: &Node, _content: &str) -> Option<Range> {
@@ -217,4 +250,25 @@ mod tests {
         assert_eq!(color_info.color.blue, 0.0);
         assert_eq!(color_info.color.alpha, 1.0);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_variable_colors() {
+        let mut parser = UssParser::new().expect("Failed to create parser");
+        let content = ":root { --accent: #00ff00; --main: var(--accent); --size: 4px; }\nButton { color: var(--main); margin: var(--size); border-color: var(--missing); }";
+        let tree = parser.parse(content, None).expect("Failed to parse");
+        let mut resolver = VariableResolver::new(UssDefinitions::shared());
+        resolver.add_variables_from_tree(tree.root_node(), content);
+
+        let color_provider = UssColorProvider::new();
+        assert_eq!(color_provider.provide_document_colors(&tree, content).len(), 1);
+
+        // the value of --accent, and the references of --accent and --main, which resolves to it
+        let colors = color_provider.provide_document_colors_with_variables(&tree, content, Some(&resolver));
+        assert_eq!(colors.len(), 3);
+        let reference = &colors[2];
+        assert_eq!(reference.range.start, Position::new(1, 16));
+        assert_eq!(reference.color.green, 1.0);
+        assert!(color_provider.is_variable_color(content, reference.range));
+        assert!(!color_provider.is_variable_color(content, colors[0].range));
+    }
+}
@@ -9,16 +9,34 @@ use tower_lsp::lsp_types::*;
 use tree_sitter::{Node, Tree};
 use url::Url;
 
-use crate::language::tree_utils::{find_node_at_position, find_node_by_type, find_node_of_type_at_position, get_node_depth, node_to_range};
+use crate::language::asset_filter::AssetFilter;
+use crate::language::completion_ranking;
+use crate::language::font_assets::FontAssetIndex;
+use crate::language::tree_utils::{find_node_at_position, find_node_by_type, find_node_of_type_at_position, get_node_depth, node_to_range, position_to_byte_offset};
 use crate::language::url_completion::UrlCompletionProvider;
+use crate::unity_project_manager::{UnityVersion, DEFAULT_DOCS_VERSION};
 use crate::uss::constants::*;
 use crate::uss::definitions::UssDefinitions;
+use crate::uss::snippet_data::{SnippetInfo, BLOCK_SNIPPETS, CURSOR_SNIPPETS, RULE_SNIPPETS};
+use crate::uss::uss_utils::{escape_identifier, unescape_identifier};
+use crate::uss::value::UssValue;
+use crate::uss::variable_resolver::VariableResolver;
+use crate::uxml::schema_fallback::fallback_elements;
 use crate::uxml_schema_manager::VisualElementsData;
 
+/// Maximum number of items returned for ranked candidate sets, see [`completion_ranking`]
+pub const MAX_RANKED_ITEMS: usize = 100;
+
 /// USS completion provider
 pub struct UssCompletionProvider {
-    pub(crate) definitions: UssDefinitions,
+    pub(crate) definitions: &'static UssDefinitions,
     url_completion_provider: Option<UrlCompletionProvider>,
+    /// Whether the client supports snippets, rule snippets are only offered if it does
+    snippet_support: bool,
+    /// Whether the client supports commit characters, values are then committed with `;`
+    commit_characters_support: bool,
+    /// Whether layout snippets are offered in empty blocks, see [`BLOCK_SNIPPETS`]
+    block_snippets: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -48,30 +66,96 @@ pub(super) enum CompletionType {
         url_string: String,
         /// The cursor position within the URL string
         cursor_position: usize,
+        /// Types of assets accepted by the property the URL is in, if it only accepts some
+        asset_filter: Option<&'static AssetFilter>,
+        /// Font assets are offered by name, until a path is typed in a `-unity-font-definition` URL
+        offers_font_assets: bool,
     },
     /// Completing import statement structure after @import
     ImportStatement,
+    /// Completing variable name inside var() function
+    VariableName,
+    /// At the start of a line at the top level, where a new rule can start
+    TopLevel,
+    /// On a blank line of a block that has no declarations yet, the node is the block
+    EmptyBlock,
     /// Unknown context
     Unknown,
 }
 
+/// What can come after a completed value, decides the semicolon inserted after it
+#[derive(Debug, Clone, Copy, Default)]
+struct ValueEnd {
+    /// The declaration is complete with the value
+    ends_declaration: bool,
+    /// More values separated by spaces can follow
+    more_values: bool,
+    /// Values are separated by commas
+    comma_separated: bool,
+    /// A semicolon already follows the value
+    followed_by_semicolon: bool,
+}
+
 impl UssCompletionProvider {
     /// Create a new USS completion provider
     pub fn new() -> Self {
         Self {
-            definitions: UssDefinitions::new(),
+            definitions: UssDefinitions::shared(),
             url_completion_provider: None,
+            snippet_support: false,
+            commit_characters_support: false,
+            block_snippets: true,
         }
     }
 
     /// Create a new USS completion provider with URL completion support
     pub fn new_with_project_root(project_root: &std::path::Path) -> Self {
         Self {
-            definitions: UssDefinitions::new(),
+            definitions: UssDefinitions::shared(),
             url_completion_provider: Some(UrlCompletionProvider::new(project_root)),
+            snippet_support: false,
+            commit_characters_support: false,
+            block_snippets: true,
         }
     }
 
+    /// Set whether the client supports snippets, from its completion capabilities
+    pub fn set_snippet_support(&mut self, snippet_support: bool) {
+        self.snippet_support = snippet_support;
+    }
+
+    /// Set whether the client supports commit characters, from its completion capabilities
+    pub fn set_commit_characters_support(&mut self, commit_characters_support: bool) {
+        self.commit_characters_support = commit_characters_support;
+    }
+
+    /// Set whether layout snippets are offered in empty blocks (they still need snippet support)
+    pub fn set_block_snippets(&mut self, enabled: bool) {
+        self.block_snippets = enabled;
+    }
+
+    /// Look for font assets again on the next refresh, e.g. after assets were created or deleted
+    pub fn invalidate_font_assets(&self) {
+        if let Some(provider) = &self.url_completion_provider {
+            provider.invalidate_font_assets();
+        }
+    }
+
+    /// Font assets offered in `-unity-font-definition` URLs, `None` without a project
+    ///
+    /// Completion only uses the font assets found by the last [`FontAssetIndex::refresh`].
+    pub fn font_asset_index(&self) -> Option<Arc<FontAssetIndex>> {
+        self.url_completion_provider.as_ref().map(|provider| provider.font_asset_index())
+    }
+
+    /// Whether completion at the position offers font assets by name
+    pub fn offers_font_assets(&self, tree: &Tree, content: &str, position: Position) -> bool {
+        matches!(
+            self.get_completion_context(tree, content, position).t,
+            CompletionType::UrlString { offers_font_assets: true, .. }
+        )
+    }
+
     /// Provide completion items for the given position
     pub fn complete(
         &self,
@@ -82,17 +166,53 @@ impl UssCompletionProvider {
         uxml_data: Option<Arc<Mutex<VisualElementsData>>>,
         unity_manager: Option<&crate::unity_project_manager::UnityProjectManager>,
     ) -> Vec<CompletionItem> {
+        self.complete_with_variables(tree, content, position, source_url, uxml_data, unity_manager, None)
+    }
+
+    /// Provide completion items for the given position, with variables of the document
+    ///
+    /// Variable names inside `var()` are only completed when a variable resolver is provided.
+    pub fn complete_with_variables(
+        &self,
+        tree: &Tree,
+        content: &str,
+        position: Position,
+        source_url: Option<&Url>,
+        uxml_data: Option<Arc<Mutex<VisualElementsData>>>,
+        unity_manager: Option<&crate::unity_project_manager::UnityProjectManager>,
+        variable_resolver: Option<&VariableResolver>,
+    ) -> Vec<CompletionItem> {
+        self.complete_list_with_variables(tree, content, position, source_url, uxml_data, unity_manager, variable_resolver)
+            .items
+    }
+
+    /// Provide a completion list for the given position, with variables of the document
+    ///
+    /// Large candidate sets (e.g. tag selectors) are ranked and cut to the best [`MAX_RANKED_ITEMS`],
+    /// the list is then incomplete and the client asks again as the user types more.
+    pub fn complete_list_with_variables(
+        &self,
+        tree: &Tree,
+        content: &str,
+        position: Position,
+        source_url: Option<&Url>,
+        uxml_data: Option<Arc<Mutex<VisualElementsData>>>,
+        unity_manager: Option<&crate::unity_project_manager::UnityProjectManager>,
+        variable_resolver: Option<&VariableResolver>,
+    ) -> CompletionList {
         let context = self.get_completion_context(tree, content, position);
 
-        let mut unity_version = "6000.0".to_string();
+        let mut unity_version = DEFAULT_DOCS_VERSION.to_string();
+        let mut project_version = None;
         if let Some(u) = unity_manager {
             if let Some(v) = u.get_unity_version_for_docs() {
                 unity_version = v;
             }
+            project_version = u.get_parsed_unity_version();
         }
 
         if let Some(current_node) = context.current_node {
-            match context.t {
+            let items = match context.t {
                 CompletionType::PropertyValue { property_name } => {
                     self.complete_property_value(&property_name, current_node, content, unity_version.as_str())
                 }
@@ -100,7 +220,11 @@ impl UssCompletionProvider {
                     context.current_node,
                     content,
                     unity_version.as_str(),
+                    project_version,
                 ),
+                CompletionType::EmptyBlock => {
+                    completion_ranking::rank_items("", self.complete_block_snippets(current_node, content, None, project_version))
+                }
                 CompletionType::PseudoClass => self.complete_pseudo_classes_with_filter(
                     current_node,
                     content,
@@ -113,19 +237,39 @@ impl UssCompletionProvider {
                     self.complete_id_selectors(tree, content, current_node)
                 }
                 CompletionType::TagSelector => {
-                    self.complete_tag_selectors(current_node, content, uxml_data)
+                    return self.complete_tag_selectors(current_node, content, uxml_data);
                 }
                 CompletionType::UrlString {
                     url_string,
                     cursor_position,
-                } => self.complete_url_function(&url_string, cursor_position, source_url),
+                    asset_filter,
+                    offers_font_assets,
+                } => {
+                    let mut items = self.complete_url_function(&url_string, cursor_position, source_url, asset_filter);
+                    if offers_font_assets {
+                        if let Some(provider) = &self.url_completion_provider {
+                            items.extend(provider.complete_font_assets(node_to_range(current_node, content)));
+                        }
+                    }
+                    items
+                }
                 CompletionType::ImportStatement => {
                     self.complete_import_statement(current_node, content)
                 }
+                CompletionType::VariableName => match variable_resolver {
+                    Some(resolver) => self.complete_variable_names(current_node, content, resolver),
+                    None => Vec::new(),
+                },
                 _ => Vec::new(),
+            };
+            CompletionList { is_incomplete: false, items }
+        } else if context.t == CompletionType::TopLevel {
+            CompletionList {
+                is_incomplete: false,
+                items: completion_ranking::rank_items("", self.complete_rule_snippets()),
             }
         } else {
-            Vec::new()
+            CompletionList::default()
         }
     }
 
@@ -186,6 +330,20 @@ impl UssCompletionProvider {
             }
         }
 
+        if Self::is_at_top_level_line_start(tree, content, position) {
+            return CompletionContext {
+                t: CompletionType::TopLevel,
+                current_node: None,
+            };
+        }
+
+        if let Some(block) = Self::empty_block_at_line_start(tree, content, position) {
+            return CompletionContext {
+                t: CompletionType::EmptyBlock,
+                current_node: Some(block),
+            };
+        }
+
         return CompletionContext {
             t: CompletionType::Unknown,
             current_node: None,
@@ -435,6 +593,13 @@ impl UssCompletionProvider {
                     return url_context;
                 }
 
+                if Self::is_in_var_function(current_node, content) {
+                    return CompletionContext {
+                        t: CompletionType::VariableName,
+                        current_node: Some(current_node),
+                    };
+                }
+
                 let property_name = property_name_node
                     .utf8_text(content.as_bytes())
                     .unwrap_or("")
@@ -453,6 +618,76 @@ impl UssCompletionProvider {
         };
     }
 
+    /// Check if the node is the opening parenthesis or the argument of a var() function
+    fn is_in_var_function(current_node: Node, content: &str) -> bool {
+        if current_node.kind() != NODE_PLAIN_VALUE && current_node.kind() != NODE_OPEN_PAREN {
+            return false;
+        }
+
+        let Some(arguments) = current_node.parent().filter(|p| p.kind() == NODE_ARGUMENTS) else {
+            return false;
+        };
+        let Some(call) = arguments.parent().filter(|p| p.kind() == NODE_CALL_EXPRESSION) else {
+            return false;
+        };
+
+        call.child(0)
+            .filter(|n| n.kind() == NODE_FUNCTION_NAME)
+            .and_then(|n| n.utf8_text(content.as_bytes()).ok())
+            == Some("var")
+    }
+
+    /// Complete variable names inside var() function, with their documentation
+    ///
+    /// ### Parameters
+    /// `current_node`: The opening parenthesis or the partial variable name before the cursor
+    fn complete_variable_names(
+        &self,
+        current_node: Node,
+        content: &str,
+        variable_resolver: &VariableResolver,
+    ) -> Vec<CompletionItem> {
+        let partial = if current_node.kind() == NODE_PLAIN_VALUE {
+            current_node.utf8_text(content.as_bytes()).unwrap_or("")
+        } else {
+            ""
+        };
+
+        let items = variable_resolver
+            .get_variables()
+            .keys()
+            .map(|name| format!("--{}", name))
+            .map(|label| {
+                let documentation = variable_resolver
+                    .create_documentation(&label[2..])
+                    .map(|doc| {
+                        Documentation::MarkupContent(MarkupContent {
+                            kind: MarkupKind::Markdown,
+                            value: doc,
+                        })
+                    });
+
+                // replace the partial name so the `--` prefix is not duplicated
+                let text_edit = (current_node.kind() == NODE_PLAIN_VALUE).then(|| {
+                    CompletionTextEdit::Edit(TextEdit {
+                        range: node_to_range(current_node, content),
+                        new_text: label.clone(),
+                    })
+                });
+
+                CompletionItem {
+                    label,
+                    kind: Some(CompletionItemKind::VARIABLE),
+                    documentation,
+                    text_edit,
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        completion_ranking::rank_items(partial, items)
+    }
+
     /// Complete property values for a given property
     ///
     /// ### Parameters
@@ -464,6 +699,10 @@ impl UssCompletionProvider {
         content: &str,
         unity_version: &str,
     ) -> Vec<CompletionItem> {
+        if let Some(number) = Self::number_being_typed(current_node) {
+            return self.complete_units(property_name, number, current_node, content);
+        }
+
         // value just started, with colon or comma
         let mut is_colon_or_comma = false;
         if current_node.kind() == NODE_COLON || current_node.kind() == NODE_COMMA {
@@ -488,7 +727,7 @@ impl UssCompletionProvider {
         }
 
         if !is_first_value_node {
-            return Vec::new();
+            return self.complete_next_value(property_name, current_node, content, unity_version);
         }
 
         let property_info_option = self.definitions.get_property_info(property_name);
@@ -507,91 +746,263 @@ impl UssCompletionProvider {
             return Vec::new();
         }
 
-        // try to find what still matches and return that
+        // rank what still matches and return that
         let mut items = Vec::new();
         let partial_value = if is_colon_or_comma {
             ""
         } else {
             current_node.utf8_text(content.as_bytes()).unwrap_or("")
         };
-        let partial_lower = partial_value.to_lowercase();
 
+        // add a semicolon for a value that doesn't have multiple values(ie. comma separated values)
+        let spec = &property_info.value_spec;
+        let end = ValueEnd {
+            ends_declaration: !spec.allows_multiple_values,
+            more_values: !spec.allows_multiple_values && spec.accepts_more_than(1),
+            comma_separated: spec.allows_multiple_values,
+            followed_by_semicolon: Self::is_followed_by_semicolon(current_node, content),
+        };
         for value in valid_values {
-            if partial_lower.is_empty() || value.starts_with(&partial_lower) {
-                // add one space if user just typed colon or comma
-                let mut text = if is_colon_or_comma {
-                    format!(" {}", value)
-                } else {
-                    format!("{}", value)
-                };
+            // add one space if user just typed colon or comma
+            let text = if is_colon_or_comma {
+                format!(" {}", value)
+            } else {
+                format!("{}", value)
+            };
+
+            items.push(self.value_item(value, property_name, text, end, unity_version));
+        }
 
-                // add a semicolon for a value that doesn't have multiple values(ie. comma separated values)
-                if !property_info.value_spec.allows_multiple_values {
-                    text.push(';');
+        if property_name == "cursor" && self.snippet_support {
+            for snippet in CURSOR_SNIPPETS {
+                let mut item = Self::snippet_item(snippet);
+                if is_colon_or_comma {
+                    item.insert_text = Some(format!(" {}", snippet.body));
                 }
+                items.push(item);
+            }
+        }
 
-                // Check if this value is a keyword and get its documentation
-                // Special case: for transition-property, treat values as property names first
-                let mut documentation = if property_name == "transition-property" {
-                    self.definitions.get_property_info(value)
-                        .map(|property_info| {
-                            let doc_content = property_info.create_documentation(value, unity_version);
-                            Documentation::MarkupContent(MarkupContent {
-                                kind: MarkupKind::Markdown,
-                                value: doc_content,
-                            })
-                        })
-                }else {
-                    None
-                };
+        completion_ranking::rank_items(partial_value, items)
+    }
 
-                if documentation.is_none() {
-                    documentation = self.definitions.get_keyword_info(value)
-                        .map(|keyword_info| {
-                            let doc_content = keyword_info.create_documentation(Some(property_name));
-                            Documentation::MarkupContent(MarkupContent {
-                                kind: MarkupKind::Markdown,
-                                value: doc_content,
-                            })
-                        })
-                }
+    /// Complete a keyword typed after other values of a declaration, e.g. the second value of `margin` or the
+    /// timing function after the duration of `transition`
+    ///
+    /// Only keywords accepted after the values before it, by the formats those values match, are offered. A
+    /// semicolon is added if the declaration can't have more values after the keyword.
+    fn complete_next_value(&self, property_name: &str, current_node: Node, content: &str, unity_version: &str) -> Vec<CompletionItem> {
+        if current_node.kind() != NODE_PLAIN_VALUE || current_node.parent().map(|p| p.kind()) != Some(NODE_DECLARATION) {
+            return Vec::new();
+        }
+        let Some(property_info) = self.definitions.get_property_info(property_name) else {
+            return Vec::new();
+        };
+        let spec = &property_info.value_spec;
+
+        let previous = Self::previous_values(current_node);
+        let values: Option<Vec<UssValue>> = previous
+            .iter()
+            .map(|node| UssValue::from_node(*node, content, self.definitions, None).ok())
+            .collect();
+        let types = match values {
+            Some(values) => spec.types_after(&values, self.definitions),
+            // a value that can't be parsed matches no format, fall back to everything accepted at the position
+            None => spec.types_at(previous.len()),
+        };
 
-                // Check if this value is a color keyword to provide color preview
-                let kind = if self.definitions.is_valid_color_keyword(value) {
-                    CompletionItemKind::COLOR
-                } else {
-                    CompletionItemKind::VALUE
-                };
+        let is_last = current_node.next_sibling().is_none();
+        let end = ValueEnd {
+            ends_declaration: is_last && !spec.allows_multiple_values && !spec.accepts_more_than(previous.len() + 1),
+            more_values: is_last && !spec.allows_multiple_values && spec.accepts_more_than(previous.len() + 1),
+            comma_separated: spec.allows_multiple_values,
+            followed_by_semicolon: Self::is_followed_by_semicolon(current_node, content),
+        };
+        let partial = current_node.utf8_text(content.as_bytes()).unwrap_or("");
+        let items = self
+            .definitions
+            .get_simple_completions_for_types(&types)
+            .into_iter()
+            .map(|value| self.value_item(value, property_name, value.to_string(), end, unity_version))
+            .collect();
+        completion_ranking::rank_items(partial, items)
+    }
 
-                let mut item = CompletionItem {
-                    label: value.to_string(),
-                    kind: Some(kind),
-                    documentation,
-                    insert_text: Some(text),
-                    insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
-                    ..Default::default()
-                };
+    /// Whether the next character after a node, other than spaces, is a semicolon
+    fn is_followed_by_semicolon(node: Node, content: &str) -> bool {
+        content
+            .get(node.end_byte()..)
+            .is_some_and(|rest| rest.trim_start_matches([' ', '\t']).starts_with(';'))
+    }
 
-                // Add color information for VS Code color preview
-                // VS Code looks at the detail property for hex values to show color preview
-                if kind == CompletionItemKind::COLOR {
-                    if let Some(hex_value) = self.definitions.get_color_hex(value) {
-                        item.detail = Some(hex_value.to_string());
-                    }
+    /// Values of a declaration before a value, since the colon or the last comma, comments are skipped
+    fn previous_values(value: Node) -> Vec<Node> {
+        let mut values = Vec::new();
+        let mut sibling = value.prev_sibling();
+        while let Some(node) = sibling {
+            match node.kind() {
+                NODE_COLON | NODE_COMMA => break,
+                NODE_COMMENT => {}
+                _ => values.push(node),
+            }
+            sibling = node.prev_sibling();
+        }
+        values.reverse();
+        values
+    }
+
+    /// Completion item for a keyword value of a property, with its documentation
+    ///
+    /// `text` is the value as inserted, a semicolon is added after it depending on `end`: if the declaration
+    /// ends, or if more values can follow and the client supports snippets, then with the cursor before the
+    /// semicolon. Nothing is added if a semicolon already follows. Values without a semicolon are committed
+    /// with `;`, or `,` for comma separated values, if the client supports commit characters.
+    fn value_item(&self, value: &str, property_name: &str, mut text: String, end: ValueEnd, unity_version: &str) -> CompletionItem {
+        let mut insert_text_format = InsertTextFormat::PLAIN_TEXT;
+        if !end.followed_by_semicolon {
+            if end.more_values && self.snippet_support {
+                text.push_str("$0;");
+                insert_text_format = InsertTextFormat::SNIPPET;
+            } else if end.ends_declaration {
+                text.push(';');
+            }
+        }
+
+        let commit_characters = (self.commit_characters_support && !text.ends_with(';')).then(|| {
+            let mut characters = Vec::new();
+            if !end.followed_by_semicolon {
+                characters.push(";".to_string());
+            }
+            if end.comma_separated {
+                characters.push(",".to_string());
+            }
+            characters
+        });
+
+        // Check if this value is a keyword and get its documentation
+        // Special case: for transition-property, treat values as property names first
+        let mut documentation = if property_name == "transition-property" {
+            self.definitions.get_property_info(value)
+                .map(|property_info| {
+                    let doc_content = property_info.create_documentation(value, unity_version);
+                    Documentation::MarkupContent(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value: doc_content,
+                    })
+                })
+        }else {
+            None
+        };
+
+        if documentation.is_none() {
+            documentation = self.definitions.keyword_documentation(value, property_name)
+                .map(|doc_content| {
+                    Documentation::MarkupContent(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value: doc_content,
+                    })
+                })
+        }
+
+        // Check if this value is a color keyword to provide color preview
+        let kind = if self.definitions.is_valid_color_keyword(value) {
+            CompletionItemKind::COLOR
+        } else {
+            CompletionItemKind::VALUE
+        };
+
+        let mut item = CompletionItem {
+            label: value.to_string(),
+            kind: Some(kind),
+            documentation,
+            insert_text: Some(text),
+            insert_text_format: Some(insert_text_format),
+            commit_characters: commit_characters.filter(|characters| !characters.is_empty()),
+            ..Default::default()
+        };
+
+        // Add color information for VS Code color preview
+        // VS Code looks at the detail property for hex values to show color preview
+        if kind == CompletionItemKind::COLOR {
+            if let Some(hex_value) = self.definitions.get_color_hex(value) {
+                item.detail = Some(hex_value.to_string());
+            }
+        }
+        item
+    }
+
+    /// The number in a declaration whose unit is being typed, `current_node` is the number or its partial unit
+    fn number_being_typed(current_node: Node) -> Option<Node> {
+        let number = match current_node.kind() {
+            NODE_UNIT => current_node.parent()?,
+            NODE_INTEGER_VALUE | NODE_FLOAT_VALUE if current_node.child_count() == 0 => current_node,
+            _ => return None,
+        };
+        let is_number = matches!(number.kind(), NODE_INTEGER_VALUE | NODE_FLOAT_VALUE);
+        (is_number && number.parent()?.kind() == NODE_DECLARATION).then_some(number)
+    }
+
+    /// Complete the unit of a number, with the units of the types the property accepts at the position of the number
+    ///
+    /// A semicolon is added if the declaration can't have more values after the number.
+    fn complete_units(&self, property_name: &str, number: Node, current_node: Node, content: &str) -> Vec<CompletionItem> {
+        let Some(property_info) = self.definitions.get_property_info(property_name) else {
+            return Vec::new();
+        };
+        let spec = &property_info.value_spec;
+
+        // values before the number, since the colon or the last comma
+        let index = Self::previous_values(number).len();
+
+        let mut units: Vec<&str> = Vec::new();
+        for value_type in spec.types_at(index) {
+            for unit in value_type.units() {
+                if !units.contains(unit) {
+                    units.push(unit);
                 }
-                items.push(item);
             }
         }
 
-        items
+        let (partial, range) = if current_node.kind() == NODE_UNIT {
+            (current_node.utf8_text(content.as_bytes()).unwrap_or(""), node_to_range(current_node, content))
+        } else {
+            let end = node_to_range(number, content).end;
+            ("", Range::new(end, end))
+        };
+        let ends_declaration = number.next_sibling().is_none()
+            && !spec.allows_multiple_values
+            && !spec.accepts_more_than(index + 1);
+
+        let items = units
+            .into_iter()
+            .map(|unit| CompletionItem {
+                label: unit.to_string(),
+                kind: Some(CompletionItemKind::UNIT),
+                documentation: self.definitions.get_unit_info(unit).map(|info| {
+                    Documentation::MarkupContent(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value: info.create_documentation(),
+                    })
+                }),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: if ends_declaration { format!("{};", unit) } else { unit.to_string() },
+                })),
+                ..Default::default()
+            })
+            .collect();
+        completion_ranking::rank_items(partial, items)
     }
 
     /// Complete property names
+    ///
+    /// Properties not supported in the project's Unity version (if known) are not suggested
     fn complete_property_names(
         &self,
         current_node: Option<tree_sitter::Node>,
         content: &str,
         unity_version: &str,
+        project_version: Option<UnityVersion>,
     ) -> Vec<CompletionItem> {
         let partial_text = if let Some(node) = current_node {
             node.utf8_text(content.as_bytes())
@@ -606,10 +1017,12 @@ impl UssCompletionProvider {
             return Vec::new();
         }
 
-        self.definitions
+        let mut items: Vec<CompletionItem> = self
+            .definitions
             .get_all_properties()
-            .keys()
-            .filter(|name| name.starts_with(&partial_text))
+            .iter()
+            .filter(|(_, info)| project_version.is_none_or(|version| info.is_supported_in(version)))
+            .map(|(name, _)| name)
             .map(|name| {
                 let property_info = self.definitions.get_property_info(name);
                 let documentation = property_info
@@ -619,13 +1032,17 @@ impl UssCompletionProvider {
                 let mut completion_item = CompletionItem {
                     label: name.to_string(),
                     kind: Some(CompletionItemKind::PROPERTY),
+                    detail: property_info.map(|info| info.value_syntax().into_owned()),
                     documentation: documentation.map(|doc| {
                         Documentation::MarkupContent(MarkupContent {
                             kind: MarkupKind::Markdown,
                             value: doc,
                         })
                     }),
-
+                    tags: self
+                        .definitions
+                        .is_property_deprecated(name, project_version)
+                        .then(|| vec![CompletionItemTag::DEPRECATED]),
                     ..Default::default()
                 };
 
@@ -646,7 +1063,20 @@ impl UssCompletionProvider {
 
                 completion_item
             })
-            .collect()
+            .collect();
+
+        if let Some(node) = current_node {
+            if let Some(block) = Self::enclosing_empty_block(node) {
+                items.extend(self.complete_block_snippets(
+                    block,
+                    content,
+                    Some(node_to_range(node, content)),
+                    project_version,
+                ));
+            }
+        }
+
+        completion_ranking::rank_items(&partial_text, items)
     }
 
     /// Complete pseudo-classes
@@ -693,10 +1123,8 @@ impl UssCompletionProvider {
         let mut items = Vec::new();
 
         for &pseudo_class in &self.definitions.valid_pseudo_classes {
-            // Filter based on partial text
-            if partial_text.is_empty()
-                || (pseudo_class.starts_with(&partial_text) && pseudo_class != partial_text)
-            {
+            // Don't suggest what is already typed
+            if pseudo_class != partial_text {
                 if let Some(info) = self.definitions.get_pseudo_class_info(pseudo_class) {
                     // Create documentation with description and link
                     let documentation_value = info.create_documentation(&unity_version);
@@ -716,7 +1144,7 @@ impl UssCompletionProvider {
             }
         }
 
-        items
+        completion_ranking::rank_items(&partial_text, items)
     }
 
     /// Analyze if we're in a selector completion context
@@ -825,7 +1253,80 @@ impl UssCompletionProvider {
             }
         }
 
-        None
+        Self::analyze_combinator_context(tree, content, position)
+    }
+
+    /// Analyze a selector typed after a combinator (`.a > B`, `.a .b`) or after a complete selector (`.a:hover .`)
+    ///
+    /// The parser often has no selector node for what is typed there yet, so this looks at the last token
+    /// before the cursor instead.
+    fn analyze_combinator_context<'a>(
+        tree: &'a Tree,
+        content: &str,
+        position: Position,
+    ) -> Option<CompletionContext<'a>> {
+        let cursor = position_to_byte_offset(content, position)?;
+        let token = Self::last_token_before(tree.root_node(), cursor)?;
+
+        // the rule must not have a block yet, otherwise this is a declaration
+        let mut top_level = token;
+        while let Some(parent) = top_level.parent().filter(|p| p.parent().is_some()) {
+            top_level = parent;
+        }
+        if content.get(top_level.start_byte()..cursor)?.contains(['{', '}', ';']) {
+            return None;
+        }
+
+        let gap = content.get(token.end_byte().min(cursor)..cursor)?;
+        let (t, current_node) = if gap.is_empty() {
+            match token.kind() {
+                "." => (CompletionType::ClassSelector, token),
+                "#" => (CompletionType::IdSelector, token),
+                NODE_COLON if token.prev_sibling().is_some_and(|s| Self::is_selector_kind(s.kind())) => {
+                    (CompletionType::PseudoClass, token)
+                }
+                NODE_TAG_NAME if !Self::is_incomplete_property_name_parsed_as_tag_name(token) => {
+                    (CompletionType::TagSelector, token)
+                }
+                _ => return None,
+            }
+        } else if gap.trim().is_empty() && token.kind() == NODE_CHILD_COMBINATOR {
+            // nothing typed after `> ` yet, all tags are offered
+            (CompletionType::TagSelector, token)
+        } else {
+            return None;
+        };
+
+        Some(CompletionContext {
+            t,
+            current_node: Some(current_node),
+        })
+    }
+
+    /// The deepest node that starts before a byte offset, i.e. the token the cursor is in or after
+    fn last_token_before(root: Node, cursor: usize) -> Option<Node> {
+        let mut node = root;
+        while let Some(child) = (0..node.child_count())
+            .rev()
+            .filter_map(|i| node.child(i))
+            .find(|c| c.start_byte() < cursor && !c.is_missing())
+        {
+            node = child;
+        }
+        (node != root).then_some(node)
+    }
+
+    /// Check if a node kind is a complete selector that a pseudo-class can follow
+    fn is_selector_kind(kind: &str) -> bool {
+        matches!(
+            kind,
+            NODE_CLASS_SELECTOR
+                | NODE_ID_SELECTOR
+                | NODE_TAG_NAME
+                | NODE_CHILD_SELECTOR
+                | NODE_DESCENDANT_SELECTOR
+                | NODE_PSEUDO_CLASS_SELECTOR
+        )
     }
 
     /// check if current node is a class selector being typed
@@ -843,7 +1344,10 @@ impl UssCompletionProvider {
             }
 
             if let Some(parent_parent) = parent.parent() {
-                if kind == NODE_IDENTIFIER && parent_kind == NODE_CLASS_NAME && parent_parent.kind() == NODE_CLASS_SELECTOR{
+                if (kind == NODE_IDENTIFIER || kind == NODE_ESCAPE_SEQUENCE)
+                    && parent_kind == NODE_CLASS_NAME
+                    && parent_parent.kind() == NODE_CLASS_SELECTOR
+                {
                     return true;
                 }
             }
@@ -863,27 +1367,22 @@ impl UssCompletionProvider {
 
         let existing_classes = self.extract_class_selectors_from_document(tree, content);
 
-        existing_classes
+        let items = existing_classes
             .into_iter()
-            .filter(|class_name| {
-                if partial_text.is_empty() {
-                    true // Show all classes when just typed '.'
-                } else {
-                    // Exclude exact matches and only show classes that start with partial_text but are longer
-                    let class_lower = class_name.to_lowercase();
-                    let partial_lower = partial_text.to_lowercase();
-                    class_lower.starts_with(&partial_lower) && class_lower != partial_lower
-                }
-            })
+            // Exclude exact matches, that is the selector being typed
+            .filter(|class_name| partial_text.is_empty() || !class_name.eq_ignore_ascii_case(&partial_text))
             .map(|class_name| CompletionItem {
-                label: class_name.clone(),
+                insert_text: Some(escape_identifier(&class_name)),
+                filter_text: Some(class_name.clone()),
+                label: class_name,
                 kind: Some(CompletionItemKind::CLASS),
                 detail: Some("Class selector".to_string()),
-                insert_text: Some(class_name),
                 insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
                 ..Default::default()
             })
-            .collect()
+            .collect();
+
+        completion_ranking::rank_items(&partial_text, items)
     }
 
     /// Complete ID selectors
@@ -897,83 +1396,199 @@ impl UssCompletionProvider {
 
         let existing_ids = self.extract_id_selectors_from_document(tree, content);
 
-        existing_ids
+        let items = existing_ids
             .into_iter()
-            .filter(|id_name| {
-                if partial_text.is_empty() {
-                    true // Show all IDs when just typed '#'
-                } else {
-                    // Exclude exact matches and only show IDs that start with partial_text but are longer
-                    let id_lower = id_name.to_lowercase();
-                    let partial_lower = partial_text.to_lowercase();
-                    id_lower.starts_with(&partial_lower) && id_lower != partial_lower
-                }
-            })
+            // Exclude exact matches, that is the selector being typed
+            .filter(|id_name| partial_text.is_empty() || !id_name.eq_ignore_ascii_case(&partial_text))
             .map(|id_name| CompletionItem {
-                label: id_name.clone(),
+                insert_text: Some(escape_identifier(&id_name)),
+                filter_text: Some(id_name.clone()),
+                label: id_name,
                 kind: Some(CompletionItemKind::CONSTANT),
                 detail: Some("ID selector".to_string()),
-                insert_text: Some(id_name),
                 insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
                 ..Default::default()
             })
+            .collect();
+
+        completion_ranking::rank_items(&partial_text, items)
+    }
+
+    /// Whether only whitespace is before the position on its line, outside of any block
+    fn is_at_top_level_line_start(tree: &Tree, content: &str, position: Position) -> bool {
+        let Some(line) = content.split('\n').nth(position.line as usize) else {
+            return false;
+        };
+        let before_cursor: String = line.chars().take(position.character as usize).collect();
+        if !before_cursor.trim().is_empty() {
+            return false;
+        }
+
+        let root = tree.root_node();
+        find_node_of_type_at_position(root, content, position, NODE_BLOCK).is_none()
+            && find_node_of_type_at_position(root, content, position, NODE_ERROR).is_none()
+    }
+
+    /// The block at a position at the start of a line (only whitespace before it), if it has no declarations
+    fn empty_block_at_line_start<'a>(tree: &'a Tree, content: &str, position: Position) -> Option<Node<'a>> {
+        let line = content.split('\n').nth(position.line as usize)?;
+        let before_cursor: String = line.chars().take(position.character as usize).collect();
+        if !before_cursor.trim().is_empty() {
+            return None;
+        }
+
+        let block = find_node_of_type_at_position(tree.root_node(), content, position, NODE_BLOCK)?;
+        Self::is_empty_block(block).then_some(block)
+    }
+
+    /// The block a node is in, if it has no declarations, e.g. the block of a property name being typed
+    fn enclosing_empty_block(node: Node) -> Option<Node> {
+        let mut current = node.parent()?;
+        while current.kind() != NODE_BLOCK {
+            current = current.parent()?;
+        }
+        Self::is_empty_block(current).then_some(current)
+    }
+
+    fn is_empty_block(block: Node) -> bool {
+        let mut cursor = block.walk();
+        let is_empty = block.children(&mut cursor).all(|child| child.kind() != NODE_DECLARATION);
+        is_empty
+    }
+
+    /// Element types in the selectors of the rule of a block, e.g. `Button` for `Button:hover`
+    fn selector_element_types<'a>(block: Node, content: &'a str) -> Vec<&'a str> {
+        let mut types = Vec::new();
+        if let Some(selectors) = block.prev_named_sibling().filter(|n| n.kind() == NODE_SELECTORS) {
+            Self::collect_tag_names(selectors, content, &mut types);
+        }
+        types
+    }
+
+    fn collect_tag_names<'a>(node: Node, content: &'a str, types: &mut Vec<&'a str>) {
+        if node.kind() == NODE_TAG_NAME {
+            if let Ok(name) = node.utf8_text(content.as_bytes()) {
+                types.push(name);
+            }
+            return;
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            Self::collect_tag_names(child, content, types);
+        }
+    }
+
+    /// Layout snippets for an empty block, the ones for element types only if its selector has the type
+    ///
+    /// `replace_range` is the range of the word being typed, the snippet replaces it.
+    ///
+    /// Snippets with a property the project's Unity version doesn't support are left out.
+    fn complete_block_snippets(
+        &self,
+        block: Node,
+        content: &str,
+        replace_range: Option<Range>,
+        project_version: Option<UnityVersion>,
+    ) -> Vec<CompletionItem> {
+        if !self.snippet_support || !self.block_snippets {
+            return Vec::new();
+        }
+
+        let selector_types = Self::selector_element_types(block, content);
+        let is_supported = |property: &str| {
+            let info = self.definitions.get_property_info(property);
+            project_version.is_none_or(|version| info.is_none_or(|info| info.is_supported_in(version)))
+        };
+        BLOCK_SNIPPETS
+            .iter()
+            .filter(|snippet| snippet.applies_to(&selector_types) && snippet.properties().all(is_supported))
+            .map(|snippet| {
+                let mut item = Self::snippet_item(&snippet.snippet);
+                if let Some(range) = replace_range {
+                    item.text_edit = item
+                        .insert_text
+                        .take()
+                        .map(|new_text| CompletionTextEdit::Edit(TextEdit { range, new_text }));
+                }
+                item
+            })
             .collect()
     }
 
-    /// Complete tag selectors
-    /// Complete tag selectors using real UXML schema data
+    /// Rule skeleton snippets, empty if the client doesn't support snippets
+    fn complete_rule_snippets(&self) -> Vec<CompletionItem> {
+        if !self.snippet_support {
+            return Vec::new();
+        }
+
+        RULE_SNIPPETS.iter().map(Self::snippet_item).collect()
+    }
+
+    fn snippet_item(snippet: &SnippetInfo) -> CompletionItem {
+        CompletionItem {
+            label: snippet.label.to_string(),
+            kind: Some(CompletionItemKind::SNIPPET),
+            detail: Some(snippet.description.to_string()),
+            filter_text: Some(snippet.prefix.to_string()),
+            insert_text: Some(snippet.body.to_string()),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            ..Default::default()
+        }
+    }
+
+    /// Complete tag selectors using real UXML schema data, and rule snippets
+    ///
+    /// There can be hundreds of elements, so they are ranked against the typed text and limited.
     fn complete_tag_selectors(
         &self,
         current_node: Node,
         content: &str,
         uxml_data: Option<Arc<Mutex<VisualElementsData>>>,
-    ) -> Vec<CompletionItem> {
-        let partial_text = current_node
-            .utf8_text(content.as_bytes())
-            .unwrap_or("")
-            .to_lowercase();
+    ) -> CompletionList {
+        // after `> ` nothing is typed yet
+        let partial_text = if current_node.kind() == NODE_CHILD_COMBINATOR {
+            ""
+        } else {
+            current_node.utf8_text(content.as_bytes()).unwrap_or("")
+        };
 
-        let mut items = Vec::new();
+        // built-in elements if element names are not available
+        let elements: Vec<(String, String)> = match uxml_data {
+            Some(data) => match data.lock() {
+                Ok(element_names) => element_names
+                    .get_all_names()
+                    .iter()
+                    .map(|(name, full_name)| (name.clone(), full_name.clone()))
+                    .collect(),
+                Err(_) => Vec::new(),
+            },
+            None => fallback_elements(None)
+                .into_iter()
+                .map(|element| (element.name, element.fully_qualified_name))
+                .collect(),
+        };
 
-        if let Some(data) = uxml_data {
-            if let Ok(element_names) = data.lock()
-            {
-                for (element_name, _) in element_names.get_all_elements() {
-                    if element_name.to_lowercase().starts_with(&partial_text) {
-                        items.push(CompletionItem {
-                            label: element_name.clone(),
-                            kind: Some(CompletionItemKind::CLASS),
-                            detail: Some("UXML Element".to_string()),
-                            insert_text: Some(element_name.clone()),
-                            insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
-                            documentation: Some(Documentation::MarkupContent(MarkupContent {
-                                kind: MarkupKind::Markdown,
-                                value: format!("**UXML Element:** `{}`", element_name),
-                            })),
-                            ..Default::default()
-                        });
-                    }
-                }
-            }
-        } else {
-            // Fallback to hardcoded list if element names are not available
-            let unity_tags = vec!["Button", "Label", "Slider", "Dropdown"];
+        let mut items: Vec<CompletionItem> = elements
+            .into_iter()
+            .map(|(element_name, full_name)| CompletionItem {
+                label: element_name.clone(),
+                kind: Some(CompletionItemKind::CLASS),
+                detail: Some(full_name.clone()),
+                insert_text: Some(element_name),
+                insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+                documentation: Some(Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: format!("**UXML Element:** `{}`", full_name),
+                })),
+                ..Default::default()
+            })
+            .collect();
 
-            for tag_name in unity_tags {
-                if tag_name.to_lowercase().starts_with(&partial_text) {
-                    items.push(CompletionItem {
-                        label: tag_name.to_string(),
-                        kind: Some(CompletionItemKind::CLASS),
-                        detail: Some("Unity UI element (fallback)".to_string()),
-                        insert_text: Some(tag_name.to_string()),
-                        insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
-                        ..Default::default()
-                    });
-                }
-            }
-        }
+        items.extend(self.complete_rule_snippets());
 
-        items
+        let mut items = completion_ranking::rank_items(partial_text, items);
+        let is_incomplete = completion_ranking::limit_ranked_items(&mut items, MAX_RANKED_ITEMS);
+        CompletionList { is_incomplete, items }
     }
 
     /// Extract partial selector text (without the prefix character)
@@ -983,11 +1598,21 @@ impl UssCompletionProvider {
         content: &str,
         prefix: char,
     ) -> String {
-        let node_text = current_node.utf8_text(content.as_bytes()).unwrap_or("");
+        // an escape splits a class name into several identifiers, the name is the whole class name
+        let name_node = match current_node.parent() {
+            Some(parent)
+                if parent.kind() == NODE_CLASS_NAME
+                    && matches!(current_node.kind(), NODE_IDENTIFIER | NODE_ESCAPE_SEQUENCE) =>
+            {
+                parent
+            }
+            _ => current_node,
+        };
+        let node_text = name_node.utf8_text(content.as_bytes()).unwrap_or("");
         // In new version 0.23 the selector names are in identifier node
-        if current_node.kind() == NODE_CLASS_NAME || current_node.kind() == NODE_ID_NAME || current_node.kind() == NODE_IDENTIFIER {
+        if matches!(name_node.kind(), NODE_CLASS_NAME | NODE_ID_NAME | NODE_IDENTIFIER) {
             // We're in the name part of the selector
-            return node_text.to_string();
+            return unescape_identifier(node_text);
         }
 
         if current_node.kind() == NODE_ERROR && node_text.starts_with(prefix) {
@@ -1055,6 +1680,8 @@ impl UssCompletionProvider {
                                         t: CompletionType::UrlString {
                                             url_string,
                                             cursor_position: cursor_offset,
+                                            asset_filter: None,
+                                            offers_font_assets: false,
                                         },
                                         current_node: Some(current_node),
                                     });
@@ -1119,14 +1746,25 @@ impl UssCompletionProvider {
             return None;
         }
 
+        // Only suggest the types of assets the property accepts
+        let property_name = Self::find_ancestor_of_type(string_value_node, NODE_DECLARATION)
+            .and_then(|declaration| declaration.child(0))
+            .filter(|property| property.kind() == NODE_PROPERTY_NAME)
+            .and_then(|property| property.utf8_text(content.as_bytes()).ok());
+        let asset_filter = property_name.and_then(|property_name| self.definitions.get_asset_filter(property_name));
+
         // Extract URL string and cursor position from the current string node
         if let Some((url_string, cursor_pos)) =
             self.extract_url_string_from_current_node(current_node, content, position)
         {
+            let offers_font_assets =
+                property_name == Some("-unity-font-definition") && !url_string[..cursor_pos].contains('/');
             return Some(CompletionContext {
                 t: CompletionType::UrlString {
                     url_string,
                     cursor_position: cursor_pos,
+                    asset_filter,
+                    offers_font_assets,
                 },
                 current_node: Some(current_node),
             });
@@ -1195,9 +1833,10 @@ impl UssCompletionProvider {
         url_string: &str,
         cursor_position: usize,
         source_url: Option<&url::Url>,
+        asset_filter: Option<&AssetFilter>,
     ) -> Vec<CompletionItem> {
         if let Some(provider) = &self.url_completion_provider {
-            provider.complete_url(url_string, cursor_position, source_url)
+            provider.complete_url_with_filter(url_string, cursor_position, source_url, asset_filter)
         } else {
             Vec::new()
         }
@@ -1327,7 +1966,7 @@ impl UssCompletionProvider {
                 if parent.kind() == NODE_CLASS_SELECTOR {
                     if let Ok(name) = node.utf8_text(content.as_bytes()) {
                         if !name.is_empty() {
-                            class_collector.insert(name.to_string());
+                            class_collector.insert(unescape_identifier(name));
                         }
                     }
                 }
@@ -1340,7 +1979,7 @@ impl UssCompletionProvider {
                 if parent.kind() == NODE_ID_SELECTOR {
                     if let Ok(name) = node.utf8_text(content.as_bytes()) {
                         if !name.is_empty() {
-                            id_collector.insert(name.to_string());
+                            id_collector.insert(unescape_identifier(name));
                         }
                     }
                 }
@@ -1,4 +1,4 @@
-use tower_lsp::lsp_types::{CompletionItemKind, Position};
+use tower_lsp::lsp_types::{CompletionItemKind, CompletionTextEdit, Position, Range};
 use crate::language::tree_printer::{print_tree, print_tree_to_stdout};
 use crate::test_utils::get_unity_project_root;
 use crate::uss::{completion::UssCompletionProvider, parser::UssParser};
@@ -455,6 +455,24 @@ fn test_class_selector_partial_completion() {
     );
 }
 
+#[test]
+fn test_escaped_class_selector_completion() {
+    let mut parser = UssParser::new().unwrap();
+    let provider = UssCompletionProvider::new();
+
+    // the same class written with two different escapes, and a class being typed after an escape
+    let content = ".hover\\:red { color: red; }\n.hover\\3A red { margin: 10px; }\n.hover\\:r";
+    let tree = parser.parse(content, None).unwrap();
+
+    let completions = provider.complete(&tree, content, Position::new(2, 9), None, None, None);
+
+    let class: Vec<_> = completions.iter().filter(|c| c.kind == Some(CompletionItemKind::CLASS)).collect();
+    assert_eq!(class.len(), 1, "{:?}", completions);
+    assert_eq!(class[0].label, "hover:red");
+    assert_eq!(class[0].insert_text.as_deref(), Some("hover\\:red"));
+    assert_eq!(class[0].filter_text.as_deref(), Some("hover:red"));
+}
+
 #[test]
 fn test_id_selector_completion_after_hash() {
     let mut parser = UssParser::new().unwrap();
@@ -672,7 +690,7 @@ fn test_tag_selector_completion() {
     assert_eq!(button_completion.kind, Some(CompletionItemKind::CLASS));
     assert_eq!(
         button_completion.detail,
-        Some("Unity UI element (fallback)".to_string())
+        Some("UnityEngine.UIElements.Button".to_string())
     );
     assert_eq!(button_completion.insert_text, Some("Button".to_string()));
 }
@@ -783,7 +801,7 @@ fn test_tag_selector_completion_no_empty_input() {
     // Should not have any tag completions for empty input
     let tag_completions: Vec<_> = completions
         .iter()
-        .filter(|c| c.detail == Some("Unity UI element (fallback)".to_string()))
+        .filter(|c| c.kind == Some(CompletionItemKind::CLASS))
         .collect();
     assert!(
         tag_completions.is_empty(),
@@ -1245,3 +1263,295 @@ fn test_import_statement_completion_inside_ruleset_2() {
     // Should NOT provide import statement completions inside a ruleset block
     assert!(completions.is_empty(), "Should NOT provide completions inside a ruleset block");
 }
+
+#[test]
+fn test_tag_selector_completion_ranked_and_limited() {
+    use std::sync::{Arc, Mutex};
+
+    use crate::uss::completion::MAX_RANKED_ITEMS;
+    use crate::uxml_schema_manager::{VisualElementInfo, VisualElementsData};
+
+    let mut data = VisualElementsData::new();
+    let mut add = |name: &str| {
+        let fully_qualified_name = format!("UnityEngine.UIElements.{}", name);
+        data.insert(
+            fully_qualified_name.clone(),
+            VisualElementInfo {
+                name: name.to_string(),
+                namespace: "UnityEngine.UIElements".to_string(),
+                fully_qualified_name,
+                attributes: Vec::new(),
+                base: None,
+            },
+        );
+    };
+    add("ProgressBar");
+    add("PropertyField");
+    add("PopupWindow");
+    for i in 0..300 {
+        add(&format!("Panel{}", i));
+    }
+    let data = Arc::new(Mutex::new(data));
+
+    let mut parser = UssParser::new().unwrap();
+    let provider = UssCompletionProvider::new();
+
+    // camel hump match ranks before fuzzy matches
+    let content = "PB";
+    let tree = parser.parse(content, None).unwrap();
+    let list = provider.complete_list_with_variables(&tree, content, Position::new(0, 2), None, Some(data.clone()), None, None);
+    assert!(!list.is_incomplete);
+    let labels: Vec<&str> = list.items.iter().map(|c| c.label.as_str()).collect();
+    assert_eq!(labels, vec!["ProgressBar"]);
+    assert_eq!(list.items[0].detail.as_deref(), Some("UnityEngine.UIElements.ProgressBar"));
+
+    // too many candidates, the list is cut and marked incomplete
+    let content = "P";
+    let tree = parser.parse(content, None).unwrap();
+    let list = provider.complete_list_with_variables(&tree, content, Position::new(0, 1), None, Some(data), None, None);
+    assert!(list.is_incomplete);
+    assert_eq!(list.items.len(), MAX_RANKED_ITEMS);
+    assert_eq!(list.items[0].label, "Panel0");
+    assert_eq!(list.items[0].sort_text.as_deref(), Some("00000"));
+}
+
+#[test]
+fn test_rule_snippet_completion() {
+    use tower_lsp::lsp_types::InsertTextFormat;
+
+    let mut parser = UssParser::new().unwrap();
+    let mut provider = UssCompletionProvider::new();
+
+    let content = ".a { color: red; }\n\n";
+    let tree = parser.parse(content, None).unwrap();
+    let position = Position::new(2, 0);
+
+    // no snippets unless the client supports them
+    assert!(provider.complete(&tree, content, position, None, None, None).is_empty());
+
+    provider.set_snippet_support(true);
+    let completions = provider.complete(&tree, content, position, None, None, None);
+    let labels: Vec<&str> = completions.iter().map(|c| c.label.as_str()).collect();
+    assert!(labels.contains(&".class-name { }"));
+    assert!(labels.contains(&":root { --variable: value; }"));
+    assert!(labels.contains(&"transition block"));
+    assert!(completions.iter().all(|c| c.insert_text_format == Some(InsertTextFormat::SNIPPET)));
+
+    // typing a word at the top level ranks snippets with tag selectors
+    let content = ".a { color: red; }\ntrans";
+    let tree = parser.parse(content, None).unwrap();
+    let completions = provider.complete(&tree, content, Position::new(1, 5), None, None, None);
+    assert_eq!(completions[0].label, "transition block");
+
+    // not inside a block
+    let content = ".a {\n    \n}";
+    let tree = parser.parse(content, None).unwrap();
+    let completions = provider.complete(&tree, content, Position::new(1, 4), None, None, None);
+    assert!(completions.iter().all(|c| c.label != ".class-name { }"));
+}
+
+#[test]
+fn test_block_snippet_completion() {
+    let mut parser = UssParser::new().unwrap();
+    let mut provider = UssCompletionProvider::new();
+    provider.set_snippet_support(true);
+    let mut labels = |provider: &UssCompletionProvider, content: &str, position: Position| {
+        let tree = parser.parse(content, None).unwrap();
+        provider
+            .complete(&tree, content, position, None, None, None)
+            .into_iter()
+            .filter(|c| c.kind == Some(CompletionItemKind::SNIPPET))
+            .map(|c| c.label)
+            .collect::<Vec<_>>()
+    };
+
+    // on a blank line of an empty block, snippets for element types only with the type in the selector
+    let block_labels = labels(&provider, ".a {\n    \n}", Position::new(1, 4));
+    assert!(block_labels.contains(&"absolute-fill".to_string()));
+    assert!(block_labels.contains(&"flex-row-center".to_string()));
+    assert!(!block_labels.contains(&"button-reset".to_string()));
+    let button_labels = labels(&provider, "Button:hover {\n    \n}", Position::new(1, 4));
+    assert!(button_labels.contains(&"button-reset".to_string()));
+    assert!(button_labels.contains(&"text-ellipsis".to_string()));
+
+    // typing a word, with the properties
+    let content = ".a {\n    abs\n}";
+    let tree = UssParser::new().unwrap().parse(content, None).unwrap();
+    let completions = provider.complete(&tree, content, Position::new(1, 7), None, None, None);
+    let snippet = completions.iter().find(|c| c.label == "absolute-fill").unwrap();
+    match &snippet.text_edit {
+        Some(CompletionTextEdit::Edit(edit)) => {
+            assert_eq!(edit.range, Range::new(Position::new(1, 4), Position::new(1, 7)));
+            assert!(edit.new_text.starts_with("position: absolute;"));
+        }
+        _ => panic!("Expected a text edit"),
+    }
+
+    // not in a block that has declarations
+    let content = ".a {\n    color: red;\n    abs\n}";
+    assert!(labels(&provider, content, Position::new(2, 7)).is_empty());
+    assert!(labels(&provider, ".a {\n    color: red;\n    \n}", Position::new(2, 4)).is_empty());
+
+    provider.set_block_snippets(false);
+    assert!(labels(&provider, ".a {\n    \n}", Position::new(1, 4)).is_empty());
+}
+
+#[test]
+fn test_block_snippets_follow_unity_version() {
+    let mut parser = UssParser::new().unwrap();
+    let mut provider = UssCompletionProvider::new();
+    provider.set_snippet_support(true);
+    let content = ".a {\n    \n}";
+    let tree = parser.parse(content, None).unwrap();
+    let labels = |version: &str| {
+        let project = crate::test_utils::UnityProjectBuilder::new().unity_version(version).build().unwrap();
+        let manager = crate::unity_project_manager::UnityProjectManager::new(project.path().to_path_buf());
+        provider
+            .complete(&tree, content, Position::new(1, 4), None, None, Some(&manager))
+            .into_iter()
+            .map(|c| c.label)
+            .collect::<Vec<_>>()
+    };
+
+    // background-size, which the snippet sets, is only supported since Unity 2022.2
+    assert!(!labels("2021.3.10f1").contains(&"background-fit".to_string()));
+    assert!(labels("2022.3.10f1").contains(&"background-fit".to_string()));
+}
+
+#[test]
+fn test_url_completion_filters_asset_types() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let ui_dir = temp_dir.path().join("Assets").join("UI");
+    std::fs::create_dir_all(ui_dir.join("Icons")).unwrap();
+    for file in ["icon.png", "Inter.ttf", "Inter SDF.asset", "main.uss", "notes.txt"] {
+        std::fs::write(ui_dir.join(file), "").unwrap();
+    }
+
+    let mut parser = UssParser::new().unwrap();
+    let provider = UssCompletionProvider::new_with_project_root(temp_dir.path());
+    let mut labels = |property: &str| {
+        let content = format!(".a {{ {}: url(\"project:/Assets/UI/\"); }}", property);
+        let tree = parser.parse(&content, None).unwrap();
+        let character = content.find("UI/").unwrap() as u32 + 3;
+        let completions = provider.complete(&tree, &content, Position::new(0, character), None, None, None);
+        let mut labels: Vec<String> = completions.into_iter().map(|c| c.label).collect();
+        labels.sort();
+        labels
+    };
+
+    // directories are always suggested
+    assert_eq!(labels("background-image"), vec!["Icons", "Inter SDF.asset", "icon.png"]);
+    assert_eq!(labels("-unity-font"), vec!["Icons", "Inter SDF.asset", "Inter.ttf"]);
+    assert_eq!(labels("-unity-font-definition"), vec!["Icons", "Inter SDF.asset", "Inter.ttf"]);
+    // a variable can be used anywhere
+    assert_eq!(labels("--image").len(), 6);
+}
+
+#[test]
+fn test_url_completion_offers_font_assets_by_name() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let fonts_dir = temp_dir.path().join("Assets").join("Fonts");
+    std::fs::create_dir_all(&fonts_dir).unwrap();
+    std::fs::write(
+        fonts_dir.join("Inter SDF.asset"),
+        "%YAML 1.1\n--- !u!114 &11400000\nMonoBehaviour:\n  m_Name: Inter SDF\n  m_FaceInfo:\n    m_FamilyName: Inter\n    m_StyleName: Regular\n",
+    )
+    .unwrap();
+    std::fs::write(fonts_dir.join("Inter SDF.asset.meta"), "fileFormatVersion: 2\nguid: 0123456789abcdef0123456789abcdef\n").unwrap();
+
+    let mut parser = UssParser::new().unwrap();
+    let provider = UssCompletionProvider::new_with_project_root(temp_dir.path());
+    provider.font_asset_index().unwrap().refresh();
+    let mut complete = |property: &str, url: &str| {
+        let content = format!(".a {{ {}: url(\"{}\"); }}", property, url);
+        let tree = parser.parse(&content, None).unwrap();
+        let position = Position::new(0, (content.find(url).unwrap() + url.len()) as u32);
+        assert_eq!(
+            provider.offers_font_assets(&tree, &content, position),
+            property == "-unity-font-definition" && !url.contains('/')
+        );
+        provider.complete(&tree, &content, position, None, None, None)
+    };
+
+    let completions = complete("-unity-font-definition", "Int");
+    let item = completions.iter().find(|c| c.label == "Inter Regular").expect("font asset is offered");
+    let Some(CompletionTextEdit::Edit(edit)) = &item.text_edit else {
+        panic!("font asset replaces the URL");
+    };
+    assert_eq!(
+        edit.new_text,
+        "project:///Assets/Fonts/Inter%20SDF.asset?fileID=11400000&guid=0123456789abcdef0123456789abcdef&type=2#Inter%20SDF"
+    );
+    assert_eq!(edit.range.start.character, ".a { -unity-font-definition: url(\"".len() as u32);
+
+    // not once a path is typed, or for properties that don't take font assets
+    assert!(complete("-unity-font-definition", "project:/Assets/").iter().all(|c| c.label != "Inter Regular"));
+    assert!(complete("-unity-font", "Int").iter().all(|c| c.label != "Inter Regular"));
+}
+
+/// Labels of the completions at the end of a selector typed after some rules
+fn complete_selector(selector: &str) -> Vec<String> {
+    let mut parser = UssParser::new().unwrap();
+    let provider = UssCompletionProvider::new();
+    let content = format!(".beta {{ }}\n#ident {{ }}\n{}", selector);
+    let tree = parser.parse(&content, None).unwrap();
+    let position = Position::new(2, selector.chars().count() as u32);
+    provider
+        .complete(&tree, &content, position, None, None, None)
+        .into_iter()
+        .map(|c| c.label)
+        .collect()
+}
+
+#[test]
+fn test_child_combinator_completion() {
+    // tags are offered after `> ` before anything is typed
+    assert!(complete_selector(".a > ").contains(&"Button".to_string()));
+    assert!(complete_selector(".a > B").contains(&"Button".to_string()));
+    assert!(complete_selector(".a>.").contains(&"beta".to_string()));
+    assert!(complete_selector(".a > .b").contains(&"beta".to_string()));
+    assert!(complete_selector(".a > #i").contains(&"ident".to_string()));
+    assert!(complete_selector(".a > B:").contains(&"hover".to_string()));
+    assert!(complete_selector(".a > B.").contains(&"beta".to_string()));
+
+    // not right after `>`, there is no space yet
+    assert!(!complete_selector(".a >").contains(&"Button".to_string()));
+}
+
+#[test]
+fn test_descendant_combinator_completion() {
+    assert!(complete_selector(".a B").contains(&"Button".to_string()));
+    assert!(complete_selector(".a .").contains(&"beta".to_string()));
+    assert!(complete_selector(".a #").contains(&"ident".to_string()));
+    assert!(complete_selector(".a B:h").contains(&"hover".to_string()));
+    assert!(complete_selector("B > .a .").contains(&"beta".to_string()));
+}
+
+#[test]
+fn test_chained_selector_completion() {
+    assert!(complete_selector(".a.").contains(&"beta".to_string()));
+    assert!(complete_selector("Button.").contains(&"beta".to_string()));
+    assert!(complete_selector(".a:hover .").contains(&"beta".to_string()));
+    assert!(complete_selector(".a:hover:").contains(&"active".to_string()));
+}
+
+#[test]
+fn test_cursor_completion() {
+    let mut parser = UssParser::new().unwrap();
+    let mut provider = UssCompletionProvider::new();
+    let content = ".a {\n    cursor: \n}";
+    let tree = parser.parse(content, None).unwrap();
+    let position = Position::new(1, 11);
+
+    let completions = provider.complete(&tree, content, position, None, None, None);
+    assert!(completions.iter().any(|c| c.label == "link"));
+    assert!(completions.iter().all(|c| c.kind != Some(CompletionItemKind::SNIPPET)));
+
+    // custom cursors with their hotspot, if the client supports snippets
+    provider.set_snippet_support(true);
+    let completions = provider.complete(&tree, content, position, None, None, None);
+    assert!(completions.iter().any(|c| c.label == "link"));
+    let url = completions.iter().find(|c| c.filter_text.as_deref() == Some("url")).unwrap();
+    assert_eq!(url.insert_text.as_deref(), Some(" url(\"${1:path}\") ${2:0} ${3:0};$0"));
+    assert!(completions.iter().any(|c| c.filter_text.as_deref() == Some("resource")));
+}
@@ -222,6 +222,23 @@ fn test_property_name_completion_partial_match() {
     );
 }
 
+#[test]
+fn test_deprecated_property_name_completion() {
+    use tower_lsp::lsp_types::CompletionItemTag;
+
+    let mut parser = UssParser::new().unwrap();
+    let provider = UssCompletionProvider::new();
+    let content = ".some { \n    -unity-back\n}";
+    let tree = parser.parse(content, None).unwrap();
+
+    let completions = provider.complete(&tree, content, Position::new(1, 15), None, None, None);
+
+    let scale_mode = completions.iter().find(|c| c.label == "-unity-background-scale-mode").unwrap();
+    assert_eq!(scale_mode.tags, Some(vec![CompletionItemTag::DEPRECATED]));
+    let tint = completions.iter().find(|c| c.label == "-unity-background-image-tint-color").unwrap();
+    assert_eq!(tint.tags, None);
+}
+
 #[test]
 fn test_property_name_completion_partial_match_before_another() {
     let mut parser = UssParser::new().unwrap();
@@ -412,6 +429,28 @@ fn test_property_name_completion_bug_after_custom_property() {
     assert!(!completion_labels.contains(&"width".to_string()), "Should not include 'width' completion for 'co'");
 }
 
+#[test]
+fn test_property_name_completion_camel_hump_and_fuzzy() {
+    let mut parser = UssParser::new().unwrap();
+    let provider = UssCompletionProvider::new();
+
+    // 'fd' has no prefix match, it matches the words of flex-direction
+    let content = ".a {\n    fd\n}";
+    let tree = parser.parse(content, None).unwrap();
+    let completions = provider.complete(&tree, content, Position::new(1, 6), None, None, None);
+    assert_eq!(completions[0].label, "flex-direction");
+    assert_eq!(completions[0].sort_text.as_deref(), Some("00000"));
+
+    // prefix matches come before camel hump and fuzzy matches
+    let content = ".a {\n    col\n}";
+    let tree = parser.parse(content, None).unwrap();
+    let completions = provider.complete(&tree, content, Position::new(1, 7), None, None, None);
+    let labels: Vec<&str> = completions.iter().map(|c| c.label.as_str()).collect();
+    assert_eq!(labels[0], "color");
+    let border_color = labels.iter().position(|l| *l == "border-color").expect("Should include fuzzy match 'border-color'");
+    assert!(border_color > 0);
+}
+
 #[test]
 fn test_property_name_completion_case_insensitive() {
     let mut parser = UssParser::new().unwrap();
@@ -656,3 +695,199 @@ fn test_color_keyword_completion_item_kind() {
         "Should find at least one color keyword completion"
     );
 }
+
+#[test]
+fn test_property_name_completion_filtered_by_unity_version() {
+    use crate::unity_project_manager::UnityProjectManager;
+
+    let mut parser = UssParser::new().unwrap();
+    let provider = UssCompletionProvider::new();
+
+    let content = ".some { \n    back\n}";
+    let tree = parser.parse(content, None).unwrap();
+    let position = Position {
+        line: 1,
+        character: 8,
+    };
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let settings_dir = temp_dir.path().join("ProjectSettings");
+    std::fs::create_dir_all(&settings_dir).unwrap();
+    std::fs::write(settings_dir.join("ProjectVersion.txt"), "m_EditorVersion: 2021.3.10f1\n").unwrap();
+    let unity_manager = UnityProjectManager::new(temp_dir.path().to_path_buf());
+
+    let completions = provider.complete(&tree, content, position, None, None, Some(&unity_manager));
+    let labels: Vec<String> = completions.iter().map(|c| c.label.clone()).collect();
+    assert!(labels.contains(&"background-color".to_string()));
+    // background-size requires Unity 2022.2
+    assert!(
+        !labels.contains(&"background-size".to_string()),
+        "Should not suggest properties unsupported in Unity 2021.3, got {:?}",
+        labels
+    );
+
+    std::fs::write(settings_dir.join("ProjectVersion.txt"), "m_EditorVersion: 2022.3.10f1\n").unwrap();
+    let completions = provider.complete(&tree, content, position, None, None, Some(&unity_manager));
+    assert!(completions.iter().any(|c| c.label == "background-size"));
+}
+
+#[test]
+fn test_variable_name_completion_in_var_function() {
+    use crate::uss::definitions::UssDefinitions;
+    use crate::uss::variable_resolver::VariableResolver;
+    use std::sync::Arc;
+    use tower_lsp::lsp_types::{CompletionItemKind, Documentation};
+
+    let mut parser = UssParser::new().unwrap();
+    let provider = UssCompletionProvider::new();
+
+    let content = ":root {\n    /* Primary brand color */\n    --brand: #ff0000;\n    --spacing: 4px;\n}\n.a { color: var(--br); }";
+    let tree = parser.parse(content, None).unwrap();
+    let mut resolver = VariableResolver::new(UssDefinitions::shared());
+    resolver.add_variables_from_tree(tree.root_node(), content);
+
+    // cursor after "--br"
+    let position = Position {
+        line: 5,
+        character: 20,
+    };
+    let completions =
+        provider.complete_with_variables(&tree, content, position, None, None, None, Some(&resolver));
+    assert_eq!(completions.len(), 1, "Got {:?}", completions.iter().map(|c| &c.label).collect::<Vec<_>>());
+    assert_eq!(completions[0].label, "--brand");
+    assert_eq!(completions[0].kind, Some(CompletionItemKind::VARIABLE));
+    let Some(Documentation::MarkupContent(doc)) = &completions[0].documentation else {
+        panic!("Expected markdown documentation");
+    };
+    assert!(doc.value.contains("Primary brand color"));
+
+    // without variables, nothing is completed
+    let completions = provider.complete(&tree, content, position, None, None, None);
+    assert!(completions.is_empty());
+}
+
+#[test]
+fn test_unit_completion_after_number() {
+    use tower_lsp::lsp_types::{CompletionItemKind, CompletionTextEdit};
+
+    let mut parser = UssParser::new().unwrap();
+    let provider = UssCompletionProvider::new();
+    let mut complete = |content: &str, character: u32| -> Vec<(String, String)> {
+        let tree = parser.parse(content, None).unwrap();
+        provider
+            .complete(&tree, content, Position::new(0, character), None, None, None)
+            .into_iter()
+            .map(|item| {
+                assert_eq!(item.kind, Some(CompletionItemKind::UNIT));
+                let Some(CompletionTextEdit::Edit(edit)) = item.text_edit else {
+                    panic!("unit without edit");
+                };
+                (item.label, edit.new_text)
+            })
+            .collect()
+    };
+    let pair = |label: &str, text: &str| (label.to_string(), text.to_string());
+
+    // the value is complete after the unit
+    let items = complete(".a { width: 10 }", 14);
+    assert!(items.contains(&pair("px", "px;")), "{:?}", items);
+    assert!(items.contains(&pair("%", "%;")), "{:?}", items);
+    assert_eq!(complete(".a { width: 10; }", 14).len(), 2);
+    assert!(complete(".a { width: 10; }", 14).contains(&pair("px", "px")));
+
+    // more values can follow
+    assert!(complete(".a { margin: 4px 10 }", 19).contains(&pair("px", "px")));
+    let items = complete(".a { transition-duration: 1s, 2 }", 31);
+    assert_eq!(items.len(), 2, "{:?}", items);
+    assert!(items.contains(&pair("ms", "ms")));
+
+    // a partial unit is replaced
+    let items = complete(".a { rotate: 4d }", 15);
+    assert_eq!(items.first(), Some(&pair("deg", "deg")));
+
+    // numbers without units
+    assert!(complete(".a { flex-grow: 1 }", 17).is_empty());
+}
+
+#[test]
+fn test_keyword_completion_after_other_values() {
+    let mut parser = UssParser::new().unwrap();
+    let provider = UssCompletionProvider::new();
+    let mut complete = |content: &str, character: u32| -> Vec<(String, String)> {
+        let tree = parser.parse(content, None).unwrap();
+        provider
+            .complete(&tree, content, Position::new(0, character), None, None, None)
+            .into_iter()
+            .map(|item| (item.label, item.insert_text.unwrap_or_default()))
+            .collect()
+    };
+    let pair = |label: &str, text: &str| (label.to_string(), text.to_string());
+
+    // the second value of margin, more values can follow
+    let items = complete(".a { margin: 4px a }", 18);
+    assert_eq!(items, vec![pair("auto", "auto")]);
+    assert_eq!(complete(".a { margin: 1px 2px 3px a }", 26), vec![pair("auto", "auto;")]);
+
+    // the timing function after the duration of a transition
+    let items = complete(".a { transition: width 2s ease-i }", 32);
+    assert_eq!(items.first(), Some(&pair("ease-in", "ease-in")), "{:?}", items);
+    assert!(!items.iter().any(|(label, _)| label == "width"));
+    let items = complete(".a { transition: width 2s, color 1s l }", 37);
+    assert_eq!(items.first(), Some(&pair("linear", "linear")), "{:?}", items);
+
+    // the second color of border-color
+    let items = complete(".a { border-color: red bl }", 25);
+    assert!(items.contains(&pair("blue", "blue")), "{:?}", items);
+
+    // nothing fits after the values before
+    assert!(complete(".a { flex-direction: row c }", 26).is_empty());
+}
+
+#[test]
+fn test_value_semicolon_and_commit_characters() {
+    use tower_lsp::lsp_types::{CompletionItem, InsertTextFormat};
+
+    let mut parser = UssParser::new().unwrap();
+    let mut provider = UssCompletionProvider::new();
+    let mut complete = |provider: &UssCompletionProvider, content: &str, character: u32, label: &str| -> CompletionItem {
+        let tree = parser.parse(content, None).unwrap();
+        provider
+            .complete(&tree, content, Position::new(0, character), None, None, None)
+            .into_iter()
+            .find(|item| item.label == label)
+            .unwrap()
+    };
+
+    // a semicolon after the cursor isn't inserted again
+    let item = complete(&provider, ".a { flex-direction: ro }", 23, "row");
+    assert_eq!(item.insert_text.as_deref(), Some("row;"));
+    let item = complete(&provider, ".a { flex-direction: ro; }", 23, "row");
+    assert_eq!(item.insert_text.as_deref(), Some("row"));
+    let item = complete(&provider, ".a { flex-direction:; }", 20, "row");
+    assert_eq!(item.insert_text.as_deref(), Some(" row"));
+    assert_eq!(item.commit_characters, None);
+
+    // with snippets, the cursor is placed before the semicolon if more values can follow
+    provider.set_snippet_support(true);
+    let item = complete(&provider, ".a { margin: }", 12, "auto");
+    assert_eq!(item.insert_text.as_deref(), Some(" auto$0;"));
+    assert_eq!(item.insert_text_format, Some(InsertTextFormat::SNIPPET));
+    let item = complete(&provider, ".a { margin: 4px a }", 18, "auto");
+    assert_eq!(item.insert_text.as_deref(), Some("auto$0;"));
+    let item = complete(&provider, ".a { flex-direction: ro }", 23, "row");
+    assert_eq!(item.insert_text.as_deref(), Some("row;"));
+    assert_eq!(item.insert_text_format, Some(InsertTextFormat::PLAIN_TEXT));
+
+    // values without a semicolon are committed with one, or with a comma for comma separated values
+    provider.set_commit_characters_support(true);
+    let item = complete(&provider, ".a { flex-direction: ro; }", 23, "row");
+    assert_eq!(item.commit_characters, None);
+    let item = complete(&provider, ".a { transition-property: op }", 28, "opacity");
+    assert_eq!(item.insert_text.as_deref(), Some("opacity"));
+    assert_eq!(item.commit_characters, Some(vec![";".to_string(), ",".to_string()]));
+    let item = complete(&provider, ".a { margin: 4px a; }", 18, "auto");
+    assert_eq!(item.commit_characters, None);
+    let item = complete(&provider, ".a { margin: 4px a 2px }", 18, "auto");
+    assert_eq!(item.insert_text.as_deref(), Some("auto"));
+    assert_eq!(item.commit_characters, Some(vec![";".to_string()]));
+}
@@ -44,6 +44,8 @@ pub const NODE_INTEGER_VALUE: &str = "integer_value";
 pub const NODE_FLOAT_VALUE: &str = "float_value";
 /// USS unit identifier (e.g., `px`, `%`, `em`)
 pub const NODE_UNIT: &str = "unit";
+/// `!important` flag at the end of a declaration (not supported in USS)
+pub const NODE_IMPORTANT: &str = "important";
 
 // Function calls
 /// USS function call (e.g., `rgb()`, `url()`, `resource()`)
@@ -54,6 +56,10 @@ pub const NODE_FUNCTION_NAME: &str = "function_name";
 pub const NODE_ARGUMENTS: &str = "arguments";
 
 // Selector types
+/// Child selector, eg. `.a > .b`
+pub const NODE_CHILD_SELECTOR: &str = "child_selector";
+/// The `>` of a child selector
+pub const NODE_CHILD_COMBINATOR: &str = ">";
 /// USS class selector(one or multiple chained, no spaces, no pseudo classes) (e.g., `.my-class` `.my-class.your-class`)
 /// Their will be multiple layers of class selectors if they are chained
 pub const NODE_CLASS_SELECTOR: &str = "class_selector";
@@ -61,6 +67,8 @@ pub const NODE_CLASS_SELECTOR: &str = "class_selector";
 pub const NODE_DESCENDANT_SELECTOR:&str= "descendant_selector";
 /// Name part of a class selector or a pseudo class selector (e.g., `my-class` in `.my-class` or `hover` in `.my-class:hover`)
 pub const NODE_CLASS_NAME: &str = "class_name";
+/// Escape in a name, e.g. `\:` in `.hover\:red`, splits the name into several identifiers
+pub const NODE_ESCAPE_SEQUENCE: &str = "escape_sequence";
 /// USS ID selector (e.g., `#my-id`)
 pub const NODE_ID_SELECTOR: &str = "id_selector";
 /// Name part of an ID selector (e.g., `my-id` in `#my-id`)
@@ -69,6 +77,16 @@ pub const NODE_ID_NAME: &str = "id_name";
 pub const NODE_TAG_NAME: &str = "tag_name";
 /// USS pseudo-class selector (e.g., `.my-class:hover`, `.my-class:active`), note this is the full selector including everything and the pseudo class part as the last child
 pub const NODE_PSEUDO_CLASS_SELECTOR: &str = "pseudo_class_selector";
+/// Pseudo-element selector (e.g., `.a::before`), not supported in USS
+pub const NODE_PSEUDO_ELEMENT_SELECTOR: &str = "pseudo_element_selector";
+/// Attribute selector (e.g., `[type="text"]`), not supported in USS
+pub const NODE_ATTRIBUTE_SELECTOR: &str = "attribute_selector";
+/// Adjacent sibling combinator selector (e.g., `.a + .b`), not supported in USS
+pub const NODE_ADJACENT_SIBLING_SELECTOR: &str = "adjacent_sibling_selector";
+/// General sibling combinator selector (e.g., `.a ~ .b`), not supported in USS
+pub const NODE_SIBLING_SELECTOR: &str = "sibling_selector";
+/// Namespace selector (e.g., `ns|Button`), not supported in USS
+pub const NODE_NAMESPACE_SELECTOR: &str = "namespace_selector";
 
 // At-rules
 /// Generic CSS at-rule
@@ -131,3 +149,36 @@ pub const UNIT_TURN: &str = "turn";
 pub const UNIT_S: &str = "s";
 /// Millisecond unit for time measurements
 pub const UNIT_MS: &str = "ms";
+/// Units of lengths
+pub const LENGTH_UNITS: &[&str] = &[UNIT_PX, UNIT_PERCENT];
+/// Units of angles
+pub const ANGLE_UNITS: &[&str] = &[UNIT_DEG, UNIT_GRAD, UNIT_RAD, UNIT_TURN];
+/// Units of times
+pub const TIME_UNITS: &[&str] = &[UNIT_S, UNIT_MS];
+/// Units written in full or in the plural, with the unit they mean
+pub const UNIT_ALIASES: &[(&str, &str)] = &[
+    ("pixel", UNIT_PX),
+    ("pixels", UNIT_PX),
+    ("percent", UNIT_PERCENT),
+    ("degree", UNIT_DEG),
+    ("degrees", UNIT_DEG),
+    ("radian", UNIT_RAD),
+    ("radians", UNIT_RAD),
+    ("gradian", UNIT_GRAD),
+    ("gradians", UNIT_GRAD),
+    ("turns", UNIT_TURN),
+    ("sec", UNIT_S),
+    ("secs", UNIT_S),
+    ("second", UNIT_S),
+    ("seconds", UNIT_S),
+    ("msec", UNIT_MS),
+    ("msecs", UNIT_MS),
+    ("millisecond", UNIT_MS),
+    ("milliseconds", UNIT_MS),
+];
+
+// CSS-wide keywords
+/// The only CSS-wide keyword USS supports, resets a property to its default value
+pub const KEYWORD_INITIAL: &str = "initial";
+/// CSS-wide keywords that USS doesn't support, only `initial` is
+pub const UNSUPPORTED_CSS_WIDE_KEYWORDS: &[&str] = &["inherit", "unset", "revert", "revert-layer"];
@@ -0,0 +1,93 @@
+//! Cursor Values
+//!
+//! `cursor` takes either a keyword, e.g. `pointer`, or a custom cursor: a texture in `url()` or `resource()`
+//! with an optional hotspot, the point of the texture that is the position of the pointer, in pixels from its
+//! top left corner. A value that doesn't match only says that it doesn't match the format, so the mistakes
+//! users make with custom cursors are reported here with what is wrong instead.
+
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::language::tree_utils::{byte_to_position, node_to_range};
+use crate::uss::value::UssValue;
+
+/// Name of the property
+pub const CURSOR_PROPERTY: &str = "cursor";
+
+/// Diagnostics of a custom cursor value, empty if it has no mistakes reported here
+///
+/// Values with variables aren't checked, what the variables are is only known where the style is used.
+pub fn value_diagnostics(values: &[UssValue], value_nodes: &[Node], content: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    if values.len() != value_nodes.len() || values.iter().any(|value| matches!(value, UssValue::VariableReference(_))) {
+        return diagnostics;
+    }
+    let Some(asset_index) = values.iter().position(|value| matches!(value, UssValue::Url(_) | UssValue::Resource(_)))
+    else {
+        return diagnostics;
+    };
+
+    for (index, value) in values.iter().enumerate() {
+        if let UssValue::Identifier(keyword) = value {
+            diagnostics.push(cursor_diagnostic(
+                node_to_range(value_nodes[index], content),
+                "cursor-keyword-with-asset",
+                format!(
+                    "A cursor is either a keyword or a custom cursor texture, '{}' can't be used with a texture",
+                    keyword
+                ),
+            ));
+        }
+    }
+    if !diagnostics.is_empty() || asset_index != 0 {
+        return diagnostics;
+    }
+
+    let hotspot = &value_nodes[1..];
+    if hotspot.len() == 1 || hotspot.len() > 2 {
+        let start = byte_to_position(hotspot[0].start_byte(), content);
+        let end = byte_to_position(hotspot[hotspot.len() - 1].end_byte(), content);
+        diagnostics.push(cursor_diagnostic(
+            Range { start, end },
+            "cursor-hotspot",
+            format!(
+                "The hotspot of a cursor is 2 numbers, its x and y in pixels, e.g. '0 0', but {} found",
+                if hotspot.len() == 1 { "1 is".to_string() } else { format!("{} are", hotspot.len()) }
+            ),
+        ));
+        return diagnostics;
+    }
+
+    for (value, node) in values[1..].iter().zip(hotspot) {
+        let UssValue::Numeric { value, unit, has_fractional } = value else {
+            continue;
+        };
+        let problem = if let Some(unit) = unit {
+            format!("has unit '{}', it is in pixels and written without unit", unit)
+        } else if *has_fractional {
+            "must be a whole number of pixels".to_string()
+        } else if *value < 0.0 {
+            "can't be negative, it is measured from the top left corner of the texture".to_string()
+        } else {
+            continue;
+        };
+        let text = node.utf8_text(content.as_bytes()).unwrap_or_default();
+        diagnostics.push(cursor_diagnostic(
+            node_to_range(*node, content),
+            "cursor-hotspot",
+            format!("Cursor hotspot coordinate '{}' {}", text, problem),
+        ));
+    }
+    diagnostics
+}
+
+fn cursor_diagnostic(range: Range, code: &str, message: String) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::ERROR),
+        code: Some(NumberOrString::String(code.to_string())),
+        source: Some("uss".to_string()),
+        message,
+        ..Default::default()
+    }
+}
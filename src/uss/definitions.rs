@@ -4,17 +4,20 @@
 //! and other validation data that can be shared across different features
 //! like diagnostics and autocomplete.
 
+use crate::language::asset_filter::{AssetFilter, CURSOR_ASSETS, FONT_ASSETS, IMAGE_ASSETS};
+use crate::unity_project_manager::UnityVersion;
 use crate::uss::color::Color;
 use crate::uss::color_keywords::create_color_keywords;
 use crate::uss::constants::*;
 use crate::uss::function_data::create_function_info;
 use crate::uss::keyword_data::create_keyword_info;
-use crate::uss::property_data::create_standard_properties;
+use crate::uss::property_data::{create_standard_properties, INACCURATE_FORMATS};
 use crate::uss::pseudo_class_data::create_pseudo_class_info;
 use crate::uss::unit_data::create_unit_info;
 use crate::uss::value_spec::{ValueSpec, ValueType};
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
-use std::sync::OnceLock;
+use std::sync::{LazyLock, OnceLock};
 
 /// Information about a USS keyword
 #[derive(Debug, Clone)]
@@ -77,15 +80,32 @@ impl KeywordInfo {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
 pub enum PropertyAnimation {
+    #[default]
     None,
     Animatable,
     Discrete,
 }
 
+/// Whether a property can be relied on
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum PropertyStatus {
+    #[default]
+    Stable,
+    /// Still read by Unity, but superseded by `replacement`
+    Deprecated {
+        /// Property to use instead, `None` if there is none
+        replacement: Option<&'static str>,
+        /// Keywords of this property and the values of the replacement that do the same
+        value_replacements: &'static [(&'static str, &'static str)],
+    },
+    /// May change or be removed in later Unity versions
+    Experimental,
+}
+
 /// Property documentation information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct PropertyInfo {
     /// Property name
     pub name: &'static str,
@@ -112,12 +132,74 @@ pub struct PropertyInfo {
     pub inherited: bool,
     /// Whether this property is animatable
     pub animatable: PropertyAnimation,
+    /// First Unity version that supports this property, `None` if supported by all versions we know of
+    pub min_unity_version: Option<UnityVersion>,
+    /// Last Unity version that supports this property, `None` if it is still supported
+    pub max_unity_version: Option<UnityVersion>,
+    /// Whether this property is deprecated or experimental
+    pub status: PropertyStatus,
     /// Complete value specification for this property
     /// Note: All properties support initial keyword to reset to default, we don't put initial in here for brevity
     pub value_spec: ValueSpec,
 }
 
 impl PropertyInfo {
+    /// Whether this property is supported in the given Unity version
+    pub fn is_supported_in(&self, version: UnityVersion) -> bool {
+        self.min_unity_version.is_none_or(|min| version >= min)
+            && self.max_unity_version.is_none_or(|max| version <= max)
+    }
+
+    /// Human readable description of the Unity versions supporting this property, `None` if all versions do
+    pub fn supported_versions_text(&self) -> Option<String> {
+        match (self.min_unity_version, self.max_unity_version) {
+            (Some(min), Some(max)) => Some(format!("Unity {} to {}", min, max)),
+            (Some(min), None) => Some(format!("Unity {} or newer", min)),
+            (None, Some(max)) => Some(format!("Unity {} or older", max)),
+            (None, None) => None,
+        }
+    }
+
+    /// Human readable description of the status of this property, `None` if it is stable
+    pub fn status_text(&self) -> Option<String> {
+        match self.status {
+            PropertyStatus::Stable => None,
+            PropertyStatus::Deprecated { replacement: Some(replacement), .. } => {
+                Some(format!("Deprecated, use `{}` instead", replacement))
+            }
+            PropertyStatus::Deprecated { replacement: None, .. } => Some("Deprecated".to_string()),
+            PropertyStatus::Experimental => Some("Experimental, it may change in later Unity versions".to_string()),
+        }
+    }
+
+    /// The declaration that replaces a declaration of this deprecated property with the given value text
+    pub fn replacement_declaration(&self, value: &str) -> Option<String> {
+        let PropertyStatus::Deprecated { replacement: Some(replacement), value_replacements } = self.status else {
+            return None;
+        };
+        let value = value_replacements
+            .iter()
+            .find(|(keyword, _)| *keyword == value)
+            .map_or(value, |(_, replaced)| *replaced);
+        Some(format!("{}: {}", replacement, value))
+    }
+
+    /// Whether the documented format describes what the property accepts, see [`INACCURATE_FORMATS`]
+    pub fn is_format_accurate(&self) -> bool {
+        !INACCURATE_FORMATS.iter().any(|(name, _)| *name == self.name)
+    }
+
+    /// Syntax of the values the property accepts
+    ///
+    /// The documented format, or the syntax of the value spec if the format is inaccurate.
+    pub fn value_syntax(&self) -> Cow<'static, str> {
+        if self.is_format_accurate() {
+            Cow::Borrowed(self.format)
+        } else {
+            Cow::Owned(self.value_spec.syntax())
+        }
+    }
+
     /// Create full markdown documentation with version-specific URL and property characteristics
     pub fn create_documentation(&self, property_name: &str, unity_version: &str) -> String {
         let doc_url = self.documentation_url.replace("{version}", unity_version);
@@ -150,8 +232,19 @@ impl PropertyInfo {
 
         content.push_str(&format!("\n\n*{}*", characteristics.join(", ")));
 
+        if let Some(versions) = self.supported_versions_text() {
+            content.push_str(&format!("\n\n**Supported in:** {}", versions));
+        }
+
+        if let Some(status) = self.status_text() {
+            content.push_str(&format!("\n\n**Status:** {}", status));
+        }
+
         // Add format specification
         content.push_str(&format!("\n\n**Format:** `{}`", self.format));
+        if !self.is_format_accurate() {
+            content.push_str(&format!("\n\n**Accepted values:** `{}`", self.value_syntax()));
+        }
 
         // Add documentation link
         content.push_str(&format!("\n\n[📖 Documentation]({})", doc_url));
@@ -241,6 +334,14 @@ pub struct PseudoClassInfo {
     pub description: &'static str,
     /// Documentation URL (may contain {version} placeholder for Unity docs)
     pub documentation_url: String,
+    /// URL of the docs about chaining pseudo-classes (may contain {version} placeholder)
+    pub chaining_url: String,
+    /// UXML of an element the pseudo-class applies to
+    pub example_uxml: &'static str,
+    /// USS rule that styles the element of [`Self::example_uxml`] in this state
+    pub example_uss: &'static str,
+    /// The other pseudo-classes, with whether an element can be in their state and this one at the same time
+    pub combinations: Vec<(&'static str, bool)>,
 }
 
 impl PseudoClassInfo {
@@ -252,8 +353,35 @@ impl PseudoClassInfo {
             self.name, self.description, doc_url
         )
     }
+
+    /// Create markdown documentation for hover, with an example and which pseudo-classes can be chained with this one
+    pub fn create_hover_documentation(&self, unity_version: &str) -> String {
+        let mut content = format!("### Pseudo Class :{}\n{}", self.name, self.description);
+
+        content.push_str("\n\n**Example:**\n```xml\n");
+        content.push_str(self.example_uxml);
+        content.push_str("\n```\n```css\n");
+        content.push_str(self.example_uss);
+        content.push_str("\n```");
+
+        content.push_str(&format!(
+            "\n\n**[Chained]({}) with other pseudo-classes:**\n\n| Selector | Can match |\n| --- | --- |\n",
+            self.chaining_url.replace("{version}", unity_version)
+        ));
+        for (other, possible) in &self.combinations {
+            let can_match = if *possible { "yes" } else { "never" };
+            content.push_str(&format!("| `:{}:{}` | {} |\n", self.name, other, can_match));
+        }
+
+        let doc_url = self.documentation_url.replace("{version}", unity_version);
+        content.push_str(&format!("\n[Documentation]({})", doc_url));
+        content
+    }
 }
 
+/// The definitions shared by the process, see [`UssDefinitions::shared`]
+static SHARED_DEFINITIONS: LazyLock<UssDefinitions> = LazyLock::new(UssDefinitions::new);
+
 /// USS language definitions and validation data
 #[derive(Clone, Debug)]
 pub struct UssDefinitions {
@@ -335,6 +463,14 @@ impl UssDefinitions {
         }
     }
 
+    /// The definitions shared by all features of the process
+    ///
+    /// The definitions never change, and the property and keyword tables allocate a lot, so features use
+    /// these instead of creating their own. The tables are still only created when first needed.
+    pub fn shared() -> &'static Self {
+        &SHARED_DEFINITIONS
+    }
+
     /// Get properties (lazy-loaded)
     fn get_properties(&self) -> &HashMap<&'static str, PropertyInfo> {
         self.properties.get_or_init(|| {
@@ -406,6 +542,19 @@ impl UssDefinitions {
         self.get_properties().get(property_name)
     }
 
+    /// Whether a property is deprecated in a Unity version
+    ///
+    /// A property whose replacement isn't supported in the version yet is not deprecated there.
+    pub fn is_property_deprecated(&self, property_name: &str, version: Option<UnityVersion>) -> bool {
+        let Some(PropertyStatus::Deprecated { replacement, .. }) = self.get_property_info(property_name).map(|info| info.status) else {
+            return false;
+        };
+        match (version, replacement.and_then(|replacement| self.get_property_info(replacement))) {
+            (Some(version), Some(replacement)) => replacement.is_supported_in(version),
+            _ => true,
+        }
+    }
+
     /// Get all properties with their information
     pub fn get_all_properties(&self) -> &HashMap<&'static str, PropertyInfo> {
         self.get_properties()
@@ -416,6 +565,24 @@ impl UssDefinitions {
         self.get_keywords().get(keyword_name)
     }
 
+    /// Documentation of a keyword as a value of a property
+    ///
+    /// What the keyword means for the property, followed by the property it sets and the values the property
+    /// accepts. `None` if the keyword isn't known.
+    pub fn keyword_documentation(&self, keyword: &str, property_name: &str) -> Option<String> {
+        let mut content = self.get_keyword_info(keyword)?.create_documentation(Some(property_name));
+        let Some(property_info) = self.get_property_info(property_name) else {
+            return Some(content);
+        };
+
+        if !property_info.value_spec.accepts_keyword(keyword) {
+            content.push_str(&format!("\n\n*`{}` is not a value of `{}`*", keyword, property_name));
+        }
+        content.push_str(&format!("\n\n**Property `{}`:** {}", property_name, property_info.description));
+        content.push_str(&format!("\n\n**Accepted values:** `{}`", property_info.value_syntax()));
+        Some(content)
+    }
+
     /// Get all keywords with their information
     pub fn get_all_keywords(&self) -> &HashMap<&'static str, KeywordInfo> {
         self.get_keywords()
@@ -436,6 +603,16 @@ impl UssDefinitions {
         self.functions.get(function_name)
     }
 
+    /// Types of assets a property accepts in `url()` and `resource()`, `None` if it doesn't take assets
+    pub fn get_asset_filter(&self, property_name: &str) -> Option<&'static AssetFilter> {
+        match property_name {
+            "background-image" => Some(&IMAGE_ASSETS),
+            "cursor" => Some(&CURSOR_ASSETS),
+            "-unity-font" | "-unity-font-definition" => Some(&FONT_ASSETS),
+            _ => None,
+        }
+    }
+
     /// Get simple completions strings for property, just keywords, colors or other simple values that will work for the property
     ///
     /// Note: no spaces, no commas, just a single value
@@ -450,36 +627,48 @@ impl UssDefinitions {
 
         let (_, property_info) = properties.get_key_value(property).unwrap();
 
-        let mut set: HashSet<&'static str> = HashSet::new();
         // see if a single value entry would work
-        for format in &property_info.value_spec.formats {
-            if format.entries.len() == 1 {
-                let entry = &format.entries[0];
-                for option in &entry.options {
-                    match option {
-                        ValueType::Color => {
-                            for (color, _) in &self.valid_color_keywords {
-                                set.insert(color);
-                            }
-                        }
-                        ValueType::Keyword(keyword) => {
-                            set.insert(keyword);
-                        }
-                        ValueType::PropertyName => {
-                            // we assume it is for an animation property here
-                            // this is our only use case now
-                            for (p, p_i) in properties {
-                                if p_i.animatable != PropertyAnimation::None {
-                                    set.insert(p);
-                                }
-                            }
+        let options: Vec<ValueType> = property_info
+            .value_spec
+            .formats
+            .iter()
+            .filter(|format| format.entries.len() == 1)
+            .flat_map(|format| format.entries[0].options.iter().copied())
+            .collect();
+        self.get_simple_completions_for_types(&options)
+    }
+
+    /// Keywords, color keywords and property names that are values of the given types, without duplicates
+    ///
+    /// Types that aren't identifiers, like lengths, have no completions.
+    pub fn get_simple_completions_for_types(&self, types: &[ValueType]) -> Vec<&'static str> {
+        let mut set: HashSet<&'static str> = HashSet::new();
+        for option in types {
+            match option {
+                ValueType::Color => {
+                    for (color, _) in &self.valid_color_keywords {
+                        set.insert(color);
+                    }
+                }
+                ValueType::Keyword(keyword) => {
+                    set.insert(keyword);
+                }
+                ValueType::PropertyName => {
+                    // we assume it is for an animation property here
+                    // this is our only use case now
+                    for (p, p_i) in self.get_properties() {
+                        if p_i.animatable != PropertyAnimation::None {
+                            set.insert(p);
                         }
-                        _ => {}
                     }
                 }
+                _ => {}
             }
         }
 
+        // CSS-wide keywords other than initial are not valid in USS, never suggest them
+        set.retain(|value| !UNSUPPORTED_CSS_WIDE_KEYWORDS.contains(value));
+
         let r: Vec<&'static str> = set.into_iter().collect();
         r
     }
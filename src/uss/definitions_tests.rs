@@ -1,5 +1,5 @@
 use crate::test_utils::get_project_root;
-use crate::uss::definitions::{PropertyAnimation, UssDefinitions};
+use crate::uss::definitions::{PropertyAnimation, PropertyStatus, UssDefinitions};
 use scraper::{Html, Selector};
 use std::fs;
 
@@ -36,6 +36,41 @@ fn test_property_exists() {
     );
 }
 
+#[test]
+fn test_property_status() {
+    let definitions = UssDefinitions::new();
+
+    let scale_mode = definitions.get_property_info("-unity-background-scale-mode").unwrap();
+    assert_eq!(scale_mode.status_text().unwrap(), "Deprecated, use `background-size` instead");
+    assert_eq!(scale_mode.replacement_declaration("stretch-to-fill").unwrap(), "background-size: 100% 100%");
+    assert_eq!(scale_mode.replacement_declaration("var(--mode)").unwrap(), "background-size: var(--mode)");
+    assert!(definitions.is_property_deprecated("-unity-background-scale-mode", None));
+
+    let generator = definitions.get_property_info("-unity-text-generator").unwrap();
+    assert_eq!(generator.status, PropertyStatus::Experimental);
+    assert!(generator.create_documentation("-unity-text-generator", "6000.0").contains("**Status:** Experimental"));
+    assert!(!definitions.is_property_deprecated("-unity-text-generator", None));
+    assert!(definitions.get_property_info("color").unwrap().status_text().is_none());
+}
+
+#[test]
+fn test_value_syntax() {
+    let definitions = UssDefinitions::new();
+
+    let padding = definitions.get_property_info("padding").unwrap();
+    assert!(padding.is_format_accurate());
+    assert_eq!(padding.value_syntax(), padding.format);
+    assert!(!padding.create_documentation("padding", "6000.0").contains("**Accepted values:**"));
+
+    // the documented format says <number>
+    let font_size = definitions.get_property_info("font-size").unwrap();
+    assert!(!font_size.is_format_accurate());
+    assert_eq!(font_size.value_syntax(), "<length-percentage> | initial");
+    assert!(font_size
+        .create_documentation("font-size", "6000.0")
+        .contains("**Accepted values:** `<length-percentage> | initial`"));
+}
+
 #[test]
 fn test_property_inheritance() {
     let definitions = UssDefinitions::new();
@@ -862,3 +897,15 @@ fn test_property_specific_documentation() {
     
     println!("\n✓ All expected keywords have proper property-specific documentation");
 }
+
+#[test]
+fn test_completions_exclude_unsupported_css_wide_keywords() {
+    let definitions = UssDefinitions::new();
+    for property in ["color", "display", "all", "transition-property"] {
+        let completions = definitions.get_simple_completions_for_property(property);
+        assert!(completions.contains(&"initial"), "{}", property);
+        for keyword in ["inherit", "unset", "revert", "revert-layer"] {
+            assert!(!completions.contains(&keyword), "{} suggests {}", property, keyword);
+        }
+    }
+}
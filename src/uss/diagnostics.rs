@@ -3,35 +3,81 @@
 //! Provides validation and error reporting for USS files.
 //! Validates syntax, properties, values, and USS-specific rules.
 
-use crate::language::asset_url::validate_url;
+use crate::language::asset_filter::file_extension;
+use crate::language::asset_url::{project_url_to_path, validate_url};
+use crate::language::spelling::closest_match;
+use crate::language::workspace_edit::{document_edit, WorkspaceEditBuilder};
 use crate::language::tree_utils::{byte_to_position, node_to_range};
 use crate::uss::constants::*;
-use crate::uss::definitions::UssDefinitions;
+use crate::uss::cursor::{self, CURSOR_PROPERTY};
+use crate::uss::transition::{self, TRANSITION_PROPERTY};
+use crate::uss::definitions::{PropertyStatus, UssDefinitions};
+use crate::uss::function_node::FunctionNode;
 use crate::uss::import_node::ImportNode;
+use crate::uss::settings::DiagnosticSettings;
+use crate::uss::unity_compatibility::{compatibility_diagnostics, unitless_length_diagnostics, ValidationMode};
 use crate::language::tree_printer;
+use crate::unity_asset_database::AssetPathResolver;
+use crate::unity_project_manager::{UnityVersion, DEFAULT_DOCS_VERSION};
 use crate::uss::url_function_node::{UrlFunctionNode, UrlReference};
+use crate::uss::url_references::UrlReferenceKind;
+use crate::uss::uss_utils::line_aware_range;
 use crate::uss::value::UssValue;
-use crate::uss::value_spec::ValueSpec;
+use crate::uss::value_spec::{ValueSpec, ValueType};
 use crate::uss::variable_resolver::{VariableResolver, VariableStatus};
 use tower_lsp::lsp_types::*;
 use tree_sitter::{Node, Tree};
 use url::Url;
 use std::collections::{HashSet, HashMap};
 
+/// Code of an `@import` after a rule, see [`move_import_action`]
+pub const IMPORT_AFTER_RULE_CODE: &str = "import-after-rule";
+
+/// Most keywords or value types listed in a message, the rest are counted
+const MAX_LISTED_VALUES: usize = 8;
+
+/// Key of the suggested keyword in the data of a misspelled keyword diagnostic
+const KEYWORD_SUGGESTION_KEY: &str = "suggestion";
+
+/// Key of the edit that replaces a deprecated property in the data of its diagnostic
+const PROPERTY_REPLACEMENT_KEY: &str = "replacement";
+
 /// USS diagnostic analyzer
+#[derive(Clone)]
 pub struct UssDiagnostics {
     /// USS language definitions
-    definitions: UssDefinitions,
+    definitions: &'static UssDefinitions,
+    /// User settings, eg. severity overrides
+    settings: DiagnosticSettings,
+    /// Unity version of the project, used to flag properties it doesn't support
+    unity_version: Option<UnityVersion>,
 }
 
 impl UssDiagnostics {
     /// Create a new diagnostics analyzer
     pub fn new() -> Self {
         Self {
-            definitions: UssDefinitions::new(),
+            definitions: UssDefinitions::shared(),
+            settings: DiagnosticSettings::default(),
+            unity_version: None,
         }
     }
 
+    /// Current diagnostic settings
+    pub fn settings(&self) -> &DiagnosticSettings {
+        &self.settings
+    }
+
+    /// Update diagnostic settings, applies to the diagnostics of subsequent analysis
+    pub fn set_settings(&mut self, settings: DiagnosticSettings) {
+        self.settings = settings;
+    }
+
+    /// Set the Unity version of the project, `None` if unknown (no version checks are done)
+    pub fn set_unity_version(&mut self, unity_version: Option<UnityVersion>) {
+        self.unity_version = unity_version;
+    }
+
     /// Analyze USS syntax tree and generate diagnostics
     pub fn analyze(&self, tree: &Tree, content: &str) -> Vec<Diagnostic> {
         self.analyze_with_source_url(tree, content, None)
@@ -107,6 +153,11 @@ impl UssDiagnostics {
             &mut diagnostics,
             &mut url_references,
         );
+        if self.settings.mode == ValidationMode::StrictUnity {
+            diagnostics.extend(compatibility_diagnostics(tree, content));
+        }
+
+        self.settings.apply(&mut diagnostics);
 
         (diagnostics, url_references)
     }
@@ -177,12 +228,33 @@ impl UssDiagnostics {
                         variable_resolver,
                     );
                 }
+
+                // reported after the value is validated so the warning doesn't suppress value validation
+                for i in 0..node.child_count() {
+                    if let Some(child) = node.child(i).filter(|c| c.kind() == NODE_IMPORTANT) {
+                        self.validate_unsupported_feature(child, content, diagnostics);
+                    }
+                }
             }
             NODE_CALL_EXPRESSION => {
                 self.validate_function_call(node, content, diagnostics, source_url, url_references)
             }
             NODE_PSEUDO_CLASS_SELECTOR => self.validate_pseudo_class(node, content, diagnostics),
-            NODE_TAG_NAME => self.validate_tag_selector(node, content, diagnostics, uxml_class_names),
+            NODE_TAG_NAME => {
+                // tag names inside pseudo-element and namespace selectors are not element types,
+                // the unsupported selector itself is already reported
+                let in_unsupported_selector = node.parent().is_some_and(|p| {
+                    p.kind() == NODE_PSEUDO_ELEMENT_SELECTOR || p.kind() == NODE_NAMESPACE_SELECTOR
+                });
+                if !in_unsupported_selector {
+                    self.validate_tag_selector(node, content, diagnostics, uxml_class_names)
+                }
+            }
+            NODE_ATTRIBUTE_SELECTOR
+            | NODE_ADJACENT_SIBLING_SELECTOR
+            | NODE_SIBLING_SELECTOR
+            | NODE_PSEUDO_ELEMENT_SELECTOR
+            | NODE_NAMESPACE_SELECTOR => self.validate_unsupported_feature(node, content, diagnostics),
             NODE_AT_RULE
             | NODE_CHARSET_STATEMENT
             | NODE_IMPORT_STATEMENT
@@ -476,6 +548,9 @@ impl UssDiagnostics {
                     return; // Don't validate values for unknown properties
                 }
 
+                self.check_property_unity_version(property_node, property_name, content, diagnostics);
+                self.check_property_deprecation(node, property_node, property_name, content, diagnostics);
+
                 // Parse values into UssValue objects first
                 let mut uss_values = Vec::new();
                 let mut parsing_failed = false;
@@ -517,7 +592,11 @@ impl UssDiagnostics {
                     if let Some(child) = node.child(i) {
                         if child.kind() == NODE_COMMA {
                             has_commas = true;
-                        } else if child.kind() != NODE_SEMICOLON && child.kind() != NODE_COMMENT && !child.kind().is_empty() {
+                        } else if child.kind() != NODE_SEMICOLON
+                            && child.kind() != NODE_COMMENT
+                            && child.kind() != NODE_IMPORTANT // reported separately
+                            && !child.kind().is_empty()
+                        {
                             value_nodes.push(child);
                         }
                     }
@@ -530,14 +609,20 @@ impl UssDiagnostics {
                         Ok(value) => uss_values.push(value),
                         Err(error) => {
                             // Report parsing error and stop
-                            let range = node_to_range(*child, content);
+                            let range = Range {
+                                start: byte_to_position(error.byte_range.0, content),
+                                end: byte_to_position(error.byte_range.1, content),
+                            };
 
                             diagnostics.push(Diagnostic {
                                 range,
                                 severity: Some(error.severity),
-                                code: Some(NumberOrString::String("invalid-value".to_string())),
+                                code: Some(NumberOrString::String(error.code.to_string())),
                                 source: Some("uss".to_string()),
                                 message: format!("Invalid value: {}", error.message),
+                                data: error
+                                    .suggestion
+                                    .map(|suggestion| serde_json::json!({ KEYWORD_SUGGESTION_KEY: suggestion })),
                                 ..Default::default()
                             });
 
@@ -655,6 +740,91 @@ impl UssDiagnostics {
         }
     }
 
+    /// Report a property that is not supported in the project's Unity version
+    fn check_property_unity_version(
+        &self,
+        property_node: Node,
+        property_name: &str,
+        content: &str,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let Some(version) = self.unity_version else {
+            return;
+        };
+        let Some(property_info) = self.definitions.get_property_info(property_name) else {
+            return;
+        };
+
+        if !property_info.is_supported_in(version) {
+            let supported = property_info.supported_versions_text().unwrap_or_default();
+            diagnostics.push(Diagnostic {
+                range: node_to_range(property_node, content),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String("unsupported-unity-version".to_string())),
+                source: Some("uss".to_string()),
+                message: format!(
+                    "Property '{}' is not supported in Unity {}, it requires {}",
+                    property_name, version, supported
+                ),
+                ..Default::default()
+            });
+        }
+    }
+
+    /// Report a deprecated property, with the edit that replaces it if there is a replacement
+    ///
+    /// Not reported if the project's Unity version doesn't support the replacement yet.
+    fn check_property_deprecation(
+        &self,
+        declaration_node: Node,
+        property_node: Node,
+        property_name: &str,
+        content: &str,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        if !self.definitions.is_property_deprecated(property_name, self.unity_version) {
+            return;
+        }
+        let Some(property_info) = self.definitions.get_property_info(property_name) else {
+            return;
+        };
+        let PropertyStatus::Deprecated { replacement, .. } = property_info.status else {
+            return;
+        };
+
+        let children: Vec<Node> = (0..declaration_node.child_count()).filter_map(|i| declaration_node.child(i)).collect();
+        let colon = children.iter().find(|child| child.kind() == NODE_COLON);
+        let value_end = children.iter().rev().find(|child| child.kind() != NODE_SEMICOLON && child.kind() != NODE_COMMENT);
+        let edit = match (colon, value_end) {
+            (Some(colon), Some(value_end)) if value_end.end_byte() > colon.end_byte() => {
+                let value = content[colon.end_byte()..value_end.end_byte()].trim();
+                property_info.replacement_declaration(value).map(|new_text| TextEdit {
+                    range: Range::new(
+                        node_to_range(property_node, content).start,
+                        node_to_range(*value_end, content).end,
+                    ),
+                    new_text,
+                })
+            }
+            _ => None,
+        };
+
+        let message = match replacement {
+            Some(replacement) => format!("Property '{}' is deprecated, use '{}' instead", property_name, replacement),
+            None => format!("Property '{}' is deprecated", property_name),
+        };
+        diagnostics.push(Diagnostic {
+            range: node_to_range(property_node, content),
+            severity: Some(DiagnosticSeverity::HINT),
+            code: Some(NumberOrString::String("deprecated-property".to_string())),
+            source: Some("uss".to_string()),
+            message,
+            tags: Some(vec![DiagnosticTag::DEPRECATED]),
+            data: edit.map(|edit| serde_json::json!({ PROPERTY_REPLACEMENT_KEY: edit })),
+            ..Default::default()
+        });
+    }
+
     /// Validate comma-separated values for properties that support multiple values.
     /// 
     /// This method splits the values into segments based on comma positions and validates
@@ -736,7 +906,10 @@ impl UssDiagnostics {
                         segments.push((current_start, value_index));
                     }
                     current_start = value_index;
-                } else if child.kind() != NODE_SEMICOLON && !child.kind().is_empty() {
+                } else if child.kind() != NODE_SEMICOLON
+                    && child.kind() != NODE_IMPORTANT
+                    && !child.kind().is_empty()
+                {
                     // This is a value node, increment the index
                     value_index += 1;
                 }
@@ -792,6 +965,62 @@ impl UssDiagnostics {
             node_to_range(node, content)
         };
         
+        let property_diagnostics = match property_name {
+            CURSOR_PROPERTY => cursor::value_diagnostics(uss_values, value_nodes, content),
+            TRANSITION_PROPERTY => {
+                let docs_url = self.property_docs_url(property_name);
+                transition::segment_diagnostics(uss_values, value_nodes, self.definitions, docs_url.as_ref(), content)
+            }
+            _ => Vec::new(),
+        };
+        let has_errors = property_diagnostics.iter().any(|d| d.severity == Some(DiagnosticSeverity::ERROR));
+        diagnostics.extend(property_diagnostics.iter().cloned());
+        if has_errors {
+            return;
+        }
+
+        // a transition of a property that isn't animatable is only a warning, the rest of the value is still
+        // validated, with the property checked as `all`
+        let warned = |node: &Node| property_diagnostics.iter().any(|d| d.range == node_to_range(*node, content));
+        let checked_values: Vec<UssValue> = uss_values
+            .iter()
+            .zip(value_nodes)
+            .map(|(value, node)| match value {
+                UssValue::Identifier(_) if property_name == TRANSITION_PROPERTY && warned(node) => {
+                    UssValue::Identifier(transition::ALL_PROPERTIES.to_string())
+                }
+                _ => value.clone(),
+            })
+            .collect();
+        self.validate_value_format(
+            &checked_values,
+            uss_values,
+            value_nodes,
+            property_name,
+            value_spec,
+            content,
+            values_range,
+            diagnostics,
+            variable_resolver,
+        );
+    }
+
+    /// Validate values against the formats of the property, and the values of their variables
+    ///
+    /// `written_values` are the values as written, for messages, `uss_values` may have values replaced
+    /// that were already reported.
+    fn validate_value_format(
+        &self,
+        uss_values: &[UssValue],
+        written_values: &[UssValue],
+        value_nodes: &[Node],
+        property_name: &str,
+        value_spec: &ValueSpec,
+        content: &str,
+        values_range: Range,
+        diagnostics: &mut Vec<Diagnostic>,
+        variable_resolver: Option<&VariableResolver>,
+    ) {
         // Validate the values
         let mut format_matches = false;
         for value_format in &value_spec.formats {
@@ -802,23 +1031,74 @@ impl UssDiagnostics {
         }
         
         if !format_matches {
-            let values_str = uss_values
+            if self.settings.mode == ValidationMode::StrictUnity {
+                if let Some(warnings) =
+                    unitless_length_diagnostics(uss_values, value_nodes, value_spec, self.definitions, content)
+                {
+                    diagnostics.extend(warnings);
+                    return;
+                }
+            }
+
+            if let Some(diagnostic) = css_wide_keyword_diagnostic(uss_values, value_nodes, content) {
+                diagnostics.push(diagnostic);
+                return;
+            }
+
+            if let Some(diagnostic) =
+                self.misspelled_keyword_diagnostic(uss_values, value_nodes, property_name, value_spec, content)
+            {
+                diagnostics.push(diagnostic);
+                return;
+            }
+
+            let values_str = written_values
                 .iter()
                 .map(|v| v.to_string())
                 .collect::<Vec<_>>()
                 .join(" ");
-            
+            let mut message = format!(
+                "Property '{}' value '{}' does not match expected format",
+                property_name, values_str
+            );
+
+            // point at the first value that doesn't fit, with what fits there
+            let mut range = values_range;
+            let mismatch = value_spec
+                .first_mismatch(uss_values, self.definitions)
+                .and_then(|mismatch| Some((value_nodes.get(mismatch.index).copied()?, mismatch)));
+            if let Some((value_node, mismatch)) = mismatch {
+                if let Some(diagnostic) =
+                    unit_kind_diagnostic(&uss_values[mismatch.index], &mismatch.expected, value_node, content)
+                {
+                    diagnostics.push(diagnostic);
+                    return;
+                }
+                range = node_to_range(value_node, content);
+                let text = value_node.utf8_text(content.as_bytes()).unwrap_or_default();
+                if mismatch.expected.is_empty() {
+                    message.push_str(&format!(", value {} '{}' is one too many", mismatch.index + 1, text));
+                } else {
+                    let expected: Vec<String> = mismatch.expected.iter().map(|t| t.to_string()).collect();
+                    message.push_str(&format!(
+                        ", value {} '{}' should be one of: {}",
+                        mismatch.index + 1,
+                        text,
+                        list_limited(&expected)
+                    ));
+                }
+            } else if let Some(property_info) = self.definitions.get_property_info(property_name) {
+                message.push_str(&format!(", expected: {}", property_info.value_syntax()));
+            }
+
             diagnostics.push(Diagnostic {
-                range: values_range,
+                range,
                 severity: Some(DiagnosticSeverity::ERROR),
                 code: Some(NumberOrString::String(
                     "invalid-property-value".to_string(),
                 )),
                 source: Some("uss".to_string()),
-                message: format!(
-                    "Property '{}' value '{}' does not match expected format",
-                    property_name, values_str
-                ),
+                message,
                 ..Default::default()
             });
         } else if let Some(resolver) = variable_resolver {
@@ -884,6 +1164,60 @@ impl UssDiagnostics {
         }
     }
 
+    /// Documentation of a property for the project's Unity version, [`DEFAULT_DOCS_VERSION`] if it isn't known
+    fn property_docs_url(&self, property_name: &str) -> Option<Url> {
+        let version = self.unity_version.map_or_else(|| DEFAULT_DOCS_VERSION.to_string(), |version| version.to_string());
+        let info = self.definitions.get_property_info(property_name)?;
+        Url::parse(&info.documentation_url.replace("{version}", &version)).ok()
+    }
+
+    /// Diagnostic for an identifier where only keywords are accepted, listing the keywords
+    ///
+    /// If a keyword is close to the identifier it is suggested, with the replacement in the diagnostic data
+    /// for [`replace_keyword_action`].
+    fn misspelled_keyword_diagnostic(
+        &self,
+        uss_values: &[UssValue],
+        value_nodes: &[Node],
+        property_name: &str,
+        value_spec: &ValueSpec,
+        content: &str,
+    ) -> Option<Diagnostic> {
+        if uss_values.iter().any(|value| matches!(value, UssValue::VariableReference(_))) {
+            return None;
+        }
+
+        let (index, identifier, keywords) = uss_values.iter().enumerate().find_map(|(index, value)| {
+            let UssValue::Identifier(identifier) = value else {
+                return None;
+            };
+            let keywords = value_spec.keywords_at(index, uss_values.len())?;
+            (!keywords.contains(&identifier.as_str())).then_some((index, identifier, keywords))
+        })?;
+
+        let mut message = format!(
+            "Unknown keyword '{}' for property '{}', expected one of: {}",
+            identifier,
+            property_name,
+            list_limited(&keywords)
+        );
+
+        let suggestion = closest_match(identifier, &keywords);
+        if let Some(suggestion) = suggestion {
+            message.push_str(&format!(". Did you mean '{}'?", suggestion));
+        }
+
+        Some(Diagnostic {
+            range: node_to_range(*value_nodes.get(index)?, content),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::String("invalid-property-value".to_string())),
+            source: Some("uss".to_string()),
+            message,
+            data: suggestion.map(|suggestion| serde_json::json!({ KEYWORD_SUGGESTION_KEY: suggestion })),
+            ..Default::default()
+        })
+    }
+
     /// Resolve variables in a list of UssValues using the variable resolver
     fn resolve_variables_in_values(
         &self,
@@ -931,6 +1265,23 @@ impl UssDiagnostics {
         source_url: Option<&Url>,
         url_references: &mut Vec<UrlReference>,
     ) {
+        if let Some(function_node) = FunctionNode::from_node(node, content, None).filter(|f| f.is_function("resource")) {
+            // which Resources folder has the asset is checked with the other references
+            if let (Ok(UssValue::Resource(url)), [argument]) = (
+                UssValue::from_node(node, content, &self.definitions, source_url),
+                function_node.argument_nodes.as_slice(),
+            ) {
+                let name = url.path().trim_start_matches("/Assets/Resources/");
+                url_references.push(UrlReference {
+                    text: urlencoding::decode(name).map(|name| name.into_owned()).unwrap_or_else(|_| name.to_string()),
+                    url,
+                    range: node_to_range(*argument, content),
+                    kind: UrlReferenceKind::Resource,
+                });
+            }
+            return;
+        }
+
         // Try to parse as UrlFunctionNode - this handles function name checking and argument extraction
         if let Some(url_function_node) = UrlFunctionNode::from_node(
             node,
@@ -956,6 +1307,8 @@ impl UssDiagnostics {
                             ..Default::default()
                         });
                     }
+
+                    self.check_asset_type(node, &validation_result.url, arg_range, content, diagnostics);
                 }
                 Err(_) => {
                     // URL validation failed - UrlFunctionNode already added syntax diagnostics
@@ -964,6 +1317,49 @@ impl UssDiagnostics {
         }
     }
 
+    /// Warn when the file referenced by a `url()` is not a type of asset the property accepts,
+    /// e.g. a style sheet in `background-image`
+    ///
+    /// Files without an extension are not checked.
+    fn check_asset_type(&self, url_node: Node, url: &Url, range: Range, content: &str, diagnostics: &mut Vec<Diagnostic>) {
+        let mut declaration = url_node;
+        while declaration.kind() != NODE_DECLARATION {
+            let Some(parent) = declaration.parent() else {
+                return;
+            };
+            declaration = parent;
+        }
+        let Some(property_name) = declaration
+            .child(0)
+            .filter(|n| n.kind() == NODE_PROPERTY_NAME)
+            .and_then(|n| n.utf8_text(content.as_bytes()).ok())
+        else {
+            return;
+        };
+        let Some(filter) = self.definitions.get_asset_filter(property_name) else {
+            return;
+        };
+
+        let path = url.path();
+        if file_extension(path).is_none() || filter.accepts(path) {
+            return;
+        }
+        let file_name = path.rsplit('/').next().unwrap_or(path);
+        diagnostics.push(Diagnostic {
+            range,
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String("incompatible-asset-type".to_string())),
+            source: Some("uss".to_string()),
+            message: format!(
+                "Property '{}' expects a {}, '{}' is not one",
+                property_name,
+                filter.description,
+                urlencoding::decode(file_name).map(|name| name.into_owned()).unwrap_or_else(|_| file_name.to_string())
+            ),
+            ..Default::default()
+        });
+    }
+
     /// Validate pseudo-class selector
     fn validate_pseudo_class(&self, node: Node, content: &str, diagnostics: &mut Vec<Diagnostic>) {
         let node_text = node.utf8_text(content.as_bytes()).unwrap_or("");
@@ -990,6 +1386,25 @@ impl UssDiagnostics {
             }
         }
 
+        // functional pseudo-classes like `:nth-child(2)` end with an arguments node
+        if let Some(arguments_node) = node.child(node.child_count() - 1).filter(|n| n.kind() == NODE_ARGUMENTS) {
+            let name_node = arguments_node.prev_sibling().unwrap_or(arguments_node);
+            let name = name_node.utf8_text(content.as_bytes()).unwrap_or("");
+            let range = Range {
+                start: byte_to_position(name_node.start_byte(), content),
+                end: byte_to_position(arguments_node.end_byte(), content),
+            };
+            diagnostics.push(Diagnostic {
+                range,
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(NumberOrString::String("unsupported-css-feature".to_string())),
+                source: Some("uss".to_string()),
+                message: format!("Functional pseudo-class ':{}()' is not supported in USS", name),
+                ..Default::default()
+            });
+            return;
+        }
+
         // the last child is the class name node
         if let Some(class_name_node) = node.child(node.child_count() - 1) {
             if class_name_node.kind() == NODE_CLASS_NAME {
@@ -1010,6 +1425,60 @@ impl UssDiagnostics {
         }
     }
 
+    /// Report CSS features that tree-sitter-css accepts but USS doesn't support
+    fn validate_unsupported_feature(&self, node: Node, content: &str, diagnostics: &mut Vec<Diagnostic>) {
+        let (severity, message) = match node.kind() {
+            // Unity ignores the flag and still applies the declaration
+            NODE_IMPORTANT => (
+                DiagnosticSeverity::WARNING,
+                "!important is not supported in USS and will be ignored".to_string(),
+            ),
+            NODE_ATTRIBUTE_SELECTOR => (
+                DiagnosticSeverity::ERROR,
+                "Attribute selectors are not supported in USS".to_string(),
+            ),
+            NODE_ADJACENT_SIBLING_SELECTOR => (
+                DiagnosticSeverity::ERROR,
+                "Adjacent sibling combinator '+' is not supported in USS. Only descendant (' ') and child ('>') combinators are supported".to_string(),
+            ),
+            NODE_SIBLING_SELECTOR => (
+                DiagnosticSeverity::ERROR,
+                "General sibling combinator '~' is not supported in USS. Only descendant (' ') and child ('>') combinators are supported".to_string(),
+            ),
+            NODE_PSEUDO_ELEMENT_SELECTOR => (
+                DiagnosticSeverity::ERROR,
+                "Pseudo-elements (e.g. '::before') are not supported in USS".to_string(),
+            ),
+            NODE_NAMESPACE_SELECTOR => (
+                DiagnosticSeverity::ERROR,
+                "Namespace selectors are not supported in USS".to_string(),
+            ),
+            _ => return,
+        };
+
+        // point at the combinator or the pseudo-element rather than the whole selector
+        let range = (0..node.child_count())
+            .filter_map(|i| node.child(i))
+            .find(|child| matches!(child.kind(), "+" | "~" | "::"))
+            .map(|marker| {
+                let end = if marker.kind() == "::" { node.end_byte() } else { marker.end_byte() };
+                Range {
+                    start: byte_to_position(marker.start_byte(), content),
+                    end: byte_to_position(end, content),
+                }
+            })
+            .unwrap_or_else(|| node_to_range(node, content));
+
+        diagnostics.push(Diagnostic {
+            range,
+            severity: Some(severity),
+            code: Some(NumberOrString::String("unsupported-css-feature".to_string())),
+            source: Some("uss".to_string()),
+            message,
+            ..Default::default()
+        });
+    }
+
     /// Check if a string looks like a CSS property name
     fn is_likely_css_property(&self, text: &str) -> bool {
         // CSS property names:
@@ -1057,6 +1526,19 @@ impl UssDiagnostics {
                     }
                     // If ImportNode::from_node returns None, it has already added appropriate diagnostics
                 };
+                if let Some(rule_set) = preceding_rule_set(node) {
+                    diagnostics.push(Diagnostic {
+                        range: node_to_range(node, content),
+                        severity: Some(DiagnosticSeverity::ERROR),
+                        code: Some(NumberOrString::String(IMPORT_AFTER_RULE_CODE.to_string())),
+                        source: Some("uss".to_string()),
+                        message: format!(
+                            "@import must be at the top of the file, it is after the rule on line {}",
+                            rule_set.start_position().row + 1
+                        ),
+                        ..Default::default()
+                    });
+                }
             }
             _ => {
                 // Generic at-rule that's not an import - these are not supported
@@ -1119,6 +1601,8 @@ impl UssDiagnostics {
                                 url_references.push(UrlReference {
                                     url: validation_result.url.clone(),
                                     range,
+                                    kind: UrlReferenceKind::Import,
+                                    text: import_path.clone(),
                                 });
 
                                 // Check for URL validation warnings
@@ -1200,6 +1684,281 @@ impl UssDiagnostics {
     }
 }
 
+/// Diagnostic for a number where only an angle or only a time fits, listing the units of that kind
+///
+/// A number without unit gets the first unit of the kind as suggestion, for [`replace_keyword_action`].
+fn unit_kind_diagnostic(value: &UssValue, expected: &[ValueType], node: Node, content: &str) -> Option<Diagnostic> {
+    let UssValue::Numeric { unit, .. } = value else {
+        return None;
+    };
+    let (kind, units) = if expected.contains(&ValueType::Angle) {
+        ("angle", ANGLE_UNITS)
+    } else if expected.contains(&ValueType::Time) {
+        ("time", TIME_UNITS)
+    } else {
+        return None;
+    };
+    // the value fits none of the expected types, with lengths it may as well be a length with a wrong unit
+    if expected.iter().any(|value_type| matches!(value_type, ValueType::LengthPercent | ValueType::Length)) {
+        return None;
+    }
+
+    let text = node.utf8_text(content.as_bytes()).unwrap_or_default();
+    let (message, suggestion) = match unit {
+        Some(unit) => (
+            format!("Expected {} value, '{}' has unit '{}', {} units are: {}", kind, text, unit, kind, units.join(", ")),
+            None,
+        ),
+        None => (
+            format!("Expected {} value, '{}' needs one of the units: {}", kind, text, units.join(", ")),
+            Some(format!("{}{}", text, units[0])),
+        ),
+    };
+    Some(Diagnostic {
+        range: node_to_range(node, content),
+        severity: Some(DiagnosticSeverity::ERROR),
+        code: Some(NumberOrString::String("invalid-unit".to_string())),
+        source: Some("uss".to_string()),
+        message,
+        data: suggestion.map(|suggestion| serde_json::json!({ KEYWORD_SUGGESTION_KEY: suggestion })),
+        ..Default::default()
+    })
+}
+
+/// Items separated by commas, at most [`MAX_LISTED_VALUES`] of them and the number of the rest
+fn list_limited<T: AsRef<str>>(items: &[T]) -> String {
+    let listed: Vec<&str> = items.iter().take(MAX_LISTED_VALUES).map(|item| item.as_ref()).collect();
+    if items.len() > MAX_LISTED_VALUES {
+        format!("{} and {} more", listed.join(", "), items.len() - MAX_LISTED_VALUES)
+    } else {
+        listed.join(", ")
+    }
+}
+
+/// Diagnostic for a CSS-wide keyword that USS doesn't support, USS only supports `initial`
+///
+/// `unset` and `revert` mostly behave like `initial` in USS, so `initial` is suggested for them.
+/// `inherit` has no equivalent: inherited properties inherit when they are not set, the declaration must go.
+fn css_wide_keyword_diagnostic(uss_values: &[UssValue], value_nodes: &[Node], content: &str) -> Option<Diagnostic> {
+    let (index, keyword) = uss_values.iter().enumerate().find_map(|(index, value)| match value {
+        UssValue::Identifier(identifier) if UNSUPPORTED_CSS_WIDE_KEYWORDS.contains(&identifier.as_str()) => {
+            Some((index, identifier.as_str()))
+        }
+        _ => None,
+    })?;
+
+    let (message, suggestion) = if keyword == "inherit" {
+        (
+            format!(
+                "'{}' is not supported in USS, only '{}' is. Inherited properties take the value of the parent element when they are not set, remove the declaration instead",
+                keyword, KEYWORD_INITIAL
+            ),
+            None,
+        )
+    } else {
+        (
+            format!(
+                "'{}' is not supported in USS, only '{}' is. Use '{}' to reset the property to its default value",
+                keyword, KEYWORD_INITIAL, KEYWORD_INITIAL
+            ),
+            Some(KEYWORD_INITIAL),
+        )
+    };
+
+    Some(Diagnostic {
+        range: node_to_range(*value_nodes.get(index)?, content),
+        severity: Some(DiagnosticSeverity::ERROR),
+        code: Some(NumberOrString::String("unsupported-css-wide-keyword".to_string())),
+        source: Some("uss".to_string()),
+        message,
+        data: suggestion.map(|suggestion| serde_json::json!({ KEYWORD_SUGGESTION_KEY: suggestion })),
+        ..Default::default()
+    })
+}
+
+/// Quick fix that replaces a keyword with the keyword suggested by its diagnostic, e.g. a misspelled keyword
+pub fn replace_keyword_action(
+    diagnostic: &Diagnostic,
+    document: &OptionalVersionedTextDocumentIdentifier,
+) -> Option<CodeAction> {
+    let suggestion = diagnostic.data.as_ref()?.get(KEYWORD_SUGGESTION_KEY)?.as_str()?;
+    let edit = TextEdit {
+        range: diagnostic.range,
+        new_text: suggestion.to_string(),
+    };
+
+    Some(CodeAction {
+        title: format!("Change to '{}'", suggestion),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(document_edit(document, vec![edit])),
+        is_preferred: Some(true),
+        ..Default::default()
+    })
+}
+
+/// Quick fix that replaces a deprecated property with its replacement, keeping the value when it still applies
+pub fn replace_deprecated_property_action(
+    diagnostic: &Diagnostic,
+    document: &OptionalVersionedTextDocumentIdentifier,
+) -> Option<CodeAction> {
+    let edit: TextEdit = serde_json::from_value(diagnostic.data.as_ref()?.get(PROPERTY_REPLACEMENT_KEY)?.clone()).ok()?;
+
+    Some(CodeAction {
+        title: format!("Change to '{}'", edit.new_text),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(document_edit(document, vec![edit])),
+        is_preferred: Some(true),
+        ..Default::default()
+    })
+}
+
+/// Quick fix that moves an `@import` after a rule to the top, after the imports that are already there
+///
+/// Comments on the lines right above the import and after it on its line are moved with it. Without
+/// imports at the top, the import is put before the first rule and its comments, with an empty line between.
+pub fn move_import_action(
+    tree: &Tree,
+    content: &str,
+    document: &OptionalVersionedTextDocumentIdentifier,
+    diagnostic: &Diagnostic,
+) -> Option<CodeAction> {
+    if diagnostic.code != Some(NumberOrString::String(IMPORT_AFTER_RULE_CODE.to_string())) {
+        return None;
+    }
+    let root = tree.root_node();
+    let import = (0..root.child_count())
+        .filter_map(|i| root.child(i))
+        .find(|n| n.kind() == NODE_IMPORT_STATEMENT && node_to_range(*n, content) == diagnostic.range)?;
+    let top_level: Vec<Node> = (0..root.child_count()).filter_map(|i| root.child(i)).collect();
+    let first_rule = top_level.iter().position(|n| !matches!(n.kind(), NODE_IMPORT_STATEMENT | NODE_COMMENT))?;
+    let last_import = top_level[..first_rule].iter().rev().find(|n| n.kind() == NODE_IMPORT_STATEMENT);
+
+    let (start, end) = attached_comments_range(import, content);
+    let (remove_start, remove_end) = line_aware_range(content, start, end);
+    let moved = &content[start..end];
+    let (insert_at, new_text) = match last_import {
+        Some(last_import) => (attached_comments_range(*last_import, content).1, format!("\n{}", moved)),
+        None => (
+            line_start(content, attached_comments_range(top_level[first_rule], content).0),
+            format!("{}\n\n", moved),
+        ),
+    };
+    let insert_at = byte_to_position(insert_at, content);
+
+    let edits = vec![
+        TextEdit {
+            range: Range::new(insert_at, insert_at),
+            new_text,
+        },
+        TextEdit {
+            range: Range::new(byte_to_position(remove_start, content), byte_to_position(remove_end, content)),
+            new_text: String::new(),
+        },
+    ];
+
+    Some(CodeAction {
+        title: "Move @import to the top".to_string(),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(WorkspaceEditBuilder::new().edit(document, edits).build()),
+        is_preferred: Some(true),
+        ..Default::default()
+    })
+}
+
+/// The closest rule before an `@import` at the top level
+fn preceding_rule_set(import: Node) -> Option<Node> {
+    if import.parent()?.parent().is_some() {
+        return None;
+    }
+    let mut sibling = import.prev_sibling();
+    while let Some(node) = sibling {
+        if node.kind() == NODE_RULE_SET {
+            return Some(node);
+        }
+        sibling = node.prev_sibling();
+    }
+    None
+}
+
+/// Byte range of a top level node with the comments that belong to it
+///
+/// Comments on the lines right above the node, without an empty line between, belong to it, and so does
+/// a comment after it on the same line.
+fn attached_comments_range(node: Node, content: &str) -> (usize, usize) {
+    let mut start = node.start_byte();
+    let mut previous = node.prev_sibling();
+    while let Some(comment) = previous.filter(|n| n.kind() == NODE_COMMENT) {
+        let between = &content[comment.end_byte()..start];
+        let on_own_line = content[line_start(content, comment.start_byte())..comment.start_byte()].trim().is_empty();
+        if !on_own_line || between.matches('\n').count() > 1 || !between.trim().is_empty() {
+            break;
+        }
+        start = comment.start_byte();
+        previous = comment.prev_sibling();
+    }
+
+    let mut end = node.end_byte();
+    if let Some(comment) = node.next_sibling().filter(|n| n.kind() == NODE_COMMENT) {
+        if !content[end..comment.start_byte()].contains('\n') {
+            end = comment.end_byte();
+        }
+    }
+    (start, end)
+}
+
+/// Byte offset of the start of the line of an offset
+fn line_start(content: &str, offset: usize) -> usize {
+    content[..offset].rfind('\n').map_or(0, |newline| newline + 1)
+}
+
+/// Diagnostic for a reference whose asset doesn't exist, `None` if it exists or isn't in the project
+///
+/// `resource()` is looked up in all `Resources` folders, and a `url()` that isn't a file can be an Addressables address.
+pub fn missing_asset_diagnostic(reference: &UrlReference, paths: &AssetPathResolver) -> Option<Diagnostic> {
+    if reference.url.scheme() != PROJECT_SCHEME {
+        return None;
+    }
+    match reference.kind {
+        UrlReferenceKind::Resource => {
+            if paths.resolve_resource_url(&reference.url).is_some() {
+                return None;
+            }
+            Some(Diagnostic {
+                range: reference.range,
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String("asset-not-found".to_string())),
+                source: Some("uss".to_string()),
+                message: format!("Resource doesn't exist in any Resources folder: {}", reference.text),
+                ..Default::default()
+            })
+        }
+        UrlReferenceKind::Url | UrlReferenceKind::Import => {
+            if paths.resolve_project_url(&reference.url).is_some()
+                || (reference.kind == UrlReferenceKind::Url && paths.resolve_address(&reference.text).is_some())
+            {
+                return None;
+            }
+            let path = project_url_to_path(paths.project_root(), &reference.url)?;
+            Some(asset_not_found_diagnostic(reference.range, &path))
+        }
+    }
+}
+
+/// Diagnostic for a `project:` URL whose file doesn't exist, `path` is the file it refers to
+pub fn asset_not_found_diagnostic(range: Range, path: &std::path::Path) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(NumberOrString::String("asset-not-found".to_string())),
+        source: Some("uss".to_string()),
+        message: format!("Asset doesn't exist on path: {}", path.display()),
+        ..Default::default()
+    }
+}
+
 fn check_extension(content: &str, diagnostics: &mut Vec<Diagnostic>, value_node: Node<'_>, path: &str) {
     let lower_path = path.to_lowercase();
     if !lower_path.ends_with(".uss") && !lower_path.ends_with(".tss") {
@@ -1218,6 +1977,114 @@ fn check_extension(content: &str, diagnostics: &mut Vec<Diagnostic>, value_node:
     }
 }
 
+/// Diagnostics silenced by comments of a document
+///
+/// - `/* uss-disable-next-line code1, code2 */` silences the codes on the line after the comment
+/// - `/* uss-disable code */` silences the code until `/* uss-enable code */` or the end of the document
+///
+/// Without codes, a directive applies to all diagnostics. `/* uss-enable */` ends all regions, while
+/// `/* uss-enable code */` only ends the regions of that code.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Suppressions {
+    /// Lines and the codes silenced on them, `None` for all codes
+    lines: Vec<(u32, Option<Vec<String>>)>,
+    /// Regions as (code, start, end), code `None` for all codes and end `None` for the end of the document
+    regions: Vec<(Option<String>, Position, Option<Position>)>,
+}
+
+impl Suppressions {
+    /// Find the suppression comments of a document
+    pub fn from_tree(tree: &Tree, content: &str) -> Self {
+        let mut suppressions = Self::default();
+        let mut comments = Vec::new();
+        collect_comments(tree.root_node(), &mut comments);
+
+        for comment in comments {
+            let Ok(text) = comment.utf8_text(content.as_bytes()) else {
+                continue;
+            };
+            let text = text.trim_start_matches("/*").trim_end_matches("*/").trim();
+            let (directive, codes) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+            let codes: Vec<String> = codes
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|code| !code.is_empty())
+                .map(str::to_string)
+                .collect();
+            let codes = if codes.is_empty() { None } else { Some(codes) };
+
+            let range = node_to_range(comment, content);
+            match directive {
+                "uss-disable-next-line" => suppressions.lines.push((range.end.line + 1, codes)),
+                "uss-disable" => match codes {
+                    Some(codes) => {
+                        for code in codes {
+                            suppressions.disable(Some(code), range.start);
+                        }
+                    }
+                    None => suppressions.disable(None, range.start),
+                },
+                "uss-enable" => {
+                    for region in suppressions.regions.iter_mut().filter(|region| region.2.is_none()) {
+                        let enabled = match (&codes, &region.0) {
+                            (None, _) => true,
+                            (Some(codes), Some(code)) => codes.contains(code),
+                            (Some(_), None) => false,
+                        };
+                        if enabled {
+                            region.2 = Some(range.end);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        suppressions
+    }
+
+    fn disable(&mut self, code: Option<String>, start: Position) {
+        // a region that is still open already silences the code
+        if !self.regions.iter().any(|region| region.0 == code && region.2.is_none()) {
+            self.regions.push((code, start, None));
+        }
+    }
+
+    /// Whether a diagnostic is silenced
+    pub fn is_suppressed(&self, diagnostic: &Diagnostic) -> bool {
+        let code = match &diagnostic.code {
+            Some(NumberOrString::String(code)) => Some(code.as_str()),
+            _ => None,
+        };
+        let applies = |codes: Option<&String>| codes.is_none_or(|c| Some(c.as_str()) == code);
+
+        let start = diagnostic.range.start;
+        self.lines.iter().any(|(line, codes)| {
+            *line == start.line && codes.as_ref().is_none_or(|codes| codes.iter().any(|c| applies(Some(c))))
+        }) || self.regions.iter().any(|(region_code, region_start, region_end)| {
+            applies(region_code.as_ref()) && *region_start <= start && region_end.is_none_or(|end| start < end)
+        })
+    }
+
+    /// Remove the silenced diagnostics
+    pub fn apply(&self, diagnostics: &mut Vec<Diagnostic>) {
+        if self.lines.is_empty() && self.regions.is_empty() {
+            return;
+        }
+        diagnostics.retain(|diagnostic| !self.is_suppressed(diagnostic));
+    }
+}
+
+fn collect_comments<'a>(node: Node<'a>, comments: &mut Vec<Node<'a>>) {
+    if node.kind() == NODE_COMMENT {
+        comments.push(node);
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_comments(child, comments);
+    }
+}
+
 impl Default for UssDiagnostics {
     fn default() -> Self {
         Self::new()
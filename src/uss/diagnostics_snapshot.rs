@@ -0,0 +1,90 @@
+//! Diagnostics Snapshot
+//!
+//! The diagnostics of a style sheet depend on more than its content: its imports and the files importing
+//! it, the assets of the project and the profile of its path. [`DiagnosticsSnapshot`] holds all of it, so
+//! the style sheet can be analyzed without access to where it was taken from. The language server takes
+//! one from its state and analyzes it on a worker, the `check` subcommand builds one per file.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tower_lsp::lsp_types::{Diagnostic, Range};
+use tree_sitter::Tree;
+use url::Url;
+
+use crate::unity_asset_database::AssetPathResolver;
+use crate::uss::diagnostics::{missing_asset_diagnostic, Suppressions, UssDiagnostics};
+use crate::uss::import_graph::ImportEdge;
+use crate::uss::profile::{profile_diagnostics, UssProfile};
+use crate::uss::unused_variables;
+use crate::uss::variable_resolver::VariableResolver;
+
+/// What the diagnostics of a style sheet need
+pub struct DiagnosticsSnapshot {
+    pub analyzer: UssDiagnostics,
+    pub tree: Tree,
+    pub content: String,
+    pub variable_resolver: VariableResolver,
+    pub project_url: Option<Url>,
+    pub project_root: PathBuf,
+    /// Imports of the style sheet, their diagnostics are already in `import_diagnostics`
+    pub imports: Vec<ImportEdge>,
+    pub import_diagnostics: Vec<Diagnostic>,
+    /// Style sheets importing this one that are read from disk, their references count as uses of its variables
+    pub importers: Vec<Url>,
+    /// Trees and content of the open documents importing this one, they may have unsaved changes
+    pub open_importers: Vec<(Tree, String)>,
+    pub asset_paths: Arc<AssetPathResolver>,
+    /// Profile detected from the path of the style sheet, comments in it can override it
+    pub profile: Option<UssProfile>,
+}
+
+impl DiagnosticsSnapshot {
+    /// Diagnostics of the style sheet, with the suppressions in it that are still to be applied
+    ///
+    /// Reads the importers from disk, async code should run it on a worker.
+    pub fn analyze(self, uxml_names: Option<&HashSet<String>>) -> (Vec<Diagnostic>, Suppressions) {
+        let (mut diagnostics, mut url_references) = self.analyzer.analyze_with_variables_and_classes(
+            &self.tree,
+            &self.content,
+            self.project_url.as_ref(),
+            Some(&self.variable_resolver),
+            uxml_names,
+        );
+
+        diagnostics.extend(self.import_diagnostics);
+        // missing imported files are already reported by the import graph
+        url_references.retain(|r| !self.imports.iter().any(|i| range_contains(i.range, r.range)));
+
+        let mut referenced_elsewhere = if self.importers.is_empty() {
+            HashSet::new()
+        } else {
+            unused_variables::references_in_files(&self.project_root, &self.importers)
+        };
+        for (tree, content) in &self.open_importers {
+            referenced_elsewhere.extend(unused_variables::variable_references(tree, content));
+        }
+        let mut unused_diagnostics = unused_variables::find_unused_variables(&self.tree, &self.content, &referenced_elsewhere);
+        self.analyzer.settings().apply(&mut unused_diagnostics);
+        diagnostics.extend(unused_diagnostics);
+
+        let mut asset_diagnostics: Vec<Diagnostic> = url_references
+            .iter()
+            .filter_map(|r| missing_asset_diagnostic(r, &self.asset_paths))
+            .collect();
+        self.analyzer.settings().apply(&mut asset_diagnostics);
+        diagnostics.extend(asset_diagnostics);
+
+        let mut profile_diagnostics = profile_diagnostics(&self.tree, &self.content, self.profile);
+        self.analyzer.settings().apply(&mut profile_diagnostics);
+        diagnostics.extend(profile_diagnostics);
+
+        let suppressions = Suppressions::from_tree(&self.tree, &self.content);
+        (diagnostics, suppressions)
+    }
+}
+
+fn range_contains(outer: Range, inner: Range) -> bool {
+    outer.start <= inner.start && inner.end <= outer.end
+}
@@ -1,5 +1,4 @@
 //! Tests for USS diagnostics functionality
-use std::sync::Arc;
 
 use super::diagnostics::*;
 use super::parser::UssParser;
@@ -7,6 +6,7 @@ use tower_lsp::lsp_types::NumberOrString;
 use url::Url;
 use crate::language::tree_printer::print_tree_to_stdout;
 use crate::uss::definitions::UssDefinitions;
+use crate::uss::url_references::UrlReferenceKind;
 
 #[test]
 fn test_import_statement_validation() {
@@ -683,7 +683,7 @@ fn test_variable_resolution_warning() {
     let tree = parser.parse(content, None).unwrap();
     
     // Create variable resolver and populate it from the parsed tree
-    let mut variable_resolver = VariableResolver::new(Arc::new(UssDefinitions::new()));
+    let mut variable_resolver = VariableResolver::new(UssDefinitions::shared());
     variable_resolver.add_variables_from_tree(tree.root_node(), content);
     
     let (results, _url_references) = diagnostics.analyze_with_variables(&tree, content, None, Some(&variable_resolver));
@@ -728,7 +728,8 @@ Button {
     let url = Url::parse("project:///Assets/UI/a.uss").unwrap();
     let (_diagnostics_result, url_references) = diagnostics.analyze_with_variables(&tree, content, Some(&url), None);
 
-    // Check that we have the expected number of URLs (3: one string import, one url() import, two url() functions)
+    // Check that we have the expected number of URLs: one string import, one url() import, two url() functions
+    // and one resource()
     // Note: String imports that are valid URLs will be collected
     let url_count = url_references.len();
     println!("Collected {} URL references", url_count);
@@ -738,8 +739,11 @@ Button {
         println!("URL {}: {} at range {:?}", i + 1, url_ref.url, url_ref.range);
     }
     
-    // Should have 4 url collected
-    assert_eq!(url_count, 4, "Should collect at least 2 URL references from url() functions, got {}", url_count);
+    // Should have the 2 imports, 2 url() functions and the resource()
+    assert_eq!(url_count, 5, "Should collect the imports, url() functions and resource(), got {}", url_count);
+    let resource = url_references.iter().find(|r| r.kind == UrlReferenceKind::Resource).unwrap();
+    assert_eq!(resource.text, "UI/border");
+    assert_eq!(resource.url.as_str(), "project:///Assets/Resources/UI/border");
 }
 
 #[test]
@@ -1283,4 +1287,499 @@ fn test_mixed_valid_invalid_comma_separated() {
     let error_message = &value_errors[0].message;
     assert!(error_message.contains("transform") || error_message.contains("invalid-duration"), 
         "Error message should reference the invalid segment: {}", error_message);
-}
\ No newline at end of file
+}
+#[test]
+fn test_unsupported_css_features() {
+    let diagnostics = UssDiagnostics::new();
+    let mut parser = UssParser::new().unwrap();
+
+    let cases = [
+        ("[type=\"text\"] { color: red; }", "Attribute selectors"),
+        (".a + .b { color: red; }", "'+'"),
+        (".a ~ .b { color: red; }", "'~'"),
+        (".a::before { color: red; }", "Pseudo-elements"),
+        ("ns|Button { color: red; }", "Namespace selectors"),
+        (".a:nth-child(2) { color: red; }", "':nth-child()'"),
+    ];
+
+    for (content, expected_message) in cases {
+        let tree = parser.parse(content, None).unwrap();
+        let class_names: std::collections::HashSet<String> = ["Button".to_string()].into_iter().collect();
+        let (results, _) = diagnostics.analyze_with_variables_and_classes(&tree, content, None, None, Some(&class_names));
+
+        assert_eq!(results.len(), 1, "Expected exactly one diagnostic for '{}', got: {:?}", content,
+            results.iter().map(|d| &d.message).collect::<Vec<_>>());
+        assert_eq!(results[0].code, Some(NumberOrString::String("unsupported-css-feature".to_string())));
+        assert!(results[0].message.contains(expected_message), "Unexpected message for '{}': {}", content, results[0].message);
+    }
+}
+
+#[test]
+fn test_important_is_reported_and_value_still_validated() {
+    let diagnostics = UssDiagnostics::new();
+    let mut parser = UssParser::new().unwrap();
+
+    let content = ".a { color: red !important; }";
+    let tree = parser.parse(content, None).unwrap();
+    let results = diagnostics.analyze(&tree, content);
+    assert_eq!(results.len(), 1, "Found: {:?}", results.iter().map(|d| &d.message).collect::<Vec<_>>());
+    assert_eq!(results[0].code, Some(NumberOrString::String("unsupported-css-feature".to_string())));
+    assert_eq!(results[0].severity, Some(tower_lsp::lsp_types::DiagnosticSeverity::WARNING));
+    assert_eq!(results[0].range.start.character, 16);
+
+    // the value itself is still validated
+    let content = ".a { color: 10px !important; }";
+    let tree = parser.parse(content, None).unwrap();
+    let results = diagnostics.analyze(&tree, content);
+    assert!(results.iter().any(|d| d.code == Some(NumberOrString::String("invalid-property-value".to_string()))));
+}
+
+#[test]
+fn test_severity_overrides_from_settings() {
+    use crate::uss::settings::{DiagnosticSettings, SeverityLevel};
+    use tower_lsp::lsp_types::DiagnosticSeverity;
+
+    let mut diagnostics = UssDiagnostics::new();
+    let mut parser = UssParser::new().unwrap();
+    let content = ".a { color: red; color: blue; unknown-prop: 1px; }";
+    let tree = parser.parse(content, None).unwrap();
+
+    let mut settings = DiagnosticSettings::default();
+    settings.severity_overrides.insert("duplicate-property".to_string(), SeverityLevel::Off);
+    settings.severity_overrides.insert("unknown-property".to_string(), SeverityLevel::Hint);
+    diagnostics.set_settings(settings);
+
+    let results = diagnostics.analyze(&tree, content);
+    assert_eq!(results.len(), 1, "Found: {:?}", results.iter().map(|d| &d.message).collect::<Vec<_>>());
+    assert_eq!(results[0].code, Some(NumberOrString::String("unknown-property".to_string())));
+    assert_eq!(results[0].severity, Some(DiagnosticSeverity::HINT));
+}
+
+#[test]
+fn test_property_unsupported_in_unity_version() {
+    use crate::unity_project_manager::UnityVersion;
+    use tower_lsp::lsp_types::DiagnosticSeverity;
+
+    let mut diagnostics = UssDiagnostics::new();
+    let mut parser = UssParser::new().unwrap();
+    let content = ".a { background-size: 10px; -unity-text-generator: advanced; color: red; }";
+    let tree = parser.parse(content, None).unwrap();
+
+    // unknown version, nothing is reported
+    assert!(diagnostics.analyze(&tree, content).is_empty());
+
+    diagnostics.set_unity_version(Some(UnityVersion::new(2022, 3)));
+    let results = diagnostics.analyze(&tree, content);
+    assert_eq!(results.len(), 1, "Found: {:?}", results.iter().map(|d| &d.message).collect::<Vec<_>>());
+    assert_eq!(results[0].code, Some(NumberOrString::String("unsupported-unity-version".to_string())));
+    assert_eq!(results[0].severity, Some(DiagnosticSeverity::WARNING));
+    assert!(results[0].message.contains("-unity-text-generator"));
+    assert!(results[0].message.contains("Unity 6000.0 or newer"));
+
+    diagnostics.set_unity_version(Some(UnityVersion::new(2021, 3)));
+    assert_eq!(diagnostics.analyze(&tree, content).len(), 2);
+
+    diagnostics.set_unity_version(Some(UnityVersion::new(6000, 0)));
+    assert!(diagnostics.analyze(&tree, content).is_empty());
+}
+
+#[test]
+fn test_suppression_comments() {
+    let diagnostics = UssDiagnostics::new();
+    let mut parser = UssParser::new().unwrap();
+    let content = r#".a {
+    /* uss-disable-next-line duplicate-property */
+    color: red;
+    color: blue;
+}
+/* uss-disable unknown-property */
+.b { foo: 1px; }
+.c { bar: 1px; color: nope; }
+/* uss-enable unknown-property */
+.d { baz: 1px; }
+/* uss-disable */
+.e { qux: 1px; color: nope; }
+"#;
+    let tree = parser.parse(content, None).unwrap();
+    let mut results = diagnostics.analyze(&tree, content);
+    let all_codes = |results: &[tower_lsp::lsp_types::Diagnostic]| -> Vec<(u32, String)> {
+        results
+            .iter()
+            .map(|d| match &d.code {
+                Some(NumberOrString::String(code)) => (d.range.start.line, code.clone()),
+                _ => (d.range.start.line, String::new()),
+            })
+            .collect()
+    };
+    let before = all_codes(&results);
+    assert!(before.contains(&(2, "duplicate-property".to_string())), "{:?}", before);
+    assert!(before.contains(&(6, "unknown-property".to_string())), "{:?}", before);
+
+    Suppressions::from_tree(&tree, content).apply(&mut results);
+    let after = all_codes(&results);
+    // the second color is reported as a duplicate on its own line, which isn't silenced
+    assert!(!after.iter().any(|(line, code)| *line == 2 && code == "duplicate-property"), "{:?}", after);
+    assert!(!after.iter().any(|(line, code)| (6..=7).contains(line) && code == "unknown-property"), "{:?}", after);
+    // other codes in the region are still reported
+    assert!(after.iter().any(|(line, code)| *line == 7 && code != "unknown-property"), "{:?}", after);
+    assert!(after.contains(&(9, "unknown-property".to_string())), "{:?}", after);
+    // silenced until the end of the document
+    assert!(!after.iter().any(|(line, _)| *line >= 11), "{:?}", after);
+}
+
+#[test]
+fn test_misspelled_keyword_suggestion() {
+    let diagnostics = UssDiagnostics::new();
+    let mut parser = UssParser::new().unwrap();
+    let content = ".a { display: flx; flex-direction: row colum; }\n.b { flex-direction: sideways; }";
+    let tree = parser.parse(content, None).unwrap();
+    let results = diagnostics.analyze(&tree, content);
+
+    let display = results.iter().find(|d| d.range.start.line == 0 && d.message.contains("'flx'")).unwrap();
+    assert_eq!(display.code, Some(NumberOrString::String("invalid-property-value".to_string())));
+    assert!(display.message.contains("expected one of: flex, none"), "{}", display.message);
+    assert!(display.message.ends_with("Did you mean 'flex'?"), "{}", display.message);
+    // only the misspelled value is flagged
+    assert_eq!(display.range.start.character, 14);
+    assert_eq!(display.range.end.character, 17);
+
+    let uri = Url::parse("file:///project/Assets/a.uss").unwrap();
+    let document = crate::language::workspace_edit::versioned_document(&uri, Some(1));
+    let action = replace_keyword_action(display, &document).unwrap();
+    assert_eq!(action.title, "Change to 'flex'");
+    let edit = action.edit.unwrap();
+    let edits = crate::language::workspace_edit::text_edits(&edit, &uri);
+    assert_eq!(edits[0].new_text, "flex");
+    assert_eq!(edits[0].range, display.range);
+
+    // too far from every keyword for a suggestion, the keywords are still listed
+    let sideways = results.iter().find(|d| d.message.contains("'sideways'")).unwrap();
+    assert!(sideways.message.contains("row-reverse"), "{}", sideways.message);
+    assert!(!sideways.message.contains("Did you mean"), "{}", sideways.message);
+    assert!(replace_keyword_action(sideways, &document).is_none());
+}
+
+#[test]
+fn test_deprecated_property() {
+    use crate::unity_project_manager::UnityVersion;
+    use tower_lsp::lsp_types::{DiagnosticSeverity, DiagnosticTag};
+
+    let mut diagnostics = UssDiagnostics::new();
+    let mut parser = UssParser::new().unwrap();
+    let content = ".a { -unity-background-scale-mode: scale-and-crop; }";
+    let tree = parser.parse(content, None).unwrap();
+    let results = diagnostics.analyze(&tree, content);
+
+    assert_eq!(results.len(), 1, "{:?}", results);
+    let deprecated = &results[0];
+    assert_eq!(deprecated.code, Some(NumberOrString::String("deprecated-property".to_string())));
+    assert_eq!(deprecated.severity, Some(DiagnosticSeverity::HINT));
+    assert_eq!(deprecated.tags, Some(vec![DiagnosticTag::DEPRECATED]));
+    assert_eq!(deprecated.message, "Property '-unity-background-scale-mode' is deprecated, use 'background-size' instead");
+    assert_eq!(deprecated.range.start.character, 5);
+    assert_eq!(deprecated.range.end.character, 33);
+
+    // the quick fix replaces the whole declaration, with the value that does the same
+    let uri = Url::parse("file:///project/Assets/a.uss").unwrap();
+    let document = crate::language::workspace_edit::versioned_document(&uri, Some(1));
+    let action = replace_deprecated_property_action(deprecated, &document).unwrap();
+    assert_eq!(action.title, "Change to 'background-size: cover'");
+    let edit = action.edit.unwrap();
+    let edits = crate::language::workspace_edit::text_edits(&edit, &uri);
+    assert_eq!(edits[0].range.start.character, 5);
+    assert_eq!(edits[0].range.end.character, 49);
+    assert!(replace_keyword_action(deprecated, &document).is_none());
+
+    // the replacement is not available before Unity 2022.2
+    diagnostics.set_unity_version(Some(UnityVersion::new(2021, 3)));
+    assert!(diagnostics.analyze(&tree, content).is_empty());
+    diagnostics.set_unity_version(Some(UnityVersion::new(2022, 3)));
+    assert_eq!(diagnostics.analyze(&tree, content).len(), 1);
+}
+
+#[test]
+fn test_unsupported_css_wide_keywords() {
+    let diagnostics = UssDiagnostics::new();
+    let mut parser = UssParser::new().unwrap();
+    let content = ".a { color: inherit; display: unset; width: initial; all: revert; }";
+    let tree = parser.parse(content, None).unwrap();
+    let results = diagnostics.analyze(&tree, content);
+
+    assert_eq!(results.len(), 3, "{:?}", results);
+    let code = Some(NumberOrString::String("unsupported-css-wide-keyword".to_string()));
+    assert!(results.iter().all(|d| d.code == code));
+
+    let inherit = &results[0];
+    assert_eq!(inherit.range.start.character, 12);
+    assert_eq!(inherit.range.end.character, 19);
+    assert!(inherit.message.starts_with("'inherit' is not supported in USS, only 'initial' is"));
+    assert!(inherit.data.is_none());
+
+    let uri = Url::parse("file:///project/Assets/a.uss").unwrap();
+    let document = crate::language::workspace_edit::versioned_document(&uri, None);
+    let action = replace_keyword_action(&results[1], &document).unwrap();
+    assert_eq!(action.title, "Change to 'initial'");
+    assert!(results[2].message.starts_with("'revert' is not supported in USS"));
+}
+
+#[test]
+fn test_incompatible_asset_type() {
+    let diagnostics = UssDiagnostics::new();
+    let mut parser = UssParser::new().unwrap();
+    let content = r#".a {
+    background-image: url("project:///Assets/UI/main.uss");
+    -unity-font: url("project:///Assets/UI/My%20Icon.png");
+    -unity-font-definition: url("project:///Assets/Fonts/Inter SDF.asset");
+    background-image: url("project:///Assets/UI/icon.PNG");
+    --image: url("project:///Assets/UI/main.uss");
+}"#;
+    let tree = parser.parse(content, None).unwrap();
+    let results: Vec<_> = diagnostics
+        .analyze(&tree, content)
+        .into_iter()
+        .filter(|d| d.code == Some(NumberOrString::String("incompatible-asset-type".to_string())))
+        .collect();
+
+    assert_eq!(results.len(), 2, "{:?}", results);
+    assert_eq!(results[0].severity, Some(tower_lsp::lsp_types::DiagnosticSeverity::WARNING));
+    assert_eq!(results[0].range.start.line, 1);
+    assert_eq!(
+        results[0].message,
+        "Property 'background-image' expects a texture, sprite or vector image, 'main.uss' is not one"
+    );
+    assert_eq!(results[1].message, "Property '-unity-font' expects a font, 'My Icon.png' is not one");
+}
+
+#[test]
+fn test_cursor_values() {
+    let diagnostics = UssDiagnostics::new();
+    let mut parser = UssParser::new().unwrap();
+    let content = r#".a {
+    cursor: link;
+    cursor: url("project:///Assets/UI/hand.png") 4 2;
+    cursor: resource("Cursors/hand");
+    cursor: link url("project:///Assets/UI/hand.png");
+    cursor: url("project:///Assets/UI/hand.png") 4;
+    cursor: url("project:///Assets/UI/hand.png") 4px -2;
+    cursor: url("project:///Assets/UI/hand.png") 1.5 0;
+    cursor: url("project:///Assets/UI/hand.svg");
+}"#;
+    let tree = parser.parse(content, None).unwrap();
+    let duplicate = Some(NumberOrString::String("duplicate-property".to_string()));
+    let results: Vec<_> = diagnostics.analyze(&tree, content).into_iter().filter(|d| d.code != duplicate).collect();
+    let codes: Vec<_> = results
+        .iter()
+        .map(|d| (d.range.start.line, d.code.clone().unwrap()))
+        .collect();
+
+    let code = |code: &str| NumberOrString::String(code.to_string());
+    assert_eq!(
+        codes,
+        vec![
+            (4, code("cursor-keyword-with-asset")),
+            (5, code("cursor-hotspot")),
+            (6, code("cursor-hotspot")),
+            (6, code("cursor-hotspot")),
+            (7, code("cursor-hotspot")),
+            (8, code("incompatible-asset-type")),
+        ],
+        "{:?}",
+        results
+    );
+    assert_eq!(results[0].range.start.character, 12);
+    assert_eq!(results[0].range.end.character, 16);
+    assert!(results[1].message.ends_with("but 1 is found"));
+    assert_eq!(results[2].message, "Cursor hotspot coordinate '4px' has unit 'px', it is in pixels and written without unit");
+    assert!(results[3].message.starts_with("Cursor hotspot coordinate '-2' can't be negative"));
+    assert_eq!(results[4].message, "Cursor hotspot coordinate '1.5' must be a whole number of pixels");
+}
+
+#[test]
+fn test_value_mismatch_range() {
+    let diagnostics = UssDiagnostics::new();
+    let mut parser = UssParser::new().unwrap();
+    let content = ".a {\n    padding: 1px red 2px;\n    margin: 1px 2px 3px 4px 5px;\n}";
+    let tree = parser.parse(content, None).unwrap();
+    let results = diagnostics.analyze(&tree, content);
+
+    assert_eq!(results.len(), 2, "{:?}", results);
+    assert_eq!(results[0].range.start, tower_lsp::lsp_types::Position::new(1, 17));
+    assert_eq!(results[0].range.end, tower_lsp::lsp_types::Position::new(1, 20));
+    assert_eq!(
+        results[0].message,
+        "Property 'padding' value '1px red 2px' does not match expected format, value 2 'red' should be one of: <length-percentage>"
+    );
+    assert_eq!(results[1].range.start, tower_lsp::lsp_types::Position::new(2, 28));
+    assert!(results[1].message.ends_with("value 5 '5px' is one too many"), "{}", results[1].message);
+
+    // all values fit but some are missing, the accepted syntax is listed
+    let content = ".a { text-shadow: 1px 2px; }";
+    let tree = parser.parse(content, None).unwrap();
+    let results = diagnostics.analyze(&tree, content);
+    assert_eq!(results.len(), 1, "{:?}", results);
+    let text_shadow = UssDefinitions::new().get_property_info("text-shadow").unwrap().format;
+    assert!(results[0].message.ends_with(&format!(", expected: {}", text_shadow)), "{}", results[0].message);
+}
+
+#[test]
+fn test_transition_segments() {
+    let diagnostics = UssDiagnostics::new();
+    let mut parser = UssParser::new().unwrap();
+    let content = r#".a { transition: width 2s ease-out 1s, all -1s, opacity 1s -0.5s; }
+.b { transition: color 1s 2s 3s; }
+.c { transition: display 1s, colour 1s linear; }
+.d { transition: var(--fade), width 1s; }"#;
+    let tree = parser.parse(content, None).unwrap();
+    let results = diagnostics.analyze(&tree, content);
+    let codes: Vec<_> = results
+        .iter()
+        .map(|d| (d.range.start.line, d.code.clone().unwrap()))
+        .collect();
+
+    let code = |code: &str| NumberOrString::String(code.to_string());
+    assert_eq!(
+        codes,
+        vec![
+            (0, code("transition-negative-duration")),
+            (1, code("transition-extra-time")),
+            (2, code("transition-not-animatable")),
+            (2, code("transition-unknown-property")),
+        ],
+        "{:?}",
+        results
+    );
+    assert_eq!(results[0].message, "Transition duration '-1s' can't be negative");
+    assert_eq!(results[0].range.start.character, 43);
+    assert_eq!(results[2].message, "Property 'display' is not animatable, the transition has no effect");
+    assert_eq!(results[2].severity, Some(tower_lsp::lsp_types::DiagnosticSeverity::WARNING));
+    assert_eq!(
+        results[3].code_description.as_ref().unwrap().href.as_str(),
+        "https://docs.unity3d.com/6000.0/Documentation/Manual/UIE-Transitions.html"
+    );
+}
+
+#[test]
+fn test_transition_warning_keeps_format_validation() {
+    let diagnostics = UssDiagnostics::new();
+    let mut parser = UssParser::new().unwrap();
+    let content = ".a { transition: display 1s bogus; }";
+    let tree = parser.parse(content, None).unwrap();
+    let results = diagnostics.analyze(&tree, content);
+    let codes: Vec<_> = results.iter().filter_map(|d| d.code.clone()).collect();
+    assert_eq!(
+        codes,
+        vec![
+            NumberOrString::String("transition-not-animatable".to_string()),
+            NumberOrString::String("invalid-property-value".to_string()),
+        ]
+    );
+    assert_eq!(results[1].range.start.character, 28, "{:?}", results[1]);
+    assert!(results[1].message.contains("'display 1s bogus'"), "{}", results[1].message);
+
+    // the rest of the value is valid
+    let content = ".a { transition: display 1s ease-in; }";
+    let tree = parser.parse(content, None).unwrap();
+    assert_eq!(diagnostics.analyze(&tree, content).len(), 1);
+}
+
+#[test]
+fn test_angle_and_time_units() {
+    let diagnostics = UssDiagnostics::new();
+    let mut parser = UssParser::new().unwrap();
+    let content = r#".a {
+    rotate: 45degs;
+    transition-duration: 2secs;
+    rotate: 45px;
+    transition-delay: 2;
+    width: 10em;
+}"#;
+    let tree = parser.parse(content, None).unwrap();
+    let duplicate = Some(NumberOrString::String("duplicate-property".to_string()));
+    let results: Vec<_> = diagnostics.analyze(&tree, content).into_iter().filter(|d| d.code != duplicate).collect();
+    let code = Some(NumberOrString::String("invalid-unit".to_string()));
+    assert_eq!(results.len(), 5, "{:?}", results);
+    assert!(results.iter().all(|d| d.code == code), "{:?}", results);
+
+    // misspelled units are underlined alone, with the nearest unit of the kind
+    assert_eq!(results[0].range.start, tower_lsp::lsp_types::Position::new(1, 14));
+    assert_eq!(results[0].range.end, tower_lsp::lsp_types::Position::new(1, 18));
+    assert_eq!(
+        results[0].message,
+        "Invalid value: Invalid unit 'degs', angle units are: deg, grad, rad, turn. Did you mean 'deg'?"
+    );
+    let uri = Url::parse("file:///project/Assets/a.uss").unwrap();
+    let document = crate::language::workspace_edit::versioned_document(&uri, None);
+    assert_eq!(replace_keyword_action(&results[0], &document).unwrap().title, "Change to 'deg'");
+    assert_eq!(replace_keyword_action(&results[1], &document).unwrap().title, "Change to 's'");
+
+    // units of another kind
+    assert_eq!(results[2].message, "Expected angle value, '45px' has unit 'px', angle units are: deg, grad, rad, turn");
+    assert!(results[2].data.is_none());
+    assert_eq!(results[3].message, "Expected time value, '2' needs one of the units: s, ms");
+    assert_eq!(replace_keyword_action(&results[3], &document).unwrap().title, "Change to '2s'");
+
+    assert!(results[4].message.starts_with("Invalid value: Invalid unit 'em'. Valid units are: px, %, deg"));
+}
+
+#[test]
+fn test_import_after_rule() {
+    use crate::language::tree_utils::position_to_byte_offset;
+    use crate::language::workspace_edit::{text_edits, versioned_document};
+
+    let move_import = |content: &str| -> (Vec<tower_lsp::lsp_types::Diagnostic>, String) {
+        let tree = UssParser::new().unwrap().parse(content, None).unwrap();
+        let results: Vec<_> = UssDiagnostics::new()
+            .analyze(&tree, content)
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::String(IMPORT_AFTER_RULE_CODE.to_string())))
+            .collect();
+        let Some(diagnostic) = results.first() else {
+            return (results, content.to_string());
+        };
+
+        let uri = Url::parse("file:///project/Assets/a.uss").unwrap();
+        let action = move_import_action(&tree, content, &versioned_document(&uri, Some(1)), diagnostic).unwrap();
+        let edit = action.edit.unwrap();
+        let mut edits = text_edits(&edit, &uri);
+        edits.sort_by_key(|e| e.range.start);
+        let mut fixed = content.to_string();
+        for edit in edits.iter().rev() {
+            let start = position_to_byte_offset(content, edit.range.start).unwrap();
+            let end = position_to_byte_offset(content, edit.range.end).unwrap();
+            fixed.replace_range(start..end, &edit.new_text);
+        }
+        (results, fixed)
+    };
+
+    let content = r#"@import "a.uss";
+
+/* buttons */
+.button { color: red; }
+
+/* theme colors */
+@import "b.uss"; /* dark */
+.label { color: blue; }
+"#;
+    let (results, fixed) = move_import(content);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].range.start.line, 6);
+    assert!(results[0].message.contains("rule on line 4"), "{}", results[0].message);
+    // the comments go with the import
+    assert_eq!(
+        fixed,
+        r#"@import "a.uss";
+/* theme colors */
+@import "b.uss"; /* dark */
+
+/* buttons */
+.button { color: red; }
+
+.label { color: blue; }
+"#
+    );
+    assert!(move_import(&fixed).0.is_empty());
+
+    // without imports at the top, the import goes before the first rule
+    let (_, fixed) = move_import("/* header */\n\n.a { color: red; }\n@import \"b.uss\";\n");
+    assert_eq!(fixed, "/* header */\n\n@import \"b.uss\";\n\n.a { color: red; }\n");
+}
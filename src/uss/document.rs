@@ -1,15 +1,16 @@
 //! USS Document
 //!
 //! Represents a single USS document with its content, syntax tree, and version.
-use std::sync::Arc;
 
-use tower_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent, Url};
+use tower_lsp::lsp_types::{OptionalVersionedTextDocumentIdentifier, Position, Range, TextDocumentContentChangeEvent, Url};
 use tree_sitter::{InputEdit, Point, Tree};
 
 use crate::uss::definitions::UssDefinitions;
 use crate::uss::parser::UssParser;
+use crate::uss::symbol_index::{DocumentSymbols, IndexedDocument, IndexedSymbol};
 use crate::uss::variable_resolver::VariableResolver;
 use crate::language::document::DocumentVersion;
+use crate::language::workspace_edit::versioned_document;
 
 /// Represents a USS document with its content, syntax tree, and version
 #[derive(Debug, Clone)]
@@ -28,11 +29,13 @@ pub struct UssDocument {
     line_starts: Vec<usize>,
     /// Variable resolver for CSS custom properties
     pub variable_resolver: VariableResolver,
+    /// Selectors and variables, updated incrementally on edits
+    symbols: DocumentSymbols,
 }
 
 impl UssDocument {
     /// Create a new USS document
-    pub fn new(uri: Url, content: String, version: i32, definitions: Arc<UssDefinitions>) -> Self {
+    pub fn new(uri: Url, content: String, version: i32, definitions: &'static UssDefinitions) -> Self {
 
         let line_starts = Self::calculate_line_starts(&content);
         Self {
@@ -43,11 +46,12 @@ impl UssDocument {
             is_open: false,
             line_starts,
             variable_resolver: VariableResolver::new(definitions),
+            symbols: DocumentSymbols::default(),
         }
     }
     
     /// Create a new USS document with explicit document version
-    pub fn new_with_document_version(uri: Url, content: String, document_version: DocumentVersion, is_open: bool, definitions: Arc<UssDefinitions>) -> Self {
+    pub fn new_with_document_version(uri: Url, content: String, document_version: DocumentVersion, is_open: bool, definitions: &'static UssDefinitions) -> Self {
         let line_starts = Self::calculate_line_starts(&content);
         Self {
             uri,
@@ -57,6 +61,7 @@ impl UssDocument {
             is_open,
             line_starts,
             variable_resolver: VariableResolver::new(definitions),
+            symbols: DocumentSymbols::default(),
         }
     }
     
@@ -67,6 +72,7 @@ impl UssDocument {
         if let Some(tree) = &self.tree {
             self.variable_resolver.add_variables_from_tree(tree.root_node(), &self.content);
         }
+        self.symbols = self.extract_symbols();
     }
     
     /// Apply incremental changes to the document
@@ -98,6 +104,7 @@ impl UssDocument {
                 if let Some(tree) = &self.tree {
                     self.variable_resolver.add_variables_from_tree(tree.root_node(), &self.content);
                 }
+                self.symbols = self.extract_symbols();
             }
         }
     }
@@ -159,13 +166,23 @@ impl UssDocument {
         if let Some(ref mut tree) = self.tree {
             tree.edit(&edit);
         }
+        self.symbols.apply_edit(&edit);
         
         // Update content and line starts
         self.content = new_content;
         self.line_starts = Self::calculate_line_starts(&self.content);
         
         // Re-parse with the old tree for incremental parsing
-        self.tree = parser.parse(&self.content, self.tree.as_ref());
+        let new_tree = parser.parse(&self.content, self.tree.as_ref());
+        match (&self.tree, &new_tree) {
+            (Some(old_tree), Some(tree)) => {
+                let changed: Vec<_> = old_tree.changed_ranges(tree).map(|r| r.start_byte..r.end_byte).collect();
+                self.symbols.update(tree, &self.content, &changed);
+            }
+            (None, Some(tree)) => self.symbols = DocumentSymbols::from_tree(tree, &self.content),
+            (_, None) => self.symbols = DocumentSymbols::default(),
+        }
+        self.tree = new_tree;
         
         // Re-extract and resolve variables after incremental parsing
         if let Some(tree) = &self.tree {
@@ -235,6 +252,36 @@ impl UssDocument {
         &self.content
     }
     
+    fn extract_symbols(&self) -> DocumentSymbols {
+        match &self.tree {
+            Some(tree) => DocumentSymbols::from_tree(tree, &self.content),
+            None => DocumentSymbols::default(),
+        }
+    }
+
+    /// Selectors and variables of the document
+    pub fn symbols(&self) -> &DocumentSymbols {
+        &self.symbols
+    }
+
+    /// Symbols of the document with their positions, for the [`crate::uss::symbol_index::SymbolIndex`]
+    pub fn indexed_symbols(&self) -> IndexedDocument {
+        let symbols = self
+            .symbols
+            .occurrences()
+            .map(|(range, symbol)| IndexedSymbol {
+                kind: symbol.kind,
+                name: symbol.name.clone(),
+                range: Range::new(self.byte_to_position(range.start), self.byte_to_position(range.end)),
+                is_declaration: symbol.is_declaration,
+            })
+            .collect();
+        IndexedDocument {
+            version: self.document_version.minor,
+            symbols,
+        }
+    }
+
     /// Re-extract and resolve variables with a source URL for proper relative URL resolution
     /// This should be called after parsing when the project URL is available
     pub fn extract_variables_with_source_url(&mut self, source_url: Option<&Url>) {
@@ -249,15 +296,8 @@ impl UssDocument {
             return Position { line: 0, character: 0 };
         }
         
-        // Find the line containing this byte
-        let mut line = 0;
-        for (i, &line_start) in self.line_starts.iter().enumerate() {
-            if byte < line_start {
-                line = i.saturating_sub(1);
-                break;
-            }
-            line = i;
-        }
+        // Find the line containing this byte, the last one that starts before it
+        let line = self.line_starts.partition_point(|&line_start| line_start <= byte).saturating_sub(1);
         
         // Calculate character position within the line
         let line_start_byte = self.line_starts[line];
@@ -285,6 +325,13 @@ impl UssDocument {
         }
     }
     
+    /// Identifier of the document at its current version, for edits computed from its current content
+    ///
+    /// The version is the client's version, `None` if the document is not open in the client.
+    pub fn versioned_identifier(&self) -> OptionalVersionedTextDocumentIdentifier {
+        versioned_document(&self.uri, self.is_open.then_some(self.document_version.minor))
+    }
+
     /// Get the current document version
     pub fn document_version(&self) -> DocumentVersion {
         self.document_version
@@ -329,7 +376,7 @@ mod tests {
     fn create_test_document() -> UssDocument {
         let uri = Url::parse("file:///test.uss").unwrap();
         let content = ".test { color: red; }".to_string();
-        UssDocument::new(uri, content, 1, Arc::new(UssDefinitions::new()))
+        UssDocument::new(uri, content, 1, UssDefinitions::shared())
     }
 
     #[test]
@@ -407,7 +454,7 @@ mod tests {
     fn test_multiline_content() {
         let uri = Url::parse("file:///test.uss").unwrap();
         let content = ".test {\n  color: red;\n  background: blue;\n}".to_string();
-        let doc = UssDocument::new(uri, content, 1, Arc::new(UssDefinitions::new()));
+        let doc = UssDocument::new(uri, content, 1, UssDefinitions::shared());
 
         
         // Test line start calculation
@@ -461,7 +508,7 @@ mod tests {
         let content = ".test { color: red; }".to_string();
         let version = DocumentVersion { major: 5, minor: 3 };
         
-        let doc = UssDocument::new_with_document_version(uri, content, version, true, Arc::new(UssDefinitions::new()));
+        let doc = UssDocument::new_with_document_version(uri, content, version, true, UssDefinitions::shared());
 
         
         assert_eq!(doc.document_version().major, 5);
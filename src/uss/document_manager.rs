@@ -3,7 +3,7 @@
 //! Manages multiple USS documents and provides operations for document lifecycle.
 
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::fmt;
 use tower_lsp::lsp_types::{TextDocumentContentChangeEvent, Url};
 
 use crate::uss::definitions::UssDefinitions;
@@ -11,11 +11,26 @@ use crate::uss::parser::UssParser;
 use crate::language::document::DocumentVersion;
 use super::document::UssDocument;
 
+/// Memory held by the open documents of a manager
+///
+/// Content is a proxy for the memory of a document, its syntax tree and symbols grow with it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DocumentMemory {
+    pub open_documents: usize,
+    pub open_bytes: usize,
+}
+
+impl fmt::Display for DocumentMemory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} open documents ({} KiB)", self.open_documents, self.open_bytes / 1024)
+    }
+}
+
 /// Document manager for USS files
 pub struct UssDocumentManager {
     documents: HashMap<Url, UssDocument>,
     parser: UssParser,
-    definitions: Arc<UssDefinitions>,
+    definitions: &'static UssDefinitions,
 }
 
 impl UssDocumentManager {
@@ -24,14 +39,14 @@ impl UssDocumentManager {
         Ok(Self {
             documents: HashMap::new(),
             parser: UssParser::new()?,
-            definitions: Arc::new(UssDefinitions::new()),
+            definitions: UssDefinitions::shared(),
         })
     }
     
     /// Open a new document
     pub fn open_document(&mut self, uri: Url, content: String, version: i32) {
         // Since closed documents are removed from memory, we always create a new document
-        let mut document = UssDocument::new(uri.clone(), content, version, self.definitions.clone());
+        let mut document = UssDocument::new(uri.clone(), content, version, self.definitions);
         document.mark_opened(version);
         document.parse(&mut self.parser);
         self.documents.insert(uri, document);
@@ -53,6 +68,14 @@ impl UssDocumentManager {
     pub fn close_document(&mut self, uri: &Url) {
         self.documents.remove(uri);
     }
+
+    /// Memory held by the open documents
+    pub fn memory(&self) -> DocumentMemory {
+        DocumentMemory {
+            open_documents: self.documents.len(),
+            open_bytes: self.documents.values().map(|document| document.content().len()).sum(),
+        }
+    }
     
     /// Get a document reference
     pub fn get_document(&self, uri: &Url) -> Option<&UssDocument> {
@@ -64,6 +87,11 @@ impl UssDocumentManager {
         self.documents.get_mut(uri)
     }
     
+    /// URIs of the open documents
+    pub fn document_uris(&self) -> impl Iterator<Item = &Url> {
+        self.documents.keys()
+    }
+
     /// Check if a document is currently open in a client
     pub fn is_document_open(&self, uri: &Url) -> bool {
         // Since closed documents are removed from memory, existence means it's open
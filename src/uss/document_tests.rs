@@ -3,17 +3,16 @@
 #[cfg(test)]
 mod tests {
 
-    use std::sync::Arc;
 
     use tower_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent, Url};
 
-    use crate::uss::{definitions::UssDefinitions, document::UssDocument, document_manager::UssDocumentManager};
+    use crate::uss::{definitions::UssDefinitions, document::UssDocument, document_manager::{DocumentMemory, UssDocumentManager}};
 
     #[test]
     fn test_document_creation() {
         let uri = Url::parse("file:///test.uss").unwrap();
         let content = ".button { color: red; }".to_string();
-        let document = UssDocument::new(uri.clone(), content.clone(), 1, Arc::new(UssDefinitions::new()));
+        let document = UssDocument::new(uri.clone(), content.clone(), 1, UssDefinitions::shared());
 
         
         assert_eq!(document.uri, uri);
@@ -35,11 +34,13 @@ mod tests {
         assert_eq!(document.content(), &content);
         assert_eq!(document.document_version().minor, 1);
         assert!(manager.is_document_open(&uri));
+        assert_eq!(manager.memory(), DocumentMemory { open_documents: 1, open_bytes: content.len() });
         
         // Close document - it should be completely removed from memory
         manager.close_document(&uri);
         assert!(manager.get_document(&uri).is_none());
         assert!(!manager.is_document_open(&uri));
+        assert_eq!(manager.memory(), DocumentMemory::default());
     }
     
     #[test]
@@ -98,7 +99,7 @@ mod tests {
     fn test_line_starts_calculation() {
         let uri = Url::parse("file:///test.uss").unwrap();
         let content = ".button {\n  color: red;\n  font-size: 12px;\n}".to_string();
-        let document = UssDocument::new(uri, content, 1, Arc::new(UssDefinitions::new()));
+        let document = UssDocument::new(uri, content, 1, UssDefinitions::shared());
 
         
         // Test position to byte conversion
@@ -0,0 +1,346 @@
+//! File Renames
+//!
+//! Editors report a renamed or moved file to `workspace/didChangeWatchedFiles` as a deleted file and a
+//! created one. [`pair_renames`] pairs them up again, and [`RenamedFiles`] remembers where renamed style
+//! sheets went, so an import of the old path gets a quick fix that updates it.
+//!
+//! Editors that rename files themselves ask first with `workspace/willRenameFiles`, then [`FileMoves`]
+//! updates the references to the renamed files in all style sheets, before they break.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, Diagnostic, NumberOrString, OptionalVersionedTextDocumentIdentifier, TextEdit,
+};
+use url::Url;
+
+use crate::language::asset_url::{project_url_to_relative_path, rewrite_reference_path};
+use crate::language::tree_utils::position_to_byte_offset;
+use crate::language::workspace_edit::WorkspaceEditBuilder;
+use crate::uss::constants::PROJECT_SCHEME;
+use crate::uss::import_graph::ImportEdge;
+use crate::uss::url_references::{UrlReferenceInfo, UrlReferenceKind};
+
+/// Key of the new URL of a renamed file in the data of an `import-not-found` diagnostic
+const RENAMED_TO_KEY: &str = "renamedTo";
+
+/// Pair deleted files with the created files they were renamed or moved to
+///
+/// A file moved to another directory keeps its name, so a deleted file is paired with the only created
+/// file of the same name. Otherwise a file renamed in place is the only deleted and the only created
+/// file of a directory with that extension.
+pub fn pair_renames(deleted: &[PathBuf], created: &[PathBuf]) -> Vec<(PathBuf, PathBuf)> {
+    let mut created: Vec<&PathBuf> = created.iter().collect();
+    let mut pairs = Vec::new();
+
+    let mut unpaired = Vec::new();
+    for old in deleted {
+        let same_name: Vec<usize> = (0..created.len())
+            .filter(|&i| created[i].file_name() == old.file_name())
+            .collect();
+        match same_name[..] {
+            [i] => pairs.push((old.clone(), created.remove(i).clone())),
+            _ => unpaired.push(old),
+        }
+    }
+
+    let same_place = |a: &PathBuf, b: &PathBuf| a.parent() == b.parent() && a.extension() == b.extension();
+    for old in &unpaired {
+        if unpaired.iter().filter(|other| same_place(old, other)).count() != 1 {
+            continue;
+        }
+        let candidates: Vec<usize> = (0..created.len()).filter(|&i| same_place(old, created[i])).collect();
+        if let [i] = candidates[..] {
+            pairs.push(((*old).clone(), created.remove(i).clone()));
+        }
+    }
+
+    pairs
+}
+
+/// Where renamed files went during this session, by their old project URL
+#[derive(Debug, Default)]
+pub struct RenamedFiles {
+    renamed: HashMap<Url, Url>,
+}
+
+impl RenamedFiles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that a file was renamed, files renamed to its old URL before are now at the new one
+    pub fn record(&mut self, from: Url, to: Url) {
+        if from == to {
+            return;
+        }
+        for target in self.renamed.values_mut() {
+            if *target == from {
+                *target = to.clone();
+            }
+        }
+        // renamed back
+        self.renamed.remove(&to);
+        self.renamed.insert(from, to);
+    }
+
+    /// The URL a file was renamed to
+    pub fn renamed_to(&self, url: &Url) -> Option<&Url> {
+        self.renamed.get(url)
+    }
+
+    /// Add the new URL to diagnostics of imports of renamed files, for [`update_import_action`]
+    pub fn add_quick_fix_data(&self, diagnostics: &mut [Diagnostic], imports: &[ImportEdge]) {
+        for diagnostic in diagnostics {
+            if diagnostic.code != Some(NumberOrString::String("import-not-found".to_string())) {
+                continue;
+            }
+            let renamed_to = imports
+                .iter()
+                .find(|edge| edge.range == diagnostic.range)
+                .and_then(|edge| self.renamed_to(&edge.url));
+            if let Some(renamed_to) = renamed_to {
+                diagnostic.data = Some(serde_json::json!({ RENAMED_TO_KEY: renamed_to }));
+            }
+        }
+    }
+}
+
+/// Quick fix that changes an import of a renamed file to its new path
+///
+/// `importer` is the project URL of the document, the new path is relative if the import was.
+pub fn update_import_action(
+    diagnostic: &Diagnostic,
+    content: &str,
+    importer: &Url,
+    document: &OptionalVersionedTextDocumentIdentifier,
+) -> Option<CodeAction> {
+    let renamed_to: Url = serde_json::from_value(diagnostic.data.as_ref()?.get(RENAMED_TO_KEY)?.clone()).ok()?;
+    let start = position_to_byte_offset(content, diagnostic.range.start)?;
+    let end = position_to_byte_offset(content, diagnostic.range.end)?;
+    let argument = content.get(start..end)?;
+
+    let edit = TextEdit {
+        range: diagnostic.range,
+        new_text: rewrite_reference(argument, importer, &renamed_to)?,
+    };
+    Some(CodeAction {
+        title: format!(
+            "Update import to '{}'",
+            project_url_to_relative_path(&renamed_to).unwrap_or_else(|| renamed_to.to_string())
+        ),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(WorkspaceEditBuilder::new().edit(document, vec![edit]).build()),
+        is_preferred: Some(true),
+        ..Default::default()
+    })
+}
+
+/// A reference as written, `url("...")` or a string, changed to refer to `target` from the file of `referrer`
+///
+/// The form of the reference, its quotes and the query of the URL are kept, see [`rewrite_reference_path`].
+fn rewrite_reference(text: &str, referrer: &Url, target: &Url) -> Option<String> {
+    let (open, inner, close) = match text.strip_prefix("url(").and_then(|a| a.strip_suffix(')')) {
+        Some(inner) => ("url(", inner.trim(), ")"),
+        None => ("", text, ""),
+    };
+    let quote = inner.chars().next().filter(|c| *c == '"' || *c == '\'').map(String::from).unwrap_or_default();
+    let path = inner.trim_matches(|c| c == '"' || c == '\'');
+    // e.g. `?fileID=...&guid=...&type=3#name` of a sprite in a texture
+    let (path, query) = path.split_at(path.find(['?', '#']).unwrap_or(path.len()));
+
+    let new_path = rewrite_reference_path(path, referrer, target)?;
+    Some(format!("{}{}{}{}{}{}", open, quote, new_path, query, quote, close))
+}
+
+/// Files and folders about to be renamed, by project relative path
+#[derive(Debug, Clone, Default)]
+pub struct FileMoves {
+    moves: Vec<(String, String)>,
+}
+
+impl FileMoves {
+    /// Moves from the project URLs of files or folders before and after renaming
+    pub fn new(moves: &[(Url, Url)]) -> Self {
+        let moves = moves
+            .iter()
+            .filter_map(|(from, to)| Some((project_url_to_relative_path(from)?, project_url_to_relative_path(to)?)))
+            .filter(|(from, to)| from != to)
+            .collect();
+        Self { moves }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// The URL of a file after renaming, also of a file in a renamed folder, `None` if it isn't moved
+    pub fn moved_url(&self, url: &Url) -> Option<Url> {
+        let path = project_url_to_relative_path(url)?;
+        self.moves.iter().find_map(|(from, to)| {
+            let rest = path.strip_prefix(from.as_str())?;
+            if !rest.is_empty() && !rest.starts_with('/') {
+                return None;
+            }
+            Url::parse(&format!("{}:/{}{}", PROJECT_SCHEME, to, rest)).ok()
+        })
+    }
+
+    /// Edits of a style sheet that keep its references working after renaming
+    ///
+    /// References to moved files are changed to their new path. If the style sheet is moved itself, its
+    /// relative references are changed to be relative to its new location. `resource()` references
+    /// name assets in `Resources` folders by name, they are not changed.
+    pub fn reference_edits(&self, file: &Url, references: &[UrlReferenceInfo]) -> Vec<TextEdit> {
+        let moved_file = self.moved_url(file);
+        let referrer = moved_file.as_ref().unwrap_or(file);
+
+        let mut edits = Vec::new();
+        for reference in references {
+            if reference.kind == UrlReferenceKind::Resource {
+                continue;
+            }
+            let Some(url) = reference.url.as_deref().and_then(|url| Url::parse(url).ok()) else {
+                continue;
+            };
+            if url.scheme() != PROJECT_SCHEME {
+                continue;
+            }
+            let target = match self.moved_url(&url) {
+                Some(target) => target,
+                None if moved_file.is_some() => url,
+                None => continue,
+            };
+            match rewrite_reference(&reference.text, referrer, &target) {
+                Some(new_text) if new_text != reference.text => edits.push(TextEdit {
+                    range: reference.range,
+                    new_text,
+                }),
+                _ => {}
+            }
+        }
+        edits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::{Position, Range};
+
+    use crate::language::workspace_edit::versioned_document;
+
+    fn paths(paths: &[&str]) -> Vec<PathBuf> {
+        paths.iter().map(PathBuf::from).collect()
+    }
+
+    fn url(s: &str) -> Url {
+        Url::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_pair_renames() {
+        // moved, renamed in place, and a deleted file that isn't paired
+        let pairs = pair_renames(
+            &paths(&["/p/Assets/UI/a.uss", "/p/Assets/UI/b.uss", "/p/Assets/Old/c.uss"]),
+            &paths(&["/p/Assets/Shared/a.uss", "/p/Assets/UI/renamed.uss"]),
+        );
+        assert_eq!(
+            pairs,
+            vec![
+                (PathBuf::from("/p/Assets/UI/a.uss"), PathBuf::from("/p/Assets/Shared/a.uss")),
+                (PathBuf::from("/p/Assets/UI/b.uss"), PathBuf::from("/p/Assets/UI/renamed.uss")),
+            ]
+        );
+
+        // two files renamed in the same directory at once can't be told apart
+        let pairs = pair_renames(&paths(&["/p/a.uss", "/p/b.uss"]), &paths(&["/p/c.uss", "/p/d.uss"]));
+        assert!(pairs.is_empty());
+    }
+
+    #[test]
+    fn test_record_renames() {
+        let mut renamed = RenamedFiles::new();
+        renamed.record(url("project:/Assets/a.uss"), url("project:/Assets/b.uss"));
+        renamed.record(url("project:/Assets/b.uss"), url("project:/Assets/c.uss"));
+        assert_eq!(renamed.renamed_to(&url("project:/Assets/a.uss")), Some(&url("project:/Assets/c.uss")));
+
+        renamed.record(url("project:/Assets/c.uss"), url("project:/Assets/a.uss"));
+        assert_eq!(renamed.renamed_to(&url("project:/Assets/a.uss")), None);
+        assert_eq!(renamed.renamed_to(&url("project:/Assets/c.uss")), Some(&url("project:/Assets/a.uss")));
+    }
+
+    #[test]
+    fn test_update_import_action() {
+        let content = "@import url(\"../Old/common.uss\");";
+        let range = Range::new(Position::new(0, 8), Position::new(0, 32));
+        let mut renamed = RenamedFiles::new();
+        renamed.record(url("project:/Assets/Old/common.uss"), url("project:/Assets/Shared/common.uss"));
+
+        let mut diagnostics = vec![Diagnostic {
+            range,
+            code: Some(NumberOrString::String("import-not-found".to_string())),
+            ..Default::default()
+        }];
+        let imports = vec![ImportEdge { url: url("project:/Assets/Old/common.uss"), range }];
+        renamed.add_quick_fix_data(&mut diagnostics, &imports);
+
+        let importer = url("project:/Assets/UI/main.uss");
+        let document = versioned_document(&url("file:///p/Assets/UI/main.uss"), Some(1));
+        let action = update_import_action(&diagnostics[0], content, &importer, &document).unwrap();
+        assert_eq!(action.title, "Update import to 'Assets/Shared/common.uss'");
+        let edit = serde_json::to_value(action.edit.unwrap()).unwrap();
+        assert_eq!(edit["documentChanges"][0]["edits"][0]["newText"], "url(\"../Shared/common.uss\")");
+    }
+
+    #[test]
+    fn test_reference_edits() {
+        use crate::uss::parser::UssParser;
+        use crate::uss::url_references::UrlReferenceResolver;
+
+        let content = r#"@import "common.uss";
+@import url("/Assets/Theme/colors.uss");
+.a { background-image: url("../Images/icon.png?fileID=2&guid=abc&type=3#icon_0"); }
+.b { background-image: url('../Images/Other/bg.png'); }
+.c { background-image: resource("Icons/star"); }
+"#;
+        let tree = UssParser::new().unwrap().parse(content, None).unwrap();
+        let main = url("project:/Assets/UI/main.uss");
+        let resolver = UrlReferenceResolver::new(PathBuf::from("/p"));
+        let references = resolver.file_references(&tree, content, &main).references;
+        let new_texts = |moves: &FileMoves| -> Vec<String> {
+            moves.reference_edits(&main, &references).into_iter().map(|e| e.new_text).collect()
+        };
+
+        // a renamed style sheet and a renamed folder
+        let moves = FileMoves::new(&[
+            (url("project:/Assets/UI/common.uss"), url("project:/Assets/UI/shared.uss")),
+            (url("project:/Assets/Images"), url("project:/Assets/Art")),
+        ]);
+        assert_eq!(
+            new_texts(&moves),
+            vec![
+                "\"shared.uss\"",
+                "url(\"../Art/icon.png?fileID=2&guid=abc&type=3#icon_0\")",
+                "url('../Art/Other/bg.png')",
+            ]
+        );
+
+        // the style sheet itself is moved, absolute references stay as they are
+        let moves = FileMoves::new(&[(main.clone(), url("project:/Assets/UI/Screens/main.uss"))]);
+        assert_eq!(
+            new_texts(&moves),
+            vec![
+                "\"../common.uss\"",
+                "url(\"../../Images/icon.png?fileID=2&guid=abc&type=3#icon_0\")",
+                "url('../../Images/Other/bg.png')",
+            ]
+        );
+
+        // a folder with a similar name isn't moved
+        let moves = FileMoves::new(&[(url("project:/Assets/Image"), url("project:/Assets/Art"))]);
+        assert!(new_texts(&moves).is_empty());
+    }
+}
@@ -2,11 +2,16 @@
 //!
 //! Provides formatting capabilities for USS files using the malva CSS formatter.
 //! Follows the formatting rules specified in USSFormatter.md.
+//!
+//! Formatting is idempotent, formatting the output again changes nothing. Top level nodes are formatted
+//! one by one and joined by this formatter, so comments between rules are kept as they are and
+//! groups of rules separated by blank lines stay grouped.
 
 use malva::{config::FormatOptions, format_text, Syntax};
 use tower_lsp::lsp_types::*;
 use tree_sitter::{Node, Tree};
 use crate::language::tree_utils::{byte_to_position, position_to_byte_offset, node_to_range, has_error_nodes};
+use crate::uss::constants::NODE_COMMENT;
 
 /// USS Formatter that handles formatting requests
 pub struct UssFormatter {
@@ -58,20 +63,61 @@ impl UssFormatter {
             return Ok(Vec::new());
         }
 
-        // Format the range content
-        match format_text(&range_content, Syntax::Css, &self.format_options) {
-            Ok(formatted) => {
-                if formatted == range_content {
-                    // No changes needed
-                    Ok(Vec::new())
-                } else {
-                    // Return an edit for the actual range
-                    Ok(vec![TextEdit {
-                        range: actual_range,
-                        new_text: formatted,
-                    }])
-                }
+        let formatted = self.format_top_level_nodes(content, tree.root_node(), actual_range)?;
+        if formatted == range_content {
+            // No changes needed
+            Ok(Vec::new())
+        } else {
+            // Return an edit for the actual range
+            Ok(vec![TextEdit {
+                range: actual_range,
+                new_text: formatted,
+            }])
+        }
+    }
+
+    /// Format the top level nodes in a range and join them
+    ///
+    /// Nodes are separated by a newline, or by a blank line if there was at least one blank line between them.
+    /// A comment that starts on the line where the previous node ends stays on that line.
+    fn format_top_level_nodes(&self, content: &str, root: Node, range: Range) -> Result<String, String> {
+        let start_offset = self.position_to_offset(content, range.start)?;
+        let end_offset = self.position_to_offset(content, range.end)?;
+
+        let mut formatted = String::new();
+        let mut previous: Option<Node> = None;
+        let mut cursor = root.walk();
+        for node in root.children(&mut cursor) {
+            if node.start_byte() < start_offset || node.end_byte() > end_offset {
+                continue;
             }
+
+            if let Some(previous) = previous {
+                let between = &content[previous.end_byte()..node.start_byte()];
+                let separator = match between.matches('\n').count() {
+                    0 if node.kind() == NODE_COMMENT => " ",
+                    0 | 1 => "\n",
+                    _ => "\n\n",
+                };
+                formatted.push_str(separator);
+            }
+
+            formatted.push_str(&self.format_node(content, node)?);
+            previous = Some(node);
+        }
+
+        Ok(formatted)
+    }
+
+    /// Format a top level node, comments are kept as they are
+    fn format_node(&self, content: &str, node: Node) -> Result<String, String> {
+        let text = &content[node.byte_range()];
+        if node.kind() == NODE_COMMENT {
+            return Ok(text.trim_end().to_string());
+        }
+
+        match format_text(text, Syntax::Css, &self.format_options) {
+            Ok(formatted) => Ok(formatted.trim_end().to_string()),
             Err(e) => {
                 log::warn!("Failed to format USS range: {}", e);
                 Err(format!("Range formatting failed: {}", e))
@@ -1,5 +1,6 @@
 use tower_lsp::lsp_types::{Position, Range};
 
+use crate::language::tree_utils::position_to_byte_offset;
 use crate::uss::{formatter::UssFormatter, parser::UssParser};
 
 fn create_parser() -> UssParser {
@@ -431,3 +432,105 @@ fn test_find_actual_format_range_multiline_rule_with_complete_rules_inside() {
         }
     );
 }
+
+/// Format a document and apply the edit
+fn format(content: &str) -> String {
+    let mut parser = create_parser();
+    let tree = parser.parse(content, None).unwrap();
+    let edits = UssFormatter::new().format_document(content, &tree).unwrap();
+    assert!(edits.len() <= 1);
+    match edits.first() {
+        Some(edit) => {
+            let start = position_to_byte_offset(content, edit.range.start).unwrap();
+            let end = position_to_byte_offset(content, edit.range.end).unwrap();
+            format!("{}{}{}", &content[..start], edit.new_text, &content[end..])
+        }
+        None => content.to_string(),
+    }
+}
+
+#[test]
+fn test_format_preserves_comments_and_blank_lines() {
+    let content = "/* header */\n\n\n.a {\n  color: red;\n}\n.b {\n  color: blue;\n} /* trailing */\n\n\n\n/* group */\n.c {\n  color: green;\n}\n";
+    let expected = "/* header */\n\n.a {\n  color: red;\n}\n.b {\n  color: blue;\n} /* trailing */\n\n/* group */\n.c {\n  color: green;\n}\n";
+    assert_eq!(format(content), expected);
+    assert_eq!(format(expected), expected);
+}
+
+#[test]
+fn test_format_separates_rules_on_one_line() {
+    let content = ".a {\n  color: red;\n} .b {\n  color: blue;\n}";
+    assert_eq!(format(content), ".a {\n  color: red;\n}\n.b {\n  color: blue;\n}");
+}
+
+/// Small deterministic random generator, so generated test cases are reproducible
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[(self.next() % items.len() as u64) as usize]
+    }
+}
+
+/// Generate a valid style sheet with random rules, comments and whitespace
+fn generate_style_sheet(rng: &mut XorShift) -> String {
+    const SELECTORS: &[&str] = &[".a", "#b", "Button", ".a > .b", ".c:hover", "Label.title", ".a, .b", "*"];
+    const DECLARATIONS: &[&str] = &[
+        "color: red;",
+        "width: 100px;",
+        "margin: 4px 8px;",
+        "background-image: url(\"project:///Assets/a.png\");",
+        "--my-color: #fff;",
+        "color: var(--my-color);",
+        "flex-direction: row;",
+    ];
+    const COMMENTS: &[&str] = &["/* comment */", "/* multi\n   line */", "/**/"];
+    const WHITESPACE: &[&str] = &["", " ", "\n", "\n\n", "\n\n\n", "\t", "  \n "];
+
+    let mut sheet = String::new();
+    for _ in 0..(rng.next() % 6) {
+        sheet.push_str(rng.pick(WHITESPACE));
+        if rng.next() % 3 == 0 {
+            sheet.push_str(rng.pick(COMMENTS));
+            sheet.push_str(rng.pick(WHITESPACE));
+        }
+
+        sheet.push_str(rng.pick(SELECTORS));
+        sheet.push_str(rng.pick(&["{", " {", " {\n"]));
+        for _ in 0..(rng.next() % 4) {
+            sheet.push_str(rng.pick(WHITESPACE));
+            sheet.push_str(rng.pick(DECLARATIONS));
+        }
+        sheet.push_str(rng.pick(WHITESPACE));
+        sheet.push('}');
+    }
+    sheet.push_str(rng.pick(WHITESPACE));
+    sheet
+}
+
+/// format(format(x)) == format(x), and comments are kept
+#[test]
+fn test_format_is_idempotent_for_generated_style_sheets() {
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+    for _ in 0..300 {
+        let content = generate_style_sheet(&mut rng);
+        let formatted = format(&content);
+        assert_eq!(format(&formatted), formatted, "Formatting is not idempotent for:\n{}", content);
+
+        for comment in ["/* comment */", "/**/"] {
+            assert_eq!(
+                content.matches(comment).count(),
+                formatted.matches(comment).count(),
+                "Comments were lost when formatting:\n{}",
+                content
+            );
+        }
+    }
+}
@@ -12,14 +12,14 @@ pub struct UssHighlighter {
     /// Semantic token legend for USS
     pub legend: SemanticTokensLegend,
     /// USS language definitions for validation
-    definitions: UssDefinitions,
+    definitions: &'static UssDefinitions,
 }
 
 impl UssHighlighter {
     /// Create a new USS highlighter with the semantic token legend
     pub fn new() -> Self {
         Self {
-            definitions: UssDefinitions::new(),
+            definitions: UssDefinitions::shared(),
             legend: SemanticTokensLegend {
                 token_types: vec![
                     SemanticTokenType::NAMESPACE,    // 0 - .class-selector
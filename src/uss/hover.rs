@@ -5,22 +5,27 @@
 //! - Documentation URLs
 //! - Unity version compatibility
 
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
-use crate::unity_project_manager::UnityProjectManager;
+use crate::unity_project_manager::{UnityProjectManager, DEFAULT_DOCS_VERSION};
 use crate::uss::definitions::UssDefinitions;
 use crate::language::tree_utils::{find_node_of_type_at_position, find_node_at_position, has_error_nodes};
 use crate::uss::import_node::ImportNode;
+use crate::uss::parser::UssParser;
 use crate::uss::url_function_node::UrlFunctionNode;
 use crate::uss::function_node::FunctionNode;
 use crate::uss::uss_utils::convert_uss_string;
+use crate::uss::variable_resolver::VariableResolver;
 use crate::language::asset_url::{project_url_to_path, project_url_to_relative_path, validate_url};
 use crate::uxml_schema_manager::VisualElementsData;
 use std::collections::HashMap;
 use tower_lsp::lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position, Url};
 use tree_sitter::{Node, Tree};
 use crate::uss::constants::*;
+use crate::uss::unit_data::{unit_conversions, unit_in_property};
 
 /// USS Hover Provider
 /// 
@@ -28,7 +33,19 @@ use crate::uss::constants::*;
 /// This includes property descriptions, documentation URLs, Unity version compatibility,
 /// function information, unit descriptions, and UXML element details.
 pub struct UssHoverProvider {
-    definitions: UssDefinitions,
+    definitions: &'static UssDefinitions,
+    /// Statistics of imported files by path, computed when an import is hovered
+    imported_files: Mutex<HashMap<PathBuf, ImportedFileStats>>,
+}
+
+/// Size of an imported style sheet and what it defines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ImportedFileStats {
+    /// Modification time when the stats were computed, with the size to know if they are outdated
+    modified: Option<SystemTime>,
+    size: u64,
+    rules: usize,
+    variables: usize,
 }
 
 impl UssHoverProvider {
@@ -39,7 +56,8 @@ impl UssHoverProvider {
     /// A new `UssHoverProvider` instance ready to provide hover information for USS code.
     pub fn new() -> Self {
         Self {
-            definitions: UssDefinitions::new(),
+            definitions: UssDefinitions::shared(),
+            imported_files: Mutex::new(HashMap::new()),
         }
     }
 
@@ -82,6 +100,23 @@ impl UssHoverProvider {
         unity_manager: &UnityProjectManager,
         source_url: Option<&Url>,
         uxml_data: Option<Arc<Mutex<VisualElementsData>>>,
+    ) -> Option<Hover> {
+        self.hover_with_variables(tree, source, position, unity_manager, source_url, uxml_data, None)
+    }
+
+    /// Provides hover information like [`Self::hover`], with variables of the document
+    ///
+    /// With a variable resolver, hovering `var(--name)` shows the variable's documentation
+    /// comment and value instead of the documentation of the `var()` function.
+    pub fn hover_with_variables(
+        &self,
+        tree: &Tree,
+        source: &str,
+        position: Position,
+        unity_manager: &UnityProjectManager,
+        source_url: Option<&Url>,
+        uxml_data: Option<Arc<Mutex<VisualElementsData>>>,
+        variable_resolver: Option<&VariableResolver>,
     ) -> Option<Hover> {
         // Find the deepest node at the position
         let node = find_node_at_position(tree.root_node(), position)?;
@@ -94,14 +129,16 @@ impl UssHoverProvider {
         // Priority 1: Functions (including URL functions)
         if let Some(call_node) = find_node_of_type_at_position(tree.root_node(), source, position, NODE_CALL_EXPRESSION) {
             if !has_error_nodes(call_node) {
-                if let Some(hover) = self.hover_for_function(call_node, source, unity_manager, source_url) {
+                if let Some(hover) = self.hover_for_function(call_node, source, unity_manager, source_url, variable_resolver) {
                     return Some(hover);
                 }
             }
         }
 
-        // Priority 2: Units
-        if let Some(unit_node) = find_node_of_type_at_position(tree.root_node(), source, position, NODE_UNIT) {
+        // Priority 2: Units, and numbers with a unit
+        let unit_node = find_node_of_type_at_position(tree.root_node(), source, position, NODE_UNIT)
+            .or_else(|| dimension_unit(node));
+        if let Some(unit_node) = unit_node {
             if !has_error_nodes(unit_node) {
                 if let Some(hover) = self.hover_for_unit(unit_node, source) {
                     return Some(hover);
@@ -119,7 +156,7 @@ impl UssHoverProvider {
         // Priority 4: Tag selectors
         if let Some(tag_node) = find_node_of_type_at_position(tree.root_node(), source, position, NODE_TAG_NAME) {
             if !has_error_nodes(tag_node) {
-                if let Some(hover) = self.hover_for_tag_selector(tag_node, source, uxml_data) {
+                if let Some(hover) = self.hover_for_tag_selector(tag_node, source, uxml_data, unity_manager) {
                     return Some(hover);
                 }
             }
@@ -144,7 +181,7 @@ impl UssHoverProvider {
     /// Provides hover information for @import statements.
     /// 
     /// Analyzes import statements and provides information about the imported file,
-    /// including file existence, resolved paths, and usage documentation.
+    /// including file existence, resolved paths, size, rules and variables, and usage documentation.
     fn hover_for_import_statement(
         &self,
         import_node: Node,
@@ -163,11 +200,18 @@ impl UssHoverProvider {
                 if let Some(relative_path) = project_url_to_relative_path(&url){
                     content.push_str(&format!("Asset path: `{}`\n\n", relative_path));
                 }
+                content.push_str(&format!("Path: `{}`\n\n", file_path.display()));
         
-                if file_path.exists() {
+                if let Some(stats) = self.imported_file_stats(&file_path) {
                     if let Ok(file_url) = Url::from_file_path(&file_path) {
                         content.push_str(&format!("📁 [Open file]({})\n\n", file_url));
                     }
+                    content.push_str(&format!(
+                        "Size: {} · {} · {}\n\n",
+                        format_file_size(stats.size),
+                        count_text(stats.rules, "rule"),
+                        count_text(stats.variables, "variable")
+                    ));
                 } else {
                     content.push_str("❌ File not found\n\n");
                 }
@@ -203,13 +247,25 @@ impl UssHoverProvider {
         source: &str,
         unity_manager: &UnityProjectManager,
         source_url: Option<&Url>,
+        variable_resolver: Option<&VariableResolver>,
     ) -> Option<Hover> {
         let function_node = FunctionNode::from_node(call_node, source, None)?;
         
         // Special handling for url() function with file links
         if function_node.function_name == "url" {
+            // the path of an import, show the imported file
+            if let Some(import_node) = call_node.parent().filter(|p| p.kind() == NODE_IMPORT_STATEMENT) {
+                return self.hover_for_import_statement(import_node, source, unity_manager, source_url);
+            }
             return self.hover_for_url_function(call_node, source, unity_manager, source_url);
         }
+
+        // Show the referenced variable for var() function
+        if function_node.function_name == "var" {
+            if let Some(hover) = variable_resolver.and_then(|r| self.hover_for_variable(&function_node, source, r)) {
+                return Some(hover);
+            }
+        }
         
         // Use structured data for all other functions
         if let Some(function_info) = self.definitions.get_function_info(&function_node.function_name) {
@@ -227,6 +283,28 @@ impl UssHoverProvider {
         }
     }
 
+    /// Provides hover information for the variable referenced by a var() function.
+    ///
+    fn hover_for_variable(
+        &self,
+        function_node: &FunctionNode,
+        source: &str,
+        variable_resolver: &VariableResolver,
+    ) -> Option<Hover> {
+        let argument = function_node.argument_nodes.first()?;
+        let text = argument.utf8_text(source.as_bytes()).ok()?;
+        let name = text.strip_prefix("--")?;
+        let content = variable_resolver.create_documentation(name)?;
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: content,
+            }),
+            range: None,
+        })
+    }
+
     /// Provides hover information for url() functions.
     /// 
     /// Analyzes url() function calls and provides information about the referenced
@@ -287,6 +365,38 @@ impl UssHoverProvider {
         }
     }
 
+    /// Statistics of an imported file, `None` if it doesn't exist
+    ///
+    /// The file is parsed the first time it is hovered, then again only if its modification time or size changed.
+    fn imported_file_stats(&self, path: &Path) -> Option<ImportedFileStats> {
+        let metadata = std::fs::metadata(path).ok().filter(|m| m.is_file())?;
+        let modified = metadata.modified().ok();
+        let size = metadata.len();
+
+        if let Ok(cache) = self.imported_files.lock() {
+            if let Some(stats) = cache.get(path).filter(|s| s.modified == modified && s.size == size) {
+                return Some(*stats);
+            }
+        }
+
+        let content = std::fs::read_to_string(path).ok()?;
+        let tree = UssParser::new().ok()?.parse(&content, None)?;
+        let mut rules = 0;
+        let mut variables = HashSet::new();
+        count_definitions(tree.root_node(), &content, &mut rules, &mut variables);
+
+        let stats = ImportedFileStats {
+            modified,
+            size,
+            rules,
+            variables: variables.len(),
+        };
+        if let Ok(mut cache) = self.imported_files.lock() {
+            cache.insert(path.to_path_buf(), stats);
+        }
+        Some(stats)
+    }
+
     /// Resolves an import file path to an absolute file system path
     fn resolve_import_file_path(
         &self,
@@ -308,12 +418,27 @@ impl UssHoverProvider {
     /// Provides hover information for CSS units.
     /// 
     /// Analyzes unit identifiers (px, %, deg, s, etc.) and provides documentation
-    /// about their meaning and usage in USS.
+    /// about their meaning and usage in USS, with the value converted to the other units
+    /// of its category and what the unit means in the enclosing property.
     fn hover_for_unit(&self, unit_node: Node, source: &str) -> Option<Hover> {
         let unit_text = unit_node.utf8_text(source.as_bytes()).ok()?;
         
         let unit_info = self.definitions.get_unit_info(unit_text)?;
-        let content = unit_info.create_documentation();
+        let mut content = unit_info.create_documentation();
+
+        let number = unit_node
+            .parent()
+            .and_then(|value| source.get(value.start_byte()..unit_node.start_byte()))
+            .and_then(|number| number.parse::<f64>().ok());
+        if let Some(conversions) = number.and_then(|number| unit_conversions(number, unit_text)) {
+            content.push_str("\n\n");
+            content.push_str(&conversions);
+        }
+
+        if let Some(meaning) = enclosing_property_name(unit_node, source).and_then(|p| unit_in_property(unit_text, p)) {
+            content.push_str("\n\n");
+            content.push_str(&meaning);
+        }
 
         Some(Hover {
             contents: HoverContents::Markup(MarkupContent {
@@ -328,7 +453,15 @@ impl UssHoverProvider {
     /// 
     /// Analyzes tag selectors that target UXML elements and provides information
     /// about the element type, including its fully qualified name when available.
-    fn hover_for_tag_selector(&self, tag_node: Node, source: &str, uxml_data: Option<Arc<Mutex<VisualElementsData>>>) -> Option<Hover> {
+    /// Built-in elements link to their scripting reference, the documentation of custom elements is added
+    /// by the server from their C# source, see [`tag_name_at_position`].
+    fn hover_for_tag_selector(
+        &self,
+        tag_node: Node,
+        source: &str,
+        uxml_data: Option<Arc<Mutex<VisualElementsData>>>,
+        unity_manager: &UnityProjectManager,
+    ) -> Option<Hover> {
         let tag_text = tag_node.utf8_text(source.as_bytes()).ok()?;
         
         // default text
@@ -343,7 +476,20 @@ impl UssHoverProvider {
                     content = format!(
                         "### UXML Element {}\n**Full Name:** `{}`\n\nThis selector targets all `{}` elements in the UI hierarchy.",
                         tag_text, fully_qualified_name, tag_text
-                    )
+                    );
+                    let ancestors = elements.ancestors(fully_qualified_name);
+                    if !ancestors.is_empty() {
+                        let names: Vec<String> = ancestors.iter().map(|a| format!("`{}`", a.name)).collect();
+                        content.push_str(&format!("\n\n**Inherits:** {}", names.join(" → ")));
+                    }
+                    if let Some(class_path) = built_in_class_path(fully_qualified_name) {
+                        let unity_version_for_docs = unity_manager.get_unity_version_for_docs()
+                            .unwrap_or_else(|| DEFAULT_DOCS_VERSION.to_string());
+                        content.push_str(&format!(
+                            "\n\n[📖 Documentation](https://docs.unity3d.com/{}/Documentation/ScriptReference/{}.html)",
+                            unity_version_for_docs, class_path
+                        ));
+                    }
                 } else {
                     content = format!("### UXML Element {}\n⚠️ Element not found in UXML schema.", tag_text)
                 }
@@ -415,9 +561,9 @@ impl UssHoverProvider {
         let pseudo_info = self.definitions.get_pseudo_class_info(pseudo_class)?;
         
         let unity_version_for_docs = unity_manager.get_unity_version_for_docs()
-            .unwrap_or_else(|| "6000.0".to_string());
+            .unwrap_or_else(|| DEFAULT_DOCS_VERSION.to_string());
         
-        let content = pseudo_info.create_documentation(&unity_version_for_docs);
+        let content = pseudo_info.create_hover_documentation(&unity_version_for_docs);
         
         Some(Hover {
             contents: HoverContents::Markup(MarkupContent {
@@ -490,8 +636,7 @@ impl UssHoverProvider {
         }
         
         // Check if this is a keyword
-        if let Some(keyword_info) = self.definitions.get_keyword_info(value_text) {
-            let content = keyword_info.create_documentation(Some(property_name));
+        if let Some(content) = self.definitions.keyword_documentation(value_text, property_name) {
             return Some(Hover {
                 contents: HoverContents::Markup(MarkupContent {
                     kind: MarkupKind::Markdown,
@@ -514,7 +659,7 @@ impl UssHoverProvider {
         unity_manager: &UnityProjectManager,
     ) -> Hover {
         let unity_version_for_docs = unity_manager.get_unity_version_for_docs()
-            .unwrap_or_else(|| "6000.0".to_string());
+            .unwrap_or_else(|| DEFAULT_DOCS_VERSION.to_string());
         let property_info = self.definitions.get_property_info(property_name);
         if property_info.is_none(){
             return Hover{
@@ -540,6 +685,90 @@ impl UssHoverProvider {
     }
 }
 
+/// The tag name of a type selector at a position, e.g. `Button` in `Button:hover`
+pub fn tag_name_at_position(tree: &Tree, source: &str, position: Position) -> Option<String> {
+    let tag_node = find_node_of_type_at_position(tree.root_node(), source, position, NODE_TAG_NAME)?;
+    if has_error_nodes(tag_node) {
+        return None;
+    }
+    tag_node.utf8_text(source.as_bytes()).ok().map(|text| text.to_string())
+}
+
+/// Path of a built-in element in the scripting reference, e.g. `UIElements.Button`
+///
+/// `None` for elements of other namespaces, like custom elements.
+pub fn built_in_class_path(fully_qualified_name: &str) -> Option<String> {
+    ["UnityEngine.", "UnityEditor."].iter().find_map(|prefix| {
+        let class_path = fully_qualified_name.strip_prefix(prefix)?;
+        class_path.starts_with("UIElements.").then(|| class_path.to_string())
+    })
+}
+
+/// The unit of a number with a unit (e.g. `10px`), when hovering its number part
+fn dimension_unit(node: Node) -> Option<Node> {
+    if node.kind() != NODE_INTEGER_VALUE && node.kind() != NODE_FLOAT_VALUE {
+        return None;
+    }
+    let mut cursor = node.walk();
+    let unit = node.children(&mut cursor).find(|child| child.kind() == NODE_UNIT);
+    unit
+}
+
+/// Name of the property of the declaration a node is in
+fn enclosing_property_name<'a>(node: Node, source: &'a str) -> Option<&'a str> {
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        if parent.kind() == NODE_DECLARATION {
+            let property = parent.child(0).filter(|n| n.kind() == NODE_PROPERTY_NAME)?;
+            return property.utf8_text(source.as_bytes()).ok();
+        }
+        current = parent.parent();
+    }
+    None
+}
+
+/// Count the rule sets under a node, and collect the names of the variables declared
+fn count_definitions<'a>(node: Node, source: &'a str, rules: &mut usize, variables: &mut HashSet<&'a str>) {
+    match node.kind() {
+        NODE_RULE_SET => *rules += 1,
+        NODE_DECLARATION => {
+            let name = node.child(0).filter(|n| n.kind() == NODE_PROPERTY_NAME).and_then(|n| n.utf8_text(source.as_bytes()).ok());
+            if let Some(name) = name.filter(|name| name.starts_with("--")) {
+                variables.insert(name);
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        count_definitions(child, source, rules, variables);
+    }
+}
+
+/// File size for display, e.g. `512 bytes` or `1.5 KB`
+fn format_file_size(size: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = KB * 1024;
+    if size < KB {
+        count_text(size as usize, "byte")
+    } else if size < MB {
+        format!("{:.1} KB", size as f64 / KB as f64)
+    } else {
+        format!("{:.1} MB", size as f64 / MB as f64)
+    }
+}
+
+/// A count with a noun, pluralized with `s`
+fn count_text(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
 impl Default for UssHoverProvider {
     fn default() -> Self {
         Self::new()
@@ -182,3 +182,219 @@ fn test_pseudo_class_hover_precision() {
 
     println!("✅ Pseudo-class hover detection is working correctly");
 }
+
+#[test]
+fn test_pseudo_class_hover_examples_and_combinations() {
+    let mut parser = UssParser::new().expect("Failed to create USS parser");
+    let hover_provider = UssHoverProvider::new();
+    let unity_manager = UnityProjectManager::new(PathBuf::from("."));
+
+    let source = ".mute-toggle:checked {\n    color: red;\n}\n.save-button:disabled {\n    opacity: 0.5;\n}";
+    let tree = parser.parse(source, None).expect("Failed to parse USS");
+
+    let hover_text = |position: Position| match hover_provider.hover(&tree, source, position, &unity_manager, None, None) {
+        Some(hover) => match hover.contents {
+            tower_lsp::lsp_types::HoverContents::Markup(markup) => markup.value,
+            _ => String::new(),
+        },
+        None => panic!("Expected hover at {:?}", position),
+    };
+
+    let content = hover_text(Position::new(0, 15));
+    assert!(content.contains("<ui:Toggle"), "{}", content);
+    assert!(content.contains(".mute-toggle:checked"), "{}", content);
+    assert!(content.contains("| `:checked:hover` | yes |"), "{}", content);
+    assert!(!content.contains("`:checked:checked`"), "{}", content);
+    assert!(content.contains("/6000.0/Documentation/Manual/UIE-USS-Selectors-Pseudo-Classes.html#"), "{}", content);
+
+    let content = hover_text(Position::new(3, 16));
+    assert!(content.contains("| `:disabled:enabled` | never |"), "{}", content);
+    assert!(content.contains("| `:disabled:active` | yes |"), "{}", content);
+}
+
+#[test]
+fn test_hover_for_documented_variable() {
+    use crate::uss::definitions::UssDefinitions;
+    use crate::uss::variable_resolver::VariableResolver;
+
+    let provider = UssHoverProvider::new();
+    let unity_manager = UnityProjectManager::new(PathBuf::from("/test/project"));
+    let mut parser = UssParser::new().unwrap();
+
+    let content = ":root {\n    /* Primary brand color */\n    --brand: #ff0000;\n}\n.a { color: var(--brand); }";
+    let tree = parser.parse(content, None).unwrap();
+    let mut resolver = VariableResolver::new(UssDefinitions::shared());
+    resolver.add_variables_from_tree(tree.root_node(), content);
+
+    // hover over "--brand" in var(--brand)
+    let position = Position::new(4, 19);
+    let hover = provider
+        .hover_with_variables(&tree, content, position, &unity_manager, None, None, Some(&resolver))
+        .expect("Should have hover for variable");
+    let HoverContents::Markup(markup) = hover.contents else {
+        panic!("Expected markup content");
+    };
+    assert!(markup.value.contains("### Variable --brand"), "{}", markup.value);
+    assert!(markup.value.contains("Primary brand color"), "{}", markup.value);
+    assert!(markup.value.contains("**Value:**"), "{}", markup.value);
+
+    // without variables the function documentation is shown
+    let hover = provider.hover(&tree, content, position, &unity_manager, None, None).unwrap();
+    let HoverContents::Markup(markup) = hover.contents else {
+        panic!("Expected markup content");
+    };
+    assert!(!markup.value.contains("Primary brand color"));
+}
+
+#[test]
+fn test_hover_for_units_in_property() {
+    let provider = UssHoverProvider::new();
+    let unity_manager = UnityProjectManager::new(PathBuf::from("/test/project"));
+    let mut parser = UssParser::new().unwrap();
+
+    let content = ".a {\n    translate: 50% 10px;\n    rotate: 90deg;\n    transition-duration: 250ms;\n}";
+    let tree = parser.parse(content, None).unwrap();
+    let hover_text = |line, character| {
+        let hover = provider.hover(&tree, content, Position::new(line, character), &unity_manager, None, None)?;
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("Expected markup content");
+        };
+        Some(markup.value)
+    };
+
+    // on the unit and on the number
+    for character in [17, 15] {
+        let text = hover_text(1, character).expect("Should have hover for %");
+        assert!(text.contains("### Unit %"), "{}", text);
+        assert!(text.contains("In `translate`, percentages are relative to the element's own size"), "{}", text);
+    }
+
+    let text = hover_text(1, 21).expect("Should have hover for px");
+    assert!(text.contains("Device-independent pixels"), "{}", text);
+
+    let text = hover_text(2, 14).expect("Should have hover for deg");
+    assert!(text.contains("`90deg` = `1.5708rad` = `100grad` = `0.25turn`"), "{}", text);
+
+    let text = hover_text(3, 27).expect("Should have hover for ms");
+    assert!(text.contains("`0.25s` = `250ms`"), "{}", text);
+}
+
+#[test]
+fn test_hover_for_keyword_in_property() {
+    let provider = UssHoverProvider::new();
+    let unity_manager = UnityProjectManager::new(PathBuf::from("/test/project"));
+    let mut parser = UssParser::new().unwrap();
+
+    let content = ".a {\n    justify-content: space-between;\n    align-items: space-between;\n}";
+    let tree = parser.parse(content, None).unwrap();
+    let hover_text = |line, character| {
+        let hover = provider.hover(&tree, content, Position::new(line, character), &unity_manager, None, None)?;
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("Expected markup content");
+        };
+        Some(markup.value)
+    };
+
+    // the keyword, with the property it sets
+    let text = hover_text(1, 25).expect("Should have hover for space-between");
+    assert!(text.starts_with("### Keyword `space-between`\nItems are evenly distributed"), "{}", text);
+    assert!(text.contains("**Property `justify-content`:** Justification of children"), "{}", text);
+    assert!(text.contains("**Accepted values:** `flex-start | flex-end | center | space-between | space-around`"), "{}", text);
+    assert!(!text.contains("is not a value of"), "{}", text);
+
+    let text = hover_text(2, 21).expect("Should have hover for space-between");
+    assert!(text.contains("*`space-between` is not a value of `align-items`*"), "{}", text);
+}
+
+#[test]
+fn test_hover_for_import_shows_file_stats() {
+    let project = tempfile::TempDir::new().unwrap();
+    let assets = project.path().join("Assets");
+    std::fs::create_dir_all(&assets).unwrap();
+    let common = assets.join("common.uss");
+    std::fs::write(&common, ":root { --a: red; --b: 1px; }\n.x { --a: blue; }\n.y { color: var(--a); }\n").unwrap();
+
+    let provider = UssHoverProvider::new();
+    let unity_manager = UnityProjectManager::new(project.path().to_path_buf());
+    let mut parser = UssParser::new().unwrap();
+    let source_url = Url::parse("project:///Assets/main.uss").unwrap();
+
+    let content = "@import \"common.uss\";\n@import url(\"missing.uss\");\n";
+    let tree = parser.parse(content, None).unwrap();
+    let hover_text = |line, character| {
+        let hover = provider
+            .hover(&tree, content, Position::new(line, character), &unity_manager, Some(&source_url), None)
+            .unwrap();
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("Expected markup content");
+        };
+        markup.value
+    };
+
+    let text = hover_text(0, 10);
+    assert!(text.contains("Asset path: `Assets/common.uss`"), "{}", text);
+    assert!(text.contains(&format!("Path: `{}`", common.display())), "{}", text);
+    assert!(text.contains("[Open file]"), "{}", text);
+    assert!(text.contains("Size: 72 bytes · 3 rules · 2 variables"), "{}", text);
+
+    // the cached stats are recomputed when the file changes
+    std::fs::write(&common, ".x { --c: 2px; }\n").unwrap();
+    let text = hover_text(0, 10);
+    assert!(text.contains("Size: 17 bytes · 1 rule · 1 variable"), "{}", text);
+
+    // on the url() of an import
+    let text = hover_text(1, 15);
+    assert!(text.contains("**@import Statement**"), "{}", text);
+    assert!(text.contains("❌ File not found"), "{}", text);
+}
+
+#[test]
+fn test_tag_selector_hover() {
+    use crate::uxml_schema_manager::{VisualElementInfo, VisualElementsData};
+    use std::sync::{Arc, Mutex};
+
+    let mut data = VisualElementsData::new();
+    for (namespace, name, base) in [
+        ("UnityEngine.UIElements", "VisualElement", None),
+        ("UnityEngine.UIElements", "Button", Some("UnityEngine.UIElements.VisualElement")),
+        ("MyGame.UI", "HealthBar", None),
+    ] {
+        let fully_qualified_name = format!("{}.{}", namespace, name);
+        data.insert(fully_qualified_name.clone(), VisualElementInfo {
+            name: name.to_string(),
+            namespace: namespace.to_string(),
+            fully_qualified_name,
+            attributes: Vec::new(),
+            base: base.map(str::to_string),
+        });
+    }
+    let data = Arc::new(Mutex::new(data));
+
+    let provider = UssHoverProvider::new();
+    let unity_manager = UnityProjectManager::new(PathBuf::from("/test/project"));
+    let mut parser = UssParser::new().unwrap();
+    let source = "Button > HealthBar { color: red; }";
+    let tree = parser.parse(source, None).unwrap();
+    let hover_text = |character| {
+        let hover = provider.hover(&tree, source, Position::new(0, character), &unity_manager, None, Some(data.clone()));
+        match hover.unwrap().contents {
+            tower_lsp::lsp_types::HoverContents::Markup(markup) => markup.value,
+            _ => String::new(),
+        }
+    };
+
+    let button = hover_text(2);
+    assert!(button.contains("`UnityEngine.UIElements.Button`"), "{}", button);
+    assert!(button.contains("/Documentation/ScriptReference/UIElements.Button.html"), "{}", button);
+    assert!(button.contains("**Inherits:** `VisualElement`"), "{}", button);
+    // the documentation of custom elements comes from their C# source
+    let health_bar = hover_text(12);
+    assert!(health_bar.contains("`MyGame.UI.HealthBar`"), "{}", health_bar);
+    assert!(!health_bar.contains("ScriptReference"), "{}", health_bar);
+    assert!(!health_bar.contains("Inherits"), "{}", health_bar);
+
+    assert_eq!(tag_name_at_position(&tree, source, Position::new(0, 12)).as_deref(), Some("HealthBar"));
+    assert_eq!(tag_name_at_position(&tree, source, Position::new(0, 22)), None);
+    assert_eq!(built_in_class_path("UnityEditor.UIElements.Toolbar").as_deref(), Some("UIElements.Toolbar"));
+    assert_eq!(built_in_class_path("MyGame.UI.HealthBar"), None);
+}
@@ -0,0 +1,494 @@
+//! USS Import Graph
+//!
+//! Graph of all USS files in a Unity project (nodes) and their `@import` statements (edges).
+//! Used to report circular imports and imports of files that don't exist,
+//! and can be exported as JSON for visualization tools.
+//!
+//! Files are identified by their project URL (e.g. `project:/Assets/UI/main.uss`).
+//! Like the UXML usage index, the graph is [scanned](crate::language::project_scan) again when it's stale
+//! and refreshed incrementally, only files whose modification time changed are parsed again.
+//! Open documents can override the imports of a file with their unsaved content.
+//! The indexed files can be saved to the [index cache](crate::language::index_cache) to start from in the next session.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Range};
+use tree_sitter::{Node, Tree};
+use url::Url;
+
+use crate::language::asset_url::{
+    create_project_url, create_project_url_with_normalization, project_url_to_path, project_url_to_relative_path,
+    validate_url_import,
+};
+use crate::language::index_cache::{load_index, save_index};
+use crate::language::progress::ProgressCounter;
+use crate::language::project_scan::{find_files, ScanTimer};
+use crate::language::tree_utils::node_to_range;
+use crate::uss::constants::*;
+use crate::uss::definitions::UssDefinitions;
+use crate::uss::import_node::ImportNode;
+use crate::uss::parser::UssParser;
+use crate::uss::unused_variables::variable_references;
+use crate::uss::value::UssValue;
+
+/// Command that returns the import graph as JSON, see [`ImportGraphJson`]
+pub const DUMP_IMPORT_GRAPH_COMMAND: &str = "uss.dumpImportGraph";
+
+/// Name of the graph in the index cache
+const CACHE_NAME: &str = "uss_imports";
+
+/// An `@import` statement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportEdge {
+    /// The imported url, resolved against the importing file
+    pub url: Url,
+    /// Range of the import argument in the importing file
+    pub range: Range,
+}
+
+/// Style sheets read by [`UssImportGraph::read_changed_files`], `None` for files that can't be read
+pub struct ChangedStyleSheets {
+    files: Vec<(Url, Option<IndexedUssFile>)>,
+}
+
+/// Imports of a single USS file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedUssFile {
+    last_modified: Option<SystemTime>,
+    imports: Vec<ImportEdge>,
+}
+
+/// Import graph of all USS files in a project
+#[derive(Clone)]
+pub struct UssImportGraph {
+    project_root: PathBuf,
+    files: HashMap<Url, IndexedUssFile>,
+    /// Imports of open documents, these take precedence over the files on disk
+    overrides: HashMap<Url, Vec<ImportEdge>>,
+    scan: ScanTimer,
+    definitions: &'static UssDefinitions,
+}
+
+impl UssImportGraph {
+    pub fn new(project_root: PathBuf) -> Self {
+        Self {
+            project_root,
+            files: HashMap::new(),
+            overrides: HashMap::new(),
+            scan: ScanTimer::default(),
+            definitions: UssDefinitions::shared(),
+        }
+    }
+
+    /// Use the files indexed by another graph of the same project, e.g. a clone refreshed on another thread
+    ///
+    /// Imports of open documents are kept.
+    pub fn adopt_index(&mut self, refreshed: UssImportGraph) {
+        self.files = refreshed.files;
+        self.scan = refreshed.scan;
+    }
+
+    /// Start from the files indexed in the last session, true if there were any
+    ///
+    /// The graph is not indexed yet, the next refresh parses the files that changed since.
+    pub fn load_cache(&mut self) -> bool {
+        let Some(files) = load_index::<HashMap<Url, IndexedUssFile>>(&self.project_root, CACHE_NAME) else {
+            return false;
+        };
+        self.files = files;
+        !self.files.is_empty()
+    }
+
+    /// Save the indexed files for the next session, imports of open documents are not saved
+    pub fn save_cache(&self) -> io::Result<()> {
+        save_index(&self.project_root, CACHE_NAME, &self.files)
+    }
+
+    /// Whether the project was scanned at least once
+    pub fn is_indexed(&self) -> bool {
+        self.scan.is_scanned()
+    }
+
+    /// Whether the last scan is older than the refresh interval
+    pub fn is_stale(&self) -> bool {
+        self.scan.is_stale()
+    }
+
+    /// Scan the project for USS files, parsing new and modified files and dropping deleted ones
+    ///
+    /// Imported files outside of the scanned directories are indexed too, so cycles through them are found.
+    pub fn refresh(&mut self) {
+        self.refresh_with_progress(&ProgressCounter::new());
+    }
+
+    /// [`refresh`](Self::refresh), each indexed file advances `progress`
+    pub fn refresh_with_progress(&mut self, progress: &ProgressCounter) {
+        let root = fs::canonicalize(&self.project_root).unwrap_or_else(|_| self.project_root.clone());
+
+        let found = find_style_sheets(&root);
+        progress.add_total(found.len());
+
+        let mut pending: Vec<Url> = found
+            .iter()
+            .filter_map(|path| create_project_url(path, &root).ok())
+            .collect();
+        let mut visited = HashSet::new();
+        let mut parser = UssParser::new().ok();
+
+        while let Some(url) = pending.pop() {
+            if !visited.insert(url.clone()) {
+                continue;
+            }
+            progress.advance();
+            let Some(path) = project_url_to_path(&root, &url) else {
+                continue;
+            };
+
+            let last_modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            let is_current = self
+                .files
+                .get(&url)
+                .is_some_and(|f| f.last_modified.is_some() && f.last_modified == last_modified);
+
+            if !is_current {
+                match self.read_file(parser.as_mut(), &url, &path) {
+                    Some(file) => {
+                        self.files.insert(url.clone(), file);
+                    }
+                    None => {
+                        self.files.remove(&url);
+                        continue;
+                    }
+                }
+            }
+
+            pending.extend(self.imports_of(&url).iter().map(|edge| edge.url.clone()));
+        }
+
+        self.files.retain(|url, _| visited.contains(url));
+        self.scan.mark_scanned();
+    }
+
+    /// Read files the editor reported as created, changed or deleted, without scanning the project
+    ///
+    /// Only the project of the graph is used, so any graph of the project can read them. Reads the
+    /// files, async code should call it with `spawn_blocking` and apply them with [`apply_changed_files`](Self::apply_changed_files).
+    pub fn read_changed_files(&self, paths: &[PathBuf]) -> ChangedStyleSheets {
+        let mut parser = UssParser::new().ok();
+        let files = paths
+            .iter()
+            .filter_map(|path| {
+                let url = create_project_url_with_normalization(path, &self.project_root).ok()?;
+                let file = self.read_file(parser.as_mut(), &url, path);
+                Some((url, file))
+            })
+            .collect();
+        ChangedStyleSheets { files }
+    }
+
+    /// Index the files read by [`read_changed_files`](Self::read_changed_files), files that no longer exist are dropped
+    pub fn apply_changed_files(&mut self, changed: ChangedStyleSheets) {
+        for (url, file) in changed.files {
+            match file {
+                Some(file) => {
+                    self.files.insert(url, file);
+                }
+                None => {
+                    self.files.remove(&url);
+                }
+            }
+        }
+    }
+
+    /// Read a file and extract its imports, `None` if it can't be read
+    fn read_file(&self, parser: Option<&mut UssParser>, url: &Url, path: &Path) -> Option<IndexedUssFile> {
+        let last_modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+        let content = fs::read_to_string(path).ok()?;
+        let imports = parser
+            .and_then(|p| p.parse(&content, None))
+            .map(|tree| self.extract_imports(&tree, &content, url))
+            .unwrap_or_default();
+        Some(IndexedUssFile { last_modified, imports })
+    }
+
+    /// Extract the imports of a parsed USS file
+    ///
+    /// `source_url` is the project URL of the file, relative imports are resolved against it.
+    pub fn extract_imports(&self, tree: &Tree, content: &str, source_url: &Url) -> Vec<ImportEdge> {
+        let root = tree.root_node();
+        let mut imports = Vec::new();
+        for i in 0..root.child_count() {
+            if let Some(child) = root.child(i) {
+                if let Some(edge) = self.import_edge(child, content, source_url) {
+                    imports.push(edge);
+                }
+            }
+        }
+        imports
+    }
+
+    fn import_edge(&self, node: Node, content: &str, source_url: &Url) -> Option<ImportEdge> {
+        if node.kind() != NODE_IMPORT_STATEMENT {
+            return None;
+        }
+
+        // diagnostics of the statement itself are reported by regular diagnostics
+        let mut diagnostics = Vec::new();
+        let import = ImportNode::from_node(node, content, &mut diagnostics)?;
+        let url = match UssValue::from_node(import.argument_node, content, &self.definitions, Some(source_url)).ok()? {
+            UssValue::String(path) => validate_url_import(&path, Some(source_url)).ok()?.url,
+            UssValue::Url(url) => url,
+            _ => return None,
+        };
+
+        Some(ImportEdge {
+            url: normalize_url(url),
+            range: node_to_range(import.argument_node, content),
+        })
+    }
+
+    /// Override the imports of a file with the imports of its open document
+    pub fn set_document_imports(&mut self, url: Url, imports: Vec<ImportEdge>) {
+        self.overrides.insert(url, imports);
+    }
+
+    /// Remove the override of a closed document, the file on disk is used again
+    pub fn remove_document_imports(&mut self, url: &Url) {
+        self.overrides.remove(url);
+    }
+
+    /// Imports of a file, from its open document if there is one
+    pub fn imports_of(&self, url: &Url) -> &[ImportEdge] {
+        if let Some(imports) = self.overrides.get(url) {
+            return imports;
+        }
+        self.files.get(url).map_or(&[], |f| &f.imports)
+    }
+
+    /// All files in the graph, sorted
+    pub fn file_urls(&self) -> Vec<&Url> {
+        let mut urls: HashSet<&Url> = self.files.keys().collect();
+        urls.extend(self.overrides.keys());
+        let mut urls: Vec<&Url> = urls.into_iter().collect();
+        urls.sort();
+        urls
+    }
+
+    /// Files that import `url`, directly or through other files, sorted
+    pub fn importers_of(&self, url: &Url) -> Vec<Url> {
+        let mut importers: HashSet<Url> = HashSet::new();
+        let mut pending = vec![url.clone()];
+        while let Some(current) = pending.pop() {
+            for file in self.file_urls() {
+                let imports_current = self.imports_of(file).iter().any(|edge| edge.url == current);
+                if imports_current && file != url && importers.insert(file.clone()) {
+                    pending.push(file.clone());
+                }
+            }
+        }
+
+        let mut importers: Vec<Url> = importers.into_iter().collect();
+        importers.sort();
+        importers
+    }
+
+    /// Files `url` imports, directly or through other files, sorted
+    pub fn imports_transitively(&self, url: &Url) -> Vec<Url> {
+        let mut imported: HashSet<Url> = HashSet::new();
+        let mut pending = vec![url.clone()];
+        while let Some(current) = pending.pop() {
+            for edge in self.imports_of(&current) {
+                if &edge.url != url && imported.insert(edge.url.clone()) {
+                    pending.push(edge.url.clone());
+                }
+            }
+        }
+
+        let mut imported: Vec<Url> = imported.into_iter().collect();
+        imported.sort();
+        imported
+    }
+
+    /// Find an import chain from `from` to `to`, both included, `None` if `to` is not reachable
+    pub fn find_import_path(&self, from: &Url, to: &Url) -> Option<Vec<Url>> {
+        let mut visited = HashSet::new();
+        let mut chain = vec![from.clone()];
+        if self.find_import_path_recursive(from, to, &mut visited, &mut chain) {
+            Some(chain)
+        } else {
+            None
+        }
+    }
+
+    fn find_import_path_recursive(
+        &self,
+        current: &Url,
+        to: &Url,
+        visited: &mut HashSet<Url>,
+        chain: &mut Vec<Url>,
+    ) -> bool {
+        if current == to {
+            return true;
+        }
+        if !visited.insert(current.clone()) {
+            return false;
+        }
+
+        for edge in self.imports_of(current) {
+            chain.push(edge.url.clone());
+            if self.find_import_path_recursive(&edge.url, to, visited, chain) {
+                return true;
+            }
+            chain.pop();
+        }
+        false
+    }
+
+    /// Diagnostics for the imports of a file: circular imports and imports of files that don't exist
+    pub fn import_diagnostics(&self, url: &Url) -> Vec<Diagnostic> {
+        self.import_diagnostics_of(url, self.imports_of(url))
+    }
+
+    /// [`import_diagnostics`](Self::import_diagnostics) for the given imports of a file, which doesn't need to be
+    /// in the graph, e.g. a theme style sheet
+    pub fn import_diagnostics_of(&self, url: &Url, imports: &[ImportEdge]) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        for edge in imports {
+            if edge.url.scheme() != PROJECT_SCHEME {
+                continue;
+            }
+
+            let exists = project_url_to_path(&self.project_root, &edge.url).is_some_and(|p| p.exists());
+            if !exists {
+                diagnostics.push(Diagnostic {
+                    range: edge.range,
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    code: Some(NumberOrString::String("import-not-found".to_string())),
+                    source: Some("uss".to_string()),
+                    message: format!("Imported file doesn't exist: {}", display_url(&edge.url)),
+                    ..Default::default()
+                });
+                continue;
+            }
+
+            if let Some(chain) = self.find_import_path(&edge.url, url) {
+                let mut files = vec![display_url(url)];
+                files.extend(chain.iter().map(display_url));
+                diagnostics.push(Diagnostic {
+                    range: edge.range,
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String("circular-import".to_string())),
+                    source: Some("uss".to_string()),
+                    message: format!("Circular import: {}", files.join(" -> ")),
+                    ..Default::default()
+                });
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Export the graph for visualization tools
+    pub fn to_json(&self) -> ImportGraphJson {
+        let urls = self.file_urls();
+        let nodes = urls
+            .iter()
+            .map(|url| ImportGraphNode {
+                url: url.to_string(),
+                path: project_url_to_relative_path(url),
+            })
+            .collect();
+
+        let edges = urls
+            .iter()
+            .flat_map(|from| {
+                self.imports_of(from).iter().map(move |edge| ImportGraphEdge {
+                    from: from.to_string(),
+                    to: edge.url.to_string(),
+                    line: edge.range.start.line,
+                })
+            })
+            .collect();
+
+        ImportGraphJson { nodes, edges }
+    }
+}
+
+/// JSON representation of the import graph
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ImportGraphJson {
+    pub nodes: Vec<ImportGraphNode>,
+    pub edges: Vec<ImportGraphEdge>,
+}
+
+/// What of a document the diagnostics of other documents depend on
+///
+/// Importers of a document find circular imports through its imports, and with
+/// [`UnusedVariableScope::Importers`](crate::uss::unused_variables::UnusedVariableScope::Importers)
+/// the files it imports look for references to their variables in it. An edit that doesn't change
+/// the signature doesn't change the diagnostics of other documents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DependencySignature {
+    imports: Vec<Url>,
+    variable_references: BTreeSet<String>,
+}
+
+impl DependencySignature {
+    pub fn new(imports: &[ImportEdge], tree: &Tree, content: &str) -> Self {
+        Self {
+            imports: imports.iter().map(|edge| edge.url.clone()).collect(),
+            variable_references: variable_references(tree, content).into_iter().collect(),
+        }
+    }
+}
+
+/// A USS file in the JSON import graph
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ImportGraphNode {
+    pub url: String,
+    /// Path relative to the project root
+    pub path: Option<String>,
+}
+
+/// An import in the JSON import graph
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ImportGraphEdge {
+    pub from: String,
+    pub to: String,
+    /// Line (0 based) of the import statement in the importing file
+    pub line: u32,
+}
+
+/// Normalize a project url to the form used for files in the graph (`project:/Assets/...`),
+/// so `project:///Assets/a.uss` and `project:/Assets/a.uss?fileID=1` refer to the same file
+fn normalize_url(url: Url) -> Url {
+    if url.scheme() != PROJECT_SCHEME {
+        return url;
+    }
+    Url::parse(&format!("{}:{}", PROJECT_SCHEME, url.path())).unwrap_or(url)
+}
+
+/// Short form of a url for messages, the project relative path for project urls
+fn display_url(url: &Url) -> String {
+    project_url_to_relative_path(url).unwrap_or_else(|| url.to_string())
+}
+
+/// All USS files in the scanned directories of a project
+pub fn find_style_sheets(project_root: &Path) -> Vec<PathBuf> {
+    find_files(project_root, "uss")
+}
+
+/// All TSS (theme) files in the scanned directories of a project
+pub fn find_theme_style_sheets(project_root: &Path) -> Vec<PathBuf> {
+    find_files(project_root, "tss")
+}
+
+#[cfg(test)]
+#[path = "import_graph_tests.rs"]
+mod import_graph_tests;
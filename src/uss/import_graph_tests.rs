@@ -0,0 +1,208 @@
+use std::fs;
+
+use tempfile::TempDir;
+use tower_lsp::lsp_types::NumberOrString;
+use url::Url;
+
+use crate::uss::import_graph::{DependencySignature, UssImportGraph};
+use crate::uss::parser::UssParser;
+
+fn create_project(files: &[(&str, &str)]) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    for (path, content) in files {
+        let path = temp_dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    temp_dir
+}
+
+fn url(s: &str) -> Url {
+    Url::parse(s).unwrap()
+}
+
+fn diagnostic_codes(graph: &UssImportGraph, file: &Url) -> Vec<String> {
+    graph
+        .import_diagnostics(file)
+        .into_iter()
+        .filter_map(|d| match d.code {
+            Some(NumberOrString::String(code)) => Some(code),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_graph_edges() {
+    let project = create_project(&[
+        ("Assets/UI/main.uss", "@import \"common.uss\";\n@import url(\"project:///Assets/Theme/colors.uss\");\n.a { color: red; }"),
+        ("Assets/UI/common.uss", ".b { color: red; }"),
+        ("Assets/Theme/colors.uss", ":root { --brand: red; }"),
+    ]);
+
+    let mut graph = UssImportGraph::new(project.path().to_path_buf());
+    graph.refresh();
+
+    let main = url("project:/Assets/UI/main.uss");
+    let imports: Vec<String> = graph.imports_of(&main).iter().map(|e| e.url.to_string()).collect();
+    assert_eq!(imports, vec!["project:/Assets/UI/common.uss", "project:/Assets/Theme/colors.uss"]);
+
+    let json = graph.to_json();
+    assert_eq!(json.nodes.len(), 3);
+    assert_eq!(json.edges.len(), 2);
+    assert_eq!(json.edges[1].line, 1);
+    assert!(json.nodes.iter().any(|n| n.path.as_deref() == Some("Assets/UI/common.uss")));
+
+    assert!(graph.import_diagnostics(&main).is_empty());
+}
+
+#[test]
+fn test_importers() {
+    let project = create_project(&[
+        ("Assets/UI/main.uss", "@import \"common.uss\";"),
+        ("Assets/UI/common.uss", "@import \"colors.uss\";"),
+        ("Assets/UI/colors.uss", ":root { --brand: red; }"),
+        ("Assets/UI/other.uss", ".a { color: red; }"),
+    ]);
+
+    let mut graph = UssImportGraph::new(project.path().to_path_buf());
+    graph.refresh();
+
+    let importers = graph.importers_of(&url("project:/Assets/UI/colors.uss"));
+    assert_eq!(importers, vec![url("project:/Assets/UI/common.uss"), url("project:/Assets/UI/main.uss")]);
+    assert!(graph.importers_of(&url("project:/Assets/UI/main.uss")).is_empty());
+}
+
+#[test]
+fn test_circular_import() {
+    let project = create_project(&[
+        ("Assets/a.uss", "@import \"b.uss\";"),
+        ("Assets/b.uss", "@import \"c.uss\";"),
+        ("Assets/c.uss", "@import \"a.uss\";"),
+        ("Assets/d.uss", "@import \"a.uss\";"),
+    ]);
+
+    let mut graph = UssImportGraph::new(project.path().to_path_buf());
+    graph.refresh();
+
+    let a = url("project:/Assets/a.uss");
+    let diagnostics = graph.import_diagnostics(&a);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "Circular import: Assets/a.uss -> Assets/b.uss -> Assets/c.uss -> Assets/a.uss"
+    );
+
+    // d imports the cycle but is not part of it
+    assert!(graph.import_diagnostics(&url("project:/Assets/d.uss")).is_empty());
+}
+
+#[test]
+fn test_missing_import() {
+    let project = create_project(&[("Assets/a.uss", "@import \"missing.uss\";\n@import \"unity-theme://default\";")]);
+
+    let mut graph = UssImportGraph::new(project.path().to_path_buf());
+    graph.refresh();
+
+    let a = url("project:/Assets/a.uss");
+    assert_eq!(diagnostic_codes(&graph, &a), vec!["import-not-found"]);
+}
+
+#[test]
+fn test_document_imports_override_file() {
+    let project = create_project(&[("Assets/a.uss", ""), ("Assets/b.uss", "@import \"a.uss\";")]);
+
+    let mut graph = UssImportGraph::new(project.path().to_path_buf());
+    graph.refresh();
+
+    // unsaved edit of a.uss creates a cycle
+    let a = url("project:/Assets/a.uss");
+    let content = "@import \"b.uss\";";
+    let tree = UssParser::new().unwrap().parse(content, None).unwrap();
+    let imports = graph.extract_imports(&tree, content, &a);
+    graph.set_document_imports(a.clone(), imports);
+    assert_eq!(diagnostic_codes(&graph, &a), vec!["circular-import"]);
+
+    graph.remove_document_imports(&a);
+    assert!(graph.import_diagnostics(&a).is_empty());
+}
+
+#[test]
+fn test_imports_transitively() {
+    let project = create_project(&[
+        ("Assets/UI/main.uss", "@import \"common.uss\";"),
+        ("Assets/UI/common.uss", "@import \"colors.uss\";\n@import \"main.uss\";"),
+        ("Assets/UI/colors.uss", ":root { --brand: red; }"),
+    ]);
+
+    let mut graph = UssImportGraph::new(project.path().to_path_buf());
+    graph.refresh();
+
+    // the cycle back to main.uss is not included
+    let imported = graph.imports_transitively(&url("project:/Assets/UI/main.uss"));
+    assert_eq!(imported, vec![url("project:/Assets/UI/colors.uss"), url("project:/Assets/UI/common.uss")]);
+    assert!(graph.imports_transitively(&url("project:/Assets/UI/colors.uss")).is_empty());
+}
+
+#[test]
+fn test_dependency_signature() {
+    let graph = UssImportGraph::new(std::path::PathBuf::from("/project"));
+    let source_url = url("project:/Assets/UI/main.uss");
+    let mut parser = UssParser::new().unwrap();
+    let mut signature = |content: &str| {
+        let tree = parser.parse(content, None).unwrap();
+        let imports = graph.extract_imports(&tree, content, &source_url);
+        DependencySignature::new(&imports, &tree, content)
+    };
+
+    let original = signature("@import \"common.uss\";\n.a { color: var(--brand); }");
+    // editing values and selectors doesn't affect other documents
+    assert_eq!(signature("@import \"common.uss\";\n.b { color: var(--brand); width: 10px; }"), original);
+    assert_ne!(signature("@import \"other.uss\";\n.a { color: var(--brand); }"), original);
+    assert_ne!(signature("@import \"common.uss\";\n.a { color: var(--accent); }"), original);
+}
+
+#[test]
+fn test_graph_cache() {
+    let project = create_project(&[
+        ("Assets/UI/main.uss", "@import \"common.uss\";\n.a { color: red; }"),
+        ("Assets/UI/common.uss", ".b { color: red; }"),
+    ]);
+
+    let mut graph = UssImportGraph::new(project.path().to_path_buf());
+    graph.refresh();
+    graph.save_cache().unwrap();
+
+    let main = url("project:/Assets/UI/main.uss");
+    let mut restarted = UssImportGraph::new(project.path().to_path_buf());
+    assert!(restarted.load_cache());
+    assert!(!restarted.is_indexed());
+    assert_eq!(restarted.imports_of(&main).len(), 1);
+
+    restarted.refresh();
+    assert_eq!(restarted.imports_of(&main), graph.imports_of(&main));
+}
+
+#[test]
+fn test_changed_files() {
+    let project = create_project(&[
+        ("Assets/UI/main.uss", "@import \"common.uss\";"),
+        ("Assets/UI/common.uss", ".b { color: red; }"),
+    ]);
+    let mut graph = UssImportGraph::new(project.path().to_path_buf());
+    graph.refresh();
+
+    // common.uss was renamed, main.uss imports the new path
+    let ui = project.path().join("Assets/UI");
+    fs::rename(ui.join("common.uss"), ui.join("shared.uss")).unwrap();
+    fs::write(ui.join("main.uss"), "@import \"shared.uss\";").unwrap();
+    // read by another graph of the project, like the server does without holding the graph
+    let changed = UssImportGraph::new(project.path().to_path_buf())
+        .read_changed_files(&[ui.join("common.uss"), ui.join("shared.uss"), ui.join("main.uss")]);
+    graph.apply_changed_files(changed);
+
+    let main = url("project:/Assets/UI/main.uss");
+    assert_eq!(graph.imports_of(&main)[0].url, url("project:/Assets/UI/shared.uss"));
+    assert_eq!(graph.importers_of(&url("project:/Assets/UI/shared.uss")), vec![main]);
+    assert!(!graph.file_urls().contains(&&url("project:/Assets/UI/common.uss")));
+}
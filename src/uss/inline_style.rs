@@ -0,0 +1,157 @@
+//! UXML Inline Styles
+//!
+//! UXML elements can set styles with a `style` attribute, e.g. `style="width: 100px; color: red;"`.
+//! The value of the attribute is a USS declaration list, it is parsed with [`UssParser::parse_declarations`]
+//! and checked like a style sheet. Positions are mapped between the UXML document and the parsed content.
+//!
+//! UXML files are not handled by this language server, the editor asks for diagnostics and completion
+//! of a style attribute with the custom requests [`INLINE_STYLE_DIAGNOSTICS_METHOD`] and
+//! [`INLINE_STYLE_COMPLETION_METHOD`].
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{CompletionList, Diagnostic, Position, Range, TextDocumentIdentifier};
+use tree_sitter::Tree;
+
+use crate::uss::parser::{map_completion_ranges, map_diagnostic_ranges, DeclarationsFragment, UssParser};
+
+/// Custom request for the diagnostics of a style attribute, takes [`InlineStyleParams`] and returns `Vec<Diagnostic>`
+pub const INLINE_STYLE_DIAGNOSTICS_METHOD: &str = "unity/inlineStyleDiagnostics";
+
+/// Custom request for completion in a style attribute, takes [`InlineStyleParams`] and returns `CompletionList`
+pub const INLINE_STYLE_COMPLETION_METHOD: &str = "unity/inlineStyleCompletion";
+
+/// Parameters of the inline style requests
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlineStyleParams {
+    /// The UXML document that contains the attribute
+    pub text_document: TextDocumentIdentifier,
+    /// Value of the style attribute, without the quotes
+    pub style: String,
+    /// Position of the start of the value in the UXML document
+    pub start: Position,
+    /// Position of the cursor in the UXML document, only used by completion
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<Position>,
+}
+
+/// A parsed style attribute
+pub struct InlineStyle {
+    fragment: DeclarationsFragment,
+    /// Position of the declarations in the UXML document
+    start: Position,
+}
+
+impl InlineStyle {
+    /// Parse the value of a style attribute that starts at `start` in the UXML document
+    pub fn parse(parser: &mut UssParser, style: &str, start: Position) -> Option<Self> {
+        Some(Self {
+            fragment: parser.parse_declarations(style)?,
+            start,
+        })
+    }
+
+    /// The declarations wrapped in a rule, what the tree was parsed from
+    pub fn content(&self) -> &str {
+        self.fragment.content()
+    }
+
+    pub fn tree(&self) -> &Tree {
+        self.fragment.tree()
+    }
+
+    /// Map a position in the UXML document to the wrapped content, `None` if it's outside of the attribute value
+    pub fn to_content_position(&self, position: Position) -> Option<Position> {
+        let line = position.line.checked_sub(self.start.line)?;
+        let character = if line == 0 {
+            position.character.checked_sub(self.start.character)?
+        } else {
+            position.character
+        };
+        self.fragment.to_content_position(Position::new(line, character))
+    }
+
+    /// Map a position in the wrapped content to the UXML document
+    ///
+    /// Positions in the wrapping rule are clamped to the attribute value.
+    pub fn to_document_position(&self, position: Position) -> Position {
+        let position = self.fragment.to_fragment_position(position);
+        if position.line == 0 {
+            Position::new(self.start.line, self.start.character + position.character)
+        } else {
+            Position::new(self.start.line + position.line, position.character)
+        }
+    }
+
+    pub fn to_document_range(&self, range: Range) -> Range {
+        Range::new(self.to_document_position(range.start), self.to_document_position(range.end))
+    }
+
+    /// Map the ranges of diagnostics of the wrapped content to the UXML document
+    pub fn map_diagnostics(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        map_diagnostic_ranges(diagnostics, |range| self.to_document_range(range))
+    }
+
+    /// Map the edits of completion items for the wrapped content to the UXML document
+    pub fn map_completion_list(&self, list: CompletionList) -> CompletionList {
+        map_completion_ranges(list, |range| self.to_document_range(range))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uss::completion::UssCompletionProvider;
+    use crate::uss::diagnostics::UssDiagnostics;
+
+    fn parse(style: &str, start: Position) -> InlineStyle {
+        InlineStyle::parse(&mut UssParser::new().unwrap(), style, start).unwrap()
+    }
+
+    #[test]
+    fn test_position_mapping() {
+        let style = parse("width: 100px;\n    color: red;", Position::new(3, 20));
+
+        assert_eq!(style.to_content_position(Position::new(3, 20)), Some(Position::new(0, 3)));
+        assert_eq!(style.to_content_position(Position::new(4, 4)), Some(Position::new(1, 4)));
+        assert_eq!(style.to_content_position(Position::new(3, 19)), None);
+        assert_eq!(style.to_content_position(Position::new(4, 16)), None);
+        assert_eq!(style.to_content_position(Position::new(5, 0)), None);
+
+        assert_eq!(style.to_document_position(Position::new(0, 3)), Position::new(3, 20));
+        assert_eq!(style.to_document_position(Position::new(0, 10)), Position::new(3, 27));
+        assert_eq!(style.to_document_position(Position::new(1, 4)), Position::new(4, 4));
+        // the closing brace of the wrapping rule is clamped to the end of the value
+        assert_eq!(style.to_document_position(Position::new(2, 1)), Position::new(4, 15));
+    }
+
+    #[test]
+    fn test_inline_style_diagnostics() {
+        let style = parse("width: 100px; colr: red; height: 10qq;", Position::new(2, 30));
+        let diagnostics = UssDiagnostics::new().analyze(style.tree(), style.content());
+        let diagnostics = style.map_diagnostics(diagnostics);
+
+        assert_eq!(diagnostics.len(), 2, "{:#?}", diagnostics);
+        assert!(diagnostics.iter().all(|d| d.range.start.line == 2));
+        let starts: Vec<u32> = diagnostics.iter().map(|d| d.range.start.character).collect();
+        assert!(starts.contains(&44), "Unknown property should be reported at colr: {:?}", starts);
+    }
+
+    #[test]
+    fn test_valid_inline_style_has_no_diagnostics() {
+        let style = parse("flex-direction: row; margin: 4px 8px", Position::new(0, 10));
+        let diagnostics = UssDiagnostics::new().analyze(style.tree(), style.content());
+        assert!(diagnostics.is_empty(), "{:#?}", diagnostics);
+    }
+
+    #[test]
+    fn test_inline_style_completion() {
+        let style = parse("width: 100px; flex-direction: r", Position::new(1, 10));
+        let position = style.to_content_position(Position::new(1, 41)).unwrap();
+
+        let items = UssCompletionProvider::new().complete(style.tree(), style.content(), position, None, None, None);
+        let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
+        assert!(labels.contains(&"row"), "{:?}", labels);
+        assert!(labels.contains(&"row-reverse"), "{:?}", labels);
+    }
+}
@@ -3,36 +3,74 @@
 //! Provides IDE features for Unity's UI Toolkit styling language using:
 //! - tree-sitter-css for parsing (USS syntax is nearly identical to CSS)
 //! - tower-lsp for Language Server Protocol implementation
+//!
+//! The modules are internal, other crates use the [`UssAnalyzer`] facade and the types it is used with.
+
+pub(crate) mod server;
+pub(crate) mod parser;
+pub(crate) mod document;
+pub(crate) mod document_manager;
+pub(crate) mod diagnostics;
+pub(crate) mod diagnostics_snapshot;
+pub(crate) mod highlighting;
+pub(crate) mod definitions;
+pub(crate) mod hover;
+
+pub(crate) mod property_data;
+pub(crate) mod keyword_data;
+pub(crate) mod color;
+pub(crate) mod color_keywords;
+pub(crate) mod value_spec;
+pub(crate) mod color_provider;
+pub(crate) mod completion;
+pub(crate) mod variable_resolver;
+pub(crate) mod value;
+pub(crate) mod uss_utils;
+pub(crate) mod constants;
+pub(crate) mod import_node;
+pub(crate) mod function_node;
+pub(crate) mod url_function_node;
+pub(crate) mod flexible_format;
+pub(crate) mod formatter;
+pub(crate) mod refactor;
+pub(crate) mod unit_data;
+pub(crate) mod function_data;
+pub(crate) mod pseudo_class_data;
+pub(crate) mod snippet_data;
+pub(crate) mod selector;
+pub(crate) mod code_lens;
+pub(crate) mod settings;
+pub(crate) mod value_syntax;
+pub(crate) mod organize;
+pub(crate) mod import_graph;
+pub(crate) mod file_renames;
+pub(crate) mod inline_style;
+pub(crate) mod package_cache;
+pub(crate) mod rule_matching;
+pub(crate) mod cascade;
+pub(crate) mod style_diff;
+pub(crate) mod theme_report;
+pub(crate) mod unused_variables;
+pub(crate) mod symbol_index;
+pub(crate) mod baseline;
+pub(crate) mod url_references;
+pub(crate) mod unit_conversion;
+pub(crate) mod unity_compatibility;
+pub(crate) mod cursor;
+pub(crate) mod transition;
+pub(crate) mod analyzer;
+pub(crate) mod paste;
+pub(crate) mod reference_form;
+pub(crate) mod profile;
 
-pub mod server;
-pub mod parser;
-pub mod document;
-pub mod document_manager;
-pub mod diagnostics;
-pub mod highlighting;
-pub mod definitions;
-pub mod hover;
-
-pub mod property_data;
-pub mod keyword_data;
-pub mod color;
-pub mod color_keywords;
-pub mod value_spec;
-pub mod color_provider;
-pub mod completion;
-pub mod variable_resolver;
-pub mod value;
-pub mod uss_utils;
-pub mod constants;
-pub mod import_node;
-pub mod function_node;
-pub mod url_function_node;
-pub mod flexible_format;
-pub mod formatter;
-pub mod refactor;
-pub mod unit_data;
-pub mod function_data;
-pub mod pseudo_class_data;
+pub use analyzer::{AnalysisResult, UssAnalyzer};
+pub use definitions::UssDefinitions;
+pub use settings::{DiagnosticSettings, SeverityLevel};
+pub use unity_compatibility::ValidationMode;
+pub use unused_variables::UnusedVariableScope;
+pub use url_function_node::UrlReference;
+pub use url_references::UrlReferenceKind;
+pub use variable_resolver::{VariableResolver, VariableStatus};
 
 #[cfg(test)]
 mod diagnostics_tests;
@@ -40,6 +78,9 @@ mod diagnostics_tests;
 #[cfg(test)]
 mod value_spec_tests;
 
+#[cfg(test)]
+mod value_syntax_tests;
+
 #[cfg(test)]
 mod document_tests;
 
@@ -0,0 +1,276 @@
+//! USS Declaration Organizer
+//!
+//! Reorders the declarations within each rule, available as the "Organize declarations" code action.
+//!
+//! Only the order of declarations changes, the whitespace between them is kept as is,
+//! so organizing can be combined with formatting in any order. Comments move with
+//! the declaration they are attached to:
+//! - comments before a declaration (after the previous declaration's line) belong to it
+//! - a comment on the same line after a declaration belongs to it
+//!
+//! Declarations are reordered without changing what the rule means:
+//! - custom properties (variables) come first, in their original order
+//! - a shorthand and its longhands (e.g. `margin` and `margin-left`) keep their relative order
+//! - rules containing `all`, or anything other than declarations and comments, are left untouched
+
+use tower_lsp::lsp_types::*;
+use tree_sitter::{Node, Tree};
+
+use crate::language::tree_utils::{byte_to_position, has_error_nodes};
+use crate::language::workspace_edit::document_edit;
+use crate::uss::constants::*;
+
+/// Kind of the organize declarations code action, so clients can run it on save
+pub const ORGANIZE_DECLARATIONS_KIND: &str = "source.organizeDeclarations";
+
+/// Strategy to order declarations in a rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeclarationOrder {
+    /// Alphabetical by property name
+    #[default]
+    Alphabetical,
+    /// Grouped by category: layout, box model, text, visual, others.
+    /// Within a group the original order is kept.
+    Grouped,
+}
+
+impl DeclarationOrder {
+    /// Parse a declaration order from its configuration name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "alphabetical" => Some(Self::Alphabetical),
+            "grouped" => Some(Self::Grouped),
+            _ => None,
+        }
+    }
+}
+
+/// A declaration with the comments attached to it, as a byte range of the document
+struct DeclarationItem {
+    property: String,
+    start: usize,
+    end: usize,
+}
+
+/// Organizes the declarations of USS rules
+pub struct UssDeclarationOrganizer {
+    order: DeclarationOrder,
+}
+
+impl UssDeclarationOrganizer {
+    pub fn new() -> Self {
+        Self {
+            order: DeclarationOrder::default(),
+        }
+    }
+
+    /// Current declaration order
+    pub fn order(&self) -> DeclarationOrder {
+        self.order
+    }
+
+    /// Set the declaration order used by subsequent requests
+    pub fn set_order(&mut self, order: DeclarationOrder) {
+        self.order = order;
+    }
+
+    /// Create edits that reorder the declarations of all rules in the document, one edit per changed rule
+    pub fn organize(&self, tree: &Tree, content: &str) -> Vec<TextEdit> {
+        let mut edits = Vec::new();
+        self.organize_node(tree.root_node(), content, &mut edits);
+        edits
+    }
+
+    /// Create the organize declarations code action, `None` if declarations are already organized
+    pub fn code_action(
+        &self,
+        tree: &Tree,
+        content: &str,
+        document: &OptionalVersionedTextDocumentIdentifier,
+    ) -> Option<CodeActionOrCommand> {
+        let edits = self.organize(tree, content);
+        if edits.is_empty() {
+            return None;
+        }
+
+        Some(CodeActionOrCommand::CodeAction(CodeAction {
+            title: "Organize declarations".to_string(),
+            kind: Some(CodeActionKind::from(ORGANIZE_DECLARATIONS_KIND)),
+            edit: Some(document_edit(document, edits)),
+            ..Default::default()
+        }))
+    }
+
+    fn organize_node(&self, node: Node, content: &str, edits: &mut Vec<TextEdit>) {
+        if node.kind() == NODE_RULE_SET {
+            if let Some(block) = node.child(1).filter(|n| n.kind() == NODE_BLOCK) {
+                if let Some(edit) = self.organize_block(block, content) {
+                    edits.push(edit);
+                }
+            }
+            return;
+        }
+
+        for i in 0..node.child_count() {
+            if let Some(child) = node.child(i) {
+                self.organize_node(child, content, edits);
+            }
+        }
+    }
+
+    fn organize_block(&self, block: Node, content: &str) -> Option<TextEdit> {
+        if has_error_nodes(block) {
+            return None;
+        }
+
+        let items = collect_items(block, content)?;
+        if items.len() < 2 || items.iter().any(|item| item.property == "all") {
+            return None;
+        }
+
+        let mut sorted: Vec<(usize, &DeclarationItem)> = items.iter().enumerate().collect();
+        sorted.sort_by_cached_key(|(index, item)| self.sort_key(*index, &item.property));
+
+        if sorted.iter().enumerate().all(|(position, (index, _))| position == *index) {
+            return None;
+        }
+
+        // put the sorted items into the original slots, keeping the text between slots
+        let start = items[0].start;
+        let end = items[items.len() - 1].end;
+        let mut new_text = String::new();
+        for (slot, (_, item)) in sorted.iter().enumerate() {
+            if slot > 0 {
+                new_text.push_str(&content[items[slot - 1].end..items[slot].start]);
+            }
+            new_text.push_str(&content[item.start..item.end]);
+        }
+
+        Some(TextEdit {
+            range: Range {
+                start: byte_to_position(start, content),
+                end: byte_to_position(end, content),
+            },
+            new_text,
+        })
+    }
+
+    /// Sort key of a declaration, `index` is its original position in the rule
+    fn sort_key(&self, index: usize, property: &str) -> (u8, u8, String, usize) {
+        if property.starts_with("--") {
+            return (0, 0, String::new(), index);
+        }
+
+        match self.order {
+            DeclarationOrder::Alphabetical => (1, 0, shorthand_of(property).to_string(), index),
+            DeclarationOrder::Grouped => (1, property_group(property), String::new(), index),
+        }
+    }
+}
+
+impl Default for UssDeclarationOrganizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Collect the declarations of a block with their attached comments
+///
+/// Returns `None` if the block contains something we don't know how to move (e.g. a nested rule),
+/// a declaration without semicolon, or comments after the last declaration that are not trailing comments.
+fn collect_items(block: Node, content: &str) -> Option<Vec<DeclarationItem>> {
+    let mut items: Vec<DeclarationItem> = Vec::new();
+    // leading comments waiting for their declaration
+    let mut pending_start: Option<usize> = None;
+    let mut last_declaration_row: Option<usize> = None;
+
+    for i in 0..block.child_count() {
+        let child = block.child(i)?;
+        match child.kind() {
+            "{" | "}" => {}
+            NODE_COMMENT => {
+                let is_trailing = pending_start.is_none()
+                    && last_declaration_row == Some(child.start_position().row);
+                if is_trailing {
+                    if let Some(last) = items.last_mut() {
+                        last.end = child.end_byte();
+                    }
+                } else if pending_start.is_none() {
+                    pending_start = Some(child.start_byte());
+                }
+            }
+            NODE_DECLARATION => {
+                let property = child
+                    .child(0)
+                    .filter(|n| n.kind() == NODE_PROPERTY_NAME)?
+                    .utf8_text(content.as_bytes())
+                    .ok()?
+                    .to_string();
+                if child.child(child.child_count() - 1)?.kind() != NODE_SEMICOLON {
+                    return None;
+                }
+
+                items.push(DeclarationItem {
+                    property,
+                    start: pending_start.take().unwrap_or(child.start_byte()),
+                    end: child.end_byte(),
+                });
+                last_declaration_row = Some(child.end_position().row);
+            }
+            _ => return None,
+        }
+    }
+
+    // comments at the end of the block don't belong to any declaration
+    if pending_start.is_some() {
+        return None;
+    }
+
+    Some(items)
+}
+
+/// The shorthand a property belongs to, or the property itself
+///
+/// Declarations with the same shorthand keep their relative order, because later ones override earlier ones.
+fn shorthand_of(property: &str) -> &str {
+    match property {
+        "margin-top" | "margin-right" | "margin-bottom" | "margin-left" => "margin",
+        "padding-top" | "padding-right" | "padding-bottom" | "padding-left" => "padding",
+        "border-top-width" | "border-right-width" | "border-bottom-width" | "border-left-width" => "border-width",
+        "border-top-color" | "border-right-color" | "border-bottom-color" | "border-left-color" => "border-color",
+        "border-top-left-radius" | "border-top-right-radius" | "border-bottom-right-radius"
+        | "border-bottom-left-radius" => "border-radius",
+        "flex-grow" | "flex-shrink" | "flex-basis" => "flex",
+        "background-position-x" | "background-position-y" => "background-position",
+        "transition-property" | "transition-duration" | "transition-timing-function" | "transition-delay" => {
+            "transition"
+        }
+        "-unity-text-outline-width" | "-unity-text-outline-color" => "-unity-text-outline",
+        _ => property,
+    }
+}
+
+/// Group of a property for grouped order: layout, box model, text, visual, others
+fn property_group(property: &str) -> u8 {
+    match shorthand_of(property) {
+        "position" | "top" | "right" | "bottom" | "left" | "display" | "flex" | "flex-direction" | "flex-wrap"
+        | "align-content" | "align-items" | "align-self" | "justify-content" | "overflow"
+        | "-unity-overflow-clip-box" => 0,
+        "width" | "height" | "min-width" | "min-height" | "max-width" | "max-height" | "margin" | "padding"
+        | "border-width" => 1,
+        "color" | "font-size" | "-unity-font" | "-unity-font-definition" | "-unity-font-style"
+        | "-unity-text-align" | "-unity-text-generator" | "-unity-text-outline" | "-unity-text-overflow-position"
+        | "-unity-paragraph-spacing" | "-unity-editor-text-rendering-mode" | "letter-spacing"
+        | "word-spacing" | "white-space" | "text-overflow" | "text-shadow" => 2,
+        "background-color" | "background-image" | "background-position" | "background-repeat"
+        | "background-size" | "-unity-background-image-tint-color" | "-unity-background-scale-mode"
+        | "border-color" | "border-radius" | "-unity-slice-left" | "-unity-slice-top" | "-unity-slice-right"
+        | "-unity-slice-bottom" | "-unity-slice-scale" | "-unity-slice-type" | "opacity" | "visibility"
+        | "cursor" | "rotate" | "scale" | "translate" | "transform-origin" | "transition" => 3,
+        _ => 4,
+    }
+}
+
+#[cfg(test)]
+#[path = "organize_tests.rs"]
+mod organize_tests;
@@ -0,0 +1,114 @@
+use tower_lsp::lsp_types::{CodeActionOrCommand, DocumentChanges, TextEdit, Url};
+
+use crate::language::tree_utils::position_to_byte_offset;
+use crate::language::workspace_edit::{text_edits, versioned_document};
+use crate::uss::organize::{DeclarationOrder, UssDeclarationOrganizer};
+use crate::uss::parser::UssParser;
+
+/// Apply edits to content, edits must not overlap
+fn apply_edits(content: &str, edits: &[TextEdit]) -> String {
+    let mut result = content.to_string();
+    let mut edits = edits.to_vec();
+    edits.sort_by_key(|e| (e.range.start.line, e.range.start.character));
+    for edit in edits.iter().rev() {
+        let start = position_to_byte_offset(content, edit.range.start).unwrap();
+        let end = position_to_byte_offset(content, edit.range.end).unwrap();
+        result.replace_range(start..end, &edit.new_text);
+    }
+    result
+}
+
+fn organize(content: &str, order: DeclarationOrder) -> String {
+    let mut parser = UssParser::new().unwrap();
+    let tree = parser.parse(content, None).unwrap();
+    let mut organizer = UssDeclarationOrganizer::new();
+    organizer.set_order(order);
+    apply_edits(content, &organizer.organize(&tree, content))
+}
+
+/// Organize and check that organizing the result again changes nothing
+fn organize_idempotent(content: &str, order: DeclarationOrder) -> String {
+    let organized = organize(content, order);
+    assert_eq!(organize(&organized, order), organized, "Organizing is not idempotent");
+    organized
+}
+
+#[test]
+fn test_alphabetical_order() {
+    let content = ".a {\n    width: 10px;\n    color: red;\n    --size: 4px;\n    align-items: center;\n}";
+    let organized = organize_idempotent(content, DeclarationOrder::Alphabetical);
+    assert_eq!(
+        organized,
+        ".a {\n    --size: 4px;\n    align-items: center;\n    color: red;\n    width: 10px;\n}"
+    );
+}
+
+#[test]
+fn test_grouped_order() {
+    let content = ".a {\n    opacity: 0.5;\n    color: red;\n    margin: 4px;\n    flex-direction: row;\n    width: 10px;\n}";
+    let organized = organize_idempotent(content, DeclarationOrder::Grouped);
+    assert_eq!(
+        organized,
+        ".a {\n    flex-direction: row;\n    margin: 4px;\n    width: 10px;\n    color: red;\n    opacity: 0.5;\n}"
+    );
+}
+
+#[test]
+fn test_comments_move_with_declarations() {
+    let content = ".a {\n    /* the text color */\n    color: red;\n    background-color: blue; /* trailing */\n}";
+    let organized = organize_idempotent(content, DeclarationOrder::Alphabetical);
+    assert_eq!(
+        organized,
+        ".a {\n    background-color: blue; /* trailing */\n    /* the text color */\n    color: red;\n}"
+    );
+}
+
+#[test]
+fn test_shorthand_keeps_relative_order() {
+    // margin overrides margin-left, moving it would change the result
+    let content = ".a { margin-left: 2px; color: red; margin: 4px; }";
+    let organized = organize_idempotent(content, DeclarationOrder::Alphabetical);
+    assert_eq!(organized, ".a { color: red; margin-left: 2px; margin: 4px; }");
+}
+
+#[test]
+fn test_unsafe_blocks_are_untouched() {
+    let contents = [
+        // all resets everything before it
+        ".a { width: 1px; all: initial; color: red; }",
+        // no semicolon on last declaration
+        ".a { width: 1px; color: red }",
+        // comment at the end of the block
+        ".a { width: 1px;\n color: red;\n /* end */ }",
+        // syntax error
+        ".a { width: 1px; color: red; { }",
+    ];
+
+    for content in contents {
+        assert_eq!(organize(content, DeclarationOrder::Alphabetical), content);
+    }
+}
+
+#[test]
+fn test_code_action() {
+    let mut parser = UssParser::new().unwrap();
+    let organizer = UssDeclarationOrganizer::new();
+    let uri = Url::parse("file:///test.uss").unwrap();
+    let document = versioned_document(&uri, Some(4));
+
+    let content = ".a { width: 1px; color: red; }\n.b { color: red; width: 1px; }";
+    let tree = parser.parse(content, None).unwrap();
+    let Some(CodeActionOrCommand::CodeAction(action)) = organizer.code_action(&tree, content, &document) else {
+        panic!("Expected organize declarations code action");
+    };
+    let edit = action.edit.unwrap();
+    assert_eq!(text_edits(&edit, &uri).len(), 1, "Only the first rule needs organizing");
+    let Some(DocumentChanges::Edits(document_edits)) = &edit.document_changes else {
+        panic!("Expected versioned document edits");
+    };
+    assert_eq!(document_edits[0].text_document.version, Some(4));
+
+    let content = ".b { color: red; width: 1px; }";
+    let tree = parser.parse(content, None).unwrap();
+    assert!(organizer.code_action(&tree, content, &document).is_none());
+}
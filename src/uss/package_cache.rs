@@ -0,0 +1,180 @@
+//! Read-only USS files of the Package Cache
+//!
+//! Packages are extracted to `Library/PackageCache`, Unity overwrites any change made there when it resolves
+//! packages again. So documents there are read-only: diagnostics are informational, edits are refused,
+//! and the [`COPY_TO_ASSETS_COMMAND`] command copies a style sheet to `Assets` where it can be changed.
+
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::jsonrpc;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Url};
+
+use crate::language::asset_url::create_project_url_with_normalization;
+
+/// Command that copies a Package Cache style sheet to Assets, takes the document URI as its argument
+/// and returns a [`CopiedStyleSheet`]
+pub const COPY_TO_ASSETS_COMMAND: &str = "uss.copyStyleSheetToAssets";
+
+/// Title of the code action of [`COPY_TO_ASSETS_COMMAND`]
+pub const COPY_TO_ASSETS_TITLE: &str = "Copy stylesheet to Assets for override";
+
+/// Folder in Assets that copies are put in, by package name
+const OVERRIDES_FOLDER: &str = "StyleOverrides";
+
+/// Result of [`COPY_TO_ASSETS_COMMAND`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopiedStyleSheet {
+    /// URI of the copy
+    pub uri: Url,
+    /// Project URL of the copy, e.g. `project:/Assets/StyleOverrides/com.unity.ui/Styles/Default.uss`
+    pub url: Url,
+    /// Import statement for the copy, to use it from another style sheet
+    pub import: String,
+    /// Whether the copy was made by this command, `false` if it existed already
+    pub created: bool,
+}
+
+fn package_cache_dir(project_root: &Path) -> PathBuf {
+    project_root.join("Library").join("PackageCache")
+}
+
+/// Whether a file is in the Package Cache of the project
+pub fn is_in_package_cache(project_root: &Path, path: &Path) -> bool {
+    path.starts_with(package_cache_dir(project_root))
+}
+
+/// Whether the document at `uri` is read-only, i.e. a file in the Package Cache
+pub fn is_read_only(project_root: &Path, uri: &Url) -> bool {
+    uri.to_file_path()
+        .is_ok_and(|path| is_in_package_cache(project_root, &path))
+}
+
+/// Error returned for edit requests on a read-only document
+pub fn read_only_error(uri: &Url) -> jsonrpc::Error {
+    jsonrpc::Error {
+        code: jsonrpc::ErrorCode::InvalidRequest,
+        message: format!(
+            "{} is in the Package Cache and is read-only, use \"{}\" to change it",
+            uri, COPY_TO_ASSETS_TITLE
+        )
+        .into(),
+        data: None,
+    }
+}
+
+/// Make diagnostics of a read-only document informational, they can't be fixed there
+pub fn make_informational(diagnostics: &mut [Diagnostic]) {
+    for diagnostic in diagnostics {
+        diagnostic.severity = Some(DiagnosticSeverity::INFORMATION);
+    }
+}
+
+/// Path in Assets that a Package Cache file is copied to
+///
+/// The version is removed from the package folder (`com.unity.ui@1.0.0` becomes `com.unity.ui`),
+/// so the copy keeps its path when the package is upgraded.
+pub fn override_path(project_root: &Path, path: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix(package_cache_dir(project_root)).ok()?;
+    let mut components = relative.components();
+    let Some(Component::Normal(package_folder)) = components.next() else {
+        return None;
+    };
+    let package_folder = package_folder.to_string_lossy();
+    let package_name = package_folder.split('@').next().unwrap_or(&package_folder);
+
+    let rest = components.as_path();
+    if rest.as_os_str().is_empty() {
+        return None;
+    }
+
+    Some(project_root.join("Assets").join(OVERRIDES_FOLDER).join(package_name).join(rest))
+}
+
+/// Copy a Package Cache style sheet to Assets, an existing copy is kept as is
+///
+/// The copy starts with a comment naming the original.
+pub fn copy_to_assets(project_root: &Path, uri: &Url) -> io::Result<CopiedStyleSheet> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidInput, format!("{}: {}", message, uri));
+
+    let source = uri.to_file_path().map_err(|_| invalid("Not a file"))?;
+    let target = override_path(project_root, &source).ok_or_else(|| invalid("Not a file in the Package Cache"))?;
+
+    let created = !target.exists();
+    if created {
+        let content = std::fs::read_to_string(&source)?;
+        let relative_source = source.strip_prefix(project_root).unwrap_or(&source);
+        let header = format!(
+            "/* Copied from {}, changes to the original are not applied here */\n",
+            relative_source.to_string_lossy().replace('\\', "/")
+        );
+
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&target, header + &content)?;
+    }
+
+    let url = create_project_url_with_normalization(&target, project_root)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+    let uri = Url::from_file_path(&target).map_err(|_| invalid("Invalid copy path"))?;
+    Ok(CopiedStyleSheet {
+        import: format!("@import url(\"{}\");", url),
+        uri,
+        url,
+        created,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read_only_detection() {
+        let root = Path::new("/projects/game");
+        let cached = Url::from_file_path("/projects/game/Library/PackageCache/com.unity.ui@1.0.0/Styles/Default.uss").unwrap();
+        let asset = Url::from_file_path("/projects/game/Assets/UI/Main.uss").unwrap();
+
+        assert!(is_read_only(root, &cached));
+        assert!(!is_read_only(root, &asset));
+        assert!(!is_read_only(root, &Url::parse("untitled:Untitled-1").unwrap()));
+    }
+
+    #[test]
+    fn test_override_path() {
+        let root = Path::new("/projects/game");
+        assert_eq!(
+            override_path(root, &root.join("Library/PackageCache/com.unity.ui@1.0.0/Styles/Default.uss")),
+            Some(root.join("Assets/StyleOverrides/com.unity.ui/Styles/Default.uss"))
+        );
+        assert_eq!(override_path(root, &root.join("Library/PackageCache/com.unity.ui@1.0.0")), None);
+        assert_eq!(override_path(root, &root.join("Assets/Main.uss")), None);
+    }
+
+    #[test]
+    fn test_copy_to_assets() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let source = root.join("Library/PackageCache/com.example.ui@2.1.0/Styles/Button.uss");
+        std::fs::create_dir_all(source.parent().unwrap()).unwrap();
+        std::fs::write(&source, ".button { color: red; }\n").unwrap();
+        let uri = Url::from_file_path(&source).unwrap();
+
+        let copied = copy_to_assets(&root, &uri).unwrap();
+        assert!(copied.created);
+        assert_eq!(copied.url.as_str(), "project:/Assets/StyleOverrides/com.example.ui/Styles/Button.uss");
+        assert_eq!(copied.import, "@import url(\"project:/Assets/StyleOverrides/com.example.ui/Styles/Button.uss\");");
+
+        let content = std::fs::read_to_string(copied.uri.to_file_path().unwrap()).unwrap();
+        assert!(content.starts_with("/* Copied from Library/PackageCache/com.example.ui@2.1.0/Styles/Button.uss"));
+        assert!(content.ends_with(".button { color: red; }\n"));
+
+        // an existing copy may have been changed, it's not overwritten
+        assert!(!copy_to_assets(&root, &uri).unwrap().created);
+        assert!(copy_to_assets(&root, &Url::from_file_path(root.join("Assets/Main.uss")).unwrap()).is_err());
+    }
+}
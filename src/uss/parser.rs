@@ -2,9 +2,23 @@
 //!
 //! Since USS syntax is nearly identical to CSS, we can use the existing
 //! tree-sitter-css grammar directly.
+//!
+//! Declaration lists without a style sheet around them (UXML inline styles, snippets) can be
+//! parsed with [`UssParser::parse_declarations`], which wraps them in a synthetic rule.
+//!
+//! A few constructs that Unity accepts make tree-sitter-css report syntax errors, those are masked
+//! before parsing, see [`mask_unsupported`].
+
+use std::borrow::Cow;
 
+use tower_lsp::lsp_types::{CompletionItem, CompletionList, CompletionTextEdit, Diagnostic, Position, Range};
 use tree_sitter::{Parser, Tree};
 
+/// Start of the synthetic rule of declaration fragments, on the same line so only the first line is shifted
+const FRAGMENT_RULE_START: &str = "* {";
+/// End of the synthetic rule, on its own line so it doesn't touch the last declaration
+const FRAGMENT_RULE_END: &str = "\n}";
+
 /// USS parser wrapper around tree-sitter-css
 pub struct UssParser {
     parser: Parser,
@@ -20,8 +34,28 @@ impl UssParser {
     }
     
     /// Parse USS content and return the syntax tree
+    ///
+    /// The tree is parsed from the masked content (see [`mask_unsupported`]), which has the same
+    /// positions as `content`, so the tree is used with `content` as usual.
     pub fn parse(&mut self, content: &str, old_tree: Option<&Tree>) -> Option<Tree> {
-        self.parser.parse(content, old_tree)
+        self.parser.parse(mask_unsupported(content).as_ref(), old_tree)
+    }
+
+    /// Parse a declaration list like `width: 100px; color: red;`
+    ///
+    /// The declarations are wrapped in a rule, the tree and content of the fragment can be given to
+    /// diagnostics and completion like a style sheet, with positions mapped by the fragment.
+    pub fn parse_declarations(&mut self, declarations: &str) -> Option<DeclarationsFragment> {
+        let content = format!("{}{}{}", FRAGMENT_RULE_START, declarations, FRAGMENT_RULE_END);
+        let tree = self.parse(&content, None)?;
+        let last_line = declarations.rsplit('\n').next().unwrap_or_default();
+
+        Some(DeclarationsFragment {
+            content,
+            tree,
+            line_count: declarations.split('\n').count() as u32,
+            last_line_len: last_line.chars().count() as u32,
+        })
     }
 }
 
@@ -31,6 +65,200 @@ impl Default for UssParser {
     }
 }
 
+/// Mask constructs that tree-sitter-css can't parse but Unity accepts
+///
+/// - Empty declarations in blocks (`color: red;;`, `{ ; color: red }`), the extra `;` is masked. Without
+///   masking, the rest of the block is a syntax error and its declarations are lost.
+/// - Unquoted relative paths in `url()` and `resource()`, in values and in `@import` (`url(../Icons/a.png)`).
+///   An argument starting with `.` isn't a value for tree-sitter-css, so the leading dots are masked.
+///
+/// `-unity-` keywords and properties, and quoted or unquoted `resource()` in `@import`, parse as they are.
+///
+/// Masked characters are replaced by ASCII characters of the same length, so every position in the masked
+/// content is the same position in `content` and nodes of the tree can be read from `content` directly.
+pub fn mask_unsupported(content: &str) -> Cow<'_, str> {
+    let bytes = content.as_bytes();
+    let mut masked: Option<Vec<u8>> = None;
+
+    let mut depth = 0usize;
+    // last significant byte outside comments and strings, to detect empty declarations
+    let mut last_significant = b'{';
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = content[i + 2..].find("*/").map_or(bytes.len(), |end| i + 2 + end + 2);
+                continue;
+            }
+            quote @ (b'"' | b'\'') => {
+                i = string_end(bytes, i + 1, quote);
+                last_significant = quote;
+                continue;
+            }
+            b';' if depth > 0 && (last_significant == b';' || last_significant == b'{') => {
+                masked.get_or_insert_with(|| bytes.to_vec())[i] = b' ';
+            }
+            b'(' if is_url_function(&content[..i]) => {
+                let mut start = i + 1;
+                while bytes.get(start).is_some_and(u8::is_ascii_whitespace) {
+                    start += 1;
+                }
+                let mut end = start;
+                while bytes.get(end) == Some(&b'.') {
+                    end += 1;
+                }
+                if end > start {
+                    masked.get_or_insert_with(|| bytes.to_vec())[start..end].fill(b'_');
+                }
+                last_significant = b'(';
+            }
+            b'{' => {
+                depth += 1;
+                last_significant = b'{';
+            }
+            b'}' => {
+                depth = depth.saturating_sub(1);
+                last_significant = b'}';
+            }
+            b if b.is_ascii_whitespace() => {}
+            b => last_significant = b,
+        }
+        i += 1;
+    }
+
+    match masked {
+        // only ASCII bytes were replaced by ASCII bytes, so the content is still valid UTF-8
+        Some(masked) => Cow::Owned(String::from_utf8(masked).unwrap_or_else(|_| content.to_string())),
+        None => Cow::Borrowed(content),
+    }
+}
+
+/// Whether the text before a `(` ends with the name of a function that takes a path
+fn is_url_function(before: &str) -> bool {
+    ["url", "resource"].iter().any(|name| {
+        before.len() >= name.len()
+            && before.is_char_boundary(before.len() - name.len())
+            && before[before.len() - name.len()..].eq_ignore_ascii_case(name)
+            && !before[..before.len() - name.len()]
+                .ends_with(|c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    })
+}
+
+/// Index after the end of a string that starts at `start` (after its opening quote)
+fn string_end(bytes: &[u8], start: usize, quote: u8) -> usize {
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b if b == quote || b == b'\n' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// A declaration list parsed in a synthetic rule, see [`UssParser::parse_declarations`]
+///
+/// Positions in the fragment are relative to the start of the declarations,
+/// positions in the content are relative to the start of the synthetic rule.
+pub struct DeclarationsFragment {
+    /// The declarations wrapped in a rule
+    content: String,
+    tree: Tree,
+    /// Number of lines of the declarations
+    line_count: u32,
+    /// Number of characters of the last line of the declarations
+    last_line_len: u32,
+}
+
+impl DeclarationsFragment {
+    /// The declarations wrapped in a rule, what the tree was parsed from
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    /// Map a position in the fragment to the content, `None` if it's past the end of the declarations
+    pub fn to_content_position(&self, position: Position) -> Option<Position> {
+        if position.line >= self.line_count
+            || (position.line == self.line_count - 1 && position.character > self.last_line_len)
+        {
+            return None;
+        }
+
+        if position.line == 0 {
+            Some(Position::new(0, position.character + FRAGMENT_RULE_START.len() as u32))
+        } else {
+            Some(position)
+        }
+    }
+
+    /// Map a position in the content to the fragment
+    ///
+    /// Positions in the synthetic rule are clamped to the declarations, so problems reported
+    /// at the end of the rule (e.g. an unclosed block) are shown at the end of the declarations.
+    pub fn to_fragment_position(&self, position: Position) -> Position {
+        if position.line >= self.line_count {
+            Position::new(self.line_count - 1, self.last_line_len)
+        } else if position.line == 0 {
+            Position::new(0, position.character.saturating_sub(FRAGMENT_RULE_START.len() as u32))
+        } else {
+            position
+        }
+    }
+
+    pub fn to_fragment_range(&self, range: Range) -> Range {
+        Range::new(self.to_fragment_position(range.start), self.to_fragment_position(range.end))
+    }
+
+    /// Map the ranges of diagnostics of the content to the fragment
+    pub fn map_diagnostics(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        map_diagnostic_ranges(diagnostics, |range| self.to_fragment_range(range))
+    }
+
+    /// Map the edits of completion items for the content to the fragment
+    pub fn map_completion_list(&self, list: CompletionList) -> CompletionList {
+        map_completion_ranges(list, |range| self.to_fragment_range(range))
+    }
+}
+
+/// Apply a range mapping to diagnostics, for content embedded in another document
+pub fn map_diagnostic_ranges(diagnostics: Vec<Diagnostic>, map: impl Fn(Range) -> Range) -> Vec<Diagnostic> {
+    diagnostics
+        .into_iter()
+        .map(|mut diagnostic| {
+            diagnostic.range = map(diagnostic.range);
+            diagnostic
+        })
+        .collect()
+}
+
+/// Apply a range mapping to the edits of completion items, for content embedded in another document
+pub fn map_completion_ranges(mut list: CompletionList, map: impl Fn(Range) -> Range) -> CompletionList {
+    for item in &mut list.items {
+        map_completion_item_ranges(item, &map);
+    }
+    list
+}
+
+fn map_completion_item_ranges(item: &mut CompletionItem, map: &impl Fn(Range) -> Range) {
+    match &mut item.text_edit {
+        Some(CompletionTextEdit::Edit(edit)) => edit.range = map(edit.range),
+        Some(CompletionTextEdit::InsertAndReplace(edit)) => {
+            edit.insert = map(edit.insert);
+            edit.replace = map(edit.replace);
+        }
+        None => {}
+    }
+
+    for edit in item.additional_text_edits.iter_mut().flatten() {
+        edit.range = map(edit.range);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::uss::constants::*;
@@ -197,4 +425,109 @@ mod tests {
         assert!(found_pseudo, "Should find pseudo-class selector");
         assert!(found_declaration, "Should find declaration");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_mask_unsupported() {
+        assert!(matches!(mask_unsupported(".a { color: red; }"), Cow::Borrowed(_)));
+        assert_eq!(mask_unsupported(".a{color:red;;}"), ".a{color:red; }");
+        assert_eq!(mask_unsupported(".a { ; width: 1px; /* c */ ; }"), ".a {   width: 1px; /* c */   }");
+        // strings, comments and top level semicolons are kept
+        assert_eq!(mask_unsupported(".a { b: \";;\"; } /* ;; */"), ".a { b: \";;\"; } /* ;; */");
+        assert_eq!(mask_unsupported("@import \"a.uss\";;"), "@import \"a.uss\";;");
+
+        // leading dots of unquoted relative paths
+        assert_eq!(mask_unsupported(".a { b: url( ../c.png); }"), ".a { b: url( __/c.png); }");
+        assert_eq!(mask_unsupported("@import URL(./a.uss);"), "@import URL(_/a.uss);");
+        assert!(matches!(mask_unsupported(".a { b: url(\"../c.png\") my-url(.5); }"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_parse_unity_constructs() {
+        let mut parser = UssParser::new().unwrap();
+        let style_sheets = [
+            // -unity- properties and keywords, also where CSS has none
+            ".a { -unity-font-style: bold-and-italic; -unity-text-align: upper-left; }",
+            ".a { transition: -unity-slice-left 1s ease-in, -unity-background-image-tint-color 2s; }",
+            ".a { transition-property: all, -unity-slice-right; -unity-text-outline: 1px -unity-x; }",
+            ".a{-unity-font-style:bold;color:red}",
+            // resource() and url() in @import and in values, quoted and not
+            "@import resource(\"UI/Base\");\n@import resource(UI/Theme);\n.a { color: red; }",
+            "@import url(../Base.uss);\n@import url(./Theme.uss?fileID=1&guid=abc#Theme);\n.a { color: red; }",
+            ".a { -unity-font-definition: resource(Fonts/Inter SDF); background-image: url(../Icons/star.png); }",
+        ];
+        for content in style_sheets {
+            assert_eq!(mask_unsupported(content).len(), content.len());
+            let tree = parser.parse(content, None).unwrap();
+            assert!(!tree.root_node().has_error(), "{}: {}", content, tree.root_node().to_sexp());
+        }
+
+        // the path is read from the original content
+        let content = ".a { background-image: url(../Icons/star.png); }";
+        let tree = parser.parse(content, None).unwrap();
+        let start = content.find("url(").unwrap();
+        let argument = tree.root_node().descendant_for_byte_range(start + 4, start + 4).unwrap();
+        assert_eq!(argument.kind(), NODE_PLAIN_VALUE);
+        assert_eq!(argument.utf8_text(content.as_bytes()).unwrap(), "../Icons/star.png");
+    }
+
+    #[test]
+    fn test_parse_empty_declarations() {
+        let mut parser = UssParser::new().unwrap();
+        let content = ".a {\n    width: 100px;;\n    ;\n    color: red;\n}";
+        let tree = parser.parse(content, None).unwrap();
+        assert!(!tree.root_node().has_error(), "{}", tree.root_node().to_sexp());
+
+        // nodes are read from the original content
+        let block = tree.root_node().child(0).unwrap().child(1).unwrap();
+        let mut cursor = block.walk();
+        let declarations: Vec<&str> = block
+            .children(&mut cursor)
+            .filter(|n| n.kind() == NODE_DECLARATION)
+            .map(|n| n.utf8_text(content.as_bytes()).unwrap())
+            .collect();
+        assert_eq!(declarations, vec!["width: 100px;", "color: red;"]);
+    }
+
+    #[test]
+    fn test_parse_declarations() {
+        let mut parser = UssParser::new().unwrap();
+        let fragment = parser.parse_declarations("width: 100px;\n    color: red").unwrap();
+
+        let root = fragment.tree().root_node();
+        assert!(!root.has_error());
+        let block = root.child(0).unwrap().child(1).unwrap();
+        assert_eq!(block.kind(), NODE_BLOCK);
+        assert_eq!(block.named_child_count(), 2);
+    }
+
+    #[test]
+    fn test_declarations_position_mapping() {
+        let mut parser = UssParser::new().unwrap();
+        let fragment = parser.parse_declarations("width: 100px;\n    color: red;").unwrap();
+
+        assert_eq!(fragment.to_content_position(Position::new(0, 0)), Some(Position::new(0, 3)));
+        assert_eq!(fragment.to_content_position(Position::new(1, 4)), Some(Position::new(1, 4)));
+        assert_eq!(fragment.to_content_position(Position::new(1, 15)), Some(Position::new(1, 15)));
+        assert_eq!(fragment.to_content_position(Position::new(1, 16)), None);
+        assert_eq!(fragment.to_content_position(Position::new(2, 0)), None);
+
+        assert_eq!(fragment.to_fragment_position(Position::new(0, 3)), Position::new(0, 0));
+        assert_eq!(fragment.to_fragment_position(Position::new(0, 1)), Position::new(0, 0));
+        assert_eq!(fragment.to_fragment_position(Position::new(1, 4)), Position::new(1, 4));
+        // the closing brace of the synthetic rule is clamped to the end of the declarations
+        assert_eq!(fragment.to_fragment_position(Position::new(2, 1)), Position::new(1, 15));
+    }
+
+    #[test]
+    fn test_declarations_diagnostics() {
+        use crate::uss::diagnostics::UssDiagnostics;
+
+        let mut parser = UssParser::new().unwrap();
+        let fragment = parser.parse_declarations("color: red;\nbackgrund-color: blue;").unwrap();
+        let diagnostics = UssDiagnostics::new().analyze(fragment.tree(), fragment.content());
+        let diagnostics = fragment.map_diagnostics(diagnostics);
+
+        assert_eq!(diagnostics.len(), 1, "{:#?}", diagnostics);
+        assert_eq!(diagnostics[0].range.start, Position::new(1, 0));
+    }
+}
@@ -0,0 +1,179 @@
+//! Formatting Pasted Text
+//!
+//! A change that inserts several lines at once is taken as a paste. Pasted rules are often indented or
+//! spaced differently than the rest of the style sheet, so the pasted text can be formatted on its own with
+//! the range formatter, leaving the code around it as it is. How is set by [`PasteFormatting`]: the
+//! "Format pasted text" code action is offered until the document changes again, or the pasted text is
+//! formatted right away.
+//!
+//! Typing never inserts several lines in one change, but completion of a multi-line snippet does. Snippets
+//! are already formatted, so formatting them changes nothing.
+
+use tower_lsp::lsp_types::*;
+use tree_sitter::Tree;
+
+use crate::language::tree_utils::{byte_to_position, position_to_byte_offset};
+use crate::language::workspace_edit::document_edit;
+use crate::uss::formatter::UssFormatter;
+
+/// A single insert of at least this many lines is taken as a paste
+const MIN_PASTED_LINES: usize = 3;
+
+const FORMAT_PASTED_TITLE: &str = "Format pasted text";
+
+/// What happens when text is pasted, set by the `pasteFormatting` setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PasteFormatting {
+    /// Pasted text is left as is
+    Off,
+    /// The "Format pasted text" code action is offered
+    #[default]
+    CodeAction,
+    /// Pasted text is formatted right away
+    Automatic,
+}
+
+impl PasteFormatting {
+    /// Parse from the configuration name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "codeaction" => Some(Self::CodeAction),
+            "automatic" => Some(Self::Automatic),
+            _ => None,
+        }
+    }
+}
+
+/// Range of the text a change pasted, `None` if the change is not a paste
+///
+/// `content` is the document after the change, which must be the only change of its notification.
+pub fn pasted_range(change: &TextDocumentContentChangeEvent, content: &str) -> Option<Range> {
+    // a change without range replaces the whole document, e.g. when it's reloaded from disk
+    let range = change.range?;
+    if change.text.trim().is_empty() || change.text.lines().count() < MIN_PASTED_LINES {
+        return None;
+    }
+
+    let start = position_to_byte_offset(content, range.start)?;
+    let end = start + change.text.len();
+    if !content.is_char_boundary(end) || content.get(start..end) != Some(change.text.as_str()) {
+        return None;
+    }
+    Some(Range::new(range.start, byte_to_position(end, content)))
+}
+
+/// Text pasted by a change and the edits that format it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PastedText {
+    pub range: Range,
+    pub edits: Vec<TextEdit>,
+}
+
+/// Edits that format the pasted text, without the code around it
+///
+/// The pasted range is widened to whole lines, the range formatter only formats whole top-level nodes.
+pub fn format_pasted_edits(formatter: &UssFormatter, tree: &Tree, content: &str, pasted: Range) -> Vec<TextEdit> {
+    // text pasted with its last line break ends at the start of the next line, which isn't pasted
+    let end_line = if pasted.end.character == 0 { pasted.end.line } else { pasted.end.line + 1 };
+    let range = Range::new(Position::new(pasted.start.line, 0), Position::new(end_line, 0));
+    formatter.format_range(content, tree, range).unwrap_or_else(|e| {
+        log::debug!("Failed to format pasted text at {:?}: {}", pasted, e);
+        Vec::new()
+    })
+}
+
+/// The "Format pasted text" code action, if `range` touches the pasted text and it isn't formatted yet
+pub fn format_pasted_action(
+    document: &OptionalVersionedTextDocumentIdentifier,
+    pasted: &PastedText,
+    range: Range,
+) -> Option<CodeActionOrCommand> {
+    if range.end < pasted.range.start || range.start > pasted.range.end || pasted.edits.is_empty() {
+        return None;
+    }
+
+    Some(CodeActionOrCommand::CodeAction(CodeAction {
+        title: FORMAT_PASTED_TITLE.to_string(),
+        kind: Some(CodeActionKind::SOURCE),
+        edit: Some(document_edit(document, pasted.edits.clone())),
+        ..Default::default()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert(line: u32, character: u32, text: &str) -> TextDocumentContentChangeEvent {
+        let position = Position::new(line, character);
+        TextDocumentContentChangeEvent {
+            range: Some(Range::new(position, position)),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_pasted_range() {
+        let pasted = ".a {\ncolor: red;\n    }\n";
+        let content = format!(".title {{ color: blue; }}\n{}.end {{}}", pasted);
+        assert_eq!(
+            pasted_range(&insert(1, 0, pasted), &content),
+            Some(Range::new(Position::new(1, 0), Position::new(4, 0)))
+        );
+
+        // pasted in the middle of a line
+        let content = ".title { color: blue; } .a {\ncolor: red;\n}";
+        assert_eq!(
+            pasted_range(&insert(0, 24, ".a {\ncolor: red;\n}"), content),
+            Some(Range::new(Position::new(0, 24), Position::new(2, 1)))
+        );
+    }
+
+    #[test]
+    fn test_not_pasted() {
+        // typed
+        assert_eq!(pasted_range(&insert(0, 8, "c"), ".title {c }"), None);
+        assert_eq!(pasted_range(&insert(0, 8, "\n"), ".title {\n }"), None);
+        // a single line
+        assert_eq!(pasted_range(&insert(0, 9, "color: red;"), ".title { color: red; }"), None);
+        // blank lines
+        assert_eq!(pasted_range(&insert(0, 0, "\n\n\n"), "\n\n\n.a {}"), None);
+
+        // the whole document
+        let content = ".a {\ncolor: red;\n}";
+        let reload = TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: content.to_string(),
+        };
+        assert_eq!(pasted_range(&reload, content), None);
+
+        // the content doesn't have the inserted text, there were other changes
+        assert_eq!(pasted_range(&insert(0, 0, ".a {\ncolor: red;\n}"), ".b {\n}"), None);
+    }
+
+    #[test]
+    fn test_format_pasted_lines_only() {
+        let pasted = ".a {\ncolor: red;\n    }\n";
+        let content = format!(".title {{ color: blue; }}\n{}.end {{}}", pasted);
+        let tree = crate::uss::parser::UssParser::new().unwrap().parse(&content, None).unwrap();
+        let range = pasted_range(&insert(1, 0, pasted), &content).unwrap();
+
+        // the rule after the pasted text is on the line the pasted range ends on
+        let edits = format_pasted_edits(&UssFormatter::new(), &tree, &content, range);
+        for edit in &edits {
+            assert!(edit.range.start >= Position::new(1, 0) && edit.range.end <= Position::new(4, 0), "{:?}", edit);
+            assert!(!edit.new_text.contains(".end"), "{:?}", edit);
+        }
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(PasteFormatting::from_name("Automatic"), Some(PasteFormatting::Automatic));
+        assert_eq!(PasteFormatting::from_name("codeAction"), Some(PasteFormatting::CodeAction));
+        assert_eq!(PasteFormatting::from_name("off"), Some(PasteFormatting::Off));
+        assert_eq!(PasteFormatting::from_name("always"), None);
+    }
+}
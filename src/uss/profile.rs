@@ -0,0 +1,173 @@
+//! Validation Profiles
+//!
+//! Some properties only do something where a style sheet is used: `-unity-editor-text-rendering-mode` only
+//! applies to text in the Editor. The profile of a style sheet tells where it is used, and a runtime style
+//! sheet gets warnings for editor-only properties. A style sheet without profile gets none of these checks.
+//!
+//! The profile is detected from the path of the style sheet:
+//! 1. the `[profiles]` patterns of [`crate::config::UnityCodeConfig`]
+//! 2. a style sheet in an `Editor` or `Editor Default Resources` folder is an editor style sheet
+//! 3. a theme (`.tss`) elsewhere is a runtime style sheet, themes are assigned to panels of the game
+//!
+//! A top-level comment `/* uss-profile: editor */` or `/* uss-profile: runtime */` overrides the detected
+//! profile.
+
+use tower_lsp::lsp_types::*;
+use tree_sitter::{Node, Tree};
+
+use crate::config::UnityCodeConfig;
+use crate::language::tree_utils::node_to_range;
+use crate::uss::constants::*;
+
+/// Properties that only apply in the Editor
+pub const EDITOR_ONLY_PROPERTIES: &[&str] = &["-unity-editor-text-rendering-mode"];
+
+/// Comment directive that sets the profile of a style sheet
+const PROFILE_DIRECTIVE: &str = "uss-profile";
+
+/// Folders whose style sheets are only used in the Editor
+const EDITOR_FOLDERS: &[&str] = &["Editor", "Editor Default Resources"];
+
+/// Where a style sheet is used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UssProfile {
+    /// Editor windows and inspectors
+    Editor,
+    /// UI of the game, in panels of a `UIDocument`
+    Runtime,
+}
+
+impl UssProfile {
+    /// Parse a profile from its configuration name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "editor" => Some(Self::Editor),
+            "runtime" => Some(Self::Runtime),
+            _ => None,
+        }
+    }
+}
+
+/// Profile of a style sheet detected from its project relative path, e.g. `Assets/Editor/Window.uss`
+pub fn detect_profile(relative_path: &str, config: &UnityCodeConfig) -> Option<UssProfile> {
+    if let Some(profile) = config.profile_of(relative_path) {
+        return Some(profile);
+    }
+    let (folders, file_name) = relative_path.rsplit_once('/').unwrap_or(("", relative_path));
+    if folders.split('/').any(|folder| EDITOR_FOLDERS.contains(&folder)) {
+        return Some(UssProfile::Editor);
+    }
+    if file_name.to_ascii_lowercase().ends_with(".tss") {
+        return Some(UssProfile::Runtime);
+    }
+    None
+}
+
+/// Diagnostics of what doesn't fit the profile of a style sheet, `detected` is the profile of its path
+///
+/// Also reports `uss-profile` comments with an unknown profile.
+pub fn profile_diagnostics(tree: &Tree, content: &str, detected: Option<UssProfile>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut profile = detected;
+
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    for comment in root.children(&mut cursor).filter(|node| node.kind() == NODE_COMMENT) {
+        let text = comment.utf8_text(content.as_bytes()).unwrap_or_default();
+        let text = text.trim_start_matches("/*").trim_end_matches("*/").trim();
+        let Some(name) = text.strip_prefix(PROFILE_DIRECTIVE) else {
+            continue;
+        };
+        let name = name.trim_start_matches([':', ' ', '\t']).trim();
+        match UssProfile::from_name(name) {
+            Some(overridden) => profile = Some(overridden),
+            None => diagnostics.push(Diagnostic {
+                range: node_to_range(comment, content),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String("unknown-profile".to_string())),
+                source: Some("uss".to_string()),
+                message: format!("Unknown profile '{}', expected 'editor' or 'runtime'", name),
+                ..Default::default()
+            }),
+        }
+    }
+
+    if profile == Some(UssProfile::Runtime) {
+        collect_editor_only_properties(root, content, &mut diagnostics);
+    }
+    diagnostics
+}
+
+fn collect_editor_only_properties(node: Node, content: &str, diagnostics: &mut Vec<Diagnostic>) {
+    if node.kind() == NODE_PROPERTY_NAME {
+        let name = node.utf8_text(content.as_bytes()).unwrap_or_default();
+        if EDITOR_ONLY_PROPERTIES.contains(&name) {
+            diagnostics.push(Diagnostic {
+                range: node_to_range(node, content),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String("editor-only-property".to_string())),
+                source: Some("uss".to_string()),
+                message: format!("'{}' only applies in the Editor, it has no effect in a runtime style sheet", name),
+                ..Default::default()
+            });
+        }
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_editor_only_properties(child, content, diagnostics);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uss::parser::UssParser;
+
+    fn codes(content: &str, detected: Option<UssProfile>) -> Vec<(String, u32)> {
+        let tree = UssParser::new().unwrap().parse(content, None).unwrap();
+        profile_diagnostics(&tree, content, detected)
+            .into_iter()
+            .filter_map(|d| match d.code {
+                Some(NumberOrString::String(code)) => Some((code, d.range.start.line)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_profile() {
+        let config = UnityCodeConfig::default();
+        assert_eq!(detect_profile("Assets/Tools/Editor/Window.uss", &config), Some(UssProfile::Editor));
+        assert_eq!(detect_profile("Assets/Editor Default Resources/Inspector.uss", &config), Some(UssProfile::Editor));
+        assert_eq!(detect_profile("Assets/UI/Themes/Game.tss", &config), Some(UssProfile::Runtime));
+        assert_eq!(detect_profile("Assets/Editor/Themes/Tool.tss", &config), Some(UssProfile::Editor));
+        assert_eq!(detect_profile("Assets/UI/Main.uss", &config), None);
+        assert_eq!(detect_profile("Assets/UI/Editor.uss", &config), None);
+
+        let config = UnityCodeConfig::parse("[profiles]\neditor = [\"Assets/Tools/**\"]\nruntime = [\"Assets/**/Editor/**\"]").unwrap();
+        assert_eq!(detect_profile("Assets/Tools/Window.uss", &config), Some(UssProfile::Editor));
+        assert_eq!(detect_profile("Assets/Game/Editor/Hud.uss", &config), Some(UssProfile::Runtime));
+    }
+
+    #[test]
+    fn test_editor_only_properties() {
+        let content = ".a {\n    -unity-editor-text-rendering-mode: legacy;\n}";
+        assert_eq!(codes(content, Some(UssProfile::Runtime)), vec![("editor-only-property".to_string(), 1)]);
+        assert!(codes(content, Some(UssProfile::Editor)).is_empty());
+        assert!(codes(content, None).is_empty());
+
+        // overridden by the directive
+        let editor = format!("/* uss-profile: editor */\n{}", content);
+        assert!(codes(&editor, Some(UssProfile::Runtime)).is_empty());
+        let runtime = format!("/* uss-profile runtime */\n{}", content);
+        assert_eq!(codes(&runtime, None), vec![("editor-only-property".to_string(), 2)]);
+
+        let unknown = format!("/* uss-profile: game */\n{}", content);
+        assert_eq!(codes(&unknown, Some(UssProfile::Runtime)), vec![
+            ("unknown-profile".to_string(), 0),
+            ("editor-only-property".to_string(), 2),
+        ]);
+    }
+}
@@ -3,9 +3,11 @@
 //! Contains the actual property definitions for USS properties.
 //! This module is separated from definitions.rs to improve maintainability.
 
-use crate::uss::definitions::{PropertyAnimation, PropertyInfo};
+use crate::unity_project_manager::UnityVersion;
+use crate::uss::definitions::{PropertyAnimation, PropertyInfo, PropertyStatus};
 use crate::uss::flexible_format::FlexibleFormatBuilder;
 use crate::uss::value_spec::{ValueEntry, ValueFormat, ValueSpec, ValueType};
+use crate::uss::value_syntax::{value_spec_from_format, ValueSyntaxError};
 use std::collections::HashMap;
 const SUPPORTED_PROPERTIES_URL: &str =
     "https://docs.unity3d.com/{version}/Documentation/Manual/UIE-USS-SupportedProperties.html";
@@ -18,7 +20,7 @@ const TRANSFORM_URL: &str =
 const TRANSITIONS_URL: &str =
     "https://docs.unity3d.com/{version}/Documentation/Manual/UIE-Transitions.html";
 
-const TIMING_FUN: [&'static str; 24] = [
+pub(crate) const TIMING_FUN: [&'static str; 24] = [
     "initial", // same as ease
     "ease",
     "ease-in",
@@ -45,6 +47,28 @@ const TIMING_FUN: [&'static str; 24] = [
     "ease-in-out-bounce",
 ];
 
+// Formats of properties whose value spec is generated from the format
+const BACKGROUND_POSITION_FORMAT: &str = "[[ left | center | right | top | bottom | <length-percentage> ] | [ left | center | right | <length-percentage> ] [ top | center | bottom | <length-percentage> ]  | [ center | [ left | right ] <length-percentage>? ] && [ center | [ top | bottom ] <length-percentage>? ]]#";
+const BACKGROUND_POSITION_X_FORMAT: &str = "[ center | [ [ left | right | x-start | x-end ]? <length-percentage>? ]! ]#";
+const BACKGROUND_POSITION_Y_FORMAT: &str = "[ center | [ [ top | bottom | y-start | y-end ]? <length-percentage>? ]! ]#";
+const BACKGROUND_REPEAT_FORMAT: &str = "repeat-x | repeat-y | [ repeat | space | round | no-repeat ]{1,2}";
+const BACKGROUND_SIZE_FORMAT: &str = "[ <length-percentage [0,∞]> | auto ]{1,2} | cover | contain";
+const ROTATE_FORMAT: &str = "none | [ x | y | z | <number>{3} ] && <angle> | <angle>";
+const SCALE_FORMAT: &str = "none | <number>{1,3}";
+
+/// Properties whose documented format doesn't describe what their value spec accepts, with the reason
+///
+/// The format is kept as documented, [`PropertyInfo::value_syntax`] shows the accepted values instead.
+/// A test verifies that the value specs of all other properties match their format.
+pub const INACCURATE_FORMATS: &[(&str, &str)] = &[
+    ("-unity-text-outline", "the documented format is <length> | <color>, but the actual format is <length> || <color>"),
+    ("cursor", "the format contains a literal comma, which a ValueFormat can't express"),
+    ("font-size", "the documented format is <number>, but the actual format is <length>"),
+    ("transform-origin", "the optional z offset only accepts <length> without percentage"),
+    ("translate", "the optional z offset only accepts <length> without percentage"),
+    ("transition", "Unity also accepts the duration and the delay both before the timing function"),
+];
+
 /// Create all standard CSS properties supported by USS
 pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
     let mut properties = HashMap::new();
@@ -62,6 +86,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Discrete,
             value_spec: ValueSpec::keywords(&["flex-start", "flex-end", "center", "stretch"]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "align-items",
@@ -79,6 +104,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
                 "center",
                 "stretch",
             ]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "align-self",
@@ -96,6 +122,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
                 "center",
                 "stretch",
             ]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "all",
@@ -107,6 +134,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::keywords(&["initial"]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "background-color",
@@ -118,6 +146,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::color(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "background-image",
@@ -129,61 +158,72 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Discrete,
             value_spec: ValueSpec::one_of(vec![ValueType::Asset, ValueType::Keyword("none")]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "background-position",
             description: "Background image position value.",
             examples_unity: None,
             examples_mozilla: Some("background-position: top;\nbackground-position: bottom;\nbackground-position: left;\nbackground-position: right;\nbackground-position: center;\nbackground-position: 25% 75%;\nbackground-position: 0 0;\nbackground-position: 1cm 2cm;\nbackground-position: 10ch 8em;\nbackground-position:\nbackground-position: bottom 10px right 20px;\nbackground-position: right 3em bottom 10px;\nbackground-position: bottom 10px right;\nbackground-position: top right 10px;\nbackground-position: inherit;\nbackground-position: initial;\nbackground-position: revert;\nbackground-position: revert-layer;\nbackground-position: unset;\nbackground-position:"),
-            format: "[[ left | center | right | top | bottom | <length-percentage> ] | [ left | center | right | <length-percentage> ] [ top | center | bottom | <length-percentage> ]  | [ center | [ left | right ] <length-percentage>? ] && [ center | [ top | bottom ] <length-percentage>? ]]#",
+            format: BACKGROUND_POSITION_FORMAT,
             documentation_url: format!("{CSS_URL}/background-position"),
             inherited: false,
             animatable: PropertyAnimation::Animatable,
-            value_spec: ValueSpec::new_with_multiple(create_formats_for_background_position(), true),
+            min_unity_version: Some(UnityVersion::new(2022, 2)),
+            value_spec: spec_from_format(BACKGROUND_POSITION_FORMAT).unwrap_or_default(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "background-position-x",
             description: "Background image x position value.",
             examples_unity: None,
             examples_mozilla: Some("background-position-x: left;\nbackground-position-x: center;\nbackground-position-x: right;\nbackground-position-x: 25%;\nbackground-position-x: 0px;\nbackground-position-x: 1cm;\nbackground-position-x: 8em;\nbackground-position-x: right 3px;\nbackground-position-x: left 25%;\nbackground-position-x: 0px, center;\nbackground-position-x: inherit;\nbackground-position-x: initial;\nbackground-position-x: revert;\nbackground-position-x: revert-layer;\nbackground-position-x: unset;\nbackground-position-x: center;\nbackground-position-x: right 20px;"),
-            format: "[ center | [ [ left | right | x-start | x-end ]? <length-percentage>? ]! ]#",
+            format: BACKGROUND_POSITION_X_FORMAT,
             documentation_url: format!("{CSS_URL}/background-position-x"),
             inherited: false,
             animatable: PropertyAnimation::Discrete,
-            value_spec: ValueSpec::new_with_multiple(create_formats_for_background_position_x(), true),
+            min_unity_version: Some(UnityVersion::new(2022, 2)),
+            value_spec: spec_from_format(BACKGROUND_POSITION_X_FORMAT).unwrap_or_default(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "background-position-y",
             description: "Background image y position value.",
             examples_unity: None,
             examples_mozilla: Some("background-position-y: top;\nbackground-position-y: center;\nbackground-position-y: bottom;\nbackground-position-y: 25%;\nbackground-position-y: 0px;\nbackground-position-y: 1cm;\nbackground-position-y: 8em;\nbackground-position-y: bottom 3px;\nbackground-position-y: bottom 10%;\nbackground-position-y: 0px, center;\nbackground-position-y: inherit;\nbackground-position-y: initial;\nbackground-position-y: revert;\nbackground-position-y: revert-layer;\nbackground-position-y: unset;\nbackground-position-y: bottom;\nbackground-position-y: bottom 10px;"),
-            format: "[ center | [ [ top | bottom | y-start | y-end ]? <length-percentage>? ]! ]#",
+            format: BACKGROUND_POSITION_Y_FORMAT,
             documentation_url: format!("{CSS_URL}/background-position-y"),
             inherited: false,
             animatable: PropertyAnimation::Discrete,
-            value_spec: ValueSpec::new_with_multiple(create_formats_for_background_position_y(), true),
+            min_unity_version: Some(UnityVersion::new(2022, 2)),
+            value_spec: spec_from_format(BACKGROUND_POSITION_Y_FORMAT).unwrap_or_default(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "background-repeat",
             description: "Background image repeat value.",
             examples_unity: None,
             examples_mozilla: Some("background-repeat: repeat;\nbackground-repeat: repeat-x;\nbackground-repeat: repeat-y;\nbackground-repeat: space;\nbackground-repeat: round;\nbackground-repeat: no-repeat;\nbackground-repeat: repeat space;\nbackground-repeat: repeat repeat;\nbackground-repeat: round space;\nbackground-repeat: no-repeat round;\nbackground-repeat: inherit;\nbackground-repeat: initial;\nbackground-repeat: revert;\nbackground-repeat: revert-layer;\nbackground-repeat: unset;\nbackground-repeat: no-repeat;\nbackground-repeat: repeat;\nbackground-repeat: repeat-x;\nbackground-repeat: repeat-y;\nbackground-repeat: space;\nbackground-repeat: round;\nbackground-repeat: repeat-x, repeat-y;"),
-            format: "repeat-x | repeat-y | [ repeat | space | round | no-repeat ]{1,2}",
+            format: BACKGROUND_REPEAT_FORMAT,
             documentation_url: format!("{CSS_URL}/background-repeat"),
             inherited: false,
             animatable: PropertyAnimation::Discrete,
-            value_spec: ValueSpec::new(create_formats_for_background_repeat()),
+            min_unity_version: Some(UnityVersion::new(2022, 2)),
+            value_spec: spec_from_format(BACKGROUND_REPEAT_FORMAT).unwrap_or_default(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "background-size",
             description: "Background image size value. Transitions are fully supported only when using size in pixels or percentages, such as pixel-to-pixel or percentage-to-percentage transitions.",
             examples_unity: None,
             examples_mozilla: Some("background-size: cover;\nbackground-size: contain;\nbackground-size: 50%;\nbackground-size: 3.2em;\nbackground-size: 12px;\nbackground-size: auto;\nbackground-size: 50% auto;\nbackground-size: 3em 25%;\nbackground-size: auto 6px;\nbackground-size: auto auto;\nbackground-size: auto, auto; /* Not to be confused with `auto auto` */\nbackground-size: 50%, 25%, 25%;\nbackground-size: 6px, auto, contain;\nbackground-size: inherit;\nbackground-size: initial;\nbackground-size: revert;\nbackground-size: revert-layer;\nbackground-size: unset;\nbackground-size: 150px;"),
-            format: "[ <length-percentage [0,∞]> | auto ]{1,2} | cover | contain",
+            format: BACKGROUND_SIZE_FORMAT,
             documentation_url: format!("{CSS_URL}/background-size"),
             inherited: false,
             animatable: PropertyAnimation::Animatable,
-            value_spec: ValueSpec::new(create_formats_for_background_size()),
+            min_unity_version: Some(UnityVersion::new(2022, 2)),
+            value_spec: spec_from_format(BACKGROUND_SIZE_FORMAT).unwrap_or_default(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "border-bottom-color",
@@ -195,6 +235,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::color(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "border-bottom-left-radius",
@@ -206,6 +247,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::LengthPercent),
+            ..Default::default()
         },
         PropertyInfo {
             name: "border-bottom-right-radius",
@@ -217,6 +259,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::LengthPercent),
+            ..Default::default()
         },
         PropertyInfo {
             name: "border-bottom-width",
@@ -228,6 +271,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::LengthPercent),
+            ..Default::default()
         },
         PropertyInfo {
             name: "border-color",
@@ -239,6 +283,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::repeat(ValueType::Color, 1, 4),
+            ..Default::default()
         },
         PropertyInfo {
             name: "border-left-color",
@@ -250,6 +295,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::color(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "border-left-width",
@@ -261,6 +307,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::LengthPercent),
+            ..Default::default()
         },
         PropertyInfo {
             name: "border-radius",
@@ -272,6 +319,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::repeat(ValueType::LengthPercent, 1, 4),
+            ..Default::default()
         },
         PropertyInfo {
             name: "border-right-color",
@@ -283,6 +331,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::color(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "border-top-left-radius",
@@ -294,6 +343,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::LengthPercent),
+            ..Default::default()
         },
         PropertyInfo {
             name: "border-top-color",
@@ -305,6 +355,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::color(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "border-right-width",
@@ -316,6 +367,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::LengthPercent),
+            ..Default::default()
         },
         PropertyInfo {
             name: "border-top-right-radius",
@@ -327,6 +379,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::LengthPercent),
+            ..Default::default()
         },
         PropertyInfo {
             name: "border-top-width",
@@ -338,6 +391,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::LengthPercent),
+            ..Default::default()
         },
         PropertyInfo {
             name: "border-width",
@@ -349,6 +403,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::repeat(ValueType::LengthPercent, 1, 4),
+            ..Default::default()
         },
         PropertyInfo {
             name: "bottom",
@@ -360,6 +415,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: spec_length_auto.clone(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "color",
@@ -371,6 +427,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: true,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::color(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "cursor",
@@ -413,6 +470,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
                 // Custom cursor: resource/url only
                 ValueFormat::single(ValueType::Asset),
             ]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "display",
@@ -424,6 +482,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::None,
             value_spec: ValueSpec::keywords(&["flex", "none"]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "flex",
@@ -435,6 +494,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::new(create_flex_formats()),
+            ..Default::default()
         },
         PropertyInfo {
             name: "flex-basis",
@@ -446,6 +506,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: spec_length_auto.clone(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "flex-direction",
@@ -457,6 +518,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Discrete,
             value_spec: ValueSpec::keywords(&["row", "row-reverse", "column", "column-reverse"]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "flex-grow",
@@ -468,6 +530,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::Number),
+            ..Default::default()
         },
         PropertyInfo {
             name: "flex-shrink",
@@ -479,6 +542,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::Number),
+            ..Default::default()
         },
         PropertyInfo {
             name: "flex-wrap",
@@ -490,6 +554,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Discrete,
             value_spec: ValueSpec::keywords(&["nowrap", "wrap", "wrap-reverse"]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "font-size",
@@ -501,6 +566,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: true,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::LengthPercent),
+            ..Default::default()
         },
         PropertyInfo {
             name: "height",
@@ -512,6 +578,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: spec_length_auto.clone(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "justify-content",
@@ -529,6 +596,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
                 "space-between",
                 "space-around",
             ]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "left",
@@ -540,6 +608,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: spec_length_auto.clone(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "letter-spacing",
@@ -551,6 +620,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: true,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::LengthPercent),
+            ..Default::default()
         },
         PropertyInfo {
             name: "margin",
@@ -570,6 +640,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
                     )
                     .build(),
             ),
+            ..Default::default()
         },
         PropertyInfo {
             name: "margin-bottom",
@@ -581,6 +652,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: spec_length_auto.clone(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "margin-left",
@@ -592,6 +664,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: spec_length_auto.clone(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "margin-right",
@@ -603,6 +676,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: spec_length_auto.clone(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "margin-top",
@@ -614,6 +688,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: spec_length_auto.clone(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "max-height",
@@ -625,6 +700,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::one_of(vec![ValueType::LengthPercent, ValueType::Keyword("none")]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "max-width",
@@ -636,6 +712,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::one_of(vec![ValueType::LengthPercent, ValueType::Keyword("none")]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "min-height",
@@ -647,6 +724,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: spec_length_auto.clone(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "min-width",
@@ -658,6 +736,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: spec_length_auto.clone(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "opacity",
@@ -669,6 +748,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::Number),
+            ..Default::default()
         },
         PropertyInfo {
             name: "overflow",
@@ -680,6 +760,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Discrete,
             value_spec: ValueSpec::keywords(&["visible", "hidden"]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "padding",
@@ -691,6 +772,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::repeat(ValueType::LengthPercent, 1, 4),
+            ..Default::default()
         },
         PropertyInfo {
             name: "padding-bottom",
@@ -702,6 +784,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::LengthPercent),
+            ..Default::default()
         },
         PropertyInfo {
             name: "padding-left",
@@ -713,6 +796,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::LengthPercent),
+            ..Default::default()
         },
         PropertyInfo {
             name: "padding-right",
@@ -724,6 +808,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::LengthPercent),
+            ..Default::default()
         },
         PropertyInfo {
             name: "padding-top",
@@ -735,6 +820,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::LengthPercent),
+            ..Default::default()
         },
         PropertyInfo {
             name: "position",
@@ -746,6 +832,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Discrete,
             value_spec: ValueSpec::keywords(&["relative", "absolute"]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "right",
@@ -757,28 +844,33 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: spec_length_auto.clone(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "rotate",
             description: "A rotation transformation.",
             examples_unity: Some("rotate: 45deg;\nrotate: -100grad;\nrotate: -3.14rad;\nrotate: 0.75turn;\nrotate: none;"),
             examples_mozilla: None,
-            format: "none | [ x | y | z | <number>{3} ] && <angle> | <angle>",
+            format: ROTATE_FORMAT,
             documentation_url: TRANSFORM_URL.to_string(),
             inherited: false,
             animatable: PropertyAnimation::Animatable,
-            value_spec: ValueSpec::new(create_rotate_formats()),
+            min_unity_version: Some(UnityVersion::new(2021, 2)),
+            value_spec: spec_from_format(ROTATE_FORMAT).unwrap_or_default(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "scale",
             description: "A scaling transformation.",
             examples_unity: Some("scale: 2.5;\nscale: -1 1;\nscale: none;"),
             examples_mozilla: None,
-            format: "none | <number>{1,3}",
+            format: SCALE_FORMAT,
             documentation_url: TRANSFORM_URL.to_string(),
             inherited: false,
             animatable: PropertyAnimation::Animatable,
-            value_spec: ValueSpec::new(create_scale_formats()),
+            min_unity_version: Some(UnityVersion::new(2021, 2)),
+            value_spec: spec_from_format(SCALE_FORMAT).unwrap_or_default(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "text-overflow",
@@ -790,6 +882,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Discrete,
             value_spec: ValueSpec::keywords(&["clip", "ellipsis"]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "text-shadow",
@@ -806,6 +899,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
                 ValueType::LengthPercent,
                 ValueType::Color,
             ]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "top",
@@ -817,6 +911,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: spec_length_auto.clone(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "transform-origin",
@@ -827,7 +922,9 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             documentation_url: TRANSFORM_URL.to_string(),
             inherited: false,
             animatable: PropertyAnimation::Animatable,
+            min_unity_version: Some(UnityVersion::new(2021, 2)),
             value_spec: ValueSpec ::new(create_transform_origin_formats()),
+            ..Default::default()
         },
         PropertyInfo {
             name: "transition",
@@ -838,7 +935,9 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             documentation_url: TRANSITIONS_URL.to_string(),
             inherited: false,
             animatable: PropertyAnimation::None,
-            value_spec: ValueSpec::new_with_multiple(FlexibleFormatBuilder::new().required(ValueEntry::options(vec![ValueType::PropertyName])).range(ValueEntry::options(vec![ValueType::Time]), 1, 2).optional(ValueEntry::keywords(&TIMING_FUN)).build(), true),
+            min_unity_version: Some(UnityVersion::new(2021, 2)),
+            value_spec: ValueSpec::new_with_multiple(create_transition_formats(), true),
+            ..Default::default()
         },
         PropertyInfo {
             name: "transition-delay",
@@ -849,7 +948,9 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             documentation_url: TRANSITIONS_URL.to_string(),
             inherited: false,
             animatable: PropertyAnimation::None,
+            min_unity_version: Some(UnityVersion::new(2021, 2)),
             value_spec: ValueSpec::new_with_multiple(vec![ValueFormat::single(ValueType::Time)], true),
+            ..Default::default()
         },
         PropertyInfo {
             name: "transition-duration",
@@ -860,7 +961,9 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             documentation_url: TRANSITIONS_URL.to_string(),
             inherited: false,
             animatable: PropertyAnimation::None,
+            min_unity_version: Some(UnityVersion::new(2021, 2)),
             value_spec: ValueSpec::new_with_multiple(vec![ValueFormat::single(ValueType::Time)], true),
+            ..Default::default()
         },
         PropertyInfo {
             name: "transition-property",
@@ -871,6 +974,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             documentation_url: TRANSITIONS_URL.to_string(),
             inherited: false,
             animatable: PropertyAnimation::None,
+            min_unity_version: Some(UnityVersion::new(2021, 2)),
             value_spec: ValueSpec::new_with_multiple(vec![ValueFormat::one_of(vec![
                 ValueType::PropertyName,
                 ValueType::Keyword("none"),
@@ -878,6 +982,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
                 ValueType::Keyword("initial"),
                 ValueType::Keyword("ignored"),
             ])], true),
+            ..Default::default()
         },
         PropertyInfo {
             name: "transition-timing-function",
@@ -888,7 +993,9 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             documentation_url: TRANSITIONS_URL.to_string(),
             inherited: false,
             animatable: PropertyAnimation::None,
+            min_unity_version: Some(UnityVersion::new(2021, 2)),
             value_spec: ValueSpec::new_with_multiple(vec![ValueFormat::keywords(&TIMING_FUN)], true),
+            ..Default::default()
         },
         PropertyInfo {
             name: "translate",
@@ -899,7 +1006,9 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             documentation_url: TRANSFORM_URL.to_string(),
             inherited: false,
             animatable: PropertyAnimation::Animatable,
+            min_unity_version: Some(UnityVersion::new(2021, 2)),
             value_spec: ValueSpec::new(create_translate_formats()),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-background-image-tint-color",
@@ -911,6 +1020,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::color(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-background-scale-mode",
@@ -921,7 +1031,17 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             documentation_url: format!("{SUPPORTED_PROPERTIES_URL}#unity-background"),
             inherited: false,
             animatable: PropertyAnimation::Discrete,
+            // superseded by the background properties in Unity 2022.2
+            status: PropertyStatus::Deprecated {
+                replacement: Some("background-size"),
+                value_replacements: &[
+                    ("stretch-to-fill", "100% 100%"),
+                    ("scale-and-crop", "cover"),
+                    ("scale-to-fit", "contain"),
+                ],
+            },
             value_spec: ValueSpec::keywords(&["stretch-to-fill", "scale-and-crop", "scale-to-fit"]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-editor-text-rendering-mode",
@@ -933,6 +1053,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: true,
             animatable: PropertyAnimation::None,
             value_spec: ValueSpec::keywords(&["legacy", "distance-field"]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-font",
@@ -944,6 +1065,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: true,
             animatable: PropertyAnimation::Discrete,
             value_spec: ValueSpec::one_of(vec![ValueType::Asset]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-font-definition",
@@ -955,6 +1077,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: true,
             animatable: PropertyAnimation::Discrete,
             value_spec: ValueSpec::one_of(vec![ValueType::Asset]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-font-style",
@@ -966,6 +1089,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: true,
             animatable: PropertyAnimation::Discrete,
             value_spec: ValueSpec::keywords(&["normal", "bold", "italic", "bold-and-italic"]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-overflow-clip-box",
@@ -977,6 +1101,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Discrete,
             value_spec: ValueSpec::keywords(&["padding-box", "content-box"]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-paragraph-spacing",
@@ -988,6 +1113,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: true,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::LengthPercent),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-slice-bottom",
@@ -999,6 +1125,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::Integer),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-slice-left",
@@ -1010,6 +1137,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::Integer),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-slice-right",
@@ -1021,6 +1149,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::Integer),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-slice-scale",
@@ -1032,6 +1161,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::LengthPercent),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-slice-top",
@@ -1043,6 +1173,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::Integer),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-slice-type",
@@ -1053,7 +1184,9 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             documentation_url: format!("{SUPPORTED_PROPERTIES_URL}#unity-slice"),
             inherited: false,
             animatable: PropertyAnimation::Discrete,
+            min_unity_version: Some(UnityVersion::new(6000, 0)),
             value_spec: ValueSpec::keywords(&["sliced", "tiled"]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-text-align",
@@ -1075,6 +1208,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
                 "middle-right",
                 "lower-right",
             ]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-text-generator",
@@ -1085,7 +1219,10 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             documentation_url: format!("{SUPPORTED_PROPERTIES_URL}#unity-text"),
             inherited: true,
             animatable: PropertyAnimation::None,
+            min_unity_version: Some(UnityVersion::new(6000, 0)),
+            status: PropertyStatus::Experimental,
             value_spec: ValueSpec::keywords(&["standard", "advanced"]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-text-outline",
@@ -1097,6 +1234,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::new(FlexibleFormatBuilder::any_order().optional(ValueEntry::options(vec![ValueType::LengthPercent])).optional(ValueEntry::options(vec![ValueType::Color])).build()),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-text-outline-color",
@@ -1108,6 +1246,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: true,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::color(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-text-outline-width",
@@ -1119,6 +1258,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: true,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::LengthPercent),
+            ..Default::default()
         },
         PropertyInfo {
             name: "-unity-text-overflow-position",
@@ -1130,6 +1270,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Discrete,
             value_spec: ValueSpec::keywords(&["start", "middle", "end"]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "visibility",
@@ -1141,6 +1282,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: true,
             animatable: PropertyAnimation::Discrete,
             value_spec: ValueSpec::keywords(&["visible", "hidden"]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "white-space",
@@ -1152,6 +1294,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: true,
             animatable: PropertyAnimation::Discrete,
             value_spec: ValueSpec::keywords(&["normal", "nowrap"]),
+            ..Default::default()
         },
         PropertyInfo {
             name: "width",
@@ -1163,6 +1306,7 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: false,
             animatable: PropertyAnimation::Animatable,
             value_spec: spec_length_auto.clone(),
+            ..Default::default()
         },
         PropertyInfo {
             name: "word-spacing",
@@ -1174,32 +1318,14 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
             inherited: true,
             animatable: PropertyAnimation::Animatable,
             value_spec: ValueSpec::single(ValueType::LengthPercent),
+            ..Default::default()
         },
     ];
 
     // Add 'initial' keyword support to all properties (since Unity supports this value for all properties)
     let mut enhanced_props = Vec::new();
     for mut prop in standard_props {
-        // Determine how to add 'initial' based on the property's value specification
-        if prop.value_spec.is_single_format_and_entry() {
-            // For single format and entry properties, add 'initial' to the entry
-            if let Some(format) = prop.value_spec.formats.get_mut(0) {
-                if let Some(entry) = format.entries.get_mut(0) {
-                    if !entry.options.contains(&ValueType::Keyword("initial")) {
-                        entry.options.push(ValueType::Keyword("initial"));
-                    }
-                }
-            }
-        } else {
-            // For other properties, add a separate format that accepts only 'initial'
-            let initial_format = ValueFormat {
-                entries: vec![ValueEntry {
-                    options: vec![ValueType::Keyword("initial")],
-                }],
-            };
-            prop.value_spec.formats.push(initial_format);
-        }
-
+        add_initial_keyword(&mut prop.value_spec);
         enhanced_props.push(prop);
     }
 
@@ -1210,18 +1336,62 @@ pub fn create_standard_properties() -> HashMap<&'static str, PropertyInfo> {
     properties
 }
 
-fn create_rotate_formats() -> Vec<ValueFormat> {
-    // none | [ x | y | z | <number>{3} ] && <angle> | <angle>
-    let mut r = vec![ValueFormat::keywords(&vec!["none"]), ValueFormat::single(ValueType::Angle)];
-    let entry_named_axis = ValueEntry::keywords(&vec!["x", "y", "z"]);
-    let entry_angle = ValueEntry::options(vec![ValueType::Angle]);
-    let format_angle_axis_named = FlexibleFormatBuilder::any_order().required(entry_named_axis).required(entry_angle.clone()).build();
-    let format_angle_axis = FlexibleFormatBuilder::new().range(ValueEntry::options(vec![ValueType::Number]), 3, 3).required(entry_angle.clone()).build();
-    let format_angle_axis2 = FlexibleFormatBuilder::new().required(entry_angle).range(ValueEntry::options(vec![ValueType::Number]), 3, 3).build();
-    r.extend(format_angle_axis_named);
-    r.extend(format_angle_axis);
-    r.extend(format_angle_axis2);
-    r
+/// What percentages are relative to in a property, `None` if the property doesn't take percentages
+/// or if it's not documented
+///
+/// Layout follows CSS (Unity uses Yoga), e.g. margins and paddings are relative to the width of the parent
+/// on all four sides, while transforms are relative to the element itself.
+pub fn percentage_reference(property_name: &str) -> Option<&'static str> {
+    match property_name {
+        "width" | "min-width" | "max-width" | "left" | "right" => Some("the width of the parent element"),
+        "height" | "min-height" | "max-height" | "top" | "bottom" => Some("the height of the parent element"),
+        "margin" | "margin-top" | "margin-right" | "margin-bottom" | "margin-left" | "padding" | "padding-top"
+        | "padding-right" | "padding-bottom" | "padding-left" => {
+            Some("the width of the parent element, for all sides including top and bottom")
+        }
+        "flex" | "flex-basis" => Some("the size of the parent element along its main axis (see `flex-direction`)"),
+        "translate" => Some("the element's own size, its width for x and its height for y"),
+        "transform-origin" => Some("the element's own size, its width for x and its height for y"),
+        "border-radius" | "border-top-left-radius" | "border-top-right-radius" | "border-bottom-left-radius"
+        | "border-bottom-right-radius" => Some("the element's own size"),
+        "background-position" | "background-position-x" | "background-position-y" => {
+            Some("the element's size minus the image's size, so `0%` aligns the image with the start and `100%` with the end")
+        }
+        "background-size" => Some("the element's own size"),
+        "font-size" => Some("the font size of the parent element"),
+        _ => None,
+    }
+}
+
+/// Generate a value spec from a property format
+///
+/// Only used for formats known to be supported, which is verified by tests. Should one not be, its property
+/// gets the default spec without formats, every value of it is reported instead of the server failing to start.
+fn spec_from_format(format: &'static str) -> Result<ValueSpec, ValueSyntaxError> {
+    value_spec_from_format(format).inspect_err(|e| log::error!("Invalid property format '{}': {}", format, e))
+}
+
+/// Add the `initial` keyword to a value spec, Unity supports it for all properties
+pub(crate) fn add_initial_keyword(value_spec: &mut ValueSpec) {
+    // Determine how to add 'initial' based on the property's value specification
+    if value_spec.is_single_format_and_entry() {
+        // For single format and entry properties, add 'initial' to the entry
+        if let Some(format) = value_spec.formats.get_mut(0) {
+            if let Some(entry) = format.entries.get_mut(0) {
+                if !entry.options.contains(&ValueType::Keyword("initial")) {
+                    entry.options.push(ValueType::Keyword("initial"));
+                }
+            }
+        }
+    } else {
+        // For other properties, add a separate format that accepts only 'initial'
+        let initial_format = ValueFormat {
+            entries: vec![ValueEntry {
+                options: vec![ValueType::Keyword("initial")],
+            }],
+        };
+        value_spec.formats.push(initial_format);
+    }
 }
 
 fn create_translate_formats() -> Vec<ValueFormat> {
@@ -1253,16 +1423,21 @@ fn create_transform_origin_formats() -> Vec<ValueFormat> {
     r
 }
 
-fn create_scale_formats() -> Vec<ValueFormat> {
+fn create_transition_formats() -> Vec<ValueFormat> {
     // format
-    // none | <number>{1,3}
-    let mut r = vec![ValueFormat::keywords(&vec!["none"])];
+    // [ <property> <duration> <delay>? <timing-function>? ]#, and the delay after the timing function as in CSS
+    let entry_property = ValueEntry::options(vec![ValueType::PropertyName]);
+    let entry_time = ValueEntry::options(vec![ValueType::Time]);
+    let entry_timing_function = ValueEntry::keywords(&TIMING_FUN);
 
-    let format1 = FlexibleFormatBuilder::new()
-        .range(ValueEntry::options(vec![ValueType::Number]), 1, 3)
+    let mut r = FlexibleFormatBuilder::new()
+        .required(entry_property.clone())
+        .range(entry_time.clone(), 1, 2)
+        .optional(entry_timing_function.clone())
         .build();
-
-    r.extend(format1);
+    r.push(ValueFormat {
+        entries: vec![entry_property, entry_time.clone(), entry_timing_function, entry_time],
+    });
     r
 }
 
@@ -1310,142 +1485,3 @@ fn create_flex_formats() -> Vec<ValueFormat> {
     r.extend(format5);
     r
 }
-
-fn create_formats_for_background_position() -> Vec<ValueFormat> {
-    // [[ left | center | right | top | bottom | <length-percentage> ] | [ left | center | right | <length-percentage> ] [ top | center | bottom | <length-percentage> ] | [ center | [ left | right ] <length-percentage>? ] && [ center | [ top | bottom ] <length-percentage>? ]]#
-    let mut result = vec![
-        ValueFormat::one_of(vec![
-            ValueType::Keyword("left"),
-            ValueType::Keyword("center"),
-            ValueType::Keyword("right"),
-            ValueType::Keyword("top"),
-            ValueType::Keyword("bottom"),
-            ValueType::LengthPercent,
-        ]), // single value
-        ValueFormat {
-            entries: vec![
-                ValueEntry {
-                    options: vec![
-                        ValueType::Keyword("left"),
-                        ValueType::Keyword("center"),
-                        ValueType::Keyword("right"),
-                        ValueType::LengthPercent,
-                    ],
-                },
-                ValueEntry {
-                    options: vec![
-                        ValueType::Keyword("top"),
-                        ValueType::Keyword("center"),
-                        ValueType::Keyword("bottom"),
-                        ValueType::LengthPercent,
-                    ],
-                },
-            ],
-        },
-        ValueFormat::sequence(vec![
-            ValueType::Keyword("center"),
-            ValueType::Keyword("center"),
-        ]),
-    ]; // center center
-    let format2 = FlexibleFormatBuilder::new()
-        .required(ValueEntry::keywords(&vec!["center"]))
-        .required(ValueEntry::keywords(&vec!["top", "bottom"]))
-        .optional(ValueEntry::options(vec![ValueType::LengthPercent]))
-        .build();
-    let format3 = FlexibleFormatBuilder::new()
-        .required(ValueEntry::keywords(&vec!["center"]))
-        .required(ValueEntry::keywords(&vec!["left", "right"]))
-        .optional(ValueEntry::options(vec![ValueType::LengthPercent]))
-        .build();
-    let format4 = FlexibleFormatBuilder::new()
-        .required(ValueEntry::keywords(&vec!["top", "bottom"]))
-        .optional(ValueEntry::options(vec![ValueType::LengthPercent]))
-        .required(ValueEntry::keywords(&vec!["center"]))
-        .build();
-    let format5 = FlexibleFormatBuilder::new()
-        .required(ValueEntry::keywords(&vec!["left", "right"]))
-        .optional(ValueEntry::options(vec![ValueType::LengthPercent]))
-        .required(ValueEntry::keywords(&vec!["center"]))
-        .build();
-    let format6 = FlexibleFormatBuilder::new()
-        .required(ValueEntry::keywords(&vec!["left", "right"]))
-        .optional(ValueEntry::options(vec![ValueType::LengthPercent]))
-        .required(ValueEntry::keywords(&vec!["top", "bottom"]))
-        .optional(ValueEntry::options(vec![ValueType::LengthPercent]))
-        .build();
-    let format7 = FlexibleFormatBuilder::new()
-        .required(ValueEntry::keywords(&vec!["top", "bottom"]))
-        .optional(ValueEntry::options(vec![ValueType::LengthPercent]))
-        .required(ValueEntry::keywords(&vec!["left", "right"]))
-        .optional(ValueEntry::options(vec![ValueType::LengthPercent]))
-        .build();
-    result.extend(format2.into_iter());
-    result.extend(format3.into_iter());
-    result.extend(format4.into_iter());
-    result.extend(format5.into_iter());
-    result.extend(format6.into_iter());
-    result.extend(format7.into_iter());
-    result
-}
-
-fn create_formats_for_background_position_x() -> Vec<ValueFormat> {
-    // [ center | [ [ left | right | x-start | x-end ]? <length-percentage>? ]! ]#
-    let mut result = vec![
-        ValueFormat::keywords(&vec!["center"]), // center
-    ];
-    let format2 = FlexibleFormatBuilder::new()
-        .optional(ValueEntry::keywords(&vec![
-            "left", "right", "x-start", "x-end",
-        ]))
-        .optional(ValueEntry::options(vec![ValueType::LengthPercent]))
-        .build();
-    result.extend(format2.into_iter());
-    result
-}
-
-fn create_formats_for_background_position_y() -> Vec<ValueFormat> {
-    // format
-    // [ center | [ [ top | bottom | y-start | y-end ]? <length>? ]! ]
-    let mut result = vec![
-        ValueFormat::keywords(&vec!["center"]), // center
-    ];
-    let format2 = FlexibleFormatBuilder::new()
-        .optional(ValueEntry::keywords(&vec![
-            "top", "bottom", "y-start", "y-end",
-        ]))
-        .optional(ValueEntry::options(vec![ValueType::LengthPercent]))
-        .build();
-    result.extend(format2.into_iter());
-    result
-}
-
-fn create_formats_for_background_repeat() -> Vec<ValueFormat> {
-    // format
-    // repeat-x | repeat-y | [ repeat | space | round | no-repeat ]{1,2}
-    let mut r = vec![ValueFormat::keywords(&vec!["repeat-x", "repeat-y"])];
-    let format2 = FlexibleFormatBuilder::new()
-        .range(
-            ValueEntry::keywords(&vec!["repeat", "space", "round", "no-repeat"]),
-            1,
-            2,
-        )
-        .build();
-    r.extend(format2.into_iter());
-    r
-}
-
-fn create_formats_for_background_size() -> Vec<ValueFormat> {
-    // [ <length-percentage [0,∞]> | auto ]{1,2} | cover | contain
-    let mut r = vec![
-        ValueFormat::keywords(&vec!["cover", "contain"]), // single special keywords
-    ];
-    let format2 = FlexibleFormatBuilder::new()
-        .range(
-            ValueEntry::options(vec![ValueType::Keyword("auto"), ValueType::LengthPercent]),
-            1,
-            2,
-        )
-        .build();
-    r.extend(format2.into_iter());
-    r
-}
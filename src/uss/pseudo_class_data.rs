@@ -2,58 +2,90 @@ use std::collections::HashMap;
 
 use crate::uss::definitions::PseudoClassInfo;
 
+/// Unity manual page of the pseudo-classes, `{version}` is the Unity version of the docs
+const PSEUDO_CLASSES_URL: &str = "https://docs.unity3d.com/{version}/Documentation/Manual/UIE-USS-Selectors-Pseudo-Classes.html";
+
+/// Pairs of pseudo-classes that never match an element at the same time, one is the negation of the other
+const EXCLUSIVE_PSEUDO_CLASSES: [(&str, &str); 2] = [("active", "inactive"), ("disabled", "enabled")];
+
+/// Name, description, example UXML and example USS of every pseudo-class, in the order of the Unity manual
+const PSEUDO_CLASSES: [(&str, &str, &str, &str); 8] = [
+    (
+        "hover",
+        "Matches an element when the cursor is positioned over the element.",
+        r#"<ui:Button text="Play" class="play-button" />"#,
+        ".play-button:hover {\n    background-color: rgb(88, 88, 88);\n}",
+    ),
+    (
+        "active",
+        "Matches an element when a user interacts with the element.",
+        r#"<ui:Button text="Play" class="play-button" />"#,
+        ".play-button:active {\n    scale: 0.95 0.95;\n}",
+    ),
+    (
+        "inactive",
+        "Matches an element when a user stops to interact with the element.",
+        r#"<ui:Button text="Play" class="play-button" />"#,
+        ".play-button:inactive {\n    scale: 1 1;\n}",
+    ),
+    (
+        "focus",
+        "Matches an element when the element has focus.",
+        r#"<ui:TextField label="Name" class="name-field" />"#,
+        ".name-field:focus {\n    border-color: rgb(58, 121, 187);\n}",
+    ),
+    (
+        "disabled",
+        "Matches an element when the element is in a disabled state.",
+        r#"<ui:Button text="Save" enabled="false" class="save-button" />"#,
+        ".save-button:disabled {\n    opacity: 0.5;\n}",
+    ),
+    (
+        "enabled",
+        "Matches an element when the element is in an enabled state.",
+        r#"<ui:Button text="Save" class="save-button" />"#,
+        ".save-button:enabled {\n    color: rgb(255, 255, 255);\n}",
+    ),
+    (
+        "checked",
+        "Matches an element when the element is a Toggle or RadioButton element and it's selected.",
+        r#"<ui:Toggle label="Mute" class="mute-toggle" />"#,
+        ".mute-toggle:checked {\n    color: rgb(255, 196, 0);\n}",
+    ),
+    (
+        "root",
+        "Matches an element when the element is the highest-level element in the visual tree that has the stylesheet applied.",
+        "<ui:VisualElement class=\"screen\">\n    <Style src=\"Main.uss\" />\n</ui:VisualElement>",
+        ":root {\n    --accent-color: rgb(58, 121, 187);\n}",
+    ),
+];
+
 /// Create pseudo-class information with documentation
 pub fn create_pseudo_class_info() -> HashMap<&'static str, PseudoClassInfo> {
-
     let mut pseudo_classes = HashMap::new();
 
-    pseudo_classes.insert("hover", PseudoClassInfo {
-        name: "hover",
-        description: "Matches an element when the cursor is positioned over the element.",
-        documentation_url: "https://docs.unity3d.com/{version}/Documentation/Manual/UIE-USS-Selectors-Pseudo-Classes.html".to_string(),
-    });
-
-    pseudo_classes.insert("active", PseudoClassInfo {
-        name: "active",
-        description: "Matches an element when a user interacts with the element.",
-        documentation_url: "https://docs.unity3d.com/{version}/Documentation/Manual/UIE-USS-Selectors-Pseudo-Classes.html".to_string(),
-    });
-
-    pseudo_classes.insert("inactive", PseudoClassInfo {
-        name: "inactive",
-        description: "Matches an element when a user stops to interact with the element.",
-        documentation_url: "https://docs.unity3d.com/{version}/Documentation/Manual/UIE-USS-Selectors-Pseudo-Classes.html".to_string(),
-    });
-
-    pseudo_classes.insert("focus", PseudoClassInfo {
-        name: "focus",
-        description: "Matches an element when the element has focus.",
-        documentation_url: "https://docs.unity3d.com/{version}/Documentation/Manual/UIE-USS-Selectors-Pseudo-Classes.html".to_string(),
-    });
-
-    pseudo_classes.insert("disabled", PseudoClassInfo {
-        name: "disabled",
-        description: "Matches an element when the element is in a disabled state.",
-        documentation_url: "https://docs.unity3d.com/{version}/Documentation/Manual/UIE-USS-Selectors-Pseudo-Classes.html".to_string(),
-    });
-
-    pseudo_classes.insert("enabled", PseudoClassInfo {
-        name: "enabled",
-        description: "Matches an element when the element is in an enabled state.",
-        documentation_url: "https://docs.unity3d.com/{version}/Documentation/Manual/UIE-USS-Selectors-Pseudo-Classes.html".to_string(),
-    });
-
-    pseudo_classes.insert("checked", PseudoClassInfo {
-        name: "checked",
-        description: "Matches an element when the element is a Toggle or RadioButton element and it's selected.",
-        documentation_url: "https://docs.unity3d.com/{version}/Documentation/Manual/UIE-USS-Selectors-Pseudo-Classes.html".to_string(),
-    });
-
-    pseudo_classes.insert("root", PseudoClassInfo {
-        name: "root",
-        description: "Matches an element when the element is the highest-level element in the visual tree that has the stylesheet applied.",
-        documentation_url: "https://docs.unity3d.com/{version}/Documentation/Manual/UIE-USS-Selectors-Pseudo-Classes.html".to_string(),
-    });
+    for (name, description, example_uxml, example_uss) in PSEUDO_CLASSES {
+        let combinations = PSEUDO_CLASSES
+            .iter()
+            .map(|(other, ..)| *other)
+            .filter(|other| *other != name)
+            .map(|other| (other, !are_exclusive(name, other)))
+            .collect();
+
+        pseudo_classes.insert(name, PseudoClassInfo {
+            name,
+            description,
+            documentation_url: format!("{PSEUDO_CLASSES_URL}#supported-pseudo-classes"),
+            chaining_url: format!("{PSEUDO_CLASSES_URL}#chain-pseudo-classes"),
+            example_uxml,
+            example_uss,
+            combinations,
+        });
+    }
 
     pseudo_classes
 }
+
+fn are_exclusive(a: &str, b: &str) -> bool {
+    EXCLUSIVE_PSEUDO_CLASSES.iter().any(|&(x, y)| (x, y) == (a, b) || (y, x) == (a, b))
+}
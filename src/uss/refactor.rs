@@ -1,11 +1,48 @@
 //! USS Refactoring functionality
 //!
-//! Provides code actions for refactoring USS files, including renaming selectors.
+//! Provides code actions for refactoring USS files, including renaming selectors and variables.
+//!
+//! Renaming is checked before it starts, a position that can't be renamed gets a [`RenameError`] that
+//! tells why, e.g. a built-in property.
 
+use thiserror::Error;
+use tower_lsp::jsonrpc;
 use tower_lsp::lsp_types::*;
 use tree_sitter::Node;
 use crate::language::tree_utils::{node_to_range, find_node_at_position};
+use crate::language::workspace_edit::document_edit;
+use crate::uss::constants::*;
 use crate::uss::document::UssDocument;
+use crate::uss::symbol_index::{IndexedDocument, SymbolKind};
+
+/// Why a rename can't be done
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RenameError {
+    #[error("'{0}' is a property and can't be renamed")]
+    Property(String),
+    #[error("'{0}' is a type of element and can't be renamed")]
+    ElementType(String),
+    #[error("':{0}' is a pseudo-class and can't be renamed")]
+    PseudoClass(String),
+    #[error("'{0}()' is a built-in function and can't be renamed")]
+    Function(String),
+    #[error("'{0}' is a keyword and can't be renamed")]
+    Keyword(String),
+    #[error("Only class selectors, ID selectors and variables can be renamed")]
+    NotRenamable,
+    #[error("'{name}' is not a valid {kind} name")]
+    InvalidName { kind: &'static str, name: String },
+}
+
+impl From<RenameError> for jsonrpc::Error {
+    fn from(error: RenameError) -> Self {
+        jsonrpc::Error {
+            code: jsonrpc::ErrorCode::InvalidRequest,
+            message: error.to_string().into(),
+            data: None,
+        }
+    }
+}
 
 /// USS Refactor provider for code actions
 pub struct UssRefactorProvider {
@@ -106,12 +143,12 @@ impl UssRefactorProvider {
         None
     }
 
-    /// Generate workspace edit for renaming a selector
+    /// Generate workspace edit for renaming a selector, for the document at the version `content` is of
     pub fn rename_selector(
         &self,
         root_node: Node,
         content: &str,
-        uri: &Url,
+        document: &OptionalVersionedTextDocumentIdentifier,
         old_name: &str,
         new_name: &str,
         selector_type: SelectorType,
@@ -130,14 +167,7 @@ impl UssRefactorProvider {
             })
             .collect();
 
-        let mut changes = std::collections::HashMap::new();
-        changes.insert(uri.clone(), text_edits);
-
-        Some(WorkspaceEdit {
-            changes: Some(changes),
-            document_changes: None,
-            change_annotations: None,
-        })
+        Some(document_edit(document, text_edits))
     }
 
     /// Generate code actions for the given range
@@ -328,12 +358,40 @@ impl UssRefactorProvider {
         None
     }
 
+    /// Why the symbol at a position can't be renamed, for positions [`prepare_rename`](Self::prepare_rename)
+    /// and [`prepare_rename_variable`] found nothing at
+    pub fn rename_error(&self, root_node: Node, content: &str, position: Position) -> RenameError {
+        let Some(node) = find_node_at_position(root_node, position) else {
+            return RenameError::NotRenamable;
+        };
+        let text = |node: Node| node.utf8_text(content.as_bytes()).unwrap_or_default().to_string();
+        let parent_kind = node.parent().map(|parent| parent.kind());
+
+        match node.kind() {
+            NODE_PROPERTY_NAME => RenameError::Property(text(node)),
+            NODE_TAG_NAME => RenameError::ElementType(text(node)),
+            NODE_CLASS_NAME if parent_kind == Some(NODE_PSEUDO_CLASS_SELECTOR) => RenameError::PseudoClass(text(node)),
+            NODE_FUNCTION_NAME => RenameError::Function(text(node)),
+            NODE_PLAIN_VALUE => RenameError::Keyword(text(node)),
+            NODE_IDENTIFIER => match node.parent() {
+                Some(class_name)
+                    if class_name.kind() == NODE_CLASS_NAME
+                        && class_name.parent().map(|n| n.kind()) == Some(NODE_PSEUDO_CLASS_SELECTOR) =>
+                {
+                    RenameError::PseudoClass(text(class_name))
+                }
+                _ => RenameError::NotRenamable,
+            },
+            _ => RenameError::NotRenamable,
+        }
+    }
+
     /// Handle rename operation by finding the selector and generating workspace edit
     pub fn handle_rename<'a>(
         &self,
         root_node: Node<'a>,
         content: &str,
-        uri: &Url,
+        document: &OptionalVersionedTextDocumentIdentifier,
         position: Position,
         new_name: &str,
     ) -> Option<WorkspaceEdit> {
@@ -358,7 +416,7 @@ impl UssRefactorProvider {
                                         return self.rename_selector(
                                             root_node,
                                             content,
-                                            uri,
+                                            document,
                                             identifier_text,
                                             new_name,
                                             SelectorType::Class,
@@ -374,7 +432,7 @@ impl UssRefactorProvider {
                                         return self.rename_selector(
                                             root_node,
                                             content,
-                                            uri,
+                                            document,
                                             identifier_text,
                                             new_name,
                                             SelectorType::Id,
@@ -414,7 +472,7 @@ impl UssRefactorProvider {
                     return self.rename_selector(
                         root_node,
                         content,
-                        uri,
+                        document,
                         old_name,
                         new_name,
                         selector_type,
@@ -433,6 +491,68 @@ impl UssRefactorProvider {
     }
 }
 
+/// Range and name of the variable at a position, from the symbols of the document
+pub fn prepare_rename_variable(symbols: &IndexedDocument, position: Position) -> Option<PrepareRenameResponse> {
+    let symbol = symbols.symbol_at(position).filter(|symbol| symbol.kind == SymbolKind::Variable)?;
+    Some(PrepareRenameResponse::RangeWithPlaceholder {
+        range: symbol.range,
+        placeholder: symbol.name.clone(),
+    })
+}
+
+/// Rename the variable at a position in its declarations and `var()` references in the document
+///
+/// The `--` prefix is added to a new name without it.
+pub fn rename_variable(
+    symbols: &IndexedDocument,
+    document: &OptionalVersionedTextDocumentIdentifier,
+    position: Position,
+    new_name: &str,
+) -> Option<Result<WorkspaceEdit, RenameError>> {
+    let symbol = symbols.symbol_at(position).filter(|symbol| symbol.kind == SymbolKind::Variable)?;
+    let new_name = if new_name.starts_with("--") { new_name.to_string() } else { format!("--{}", new_name) };
+    if let Err(e) = validate_new_name(SymbolKind::Variable, &new_name) {
+        return Some(Err(e));
+    }
+
+    let edits = symbols
+        .symbols
+        .iter()
+        .filter(|other| other.kind == SymbolKind::Variable && other.name == symbol.name)
+        .map(|other| TextEdit {
+            range: other.range,
+            new_text: new_name.clone(),
+        })
+        .collect();
+    Some(Ok(document_edit(document, edits)))
+}
+
+/// Check a new name of a class, ID or variable (with its `--` prefix)
+pub fn validate_new_name(kind: SymbolKind, new_name: &str) -> Result<(), RenameError> {
+    let (kind_name, name) = match kind {
+        SymbolKind::Class => ("class", Some(new_name)),
+        SymbolKind::Id => ("ID", Some(new_name)),
+        SymbolKind::Variable => ("variable", new_name.strip_prefix("--")),
+    };
+    let is_name_char = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+    let valid = name.is_some_and(|name| {
+        let mut chars = name.chars();
+        match kind {
+            // a variable name may start with a digit, e.g. `--2x`
+            SymbolKind::Variable => !name.is_empty() && name.chars().all(is_name_char),
+            _ => {
+                let first = chars.clone().find(|c| *c != '-');
+                chars.all(is_name_char) && first.is_some_and(|c| c.is_alphabetic() || c == '_') && !name.starts_with("--")
+            }
+        }
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err(RenameError::InvalidName { kind: kind_name, name: new_name.to_string() })
+    }
+}
+
 /// Type of CSS selector
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SelectorType {
@@ -2,9 +2,9 @@
 //!
 //! These tests help understand the tree structure and validate refactoring operations.
 
-use std::sync::Arc;
 
 use crate::{language::tree_utils::find_node_at_position, uss::definitions::UssDefinitions};
+use crate::language::workspace_edit::{text_edits, versioned_document};
 use crate::uss::parser::UssParser;
 use crate::uss::refactor::*;
 use tower_lsp::lsp_types::{Position, PrepareRenameResponse, Range};
@@ -138,7 +138,7 @@ fn test_rename_class_selector() {
     let workspace_edit = provider.rename_selector(
         tree.root_node(),
         content,
-        &uri,
+        &versioned_document(&uri, None),
         "old-class",
         "new-class",
         SelectorType::Class,
@@ -147,8 +147,8 @@ fn test_rename_class_selector() {
     assert!(workspace_edit.is_some(), "Should generate workspace edit");
     let edit = workspace_edit.unwrap();
     
-    if let Some(changes) = edit.changes {
-        let file_changes = changes.get(&uri).expect("Should have changes for the file");
+    let file_changes = text_edits(&edit, &uri);
+    if !file_changes.is_empty() {
         assert_eq!(file_changes.len(), 2, "Should have 2 text edits for 2 occurrences");
         
         for text_edit in file_changes {
@@ -170,7 +170,7 @@ fn test_rename_id_selector() {
     let workspace_edit = provider.rename_selector(
         tree.root_node(),
         content,
-        &uri,
+        &versioned_document(&uri, None),
         "old-id",
         "new-id",
         SelectorType::Id,
@@ -179,8 +179,8 @@ fn test_rename_id_selector() {
     assert!(workspace_edit.is_some(), "Should generate workspace edit");
     let edit = workspace_edit.unwrap();
     
-    if let Some(changes) = edit.changes {
-        let file_changes = changes.get(&uri).expect("Should have changes for the file");
+    let file_changes = text_edits(&edit, &uri);
+    if !file_changes.is_empty() {
         assert_eq!(file_changes.len(), 1, "Should have 1 text edit");
         
         let text_edit = &file_changes[0];
@@ -201,7 +201,7 @@ fn test_rename_nonexistent_selector() {
     let workspace_edit = provider.rename_selector(
         tree.root_node(),
         content,
-        &uri,
+        &versioned_document(&uri, None),
         "nonexistent-class",
         "new-class",
         SelectorType::Class,
@@ -355,7 +355,7 @@ fn test_rename_class_in_chained_selector() {
     let workspace_edit = provider.rename_selector(
         tree.root_node(),
         content,
-        &uri,
+        &versioned_document(&uri, None),
         "my-class",
         "new-class",
         SelectorType::Class,
@@ -364,8 +364,8 @@ fn test_rename_class_in_chained_selector() {
     assert!(workspace_edit.is_some(), "Should generate workspace edit for chained selector");
     let edit = workspace_edit.unwrap();
     
-    if let Some(changes) = edit.changes {
-        let file_changes = changes.get(&uri).expect("Should have changes for the file");
+    let file_changes = text_edits(&edit, &uri);
+    if !file_changes.is_empty() {
         assert_eq!(file_changes.len(), 1, "Should have 1 text edit for .my-class");
         
         let text_edit = &file_changes[0];
@@ -390,7 +390,7 @@ fn test_rename_id_in_chained_selector() {
     let workspace_edit = provider.rename_selector(
         tree.root_node(),
         content,
-        &uri,
+        &versioned_document(&uri, None),
         "b",
         "new-id",
         SelectorType::Id,
@@ -399,8 +399,8 @@ fn test_rename_id_in_chained_selector() {
     assert!(workspace_edit.is_some(), "Should generate workspace edit for chained selector");
     let edit = workspace_edit.unwrap();
     
-    if let Some(changes) = edit.changes {
-        let file_changes = changes.get(&uri).expect("Should have changes for the file");
+    let file_changes = text_edits(&edit, &uri);
+    if !file_changes.is_empty() {
         assert_eq!(file_changes.len(), 1, "Should have 1 text edit for #b");
         
         let text_edit = &file_changes[0];
@@ -508,7 +508,7 @@ fn test_handle_rename_chained_selector() {
     let provider = UssRefactorProvider::new();
     let content = ".class1.class2 { color: red; }\n.class1 { margin: 10px; }\n.class2 { padding: 5px; }";
     let uri = Url::parse("file:///test.uss").unwrap();
-    let mut document = UssDocument::new(uri.clone(), content.to_string(), 1, Arc::new(UssDefinitions::new()));
+    let mut document = UssDocument::new(uri.clone(), content.to_string(), 1, UssDefinitions::shared());
     
     // Parse the document to create the syntax tree
     let mut parser = crate::uss::parser::UssParser::new().expect("Failed to create USS parser");
@@ -522,16 +522,12 @@ fn test_handle_rename_chained_selector() {
     let position = Position::new(0, 1); // Position at start of class1
     let new_name = "newclass1";
     
-    let workspace_edit = provider.handle_rename(tree.root_node(), document.content(), &uri, position, new_name);
+    let workspace_edit = provider.handle_rename(tree.root_node(), document.content(), &versioned_document(&uri, Some(1)), position, new_name);
     assert!(workspace_edit.is_some(), "handle_rename should return a WorkspaceEdit");
     
     let edit = workspace_edit.unwrap();
-    assert!(edit.changes.is_some(), "WorkspaceEdit should contain changes");
-    
-    let changes = edit.changes.unwrap();
-    assert!(changes.contains_key(&uri), "Changes should contain edits for the test file");
-    
-    let text_edits = &changes[&uri];
+    let text_edits = text_edits(&edit, &uri);
+    assert!(!text_edits.is_empty(), "Changes should contain edits for the test file");
     
     // We expect at least 2 edits: one for .class1.class2 and one for standalone .class1
     assert!(text_edits.len() >= 2, "Should have at least 2 text edits, got {}", text_edits.len());
@@ -592,3 +588,58 @@ fn test_prepare_rename_chained_selector() {
         PrepareRenameResponse::DefaultBehavior { default_behavior } => {},
     }
 }
+
+#[test]
+fn test_rename_variable() {
+    let content = ":root { --main-color: red; }\n.a { color: var(--main-color); border-color: var(--other); }";
+    let uri = Url::parse("file:///test.uss").unwrap();
+    let mut document = UssDocument::new(uri.clone(), content.to_string(), 1, UssDefinitions::shared());
+    document.parse(&mut UssParser::new().unwrap());
+    let symbols = document.indexed_symbols();
+
+    let position = Position::new(1, 20); // in var(--main-color)
+    match prepare_rename_variable(&symbols, position) {
+        Some(PrepareRenameResponse::RangeWithPlaceholder { range, placeholder }) => {
+            assert_eq!(placeholder, "--main-color");
+            assert_eq!(range, Range::new(Position::new(1, 16), Position::new(1, 28)));
+        }
+        other => panic!("unexpected response {:?}", other),
+    }
+
+    // the prefix is added when it's left out
+    let edit = rename_variable(&symbols, &versioned_document(&uri, Some(1)), position, "accent").unwrap().unwrap();
+    let edits = text_edits(&edit, &uri);
+    assert_eq!(edits.len(), 2);
+    assert!(edits.iter().all(|edit| edit.new_text == "--accent"));
+
+    let error = rename_variable(&symbols, &versioned_document(&uri, Some(1)), position, "--a b").unwrap();
+    assert_eq!(error.unwrap_err().to_string(), "'--a b' is not a valid variable name");
+}
+
+#[test]
+fn test_rename_error() {
+    let provider = UssRefactorProvider::new();
+    let content = "Button:hover { color: red; background-image: url(\"a.png\"); }";
+    let tree = UssParser::new().unwrap().parse(content, None).unwrap();
+    let error_at = |character| provider.rename_error(tree.root_node(), content, Position::new(0, character)).to_string();
+
+    assert_eq!(error_at(2), "'Button' is a type of element and can't be renamed");
+    assert_eq!(error_at(9), "':hover' is a pseudo-class and can't be renamed");
+    assert_eq!(error_at(16), "'color' is a property and can't be renamed");
+    assert_eq!(error_at(23), "'red' is a keyword and can't be renamed");
+    assert_eq!(error_at(46), "'url()' is a built-in function and can't be renamed");
+    assert_eq!(error_at(14), "Only class selectors, ID selectors and variables can be renamed");
+}
+
+#[test]
+fn test_validate_new_name() {
+    use crate::uss::symbol_index::SymbolKind;
+
+    assert!(validate_new_name(SymbolKind::Class, "primary-button").is_ok());
+    assert!(validate_new_name(SymbolKind::Class, "-unity-like").is_ok());
+    assert!(validate_new_name(SymbolKind::Class, ".button").is_err());
+    assert!(validate_new_name(SymbolKind::Class, "2col").is_err());
+    assert!(validate_new_name(SymbolKind::Id, "title bar").is_err());
+    assert!(validate_new_name(SymbolKind::Variable, "--spacing-2").is_ok());
+    assert!(validate_new_name(SymbolKind::Variable, "spacing").is_err());
+}
@@ -0,0 +1,198 @@
+//! Reference Form Code Actions
+//!
+//! Assets can be referenced with a path relative to the style sheet (`../Images/icon.png`) or with a project
+//! URL (`project:///Assets/UI/Images/icon.png`). Teams usually settle on one form, so `@import` and `url()`
+//! references have code actions that convert one form to the other: a relative path becomes a project URL,
+//! a project URL or an absolute path (`/Assets/UI/Images/icon.png`) becomes a relative path.
+//!
+//! The form of the reference is all that changes, its quotes and the query of a sprite reference
+//! (`?fileID=...&guid=...`) are kept. An action is only offered if the asset exists and the new reference
+//! resolves to the same asset. `resource()` names an asset in a `Resources` folder, so it isn't converted.
+
+use tower_lsp::lsp_types::*;
+use tree_sitter::{Node, Tree};
+use url::Url;
+
+use crate::language::asset_url::{project_url_to_relative_path, relative_reference_path, validate_url_complete};
+use crate::language::tree_utils::{find_node_at_position, node_to_range};
+use crate::language::workspace_edit::document_edit;
+use crate::unity_asset_database::AssetPathResolver;
+use crate::uss::constants::*;
+use crate::uss::import_node::ImportNode;
+use crate::uss::url_function_node::UrlFunctionNode;
+use crate::uss::uss_utils::convert_uss_string;
+
+/// Code actions that convert the reference at `range` to the other form
+///
+/// `source_url` is the project URL of the style sheet.
+pub fn reference_form_actions(
+    tree: &Tree,
+    content: &str,
+    source_url: &Url,
+    document: &OptionalVersionedTextDocumentIdentifier,
+    range: Range,
+    paths: &AssetPathResolver,
+) -> Vec<CodeActionOrCommand> {
+    let Some((argument, path, is_import)) = reference_at(tree, content, range.start) else {
+        return Vec::new();
+    };
+    let Some(new_path) = convert_path(&path, source_url, is_import, paths) else {
+        return Vec::new();
+    };
+
+    let raw = argument.utf8_text(content.as_bytes()).unwrap_or_default();
+    let quote = raw.chars().next().filter(|c| *c == '"' || *c == '\'').map(String::from).unwrap_or_default();
+    let title = if new_path.starts_with("project:") {
+        format!("Use project URL '{}'", new_path)
+    } else {
+        format!("Use relative path '{}'", new_path)
+    };
+    let edit = TextEdit {
+        range: node_to_range(argument, content),
+        new_text: format!("{}{}{}", quote, new_path, quote),
+    };
+    vec![CodeActionOrCommand::CodeAction(CodeAction {
+        title,
+        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+        edit: Some(document_edit(document, vec![edit])),
+        ..Default::default()
+    })]
+}
+
+/// The argument of the `@import` or `url()` at a position, its path and whether it's an import
+pub fn reference_at<'a>(tree: &'a Tree, content: &str, position: Position) -> Option<(Node<'a>, String, bool)> {
+    let mut node = find_node_at_position(tree.root_node(), position)?;
+    loop {
+        match node.kind() {
+            NODE_CALL_EXPRESSION => {
+                if let Some(url) = UrlFunctionNode::from_node(node, content, None, None, None, false) {
+                    let is_import = node.parent().is_some_and(|parent| parent.kind() == NODE_IMPORT_STATEMENT);
+                    return Some((url.argument_node, url.url_string, is_import));
+                }
+            }
+            NODE_IMPORT_STATEMENT => {
+                let argument = ImportNode::from_node(node, content, &mut Vec::new())?.argument_node;
+                if argument.kind() == NODE_CALL_EXPRESSION {
+                    let url = UrlFunctionNode::from_node(argument, content, None, None, None, true)?;
+                    return Some((url.argument_node, url.url_string, true));
+                }
+                let path = convert_uss_string(argument.utf8_text(content.as_bytes()).ok()?).ok()?;
+                return Some((argument, path, true));
+            }
+            _ => {}
+        }
+        node = node.parent()?;
+    }
+}
+
+/// `path` in the other form, `None` if its asset doesn't exist or the new path doesn't resolve to it
+fn convert_path(path: &str, source_url: &Url, is_import: bool, paths: &AssetPathResolver) -> Option<String> {
+    // characters that would have to be escaped in the new path
+    if path.contains(['"', '\'', '\\']) {
+        return None;
+    }
+    let url = validate_url_complete(path, Some(source_url), is_import).ok()?.url;
+    if url.scheme() != PROJECT_SCHEME {
+        return None;
+    }
+    let mut target = url.clone();
+    target.set_query(None);
+    target.set_fragment(None);
+    paths.resolve_project_url(&target)?;
+
+    // e.g. `?fileID=...&guid=...&type=3#name` of a sprite in a texture
+    let query = &path[path.find(['?', '#']).unwrap_or(path.len())..];
+    let new_path = if path.starts_with("project:") || path.starts_with('/') {
+        relative_reference_path(source_url, &target)?
+    } else {
+        format!("project:///{}", project_url_to_relative_path(&target)?)
+    } + query;
+
+    // `project:/Assets/...` has no host and `project:///Assets/...` an empty one, only the rest must be equal
+    let resolved = validate_url_complete(&new_path, Some(source_url), is_import).ok()?.url;
+    let same = |a: &Url, b: &Url| a.scheme() == b.scheme() && a.path() == b.path() && a.query() == b.query() && a.fragment() == b.fragment();
+    same(&resolved, &url).then_some(new_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::workspace_edit::text_edits;
+    use crate::language::workspace_edit::versioned_document;
+    use crate::test_utils::UnityProjectBuilder;
+    use crate::uss::parser::UssParser;
+
+    /// Titles and new texts of the actions at the first `|` in `content`
+    fn actions(paths: &AssetPathResolver, content: &str) -> Vec<(String, String)> {
+        let offset = content.find('|').unwrap();
+        let content = content.replace('|', "");
+        let position = Position::new(0, offset as u32);
+        let tree = UssParser::new().unwrap().parse(&content, None).unwrap();
+        let source_url = Url::parse("project:///Assets/UI/Main/main.uss").unwrap();
+        let uri = Url::parse("file:///project/Assets/UI/Main/main.uss").unwrap();
+        let document = versioned_document(&uri, Some(1));
+
+        reference_form_actions(&tree, &content, &source_url, &document, Range::new(position, position), paths)
+            .into_iter()
+            .map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => {
+                    let edit = action.edit.unwrap();
+                    (action.title, text_edits(&edit, &uri)[0].new_text.clone())
+                }
+                CodeActionOrCommand::Command(_) => panic!("expected a code action"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_convert_reference_form() {
+        let project = UnityProjectBuilder::new()
+            .file("Assets/UI/Shared/common.uss", "")
+            .file("Assets/UI/Images/star.png", "")
+            .file("Assets/UI/Main/local.uss", "")
+            .build()
+            .unwrap();
+        let paths = AssetPathResolver::new(project.path());
+
+        assert_eq!(
+            actions(&paths, "@import \"../Shar|ed/common.uss\";"),
+            vec![(
+                "Use project URL 'project:///Assets/UI/Shared/common.uss'".to_string(),
+                "\"project:///Assets/UI/Shared/common.uss\"".to_string()
+            )]
+        );
+        assert_eq!(
+            actions(&paths, "@import url('project:/Assets/UI/Main/lo|cal.uss');"),
+            vec![("Use relative path 'local.uss'".to_string(), "'local.uss'".to_string())]
+        );
+        assert_eq!(
+            actions(&paths, ".a { background-image: u|rl(\"/Assets/UI/Images/star.png?fileID=2&guid=abc&type=3#star\"); }"),
+            vec![(
+                "Use relative path '../Images/star.png?fileID=2&guid=abc&type=3#star'".to_string(),
+                "\"../Images/star.png?fileID=2&guid=abc&type=3#star\"".to_string()
+            )]
+        );
+        assert_eq!(
+            actions(&paths, ".a { background-image: url(\"../Images/st|ar.png\"); }"),
+            vec![(
+                "Use project URL 'project:///Assets/UI/Images/star.png'".to_string(),
+                "\"project:///Assets/UI/Images/star.png\"".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_no_conversion() {
+        let project = UnityProjectBuilder::new().file("Assets/UI/Images/star.png", "").build().unwrap();
+        let paths = AssetPathResolver::new(project.path());
+
+        // the asset doesn't exist
+        assert!(actions(&paths, "@import \"miss|ing.uss\";").is_empty());
+        // not a file of the project
+        assert!(actions(&paths, "@import \"unity-theme://def|ault\";").is_empty());
+        // named by resource()
+        assert!(actions(&paths, ".a { background-image: resource(\"Images/st|ar\"); }").is_empty());
+        // not a reference
+        assert!(actions(&paths, ".a { co|lor: red; }").is_empty());
+    }
+}
@@ -0,0 +1,270 @@
+//! USS Rule Matching
+//!
+//! Finds the USS rules of the project that apply to an element of a UXML document, like the "Styles"
+//! panel of a web inspector. The editor asks with the custom request [`MATCH_RULES_METHOD`].
+//!
+//! Selectors are matched on type, name, classes and ancestors. Pseudo-classes depend on runtime state,
+//! so rules with pseudo-classes are returned too, with their pseudo-classes, for the client to show
+//! as conditional.
+
+use std::cmp::Reverse;
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Position, Range, TextDocumentIdentifier, Url};
+use tree_sitter::{Node, Tree};
+
+use crate::language::tree_utils::node_to_range;
+use crate::uss::constants::*;
+use crate::uss::selector::{rule_selector_texts, ComplexSelector};
+use crate::uxml::parser::UxmlElement;
+use crate::uxml::usage_index::selector_matches;
+
+/// Custom request for the rules that apply to a UXML element, takes [`MatchRulesParams`] and returns [`MatchRulesResult`]
+pub const MATCH_RULES_METHOD: &str = "unityCode/matchRules";
+
+/// Parameters of [`MATCH_RULES_METHOD`], the element is given by `element_path` or by `position`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchRulesParams {
+    /// The UXML document
+    pub text_document: TextDocumentIdentifier,
+    /// Content of the document if it has unsaved changes, otherwise it's read from disk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Position in the document, the innermost element that contains it is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<Position>,
+    /// Child indices from the root element, e.g. `[0, 2]` is the third child of the first child of the root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub element_path: Option<Vec<usize>>,
+}
+
+/// The element that rules were matched against
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchedElement {
+    pub tag: String,
+    pub name: Option<String>,
+    pub classes: Vec<String>,
+    pub range: Range,
+}
+
+/// A declaration of a matched rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchedDeclaration {
+    pub property: String,
+    pub value: String,
+    pub important: bool,
+    pub range: Range,
+}
+
+/// A rule that applies to the element
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchedRule {
+    /// The style sheet of the rule
+    pub uri: Url,
+    /// The selector of the rule that matched, the one with the highest specificity if several did
+    pub selector: String,
+    /// Specificity of the selector as (names, classes and pseudo-classes, types)
+    pub specificity: [u32; 3],
+    /// Pseudo-classes the element must be in for the rule to apply, empty if it always applies
+    pub pseudo_classes: Vec<String>,
+    /// Range of the whole rule
+    pub range: Range,
+    pub declarations: Vec<MatchedDeclaration>,
+}
+
+/// Result of [`MATCH_RULES_METHOD`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchRulesResult {
+    /// `None` if no element was found
+    pub element: Option<MatchedElement>,
+    /// Matched rules, the rule that takes precedence first
+    pub rules: Vec<MatchedRule>,
+}
+
+impl MatchedElement {
+    pub fn new(element: &UxmlElement) -> Self {
+        Self {
+            tag: element.tag.clone(),
+            name: element.name_value().map(str::to_string),
+            classes: element.class_names().into_iter().map(str::to_string).collect(),
+            range: element.range,
+        }
+    }
+}
+
+/// Index of the innermost element that contains `position`
+pub fn find_element_at(elements: &[UxmlElement], position: Position) -> Option<usize> {
+    // elements are in document order, so the last one that contains the position is the innermost
+    elements
+        .iter()
+        .rposition(|element| element.range.start <= position && position <= element.range.end)
+}
+
+/// Index of the element at a path of child indices from the root element
+pub fn find_element_by_path(elements: &[UxmlElement], path: &[usize]) -> Option<usize> {
+    let mut current = elements.iter().position(|element| element.parent.is_none())?;
+    for &child in path {
+        current = elements
+            .iter()
+            .enumerate()
+            .filter(|(_, element)| element.parent == Some(current))
+            .nth(child)?
+            .0;
+    }
+    Some(current)
+}
+
+/// Rules of a style sheet that match the element at `index`
+pub fn match_style_sheet(tree: &Tree, content: &str, uri: &Url, elements: &[UxmlElement], index: usize) -> Vec<MatchedRule> {
+    let mut rules = Vec::new();
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    for rule_set in root.children(&mut cursor).filter(|n| n.kind() == NODE_RULE_SET) {
+        let best = rule_selector_texts(rule_set, content)
+            .into_iter()
+            .filter_map(|text| Some((text, ComplexSelector::parse(text)?)))
+            .filter(|(_, selector)| selector_matches(selector, elements, index))
+            .max_by_key(|(_, selector)| selector.specificity());
+
+        if let Some((text, selector)) = best {
+            let (names, classes, types) = selector.specificity();
+            rules.push(MatchedRule {
+                uri: uri.clone(),
+                selector: text.trim().to_string(),
+                specificity: [names, classes, types],
+                pseudo_classes: selector.subject().pseudo_classes.clone(),
+                range: node_to_range(rule_set, content),
+                declarations: rule_declarations(rule_set, content),
+            });
+        }
+    }
+    rules
+}
+
+/// Sort rules so the one that takes precedence comes first
+///
+/// Higher specificity wins, and of rules with the same specificity in the same style sheet the later one wins.
+/// Rules of different style sheets with the same specificity are ordered by style sheet for a stable result,
+/// which of them wins depends on the order the style sheets are added to the element at runtime.
+pub fn sort_by_precedence(rules: &mut [MatchedRule]) {
+    rules.sort_by(|a, b| {
+        Reverse(a.specificity)
+            .cmp(&Reverse(b.specificity))
+            .then_with(|| a.uri.as_str().cmp(b.uri.as_str()))
+            .then_with(|| Reverse(a.range.start).cmp(&Reverse(b.range.start)))
+    });
+}
+
+/// Declarations of a rule set, in source order
+pub fn rule_declarations(rule_set: Node, content: &str) -> Vec<MatchedDeclaration> {
+    let Some(block) = rule_set.child_by_field_name("block").or_else(|| rule_set.child(1)) else {
+        return Vec::new();
+    };
+
+    let mut declarations = Vec::new();
+    let mut cursor = block.walk();
+    for declaration in block.children(&mut cursor).filter(|n| n.kind() == NODE_DECLARATION) {
+        let mut property = None;
+        let mut important = false;
+        let mut value_range: Option<(usize, usize)> = None;
+        let mut after_colon = false;
+
+        let mut declaration_cursor = declaration.walk();
+        for child in declaration.children(&mut declaration_cursor) {
+            match child.kind() {
+                NODE_PROPERTY_NAME => property = child.utf8_text(content.as_bytes()).ok(),
+                NODE_COLON => after_colon = true,
+                NODE_IMPORTANT => important = true,
+                NODE_SEMICOLON | NODE_COMMENT => {}
+                _ if after_colon => {
+                    let start = value_range.map_or(child.start_byte(), |(start, _)| start);
+                    value_range = Some((start, child.end_byte()));
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(property) = property {
+            declarations.push(MatchedDeclaration {
+                property: property.to_string(),
+                value: value_range.map_or(String::new(), |(start, end)| content[start..end].to_string()),
+                important,
+                range: node_to_range(declaration, content),
+            });
+        }
+    }
+    declarations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uss::parser::UssParser;
+    use crate::uxml::parser::parse_uxml_elements;
+
+    const UXML: &str = r#"<ui:UXML xmlns:ui="UnityEngine.UIElements">
+    <ui:VisualElement name="panel" class="container">
+        <ui:Button name="ok" class="primary large" />
+        <ui:Label class="title" />
+    </ui:VisualElement>
+</ui:UXML>"#;
+
+    fn match_rules(uss: &str, elements: &[UxmlElement], index: usize) -> Vec<MatchedRule> {
+        let tree = UssParser::new().unwrap().parse(uss, None).unwrap();
+        let uri = Url::parse("project:/Assets/main.uss").unwrap();
+        let mut rules = match_style_sheet(&tree, uss, &uri, elements, index);
+        sort_by_precedence(&mut rules);
+        rules
+    }
+
+    #[test]
+    fn test_find_element() {
+        let elements = parse_uxml_elements(UXML);
+
+        assert_eq!(find_element_by_path(&elements, &[]), Some(0));
+        assert_eq!(find_element_by_path(&elements, &[0, 1]), Some(3));
+        assert_eq!(find_element_by_path(&elements, &[0, 2]), None);
+
+        assert_eq!(find_element_at(&elements, Position::new(2, 20)), Some(2));
+        // between the children, in the content of the parent
+        assert_eq!(find_element_at(&elements, Position::new(3, 0)), Some(1));
+        assert_eq!(find_element_at(&elements, Position::new(10, 0)), None);
+    }
+
+    #[test]
+    fn test_match_rules_ordered_by_specificity() {
+        let elements = parse_uxml_elements(UXML);
+        let uss = r#"
+Button { color: red; }
+.container > .primary { color: blue !important; margin: 4px 8px; }
+#ok:hover { color: green; }
+.title { color: white; }
+.large, #panel Button.primary { font-size: 20px; }
+Button { color: yellow; }
+"#;
+        let rules = match_rules(uss, &elements, 2);
+        let selectors: Vec<&str> = rules.iter().map(|r| r.selector.as_str()).collect();
+        assert_eq!(
+            selectors,
+            vec!["#panel Button.primary", "#ok:hover", ".container > .primary", "Button", "Button"]
+        );
+
+        assert_eq!(rules[0].specificity, [1, 1, 1]);
+        assert!(rules[0].pseudo_classes.is_empty());
+        assert_eq!(rules[1].pseudo_classes, vec!["hover"]);
+        // of equal rules the later one wins
+        assert_eq!(rules[3].declarations[0].value, "yellow");
+
+        let declarations = &rules[2].declarations;
+        assert_eq!(declarations.len(), 2);
+        assert_eq!(declarations[0].property, "color");
+        assert_eq!(declarations[0].value, "blue");
+        assert!(declarations[0].important);
+        assert_eq!(declarations[1].value, "4px 8px");
+    }
+}
@@ -0,0 +1,272 @@
+//! USS Selector Model
+//!
+//! A lightweight structural model of USS selectors, parsed from selector text.
+//!
+//! tree-sitter-css nests compound and complex selectors in ways that are awkward to walk
+//! (e.g. `.a .b#c` is parsed as an `id_selector` wrapping a `descendant_selector`),
+//! so features that need to reason about what a selector targets use this model instead.
+
+use tree_sitter::Node;
+
+use crate::uss::constants::*;
+use crate::uss::uss_utils::unescape_identifier;
+
+/// Combinator between two compound selectors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combinator {
+    /// Whitespace, eg. `.a .b`
+    Descendant,
+    /// `>`, eg. `.a > .b`
+    Child,
+}
+
+/// A compound selector, ie. a sequence of simple selectors without combinators (e.g. `Button.primary:hover`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompoundSelector {
+    /// Type selector (UXML element name), if any
+    pub tag: Option<String>,
+    /// Whether the universal selector `*` is present
+    pub is_universal: bool,
+    /// Name selector (`#name`), if any
+    pub id: Option<String>,
+    /// Class selectors without the leading dot
+    pub classes: Vec<String>,
+    /// Pseudo-classes without the leading colon
+    pub pseudo_classes: Vec<String>,
+}
+
+impl CompoundSelector {
+    /// Check if this compound selector matches an element, ignoring pseudo-classes
+    ///
+    /// Pseudo-classes depend on runtime state, so they are treated as always matching.
+    pub fn matches_element(&self, tag: &str, name: Option<&str>, classes: &[&str]) -> bool {
+        if let Some(expected_tag) = &self.tag {
+            if expected_tag != tag {
+                return false;
+            }
+        }
+
+        if let Some(expected_id) = &self.id {
+            if name != Some(expected_id.as_str()) {
+                return false;
+            }
+        }
+
+        self.classes.iter().all(|c| classes.contains(&c.as_str()))
+    }
+
+    /// Whether this compound and `other` target the same elements, one possibly narrower than the other
+    ///
+    /// They do if their types and names don't contradict and the classes of one are all in the other,
+    /// e.g. `Button` and `.primary`, or `.a` and `.a.b`, but not `.a` and `.b` which only apply to
+    /// the same element if it happens to have both classes. Pseudo-classes are ignored.
+    pub fn overlaps(&self, other: &CompoundSelector) -> bool {
+        let compatible = |a: &Option<String>, b: &Option<String>| a.is_none() || b.is_none() || a == b;
+        let contains_all = |a: &[String], b: &[String]| b.iter().all(|class| a.contains(class));
+        compatible(&self.tag, &other.tag)
+            && compatible(&self.id, &other.id)
+            && (contains_all(&self.classes, &other.classes) || contains_all(&other.classes, &self.classes))
+    }
+
+    /// Whether this compound has no simple selectors at all
+    pub fn is_empty(&self) -> bool {
+        self.tag.is_none()
+            && !self.is_universal
+            && self.id.is_none()
+            && self.classes.is_empty()
+            && self.pseudo_classes.is_empty()
+    }
+}
+
+/// A complex selector, ie. compound selectors joined by combinators (e.g. `.panel > Button:hover`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComplexSelector {
+    /// Compound selectors in source order, each with the combinator that precedes it
+    /// (the first compound has no combinator)
+    pub parts: Vec<(Option<Combinator>, CompoundSelector)>,
+}
+
+impl ComplexSelector {
+    /// Parse a single selector (no commas) from its source text
+    ///
+    /// Returns `None` if the text contains anything this model doesn't understand.
+    pub fn parse(text: &str) -> Option<Self> {
+        let chars: Vec<char> = strip_comments(text).chars().collect();
+        let mut parts: Vec<(Option<Combinator>, CompoundSelector)> = Vec::new();
+        let mut current = CompoundSelector::default();
+        let mut pending_combinator: Option<Combinator> = None;
+        let mut i = 0;
+
+        while i < chars.len() {
+            let ch = chars[i];
+            if ch.is_whitespace() || ch == '>' {
+                // close the current compound, if any
+                if !current.is_empty() {
+                    parts.push((pending_combinator.take(), std::mem::take(&mut current)));
+                    pending_combinator = Some(Combinator::Descendant);
+                }
+                if ch == '>' {
+                    if parts.is_empty() || pending_combinator.is_none() {
+                        return None;
+                    }
+                    pending_combinator = Some(Combinator::Child);
+                }
+                i += 1;
+                continue;
+            }
+
+            match ch {
+                '*' => {
+                    current.is_universal = true;
+                    i += 1;
+                }
+                '.' | '#' | ':' => {
+                    let (name, next) = read_identifier(&chars, i + 1)?;
+                    match ch {
+                        '.' => current.classes.push(name),
+                        '#' => current.id = Some(name),
+                        _ => current.pseudo_classes.push(name),
+                    }
+                    i = next;
+                }
+                _ => {
+                    let (name, next) = read_identifier(&chars, i)?;
+                    current.tag = Some(name);
+                    i = next;
+                }
+            }
+        }
+
+        if current.is_empty() {
+            // trailing combinator like `.a >` is not a valid selector
+            if pending_combinator == Some(Combinator::Child) {
+                return None;
+            }
+        } else {
+            parts.push((pending_combinator.take(), current));
+        }
+
+        if parts.is_empty() {
+            return None;
+        }
+
+        Some(Self { parts })
+    }
+
+    /// The subject of the selector, ie. the rightmost compound, which is the element the rule applies to
+    pub fn subject(&self) -> &CompoundSelector {
+        // parse never produces an empty selector
+        &self.parts.last().expect("selector has at least one compound").1
+    }
+
+    /// Selector specificity as (names, classes and pseudo-classes, types)
+    pub fn specificity(&self) -> (u32, u32, u32) {
+        let mut specificity = (0, 0, 0);
+        for (_, compound) in &self.parts {
+            if compound.id.is_some() {
+                specificity.0 += 1;
+            }
+            specificity.1 += (compound.classes.len() + compound.pseudo_classes.len()) as u32;
+            if compound.tag.is_some() {
+                specificity.2 += 1;
+            }
+        }
+        specificity
+    }
+}
+
+/// Extract the source text of every selector in a rule set (split at top level commas)
+pub fn rule_selector_texts<'a>(rule_set: Node, content: &'a str) -> Vec<&'a str> {
+    let mut texts = Vec::new();
+    let Some(selectors) = rule_set.child(0).filter(|n| n.kind() == NODE_SELECTORS) else {
+        return texts;
+    };
+
+    for i in 0..selectors.child_count() {
+        if let Some(child) = selectors.child(i) {
+            if child.kind() == NODE_COMMA || child.kind() == NODE_COMMENT {
+                continue;
+            }
+            if let Ok(text) = child.utf8_text(content.as_bytes()) {
+                texts.push(text);
+            }
+        }
+    }
+
+    texts
+}
+
+/// Parse all selectors of a rule set, skipping the ones that can't be understood
+pub fn parse_rule_selectors(rule_set: Node, content: &str) -> Vec<ComplexSelector> {
+    rule_selector_texts(rule_set, content)
+        .into_iter()
+        .filter_map(ComplexSelector::parse)
+        .collect()
+}
+
+/// Read an identifier starting at `start`, returns the identifier with its escapes resolved and the index after it
+fn read_identifier(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let mut end = start;
+    while end < chars.len() {
+        if chars[end] == '\\' {
+            end = escape_end(chars, end)?;
+        } else if is_identifier_char(chars[end]) {
+            end += 1;
+        } else {
+            break;
+        }
+    }
+
+    if end == start {
+        return None;
+    }
+
+    let text: String = chars[start..end].iter().collect();
+    Some((unescape_identifier(&text), end))
+}
+
+/// Index after the escape whose backslash is at `start`, `None` if the backslash doesn't start an escape
+///
+/// A hexadecimal escape is up to 6 digits and the whitespace after them, e.g. `\3A ` in `.hover\3A red`.
+fn escape_end(chars: &[char], start: usize) -> Option<usize> {
+    let next = *chars.get(start + 1)?;
+    if matches!(next, '\n' | '\r' | '\x0C') {
+        return None;
+    }
+    if !next.is_ascii_hexdigit() {
+        return Some(start + 2);
+    }
+
+    let mut end = start + 1;
+    while end < chars.len() && end - start <= 6 && chars[end].is_ascii_hexdigit() {
+        end += 1;
+    }
+    if end < chars.len() && chars[end].is_whitespace() {
+        end += 1;
+    }
+    Some(end)
+}
+
+fn is_identifier_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '-' || ch == '_'
+}
+
+/// Replace comments with a single space so they still separate compounds
+fn strip_comments(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("/*") {
+        result.push_str(&rest[..start]);
+        result.push(' ');
+        match rest[start + 2..].find("*/") {
+            Some(end) => rest = &rest[start + 2 + end + 2..],
+            None => return result,
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+#[path = "selector_tests.rs"]
+mod selector_tests;
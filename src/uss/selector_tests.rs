@@ -0,0 +1,108 @@
+use super::*;
+use crate::uss::parser::UssParser;
+
+#[test]
+fn test_parse_compound_selector() {
+    let selector = ComplexSelector::parse("Button.primary.large:hover").unwrap();
+    assert_eq!(selector.parts.len(), 1);
+
+    let subject = selector.subject();
+    assert_eq!(subject.tag.as_deref(), Some("Button"));
+    assert_eq!(subject.classes, vec!["primary", "large"]);
+    assert_eq!(subject.pseudo_classes, vec!["hover"]);
+    assert!(subject.id.is_none());
+}
+
+#[test]
+fn test_parse_combinators() {
+    let selector = ComplexSelector::parse(".panel > Label #title").unwrap();
+    assert_eq!(selector.parts.len(), 3);
+    assert_eq!(selector.parts[0].0, None);
+    assert_eq!(selector.parts[1].0, Some(Combinator::Child));
+    assert_eq!(selector.parts[2].0, Some(Combinator::Descendant));
+    assert_eq!(selector.subject().id.as_deref(), Some("title"));
+}
+
+#[test]
+fn test_parse_invalid_selector() {
+    assert!(ComplexSelector::parse("").is_none());
+    assert!(ComplexSelector::parse(".a >").is_none());
+    assert!(ComplexSelector::parse("> .a").is_none());
+    assert!(ComplexSelector::parse(".").is_none());
+    assert!(ComplexSelector::parse("[type=text]").is_none());
+}
+
+#[test]
+fn test_parse_with_comments() {
+    let selector = ComplexSelector::parse(".a/* comment */.b").unwrap();
+    assert_eq!(selector.parts.len(), 2);
+}
+
+#[test]
+fn test_specificity() {
+    let selector = ComplexSelector::parse("#root .panel Button:hover").unwrap();
+    assert_eq!(selector.specificity(), (1, 2, 1));
+
+    let selector = ComplexSelector::parse("*").unwrap();
+    assert_eq!(selector.specificity(), (0, 0, 0));
+}
+
+#[test]
+fn test_matches_element() {
+    let selector = ComplexSelector::parse("Button.primary:hover").unwrap();
+    let subject = selector.subject();
+
+    assert!(subject.matches_element("Button", None, &["primary", "other"]));
+    assert!(!subject.matches_element("Label", None, &["primary"]));
+    assert!(!subject.matches_element("Button", None, &["other"]));
+
+    let selector = ComplexSelector::parse("#title").unwrap();
+    assert!(selector.subject().matches_element("Label", Some("title"), &[]));
+    assert!(!selector.subject().matches_element("Label", Some("other"), &[]));
+}
+
+#[test]
+fn test_parse_escapes() {
+    let selector = ComplexSelector::parse(r".hover\:bg-red .md\3A w-full#item\.1").unwrap();
+    assert_eq!(selector.parts.len(), 2);
+    assert_eq!(selector.parts[0].1.classes, vec!["hover:bg-red"]);
+    // the space after a hexadecimal escape belongs to it
+    assert_eq!(selector.parts[1].1.classes, vec!["md:w-full"]);
+    assert_eq!(selector.parts[1].1.id.as_deref(), Some("item.1"));
+
+    let selector = ComplexSelector::parse(r".\31 0").unwrap();
+    assert!(selector.subject().matches_element("Label", None, &["10"]));
+}
+
+#[test]
+fn test_overlaps() {
+    let overlaps = |a: &str, b: &str| {
+        let (a, b) = (ComplexSelector::parse(a).unwrap(), ComplexSelector::parse(b).unwrap());
+        a.subject().overlaps(b.subject())
+    };
+
+    assert!(overlaps("Button", ".primary"));
+    assert!(overlaps(".a", ".a.b:hover"));
+    assert!(overlaps(".panel #ok", "Button"));
+    assert!(overlaps("*", "Label.title"));
+    assert!(!overlaps(".a", ".b"));
+    assert!(!overlaps("Button", "Label"));
+    assert!(!overlaps("#ok", "#cancel.primary"));
+}
+
+#[test]
+fn test_parse_rule_selectors() {
+    let content = ".a .b#c, Button > .d, * { color: red; }";
+    let mut parser = UssParser::new().unwrap();
+    let tree = parser.parse(content, None).unwrap();
+    let rule_set = tree.root_node().child(0).unwrap();
+
+    let texts = rule_selector_texts(rule_set, content);
+    assert_eq!(texts, vec![".a .b#c", "Button > .d", "*"]);
+
+    let selectors = parse_rule_selectors(rule_set, content);
+    assert_eq!(selectors.len(), 3);
+    assert_eq!(selectors[0].subject().id.as_deref(), Some("c"));
+    assert_eq!(selectors[0].subject().classes, vec!["b"]);
+    assert!(selectors[2].subject().is_universal);
+}
@@ -2,26 +2,95 @@
 //!
 //! Provides Language Server Protocol features for USS files using tower-lsp.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
+use tree_sitter::Tree;
 use url::Url;
 
-use crate::language::asset_url::project_url_to_path;
+use crate::dir_changed::BULK_CHANGE_THRESHOLD;
+use crate::check::{CheckError, Checker};
+use crate::config::{ConfigError, ConfigFile, UnityCodeConfig, CONFIG_FILE_NAME, RELOAD_CONFIG_COMMAND};
+use crate::cs::docs_manager::CsDocsManager;
+use crate::cs::xml_doc_utils::summary_to_markdown;
+use crate::language::asset_filter::{FONT_ASSETS, IMAGE_ASSETS};
+use crate::language::asset_url::{create_project_url_with_normalization, project_url_to_relative_path};
+use crate::language::cancellation::CancellationToken;
+use crate::language::panic_guard::catch_unwind;
+use crate::language::progress::{ProgressCounter, ProgressReporter};
+use crate::language::project_packages::ProjectPackages;
+use crate::logging::{set_log_filter, LogFilter, SET_LOG_LEVEL_COMMAND};
+use crate::language::request_timing::{self, METRICS_COMMAND};
+use crate::language::tree_utils::position_to_byte_offset;
+use crate::language::workspace_edit::{document_edit, versioned_document, without_versions, WorkspaceEditBuilder};
+use crate::language::worker_pool::{WorkError, WorkerPool};
+use crate::unity_asset_database::AssetPathResolver;
 use crate::unity_project_manager::UnityProjectManager;
+use crate::uss::code_lens::{CodeLensData, UssCodeLensProvider};
 use crate::uss::color_provider::UssColorProvider;
 use crate::uss::completion::UssCompletionProvider;
 use crate::uss::constants::*;
-use crate::uss::diagnostics::UssDiagnostics;
+use crate::uss::diagnostics::{move_import_action, replace_deprecated_property_action, replace_keyword_action, UssDiagnostics};
+use crate::uss::diagnostics_snapshot::DiagnosticsSnapshot;
 use crate::uss::document_manager::UssDocumentManager;
+use crate::uss::file_renames::{pair_renames, update_import_action, FileMoves, RenamedFiles};
 use crate::uss::formatter::UssFormatter;
 use crate::uss::highlighting::UssHighlighter;
-use crate::uss::hover::UssHoverProvider;
-use crate::uss::refactor::UssRefactorProvider;
+use crate::uss::hover::{built_in_class_path, tag_name_at_position, UssHoverProvider};
+use crate::uss::inline_style::{
+    InlineStyle, InlineStyleParams, INLINE_STYLE_COMPLETION_METHOD, INLINE_STYLE_DIAGNOSTICS_METHOD,
+};
+use crate::uss::unused_variables::{self, UnusedVariableScope, VariableLocations};
+use crate::uss::baseline::{
+    BaselineFile, BaselineSummary, DiagnosticsBaseline, BASELINE_FILE_NAME, UPDATE_BASELINE_COMMAND,
+};
+use crate::uss::unit_conversion::{unit_conversion_actions, ConvertLengthArguments, CONVERT_LENGTH_COMMAND};
+use crate::uss::variable_resolver::VariableResolver;
+use crate::uss::url_references::{
+    FileUrlReferences, UrlReferenceResolver, UrlReferencesParams, UrlReferencesReport, URL_REFERENCES_METHOD,
+};
+use crate::uss::import_graph::{
+    find_style_sheets, find_theme_style_sheets, DependencySignature, ImportEdge, UssImportGraph, DUMP_IMPORT_GRAPH_COMMAND,
+};
+use crate::uss::parser::UssParser;
+use crate::uss::organize::{UssDeclarationOrganizer, ORGANIZE_DECLARATIONS_KIND};
+use crate::uss::profile::detect_profile;
+use crate::uss::reference_form::{reference_at, reference_form_actions};
+use crate::uss::paste::{format_pasted_action, format_pasted_edits, pasted_range, PasteFormatting, PastedText};
+use crate::uss::package_cache::{self, COPY_TO_ASSETS_COMMAND, COPY_TO_ASSETS_TITLE};
+use crate::uss::refactor::{prepare_rename_variable, rename_variable, validate_new_name, UssRefactorProvider};
+use crate::uss::cascade::{self, declaration_cascade};
+use crate::uss::rule_matching::{self, MatchRulesParams, MatchRulesResult, MatchedElement, MATCH_RULES_METHOD};
+use crate::uss::style_diff::{self, StyleDiff, StyleDiffParams, STYLE_DIFF_METHOD};
+use crate::uss::settings::UssSettings;
+use crate::uss::symbol_index::SymbolIndex;
+use crate::uss::theme_report::{variable_name_at_position, ThemeReport, ThemeReportCache};
+use crate::uxml::attribute_completion::{
+    self, UxmlAttributeCompletionParams, UXML_ATTRIBUTE_COMPLETION_METHOD,
+};
+use crate::uxml::parser::parse_uxml_elements;
+use crate::uxml::templates::{
+    TemplateResolver, UxmlTemplateDefinitionParams, UxmlTemplateDiagnosticsParams, UXML_TEMPLATE_DEFINITION_METHOD,
+    UXML_TEMPLATE_DIAGNOSTICS_METHOD,
+};
+use crate::uxml::usage_index::{self, RefreshPlan, UxmlUsageIndex};
 use crate::uxml_schema_manager::{UxmlSchemaManager, VisualElementsData};
 
+/// Fewer UXML files than this are indexed without reporting progress
+const MIN_FILES_FOR_PROGRESS: usize = 50;
+
+/// Files the editor reports changes of, so the indexes are updated without waiting for a refresh
+const WATCHED_FILE_GLOBS: &[&str] = &["**/*.uss", "**/*.tss", "**/*.uxml", "**/*.asset"];
+
+/// Assets created or deleted here change which `url()` references exist
+const WATCHED_ASSETS_GLOB: &str = "**/{Assets,Packages}/**";
+
+/// Command that indexes the project from scratch and drops cached schema and documentation data,
+/// returns the number of indexed files
+pub const REINDEX_COMMAND: &str = "unityCode/reindex";
+
 /// USS Language Server
 pub struct UssLanguageServer {
     client: Client,
@@ -31,6 +100,10 @@ pub struct UssLanguageServer {
     /// 3. Async method boundaries require thread-safe primitives even in single-threaded context
     state: Arc<Mutex<UssServerState>>,
     uxml_schema_manager: Arc<tokio::sync::Mutex<UxmlSchemaManager>>,
+    /// Documentation of the C# classes of custom elements, shared with the UDP server that compiles it
+    docs_manager: Arc<tokio::sync::Mutex<CsDocsManager>>,
+    /// Analyzes documents off the runtime, so requests are answered while many documents are analyzed
+    analysis_pool: WorkerPool<Url>,
 }
 
 /// Internal state for the USS language server
@@ -43,12 +116,56 @@ struct UssServerState {
     completion_provider: UssCompletionProvider,
     formatter: UssFormatter,
     refactor_provider: UssRefactorProvider,
+    declaration_organizer: UssDeclarationOrganizer,
+    code_lens_provider: UssCodeLensProvider,
     unity_manager: UnityProjectManager,
+    /// Index of UXML elements in the project, built lazily when code lenses are resolved
+    uxml_usage_index: UxmlUsageIndex,
+    /// Import graph of USS files in the project, built lazily when diagnostics are requested
+    import_graph: UssImportGraph,
+    /// Resolves references to assets for diagnostics, created when needed and dropped when assets are added or removed
+    asset_paths: Option<Arc<AssetPathResolver>>,
+    /// Number of times `asset_paths` was dropped, a resolver built meanwhile is outdated
+    asset_paths_generation: u64,
+    /// Where style sheets renamed during the session went, for updating imports of the old path
+    renamed_files: RenamedFiles,
+    /// Selectors and variables of the open documents, for references
+    symbol_index: SymbolIndex,
+    /// What the diagnostics of other documents depend on, by open document
+    dependency_signatures: HashMap<Url, DependencySignature>,
+    /// Parser for UXML style attributes, separate from the documents' parser
+    inline_style_parser: UssParser,
+    /// Values of variables in the project's themes, shown in hover
+    theme_report: ThemeReportCache,
+    /// Settings from the editor, before the project's configuration is applied
+    client_settings: UssSettings,
+    /// The project's `UnityCode.toml`
+    config: UnityCodeConfig,
+    config_file: ConfigFile,
+    /// The project's `UnityCode.baseline.json`, diagnostics in it are not reported
+    baseline: BaselineFile,
+    format_enabled: bool,
+    paste_formatting: PasteFormatting,
+    /// Text pasted by the last change of a document, until it changes again, for the "Format pasted text" action
+    pasted_texts: HashMap<Url, PastedText>,
+    /// Whether hover of a property lists the other rules that set it
+    hover_cascade: bool,
+    /// Whether the client accepts versioned document edits, otherwise edits are sent without versions
+    document_changes_support: bool,
+    /// Whether the client shows `window/workDoneProgress`
+    work_done_progress_support: bool,
+    /// Whether the client accepts file watchers registered by the server
+    watched_files_support: bool,
 }
 
 impl UssLanguageServer {
     /// Create a new USS language server
-    pub fn new(client: Client, project_path: std::path::PathBuf, uxml_schema_manager: Arc<tokio::sync::Mutex<UxmlSchemaManager>>) -> Self {
+    pub fn new(
+        client: Client,
+        project_path: std::path::PathBuf,
+        uxml_schema_manager: Arc<tokio::sync::Mutex<UxmlSchemaManager>>,
+        docs_manager: Arc<tokio::sync::Mutex<CsDocsManager>>,
+    ) -> Self {
         let state = UssServerState {
             document_manager: UssDocumentManager::new()
                 .expect("Failed to create USS document manager"),
@@ -59,29 +176,278 @@ impl UssLanguageServer {
             completion_provider: UssCompletionProvider::new_with_project_root(&project_path),
             formatter: UssFormatter::new(),
             refactor_provider: UssRefactorProvider::new(),
+            declaration_organizer: UssDeclarationOrganizer::new(),
+            code_lens_provider: UssCodeLensProvider::new(),
             unity_manager: UnityProjectManager::new(project_path.clone()),
+            uxml_usage_index: UxmlUsageIndex::new(project_path.clone()),
+            import_graph: UssImportGraph::new(project_path.clone()),
+            asset_paths: None,
+            asset_paths_generation: 0,
+            renamed_files: RenamedFiles::new(),
+            symbol_index: SymbolIndex::new(),
+            dependency_signatures: HashMap::new(),
+            inline_style_parser: UssParser::default(),
+            theme_report: ThemeReportCache::default(),
+            client_settings: UssSettings::default(),
+            config: UnityCodeConfig::default(),
+            config_file: ConfigFile::new(&project_path),
+            baseline: BaselineFile::new(project_path.clone()),
+            format_enabled: true,
+            paste_formatting: PasteFormatting::default(),
+            pasted_texts: HashMap::new(),
+            hover_cascade: true,
+            document_changes_support: false,
+            work_done_progress_support: false,
+            watched_files_support: false,
         };
 
         Self {
             uxml_schema_manager,
+            docs_manager,
             client,
             state: Arc::new(Mutex::new(state)),
+            analysis_pool: WorkerPool::per_core(),
+        }
+    }
+
+    /// Apply user settings to the providers, what the project's configuration file sets takes precedence
+    fn apply_settings(&self, client_settings: UssSettings) {
+        let mut settings = client_settings.clone();
+        if let Ok(mut state) = self.state.lock() {
+            state.config.apply_to(&mut settings);
+            state.client_settings = client_settings;
+            state.diagnostics.set_settings(settings.diagnostics);
+            state.declaration_organizer.set_order(settings.declaration_order);
+            state.format_enabled = settings.format_enabled;
+            state.paste_formatting = settings.paste_formatting;
+            state.hover_cascade = settings.hover_cascade;
+            state.completion_provider.set_block_snippets(settings.block_snippets);
+        } else {
+            log::error!("Failed to acquire state lock for applying settings");
+        }
+
+        request_timing::set_logging(settings.request_timing);
+    }
+
+    /// Load the project's configuration file and apply it, an invalid file leaves the current configuration as is
+    fn reload_config(&self) -> std::result::Result<UnityCodeConfig, ConfigError> {
+        let project_root = match self.state.lock() {
+            Ok(mut state) => {
+                state.config_file.mark_loaded();
+                state.unity_manager.project_path().clone()
+            }
+            Err(_) => return Ok(UnityCodeConfig::default()),
+        };
+
+        let config = UnityCodeConfig::load(&project_root)?.unwrap_or_default();
+        let client_settings = match self.state.lock() {
+            Ok(mut state) => {
+                state.config = config.clone();
+                state.client_settings.clone()
+            }
+            Err(_) => return Ok(config),
+        };
+        self.apply_settings(client_settings);
+        Ok(config)
+    }
+
+    /// Reload the project's configuration file if it was changed, returns whether it was
+    async fn reload_config_if_changed(&self) -> bool {
+        if !self.state.lock().is_ok_and(|mut state| state.config_file.has_changed()) {
+            return false;
+        }
+        self.reload_config_and_warn().await;
+        true
+    }
+
+    /// Reload the project's configuration file, telling the user if it is invalid
+    async fn reload_config_and_warn(&self) {
+        match self.reload_config() {
+            Ok(_) => log::info!("Reloaded {}", CONFIG_FILE_NAME),
+            Err(e) => {
+                log::warn!("{}", e);
+                self.client.show_message(MessageType::WARNING, e.to_string()).await;
+            }
+        }
+    }
+
+    /// Whether the project's configuration ignores the document, ignored documents get no diagnostics
+    fn is_ignored(&self, uri: &Url) -> bool {
+        let Some(path) = self.project_url(uri).and_then(|url| project_url_to_relative_path(&url)) else {
+            return false;
+        };
+        self.state.lock().is_ok_and(|state| state.config.is_ignored(&path))
+    }
+
+    /// Remove the diagnostics of a document that are in the project's baseline
+    fn apply_baseline(&self, uri: &Url, diagnostics: &mut Vec<Diagnostic>) {
+        let Some(path) = self.project_url(uri).and_then(|url| project_url_to_relative_path(&url)) else {
+            return;
+        };
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let state = &mut *state;
+        let (Some(baseline), Some(document)) = (state.baseline.baseline(), state.document_manager.get_document(uri)) else {
+            return;
+        };
+        let suppressed = baseline.filter(&path, diagnostics, document.content());
+        if suppressed > 0 {
+            log::debug!("{} diagnostics of {} are in the baseline", suppressed, path);
+        }
+    }
+
+    /// Record the diagnostics of all style sheets that aren't ignored as the project's baseline
+    ///
+    /// The style sheets are checked from disk like the `check` subcommand does, open documents aren't touched.
+    async fn update_baseline(&self) -> Result<BaselineSummary> {
+        let project_root = match self.state.lock() {
+            Ok(state) => state.unity_manager.project_path().clone(),
+            Err(_) => return Ok(BaselineSummary::default()),
+        };
+
+        let result = tokio::task::spawn_blocking(move || -> std::result::Result<BaselineSummary, CheckError> {
+            let checker = Checker::without_baseline(project_root.clone())?;
+            let baseline = checker.baseline_of(&find_style_sheets(&project_root));
+            baseline.save(&project_root)?;
+            Ok(baseline.summary())
+        })
+        .await;
+        let message = match result {
+            Ok(Ok(summary)) => return Ok(summary),
+            Ok(Err(e)) => e.to_string(),
+            Err(e) => format!("Failed to update {}: {}", BASELINE_FILE_NAME, e),
+        };
+        Err(tower_lsp::jsonrpc::Error {
+            code: tower_lsp::jsonrpc::ErrorCode::InternalError,
+            message: message.into(),
+            data: None,
+        })
+    }
+
+    /// Resolver of asset references, built on a blocking thread if assets were added or removed since the last one
+    async fn asset_paths(&self) -> Option<Arc<AssetPathResolver>> {
+        let (project_root, generation) = {
+            let state = self.state.lock().ok()?;
+            if let Some(asset_paths) = &state.asset_paths {
+                return Some(asset_paths.clone());
+            }
+            (state.unity_manager.project_path().clone(), state.asset_paths_generation)
+        };
+
+        let asset_paths = Arc::new(tokio::task::spawn_blocking(move || AssetPathResolver::new(&project_root)).await.ok()?);
+        if let Ok(mut state) = self.state.lock() {
+            if state.asset_paths_generation == generation {
+                // another request may have built one at the same time, both are up to date
+                state.asset_paths.get_or_insert_with(|| asset_paths.clone());
+            }
+        }
+        Some(asset_paths)
+    }
+
+    /// Scan the project for changes of the import graph if it's stale
+    ///
+    /// A copy of the graph is refreshed without holding the state, requests use the graph as it is meanwhile.
+    async fn refresh_import_graph(&self) {
+        let mut graph = match self.state.lock() {
+            Ok(state) if state.import_graph.is_stale() => state.import_graph.clone(),
+            _ => return,
+        };
+        let Ok(graph) = tokio::task::spawn_blocking(move || {
+            graph.refresh();
+            graph
+        })
+        .await
+        else {
+            return;
+        };
+        if let Ok(mut state) = self.state.lock() {
+            state.import_graph.adopt_index(graph);
+        }
+    }
+
+    /// Build the theme report again without holding the state if it's stale
+    async fn refresh_theme_report(&self) {
+        let project_root = match self.state.lock() {
+            Ok(state) if state.theme_report.is_stale() => state.unity_manager.project_path().clone(),
+            _ => return,
+        };
+        if let Ok(report) = tokio::task::spawn_blocking(move || ThemeReport::for_project(&project_root)).await {
+            if let Ok(mut state) = self.state.lock() {
+                state.theme_report.set_report(report);
+            }
+        }
+    }
+
+    /// Project URL of a document, the path is resolved on the file system without holding the state
+    fn project_url(&self, uri: &Url) -> Option<Url> {
+        let unity_manager = self.state.lock().ok()?.unity_manager.clone();
+        unity_manager.convert_to_project_url(uri)
+    }
+
+    /// Whether the document is in the Package Cache, such documents must not be edited
+    fn is_read_only(&self, uri: &Url) -> bool {
+        self.state
+            .lock()
+            .is_ok_and(|state| package_cache::is_read_only(state.unity_manager.project_path(), uri))
+    }
+
+    /// Handle a request, catching panics so one failing request doesn't take down the server
+    ///
+    /// A panic is logged with the document URI and version and reported to the client as an internal error.
+    /// The time it took is recorded in the [`request_timing`] under the name of the request.
+    async fn guarded<T>(
+        &self,
+        request: &str,
+        uri: Option<&Url>,
+        handler: impl std::future::Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let start = std::time::Instant::now();
+        let result = catch_unwind(handler).await;
+        request_timing::record(request, start.elapsed(), matches!(result, Ok(Ok(_))));
+        match result {
+            Ok(result) => result,
+            Err(message) => {
+                // the state lock may have been held by the panicking handler
+                self.state.clear_poison();
+
+                let version = uri.and_then(|uri| {
+                    let state = self.state.lock().ok()?;
+                    Some(state.document_manager.get_document(uri)?.document_version())
+                });
+                log::error!(
+                    "Panic while handling {} for {} (version {}): {}",
+                    request,
+                    uri.map_or("<no document>".to_string(), |uri| uri.to_string()),
+                    version.map_or("unknown".to_string(), |v| format!("{}.{}", v.major, v.minor)),
+                    message
+                );
+
+                Err(tower_lsp::jsonrpc::Error {
+                    code: tower_lsp::jsonrpc::ErrorCode::InternalError,
+                    message: format!("Internal error while handling {}: {}", request, message).into(),
+                    data: None,
+                })
+            }
         }
     }
 
     /// Open and parse a new document
     async fn open_document(&self, uri: &Url, content: &str, version: i32) {
+        let project_url = self.project_url(uri);
+        if project_url.is_none() {
+            log::warn!("[open_document] Failed to convert URI to project URL");
+        }
+
         if let Ok(mut state) = self.state.lock() {
             state
                 .document_manager
                 .open_document(uri.clone(), content.to_string(), version);
-            let project_url = state.unity_manager.convert_to_project_url(&uri);
-            if project_url.is_none() {
-                log::warn!("[open_document] Failed to convert URI to project URL");
-            }
 
             if let Some(document) = state.document_manager.get_document_mut(uri) {
                 document.extract_variables_with_source_url(project_url.as_ref());
+                let symbols = document.indexed_symbols();
+                state.symbol_index.update_document(uri.clone(), symbols);
             } else {
                 log::warn!("[open_document] Document not found in manager after opening");
             }
@@ -97,17 +463,20 @@ impl UssLanguageServer {
         changes: Vec<TextDocumentContentChangeEvent>,
         version: i32,
     ) {
+        let project_url = self.project_url(uri);
+        if project_url.is_none() {
+            log::warn!("[update_document] Failed to convert URI to project URL");
+        }
+
         if let Ok(mut state) = self.state.lock() {
             state
                 .document_manager
                 .update_document(uri, changes, version);
-            let project_url = state.unity_manager.convert_to_project_url(&uri);
-            if project_url.is_none() {
-                log::warn!("[update_document] Failed to convert URI to project URL");
-            }
 
             if let Some(document) = state.document_manager.get_document_mut(uri) {
                 document.extract_variables_with_source_url(project_url.as_ref());
+                let symbols = document.indexed_symbols();
+                state.symbol_index.update_document(uri.clone(), symbols);
             } else {
                 log::warn!("[update_document] Document not found in manager after update");
             }
@@ -116,6 +485,94 @@ impl UssLanguageServer {
         }
     }
 
+    /// Remember the text pasted by the last change of a document, or format it right away, see [`crate::uss::paste`]
+    async fn handle_paste(&self, uri: &Url, change: Option<&TextDocumentContentChangeEvent>) {
+        let read_only = self.is_read_only(uri);
+        let edit = {
+            let Ok(mut state) = self.state.lock() else {
+                return;
+            };
+            let state = &mut *state;
+            state.pasted_texts.remove(uri);
+            if read_only || !state.format_enabled || state.paste_formatting == PasteFormatting::Off {
+                return;
+            }
+            let Some(document) = state.document_manager.get_document(uri) else {
+                return;
+            };
+            let Some(pasted) = change.and_then(|change| pasted_range(change, document.content())) else {
+                return;
+            };
+            let Some(tree) = document.tree() else {
+                return;
+            };
+            // formatted once, the code action offers the same edits until the document changes
+            let edits = format_pasted_edits(&state.formatter, tree, document.content(), pasted);
+            if edits.is_empty() {
+                return;
+            }
+            if state.paste_formatting == PasteFormatting::CodeAction {
+                state.pasted_texts.insert(uri.clone(), PastedText { range: pasted, edits });
+                return;
+            }
+            let edit = document_edit(&document.versioned_identifier(), edits);
+            if state.document_changes_support { edit } else { without_versions(edit) }
+        };
+
+        match self.client.apply_edit(edit).await {
+            Ok(response) if !response.applied => {
+                log::debug!("Formatting of pasted text in {} was not applied: {:?}", uri, response.failure_reason)
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("Failed to format pasted text in {}: {}", uri, e),
+        }
+    }
+
+    /// Ask the client to pull diagnostics again if a change of a document affects other open documents
+    ///
+    /// Diagnostics are pulled by the client, which only pulls them for the document being edited.
+    /// The first call for a document records what others depend on and never asks.
+    async fn revalidate_dependents(&self, uri: &Url) {
+        let Some(project_url) = self.project_url(uri) else {
+            return;
+        };
+        let changed = self.state.lock().ok().and_then(|mut state| {
+            let state = &mut *state;
+            let document = state.document_manager.get_document(uri)?;
+            let tree = document.tree()?;
+            let imports = state.import_graph.extract_imports(tree, document.content(), &project_url);
+            let signature = DependencySignature::new(&imports, tree, document.content());
+            match state.dependency_signatures.insert(uri.clone(), signature.clone()) {
+                Some(previous) if previous != signature => {}
+                _ => return None,
+            }
+            state.import_graph.set_document_imports(project_url.clone(), imports);
+
+            let mut dependents: HashSet<Url> = state.import_graph.importers_of(&project_url).into_iter().collect();
+            if state.diagnostics.settings().unused_variables == UnusedVariableScope::Importers {
+                dependents.extend(state.import_graph.imports_transitively(&project_url));
+            }
+            let open: Vec<Url> = state.document_manager.document_uris().filter(|open| *open != uri).cloned().collect();
+            Some((dependents, open, state.unity_manager.clone()))
+        });
+        let Some((dependents, open, unity_manager)) = changed else {
+            return;
+        };
+
+        // the open documents are resolved on the file system without holding the state
+        let has_open_dependents = open
+            .iter()
+            .filter_map(|open| unity_manager.convert_to_project_url(open))
+            .any(|open| dependents.contains(&open));
+
+        if has_open_dependents {
+            log::debug!("Diagnostics of documents depending on {} changed", uri);
+            if let Err(e) = self.client.workspace_diagnostic_refresh().await {
+                log::debug!("Failed to request diagnostic refresh: {}", e);
+            }
+        }
+    }
+
     /// Generate semantic tokens for syntax highlighting
     fn generate_semantic_tokens(&self, uri: &Url) -> Option<Vec<SemanticToken>> {
         let state = self.state.lock().ok()?;
@@ -126,6 +583,122 @@ impl UssLanguageServer {
         Some(state.highlighter.generate_tokens(tree, content))
     }
 
+    /// Diagnostics of an open document, with suppression comments applied
+    ///
+    /// The document is analyzed on the [`WorkerPool`], fails with [`WorkError::Superseded`] if a newer request
+    /// for its diagnostics came before the analysis started.
+    async fn document_diagnostics(&self, uri: &Url) -> std::result::Result<Vec<Diagnostic>, WorkError> {
+        let uxml_data = self.update_uxml_schema_and_get_data().await;
+
+        let mut uxml_names:Option<HashSet<String>> = None;
+        if let Ok(data) = uxml_data.lock() {
+            // unknown tags are not reported while only the built-in elements are known
+            if !data.is_incomplete() {
+                uxml_names = Some(data.get_all_names().keys().cloned().collect());
+            }
+        }
+
+        let Some(asset_paths) = self.asset_paths().await else {
+            return Ok(Vec::new());
+        };
+        self.refresh_import_graph().await;
+
+        // the project version and the project URLs of the document and of the open documents, which
+        // may import it, are read from the file system without holding the state
+        let (unity_manager, open_uris) = match self.state.lock() {
+            Ok(state) => (state.unity_manager.clone(), state.document_manager.document_uris().cloned().collect::<Vec<_>>()),
+            Err(_) => return Ok(Vec::new()),
+        };
+        // the project may have been upgraded, the version is only read again when ProjectVersion.txt changes
+        let unity_version = unity_manager.get_parsed_unity_version();
+        let project_url = unity_manager.convert_to_project_url(uri);
+        let open_urls: Vec<(Url, Url)> = open_uris
+            .into_iter()
+            .filter_map(|open| Some((unity_manager.convert_to_project_url(&open)?, open)))
+            .collect();
+
+        // Take what the analysis needs from the state and release the lock quickly
+        let snapshot = {
+            let Ok(mut state) = self.state.lock() else {
+                return Ok(Vec::new());
+            };
+            let state = &mut *state;
+            state.diagnostics.set_unity_version(unity_version);
+
+            let project_root = unity_manager.project_path().clone();
+            let Some(document) = state.document_manager.get_document(uri) else {
+                return Ok(Vec::new());
+            };
+            let Some(tree) = document.tree().cloned() else {
+                return Ok(Vec::new());
+            };
+            let content = document.content().to_string();
+            let variable_resolver = document.variable_resolver.clone();
+
+            let mut imports = Vec::new();
+            let mut import_diagnostics = Vec::new();
+            if let Some(project_url) = &project_url {
+                imports = state.import_graph.extract_imports(&tree, &content, project_url);
+                state.import_graph.set_document_imports(project_url.clone(), imports.clone());
+
+                import_diagnostics = state.import_graph.import_diagnostics(project_url);
+                state.renamed_files.add_quick_fix_data(&mut import_diagnostics, &imports);
+                state.diagnostics.settings().apply(&mut import_diagnostics);
+            }
+
+            let mut importers = match (state.diagnostics.settings().unused_variables, &project_url) {
+                (UnusedVariableScope::Importers, Some(project_url)) => state.import_graph.importers_of(project_url),
+                _ => Vec::new(),
+            };
+            // open importers are taken as they are in the editor, the others are read from disk
+            let mut open_importers = Vec::new();
+            for (open_url, open) in &open_urls {
+                let Some(position) = importers.iter().position(|importer| importer == open_url) else {
+                    continue;
+                };
+                importers.swap_remove(position);
+                let Some(importer) = state.document_manager.get_document(open) else {
+                    continue;
+                };
+                if let Some(tree) = importer.tree() {
+                    open_importers.push((tree.clone(), importer.content().to_string()));
+                }
+            }
+            let profile = project_url
+                .as_ref()
+                .and_then(project_url_to_relative_path)
+                .and_then(|path| detect_profile(&path, &state.config));
+
+            DiagnosticsSnapshot {
+                analyzer: state.diagnostics.clone(),
+                tree,
+                content,
+                variable_resolver,
+                project_url,
+                project_root,
+                imports,
+                import_diagnostics,
+                importers,
+                open_importers,
+                asset_paths,
+                profile,
+            }
+        }; // Lock is released here
+
+        let (mut diagnostics, suppressions) = self.analysis_pool.run(uri.clone(), move || snapshot.analyze(uxml_names.as_ref())).await?;
+        suppressions.apply(&mut diagnostics);
+        Ok(diagnostics)
+    }
+
+    /// Summary of the documentation of a C# type of user code, as a hover section
+    ///
+    /// Only uses docs that are already compiled, and none while the docs manager is busy, e.g. compiling.
+    fn type_summary(&self, type_name: Option<String>) -> Option<String> {
+        let type_name = type_name?;
+        let docs_manager = self.docs_manager.try_lock().ok()?;
+        summary_to_markdown(&docs_manager.compiled_docs_for_type(&type_name)?.xml_doc)
+    }
+
     /// Update UXML schema manager
     async fn update_uxml_schema_and_get_data(&self) -> Arc<Mutex<VisualElementsData>> {
         let mut manager = self.uxml_schema_manager.lock().await;
@@ -134,12 +707,526 @@ impl UssLanguageServer {
         }
         return manager.get_visual_elements_data()
     }
+
+    /// Ask the editor to report changes of style sheets, UXML files and assets, if it can
+    ///
+    /// Without watchers the indexes still notice changes when they are refreshed, just later.
+    async fn register_file_watchers(&self) {
+        let supported = self.state.lock().is_ok_and(|state| state.watched_files_support);
+        if !supported {
+            return;
+        }
+
+        let mut watchers: Vec<FileSystemWatcher> = WATCHED_FILE_GLOBS
+            .iter()
+            .map(|glob| FileSystemWatcher {
+                glob_pattern: GlobPattern::String(glob.to_string()),
+                kind: None,
+            })
+            .collect();
+        watchers.push(FileSystemWatcher {
+            glob_pattern: GlobPattern::String(WATCHED_ASSETS_GLOB.to_string()),
+            kind: Some(WatchKind::Create | WatchKind::Delete),
+        });
+        watchers.push(FileSystemWatcher {
+            glob_pattern: GlobPattern::String(format!("**/{}", CONFIG_FILE_NAME)),
+            kind: None,
+        });
+
+        let registration = Registration {
+            id: "uss-watched-files".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(DidChangeWatchedFilesRegistrationOptions { watchers }).ok(),
+        };
+        if let Err(e) = self.client.register_capability(vec![registration]).await {
+            log::warn!("Failed to register file watchers: {}", e);
+        }
+    }
+
+    /// Index the UXML and USS files of the project, reporting progress to the editor
+    ///
+    /// The indexes are otherwise built by the first request that needs them, which is slow on large projects.
+    /// Both are built at the same time on other threads, so the total is known from the start.
+    /// With `rebuild` all files are parsed again, instead of starting from the last session's cache.
+    /// Returns the number of indexed files.
+    async fn index_project(&self, rebuild: bool) -> usize {
+        let (project_root, progress_support) = match self.state.lock() {
+            Ok(state) => (state.unity_manager.project_path().clone(), state.work_done_progress_support),
+            Err(_) => return 0,
+        };
+
+        let start = std::time::Instant::now();
+        let counter = ProgressCounter::new();
+        let title = if rebuild { "Reindexing Unity project" } else { "Indexing Unity project" };
+        let mut reporter = ProgressReporter::begin(&self.client, progress_support, title).await;
+
+        // the current indexes answer requests until the new ones are ready
+        let uxml_root = project_root.clone();
+        let uxml_counter = counter.clone();
+        let uss_counter = counter.clone();
+        let uxml = tokio::task::spawn_blocking(move || {
+            let mut index = UxmlUsageIndex::new(uxml_root);
+            // files indexed in the last session are only parsed again if they changed
+            if !rebuild {
+                index.load_cache();
+            }
+            let plan = index.plan_refresh();
+            let parsed = usage_index::parse_files(&plan.to_parse, &CancellationToken::new(), &uxml_counter).ok()?;
+            index.apply_refresh(plan, parsed);
+            if let Err(e) = index.save_cache() {
+                log::warn!("Failed to save the UXML index: {}", e);
+            }
+            Some(index)
+        });
+        let uss = tokio::task::spawn_blocking(move || {
+            let mut graph = UssImportGraph::new(project_root);
+            if !rebuild {
+                graph.load_cache();
+            }
+            graph.refresh_with_progress(&uss_counter);
+            if let Err(e) = graph.save_cache() {
+                log::warn!("Failed to save the USS import graph: {}", e);
+            }
+            graph
+        });
+        let (uxml, uss) = reporter.track(&counter, async { tokio::join!(uxml, uss) }).await;
+
+        if let Ok(mut state) = self.state.lock() {
+            // requests may have built the indexes in the meantime
+            if let Ok(Some(index)) = uxml {
+                if rebuild || !state.uxml_usage_index.is_indexed() {
+                    state.uxml_usage_index = index;
+                }
+            }
+            if let Ok(graph) = uss {
+                if rebuild || !state.import_graph.is_indexed() {
+                    state.import_graph.adopt_index(graph);
+                }
+            }
+        }
+
+        log::info!("Indexed {} files in {:.2}ms", counter.done(), start.elapsed().as_secs_f64() * 1000.0);
+        reporter.end(Some(format!("Indexed {} files", counter.done()))).await;
+        counter.done()
+    }
+
+    /// Rebuild everything derived from project files in one pass, see [`REINDEX_COMMAND`]
+    ///
+    /// Also done when many files change at once, e.g. after switching branches, updating the indexes
+    /// file by file is then slower. Returns the number of indexed files.
+    async fn reindex_project(&self) -> usize {
+        if let Ok(mut state) = self.state.lock() {
+            state.theme_report.invalidate();
+            state.completion_provider.invalidate_font_assets();
+            state.asset_paths = None;
+            state.asset_paths_generation += 1;
+        }
+        self.uxml_schema_manager.lock().await.invalidate();
+        let invalidated = self.docs_manager.lock().await.invalidate_all().await;
+        log::debug!("Invalidated docs of {:?}", invalidated);
+
+        let files = self.index_project(true).await;
+        if let Err(e) = self.client.workspace_diagnostic_refresh().await {
+            log::debug!("Failed to request diagnostic refresh: {}", e);
+        }
+        files
+    }
+
+    /// Diagnostics of a UXML style attribute, see [`INLINE_STYLE_DIAGNOSTICS_METHOD`]
+    async fn inline_style_diagnostics(&self, params: InlineStyleParams) -> Result<Vec<Diagnostic>> {
+        let request_uri = params.text_document.uri.clone();
+        self.guarded("inline_style_diagnostics", Some(&request_uri), async move {
+            let Ok(mut state) = self.state.lock() else {
+                log::error!("Failed to lock state");
+                return Ok(Vec::new());
+            };
+            let state = &mut *state;
+
+            let unity_version = state.unity_manager.get_parsed_unity_version();
+            state.diagnostics.set_unity_version(unity_version);
+
+            let Some(style) = InlineStyle::parse(&mut state.inline_style_parser, &params.style, params.start) else {
+                return Ok(Vec::new());
+            };
+            let diagnostics = state.diagnostics.analyze(style.tree(), style.content());
+            Ok(style.map_diagnostics(diagnostics))
+        })
+        .await
+    }
+
+    /// Completion in a UXML style attribute, see [`INLINE_STYLE_COMPLETION_METHOD`]
+    async fn inline_style_completion(&self, params: InlineStyleParams) -> Result<CompletionList> {
+        let request_uri = params.text_document.uri.clone();
+        self.guarded("inline_style_completion", Some(&request_uri), async move {
+            let empty = CompletionList { is_incomplete: false, items: Vec::new() };
+            let Some(position) = params.position else {
+                return Err(tower_lsp::jsonrpc::Error::invalid_params("position is required for completion"));
+            };
+
+            let uxml_data = self.update_uxml_schema_and_get_data().await;
+
+            let Ok(mut state) = self.state.lock() else {
+                log::error!("Failed to lock state");
+                return Ok(empty);
+            };
+            let state = &mut *state;
+
+            let Some(style) = InlineStyle::parse(&mut state.inline_style_parser, &params.style, params.start) else {
+                return Ok(empty);
+            };
+            let Some(content_position) = style.to_content_position(position) else {
+                return Ok(empty);
+            };
+
+            let completions = state.completion_provider.complete_list_with_variables(
+                style.tree(),
+                style.content(),
+                content_position,
+                None,
+                Some(uxml_data),
+                Some(&state.unity_manager),
+                None,
+            );
+            Ok(style.map_completion_list(completions))
+        })
+        .await
+    }
+
+    /// Rules of the project that apply to a UXML element, see [`MATCH_RULES_METHOD`]
+    async fn match_rules(&self, params: MatchRulesParams) -> Result<MatchRulesResult> {
+        let request_uri = params.text_document.uri.clone();
+        self.guarded("match_rules", Some(&request_uri), async move {
+            let invalid_params = |message: String| tower_lsp::jsonrpc::Error::invalid_params(message);
+
+            let uxml = read_document(&params.text_document.uri, params.text).await?;
+            let elements = parse_uxml_elements(&uxml);
+            let index = match (&params.element_path, params.position) {
+                (Some(path), _) => rule_matching::find_element_by_path(&elements, path),
+                (None, Some(position)) => rule_matching::find_element_at(&elements, position),
+                (None, None) => return Err(invalid_params("elementPath or position is required".to_string())),
+            };
+            let Some(index) = index else {
+                return Ok(MatchRulesResult::default());
+            };
+
+            let project_root = match self.state.lock() {
+                Ok(state) => state.unity_manager.project_path().clone(),
+                Err(_) => return Ok(MatchRulesResult::default()),
+            };
+            let element = MatchedElement::new(&elements[index]);
+            let open = self.open_style_sheets();
+
+            // the project is walked and closed style sheets are read and parsed without holding the state
+            let rules = tokio::task::spawn_blocking(move || {
+                let mut parser = UssParser::default();
+                let mut rules = Vec::new();
+                for path in find_style_sheets(&project_root) {
+                    let Ok(uri) = Url::from_file_path(&path) else {
+                        continue;
+                    };
+
+                    // open documents may have unsaved changes
+                    let matched = match open.get(&uri) {
+                        Some(open) => rule_matching::match_style_sheet(&open.tree, &open.content, &uri, &elements, index),
+                        None => {
+                            let Ok(content) = std::fs::read_to_string(&path) else {
+                                continue;
+                            };
+                            let Some(tree) = parser.parse(&content, None) else {
+                                continue;
+                            };
+                            rule_matching::match_style_sheet(&tree, &content, &uri, &elements, index)
+                        }
+                    };
+                    rules.extend(matched);
+                }
+                rule_matching::sort_by_precedence(&mut rules);
+                rules
+            })
+            .await
+            .unwrap_or_default();
+
+            Ok(MatchRulesResult { element: Some(element), rules })
+        })
+        .await
+    }
+
+    /// Changes between two versions of a style sheet, see [`STYLE_DIFF_METHOD`]
+    async fn style_diff(&self, params: StyleDiffParams) -> Result<StyleDiff> {
+        let request_uri = params.text_document.as_ref().map(|document| document.uri.clone());
+        self.guarded("style_diff", request_uri.as_ref(), async move {
+            let invalid_params = |message: String| tower_lsp::jsonrpc::Error::invalid_params(message);
+
+            let content = match (params.text, params.text_document) {
+                (Some(text), _) => text,
+                (None, Some(document)) => {
+                    // open documents may have unsaved changes
+                    let open = self.state.lock().ok().and_then(|state| {
+                        state.document_manager.get_document(&document.uri).map(|d| d.content().to_string())
+                    });
+                    match open {
+                        Some(content) => content,
+                        None => read_document(&document.uri, None).await?,
+                    }
+                }
+                (None, None) => return Err(invalid_params("text or textDocument is required".to_string())),
+            };
+
+            let mut parser = UssParser::default();
+            let (Some(old_tree), Some(tree)) = (parser.parse(&params.old_text, None), parser.parse(&content, None)) else {
+                return Ok(StyleDiff::default());
+            };
+            Ok(style_diff::diff_style_sheets(&old_tree, &params.old_text, &tree, &content))
+        })
+        .await
+    }
+
+    /// Completion in a UXML attribute value, see [`UXML_ATTRIBUTE_COMPLETION_METHOD`]
+    async fn uxml_attribute_completion(&self, params: UxmlAttributeCompletionParams) -> Result<CompletionList> {
+        let request_uri = params.text_document.uri.clone();
+        self.guarded("uxml_attribute_completion", Some(&request_uri), async move {
+            let empty = CompletionList { is_incomplete: false, items: Vec::new() };
+            let uxml = read_document(&params.text_document.uri, params.text).await?;
+            let Some(cursor) = position_to_byte_offset(&uxml, params.position) else {
+                return Ok(empty);
+            };
+            let Some(context) = attribute_completion::attribute_value_at(&uxml, cursor) else {
+                return Ok(empty);
+            };
+
+            // style sheets are only read for the class attribute
+            let mut classes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+            if context.attribute == "class" {
+                let project_root = match self.state.lock() {
+                    Ok(state) => state.unity_manager.project_path().clone(),
+                    Err(_) => return Ok(empty),
+                };
+                let open = self.open_style_sheets();
+
+                // the project is walked and closed style sheets are read and parsed without holding the state
+                classes = tokio::task::spawn_blocking(move || {
+                    let mut classes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+                    let mut parser = UssParser::default();
+                    for path in find_style_sheets(&project_root) {
+                        let Ok(uri) = Url::from_file_path(&path) else {
+                            continue;
+                        };
+
+                        // open documents may have unsaved changes
+                        let mut class_names = HashSet::new();
+                        match open.get(&uri) {
+                            Some(open) => attribute_completion::collect_class_names(&open.tree, &open.content, &mut class_names),
+                            None => {
+                                let Ok(content) = std::fs::read_to_string(&path) else {
+                                    continue;
+                                };
+                                let Some(tree) = parser.parse(&content, None) else {
+                                    continue;
+                                };
+                                attribute_completion::collect_class_names(&tree, &content, &mut class_names);
+                            }
+                        }
+
+                        let relative_path =
+                            path.strip_prefix(&project_root).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+                        for class_name in class_names {
+                            classes.entry(class_name).or_default().push(relative_path.clone());
+                        }
+                    }
+                    classes
+                })
+                .await
+                .unwrap_or_default();
+            }
+
+            let uxml_data = self.update_uxml_schema_and_get_data().await;
+            let items = match uxml_data.lock() {
+                Ok(data) => attribute_completion::complete_attribute_value(
+                    &context,
+                    &uxml,
+                    data.lookup_by_name(context.tag),
+                    &classes,
+                ),
+                Err(_) => Vec::new(),
+            };
+            Ok(CompletionList { is_incomplete: false, items })
+        })
+        .await
+    }
+
+    /// The template file of a UXML template instance, see [`UXML_TEMPLATE_DEFINITION_METHOD`]
+    async fn uxml_template_definition(&self, params: UxmlTemplateDefinitionParams) -> Result<Option<Location>> {
+        let request_uri = params.text_document.uri.clone();
+        self.guarded("uxml_template_definition", Some(&request_uri), async move {
+            let uri = params.text_document.uri;
+            let uxml = read_document(&uri, params.text).await?;
+            let Ok(document_path) = uri.to_file_path() else {
+                return Ok(None);
+            };
+            let project_root = match self.state.lock() {
+                Ok(state) => state.unity_manager.project_path().clone(),
+                Err(_) => return Ok(None),
+            };
+
+            let packages = ProjectPackages::find(&project_root);
+            let resolver = TemplateResolver {
+                document_path: &document_path,
+                project_root: &project_root,
+                packages: Some(&packages),
+            };
+            Ok(resolver.definition(&parse_uxml_elements(&uxml), &uri, params.position))
+        })
+        .await
+    }
+
+    /// Unknown templates and missing template files of a UXML document, see [`UXML_TEMPLATE_DIAGNOSTICS_METHOD`]
+    async fn uxml_template_diagnostics(&self, params: UxmlTemplateDiagnosticsParams) -> Result<Vec<Diagnostic>> {
+        let request_uri = params.text_document.uri.clone();
+        self.guarded("uxml_template_diagnostics", Some(&request_uri), async move {
+            let uri = params.text_document.uri;
+            let uxml = read_document(&uri, params.text).await?;
+            let Ok(document_path) = uri.to_file_path() else {
+                return Ok(Vec::new());
+            };
+            let project_root = match self.state.lock() {
+                Ok(state) => state.unity_manager.project_path().clone(),
+                Err(_) => return Ok(Vec::new()),
+            };
+
+            let packages = ProjectPackages::find(&project_root);
+            let resolver = TemplateResolver {
+                document_path: &document_path,
+                project_root: &project_root,
+                packages: Some(&packages),
+            };
+            Ok(resolver.diagnostics(&parse_uxml_elements(&uxml)))
+        })
+        .await
+    }
+
+    /// References of style sheets to assets, see [`URL_REFERENCES_METHOD`]
+    async fn url_references(&self, params: UrlReferencesParams) -> Result<UrlReferencesReport> {
+        let request_uri = params.text_document.as_ref().map(|document| document.uri.clone());
+        self.guarded("url_references", request_uri.as_ref(), async move {
+            let unity_manager = match self.state.lock() {
+                Ok(state) => state.unity_manager.clone(),
+                Err(_) => return Ok(UrlReferencesReport::default()),
+            };
+            let Some(asset_paths) = self.asset_paths().await else {
+                return Ok(UrlReferencesReport::default());
+            };
+            let open = self.open_style_sheets();
+
+            // the project is walked and closed style sheets are read from disk, the guard stops that
+            // when tower-lsp drops this future
+            let token = CancellationToken::new();
+            let _guard = token.cancel_on_drop();
+            let report = tokio::task::spawn_blocking(move || {
+                let project_root = unity_manager.project_path();
+                let uris: Vec<Url> = match params.text_document {
+                    Some(document) => vec![document.uri],
+                    None => find_style_sheets(project_root)
+                        .into_iter()
+                        .chain(find_theme_style_sheets(project_root))
+                        .filter_map(|path| Url::from_file_path(path).ok())
+                        .collect(),
+                };
+
+                let resolver = UrlReferenceResolver::with_paths(asset_paths);
+                let mut parser = UssParser::default();
+                let mut report = UrlReferencesReport::default();
+                for uri in uris {
+                    if token.is_cancelled() {
+                        break;
+                    }
+                    if let Some((_, file, _)) = style_sheet_references(&unity_manager, &resolver, &mut parser, &open, &uri) {
+                        report.add(file);
+                    }
+                }
+                report
+            })
+            .await
+            .unwrap_or_default();
+            Ok(report)
+        })
+        .await
+    }
+
+    /// The documents in the document manager, so they can be read without holding the state
+    fn open_style_sheets(&self) -> HashMap<Url, OpenStyleSheet> {
+        let Ok(state) = self.state.lock() else {
+            return HashMap::new();
+        };
+        state
+            .document_manager
+            .document_uris()
+            .filter_map(|uri| {
+                let document = state.document_manager.get_document(uri)?;
+                let open = OpenStyleSheet {
+                    tree: document.tree()?.clone(),
+                    content: document.content().to_string(),
+                    document: document.versioned_identifier(),
+                };
+                Some((uri.clone(), open))
+            })
+            .collect()
+    }
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for UssLanguageServer {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
-        let legend = if let Ok(state) = self.state.lock() {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        if let Some(options) = &params.initialization_options {
+            self.apply_settings(UssSettings::from_json(options));
+        }
+
+        let snippet_support = params
+            .capabilities
+            .text_document
+            .as_ref()
+            .and_then(|t| t.completion.as_ref())
+            .and_then(|c| c.completion_item.as_ref())
+            .and_then(|i| i.snippet_support)
+            .unwrap_or(false);
+
+        let commit_characters_support = params
+            .capabilities
+            .text_document
+            .as_ref()
+            .and_then(|t| t.completion.as_ref())
+            .and_then(|c| c.completion_item.as_ref())
+            .and_then(|i| i.commit_characters_support)
+            .unwrap_or(false);
+
+        let document_changes_support = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|w| w.workspace_edit.as_ref())
+            .and_then(|e| e.document_changes)
+            .unwrap_or(false);
+
+        let work_done_progress_support = params
+            .capabilities
+            .window
+            .as_ref()
+            .and_then(|w| w.work_done_progress)
+            .unwrap_or(false);
+
+        let watched_files_support = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|w| w.did_change_watched_files.as_ref())
+            .and_then(|w| w.dynamic_registration)
+            .unwrap_or(false);
+
+        let legend = if let Ok(mut state) = self.state.lock() {
+            state.completion_provider.set_snippet_support(snippet_support);
+            state.completion_provider.set_commit_characters_support(commit_characters_support);
+            state.document_changes_support = document_changes_support;
+            state.work_done_progress_support = work_done_progress_support;
+            state.watched_files_support = watched_files_support;
             state.highlighter.legend.clone()
         } else {
             // Fallback legend if state is locked
@@ -186,11 +1273,42 @@ impl LanguageServer for UssLanguageServer {
                 }),
                 document_formatting_provider: Some(OneOf::Left(true)),
                 document_range_formatting_provider: Some(OneOf::Left(true)),
-                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+                    code_action_kinds: Some(vec![
+                        CodeActionKind::REFACTOR,
+                        CodeActionKind::REFACTOR_REWRITE,
+                        CodeActionKind::from(ORGANIZE_DECLARATIONS_KIND),
+                    ]),
+                    ..Default::default()
+                })),
+                references_provider: Some(OneOf::Left(true)),
                 rename_provider: Some(OneOf::Right(RenameOptions {
                     prepare_provider: Some(true),
                     work_done_progress_options: WorkDoneProgressOptions::default(),
                 })),
+                code_lens_provider: Some(CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                workspace: Some(WorkspaceServerCapabilities {
+                    workspace_folders: None,
+                    file_operations: Some(WorkspaceFileOperationsServerCapabilities {
+                        will_rename: Some(FileOperationRegistrationOptions { filters: renamed_file_filters() }),
+                        ..Default::default()
+                    }),
+                }),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: vec![
+                        DUMP_IMPORT_GRAPH_COMMAND.to_string(),
+                        SET_LOG_LEVEL_COMMAND.to_string(),
+                        COPY_TO_ASSETS_COMMAND.to_string(),
+                        RELOAD_CONFIG_COMMAND.to_string(),
+                        UPDATE_BASELINE_COMMAND.to_string(),
+                        CONVERT_LENGTH_COMMAND.to_string(),
+                        REINDEX_COMMAND.to_string(),
+                        METRICS_COMMAND.to_string(),
+                    ],
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                }),
                 ..Default::default()
             },
             ..Default::default()
@@ -198,454 +1316,1248 @@ impl LanguageServer for UssLanguageServer {
     }
 
     async fn initialized(&self, _: InitializedParams) {
+        if let Err(e) = self.reload_config() {
+            log::warn!("{}", e);
+            self.client.show_message(MessageType::WARNING, e.to_string()).await;
+        }
+
         self.client
             .log_message(MessageType::INFO, "USS Language Server initialized")
             .await;
+
+        self.register_file_watchers().await;
+        self.index_project(false).await;
+    }
+
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        self.apply_settings(UssSettings::from_json(&params.settings));
+
+        // diagnostics are pulled by the client, ask it to pull them again with the new settings
+        if let Err(e) = self.client.workspace_diagnostic_refresh().await {
+            log::debug!("Failed to request diagnostic refresh: {}", e);
+        }
     }
 
     async fn shutdown(&self) -> Result<()> {
+        request_timing::log_all_summaries();
+        // the indexes were refreshed during the session, the next one starts from them
+        if let Ok(state) = self.state.lock() {
+            log::info!("USS documents: {}", state.document_manager.memory());
+            if state.uxml_usage_index.is_indexed() {
+                if let Err(e) = state.uxml_usage_index.save_cache() {
+                    log::warn!("Failed to save the UXML index: {}", e);
+                }
+            }
+            if state.import_graph.is_indexed() {
+                if let Err(e) = state.import_graph.save_cache() {
+                    log::warn!("Failed to save the USS import graph: {}", e);
+                }
+            }
+        }
         Ok(())
     }
 
-    async fn did_open(&self, params: DidOpenTextDocumentParams) {
-        let uri = params.text_document.uri;
-        let content = params.text_document.text;
-        let version = params.text_document.version;
-
-        // Open and parse the document
-        self.open_document(&uri, &content, version).await;
+    async fn will_rename_files(&self, params: RenameFilesParams) -> Result<Option<WorkspaceEdit>> {
+        self.guarded("will_rename_files", None, async move {
+            let unity_manager = match self.state.lock() {
+                Ok(state) => state.unity_manager.clone(),
+                Err(_) => return Ok(None),
+            };
+            let project_root = unity_manager.project_path().clone();
+            let moves: Vec<(Url, Url)> = params
+                .files
+                .iter()
+                .filter_map(|rename| {
+                    let old_path = Url::parse(&rename.old_uri).ok()?.to_file_path().ok()?;
+                    let new_path = Url::parse(&rename.new_uri).ok()?.to_file_path().ok()?;
+                    Some((
+                        create_project_url_with_normalization(&old_path, &project_root).ok()?,
+                        create_project_url_with_normalization(&new_path, &project_root).ok()?,
+                    ))
+                })
+                .collect();
+            let moves = FileMoves::new(&moves);
+            if moves.is_empty() {
+                return Ok(None);
+            }
 
-        self.client
-            .log_message(MessageType::INFO, format!("Opened USS document: {}", uri))
+            let Some(asset_paths) = self.asset_paths().await else {
+                return Ok(None);
+            };
+            let open = self.open_style_sheets();
+
+            // the project is walked and closed style sheets are read from disk, the guard stops that
+            // when tower-lsp drops this future
+            let token = CancellationToken::new();
+            let _guard = token.cancel_on_drop();
+            let builder = tokio::task::spawn_blocking(move || {
+                let resolver = UrlReferenceResolver::with_paths(asset_paths);
+                let mut parser = UssParser::default();
+                let mut builder = WorkspaceEditBuilder::new();
+                let style_sheets = find_style_sheets(&project_root).into_iter().chain(find_theme_style_sheets(&project_root));
+                for uri in style_sheets.filter_map(|path| Url::from_file_path(path).ok()) {
+                    if token.is_cancelled() {
+                        break;
+                    }
+                    if package_cache::is_read_only(&project_root, &uri) {
+                        continue;
+                    }
+                    let Some((project_url, file, document)) =
+                        style_sheet_references(&unity_manager, &resolver, &mut parser, &open, &uri)
+                    else {
+                        continue;
+                    };
+                    let edits = moves.reference_edits(&project_url, &file.references);
+                    if !edits.is_empty() {
+                        builder = builder.edit(&document, edits);
+                    }
+                }
+                builder
+            })
             .await;
-    }
-
-    async fn did_change(&self, params: DidChangeTextDocumentParams) {
-        let uri = params.text_document.uri;
-        let version = params.text_document.version;
-        let changes = params.content_changes;
+            let Ok(builder) = builder else {
+                return Ok(None);
+            };
+            if builder.is_empty() {
+                return Ok(None);
+            }
 
-        // Update the document with incremental changes
-        self.update_document(&uri, changes, version).await;
+            let edit = builder.build();
+            let document_changes_support = self.state.lock().is_ok_and(|state| state.document_changes_support);
+            Ok(Some(if document_changes_support { edit } else { without_versions(edit) }))
+        })
+        .await
     }
 
-    async fn did_close(&self, params: DidCloseTextDocumentParams) {
-        let uri = params.text_document.uri;
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        let _ = self.guarded("did_change_watched_files", None, async move {
+            if params.changes.len() >= BULK_CHANGE_THRESHOLD {
+                log::info!("{} files changed at once, reindexing the project", params.changes.len());
+                self.reindex_project().await;
+                return Ok(());
+            }
 
-        if let Ok(mut state) = self.state.lock() {
-            state.document_manager.close_document(&uri);
-        }
+            let mut style_sheets = Vec::new();
+            let mut uxml_files = Vec::new();
+            let mut deleted = Vec::new();
+            let mut created = Vec::new();
+            let mut font_assets_changed = false;
+            let mut assets_changed = false;
+            let mut config_changed = false;
+
+            for event in &params.changes {
+                let Ok(path) = event.uri.to_file_path() else {
+                    continue;
+                };
+                if path.file_name().is_some_and(|name| name == CONFIG_FILE_NAME) {
+                    config_changed = true;
+                    continue;
+                }
+                assets_changed |= event.typ != FileChangeType::CHANGED;
+                let extension = path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+                match extension.as_deref() {
+                    Some("uss") | Some("tss") => {
+                        if event.typ == FileChangeType::DELETED {
+                            deleted.push(path.clone());
+                        } else if event.typ == FileChangeType::CREATED {
+                            created.push(path.clone());
+                        }
+                        style_sheets.push(path);
+                    }
+                    Some("uxml") => uxml_files.push(path),
+                    Some("asset") => font_assets_changed = true,
+                    _ => {}
+                }
+            }
 
-        self.client
-            .log_message(MessageType::INFO, format!("Closed USS document: {}", uri))
+            let project_root = match self.state.lock() {
+                Ok(state) => state.unity_manager.project_path().clone(),
+                Err(_) => return Ok(()),
+            };
+            let style_sheets_changed = !style_sheets.is_empty();
+
+            // changed files are read and renames resolved without holding the state
+            let read = tokio::task::spawn_blocking(move || {
+                let uxml_files = (!uxml_files.is_empty())
+                    .then(|| UxmlUsageIndex::new(project_root.clone()).read_changed_files(&uxml_files));
+                let style_sheets = (!style_sheets.is_empty())
+                    .then(|| UssImportGraph::new(project_root.clone()).read_changed_files(&style_sheets));
+                let renames: Vec<(Url, Url)> = pair_renames(&deleted, &created)
+                    .into_iter()
+                    .filter_map(|(from, to)| {
+                        Some((
+                            create_project_url_with_normalization(&from, &project_root).ok()?,
+                            create_project_url_with_normalization(&to, &project_root).ok()?,
+                        ))
+                    })
+                    .collect();
+                (uxml_files, style_sheets, renames)
+            })
             .await;
-    }
-
-    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
-        let uri = params.text_document_position_params.text_document.uri;
-        let position = params.text_document_position_params.position;
+            let Ok((uxml_files, style_sheets, renames)) = read else {
+                return Ok(());
+            };
+
+            if let Ok(mut state) = self.state.lock() {
+                let state = &mut *state;
+                if let Some(uxml_files) = uxml_files {
+                    state.uxml_usage_index.apply_changed_files(uxml_files);
+                }
+                if let Some(style_sheets) = style_sheets {
+                    state.import_graph.apply_changed_files(style_sheets);
+                    state.theme_report.invalidate();
+                }
+                if font_assets_changed {
+                    state.completion_provider.invalidate_font_assets();
+                }
+                // Addressables groups are `.asset` files too
+                if assets_changed || font_assets_changed {
+                    state.asset_paths = None;
+                    state.asset_paths_generation += 1;
+                }
 
-        let uxml_data = self.update_uxml_schema_and_get_data().await;
+                for (from, to) in renames {
+                    log::info!("Style sheet {} was renamed to {}", from, to);
+                    state.renamed_files.record(from, to);
+                }
+            }
 
-        let state = self.state.lock().ok();
-        if let Some(state) = state {
-            if let Some(document) = state.document_manager.get_document(&uri) {
-                if let Some(tree) = document.tree() {
-                    let project_url = state.unity_manager.convert_to_project_url(&uri);
+            if config_changed {
+                self.reload_config_and_warn().await;
+            }
 
-                    let hover = state.hover_provider.hover(
-                        tree,
-                        document.content(),
-                        position,
-                        &state.unity_manager,
-                        project_url.as_ref(),
-                        Some(uxml_data),
-                    );
-                    return Ok(hover);
+            // imports of renamed or deleted files and url() references of deleted assets are reported now,
+            // a changed configuration can change the diagnostics of every document
+            if assets_changed || config_changed || style_sheets_changed {
+                if let Err(e) = self.client.workspace_diagnostic_refresh().await {
+                    log::debug!("Failed to request diagnostic refresh: {}", e);
                 }
             }
-        }
-
-        Ok(None)
+            Ok(())
+        })
+        .await;
     }
 
-    async fn semantic_tokens_full(
-        &self,
-        params: SemanticTokensParams,
-    ) -> Result<Option<SemanticTokensResult>> {
-        let uri = params.text_document.uri;
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        let request_uri = params.text_document.uri.clone();
+        let _ = self.guarded("did_open", Some(&request_uri), async move {
+            let uri = params.text_document.uri;
+            let content = params.text_document.text;
+            let version = params.text_document.version;
+
+            // Open and parse the document
+            self.open_document(&uri, &content, version).await;
+            self.revalidate_dependents(&uri).await;
+
+            self.client
+                .log_message(MessageType::INFO, format!("Opened USS document: {}", uri))
+                .await;
+            Ok(())
+        })
+        .await;
+    }
 
-        if let Some(tokens) = self.generate_semantic_tokens(&uri) {
-            Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
-                result_id: None,
-                data: tokens,
-            })))
-        } else {
-            Ok(None)
-        }
+    async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        let request_uri = params.text_document.uri.clone();
+        let _ = self.guarded("did_change", Some(&request_uri), async move {
+            let uri = params.text_document.uri;
+            let version = params.text_document.version;
+            let changes = params.content_changes;
+            let paste = match changes.as_slice() {
+                [change] => Some(change.clone()),
+                _ => None,
+            };
+
+            // Update the document with incremental changes
+            self.update_document(&uri, changes, version).await;
+            self.handle_paste(&uri, paste.as_ref()).await;
+            self.revalidate_dependents(&uri).await;
+            Ok(())
+        })
+        .await;
     }
 
-    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
-        let uri = params.text_document_position.text_document.uri;
-        let position = params.text_document_position.position;
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let request_uri = params.text_document.uri.clone();
+        let _ = self.guarded("did_close", Some(&request_uri), async move {
+            let uri = params.text_document.uri;
+            let project_url = self.project_url(&uri);
+
+            if let Ok(mut state) = self.state.lock() {
+                state.document_manager.close_document(&uri);
+                state.symbol_index.remove_document(&uri);
+                state.dependency_signatures.remove(&uri);
+                state.pasted_texts.remove(&uri);
+                if let Some(project_url) = project_url {
+                    state.import_graph.remove_document_imports(&project_url);
+                }
+            }
 
-        // Debug logging
-        log::info!(
-            "Completion requested at {}:{} in {}",
-            position.line,
-            position.character,
-            uri
-        );
+            self.client
+                .log_message(MessageType::INFO, format!("Closed USS document: {}", uri))
+                .await;
+            Ok(())
+        })
+        .await;
+    }
 
-        let uxml_data = self.update_uxml_schema_and_get_data().await;
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let request_uri = params.text_document_position_params.text_document.uri.clone();
+        self.guarded("hover", Some(&request_uri), async move {
+            let uri = params.text_document_position_params.text_document.uri;
+            let position = params.text_document_position_params.position;
+
+            let uxml_data = self.update_uxml_schema_and_get_data().await;
+
+            // the values of a variable in each theme come from the theme report, built without holding the state
+            let on_variable = self.state.lock().is_ok_and(|state| {
+                state.document_manager.get_document(&uri).is_some_and(|document| {
+                    document
+                        .tree()
+                        .is_some_and(|tree| variable_name_at_position(tree, document.content(), position).is_some())
+                })
+            });
+            if on_variable {
+                self.refresh_theme_report().await;
+            }
 
-        // Perform all operations within a single lock scope
-        let completions = {
-            if let Ok(state) = self.state.lock() {
-                // Get document and validate
-                let document = match state.document_manager.get_document(&uri) {
-                    Some(doc) => doc,
-                    None => {
-                        log::warn!("Document not found for URI: {}", uri);
-                        return Ok(None);
-                    }
+            let project_url = self.project_url(&uri);
+            let (mut hover, tag_name, cascade_request) = {
+                let Ok(mut state) = self.state.lock() else {
+                    return Ok(None);
                 };
-
-                let tree = match document.tree() {
-                    Some(tree) => tree,
-                    None => {
-                        log::warn!("No syntax tree available for URI: {}", uri);
-                        return Ok(None);
-                    }
+                let state = &mut *state;
+                let Some(document) = state.document_manager.get_document(&uri) else {
+                    return Ok(None);
+                };
+                let Some(tree) = document.tree() else {
+                    return Ok(None);
                 };
 
-                let document_content = document.content();
-                let project_url = state.unity_manager.convert_to_project_url(&uri);
-
-                // Generate completions
-                state.completion_provider.complete(
+                let mut hover = state.hover_provider.hover_with_variables(
                     tree,
-                    document_content,
+                    document.content(),
                     position,
+                    &state.unity_manager,
                     project_url.as_ref(),
-                    Some(uxml_data),
-                    Some(&state.unity_manager),
-                )
-            } else {
-                log::error!("Failed to lock state");
-                return Ok(None);
-            }
-        };
+                    Some(uxml_data.clone()),
+                    Some(&document.variable_resolver),
+                );
+
+                // themes can override the variable, show its value in each of them
+                let theme_section = variable_name_at_position(tree, document.content(), position)
+                    .and_then(|name| state.theme_report.report().hover_section(&name));
+                if let Some(section) = theme_section {
+                    hover = Some(append_hover_section(hover, section));
+                }
 
-        // Debug: log completion results
-        log::info!("Generated {} completion items", completions.len());
+                // other rules that set the hovered property for the same elements, the open documents that may
+                // be imported are resolved once the lock is released
+                let cascade_request = (state.hover_cascade && cascade::is_on_property(tree, document.content(), position))
+                    .then(|| {
+                        let imports = project_url
+                            .as_ref()
+                            .map_or_else(Vec::new, |project_url| cascade::imported_urls(&state.import_graph, project_url));
+                        let open_uris: Vec<Url> = state.document_manager.document_uris().cloned().collect();
+                        (tree.clone(), document.content().to_string(), imports, open_uris, state.unity_manager.clone())
+                    });
+                (hover, tag_name_at_position(tree, document.content(), position), cascade_request)
+            };
+
+            if let Some((tree, content, imports, open_uris, unity_manager)) = cascade_request {
+                let open: HashMap<Url, Url> = open_uris
+                    .into_iter()
+                    .filter_map(|open| Some((unity_manager.convert_to_project_url(&open)?, open)))
+                    .collect();
+                // open imports are read from their documents, the others from the file system
+                let imports: Vec<(Url, Option<String>)> = match self.state.lock() {
+                    Ok(state) => imports
+                        .into_iter()
+                        .map(|url| {
+                            let content = open
+                                .get(&url)
+                                .and_then(|open| state.document_manager.get_document(open))
+                                .map(|document| document.content().to_string());
+                            (url, content)
+                        })
+                        .collect(),
+                    Err(_) => imports.into_iter().map(|url| (url, None)).collect(),
+                };
+                let project_root = unity_manager.project_path().clone();
+                let imports = tokio::task::spawn_blocking(move || cascade::read_style_sheets(&project_root, imports))
+                    .await
+                    .unwrap_or_default();
+                let entries = declaration_cascade(&tree, &content, position, || imports);
+                if let Some(section) = cascade::hover_section(&entries) {
+                    hover = Some(append_hover_section(hover, section));
+                }
+            }
 
-        if completions.is_empty() {
-            log::info!("Returning no completions");
-            Ok(None)
-        } else {
-            log::info!("Returning {} completions", completions.len());
-            Ok(Some(CompletionResponse::Array(completions)))
-        }
+            // custom elements are documented in their C# source, read without holding the state
+            let type_name = tag_name.and_then(|tag_name| {
+                let elements = uxml_data.lock().ok()?;
+                let fully_qualified_name = elements.get_all_names().get(&tag_name)?;
+                built_in_class_path(fully_qualified_name).is_none().then(|| fully_qualified_name.clone())
+            });
+            if let Some(section) = self.type_summary(type_name) {
+                hover = Some(append_hover_section(hover, section));
+            }
+            Ok(hover)
+        })
+        .await
     }
 
-    async fn diagnostic(
+    async fn semantic_tokens_full(
         &self,
-        params: DocumentDiagnosticParams,
-    ) -> Result<DocumentDiagnosticReportResult> {
-        let uri = params.text_document.uri;
+        params: SemanticTokensParams,
+    ) -> Result<Option<SemanticTokensResult>> {
+        let request_uri = params.text_document.uri.clone();
+        self.guarded("semantic_tokens_full", Some(&request_uri), async move {
+            let uri = params.text_document.uri;
 
-        let uxml_data = self.update_uxml_schema_and_get_data().await;
+            if let Some(tokens) = self.generate_semantic_tokens(&uri) {
+                Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
+                    result_id: None,
+                    data: tokens,
+                })))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
 
-        let mut uxml_names:Option<HashSet<String>> = None;
-        if let Ok(data) = uxml_data.lock() {
-            uxml_names = Some(data.get_all_names().keys().cloned().collect());
-        }
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        let request_uri = params.text_document_position.text_document.uri.clone();
+        self.guarded("completion", Some(&request_uri), async move {
+            let uri = params.text_document_position.text_document.uri;
+            let position = params.text_document_position.position;
+
+            // Debug logging
+            log::info!(
+                "Completion requested at {}:{} in {}",
+                position.line,
+                position.character,
+                uri
+            );
+
+            let uxml_data = self.update_uxml_schema_and_get_data().await;
+
+            // font assets are found by walking `Assets`, which is done without holding the state
+            let font_asset_index = self.state.lock().ok().and_then(|state| {
+                let document = state.document_manager.get_document(&uri)?;
+                let offers_font_assets =
+                    state.completion_provider.offers_font_assets(document.tree()?, document.content(), position);
+                offers_font_assets.then(|| state.completion_provider.font_asset_index()).flatten()
+            });
+            if let Some(index) = font_asset_index.filter(|index| index.is_stale()) {
+                if let Err(e) = tokio::task::spawn_blocking(move || index.refresh()).await {
+                    log::error!("Failed to find font assets: {}", e);
+                }
+            }
 
-        // Extract necessary data from state and release lock quickly
-        let (mut diagnostics, url_references, _, project_root) = {
-            if let Ok(state) = self.state.lock() {
-                // Generate diagnostics immediately
-                let (tree_clone, content, doc_version) =
-                    if let Some(document) = state.document_manager.get_document(&uri) {
-                        if let Some(tree) = document.tree() {
-                            (
-                                Some(tree.clone()),
-                                document.content().to_string(),
-                                document.document_version(),
-                            )
-                        } else {
-                            (None, String::new(), document.document_version())
+            // Perform all operations within a single lock scope
+            let completions = {
+                if let Ok(state) = self.state.lock() {
+                    // Get document and validate
+                    let document = match state.document_manager.get_document(&uri) {
+                        Some(doc) => doc,
+                        None => {
+                            log::warn!("Document not found for URI: {}", uri);
+                            return Ok(None);
                         }
-                    } else {
-                        (
-                            None,
-                            String::new(),
-                            crate::language::document::DocumentVersion { major: 0, minor: 0 },
-                        )
                     };
 
-                if let Some(tree) = tree_clone {
-                    let project_url = state.unity_manager.convert_to_project_url(&uri);
+                    let tree = match document.tree() {
+                        Some(tree) => tree,
+                        None => {
+                            log::warn!("No syntax tree available for URI: {}", uri);
+                            return Ok(None);
+                        }
+                    };
 
-                    // Get the variable resolver from the document for enhanced diagnostics
-                    let variable_resolver =
-                        if let Some(document) = state.document_manager.get_document(&uri) {
-                            Some(&document.variable_resolver)
-                        } else {
-                            None
-                        };
+                    let document_content = document.content();
+                    let project_url = state.unity_manager.convert_to_project_url(&uri);
 
-                    let (diagnostics, url_references) = state.diagnostics.analyze_with_variables_and_classes(
-                        &tree,
-                        &content,
+                    // Generate completions
+                    state.completion_provider.complete_list_with_variables(
+                        tree,
+                        document_content,
+                        position,
                         project_url.as_ref(),
-                        variable_resolver,
-                        uxml_names.as_ref(),
-                    );
-
-                    let project_root = state.unity_manager.project_path().clone();
-
-                    (diagnostics, url_references, doc_version, project_root)
-                } else {
-                    (
-                        Vec::new(),
-                        Vec::new(),
-                        doc_version,
-                        state.unity_manager.project_path().clone(),
+                        Some(uxml_data),
+                        Some(&state.unity_manager),
+                        Some(&document.variable_resolver),
                     )
+                } else {
+                    log::error!("Failed to lock state");
+                    return Ok(None);
                 }
+            };
+
+            // Debug: log completion results
+            log::info!("Generated {} completion items", completions.items.len());
+
+            if completions.items.is_empty() {
+                log::info!("Returning no completions");
+                Ok(None)
+            } else if completions.is_incomplete {
+                // the client asks again as the user types, so the candidates are ranked again
+                log::info!("Returning {} completions (incomplete)", completions.items.len());
+                Ok(Some(CompletionResponse::List(completions)))
             } else {
-                (
-                    Vec::new(),
-                    Vec::new(),
-                    crate::language::document::DocumentVersion { major: 0, minor: 0 },
-                    std::path::PathBuf::new(),
-                )
+                log::info!("Returning {} completions", completions.items.len());
+                Ok(Some(CompletionResponse::Array(completions.items)))
             }
-        }; // Lock is released here
+        })
+        .await
+    }
 
-        // Perform async asset validation outside the lock (inline, no task spawning)
-        for url_ref in &url_references {
-            // Handle project:// URLs manually since to_file_path() doesn't work with custom schemes
-            if url_ref.url.scheme() == PROJECT_SCHEME {
-                if let Some(full_path) = project_url_to_path(&project_root, &url_ref.url) {
-                    // Check if the asset file exists using async try_exists for better error handling
-                    match tokio::fs::try_exists(&full_path).await {
-                        Ok(false) => {
-                            diagnostics.push(Diagnostic {
-                                range: url_ref.range,
-                                severity: Some(DiagnosticSeverity::WARNING),
-                                code: Some(NumberOrString::String("asset-not-found".to_string())),
-                                source: Some("uss".to_string()),
-                                message: format!(
-                                    "Asset doesn't exist on path: {}",
-                                    full_path.display()
-                                ),
-                                ..Default::default()
-                            });
-                        }
-                        Err(e) => {
-                            // Log the error but don't create a diagnostic for permission/access issues
-                            log::debug!(
-                                "Cannot check asset existence for {}: {}",
-                                full_path.display(),
-                                e
-                            );
-                        }
-                        Ok(true) => {
-                            // File exists, no diagnostic needed
-                        }
-                    }
+    async fn diagnostic(
+        &self,
+        params: DocumentDiagnosticParams,
+    ) -> Result<DocumentDiagnosticReportResult> {
+        let request_uri = params.text_document.uri.clone();
+        self.guarded("diagnostic", Some(&request_uri), async move {
+            let uri = params.text_document.uri;
+
+            if self.reload_config_if_changed().await {
+                // other documents may have changed too
+                if let Err(e) = self.client.workspace_diagnostic_refresh().await {
+                    log::debug!("Failed to request diagnostic refresh: {}", e);
                 }
             }
-        }
+            if self.is_ignored(&uri) {
+                return Ok(DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(
+                    RelatedFullDocumentDiagnosticReport::default(),
+                )));
+            }
 
-        // Asset validation is now performed synchronously above and included in diagnostics
+            let mut diagnostics = match self.document_diagnostics(&uri).await {
+                Ok(diagnostics) => diagnostics,
+                // a newer request for the document's diagnostics is answered instead
+                Err(WorkError::Superseded) => {
+                    return Err(tower_lsp::jsonrpc::Error {
+                        code: tower_lsp::jsonrpc::ErrorCode::ContentModified,
+                        message: "Diagnostics of a newer request".into(),
+                        data: None,
+                    });
+                }
+                // the panic is raised again here, so it's logged and reported like a panic of the handler
+                Err(WorkError::Panicked(e)) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+                Err(WorkError::Panicked(e)) => {
+                    return Err(tower_lsp::jsonrpc::Error {
+                        code: tower_lsp::jsonrpc::ErrorCode::InternalError,
+                        message: format!("Internal error while handling diagnostic: {}", e).into(),
+                        data: None,
+                    });
+                }
+            };
+            self.apply_baseline(&uri, &mut diagnostics);
 
-        Ok(DocumentDiagnosticReportResult::Report(
-            DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
-                related_documents: None,
-                full_document_diagnostic_report: FullDocumentDiagnosticReport {
-                    result_id: None,
-                    items: diagnostics,
-                },
-            }),
-        ))
+            if self.is_read_only(&uri) {
+                package_cache::make_informational(&mut diagnostics);
+            }
+
+            Ok(DocumentDiagnosticReportResult::Report(
+                DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                    related_documents: None,
+                    full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                        result_id: None,
+                        items: diagnostics,
+                    },
+                }),
+            ))
+        })
+        .await
     }
 
     async fn document_color(&self, params: DocumentColorParams) -> Result<Vec<ColorInformation>> {
-        let uri = params.text_document.uri;
-
-        let colors = if let Ok(state) = self.state.lock() {
-            if let Some(document) = state.document_manager.get_document(&uri) {
-                if let Some(tree) = document.tree() {
-                    state
-                        .color_provider
-                        .provide_document_colors(tree, document.content())
+        let request_uri = params.text_document.uri.clone();
+        self.guarded("document_color", Some(&request_uri), async move {
+            let uri = params.text_document.uri;
+
+            let colors = if let Ok(state) = self.state.lock() {
+                if let Some(document) = state.document_manager.get_document(&uri) {
+                    if let Some(tree) = document.tree() {
+                        state.color_provider.provide_document_colors_with_variables(
+                            tree,
+                            document.content(),
+                            Some(&document.variable_resolver),
+                        )
+                    } else {
+                        Vec::new()
+                    }
                 } else {
                     Vec::new()
                 }
             } else {
                 Vec::new()
-            }
-        } else {
-            Vec::new()
-        };
+            };
 
-        Ok(colors)
+            Ok(colors)
+        })
+        .await
     }
 
     async fn color_presentation(
         &self,
         params: ColorPresentationParams,
     ) -> Result<Vec<ColorPresentation>> {
-        let state = self.state.lock().ok();
-        if let Some(state) = state {
-            let presentations = state
-                .color_provider
-                .provide_color_presentations(&params.color, params.range);
-            Ok(presentations)
-        } else {
-            Ok(Vec::new())
-        }
+        let request_uri = params.text_document.uri.clone();
+        let presentations = self.guarded("color_presentation", Some(&request_uri), async move {
+            let Ok(state) = self.state.lock() else {
+                return Ok(Vec::new());
+            };
+            // colors of variables are shown but can't be changed where the variable is referenced
+            let is_variable_color = state
+                .document_manager
+                .get_document(&params.text_document.uri)
+                .is_some_and(|document| state.color_provider.is_variable_color(document.content(), params.range));
+            if is_variable_color {
+                return Ok(Vec::new());
+            }
+
+            Ok(state.color_provider.provide_color_presentations(&params.color, params.range))
+        })
+        .await;
+        // the color picker has nothing to offer instead of showing an error
+        Ok(presentations.unwrap_or_default())
     }
 
     async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
-        let uri = params.text_document.uri;
-
-        let result = if let Ok(state) = self.state.lock() {
-            if let Some(document) = state.document_manager.get_document(&uri) {
-                if let Some(tree) = document.tree() {
-                    match state.formatter.format_document(document.content(), tree) {
-                        Ok(edits) => {
-                            if edits.is_empty() {
-                                log::debug!("No formatting changes needed for {}", uri);
+        let request_uri = params.text_document.uri.clone();
+        self.guarded("formatting", Some(&request_uri), async move {
+            let uri = params.text_document.uri;
+            if self.is_read_only(&uri) {
+                return Err(package_cache::read_only_error(&uri));
+            }
+            if !self.state.lock().is_ok_and(|state| state.format_enabled) {
+                log::debug!("Formatting is disabled by {}", CONFIG_FILE_NAME);
+                return Ok(None);
+            }
+
+            let result = if let Ok(state) = self.state.lock() {
+                if let Some(document) = state.document_manager.get_document(&uri) {
+                    if let Some(tree) = document.tree() {
+                        match state.formatter.format_document(document.content(), tree) {
+                            Ok(edits) => {
+                                if edits.is_empty() {
+                                    log::debug!("No formatting changes needed for {}", uri);
+                                    None
+                                } else {
+                                    log::info!("Applied {} formatting edits to {}", edits.len(), uri);
+                                    Some(edits)
+                                }
+                            }
+                            Err(e) => {
+                                log::warn!("Failed to format document {}: {}", uri, e);
                                 None
-                            } else {
-                                log::info!("Applied {} formatting edits to {}", edits.len(), uri);
-                                Some(edits)
                             }
                         }
-                        Err(e) => {
-                            log::warn!("Failed to format document {}: {}", uri, e);
-                            None
-                        }
+                    } else {
+                        log::warn!("No syntax tree available for formatting: {}", uri);
+                        None
                     }
                 } else {
-                    log::warn!("No syntax tree available for formatting: {}", uri);
+                    log::warn!("Document not found for formatting: {}", uri);
                     None
                 }
             } else {
-                log::warn!("Document not found for formatting: {}", uri);
+                log::error!("Failed to acquire state lock for formatting");
                 None
-            }
-        } else {
-            log::error!("Failed to acquire state lock for formatting");
-            None
-        };
+            };
 
-        Ok(result)
+            Ok(result)
+        })
+        .await
     }
 
     async fn range_formatting(
         &self,
         params: DocumentRangeFormattingParams,
     ) -> Result<Option<Vec<TextEdit>>> {
-        let uri = params.text_document.uri;
-        let range = params.range;
-
-        let result = if let Ok(state) = self.state.lock() {
-            if let Some(document) = state.document_manager.get_document(&uri) {
-                if let Some(tree) = document.tree() {
-                    match state.formatter.format_range(document.content(), tree, range) {
-                        Ok(edits) => {
-                            if edits.is_empty() {
-                                log::debug!("No range formatting changes needed for {} at {:?}", uri, range);
+        let request_uri = params.text_document.uri.clone();
+        self.guarded("range_formatting", Some(&request_uri), async move {
+            let uri = params.text_document.uri;
+            if self.is_read_only(&uri) {
+                return Err(package_cache::read_only_error(&uri));
+            }
+            if !self.state.lock().is_ok_and(|state| state.format_enabled) {
+                log::debug!("Formatting is disabled by {}", CONFIG_FILE_NAME);
+                return Ok(None);
+            }
+            let range = params.range;
+
+            let result = if let Ok(state) = self.state.lock() {
+                if let Some(document) = state.document_manager.get_document(&uri) {
+                    if let Some(tree) = document.tree() {
+                        match state.formatter.format_range(document.content(), tree, range) {
+                            Ok(edits) => {
+                                if edits.is_empty() {
+                                    log::debug!("No range formatting changes needed for {} at {:?}", uri, range);
+                                    None
+                                } else {
+                                    log::info!("Applied {} range formatting edits to {} at {:?}", edits.len(), uri, range);
+                                    Some(edits)
+                                }
+                            }
+                            Err(e) => {
+                                log::warn!("Failed to format range in document {}: {}", uri, e);
                                 None
-                            } else {
-                                log::info!("Applied {} range formatting edits to {} at {:?}", edits.len(), uri, range);
-                                Some(edits)
                             }
                         }
-                        Err(e) => {
-                            log::warn!("Failed to format range in document {}: {}", uri, e);
-                            None
-                        }
+                    } else {
+                        log::warn!("No syntax tree available for range formatting: {}", uri);
+                        None
                     }
                 } else {
-                    log::warn!("No syntax tree available for range formatting: {}", uri);
+                    log::warn!("Document not found for range formatting: {}", uri);
                     None
                 }
             } else {
-                log::warn!("Document not found for range formatting: {}", uri);
+                log::error!("Failed to acquire state lock for range formatting");
                 None
-            }
-        } else {
-            log::error!("Failed to acquire state lock for range formatting");
-            None
-        };
+            };
 
-        Ok(result)
+            Ok(result)
+        })
+        .await
     }
 
     async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
-        let uri = params.text_document.uri;
-        let range = params.range;
+        let request_uri = params.text_document.uri.clone();
+        self.guarded("code_action", Some(&request_uri), async move {
+            let uri = params.text_document.uri;
+            let range = params.range;
+
+            // edits can't be applied here, offer to make a copy that can be edited instead
+            if self.is_read_only(&uri) {
+                let action = CodeAction {
+                    title: COPY_TO_ASSETS_TITLE.to_string(),
+                    kind: Some(CodeActionKind::REFACTOR),
+                    command: Some(Command {
+                        title: COPY_TO_ASSETS_TITLE.to_string(),
+                        command: COPY_TO_ASSETS_COMMAND.to_string(),
+                        arguments: Some(vec![serde_json::Value::String(uri.to_string())]),
+                    }),
+                    ..Default::default()
+                };
+                return Ok(Some(vec![CodeActionOrCommand::CodeAction(action)]));
+            }
         
-        if let Ok(state) = self.state.lock() {
-            if let Some(document) = state.document_manager.get_document(&uri) {
-                if let Some(tree) = document.tree() {
-                    if let Some(actions) = state.refactor_provider.get_code_actions(tree, document.content(), &uri, range) {
-                        return Ok(Some(CodeActionResponse::from(actions)));
+            // the resolver may need to be built, which walks the project, only get it for a reference at the cursor
+            let at_reference = self.state.lock().is_ok_and(|state| {
+                state
+                    .document_manager
+                    .get_document(&uri)
+                    .and_then(|document| reference_at(document.tree()?, document.content(), range.start))
+                    .is_some()
+            });
+            let asset_paths = if at_reference { self.asset_paths().await } else { None };
+
+            if let Ok(mut state) = self.state.lock() {
+                if let Some(document) = state.document_manager.get_document(&uri) {
+                    if let Some(tree) = document.tree() {
+                        let versioned = document.versioned_identifier();
+                        let project_url = state.unity_manager.convert_to_project_url(&uri);
+                        let mut actions = state
+                            .refactor_provider
+                            .get_code_actions(tree, document.content(), &uri, range)
+                            .unwrap_or_default();
+                        actions.extend(unit_conversion_actions(tree, document.content(), &versioned, range));
+                        if let (Some(project_url), Some(asset_paths)) = (&project_url, &asset_paths) {
+                            actions.extend(reference_form_actions(tree, document.content(), project_url, &versioned, range, asset_paths));
+                        }
+                        if let Some(action) = state.declaration_organizer.code_action(tree, document.content(), &versioned) {
+                            actions.push(action);
+                        }
+                        if let Some(pasted) = state.pasted_texts.get(&uri).filter(|_| state.format_enabled) {
+                            actions.extend(format_pasted_action(&versioned, pasted, range));
+                        }
+                        for diagnostic in &params.context.diagnostics {
+                            if let Some(action) =
+                                unused_variables::remove_variable_action(tree, document.content(), &versioned, diagnostic)
+                            {
+                                actions.push(CodeActionOrCommand::CodeAction(action));
+                            }
+                            if let Some(action) = replace_keyword_action(diagnostic, &versioned) {
+                                actions.push(CodeActionOrCommand::CodeAction(action));
+                            }
+                            if let Some(action) = replace_deprecated_property_action(diagnostic, &versioned) {
+                                actions.push(CodeActionOrCommand::CodeAction(action));
+                            }
+                            if let Some(action) = move_import_action(tree, document.content(), &versioned, diagnostic) {
+                                actions.push(CodeActionOrCommand::CodeAction(action));
+                            }
+                            if let Some(action) = project_url.as_ref().and_then(|importer| {
+                                update_import_action(diagnostic, document.content(), importer, &versioned)
+                            }) {
+                                actions.push(CodeActionOrCommand::CodeAction(action));
+                            }
+                        }
+
+                        if !state.document_changes_support {
+                            for action in &mut actions {
+                                if let CodeActionOrCommand::CodeAction(action) = action {
+                                    action.edit = action.edit.take().map(without_versions);
+                                }
+                            }
+                        }
+                        if !actions.is_empty() {
+                            return Ok(Some(CodeActionResponse::from(actions)));
+                        }
                     }
                 }
             }
-        }
         
-        Ok(None)
+            Ok(None)
+        })
+        .await
+    }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let request_uri = params.text_document_position.text_document.uri.clone();
+        self.guarded("references", Some(&request_uri), async move {
+            let uri = params.text_document_position.text_document.uri;
+            let position = params.text_document_position.position;
+            // a snapshot, so the index can be updated while the locations are collected
+            let snapshot = match self.state.lock() {
+                Ok(state) => state.symbol_index.snapshot(),
+                Err(_) => return Ok(None),
+            };
+
+            // the guard stops the search when tower-lsp drops this future
+            let token = CancellationToken::new();
+            let _guard = token.cancel_on_drop();
+            let include_declaration = params.context.include_declaration;
+            let locations = tokio::task::spawn_blocking(move || {
+                let symbol = snapshot.document(&uri)?.symbol_at(position)?;
+                snapshot.find(symbol.kind, &symbol.name, include_declaration, &token).ok()
+            })
+            .await
+            .ok()
+            .flatten();
+            Ok(locations)
+        })
+        .await
     }
 
     async fn prepare_rename(&self, params: TextDocumentPositionParams) -> Result<Option<PrepareRenameResponse>> {
-        let uri = params.text_document.uri;
-        let position = params.position;
+        let request_uri = params.text_document.uri.clone();
+        self.guarded("prepare_rename", Some(&request_uri), async move {
+            let uri = params.text_document.uri;
+            if self.is_read_only(&uri) {
+                return Err(package_cache::read_only_error(&uri));
+            }
+            let position = params.position;
         
-        if let Ok(state) = self.state.lock() {
-            if let Some(document) = state.document_manager.get_document(&uri) {
-                if let Some(tree) = document.tree() {
-                    return Ok(state.refactor_provider.prepare_rename(tree.root_node(), document.content(), position));
-
+            if let Ok(state) = self.state.lock() {
+                if let Some(document) = state.document_manager.get_document(&uri) {
+                    if let Some(tree) = document.tree() {
+                        let response = state
+                            .refactor_provider
+                            .prepare_rename(tree.root_node(), document.content(), position)
+                            .or_else(|| prepare_rename_variable(&document.indexed_symbols(), position));
+                        // tell the user why, instead of the editor's generic message
+                        return match response {
+                            Some(response) => Ok(Some(response)),
+                            None => Err(state.refactor_provider.rename_error(tree.root_node(), document.content(), position).into()),
+                        };
+                    }
                 }
             }
-        }
         
-        Ok(None)
+            Ok(None)
+        })
+        .await
     }
 
     async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
-        let uri = params.text_document_position.text_document.uri;
-        let position = params.text_document_position.position;
-        let new_name = params.new_name;
+        let request_uri = params.text_document_position.text_document.uri.clone();
+        self.guarded("rename", Some(&request_uri), async move {
+            let uri = params.text_document_position.text_document.uri;
+            let position = params.text_document_position.position;
+            let new_name = params.new_name;
+            if self.is_read_only(&uri) {
+                return Err(package_cache::read_only_error(&uri));
+            }
         
-        if let Ok(state) = self.state.lock() {
-            if let Some(document) = state.document_manager.get_document(&uri) {
-                if let Some(tree) = document.tree() {
-                    return Ok(state.refactor_provider.handle_rename(tree.root_node(), document.content(), &uri, position, &new_name));
+            if let Ok(state) = self.state.lock() {
+                if let Some(document) = state.document_manager.get_document(&uri) {
+                    if let Some(tree) = document.tree() {
+                        let symbols = document.indexed_symbols();
+                        let edit = match rename_variable(&symbols, &document.versioned_identifier(), position, &new_name) {
+                            Some(edit) => Some(edit?),
+                            None => {
+                                if let Some(symbol) = symbols.symbol_at(position) {
+                                    validate_new_name(symbol.kind, &new_name)?;
+                                }
+                                state.refactor_provider.handle_rename(
+                                    tree.root_node(),
+                                    document.content(),
+                                    &document.versioned_identifier(),
+                                    position,
+                                    &new_name,
+                                )
+                            }
+                        };
+                        if !state.document_changes_support {
+                            return Ok(edit.map(without_versions));
+                        }
+                        return Ok(edit);
+                    }
                 }
             }
-        }
         
-        Ok(None)
+            Ok(None)
+        })
+        .await
+    }
+
+    async fn execute_command(&self, params: ExecuteCommandParams) -> Result<Option<serde_json::Value>> {
+        self.guarded("execute_command", None, async move {
+            match params.command.as_str() {
+                DUMP_IMPORT_GRAPH_COMMAND => {
+                    self.refresh_import_graph().await;
+                    if let Ok(state) = self.state.lock() {
+                        return Ok(serde_json::to_value(state.import_graph.to_json()).ok());
+                    }
+                }
+                RELOAD_CONFIG_COMMAND => {
+                    let config = self.reload_config().map_err(|e| tower_lsp::jsonrpc::Error {
+                        code: tower_lsp::jsonrpc::ErrorCode::InvalidRequest,
+                        message: e.to_string().into(),
+                        data: None,
+                    })?;
+                    if let Err(e) = self.client.workspace_diagnostic_refresh().await {
+                        log::debug!("Failed to request diagnostic refresh: {}", e);
+                    }
+                    return Ok(serde_json::to_value(config).ok());
+                }
+                UPDATE_BASELINE_COMMAND => {
+                    let summary = self
+                        .update_baseline()
+                        .await?;
+                    log::info!(
+                        "Wrote {} with {} diagnostics in {} files",
+                        BASELINE_FILE_NAME,
+                        summary.diagnostics,
+                        summary.files
+                    );
+                    if let Err(e) = self.client.workspace_diagnostic_refresh().await {
+                        log::debug!("Failed to request diagnostic refresh: {}", e);
+                    }
+                    return Ok(serde_json::to_value(summary).ok());
+                }
+                METRICS_COMMAND => {
+                    return Ok(serde_json::to_value(request_timing::report()).ok());
+                }
+                REINDEX_COMMAND => {
+                    let files = self.reindex_project().await;
+                    return Ok(Some(serde_json::json!({ "files": files })));
+                }
+                SET_LOG_LEVEL_COMMAND => {
+                    let filter = params
+                        .arguments
+                        .first()
+                        .and_then(|a| a.as_str())
+                        .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Expected the log filter"))?;
+                    match filter.parse::<LogFilter>() {
+                        Ok(filter) => {
+                            set_log_filter(filter.clone());
+                            log::info!("Log filter changed to {:?}", filter);
+                        }
+                        Err(e) => return Err(tower_lsp::jsonrpc::Error::invalid_params(e.to_string())),
+                    }
+                }
+                CONVERT_LENGTH_COMMAND => {
+                    let arguments = params
+                        .arguments
+                        .first()
+                        .and_then(|a| serde_json::from_value::<ConvertLengthArguments>(a.clone()).ok())
+                        .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Expected the length to convert"))?;
+                    let mut edit = arguments.convert().map_err(tower_lsp::jsonrpc::Error::invalid_params)?;
+                    let document_changes_support = match self.state.lock() {
+                        Ok(state) => state.document_changes_support,
+                        Err(_) => return Ok(None),
+                    };
+                    if !document_changes_support {
+                        edit = without_versions(edit);
+                    }
+
+                    let response = self
+                        .client
+                        .apply_edit(edit)
+                        .await
+                        .map_err(|e| tower_lsp::jsonrpc::Error {
+                            code: tower_lsp::jsonrpc::ErrorCode::InternalError,
+                            message: format!("Failed to apply the length conversion: {}", e).into(),
+                            data: None,
+                        })?;
+                    if !response.applied {
+                        log::warn!(
+                            "Length conversion was not applied: {}",
+                            response.failure_reason.unwrap_or_default()
+                        );
+                    }
+                    return Ok(Some(serde_json::Value::Bool(response.applied)));
+                }
+                COPY_TO_ASSETS_COMMAND => {
+                    let uri = params
+                        .arguments
+                        .first()
+                        .and_then(|a| serde_json::from_value::<Url>(a.clone()).ok())
+                        .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Expected the URI of a style sheet"))?;
+                    let project_root = match self.state.lock() {
+                        Ok(state) => state.unity_manager.project_path().clone(),
+                        Err(_) => return Ok(None),
+                    };
+
+                    let copied = package_cache::copy_to_assets(&project_root, &uri)
+                        .map_err(|e| tower_lsp::jsonrpc::Error::invalid_params(e.to_string()))?;
+                    log::info!("Copied {} to {}", uri, copied.url);
+
+                    let show = ShowDocumentParams {
+                        uri: copied.uri.clone(),
+                        external: None,
+                        take_focus: Some(true),
+                        selection: None,
+                    };
+                    if let Err(e) = self.client.show_document(show).await {
+                        log::warn!("Failed to show {}: {}", copied.uri, e);
+                    }
+                    return Ok(serde_json::to_value(copied).ok());
+                }
+                _ => log::warn!("Unknown command: {}", params.command),
+            }
+
+            Ok(None)
+        })
+        .await
+    }
+
+    async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+        let request_uri = params.text_document.uri.clone();
+        self.guarded("code_lens", Some(&request_uri), async move {
+            let uri = params.text_document.uri;
+
+            if let Ok(state) = self.state.lock() {
+                if let Some(document) = state.document_manager.get_document(&uri) {
+                    if let Some(tree) = document.tree() {
+                        let lenses = state.code_lens_provider.provide_code_lenses(tree.root_node(), document.content(), &uri);
+                        return Ok(Some(lenses));
+                    }
+                }
+            }
+
+            Ok(None)
+        })
+        .await
+    }
+
+    async fn code_lens_resolve(&self, params: CodeLens) -> Result<CodeLens> {
+        self.guarded("code_lens_resolve", None, async move {
+            let uri = params
+                .data
+                .as_ref()
+                .and_then(|data| data.get("uri"))
+                .and_then(|uri| serde_json::from_value::<Url>(uri.clone()).ok());
+            let data = params
+                .data
+                .clone()
+                .and_then(|data| serde_json::from_value::<CodeLensData>(data).ok());
+
+            // scanning the project for UXML files can take a while, do it off the runtime so the
+            // request can be cancelled, the guard stops the scan when tower-lsp drops this future
+            let scan = match self.state.lock() {
+                Ok(state) if matches!(data, Some(CodeLensData::Selector { .. })) && state.uxml_usage_index.is_stale() => Some((
+                    state.unity_manager.project_path().clone(),
+                    state.uxml_usage_index.indexed_versions(),
+                    state.work_done_progress_support,
+                )),
+                _ => None,
+            };
+            let plan = match scan {
+                Some((project_root, indexed, progress_support)) => {
+                    tokio::task::spawn_blocking(move || RefreshPlan::new(&project_root, &indexed))
+                        .await
+                        .ok()
+                        .map(|plan| (plan, progress_support))
+                }
+                None => None,
+            };
+            if let Some((plan, progress_support)) = plan {
+                let token = CancellationToken::new();
+                let _guard = token.cancel_on_drop();
+                let counter = ProgressCounter::new();
+                // small refreshes are over before the editor would show them
+                let mut reporter = ProgressReporter::begin(
+                    &self.client,
+                    progress_support && plan.to_parse.len() >= MIN_FILES_FOR_PROGRESS,
+                    "Indexing UXML files",
+                )
+                .await;
+
+                let paths = plan.to_parse.clone();
+                let work_counter = counter.clone();
+                let parsed = reporter
+                    .track(&counter, tokio::task::spawn_blocking(move || usage_index::parse_files(&paths, &token, &work_counter)))
+                    .await;
+                reporter.end(None).await;
+                if let (Ok(Ok(parsed)), Ok(mut state)) = (parsed, self.state.lock()) {
+                    state.uxml_usage_index.apply_refresh(plan, parsed);
+                }
+            }
+
+            // references and declarations of a variable in the style sheets that import the document, read from disk
+            let mut elsewhere = VariableLocations::default();
+            if let Some(CodeLensData::Variable { uri, name }) = data {
+                self.refresh_import_graph().await;
+                let project_url = self.project_url(&uri);
+                let importers = project_url.and_then(|project_url| {
+                    let state = self.state.lock().ok()?;
+                    Some((state.unity_manager.project_path().clone(), state.import_graph.importers_of(&project_url)))
+                });
+                if let Some((project_root, importers)) = importers {
+                    elsewhere = tokio::task::spawn_blocking(move || {
+                        unused_variables::variable_locations_in_files(&project_root, &importers, &name)
+                    })
+                    .await
+                    .unwrap_or_default();
+                }
+            }
+
+            if let (Some(uri), Ok(state)) = (uri, self.state.lock()) {
+                if let Some(document) = state.document_manager.get_document(&uri) {
+                    if let Some(tree) = document.tree() {
+                        return Ok(state.code_lens_provider.resolve_code_lens(
+                            params,
+                            tree.root_node(),
+                            document.content(),
+                            &state.uxml_usage_index,
+                            Some(&document.variable_resolver),
+                            &elsewhere,
+                        ));
+                    }
+                }
+            }
+
+            Ok(params)
+        })
+        .await
+    }
+}
+
+/// A document of the document manager, its content may have unsaved changes
+struct OpenStyleSheet {
+    tree: Tree,
+    content: String,
+    document: OptionalVersionedTextDocumentIdentifier,
+}
+
+/// The URL references of a style sheet, with its project URL and the version of the document they are from
+///
+/// References of an open document are taken from its content, other style sheets are read from disk,
+/// async code should call it on a blocking thread.
+fn style_sheet_references(
+    unity_manager: &UnityProjectManager,
+    resolver: &UrlReferenceResolver,
+    parser: &mut UssParser,
+    open: &HashMap<Url, OpenStyleSheet>,
+    uri: &Url,
+) -> Option<(Url, FileUrlReferences, OptionalVersionedTextDocumentIdentifier)> {
+    let project_url = unity_manager.convert_to_project_url(uri)?;
+    if let Some(open) = open.get(uri) {
+        let references = resolver.file_references(&open.tree, &open.content, &project_url);
+        return Some((project_url, references, open.document.clone()));
+    }
+
+    let content = std::fs::read_to_string(uri.to_file_path().ok()?).ok()?;
+    let tree = parser.parse(&content, None)?;
+    let references = resolver.file_references(&tree, &content, &project_url);
+    Some((project_url, references, versioned_document(uri, None)))
+}
+
+/// Files and folders whose renaming updates references in style sheets, see [`FileMoves`]
+fn renamed_file_filters() -> Vec<FileOperationFilter> {
+    let mut extensions = vec!["uss", "tss"];
+    extensions.extend(IMAGE_ASSETS.extensions);
+    extensions.extend(FONT_ASSETS.extensions.iter().filter(|e| !IMAGE_ASSETS.extensions.contains(e)));
+
+    let filter = |glob: String, matches| FileOperationFilter {
+        scheme: Some("file".to_string()),
+        pattern: FileOperationPattern {
+            glob,
+            matches: Some(matches),
+            options: Some(FileOperationPatternOptions { ignore_case: Some(true) }),
+        },
+    };
+    vec![
+        filter(format!("**/*.{{{}}}", extensions.join(",")), FileOperationPatternKind::File),
+        filter("**/{Assets,Packages}/**".to_string(), FileOperationPatternKind::Folder),
+    ]
+}
+
+/// Append a markdown section to a hover, or create a hover with it
+fn append_hover_section(hover: Option<Hover>, section: String) -> Hover {
+    match hover {
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent { kind, value }),
+            range,
+        }) => Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind,
+                value: format!("{}\n\n{}", value, section),
+            }),
+            range,
+        },
+        // other kinds of contents are not created by the hover provider
+        Some(hover) => hover,
+        None => Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: section,
+            }),
+            range: None,
+        },
+    }
+}
+
+/// Content of a document of a custom request, `text` if the editor sent it, otherwise read from disk
+async fn read_document(uri: &Url, text: Option<String>) -> Result<String> {
+    let invalid_params = |message: String| tower_lsp::jsonrpc::Error::invalid_params(message);
+    match text {
+        Some(text) => Ok(text),
+        None => {
+            let path = uri.to_file_path().map_err(|_| invalid_params(format!("Not a file: {}", uri)))?;
+            tokio::fs::read_to_string(&path)
+                .await
+                .map_err(|e| invalid_params(format!("Failed to read {}: {}", path.display(), e)))
+        }
     }
 }
 
 /// Create and start the USS language server
-pub async fn start_uss_language_server(project_path: std::path::PathBuf, uxml_schema_manager: Arc<tokio::sync::Mutex<UxmlSchemaManager>>) -> Result<()> {
+pub async fn start_uss_language_server(
+    project_path: std::path::PathBuf,
+    uxml_schema_manager: Arc<tokio::sync::Mutex<UxmlSchemaManager>>,
+    docs_manager: Arc<tokio::sync::Mutex<CsDocsManager>>,
+) -> Result<()> {
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
 
     let (service, socket) =
-        LspService::new(|client| UssLanguageServer::new(client, project_path.clone(), uxml_schema_manager));
+        LspService::build(|client| UssLanguageServer::new(client, project_path.clone(), uxml_schema_manager, docs_manager))
+            .custom_method(INLINE_STYLE_DIAGNOSTICS_METHOD, UssLanguageServer::inline_style_diagnostics)
+            .custom_method(INLINE_STYLE_COMPLETION_METHOD, UssLanguageServer::inline_style_completion)
+            .custom_method(MATCH_RULES_METHOD, UssLanguageServer::match_rules)
+            .custom_method(STYLE_DIFF_METHOD, UssLanguageServer::style_diff)
+            .custom_method(UXML_ATTRIBUTE_COMPLETION_METHOD, UssLanguageServer::uxml_attribute_completion)
+            .custom_method(UXML_TEMPLATE_DEFINITION_METHOD, UssLanguageServer::uxml_template_definition)
+            .custom_method(UXML_TEMPLATE_DIAGNOSTICS_METHOD, UssLanguageServer::uxml_template_diagnostics)
+            .custom_method(URL_REFERENCES_METHOD, UssLanguageServer::url_references)
+            .finish();
     Server::new(stdin, stdout, socket).serve(service).await;
 
     Ok(())
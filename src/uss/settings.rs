@@ -0,0 +1,305 @@
+//! USS Language Server Settings
+//!
+//! User configurable settings, read from `initializationOptions` and from
+//! `workspace/didChangeConfiguration` notifications.
+//!
+//! Settings can be provided either directly or under a `uss` section, eg.
+//!
+//! ```json
+//! { "uss": { "diagnostics": { "severity": { "duplicate-property": "off", "missing-uss-extension": "error" }, "unusedVariables": "importers", "mode": "strict-unity" }, "declarationOrder": "grouped", "requestTiming": true, "blockSnippets": false, "hoverCascade": false } }
+//! ```
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+
+use crate::uss::organize::DeclarationOrder;
+use crate::uss::paste::PasteFormatting;
+use crate::uss::unity_compatibility::ValidationMode;
+use crate::uss::unused_variables::UnusedVariableScope;
+
+/// Severity level a diagnostic code can be configured to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeverityLevel {
+    Error,
+    Warning,
+    Info,
+    Hint,
+    /// The diagnostic is not reported at all
+    Off,
+}
+
+impl SeverityLevel {
+    /// Parse a severity level from its configuration name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "error" => Some(Self::Error),
+            "warning" => Some(Self::Warning),
+            "info" | "information" => Some(Self::Info),
+            "hint" => Some(Self::Hint),
+            "off" | "none" => Some(Self::Off),
+            _ => None,
+        }
+    }
+
+    /// The LSP severity, `None` for `Off`
+    pub fn to_lsp(self) -> Option<DiagnosticSeverity> {
+        match self {
+            Self::Error => Some(DiagnosticSeverity::ERROR),
+            Self::Warning => Some(DiagnosticSeverity::WARNING),
+            Self::Info => Some(DiagnosticSeverity::INFORMATION),
+            Self::Hint => Some(DiagnosticSeverity::HINT),
+            Self::Off => None,
+        }
+    }
+}
+
+/// Diagnostic related settings
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiagnosticSettings {
+    /// Severity overrides by diagnostic code (e.g. `duplicate-property`)
+    pub severity_overrides: HashMap<String, SeverityLevel>,
+    /// Where references are searched for unused variable diagnostics
+    pub unused_variables: UnusedVariableScope,
+    /// Which rules values are validated with, see [`crate::uss::unity_compatibility`]
+    pub mode: ValidationMode,
+}
+
+impl DiagnosticSettings {
+    /// Get the configured severity for a diagnostic code
+    pub fn severity_for(&self, code: &str) -> Option<SeverityLevel> {
+        self.severity_overrides.get(code).copied()
+    }
+
+    /// Apply severity overrides to diagnostics, removing the ones that are turned off
+    pub fn apply(&self, diagnostics: &mut Vec<Diagnostic>) {
+        if self.severity_overrides.is_empty() {
+            return;
+        }
+
+        diagnostics.retain_mut(|diagnostic| {
+            let Some(NumberOrString::String(code)) = &diagnostic.code else {
+                return true;
+            };
+
+            match self.severity_for(code) {
+                Some(SeverityLevel::Off) => false,
+                Some(level) => {
+                    diagnostic.severity = level.to_lsp();
+                    true
+                }
+                None => true,
+            }
+        });
+    }
+
+    fn from_json(value: &Value) -> Self {
+        let mut settings = Self::default();
+
+        if let Some(severity) = value.get("severity").and_then(|s| s.as_object()) {
+            for (code, level) in severity {
+                match level.as_str().and_then(SeverityLevel::from_name) {
+                    Some(level) => {
+                        settings.severity_overrides.insert(code.clone(), level);
+                    }
+                    None => log::warn!("Ignoring invalid severity {} for diagnostic code '{}'", level, code),
+                }
+            }
+        }
+
+        if let Some(scope) = value.get("unusedVariables") {
+            match scope.as_str().and_then(UnusedVariableScope::from_name) {
+                Some(scope) => settings.unused_variables = scope,
+                None => log::warn!("Ignoring invalid unused variables scope {}", scope),
+            }
+        }
+
+        if let Some(mode) = value.get("mode") {
+            match mode.as_str().and_then(ValidationMode::from_name) {
+                Some(mode) => settings.mode = mode,
+                None => log::warn!("Ignoring invalid validation mode {}", mode),
+            }
+        }
+
+        settings
+    }
+}
+
+/// All USS language server settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct UssSettings {
+    pub diagnostics: DiagnosticSettings,
+    /// Order used by the organize declarations code action
+    pub declaration_order: DeclarationOrder,
+    /// Log latency percentiles of requests, see [`crate::language::request_timing`]
+    pub request_timing: bool,
+    /// Whether documents can be formatted, only set by the project's [`crate::config::UnityCodeConfig`]
+    pub format_enabled: bool,
+    /// Whether layout snippets are offered in empty blocks, see [`crate::uss::snippet_data::BLOCK_SNIPPETS`]
+    pub block_snippets: bool,
+    /// Whether hover of a property lists the other rules that set it, see [`crate::uss::cascade`]
+    pub hover_cascade: bool,
+    /// What happens when text is pasted, see [`crate::uss::paste`]
+    pub paste_formatting: PasteFormatting,
+}
+
+impl Default for UssSettings {
+    fn default() -> Self {
+        Self {
+            diagnostics: DiagnosticSettings::default(),
+            declaration_order: DeclarationOrder::default(),
+            request_timing: false,
+            format_enabled: true,
+            block_snippets: true,
+            hover_cascade: true,
+            paste_formatting: PasteFormatting::default(),
+        }
+    }
+}
+
+impl UssSettings {
+    /// Read settings from JSON, invalid entries are ignored (with a warning in log)
+    pub fn from_json(value: &Value) -> Self {
+        // settings may be nested in a `uss` section (e.g. VS Code workspace configuration)
+        let value = value.get("uss").unwrap_or(value);
+
+        let diagnostics = value
+            .get("diagnostics")
+            .map(DiagnosticSettings::from_json)
+            .unwrap_or_default();
+
+        let declaration_order = match value.get("declarationOrder") {
+            Some(order) => order.as_str().and_then(DeclarationOrder::from_name).unwrap_or_else(|| {
+                log::warn!("Ignoring invalid declaration order {}", order);
+                DeclarationOrder::default()
+            }),
+            None => DeclarationOrder::default(),
+        };
+
+        let request_timing = match value.get("requestTiming") {
+            Some(enabled) => enabled.as_bool().unwrap_or_else(|| {
+                log::warn!("Ignoring invalid request timing setting {}", enabled);
+                false
+            }),
+            None => false,
+        };
+
+        let block_snippets = match value.get("blockSnippets") {
+            Some(enabled) => enabled.as_bool().unwrap_or_else(|| {
+                log::warn!("Ignoring invalid block snippets setting {}", enabled);
+                true
+            }),
+            None => true,
+        };
+
+        let hover_cascade = match value.get("hoverCascade") {
+            Some(enabled) => enabled.as_bool().unwrap_or_else(|| {
+                log::warn!("Ignoring invalid hover cascade setting {}", enabled);
+                true
+            }),
+            None => true,
+        };
+
+        let paste_formatting = match value.get("pasteFormatting") {
+            Some(mode) => mode.as_str().and_then(PasteFormatting::from_name).unwrap_or_else(|| {
+                log::warn!("Ignoring invalid paste formatting {}", mode);
+                PasteFormatting::default()
+            }),
+            None => PasteFormatting::default(),
+        };
+
+        Self {
+            diagnostics,
+            declaration_order,
+            request_timing,
+            block_snippets,
+            hover_cascade,
+            paste_formatting,
+            ..Self::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tower_lsp::lsp_types::Range;
+
+    fn diagnostic(code: &str, severity: DiagnosticSeverity) -> Diagnostic {
+        Diagnostic {
+            range: Range::default(),
+            severity: Some(severity),
+            code: Some(NumberOrString::String(code.to_string())),
+            source: Some("uss".to_string()),
+            message: String::new(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_from_json() {
+        let settings = UssSettings::from_json(&json!({
+            "uss": {
+                "diagnostics": {
+                    "severity": {
+                        "duplicate-property": "off",
+                        "missing-uss-extension": "Error",
+                        "unknown-property": "loud"
+                    },
+                    "unusedVariables": "importers",
+                    "mode": "Strict-Unity"
+                },
+                "declarationOrder": "Grouped",
+                "requestTiming": true,
+                "blockSnippets": false,
+                "hoverCascade": false,
+                "pasteFormatting": "automatic"
+            }
+        }));
+
+        let overrides = &settings.diagnostics.severity_overrides;
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(overrides["duplicate-property"], SeverityLevel::Off);
+        assert_eq!(overrides["missing-uss-extension"], SeverityLevel::Error);
+        assert_eq!(settings.diagnostics.unused_variables, UnusedVariableScope::Importers);
+        assert_eq!(settings.diagnostics.mode, ValidationMode::StrictUnity);
+        assert_eq!(settings.declaration_order, DeclarationOrder::Grouped);
+        assert!(settings.request_timing);
+        assert!(!settings.block_snippets);
+        assert!(!settings.hover_cascade);
+        assert_eq!(settings.paste_formatting, PasteFormatting::Automatic);
+
+        // not nested in a `uss` section
+        let settings = UssSettings::from_json(&json!({ "diagnostics": { "severity": { "a": "hint" } } }));
+        assert_eq!(settings.diagnostics.severity_for("a"), Some(SeverityLevel::Hint));
+        assert_eq!(settings.declaration_order, DeclarationOrder::Alphabetical);
+        assert_eq!(settings.diagnostics.unused_variables, UnusedVariableScope::Document);
+        assert_eq!(settings.diagnostics.mode, ValidationMode::Standard);
+        assert!(!settings.request_timing);
+        assert!(settings.block_snippets);
+        assert!(settings.hover_cascade);
+        assert_eq!(settings.paste_formatting, PasteFormatting::CodeAction);
+
+        assert_eq!(UssSettings::from_json(&json!(null)), UssSettings::default());
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut settings = DiagnosticSettings::default();
+        settings.severity_overrides.insert("duplicate-property".to_string(), SeverityLevel::Off);
+        settings.severity_overrides.insert("missing-uss-extension".to_string(), SeverityLevel::Error);
+
+        let mut diagnostics = vec![
+            diagnostic("duplicate-property", DiagnosticSeverity::WARNING),
+            diagnostic("missing-uss-extension", DiagnosticSeverity::WARNING),
+            diagnostic("unknown-property", DiagnosticSeverity::ERROR),
+        ];
+        settings.apply(&mut diagnostics);
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+        assert_eq!(diagnostics[1].code, Some(NumberOrString::String("unknown-property".to_string())));
+    }
+}
@@ -0,0 +1,172 @@
+//! USS Snippets
+//!
+//! Rule skeletons are offered as completions at the top level of a stylesheet, layout snippets (several
+//! declarations users often write together) inside a block that has no declarations yet. Custom cursor
+//! snippets are offered as values of `cursor`.
+
+/// A snippet completion
+pub struct SnippetInfo {
+    /// The word the snippet is matched against
+    pub prefix: &'static str,
+    /// What the snippet is shown as in the completion list
+    pub label: &'static str,
+    pub description: &'static str,
+    /// Snippet body in LSP snippet syntax, tabs are converted to the indentation of the client
+    pub body: &'static str,
+}
+
+/// Snippets offered where a new rule can start
+pub const RULE_SNIPPETS: &[SnippetInfo] = &[
+    SnippetInfo {
+        prefix: "class",
+        label: ".class-name { }",
+        description: "Rule with a class selector",
+        body: ".${1:class-name} {\n\t$0\n}",
+    },
+    SnippetInfo {
+        prefix: "id",
+        label: "#id { }",
+        description: "Rule with an ID selector",
+        body: "#${1:id} {\n\t$0\n}",
+    },
+    SnippetInfo {
+        prefix: "root",
+        label: ":root { --variable: value; }",
+        description: "Variables on the root element, available to all elements",
+        body: ":root {\n\t--${1:name}: ${2:value};$0\n}",
+    },
+    SnippetInfo {
+        prefix: "hover",
+        label: ".class-name:hover { }",
+        description: "Rule for an element under the cursor",
+        body: ".${1:class-name}:hover {\n\t$0\n}",
+    },
+    SnippetInfo {
+        prefix: "transition",
+        label: "transition block",
+        description: "Rule that animates a property, with a hover rule that changes it",
+        body: ".${1:class-name} {\n\ttransition-property: ${2:opacity};\n\ttransition-duration: ${3:0.3s};\n\ttransition-timing-function: ${4:ease-in-out};\n\ttransition-delay: ${5:0s};\n}\n\n.${1:class-name}:hover {\n\t${2:opacity}: ${6:0.5};\n}$0",
+    },
+];
+
+/// A snippet of declarations, for any element or for some types of elements
+pub struct BlockSnippet {
+    pub snippet: SnippetInfo,
+    /// Names of the element types the snippet is for, e.g. `Button`, any element if empty
+    ///
+    /// These snippets are only offered in rules whose selector has one of the types.
+    pub element_types: &'static [&'static str],
+}
+
+impl BlockSnippet {
+    /// Whether the snippet is offered in a rule with the element types of its selector
+    pub fn applies_to(&self, selector_types: &[&str]) -> bool {
+        self.element_types.is_empty() || self.element_types.iter().any(|t| selector_types.contains(t))
+    }
+
+    /// Names of the properties the snippet declares
+    pub fn properties(&self) -> impl Iterator<Item = &'static str> {
+        self.snippet.body.lines().filter_map(|line| line.split_once(':').map(|(name, _)| name.trim()))
+    }
+}
+
+/// Snippets offered inside a block that has no declarations
+pub const BLOCK_SNIPPETS: &[BlockSnippet] = &[
+    BlockSnippet {
+        snippet: SnippetInfo {
+            prefix: "absolute-fill",
+            label: "absolute-fill",
+            description: "Fill the parent, out of the layout of its siblings",
+            body: "position: absolute;\nleft: 0;\ntop: 0;\nright: 0;\nbottom: 0;$0",
+        },
+        element_types: &[],
+    },
+    BlockSnippet {
+        snippet: SnippetInfo {
+            prefix: "flex-row-center",
+            label: "flex-row-center",
+            description: "Lay out children in a row, centered on both axes",
+            body: "flex-direction: row;\njustify-content: ${1:center};\nalign-items: ${2:center};$0",
+        },
+        element_types: &[],
+    },
+    BlockSnippet {
+        snippet: SnippetInfo {
+            prefix: "flex-column-center",
+            label: "flex-column-center",
+            description: "Lay out children in a column, centered on both axes",
+            body: "flex-direction: column;\njustify-content: ${1:center};\nalign-items: ${2:center};$0",
+        },
+        element_types: &[],
+    },
+    BlockSnippet {
+        snippet: SnippetInfo {
+            prefix: "flex-grow-fill",
+            label: "flex-grow-fill",
+            description: "Take the remaining space of the parent, and let the content shrink",
+            body: "flex-grow: 1;\nflex-shrink: 1;\nflex-basis: 0;$0",
+        },
+        element_types: &[],
+    },
+    BlockSnippet {
+        snippet: SnippetInfo {
+            prefix: "safe-area-padding",
+            label: "safe-area-padding",
+            description: "Padding for the safe area of the screen, the insets come from Screen.safeArea (set from C#)",
+            body: "padding-top: ${1:0px};\npadding-right: ${2:0px};\npadding-bottom: ${3:0px};\npadding-left: ${4:0px};$0",
+        },
+        element_types: &[],
+    },
+    BlockSnippet {
+        snippet: SnippetInfo {
+            prefix: "text-ellipsis",
+            label: "text-ellipsis",
+            description: "Single line of text that ends with ... when it doesn't fit",
+            body: "white-space: nowrap;\noverflow: hidden;\ntext-overflow: ellipsis;\n-unity-text-overflow-position: ${1:end};$0",
+        },
+        element_types: &["Label", "Button", "TextElement"],
+    },
+    BlockSnippet {
+        snippet: SnippetInfo {
+            prefix: "button-reset",
+            label: "button-reset",
+            description: "Remove the default background, border and spacing of a button",
+            body: "background-color: rgba(0, 0, 0, 0);\nborder-width: 0;\nmargin: 0;\npadding: 0;$0",
+        },
+        element_types: &["Button"],
+    },
+    BlockSnippet {
+        snippet: SnippetInfo {
+            prefix: "background-fit",
+            label: "background-fit",
+            description: "Background image scaled to the element, keeping its aspect ratio",
+            body: "background-image: url(\"${1}\");\nbackground-size: ${2|contain,cover,100% 100%|};$0",
+        },
+        element_types: &[],
+    },
+    BlockSnippet {
+        snippet: SnippetInfo {
+            prefix: "scroll-view-fill",
+            label: "scroll-view-fill",
+            description: "Take the remaining height of the parent, so the scroll view scrolls instead of growing",
+            body: "flex-grow: 1;\nflex-shrink: 1;\nmin-height: 0;$0",
+        },
+        element_types: &["ScrollView", "ListView", "TreeView", "MultiColumnListView"],
+    },
+];
+
+/// Custom cursor values of `cursor`, a texture and the hotspot in pixels from its top left corner
+pub const CURSOR_SNIPPETS: &[SnippetInfo] = &[
+    SnippetInfo {
+        prefix: "url",
+        label: "url(\"…\") x y",
+        description: "Custom cursor from a texture path, with its hotspot",
+        body: "url(\"${1:path}\") ${2:0} ${3:0};$0",
+    },
+    SnippetInfo {
+        prefix: "resource",
+        label: "resource(\"…\") x y",
+        description: "Custom cursor from a texture in a Resources folder, with its hotspot",
+        body: "resource(\"${1:name}\") ${2:0} ${3:0};$0",
+    },
+];
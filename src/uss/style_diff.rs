@@ -0,0 +1,402 @@
+//! Style Sheet Diff
+//!
+//! Compares two versions of a style sheet by rules and declarations instead of by lines, for tools that
+//! apply style changes to a running editor without reloading the whole style sheet. The editor asks with
+//! the custom request [`STYLE_DIFF_METHOD`].
+//!
+//! Rules are paired by their selectors, written with any whitespace. Several rules with the same selectors
+//! are paired in the order they are written. Declarations are paired by property, of a property set more
+//! than once in a rule only the last declaration counts, like at runtime. Values are compared by their
+//! tokens, so a change of whitespace or comments is not a change.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Range, TextDocumentIdentifier};
+use tree_sitter::{Node, Tree};
+
+use crate::language::tree_utils::node_to_range;
+use crate::uss::constants::*;
+use crate::uss::rule_matching::{rule_declarations, MatchedDeclaration};
+use crate::uss::selector::rule_selector_texts;
+
+/// Custom request for the changes between two versions of a style sheet, takes [`StyleDiffParams`] and returns [`StyleDiff`]
+pub const STYLE_DIFF_METHOD: &str = "unityCode/styleDiff";
+
+/// Parameters of [`STYLE_DIFF_METHOD`], the new version is `text` or the content of `text_document`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StyleDiffParams {
+    /// Content of the old version
+    pub old_text: String,
+    /// Content of the new version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// The style sheet whose current content is the new version, with unsaved changes if it's open
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_document: Option<TextDocumentIdentifier>,
+}
+
+/// How a rule, declaration or import changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A declaration that was added, removed or got a different value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeclarationChange {
+    pub kind: ChangeKind,
+    pub property: String,
+    /// Value in the old version, `None` if the declaration was added
+    pub old_value: Option<String>,
+    /// Value in the new version, `None` if the declaration was removed
+    pub value: Option<String>,
+    /// Whether the declaration is `!important` in the version it's in, the new one if it's in both
+    pub important: bool,
+}
+
+/// An `@import` statement that was added or removed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportChange {
+    pub kind: ChangeKind,
+    /// Argument of the statement, e.g. `"common.uss"` or `url("common.uss")`
+    pub argument: String,
+}
+
+/// A rule that was added, removed or has changed declarations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleChange {
+    pub kind: ChangeKind,
+    /// Selectors of the rule, separated by `, `
+    pub selector: String,
+    /// Range of the rule in the new version, or in the old version if it was removed
+    pub range: Range,
+    /// All declarations of an added or removed rule, the changed ones of a changed rule
+    pub declarations: Vec<DeclarationChange>,
+}
+
+/// Changes between two versions of a style sheet
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StyleDiff {
+    pub imports: Vec<ImportChange>,
+    /// Changed rules, in the order of the new version, followed by removed rules in the order of the old version
+    pub rules: Vec<RuleChange>,
+    /// Whether rules in both versions are in a different order, which can change the cascade
+    pub reordered: bool,
+}
+
+impl StyleDiff {
+    pub fn is_empty(&self) -> bool {
+        self.imports.is_empty() && self.rules.is_empty() && !self.reordered
+    }
+}
+
+/// A rule of a style sheet with the values it is compared by
+struct Rule {
+    selector: String,
+    range: Range,
+    declarations: Vec<MatchedDeclaration>,
+}
+
+/// Changes from the style sheet `old` to `new`, each with its tree and content
+pub fn diff_style_sheets(old_tree: &Tree, old_content: &str, new_tree: &Tree, new_content: &str) -> StyleDiff {
+    let old_rules = collect_rules(old_tree.root_node(), old_content);
+    let new_rules = collect_rules(new_tree.root_node(), new_content);
+
+    // pair the nth rule with some selectors in the old version with the nth one in the new version
+    let mut old_by_selector: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, rule) in old_rules.iter().enumerate().rev() {
+        old_by_selector.entry(rule.selector.as_str()).or_default().push(index);
+    }
+    let mut paired = vec![false; old_rules.len()];
+    let mut old_order = Vec::new();
+    let mut rules = Vec::new();
+    for rule in &new_rules {
+        let Some(old_index) = old_by_selector.get_mut(rule.selector.as_str()).and_then(Vec::pop) else {
+            rules.push(rule_change(ChangeKind::Added, rule));
+            continue;
+        };
+        paired[old_index] = true;
+        old_order.push(old_index);
+
+        let declarations = diff_declarations(&old_rules[old_index].declarations, &rule.declarations);
+        if !declarations.is_empty() {
+            rules.push(RuleChange {
+                kind: ChangeKind::Changed,
+                selector: rule.selector.clone(),
+                range: rule.range,
+                declarations,
+            });
+        }
+    }
+    for (rule, _) in old_rules.iter().zip(&paired).filter(|(_, paired)| !**paired) {
+        rules.push(rule_change(ChangeKind::Removed, rule));
+    }
+
+    let old_imports = collect_imports(old_tree.root_node(), old_content);
+    let new_imports = collect_imports(new_tree.root_node(), new_content);
+    let imports = new_imports
+        .iter()
+        .filter(|import| !old_imports.contains(import))
+        .map(|import| ImportChange { kind: ChangeKind::Added, argument: import.clone() })
+        .chain(
+            old_imports
+                .iter()
+                .filter(|import| !new_imports.contains(import))
+                .map(|import| ImportChange { kind: ChangeKind::Removed, argument: import.clone() }),
+        )
+        .collect();
+
+    StyleDiff {
+        imports,
+        rules,
+        reordered: old_order.windows(2).any(|pair| pair[0] > pair[1]),
+    }
+}
+
+/// Changes of the declarations of a rule, in the order of the new version followed by removed ones
+fn diff_declarations(old: &[MatchedDeclaration], new: &[MatchedDeclaration]) -> Vec<DeclarationChange> {
+    let old = effective_declarations(old);
+    let new = effective_declarations(new);
+
+    let mut changes = Vec::new();
+    for declaration in &new {
+        match old.iter().find(|other| other.property == declaration.property) {
+            None => changes.push(declaration_change(ChangeKind::Added, declaration)),
+            Some(other) if normalize_value(&other.value) != normalize_value(&declaration.value) || other.important != declaration.important => {
+                changes.push(DeclarationChange {
+                    kind: ChangeKind::Changed,
+                    property: declaration.property.clone(),
+                    old_value: Some(other.value.clone()),
+                    value: Some(declaration.value.clone()),
+                    important: declaration.important,
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    for declaration in old.iter().filter(|declaration| !new.iter().any(|other| other.property == declaration.property)) {
+        changes.push(declaration_change(ChangeKind::Removed, declaration));
+    }
+    changes
+}
+
+/// The last declaration of every property, in the order of the properties' first declarations
+fn effective_declarations(declarations: &[MatchedDeclaration]) -> Vec<&MatchedDeclaration> {
+    let mut effective: Vec<&MatchedDeclaration> = Vec::new();
+    for declaration in declarations {
+        match effective.iter_mut().find(|other| other.property == declaration.property) {
+            Some(other) => *other = declaration,
+            None => effective.push(declaration),
+        }
+    }
+    effective
+}
+
+fn rule_change(kind: ChangeKind, rule: &Rule) -> RuleChange {
+    RuleChange {
+        kind,
+        selector: rule.selector.clone(),
+        range: rule.range,
+        declarations: effective_declarations(&rule.declarations)
+            .into_iter()
+            .map(|declaration| declaration_change(kind, declaration))
+            .collect(),
+    }
+}
+
+fn declaration_change(kind: ChangeKind, declaration: &MatchedDeclaration) -> DeclarationChange {
+    let value = Some(declaration.value.clone());
+    DeclarationChange {
+        kind,
+        property: declaration.property.clone(),
+        old_value: if kind == ChangeKind::Added { None } else { value.clone() },
+        value: if kind == ChangeKind::Removed { None } else { value },
+        important: declaration.important,
+    }
+}
+
+/// Top level rules with their selectors written the same way
+fn collect_rules(root: Node, content: &str) -> Vec<Rule> {
+    let mut cursor = root.walk();
+    root.children(&mut cursor)
+        .filter(|node| node.kind() == NODE_RULE_SET)
+        .map(|rule_set| Rule {
+            selector: rule_selector_texts(rule_set, content)
+                .into_iter()
+                .map(normalize_value)
+                .collect::<Vec<_>>()
+                .join(", "),
+            range: node_to_range(rule_set, content),
+            declarations: rule_declarations(rule_set, content),
+        })
+        .collect()
+}
+
+/// Arguments of the `@import` statements, e.g. `"common.uss"` or `url("common.uss")`
+fn collect_imports(root: Node, content: &str) -> Vec<String> {
+    let mut cursor = root.walk();
+    root.children(&mut cursor)
+        .filter(|node| node.kind() == NODE_IMPORT_STATEMENT)
+        .filter_map(|node| node.child(1)?.utf8_text(content.as_bytes()).ok())
+        .map(normalize_value)
+        .collect()
+}
+
+/// Text with comments left out and whitespace between tokens collapsed to a single space, strings are kept as they are
+///
+/// Commas are followed by a space, and there is none after `(` or before `,` and `)`.
+pub fn normalize_value(text: &str) -> String {
+    let mut normalized = String::new();
+    let mut chars = text.chars().peekable();
+    let mut pending_space = false;
+    while let Some(c) = chars.next() {
+        if c == '/' && chars.peek() == Some(&'*') {
+            chars.next();
+            let mut previous = ' ';
+            for c in chars.by_ref() {
+                if previous == '*' && c == '/' {
+                    break;
+                }
+                previous = c;
+            }
+            pending_space = true;
+            continue;
+        }
+        if c.is_whitespace() {
+            pending_space = true;
+            continue;
+        }
+
+        // `rgb(0,0,0)` is the same as `rgb( 0, 0, 0 )`
+        if pending_space && !normalized.is_empty() && !matches!(c, ',' | ')') && !normalized.ends_with(['(', ' ']) {
+            normalized.push(' ');
+        }
+        pending_space = false;
+        normalized.push(c);
+        if c == ',' {
+            normalized.push(' ');
+        }
+        if c == '"' || c == '\'' {
+            while let Some(next) = chars.next() {
+                normalized.push(next);
+                if next == '\\' {
+                    normalized.extend(chars.next());
+                } else if next == c {
+                    break;
+                }
+            }
+        }
+    }
+    normalized.truncate(normalized.trim_end().len());
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uss::parser::UssParser;
+
+    fn diff(old: &str, new: &str) -> StyleDiff {
+        let mut parser = UssParser::new().unwrap();
+        let old_tree = parser.parse(old, None).unwrap();
+        let new_tree = parser.parse(new, None).unwrap();
+        diff_style_sheets(&old_tree, old, &new_tree, new)
+    }
+
+    #[test]
+    fn test_diff_style_sheets() {
+        let old = r#"@import "common.uss";
+.button, .label { color: red; margin: 4px; }
+#title { font-size: 12px; }
+.old { color: blue; }
+"#;
+        let new = r#"@import "theme.uss";
+.button,
+.label {
+    color: rgb(0, 0, 0);
+    margin: 4px /* unchanged */;
+    padding: 2px;
+}
+#title { font-size: 12px; font-size: 14px !important; }
+.new { color: green; }
+"#;
+        let diff = diff(old, new);
+
+        assert_eq!(
+            diff.imports,
+            vec![
+                ImportChange { kind: ChangeKind::Added, argument: "\"theme.uss\"".to_string() },
+                ImportChange { kind: ChangeKind::Removed, argument: "\"common.uss\"".to_string() },
+            ]
+        );
+        let rules: Vec<(ChangeKind, &str, usize)> =
+            diff.rules.iter().map(|rule| (rule.kind, rule.selector.as_str(), rule.declarations.len())).collect();
+        assert_eq!(
+            rules,
+            vec![
+                (ChangeKind::Changed, ".button, .label", 2),
+                (ChangeKind::Changed, "#title", 1),
+                (ChangeKind::Added, ".new", 1),
+                (ChangeKind::Removed, ".old", 1),
+            ]
+        );
+        assert_eq!(
+            diff.rules[0].declarations,
+            vec![
+                DeclarationChange {
+                    kind: ChangeKind::Changed,
+                    property: "color".to_string(),
+                    old_value: Some("red".to_string()),
+                    value: Some("rgb(0, 0, 0)".to_string()),
+                    important: false,
+                },
+                DeclarationChange {
+                    kind: ChangeKind::Added,
+                    property: "padding".to_string(),
+                    old_value: None,
+                    value: Some("2px".to_string()),
+                    important: false,
+                },
+            ]
+        );
+        assert_eq!(diff.rules[0].range.start.line, 1);
+        assert_eq!(diff.rules[1].declarations[0].value.as_deref(), Some("14px"));
+        assert!(diff.rules[1].declarations[0].important);
+        assert_eq!(diff.rules[3].range.start.line, 3);
+        assert!(!diff.reordered);
+    }
+
+    #[test]
+    fn test_same_selectors_and_order() {
+        let old = ".a { color: red; }\n.b { color: red; }\n.a { color: blue; }\n";
+
+        // the same rules with different whitespace
+        assert!(diff(old, ".a{color:red}\n.b {\n  color: red;\n}\n.a { color:  blue }\n").is_empty());
+
+        // the second `.a` rule is paired with the second one
+        let changed = diff(old, ".a { color: red; }\n.b { color: red; }\n.a { color: green; }\n");
+        assert_eq!(changed.rules.len(), 1);
+        assert_eq!(changed.rules[0].range.start.line, 2);
+
+        let moved = diff(old, ".b { color: red; }\n.a { color: red; }\n.a { color: blue; }\n");
+        assert!(moved.rules.is_empty());
+        assert!(moved.reordered);
+    }
+
+    #[test]
+    fn test_normalize_value() {
+        assert_eq!(normalize_value("  1px\n  2px /* top */ 3px "), "1px 2px 3px");
+        assert_eq!(normalize_value("url(\"a  b.png\")"), "url(\"a  b.png\")");
+        assert_eq!(normalize_value("rgb( 0,0 , 0 )"), "rgb(0, 0, 0)");
+        assert_eq!(normalize_value("\"a \\\" b\"  c"), "\"a \\\" b\" c");
+    }
+}
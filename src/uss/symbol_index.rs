@@ -0,0 +1,319 @@
+//! USS Symbol Index
+//!
+//! Class selectors, ID selectors and variables of the open documents, for features that look for a
+//! symbol across documents (e.g. find references).
+//!
+//! Every document keeps a [`DocumentSymbols`] table with the symbols of each top level node (a rule or
+//! an import statement), stored relative to the start of the node. When the document is edited, the
+//! nodes before the edit are kept, the ones after it are only moved, and only the nodes that changed
+//! are extracted again, so typing in a large style sheet extracts a single rule.
+//!
+//! The tables of the documents are merged into a [`SymbolIndex`]. An update replaces the whole
+//! [`SymbolIndexSnapshot`] with a new generation instead of modifying it, so a query that holds a
+//! snapshot never sees a partially updated index.
+
+use std::collections::HashMap;
+use std::ops::Range as ByteRange;
+use std::sync::Arc;
+
+use tower_lsp::lsp_types::{Location, Position, Range, Url};
+use tree_sitter::{InputEdit, Node, Tree};
+
+use crate::language::cancellation::{CancellationToken, Cancelled};
+use crate::uss::constants::*;
+use crate::uss::function_node::FunctionNode;
+use crate::uss::uss_utils::unescape_identifier;
+
+/// Kind of a symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    /// A class selector, e.g. `.button`
+    Class,
+    /// An ID selector, e.g. `#title`
+    Id,
+    /// A variable, declared with `--name: value` and referenced with `var(--name)`
+    Variable,
+}
+
+/// A symbol in a document, in bytes relative to the start of its top level node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolOccurrence {
+    pub kind: SymbolKind,
+    /// Name without the `.` or `#` of selectors, with the `--` prefix of variables, with escapes resolved
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+    /// Whether this declares a variable, selectors are not declarations (classes are declared in UXML)
+    pub is_declaration: bool,
+}
+
+/// The symbols of a top level node
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TopLevelEntry {
+    start: usize,
+    end: usize,
+    symbols: Vec<SymbolOccurrence>,
+    /// An edit touched the node, its symbols must be extracted again
+    dirty: bool,
+}
+
+/// The symbols of a document, updated incrementally as it is edited
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocumentSymbols {
+    entries: Vec<TopLevelEntry>,
+    /// Number of top level nodes extracted by the last update
+    last_extracted: usize,
+}
+
+impl DocumentSymbols {
+    /// Extract the symbols of every top level node of a tree
+    pub fn from_tree(tree: &Tree, content: &str) -> Self {
+        let mut symbols = Self::default();
+        symbols.update(tree, content, &[]);
+        symbols
+    }
+
+    /// Move the symbols after an edit, and mark the nodes it touches to be extracted again
+    ///
+    /// Call [`update`](Self::update) with the tree parsed after the edit.
+    pub fn apply_edit(&mut self, edit: &InputEdit) {
+        for entry in &mut self.entries {
+            if entry.end < edit.start_byte {
+                continue;
+            }
+            if entry.start > edit.old_end_byte {
+                entry.start = entry.start - edit.old_end_byte + edit.new_end_byte;
+                entry.end = entry.end - edit.old_end_byte + edit.new_end_byte;
+            } else {
+                entry.dirty = true;
+            }
+        }
+    }
+
+    /// Update the symbols from the tree parsed after [`apply_edit`](Self::apply_edit)
+    ///
+    /// `changed_ranges` are the byte ranges where the syntax changed (from `Tree::changed_ranges`), nodes
+    /// that overlap them are extracted again even if no edit touched them, e.g. rules that became part
+    /// of a comment.
+    pub fn update(&mut self, tree: &Tree, content: &str, changed_ranges: &[ByteRange<usize>]) {
+        let mut previous: HashMap<(usize, usize), TopLevelEntry> = std::mem::take(&mut self.entries)
+            .into_iter()
+            .filter(|entry| !entry.dirty)
+            .map(|entry| ((entry.start, entry.end), entry))
+            .collect();
+        self.last_extracted = 0;
+
+        let root = tree.root_node();
+        let mut cursor = root.walk();
+        for node in root.children(&mut cursor) {
+            let (start, end) = (node.start_byte(), node.end_byte());
+            let is_changed = changed_ranges.iter().any(|range| range.start < end && range.end > start);
+            let entry = match previous.remove(&(start, end)) {
+                Some(entry) if !is_changed => entry,
+                _ => {
+                    self.last_extracted += 1;
+                    let mut symbols = Vec::new();
+                    collect_symbols(node, content, start, &mut symbols);
+                    TopLevelEntry {
+                        start,
+                        end,
+                        symbols,
+                        dirty: false,
+                    }
+                }
+            };
+            self.entries.push(entry);
+        }
+    }
+
+    /// Number of top level nodes whose symbols were extracted by the last update
+    pub fn last_extracted(&self) -> usize {
+        self.last_extracted
+    }
+
+    /// The symbols with their byte range in the document
+    pub fn occurrences(&self) -> impl Iterator<Item = (ByteRange<usize>, &SymbolOccurrence)> {
+        self.entries.iter().flat_map(|entry| {
+            entry
+                .symbols
+                .iter()
+                .map(move |symbol| (entry.start + symbol.start..entry.start + symbol.end, symbol))
+        })
+    }
+}
+
+fn collect_symbols(node: Node, content: &str, offset: usize, symbols: &mut Vec<SymbolOccurrence>) {
+    let mut push = |kind: SymbolKind, name_node: Node, is_declaration: bool| {
+        if let Ok(name) = name_node.utf8_text(content.as_bytes()) {
+            symbols.push(SymbolOccurrence {
+                kind,
+                name: unescape_identifier(name),
+                start: name_node.start_byte() - offset,
+                end: name_node.end_byte() - offset,
+                is_declaration,
+            });
+        }
+    };
+
+    match node.kind() {
+        NODE_CLASS_SELECTOR => {
+            if let Some(class_name) = named_child(node, NODE_CLASS_NAME) {
+                push(SymbolKind::Class, class_name, false);
+            }
+        }
+        NODE_ID_SELECTOR => {
+            if let Some(id_name) = named_child(node, NODE_ID_NAME) {
+                push(SymbolKind::Id, id_name, false);
+            }
+        }
+        NODE_DECLARATION => {
+            let property = node.child(0).filter(|n| n.kind() == NODE_PROPERTY_NAME);
+            if let Some(property) = property.filter(|n| n.utf8_text(content.as_bytes()).is_ok_and(|t| t.starts_with("--"))) {
+                push(SymbolKind::Variable, property, true);
+            }
+        }
+        _ => {
+            if let Some(function) = FunctionNode::from_node(node, content, None) {
+                if function.function_name == "var" {
+                    let argument = function.argument_nodes.first().copied();
+                    if let Some(argument) = argument.filter(|n| n.utf8_text(content.as_bytes()).is_ok_and(|t| t.starts_with("--"))) {
+                        push(SymbolKind::Variable, argument, false);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_symbols(child, content, offset, symbols);
+    }
+}
+
+fn named_child<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+    let mut cursor = node.walk();
+    let child = node.children(&mut cursor).find(|child| child.kind() == kind);
+    child
+}
+
+/// A symbol of an indexed document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedSymbol {
+    pub kind: SymbolKind,
+    pub name: String,
+    pub range: Range,
+    pub is_declaration: bool,
+}
+
+/// The symbols of a document at a version
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexedDocument {
+    pub version: i32,
+    pub symbols: Vec<IndexedSymbol>,
+}
+
+impl IndexedDocument {
+    /// The symbol at a position, if any
+    pub fn symbol_at(&self, position: Position) -> Option<&IndexedSymbol> {
+        self.symbols
+            .iter()
+            .find(|symbol| symbol.range.start <= position && position <= symbol.range.end)
+    }
+}
+
+/// The indexed documents at a generation, never modified once created
+#[derive(Debug, Default)]
+pub struct SymbolIndexSnapshot {
+    generation: u64,
+    documents: HashMap<Url, Arc<IndexedDocument>>,
+}
+
+impl SymbolIndexSnapshot {
+    /// Incremented by every update of the index
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn document(&self, uri: &Url) -> Option<&IndexedDocument> {
+        self.documents.get(uri).map(|document| document.as_ref())
+    }
+
+    /// Locations of a symbol in all documents, sorted by document and position
+    ///
+    /// The token is checked before every document, so an abandoned search stops early.
+    pub fn find(
+        &self,
+        kind: SymbolKind,
+        name: &str,
+        include_declarations: bool,
+        token: &CancellationToken,
+    ) -> Result<Vec<Location>, Cancelled> {
+        let mut locations = Vec::new();
+        for (uri, document) in &self.documents {
+            token.check()?;
+            locations.extend(
+                document
+                    .symbols
+                    .iter()
+                    .filter(|s| s.kind == kind && s.name == name && (include_declarations || !s.is_declaration))
+                    .map(|s| Location::new(uri.clone(), s.range)),
+            );
+        }
+        locations.sort_by(|a, b| {
+            a.uri
+                .as_str()
+                .cmp(b.uri.as_str())
+                .then(a.range.start.cmp(&b.range.start))
+        });
+        Ok(locations)
+    }
+}
+
+/// Symbols of all open documents
+#[derive(Debug, Default)]
+pub struct SymbolIndex {
+    current: Arc<SymbolIndexSnapshot>,
+}
+
+impl SymbolIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The current snapshot, it stays the same while the index is updated
+    pub fn snapshot(&self) -> Arc<SymbolIndexSnapshot> {
+        self.current.clone()
+    }
+
+    /// Replace the symbols of a document
+    pub fn update_document(&mut self, uri: Url, document: IndexedDocument) {
+        self.publish(|documents| {
+            documents.insert(uri, Arc::new(document));
+        });
+    }
+
+    /// Remove a document, e.g. when it is closed
+    pub fn remove_document(&mut self, uri: &Url) {
+        if self.current.documents.contains_key(uri) {
+            self.publish(|documents| {
+                documents.remove(uri);
+            });
+        }
+    }
+
+    /// Publish a new snapshot with the documents of the current one changed by `change`
+    ///
+    /// The documents are shared between snapshots, only the map is copied.
+    fn publish(&mut self, change: impl FnOnce(&mut HashMap<Url, Arc<IndexedDocument>>)) {
+        let mut documents = self.current.documents.clone();
+        change(&mut documents);
+        self.current = Arc::new(SymbolIndexSnapshot {
+            generation: self.current.generation + 1,
+            documents,
+        });
+    }
+}
+
+#[cfg(test)]
+#[path = "symbol_index_tests.rs"]
+mod symbol_index_tests;
@@ -0,0 +1,131 @@
+use tower_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent, Url};
+
+use crate::language::cancellation::{CancellationToken, Cancelled};
+use crate::uss::definitions::UssDefinitions;
+use crate::uss::document::UssDocument;
+use crate::uss::parser::UssParser;
+use crate::uss::symbol_index::*;
+use crate::uss::uss_utils::unescape_identifier;
+
+fn names(symbols: &DocumentSymbols, content: &str) -> Vec<(SymbolKind, String, bool)> {
+    symbols
+        .occurrences()
+        .map(|(range, symbol)| {
+            assert_eq!(unescape_identifier(&content[range]), symbol.name);
+            (symbol.kind, symbol.name.clone(), symbol.is_declaration)
+        })
+        .collect()
+}
+
+fn edit(document: &mut UssDocument, parser: &mut UssParser, range: Range, text: &str) {
+    let change = TextDocumentContentChangeEvent {
+        range: Some(range),
+        range_length: None,
+        text: text.to_string(),
+    };
+    document.apply_changes(vec![change], 2, parser);
+}
+
+#[test]
+fn test_extract_symbols() {
+    let content = ":root { --main: red; }\n.button, #title .label:hover { color: var(--main); }\n@import url(\"a.uss\");\n";
+    let tree = UssParser::new().unwrap().parse(content, None).unwrap();
+    let symbols = DocumentSymbols::from_tree(&tree, content);
+
+    assert_eq!(
+        names(&symbols, content),
+        vec![
+            (SymbolKind::Variable, "--main".to_string(), true),
+            (SymbolKind::Class, "button".to_string(), false),
+            (SymbolKind::Id, "title".to_string(), false),
+            (SymbolKind::Class, "label".to_string(), false),
+            (SymbolKind::Variable, "--main".to_string(), false),
+        ]
+    );
+    assert_eq!(symbols.last_extracted(), 3);
+}
+
+#[test]
+fn test_incremental_update() {
+    let mut parser = UssParser::new().unwrap();
+    let uri = Url::parse("file:///project/Assets/a.uss").unwrap();
+    let content = ".a { color: red; }\n.b { color: var(--c); }\n.d { --c: blue; }\n";
+    let mut document = UssDocument::new(uri, content.to_string(), 1, UssDefinitions::shared());
+    document.mark_opened(1);
+    document.parse(&mut parser);
+
+    // rename .b to .bee, only that rule is extracted again and the rule after it moves
+    edit(&mut document, &mut parser, Range::new(Position::new(1, 1), Position::new(1, 2)), "bee");
+    assert_eq!(document.symbols().last_extracted(), 1);
+    let full = DocumentSymbols::from_tree(document.tree().unwrap(), document.content());
+    assert_eq!(names(document.symbols(), document.content()), names(&full, document.content()));
+    assert!(names(document.symbols(), document.content()).contains(&(SymbolKind::Class, "bee".to_string(), false)));
+
+    // an unclosed comment changes how the rules after it are parsed
+    edit(&mut document, &mut parser, Range::new(Position::new(1, 0), Position::new(1, 0)), "/*");
+    let full = DocumentSymbols::from_tree(document.tree().unwrap(), document.content());
+    assert_eq!(names(document.symbols(), document.content()), names(&full, document.content()));
+
+    let indexed = document.indexed_symbols();
+    assert_eq!(indexed.version, 2);
+    assert_eq!(indexed.symbols[0].range, Range::new(Position::new(0, 1), Position::new(0, 2)));
+}
+
+#[test]
+fn test_symbol_index_snapshots() {
+    let mut parser = UssParser::new().unwrap();
+    let mut open = |path: &str, content: &str| {
+        let uri = Url::parse(path).unwrap();
+        let mut document = UssDocument::new(uri.clone(), content.to_string(), 1, UssDefinitions::shared());
+        document.parse(&mut parser);
+        (uri, document.indexed_symbols())
+    };
+    let (a, a_symbols) = open("file:///project/Assets/a.uss", ":root { --main: red; }\n.title { color: var(--main); }");
+    let (b, b_symbols) = open("file:///project/Assets/b.uss", ".title { border-color: var(--main); }");
+
+    let token = CancellationToken::new();
+    let mut index = SymbolIndex::new();
+    index.update_document(a.clone(), a_symbols);
+    let before = index.snapshot();
+    index.update_document(b.clone(), b_symbols);
+    let after = index.snapshot();
+
+    // a snapshot doesn't change when the index is updated
+    assert_eq!(after.generation(), before.generation() + 1);
+    assert_eq!(before.find(SymbolKind::Class, "title", true, &token).unwrap().len(), 1);
+    let titles = after.find(SymbolKind::Class, "title", true, &token).unwrap();
+    assert_eq!(titles.iter().map(|l| &l.uri).collect::<Vec<_>>(), vec![&a, &b]);
+
+    let symbol = after.document(&a).unwrap().symbol_at(Position::new(1, 23)).unwrap();
+    assert_eq!(symbol.name, "--main");
+    assert_eq!(after.find(SymbolKind::Variable, "--main", true, &token).unwrap().len(), 3);
+    assert_eq!(after.find(SymbolKind::Variable, "--main", false, &token).unwrap().len(), 2);
+
+    index.remove_document(&b);
+    assert_eq!(index.snapshot().find(SymbolKind::Class, "title", true, &token).unwrap().len(), 1);
+    assert_eq!(after.find(SymbolKind::Class, "title", true, &token).unwrap().len(), 2);
+    // removing a document that isn't indexed doesn't publish a generation
+    let generation = index.snapshot().generation();
+    index.remove_document(&b);
+    assert_eq!(index.snapshot().generation(), generation);
+
+    // an abandoned search stops
+    token.cancel();
+    assert_eq!(after.find(SymbolKind::Class, "title", true, &token), Err(Cancelled));
+}
+
+#[test]
+fn test_escaped_class_names() {
+    let content = ".hover\\:red { color: red; }\n.hover\\3A red, .plain { color: blue; }\n";
+    let tree = UssParser::new().unwrap().parse(content, None).unwrap();
+    let symbols = DocumentSymbols::from_tree(&tree, content);
+
+    assert_eq!(
+        names(&symbols, content),
+        vec![
+            (SymbolKind::Class, "hover:red".to_string(), false),
+            (SymbolKind::Class, "hover:red".to_string(), false),
+            (SymbolKind::Class, "plain".to_string(), false),
+        ]
+    );
+}
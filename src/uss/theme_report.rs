@@ -0,0 +1,424 @@
+//! Theme Variable Report
+//!
+//! Unity themes (TSS files) import the default theme and style sheets, and override `:root` variables
+//! to change how everything looks. This report lists every variable of a set of style sheets: where it
+//! is defined, and for each theme which value wins and which definitions it overrides.
+//!
+//! The value that wins in a theme is found by following its imports depth first, imports before the
+//! importing file and in the order they are written, the last `:root` definition wins.
+//! Definitions under other selectors are listed but don't take part, they only apply to some elements.
+//!
+//! The report is exported by the `theme-report` subcommand and shown in hover of variables.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Position, Range};
+use tree_sitter::{Node, Tree};
+use url::Url;
+
+use crate::language::asset_url::{create_project_url, project_url_to_path, project_url_to_relative_path};
+use crate::language::project_scan::ScanTimer;
+use crate::language::tree_utils::{find_node_at_position, node_to_range};
+use crate::uss::constants::*;
+use crate::uss::import_graph::{find_theme_style_sheets, UssImportGraph};
+use crate::uss::parser::UssParser;
+use crate::uss::selector::rule_selector_texts;
+
+/// Selector of global variables
+const ROOT_SELECTOR: &str = ":root";
+
+/// A declaration of a variable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariableDefinition {
+    /// Project URL of the style sheet
+    pub file: Url,
+    pub range: Range,
+    /// Selectors of the rule that declares the variable
+    pub selector: String,
+    pub value: String,
+}
+
+/// Value of a variable in a theme
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThemeValue {
+    /// Project URL of the theme
+    pub theme: Url,
+    /// The value that wins
+    pub value: String,
+    /// Style sheet of the definition that wins
+    pub defined_in: Url,
+    /// Style sheets of the definitions it overrides, in cascade order
+    pub overrides: Vec<Url>,
+}
+
+/// Everything known about one variable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariableReport {
+    /// Name with the `--` prefix
+    pub name: String,
+    pub definitions: Vec<VariableDefinition>,
+    /// Value per theme, only themes that define the variable in `:root`
+    pub themes: Vec<ThemeValue>,
+}
+
+/// Variables of a set of style sheets, sorted by name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThemeReport {
+    /// Project URLs of the themes
+    pub themes: Vec<Url>,
+    pub variables: Vec<VariableReport>,
+}
+
+/// Variables and imports of one style sheet
+#[derive(Debug, Default)]
+struct StyleSheetVariables {
+    imports: Vec<Url>,
+    /// Definitions by variable name
+    definitions: Vec<(String, VariableDefinition)>,
+}
+
+impl ThemeReport {
+    /// Build the report for style sheets of the project, TSS files among them are the themes
+    ///
+    /// Files imported by them are read too.
+    pub fn build(project_root: &Path, files: &[PathBuf]) -> Self {
+        let root = fs::canonicalize(project_root).unwrap_or_else(|_| project_root.to_path_buf());
+        let mut builder = ReportBuilder::new(&root);
+
+        let urls: Vec<Url> = files
+            .iter()
+            .filter_map(|path| {
+                let path = fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+                create_project_url(&path, &root).ok()
+            })
+            .collect();
+        for url in &urls {
+            builder.load(url);
+        }
+
+        let themes: Vec<Url> = urls.into_iter().filter(|url| url.path().ends_with(".tss")).collect();
+        builder.report(themes)
+    }
+
+    /// Build the report for all themes of the project
+    pub fn for_project(project_root: &Path) -> Self {
+        Self::build(project_root, &find_theme_style_sheets(project_root))
+    }
+
+    /// Report of a variable, name with the `--` prefix
+    pub fn variable(&self, name: &str) -> Option<&VariableReport> {
+        self.variables
+            .binary_search_by(|variable| variable.name.as_str().cmp(name))
+            .ok()
+            .map(|index| &self.variables[index])
+    }
+
+    /// Markdown with the value of a variable in each theme, `None` if no theme defines it
+    pub fn hover_section(&self, name: &str) -> Option<String> {
+        let variable = self.variable(name)?;
+        if variable.themes.is_empty() {
+            return None;
+        }
+
+        let mut content = String::from("**Themes:**\n");
+        for theme in &variable.themes {
+            content.push_str(&format!("- {}: `{}`", display_url(&theme.theme), theme.value));
+            if theme.defined_in != theme.theme {
+                content.push_str(&format!(" (from {})", display_url(&theme.defined_in)));
+            }
+            content.push('\n');
+        }
+        Some(content)
+    }
+}
+
+struct ReportBuilder<'a> {
+    root: &'a Path,
+    parser: Option<UssParser>,
+    /// Used to extract imports
+    import_graph: UssImportGraph,
+    style_sheets: HashMap<Url, StyleSheetVariables>,
+}
+
+impl<'a> ReportBuilder<'a> {
+    fn new(root: &'a Path) -> Self {
+        Self {
+            root,
+            parser: UssParser::new().ok(),
+            import_graph: UssImportGraph::new(root.to_path_buf()),
+            style_sheets: HashMap::new(),
+        }
+    }
+
+    /// Read a style sheet and the style sheets it imports, unless they were read already
+    fn load(&mut self, url: &Url) {
+        if self.style_sheets.contains_key(url) {
+            return;
+        }
+        // inserted first, so import cycles end here
+        self.style_sheets.insert(url.clone(), StyleSheetVariables::default());
+
+        let Some(content) = project_url_to_path(self.root, url).and_then(|path| fs::read_to_string(path).ok()) else {
+            return;
+        };
+        let Some(tree) = self.parser.as_mut().and_then(|parser| parser.parse(&content, None)) else {
+            return;
+        };
+
+        let variables = StyleSheetVariables {
+            imports: self
+                .import_graph
+                .extract_imports(&tree, &content, url)
+                .into_iter()
+                .map(|edge| edge.url)
+                .filter(|url| url.scheme() == PROJECT_SCHEME)
+                .collect(),
+            definitions: extract_definitions(&tree, &content, url),
+        };
+        let imports = variables.imports.clone();
+        self.style_sheets.insert(url.clone(), variables);
+
+        for import in &imports {
+            self.load(import);
+        }
+    }
+
+    /// `:root` definitions that apply in a theme, in cascade order
+    fn cascade<'s>(&'s self, url: &Url, visited: &mut HashSet<Url>, cascade: &mut Vec<&'s (String, VariableDefinition)>) {
+        if !visited.insert(url.clone()) {
+            return;
+        }
+        let Some(style_sheet) = self.style_sheets.get(url) else {
+            return;
+        };
+
+        for import in &style_sheet.imports {
+            self.cascade(import, visited, cascade);
+        }
+        cascade.extend(style_sheet.definitions.iter().filter(|(_, d)| is_root_selector(&d.selector)));
+    }
+
+    fn report(&self, themes: Vec<Url>) -> ThemeReport {
+        let mut variables: BTreeMap<String, VariableReport> = BTreeMap::new();
+
+        let mut urls: Vec<&Url> = self.style_sheets.keys().collect();
+        urls.sort();
+        for url in urls {
+            for (name, definition) in &self.style_sheets[url].definitions {
+                variables
+                    .entry(name.clone())
+                    .or_insert_with(|| VariableReport {
+                        name: name.clone(),
+                        definitions: Vec::new(),
+                        themes: Vec::new(),
+                    })
+                    .definitions
+                    .push(definition.clone());
+            }
+        }
+
+        for theme in &themes {
+            let mut cascade = Vec::new();
+            self.cascade(theme, &mut HashSet::new(), &mut cascade);
+
+            let mut by_name: BTreeMap<&str, Vec<&VariableDefinition>> = BTreeMap::new();
+            for (name, definition) in cascade {
+                by_name.entry(name.as_str()).or_default().push(definition);
+            }
+
+            for (name, definitions) in by_name {
+                let Some((winner, overridden)) = definitions.split_last() else {
+                    continue;
+                };
+                if let Some(variable) = variables.get_mut(name) {
+                    variable.themes.push(ThemeValue {
+                        theme: theme.clone(),
+                        value: winner.value.clone(),
+                        defined_in: winner.file.clone(),
+                        overrides: overridden.iter().map(|d| d.file.clone()).collect(),
+                    });
+                }
+            }
+        }
+
+        ThemeReport {
+            themes,
+            variables: variables.into_values().collect(),
+        }
+    }
+}
+
+fn is_root_selector(selectors: &str) -> bool {
+    selectors.split(',').any(|selector| selector.trim() == ROOT_SELECTOR)
+}
+
+/// Variable declarations of a style sheet
+fn extract_definitions(tree: &Tree, content: &str, url: &Url) -> Vec<(String, VariableDefinition)> {
+    let mut definitions = Vec::new();
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    for rule_set in root.children(&mut cursor).filter(|n| n.kind() == NODE_RULE_SET) {
+        let selector = rule_selector_texts(rule_set, content)
+            .iter()
+            .map(|text| text.trim())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let Some(block) = rule_set.child(1).filter(|n| n.kind() == NODE_BLOCK) else {
+            continue;
+        };
+
+        let mut block_cursor = block.walk();
+        for declaration in block.children(&mut block_cursor).filter(|n| n.kind() == NODE_DECLARATION) {
+            if let Some((name, value)) = variable_declaration(declaration, content) {
+                let definition = VariableDefinition {
+                    file: url.clone(),
+                    range: node_to_range(declaration, content),
+                    selector: selector.clone(),
+                    value: value.to_string(),
+                };
+                definitions.push((name.to_string(), definition));
+            }
+        }
+    }
+    definitions
+}
+
+/// Name and value text of a variable declaration
+fn variable_declaration<'a>(declaration: Node, content: &'a str) -> Option<(&'a str, &'a str)> {
+    let name = declaration.child(0).filter(|n| n.kind() == NODE_PROPERTY_NAME)?;
+    let name = name.utf8_text(content.as_bytes()).ok()?;
+    if !name.starts_with("--") {
+        return None;
+    }
+
+    let colon = declaration.child(1).filter(|n| n.kind() == NODE_COLON)?;
+    let mut end = colon.end_byte();
+    let mut cursor = declaration.walk();
+    for child in declaration.children(&mut cursor).skip(2) {
+        if matches!(child.kind(), NODE_SEMICOLON | NODE_COMMENT) {
+            continue;
+        }
+        end = child.end_byte();
+    }
+    Some((name, content[colon.end_byte()..end].trim()))
+}
+
+/// Name of the variable at a position, in a `var()` argument or a declaration, with the `--` prefix
+pub fn variable_name_at_position(tree: &Tree, content: &str, position: Position) -> Option<String> {
+    let node = find_node_at_position(tree.root_node(), position)?;
+    let text = node.utf8_text(content.as_bytes()).ok()?;
+    text.starts_with("--").then(|| text.to_string())
+}
+
+/// Short form of a project URL, the project relative path
+fn display_url(url: &Url) -> String {
+    project_url_to_relative_path(url).unwrap_or_else(|| url.to_string())
+}
+
+/// Report of the project's themes for hover, built again when it's older than the refresh interval
+///
+/// Building the report reads the themes of the project, it is built with [`ThemeReport::for_project`]
+/// and stored with [`set_report`](Self::set_report).
+#[derive(Debug, Default)]
+pub struct ThemeReportCache {
+    report: ThemeReport,
+    scan: ScanTimer,
+}
+
+impl ThemeReportCache {
+    /// Build the report again on next use, e.g. after a theme or style sheet changed
+    pub fn invalidate(&mut self) {
+        self.scan.invalidate();
+    }
+
+    /// Whether the report should be built again before it's used
+    pub fn is_stale(&self) -> bool {
+        self.scan.is_stale()
+    }
+
+    pub fn set_report(&mut self, report: ThemeReport) {
+        self.report = report;
+        self.scan.mark_scanned();
+    }
+
+    /// The last report that was built, empty before the first one
+    pub fn report(&self) -> &ThemeReport {
+        &self.report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, relative: &str, content: &str) {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn create_project() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "Assets/Styles/Base.uss",
+            ":root { --text-color: #000; --spacing: 4px; }\n.button { --text-color: red; }\n",
+        );
+        write(
+            root,
+            "Assets/Themes/Dark.tss",
+            "@import url(\"unity-theme://default\");\n@import url(\"../Styles/Base.uss\");\n:root { --text-color: #eee; }\n",
+        );
+        write(root, "Assets/Themes/Light.tss", "@import url(\"/Assets/Styles/Base.uss\");\n");
+        dir
+    }
+
+    #[test]
+    fn test_theme_report() {
+        let project = create_project();
+        let report = ThemeReport::for_project(project.path());
+
+        assert_eq!(report.themes.len(), 2);
+        let names: Vec<&str> = report.variables.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, vec!["--spacing", "--text-color"]);
+
+        let text_color = report.variable("--text-color").unwrap();
+        assert_eq!(text_color.definitions.len(), 3, "{:#?}", text_color.definitions);
+        assert!(text_color.definitions.iter().any(|d| d.selector == ".button" && d.value == "red"));
+
+        let dark = text_color.themes.iter().find(|t| t.theme.path().ends_with("Dark.tss")).unwrap();
+        assert_eq!(dark.value, "#eee");
+        assert_eq!(dark.defined_in, dark.theme);
+        assert_eq!(dark.overrides.len(), 1);
+        assert!(dark.overrides[0].path().ends_with("Base.uss"));
+
+        let light = text_color.themes.iter().find(|t| t.theme.path().ends_with("Light.tss")).unwrap();
+        assert_eq!(light.value, "#000");
+        assert!(light.defined_in.path().ends_with("Base.uss"));
+        assert!(light.overrides.is_empty());
+
+        let hover = report.hover_section("--text-color").unwrap();
+        assert!(hover.contains("Assets/Themes/Dark.tss: `#eee`"), "{}", hover);
+        assert!(hover.contains("Assets/Themes/Light.tss: `#000` (from Assets/Styles/Base.uss)"), "{}", hover);
+        assert!(report.hover_section("--unknown").is_none());
+    }
+
+    #[test]
+    fn test_variable_name_at_position() {
+        let content = ".a { --size: 4px; width: var(--size); }";
+        let tree = UssParser::new().unwrap().parse(content, None).unwrap();
+
+        assert_eq!(variable_name_at_position(&tree, content, Position::new(0, 7)), Some("--size".to_string()));
+        assert_eq!(variable_name_at_position(&tree, content, Position::new(0, 32)), Some("--size".to_string()));
+        assert_eq!(variable_name_at_position(&tree, content, Position::new(0, 20)), None);
+    }
+}
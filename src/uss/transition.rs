@@ -0,0 +1,104 @@
+//! Transition Shorthand
+//!
+//! Each comma separated transition of `transition` is a property, up to two times and a timing function.
+//! Which time is which only depends on their order: the first is the duration, the second the delay. A
+//! transition that doesn't match only says that it doesn't match the format, so what a transition means
+//! is checked here, with a code for each problem.
+
+use tower_lsp::lsp_types::*;
+use tree_sitter::Node;
+
+use crate::language::tree_utils::node_to_range;
+use crate::uss::constants::*;
+use crate::uss::definitions::{PropertyAnimation, UssDefinitions};
+use crate::uss::property_data::TIMING_FUN;
+use crate::uss::value::UssValue;
+
+/// Name of the property
+pub const TRANSITION_PROPERTY: &str = "transition";
+
+/// Keywords that are transitioned properties without being a property
+const PROPERTY_KEYWORDS: &[&str] = &[ALL_PROPERTIES, "none", "ignored", KEYWORD_INITIAL];
+
+/// Transition of all animatable properties
+pub const ALL_PROPERTIES: &str = "all";
+
+/// Diagnostics of one comma separated transition, empty if it has no problems reported here
+///
+/// Transitions with variables aren't checked, what the variables are is only known where the style is used.
+/// `docs_url` is linked from the diagnostics.
+pub fn segment_diagnostics(
+    values: &[UssValue],
+    value_nodes: &[Node],
+    definitions: &UssDefinitions,
+    docs_url: Option<&Url>,
+    content: &str,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    if values.len() != value_nodes.len() || values.iter().any(|value| matches!(value, UssValue::VariableReference(_))) {
+        return diagnostics;
+    }
+    let mut report = |node: &Node, severity: DiagnosticSeverity, code: &str, message: String| {
+        diagnostics.push(Diagnostic {
+            range: node_to_range(*node, content),
+            severity: Some(severity),
+            code: Some(NumberOrString::String(code.to_string())),
+            code_description: docs_url.map(|href| CodeDescription { href: href.clone() }),
+            source: Some("uss".to_string()),
+            message,
+            ..Default::default()
+        });
+    };
+
+    let mut times = 0;
+    let mut has_property = false;
+    for (value, node) in values.iter().zip(value_nodes) {
+        let text = node.utf8_text(content.as_bytes()).unwrap_or_default();
+        match value {
+            UssValue::Numeric { value, unit: Some(unit), .. } if unit == UNIT_S || unit == UNIT_MS => {
+                times += 1;
+                if times == 1 && *value < 0.0 {
+                    report(
+                        node,
+                        DiagnosticSeverity::ERROR,
+                        "transition-negative-duration",
+                        format!("Transition duration '{}' can't be negative", text),
+                    );
+                } else if times > 2 {
+                    report(
+                        node,
+                        DiagnosticSeverity::ERROR,
+                        "transition-extra-time",
+                        format!(
+                            "A transition has a duration and a delay, '{}' is a time too many",
+                            text
+                        ),
+                    );
+                }
+            }
+            UssValue::Identifier(name) if !has_property && !TIMING_FUN.contains(&name.as_str()) => {
+                has_property = true;
+                if PROPERTY_KEYWORDS.contains(&name.as_str()) || name.starts_with("--") {
+                    continue;
+                }
+                match definitions.get_property_info(name) {
+                    Some(info) if info.animatable == PropertyAnimation::None => report(
+                        node,
+                        DiagnosticSeverity::WARNING,
+                        "transition-not-animatable",
+                        format!("Property '{}' is not animatable, the transition has no effect", name),
+                    ),
+                    Some(_) => {}
+                    None => report(
+                        node,
+                        DiagnosticSeverity::ERROR,
+                        "transition-unknown-property",
+                        format!("Unknown property '{}' in transition", name),
+                    ),
+                }
+            }
+            _ => {}
+        }
+    }
+    diagnostics
+}
@@ -0,0 +1,212 @@
+//! Unit Conversion Code Actions
+//!
+//! Times convert between `s` and `ms` directly, e.g. `0.3s` becomes `300ms`.
+//!
+//! Lengths convert between `px` and `%` only in properties where percentages are relative to a known
+//! reference (see [`percentage_reference`]), and the size of that reference is only known at runtime. So
+//! the code action runs [`CONVERT_LENGTH_COMMAND`] with [`ConvertLengthArguments`] without a reference
+//! size, the client asks the user for it and runs the command again with `referenceSize` set, the server
+//! then applies the edit.
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::*;
+use tree_sitter::{Node, Tree};
+
+use crate::language::tree_utils::{find_node_at_position, node_to_range};
+use crate::language::workspace_edit::document_edit;
+use crate::uss::constants::*;
+use crate::uss::property_data::percentage_reference;
+use crate::uss::unit_data::format_number;
+
+/// Command that converts a length between `px` and `%`, takes [`ConvertLengthArguments`]
+pub const CONVERT_LENGTH_COMMAND: &str = "unityCode/convertLength";
+
+/// Arguments of [`CONVERT_LENGTH_COMMAND`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvertLengthArguments {
+    /// Document the length is in, with the version the range is for
+    pub text_document: OptionalVersionedTextDocumentIdentifier,
+    /// Range of the length, number and unit
+    pub range: Range,
+    pub value: f64,
+    /// `px` or `%`
+    pub unit: String,
+    /// `%` or `px`
+    pub target_unit: String,
+    /// What percentages are relative to, shown to the user when asking for the reference size
+    pub reference: String,
+    /// Size of the reference in pixels, provided by the user
+    pub reference_size: Option<f64>,
+}
+
+impl ConvertLengthArguments {
+    /// The edit that converts the length, an error if there is no valid reference size
+    pub fn convert(&self) -> Result<WorkspaceEdit, String> {
+        let reference_size = match self.reference_size {
+            Some(size) if size > 0.0 && size.is_finite() => size,
+            Some(size) => return Err(format!("Reference size must be a positive number of pixels, got {}", size)),
+            None => return Err(format!("Expected the size of {} in pixels", self.reference)),
+        };
+        let converted = match (self.unit.as_str(), self.target_unit.as_str()) {
+            ("px", "%") => self.value / reference_size * 100.0,
+            ("%", "px") => self.value / 100.0 * reference_size,
+            (unit, target_unit) => return Err(format!("Can't convert {} to {}", unit, target_unit)),
+        };
+        let edit = TextEdit::new(self.range, format!("{}{}", format_number(converted), self.target_unit));
+        Ok(document_edit(&self.text_document, vec![edit]))
+    }
+}
+
+/// Code actions that convert the unit of the value at the start of `range`
+pub fn unit_conversion_actions(
+    tree: &Tree,
+    content: &str,
+    document: &OptionalVersionedTextDocumentIdentifier,
+    range: Range,
+) -> Vec<CodeActionOrCommand> {
+    let Some((node, value, unit)) = find_dimension(tree, content, range.start) else {
+        return Vec::new();
+    };
+    let value_range = node_to_range(node, content);
+
+    let time = match unit {
+        "s" => Some((value * 1000.0, "ms")),
+        "ms" => Some((value / 1000.0, "s")),
+        _ => None,
+    };
+    if let Some((converted, target_unit)) = time {
+        let new_text = format!("{}{}", format_number(converted), target_unit);
+        return vec![CodeActionOrCommand::CodeAction(CodeAction {
+            title: format!("Convert to {}", new_text),
+            kind: Some(CodeActionKind::REFACTOR_REWRITE),
+            edit: Some(document_edit(document, vec![TextEdit::new(value_range, new_text)])),
+            ..Default::default()
+        })];
+    }
+
+    let target_unit = match unit {
+        "px" => "%",
+        "%" => "px",
+        _ => return Vec::new(),
+    };
+    let Some(reference) = property_name(node, content).and_then(percentage_reference) else {
+        return Vec::new();
+    };
+    let arguments = ConvertLengthArguments {
+        text_document: document.clone(),
+        range: value_range,
+        value,
+        unit: unit.to_string(),
+        target_unit: target_unit.to_string(),
+        reference: reference.to_string(),
+        reference_size: None,
+    };
+    let title = format!("Convert to {}", target_unit);
+    vec![CodeActionOrCommand::CodeAction(CodeAction {
+        title: title.clone(),
+        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+        command: Some(Command {
+            title,
+            command: CONVERT_LENGTH_COMMAND.to_string(),
+            arguments: serde_json::to_value(arguments).ok().map(|a| vec![a]),
+        }),
+        ..Default::default()
+    })]
+}
+
+/// A number with a unit at a position, with its number and unit
+fn find_dimension<'a>(tree: &'a Tree, content: &'a str, position: Position) -> Option<(Node<'a>, f64, &'a str)> {
+    let mut node = find_node_at_position(tree.root_node(), position)?;
+    if node.kind() == NODE_UNIT {
+        node = node.parent()?;
+    }
+    if node.kind() != NODE_INTEGER_VALUE && node.kind() != NODE_FLOAT_VALUE {
+        return None;
+    }
+
+    let unit_node = (0..node.child_count()).filter_map(|i| node.child(i)).find(|c| c.kind() == NODE_UNIT)?;
+    let text = node.utf8_text(content.as_bytes()).ok()?;
+    let unit = unit_node.utf8_text(content.as_bytes()).ok()?;
+    let value = text[..unit_node.start_byte() - node.start_byte()].parse().ok()?;
+    Some((node, value, unit))
+}
+
+/// Name of the property a value is declared for
+fn property_name<'a>(node: Node, content: &'a str) -> Option<&'a str> {
+    let mut current = node.parent()?;
+    while current.kind() != NODE_DECLARATION {
+        current = current.parent()?;
+    }
+    current
+        .child(0)
+        .filter(|property| property.kind() == NODE_PROPERTY_NAME)
+        .and_then(|property| property.utf8_text(content.as_bytes()).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::workspace_edit::text_edits;
+    use crate::uss::parser::UssParser;
+
+    fn actions(content: &str, character: u32) -> (Url, Vec<CodeAction>) {
+        let tree = UssParser::new().unwrap().parse(content, None).unwrap();
+        let uri = Url::parse("file:///project/Assets/a.uss").unwrap();
+        let document = OptionalVersionedTextDocumentIdentifier { uri: uri.clone(), version: Some(2) };
+        let position = Position::new(0, character);
+        let actions = unit_conversion_actions(&tree, content, &document, Range::new(position, position))
+            .into_iter()
+            .filter_map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => Some(action),
+                CodeActionOrCommand::Command(_) => None,
+            })
+            .collect();
+        (uri, actions)
+    }
+
+    #[test]
+    fn test_convert_time() {
+        let content = ".a { transition: opacity 0.3s ease 250ms; }";
+        let (uri, found) = actions(content, 26);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].title, "Convert to 300ms");
+        let edit = found[0].edit.as_ref().unwrap();
+        assert_eq!(text_edits(edit, &uri)[0].range, Range::new(Position::new(0, 25), Position::new(0, 29)));
+
+        let (_, found) = actions(content, 36);
+        assert_eq!(found[0].title, "Convert to 0.25s");
+
+        // not on other values
+        assert!(actions(content, 19).1.is_empty());
+    }
+
+    #[test]
+    fn test_convert_length() {
+        let content = ".a { width: 50%; border-width: 2px; }";
+        let (uri, found) = actions(content, 13);
+        assert_eq!(found.len(), 1);
+        let command = found[0].command.as_ref().unwrap();
+        assert_eq!(command.command, CONVERT_LENGTH_COMMAND);
+        let mut arguments: ConvertLengthArguments =
+            serde_json::from_value(command.arguments.as_ref().unwrap()[0].clone()).unwrap();
+        assert_eq!(arguments.target_unit, "px");
+        assert_eq!(arguments.reference, "the width of the parent element");
+
+        // the client asks for the reference size
+        assert!(arguments.convert().is_err());
+        arguments.reference_size = Some(300.0);
+        let edit = arguments.convert().unwrap();
+        let edits = text_edits(&edit, &uri);
+        assert_eq!(edits[0].new_text, "150px");
+        assert_eq!(edits[0].range, Range::new(Position::new(0, 12), Position::new(0, 15)));
+
+        arguments.unit = "px".to_string();
+        arguments.target_unit = "%".to_string();
+        arguments.value = 100.0;
+        assert_eq!(text_edits(&arguments.convert().unwrap(), &uri)[0].new_text, "33.3333%");
+
+        // percentages have no meaning in border-width
+        assert!(actions(content, 32).1.is_empty());
+    }
+}
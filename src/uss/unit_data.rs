@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use crate::uss::definitions::UnitInfo;
+use crate::uss::property_data::percentage_reference;
 
 
 /// Create unit information with documentation
@@ -11,14 +12,14 @@ pub fn create_unit_info() -> HashMap<&'static str, UnitInfo> {
     units.insert("px", UnitInfo {
         name: "px",
         category: "Length",
-        description: "Absolute length unit representing screen pixels.",
-        details: None,
+        description: "Device-independent pixels, not physical screen pixels.",
+        details: Some("The size on screen depends on the scale of the panel (the scale mode of its Panel Settings and the screen DPI), like CSS pixels on a high DPI display."),
     });
 
     units.insert("%", UnitInfo {
         name: "%",
         category: "Length",
-        description: "Relative unit based on the parent element's corresponding property.",
+        description: "Percentage, what it is relative to depends on the property, usually the size of the parent element.",
         details: None,
     });
 
@@ -68,3 +69,80 @@ pub fn create_unit_info() -> HashMap<&'static str, UnitInfo> {
 
     units
 }
+
+/// Degrees in a full rotation of each angle unit
+const ANGLE_UNITS: [(&str, f64); 4] = [("deg", 360.0), ("rad", std::f64::consts::TAU), ("grad", 400.0), ("turn", 1.0)];
+
+/// Milliseconds in each time unit
+const TIME_UNITS: [(&str, f64); 2] = [("s", 1000.0), ("ms", 1.0)];
+
+/// A value converted to the other units of its category, e.g. `` `90deg` = `1.5708rad` = `100grad` = `0.25turn` ``
+///
+/// `None` for units that can't be converted (lengths and percentages depend on the layout).
+pub fn unit_conversions(value: f64, unit: &str) -> Option<String> {
+    let units: &[(&str, f64)] = if ANGLE_UNITS.iter().any(|(name, _)| *name == unit) {
+        &ANGLE_UNITS
+    } else if TIME_UNITS.iter().any(|(name, _)| *name == unit) {
+        &TIME_UNITS
+    } else {
+        return None;
+    };
+
+    let (_, scale) = units.iter().find(|(name, _)| *name == unit)?;
+    // angle scales are per rotation and time scales are per unit, so convert through a common base
+    let is_angle = units.len() == ANGLE_UNITS.len();
+    let converted: Vec<String> = units
+        .iter()
+        .map(|(name, other_scale)| {
+            let converted = if is_angle {
+                value / scale * other_scale
+            } else {
+                value * scale / other_scale
+            };
+            format!("`{}{}`", format_number(converted), name)
+        })
+        .collect();
+    Some(converted.join(" = "))
+}
+
+/// What a unit means in a property, for units whose meaning depends on the property
+pub fn unit_in_property(unit: &str, property_name: &str) -> Option<String> {
+    match unit {
+        "%" => percentage_reference(property_name)
+            .map(|reference| format!("In `{}`, percentages are relative to {}.", property_name, reference)),
+        _ => None,
+    }
+}
+
+/// Format a number with at most 4 decimals and no trailing zeros
+pub(crate) fn format_number(value: f64) -> String {
+    let text = format!("{:.4}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { "0".to_string() } else { text.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_conversions() {
+        assert_eq!(unit_conversions(90.0, "deg").unwrap(), "`90deg` = `1.5708rad` = `100grad` = `0.25turn`");
+        assert_eq!(unit_conversions(0.5, "turn").unwrap(), "`180deg` = `3.1416rad` = `200grad` = `0.5turn`");
+        assert_eq!(unit_conversions(250.0, "ms").unwrap(), "`0.25s` = `250ms`");
+        assert_eq!(unit_conversions(1.5, "s").unwrap(), "`1.5s` = `1500ms`");
+        assert_eq!(unit_conversions(10.0, "px"), None);
+        assert_eq!(unit_conversions(10.0, "%"), None);
+    }
+
+    #[test]
+    fn test_unit_in_property() {
+        assert_eq!(
+            unit_in_property("%", "translate").unwrap(),
+            "In `translate`, percentages are relative to the element's own size, its width for x and its height for y."
+        );
+        assert!(unit_in_property("%", "padding-top").unwrap().contains("width of the parent"));
+        assert_eq!(unit_in_property("%", "color"), None);
+        assert_eq!(unit_in_property("px", "width"), None);
+    }
+}
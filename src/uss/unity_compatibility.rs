@@ -0,0 +1,207 @@
+//! Unity Compatibility Rules
+//!
+//! The USS importer of Unity doesn't accept exactly what the validation here does. It reads some values this
+//! parser rejects, e.g. a length without unit is read as pixels, and it reads some constructs this parser
+//! accepts differently than written. In the `strict-unity` [`ValidationMode`] diagnostics follow what Unity
+//! does: values Unity accepts are warnings instead of errors, and constructs that Unity handles differently
+//! are reported.
+//!
+//! Every rule is listed in [`COMPATIBILITY_RULES`], its code can be configured like any other diagnostic code.
+
+use tower_lsp::lsp_types::*;
+use tree_sitter::{Node, Tree};
+
+use crate::language::tree_utils::node_to_range;
+use crate::uss::constants::*;
+use crate::uss::definitions::UssDefinitions;
+use crate::uss::value::UssValue;
+use crate::uss::value_spec::ValueSpec;
+
+/// Which rules values are validated with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidationMode {
+    /// The rules of USS as documented
+    #[default]
+    Standard,
+    /// What Unity's importer actually accepts, with warnings for what it reads differently
+    StrictUnity,
+}
+
+impl ValidationMode {
+    /// Parse a validation mode from its configuration name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "standard" => Some(Self::Standard),
+            "strict-unity" => Some(Self::StrictUnity),
+            _ => None,
+        }
+    }
+}
+
+/// A difference between Unity's importer and the validation here
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompatibilityRule {
+    /// Diagnostic code
+    pub code: &'static str,
+    /// What Unity does
+    pub description: &'static str,
+}
+
+/// A number without unit where a length is expected
+pub const UNITLESS_LENGTH: CompatibilityRule = CompatibilityRule {
+    code: "unitless-length",
+    description: "Unity reads a length without unit as pixels, USS requires the unit for lengths other than 0",
+};
+
+/// A `url()` whose argument is not a string
+pub const UNQUOTED_URL: CompatibilityRule = CompatibilityRule {
+    code: "unquoted-url",
+    description: "Unity reads escapes and special characters of unquoted URLs differently, only quoted URLs are documented",
+};
+
+/// All compatibility rules of the `strict-unity` mode
+pub const COMPATIBILITY_RULES: &[CompatibilityRule] = &[UNITLESS_LENGTH, UNQUOTED_URL];
+
+/// Diagnostics of the constructs Unity reads differently, found anywhere in the document
+pub fn compatibility_diagnostics(tree: &Tree, content: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    collect_unquoted_urls(tree.root_node(), content, &mut diagnostics);
+    diagnostics
+}
+
+/// Warnings for the unitless numbers of a value that doesn't match the property, if Unity accepts it
+///
+/// Unity reads the numbers as pixels, so the value is accepted if it matches with `px` added to them.
+pub fn unitless_length_diagnostics(
+    values: &[UssValue],
+    value_nodes: &[Node],
+    value_spec: &ValueSpec,
+    definitions: &UssDefinitions,
+    content: &str,
+) -> Option<Vec<Diagnostic>> {
+    let is_unitless = |value: &UssValue| matches!(value, UssValue::Numeric { value, unit: None, .. } if *value != 0.0);
+    if !values.iter().any(is_unitless) {
+        return None;
+    }
+
+    let as_pixels: Vec<UssValue> = values
+        .iter()
+        .map(|value| match value {
+            UssValue::Numeric { value: number, unit: None, has_fractional } if is_unitless(value) => UssValue::Numeric {
+                value: *number,
+                unit: Some("px".to_string()),
+                has_fractional: *has_fractional,
+            },
+            other => other.clone(),
+        })
+        .collect();
+    if !value_spec.formats.iter().any(|format| format.is_match(&as_pixels, definitions)) {
+        return None;
+    }
+
+    let diagnostics = values
+        .iter()
+        .zip(value_nodes)
+        .filter(|(value, _)| is_unitless(value))
+        .map(|(value, node)| Diagnostic {
+            range: node_to_range(*node, content),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(UNITLESS_LENGTH.code.to_string())),
+            source: Some("uss".to_string()),
+            message: format!("Length without unit, Unity reads it as '{}px'", value.to_string()),
+            ..Default::default()
+        })
+        .collect();
+    Some(diagnostics)
+}
+
+fn collect_unquoted_urls(node: Node, content: &str, diagnostics: &mut Vec<Diagnostic>) {
+    if node.kind() == NODE_CALL_EXPRESSION {
+        let is_url = node
+            .child(0)
+            .filter(|name| name.kind() == NODE_FUNCTION_NAME)
+            .and_then(|name| name.utf8_text(content.as_bytes()).ok())
+            == Some("url");
+        let argument = node
+            .child(1)
+            .filter(|arguments| arguments.kind() == NODE_ARGUMENTS)
+            .and_then(|arguments| arguments.named_child(0));
+        if let (true, Some(argument)) = (is_url, argument) {
+            if argument.kind() != NODE_STRING_VALUE {
+                let text = argument.utf8_text(content.as_bytes()).unwrap_or_default();
+                diagnostics.push(Diagnostic {
+                    range: node_to_range(argument, content),
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    code: Some(NumberOrString::String(UNQUOTED_URL.code.to_string())),
+                    source: Some("uss".to_string()),
+                    message: format!("Unquoted URL, write url(\"{}\") so Unity reads it as written", text),
+                    ..Default::default()
+                });
+            }
+        }
+        return;
+    }
+
+    for i in 0..node.child_count() {
+        if let Some(child) = node.child(i) {
+            collect_unquoted_urls(child, content, diagnostics);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uss::diagnostics::UssDiagnostics;
+    use crate::uss::parser::UssParser;
+    use crate::uss::settings::DiagnosticSettings;
+
+    fn codes(content: &str, mode: ValidationMode) -> Vec<(String, DiagnosticSeverity)> {
+        let tree = UssParser::new().unwrap().parse(content, None).unwrap();
+        let mut diagnostics = UssDiagnostics::new();
+        diagnostics.set_settings(DiagnosticSettings { mode, ..Default::default() });
+        diagnostics
+            .analyze(&tree, content)
+            .into_iter()
+            .filter_map(|d| match d.code {
+                Some(NumberOrString::String(code)) => Some((code, d.severity.unwrap())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_unitless_length() {
+        let content = ".a { width: 10; margin: 0 5; rotate: 10; }";
+        assert_eq!(
+            codes(content, ValidationMode::Standard),
+            vec![
+                ("invalid-property-value".to_string(), DiagnosticSeverity::ERROR),
+                ("invalid-property-value".to_string(), DiagnosticSeverity::ERROR),
+                ("invalid-property-value".to_string(), DiagnosticSeverity::ERROR),
+            ]
+        );
+        // an angle without unit is not a length, Unity doesn't accept it either
+        assert_eq!(
+            codes(content, ValidationMode::StrictUnity),
+            vec![
+                ("unitless-length".to_string(), DiagnosticSeverity::WARNING),
+                ("unitless-length".to_string(), DiagnosticSeverity::WARNING),
+                ("invalid-property-value".to_string(), DiagnosticSeverity::ERROR),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unquoted_url() {
+        let content = ".a { background-image: url(icon.png); }\n.b { background-image: url(\"icon.png\"); }";
+        assert!(codes(content, ValidationMode::Standard).iter().all(|(code, _)| code != UNQUOTED_URL.code));
+
+        let tree = UssParser::new().unwrap().parse(content, None).unwrap();
+        let diagnostics = compatibility_diagnostics(&tree, content);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "Unquoted URL, write url(\"icon.png\") so Unity reads it as written");
+        assert_eq!(diagnostics[0].range.start, Position::new(0, 27));
+        assert!(codes(content, ValidationMode::StrictUnity).iter().any(|(code, _)| code == UNQUOTED_URL.code));
+    }
+}
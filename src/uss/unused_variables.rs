@@ -0,0 +1,319 @@
+//! Unused Variable Detection
+//!
+//! Reports variables that are declared but never referenced with `var()`, with a quick fix that removes
+//! the declaration. By default only the document itself is searched for references, with the
+//! [`UnusedVariableScope::Importers`] setting the files that import it (directly or not) are searched too.
+//!
+//! Variables are inherited by child elements, so a variable can also be used by style sheets that don't
+//! import the document, or from C#. That's why these are hints, not warnings.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use tower_lsp::lsp_types::*;
+use tree_sitter::{Node, Tree};
+
+use crate::language::asset_url::project_url_to_path;
+use crate::language::tree_utils::{byte_to_position, node_to_range, position_to_byte_offset};
+use crate::language::workspace_edit::document_edit;
+use crate::uss::constants::*;
+use crate::uss::function_node::FunctionNode;
+use crate::uss::parser::UssParser;
+use crate::uss::uss_utils::line_aware_range;
+
+/// Diagnostic code of unused variables
+pub const UNUSED_VARIABLE_CODE: &str = "unused-variable";
+
+/// Where references of a variable are searched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnusedVariableScope {
+    /// Only the document that declares the variable
+    #[default]
+    Document,
+    /// The document and the files that import it
+    Importers,
+}
+
+impl UnusedVariableScope {
+    /// Parse a scope from its configuration name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "document" => Some(Self::Document),
+            "importers" => Some(Self::Importers),
+            _ => None,
+        }
+    }
+}
+
+/// Names of the variables referenced by `var()` in a document, with the `--` prefix
+pub fn variable_references(tree: &Tree, content: &str) -> HashSet<String> {
+    let mut references = HashSet::new();
+    collect_references(tree.root_node(), content, &mut references);
+    references
+}
+
+fn collect_references(node: Node, content: &str, references: &mut HashSet<String>) {
+    if let Some(function) = FunctionNode::from_node(node, content, None) {
+        if function.function_name == "var" {
+            let name = function.argument_nodes.first().and_then(|n| n.utf8_text(content.as_bytes()).ok());
+            if let Some(name) = name.filter(|name| name.starts_with("--")) {
+                references.insert(name.to_string());
+            }
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_references(child, content, references);
+    }
+}
+
+/// Variables referenced by style sheets of the project, by their project URLs
+///
+/// Files are read from disk, unsaved changes of open documents are not seen.
+pub fn references_in_files(project_root: &Path, urls: &[Url]) -> HashSet<String> {
+    let mut references = HashSet::new();
+    let Ok(mut parser) = UssParser::new() else {
+        return references;
+    };
+
+    for url in urls {
+        let Some(content) = project_url_to_path(project_root, url).and_then(|path| std::fs::read_to_string(path).ok()) else {
+            continue;
+        };
+        if let Some(tree) = parser.parse(&content, None) {
+            references.extend(variable_references(&tree, &content));
+        }
+    }
+    references
+}
+
+/// Ranges of the `var()` references of a variable under a node, name with the `--` prefix
+pub fn reference_ranges(node: Node, content: &str, name: &str) -> Vec<Range> {
+    let mut ranges = Vec::new();
+    collect_reference_ranges(node, content, name, &mut ranges);
+    ranges
+}
+
+fn collect_reference_ranges(node: Node, content: &str, name: &str, ranges: &mut Vec<Range>) {
+    if let Some(function) = FunctionNode::from_node(node, content, None) {
+        if function.function_name == "var" {
+            if let Some(argument) = function.argument_nodes.first() {
+                if argument.utf8_text(content.as_bytes()) == Ok(name) {
+                    ranges.push(node_to_range(*argument, content));
+                }
+            }
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_reference_ranges(child, content, name, ranges);
+    }
+}
+
+/// References and declarations of a variable in other style sheets, see [`variable_locations_in_files`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VariableLocations {
+    pub references: Vec<Location>,
+    /// Property names of the declarations, which override the variable for the elements they apply to
+    pub declarations: Vec<Location>,
+}
+
+/// Locations of the references and declarations of a variable in style sheets of the project, by their project URLs
+///
+/// Files are read from disk like [`references_in_files`], locations have file URLs.
+pub fn variable_locations_in_files(project_root: &Path, urls: &[Url], name: &str) -> VariableLocations {
+    let mut locations = VariableLocations::default();
+    let Ok(mut parser) = UssParser::new() else {
+        return locations;
+    };
+
+    for url in urls {
+        let Some(path) = project_url_to_path(project_root, url) else {
+            continue;
+        };
+        let (Ok(content), Ok(file_url)) = (std::fs::read_to_string(&path), Url::from_file_path(&path)) else {
+            continue;
+        };
+        let Some(tree) = parser.parse(&content, None) else {
+            continue;
+        };
+        let location = |range| Location {
+            uri: file_url.clone(),
+            range,
+        };
+        locations.references.extend(reference_ranges(tree.root_node(), &content, name).into_iter().map(location));
+
+        let mut declarations = Vec::new();
+        collect_declarations(tree.root_node(), &content, &mut declarations);
+        locations.declarations.extend(
+            declarations
+                .into_iter()
+                .filter(|(declared, _)| *declared == name)
+                .map(|(_, property)| location(node_to_range(property, &content))),
+        );
+    }
+    locations
+}
+
+/// Diagnostics for variables that are not referenced in the document nor in `referenced_elsewhere`
+pub fn find_unused_variables(tree: &Tree, content: &str, referenced_elsewhere: &HashSet<String>) -> Vec<Diagnostic> {
+    let references = variable_references(tree, content);
+    let mut declarations = Vec::new();
+    collect_declarations(tree.root_node(), content, &mut declarations);
+
+    declarations
+        .into_iter()
+        .filter(|(name, _)| !references.contains(*name) && !referenced_elsewhere.contains(*name))
+        .map(|(name, property_node)| Diagnostic {
+            range: node_to_range(property_node, content),
+            severity: Some(DiagnosticSeverity::HINT),
+            code: Some(NumberOrString::String(UNUSED_VARIABLE_CODE.to_string())),
+            source: Some("uss".to_string()),
+            message: format!("Variable {} is never used", name),
+            tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Variable declarations as (name, property name node)
+fn collect_declarations<'a>(node: Node<'a>, content: &'a str, declarations: &mut Vec<(&'a str, Node<'a>)>) {
+    if node.kind() == NODE_DECLARATION {
+        if let Some(property) = node.child(0).filter(|n| n.kind() == NODE_PROPERTY_NAME) {
+            if let Ok(name) = property.utf8_text(content.as_bytes()) {
+                if name.starts_with("--") {
+                    declarations.push((name, property));
+                }
+            }
+        }
+        return;
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_declarations(child, content, declarations);
+    }
+}
+
+/// Quick fix that removes the declaration of an unused variable
+pub fn remove_variable_action(
+    tree: &Tree,
+    content: &str,
+    document: &OptionalVersionedTextDocumentIdentifier,
+    diagnostic: &Diagnostic,
+) -> Option<CodeAction> {
+    if diagnostic.code != Some(NumberOrString::String(UNUSED_VARIABLE_CODE.to_string())) {
+        return None;
+    }
+
+    let offset = position_to_byte_offset(content, diagnostic.range.start)?;
+    let mut declaration = tree.root_node().descendant_for_byte_range(offset, offset)?;
+    while declaration.kind() != NODE_DECLARATION {
+        declaration = declaration.parent()?;
+    }
+    let name = declaration.child(0)?.utf8_text(content.as_bytes()).ok()?;
+
+    let (start, end) = line_aware_range(content, declaration.start_byte(), declaration.end_byte());
+    let edit = TextEdit {
+        range: Range::new(byte_to_position(start, content), byte_to_position(end, content)),
+        new_text: String::new(),
+    };
+
+    Some(CodeAction {
+        title: format!("Remove unused variable {}", name),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(document_edit(document, vec![edit])),
+        is_preferred: Some(true),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::workspace_edit::{text_edits, versioned_document};
+
+    fn parse(content: &str) -> Tree {
+        UssParser::new().unwrap().parse(content, None).unwrap()
+    }
+
+    fn apply(content: &str, edit: &TextEdit) -> String {
+        let start = position_to_byte_offset(content, edit.range.start).unwrap();
+        let end = position_to_byte_offset(content, edit.range.end).unwrap();
+        format!("{}{}{}", &content[..start], edit.new_text, &content[end..])
+    }
+
+    #[test]
+    fn test_find_unused_variables() {
+        let content = ":root {\n    --used: red;\n    --unused: 4px;\n    --elsewhere: blue;\n}\n.a { color: var(--used); }\n";
+        let tree = parse(content);
+
+        let referenced_elsewhere = HashSet::from(["--elsewhere".to_string()]);
+        let diagnostics = find_unused_variables(&tree, content, &referenced_elsewhere);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "Variable --unused is never used");
+        assert_eq!(diagnostics[0].range, Range::new(Position::new(2, 4), Position::new(2, 12)));
+        assert_eq!(diagnostics[0].tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+
+        assert_eq!(find_unused_variables(&tree, content, &HashSet::new()).len(), 2);
+    }
+
+    #[test]
+    fn test_reference_ranges() {
+        let content = ".a { color: var(--main); border-color: var(--other, var(--main)); }";
+        let tree = parse(content);
+
+        let ranges = reference_ranges(tree.root_node(), content, "--main");
+        assert_eq!(
+            ranges,
+            vec![
+                Range::new(Position::new(0, 16), Position::new(0, 22)),
+                Range::new(Position::new(0, 56), Position::new(0, 62)),
+            ]
+        );
+        assert!(reference_ranges(tree.root_node(), content, "--missing").is_empty());
+    }
+
+    #[test]
+    fn test_variable_locations_in_files() {
+        let project = tempfile::TempDir::new().unwrap();
+        let ui = project.path().join("Assets").join("UI");
+        std::fs::create_dir_all(&ui).unwrap();
+        std::fs::write(ui.join("Main.uss"), ".a { --main: blue; color: var(--main); }\n:root { --other: 1px; }\n").unwrap();
+
+        let urls = [Url::parse("project:/Assets/UI/Main.uss").unwrap(), Url::parse("project:/Assets/Missing.uss").unwrap()];
+        let locations = variable_locations_in_files(project.path(), &urls, "--main");
+        assert_eq!(locations.references.len(), 1);
+        assert_eq!(locations.references[0].range, Range::new(Position::new(0, 30), Position::new(0, 36)));
+        assert_eq!(locations.declarations.len(), 1);
+        assert_eq!(locations.declarations[0].range, Range::new(Position::new(0, 5), Position::new(0, 11)));
+        assert!(locations.declarations[0].uri.path().ends_with("/Assets/UI/Main.uss"));
+    }
+
+    #[test]
+    fn test_remove_variable_quick_fix() {
+        let uri = Url::parse("file:///project/Assets/a.uss").unwrap();
+        let document = versioned_document(&uri, Some(2));
+        let content = ":root {\n    --used: red;\n    --unused: 4px;\n}\n.a { --inline: 1px; color: var(--used); }\n";
+        let tree = parse(content);
+        let diagnostics = find_unused_variables(&tree, content, &HashSet::new());
+        assert_eq!(diagnostics.len(), 2);
+
+        let action = remove_variable_action(&tree, content, &document, &diagnostics[0]).unwrap();
+        assert_eq!(action.title, "Remove unused variable --unused");
+        let edit = action.edit.unwrap();
+        assert_eq!(text_edits(&edit, &uri).len(), 1);
+        assert_eq!(
+            apply(content, text_edits(&edit, &uri)[0]),
+            ":root {\n    --used: red;\n}\n.a { --inline: 1px; color: var(--used); }\n"
+        );
+
+        // a declaration that shares its line keeps the rest of the line
+        let action = remove_variable_action(&tree, content, &document, &diagnostics[1]).unwrap();
+        let edit = action.edit.unwrap();
+        assert_eq!(apply(content, text_edits(&edit, &uri)[0]), ":root {\n    --used: red;\n    --unused: 4px;\n}\n.a { color: var(--used); }\n");
+    }
+}
@@ -10,6 +10,7 @@ use crate::language::tree_utils::node_to_range;
 use crate::uss::function_node::FunctionNode;
 use crate::uss::uss_utils::convert_uss_string;
 use crate::uss::constants::{NODE_STRING_VALUE, NODE_PLAIN_VALUE};
+use crate::uss::url_references::UrlReferenceKind;
 
 /// Represents a URL found in USS code along with its location range
 /// Used for future asset validation (file existence checks, etc.)
@@ -19,6 +20,10 @@ pub struct UrlReference {
     pub url: Url,
     /// The LSP range of the URL (for url() functions, this is just the argument range, not including the function name)
     pub range: Range,
+    /// How the asset is referenced, `resource()` is resolved differently
+    pub kind: UrlReferenceKind,
+    /// The URL as written, without quotes and with escapes processed, can be an Addressables address
+    pub text: String,
 }
 
 /// Represents a validated USS url() function call with extracted URL string
@@ -148,6 +153,8 @@ impl<'a> UrlFunctionNode<'a> {
                 url_references.unwrap().push(UrlReference {
                     url: validation_result.url.clone(),
                     range: arg_range,
+                    kind: UrlReferenceKind::Url,
+                    text: url_string.clone(),
                 });
             }
         }
@@ -0,0 +1,282 @@
+//! URL References Report
+//!
+//! Lists the assets style sheets reference, with `@import`, `url()` and `resource()`, and whether they
+//! exist, so build tooling can find missing assets before building a player. The report is returned by
+//! [`URL_REFERENCES_METHOD`] and exported by the `url-references` subcommand.
+//!
+//! References are resolved with [`AssetPathResolver`]: `resource()` names an asset in a `Resources` folder
+//! anywhere in the project or its packages, without its extension, or in `Assets/Editor Default Resources`,
+//! with its extension, and a `url()` can be an Addressables address.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Range, TextDocumentIdentifier};
+use tree_sitter::{Node, Tree};
+use url::Url;
+
+use crate::language::asset_url::{project_url_to_relative_path, validate_url_import};
+use crate::language::tree_utils::node_to_range;
+use crate::unity_asset_database::AssetPathResolver;
+use crate::uss::constants::*;
+use crate::uss::definitions::UssDefinitions;
+use crate::uss::import_node::ImportNode;
+use crate::uss::url_function_node::UrlFunctionNode;
+use crate::uss::value::UssValue;
+
+/// Custom request for the URL references of a style sheet or the project, takes [`UrlReferencesParams`] and returns [`UrlReferencesReport`]
+pub const URL_REFERENCES_METHOD: &str = "unityCode/urlReferences";
+
+/// Parameters of [`URL_REFERENCES_METHOD`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlReferencesParams {
+    /// The style sheet, all style sheets of the project if not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_document: Option<TextDocumentIdentifier>,
+}
+
+/// How an asset is referenced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UrlReferenceKind {
+    Import,
+    Url,
+    Resource,
+}
+
+/// Whether a reference refers to an existing asset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ResolutionStatus {
+    Resolved,
+    Missing,
+    /// The URL can't be parsed
+    Invalid,
+    /// The URL is not in the project, e.g. `unity-theme://default`
+    Unchecked,
+}
+
+/// A reference to an asset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlReferenceInfo {
+    pub kind: UrlReferenceKind,
+    /// The reference as written, e.g. `url("../Images/icon.png")`
+    pub text: String,
+    pub range: Range,
+    /// The URL it resolves to, `None` if it is invalid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Path of the asset, relative to the project if it's in the project
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub status: ResolutionStatus,
+    /// Why the reference is invalid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// The references of a style sheet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileUrlReferences {
+    /// Project relative path of the style sheet
+    pub path: String,
+    pub references: Vec<UrlReferenceInfo>,
+}
+
+/// Result of [`URL_REFERENCES_METHOD`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlReferencesReport {
+    pub files: Vec<FileUrlReferences>,
+    /// Number of references that are missing or invalid
+    pub unresolved: usize,
+}
+
+impl UrlReferencesReport {
+    pub fn add(&mut self, file: FileUrlReferences) {
+        self.unresolved += file
+            .references
+            .iter()
+            .filter(|r| matches!(r.status, ResolutionStatus::Missing | ResolutionStatus::Invalid))
+            .count();
+        self.files.push(file);
+    }
+}
+
+/// Resolves the references of style sheets of a project
+pub struct UrlReferenceResolver {
+    paths: Arc<AssetPathResolver>,
+}
+
+impl UrlReferenceResolver {
+    /// Finds the assets of the project, see [`UrlReferenceResolver::with_paths`] to reuse a resolver
+    pub fn new(project_root: PathBuf) -> Self {
+        Self::with_paths(Arc::new(AssetPathResolver::new(&project_root)))
+    }
+
+    pub fn with_paths(paths: Arc<AssetPathResolver>) -> Self {
+        Self { paths }
+    }
+
+    /// The references of a parsed style sheet, `source_url` is its project URL
+    pub fn file_references(&self, tree: &Tree, content: &str, source_url: &Url) -> FileUrlReferences {
+        let mut references = Vec::new();
+        self.collect(tree.root_node(), content, source_url, &mut references);
+        FileUrlReferences {
+            path: project_url_to_relative_path(source_url).unwrap_or_else(|| source_url.to_string()),
+            references,
+        }
+    }
+
+    fn collect(&self, node: Node, content: &str, source_url: &Url, references: &mut Vec<UrlReferenceInfo>) {
+        match node.kind() {
+            NODE_IMPORT_STATEMENT => {
+                let mut diagnostics = Vec::new();
+                if let Some(import) = ImportNode::from_node(node, content, &mut diagnostics) {
+                    references.push(self.reference(UrlReferenceKind::Import, import.argument_node, content, source_url));
+                }
+                return;
+            }
+            NODE_CALL_EXPRESSION => {
+                let name = node.child(0).and_then(|n| n.utf8_text(content.as_bytes()).ok());
+                let kind = match name {
+                    Some("url") => Some(UrlReferenceKind::Url),
+                    Some("resource") => Some(UrlReferenceKind::Resource),
+                    _ => None,
+                };
+                if let Some(kind) = kind {
+                    references.push(self.reference(kind, node, content, source_url));
+                    return;
+                }
+            }
+            _ => {}
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.collect(child, content, source_url, references);
+        }
+    }
+
+    fn reference(&self, kind: UrlReferenceKind, node: Node, content: &str, source_url: &Url) -> UrlReferenceInfo {
+        let mut reference = UrlReferenceInfo {
+            kind,
+            text: node.utf8_text(content.as_bytes()).unwrap_or_default().to_string(),
+            range: node_to_range(node, content),
+            url: None,
+            path: None,
+            status: ResolutionStatus::Invalid,
+            message: None,
+        };
+
+        let url = match UssValue::from_node(node, content, UssDefinitions::shared(), Some(source_url)) {
+            Ok(UssValue::String(path)) if kind == UrlReferenceKind::Import => {
+                validate_url_import(&path, Some(source_url)).map(|result| result.url).map_err(|e| e.message)
+            }
+            Ok(UssValue::Url(url) | UssValue::Resource(url)) => Ok(url),
+            Ok(_) => Err("Expected a URL".to_string()),
+            Err(e) => Err(e.message),
+        };
+        let url = match url {
+            Ok(url) => url,
+            Err(message) => {
+                reference.message = Some(message);
+                return reference;
+            }
+        };
+
+        reference.url = Some(url.to_string());
+        if url.scheme() != PROJECT_SCHEME {
+            reference.status = ResolutionStatus::Unchecked;
+            return reference;
+        }
+        let found = match kind {
+            UrlReferenceKind::Resource => self.paths.resolve_resource_url(&url),
+            UrlReferenceKind::Import => self.paths.resolve_project_url(&url),
+            UrlReferenceKind::Url => self.paths.resolve_project_url(&url).or_else(|| {
+                let written = UrlFunctionNode::from_node(node, content, None, None, None, true)?.url_string;
+                self.paths.resolve_address(&written)
+            }),
+        };
+        match found {
+            Some(path) => {
+                reference.path = Some(self.display_path(&path));
+                reference.status = ResolutionStatus::Resolved;
+            }
+            None => {
+                reference.path = project_url_to_relative_path(&url);
+                reference.status = ResolutionStatus::Missing;
+            }
+        }
+        reference
+    }
+
+    fn display_path(&self, path: &Path) -> String {
+        match path.strip_prefix(self.paths.project_root()) {
+            Ok(relative) => relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/"),
+            Err(_) => path.display().to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uss::parser::UssParser;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_file_references() {
+        let project = TempDir::new().unwrap();
+        let root = project.path();
+        for dir in ["Assets/UI", "Assets/Images", "Assets/Art/Resources/Icons", "Assets/Editor Default Resources"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("Assets/UI/Common.uss"), "").unwrap();
+        fs::write(root.join("Assets/Images/bg.png"), "").unwrap();
+        fs::write(root.join("Assets/Art/Resources/Icons/star.png"), "").unwrap();
+        fs::write(root.join("Assets/Editor Default Resources/tool.png"), "").unwrap();
+
+        let content = r#"@import "Common.uss";
+@import url("Missing.uss");
+.a { background-image: url("../Images/bg.png"); }
+.b { background-image: resource("Icons/star"); }
+.c { background-image: resource("tool.png"); }
+.d { background-image: url("../Images/gone.png"); }
+.e { background-image: resource("Icons/moon"); }
+"#;
+        let tree = UssParser::new().unwrap().parse(content, None).unwrap();
+        let source_url = Url::parse("project:///Assets/UI/Main.uss").unwrap();
+        let resolver = UrlReferenceResolver::new(root.to_path_buf());
+        let file = resolver.file_references(&tree, content, &source_url);
+
+        assert_eq!(file.path, "Assets/UI/Main.uss");
+        let summary: Vec<_> = file
+            .references
+            .iter()
+            .map(|r| (r.kind, r.status, r.path.as_deref().unwrap_or_default()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (UrlReferenceKind::Import, ResolutionStatus::Resolved, "Assets/UI/Common.uss"),
+                (UrlReferenceKind::Import, ResolutionStatus::Missing, "Assets/UI/Missing.uss"),
+                (UrlReferenceKind::Url, ResolutionStatus::Resolved, "Assets/Images/bg.png"),
+                (UrlReferenceKind::Resource, ResolutionStatus::Resolved, "Assets/Art/Resources/Icons/star.png"),
+                (UrlReferenceKind::Resource, ResolutionStatus::Resolved, "Assets/Editor Default Resources/tool.png"),
+                (UrlReferenceKind::Url, ResolutionStatus::Missing, "Assets/Images/gone.png"),
+                (UrlReferenceKind::Resource, ResolutionStatus::Missing, "Assets/Resources/Icons/moon"),
+            ]
+        );
+        assert_eq!(file.references[2].text, r#"url("../Images/bg.png")"#);
+
+        let mut report = UrlReferencesReport::default();
+        report.add(file);
+        assert_eq!(report.unresolved, 3);
+    }
+}
@@ -17,6 +17,66 @@ impl UssStringError {
     }
 }
 
+/// Extend a range to its whole line if nothing else is on the line, otherwise to the whitespace after it
+pub fn line_aware_range(content: &str, start: usize, end: usize) -> (usize, usize) {
+    let bytes = content.as_bytes();
+    let is_blank = |b: u8| b == b' ' || b == b'\t';
+
+    let mut line_start = start;
+    while line_start > 0 && is_blank(bytes[line_start - 1]) {
+        line_start -= 1;
+    }
+    let mut after = end;
+    while after < bytes.len() && is_blank(bytes[after]) {
+        after += 1;
+    }
+
+    let starts_line = line_start == 0 || bytes[line_start - 1] == b'\n';
+    let ends_line = after == bytes.len() || bytes[after] == b'\n' || bytes[after] == b'\r';
+    if starts_line && ends_line {
+        // remove the line break too
+        let line_end = match content[after..].find('\n') {
+            Some(newline) => after + newline + 1,
+            None => after,
+        };
+        (line_start, line_end)
+    } else {
+        (start, after)
+    }
+}
+
+/// The name an identifier stands for, with its escapes resolved like in strings
+///
+/// e.g. `hover\:bg-red` and `hover\3A bg-red` are `hover:bg-red`, `\31 0` is `10`. An identifier with an
+/// invalid escape is returned as written.
+pub fn unescape_identifier(text: &str) -> String {
+    if !text.contains('\\') {
+        return text.to_string();
+    }
+    let chars: Vec<char> = text.chars().collect();
+    unescape(&chars).unwrap_or_else(|_| text.to_string())
+}
+
+/// An identifier for a name, with the characters that can't be written as they are escaped
+///
+/// e.g. `hover:bg-red` is `hover\:bg-red` and `10` is `\31 0`. Characters that aren't ASCII are kept.
+pub fn escape_identifier(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    for (index, ch) in name.chars().enumerate() {
+        // a digit can't start an identifier, and neither can a hyphen followed by one
+        let leading_digit = ch.is_ascii_digit() && (index == 0 || (index == 1 && name.starts_with('-')));
+        if leading_digit || ch.is_control() || ch.is_whitespace() {
+            result.push_str(&format!("\\{:X} ", ch as u32));
+        } else if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' || !ch.is_ascii() {
+            result.push(ch);
+        } else {
+            result.push('\\');
+            result.push(ch);
+        }
+    }
+    result
+}
+
 /// Convert a USS string literal (including quotes) to its actual string value.
 /// 
 /// This function handles:
@@ -70,7 +130,11 @@ pub fn convert_uss_string(input: &str) -> Result<String, UssStringError> {
     }
 
     // Extract content between quotes
-    let content = &chars[1..chars.len() - 1];
+    unescape(&chars[1..chars.len() - 1])
+}
+
+/// Resolve the escapes of the content of a string or of an identifier
+fn unescape(content: &[char]) -> Result<String, UssStringError> {
     let mut result = String::new();
     let mut i = 0;
 
@@ -238,6 +302,24 @@ more\"").unwrap(), "testmore");
         assert_eq!(convert_uss_string(r#""te\73 t""#).unwrap(), "test");
     }
 
+    #[test]
+    fn test_escape_identifiers() {
+        assert_eq!(unescape_identifier(r"hover\:bg-red"), "hover:bg-red");
+        assert_eq!(unescape_identifier(r"hover\3A bg-red"), "hover:bg-red");
+        assert_eq!(unescape_identifier(r"\31 0"), "10");
+        assert_eq!(unescape_identifier("café"), "café");
+        // invalid escapes are kept
+        assert_eq!(unescape_identifier(r"a\0 b"), r"a\0 b");
+
+        assert_eq!(escape_identifier("hover:bg-red"), r"hover\:bg-red");
+        assert_eq!(escape_identifier("10"), r"\31 0");
+        assert_eq!(escape_identifier("w-1/2"), r"w-1\/2");
+        assert_eq!(escape_identifier("café"), "café");
+        for name in ["hover:bg-red", "10", "-1", "a b", "w-1/2.5"] {
+            assert_eq!(unescape_identifier(&escape_identifier(name)), name);
+        }
+    }
+
     #[test]
     fn test_error_cases() {
         // Missing quotes
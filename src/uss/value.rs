@@ -2,6 +2,7 @@ use tree_sitter::Node;
 use url::Url;
 
 use crate::language::asset_url::{validate_url, validate_url_complete};
+use crate::language::spelling::closest_match;
 use crate::uss::uss_utils::convert_uss_string;
 use crate::uss::definitions::UssDefinitions;
 use crate::uss::color::Color;
@@ -24,6 +25,10 @@ pub struct UssValueError {
     pub message: String,
     /// Diagnostic severity level
     pub severity: tower_lsp::lsp_types::DiagnosticSeverity,
+    /// Diagnostic code
+    pub code: &'static str,
+    /// Text that likely was meant instead of the node, e.g. a unit for a misspelled unit
+    pub suggestion: Option<&'static str>,
 }
 
 impl UssValueError {
@@ -38,6 +43,52 @@ impl UssValueError {
             byte_range: (node.start_byte(), node.end_byte()),
             message,
             severity: tower_lsp::lsp_types::DiagnosticSeverity::ERROR,
+            code: "invalid-value",
+            suggestion: None,
+        }
+    }
+
+    /// Error of a unit that doesn't exist, listing the units of the kind it is closest to
+    fn invalid_unit(unit_node: Node, content: &str, unit: &str) -> Self {
+        let lower = unit.to_ascii_lowercase();
+        let all_units: Vec<&str> = [LENGTH_UNITS, ANGLE_UNITS, TIME_UNITS].concat();
+        let suggestion = UNIT_ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == lower)
+            .map(|(_, unit)| *unit)
+            .or_else(|| all_units.iter().find(|candidate| **candidate == lower).copied())
+            .or_else(|| closest_match(unit, &all_units));
+
+        let message = match suggestion {
+            Some(suggestion) => {
+                let (kind, units) = if ANGLE_UNITS.contains(&suggestion) {
+                    ("angle", ANGLE_UNITS)
+                } else if TIME_UNITS.contains(&suggestion) {
+                    ("time", TIME_UNITS)
+                } else {
+                    ("length", LENGTH_UNITS)
+                };
+                format!(
+                    "Invalid unit '{}', {} units are: {}. Did you mean '{}'?",
+                    unit,
+                    kind,
+                    units.join(", "),
+                    suggestion
+                )
+            }
+            None => format!(
+                "Invalid unit '{}'. Valid units are: {}, {}, {}",
+                unit,
+                LENGTH_UNITS.join(", "),
+                ANGLE_UNITS.join(", "),
+                TIME_UNITS.join(", ")
+            ),
+        };
+
+        Self {
+            code: "invalid-unit",
+            suggestion,
+            ..Self::new(unit_node, content, message)
         }
     }
 }
@@ -97,8 +148,7 @@ impl UssValue {
             }
             
             // Check if the numeric value has a unit - color functions expect unitless numbers
-            if arg_node.child_count() > 0 {
-                let child = arg_node.child(0).unwrap();
+            if let Some(child) = arg_node.child(0) {
                 if child.kind() == NODE_UNIT {
                     let unit_text = child.utf8_text(content.as_bytes())
                         .map_err(|_| UssValueError::new(child, content, "Invalid UTF-8 in unit text".to_string()))?;
@@ -145,10 +195,12 @@ impl UssValue {
                 let has_fractional = node_kind == NODE_FLOAT_VALUE;
                 
                 // Check for unit child - must have 0 or 1 child, otherwise it's malformed
+                let unit_node = node.child(0);
                 let unit = match node.child_count() {
                     0 => None,
                     1 => {
-                        let child = node.child(0).unwrap();
+                        let child = node.child(0)
+                            .ok_or_else(|| UssValueError::new(node, content, "Missing unit child".to_string()))?;
                         if child.kind() == NODE_UNIT {
                             child.utf8_text(content.as_bytes())
                                 .map_err(|_| UssValueError::new(child, content, "Invalid UTF-8 in unit text".to_string()))?
@@ -177,9 +229,9 @@ impl UssValue {
                     .map_err(|_| UssValueError::new(node, content, format!("Cannot parse '{}' as numeric value", value_text)))?;
                 
                 // Validate unit if present
-                if let Some(unit_str) = &unit {
+                if let (Some(unit_str), Some(unit_node)) = (&unit, unit_node) {
                     if !definitions.is_valid_unit(unit_str) {
-                        return Err(UssValueError::new(node, content, format!("Invalid unit '{}'. Valid units are: px, %, deg, rad, grad, turn, s, ms", unit_str)));
+                        return Err(UssValueError::invalid_unit(unit_node, content, unit_str));
                     }
                 }
                 
@@ -34,6 +34,38 @@ pub enum ValueType {
     PropertyName
 }
 
+impl std::fmt::Display for ValueType {
+    /// How the type is written in messages, a keyword as itself and other types in CSS syntax, e.g. `<length>`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueType::LengthPercent => f.write_str("<length-percentage>"),
+            ValueType::Length => f.write_str("<length>"),
+            ValueType::Number => f.write_str("<number>"),
+            ValueType::Integer => f.write_str("<integer>"),
+            ValueType::String => f.write_str("<string>"),
+            ValueType::Time => f.write_str("<time>"),
+            ValueType::Color => f.write_str("<color>"),
+            ValueType::Angle => f.write_str("<angle>"),
+            ValueType::Keyword(keyword) => f.write_str(keyword),
+            ValueType::Asset => f.write_str("url()/resource()"),
+            ValueType::PropertyName => f.write_str("<property-name>"),
+        }
+    }
+}
+
+impl ValueType {
+    /// Units of values of this type, empty if it has none
+    pub fn units(&self) -> &'static [&'static str] {
+        match self {
+            ValueType::LengthPercent => LENGTH_UNITS,
+            ValueType::Length => &[UNIT_PX],
+            ValueType::Time => TIME_UNITS,
+            ValueType::Angle => ANGLE_UNITS,
+            _ => &[],
+        }
+    }
+}
+
 /// one value entry of property
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct ValueEntry {
@@ -154,6 +186,21 @@ impl ValueFormat {
         }
     }
 
+    /// The entry of a format that is one entry repeated, e.g. `<number> <number> <number>`
+    fn repeated_entry(&self) -> Option<&ValueEntry> {
+        let first = self.entries.first()?;
+        self.entries.iter().all(|entry| entry == first).then_some(first)
+    }
+
+    /// Number of leading values that match the entries of this format
+    fn matched_prefix_len(&self, values: &[UssValue], definitions: &UssDefinitions) -> usize {
+        values
+            .iter()
+            .zip(&self.entries)
+            .take_while(|(value, entry)| self.is_value_valid(value, entry, definitions))
+            .count()
+    }
+
     /// Check if a subset of values can match any subset of format entries
     /// This is used for flexible matching when CSS variables are present
     fn can_match_subset_values(&self, values: &[&UssValue], definitions: &UssDefinitions) -> bool {
@@ -196,10 +243,10 @@ impl ValueFormat {
             UssValue::Numeric { unit: Some(unit_str), has_fractional: _, .. } => {
                   // Check if this numeric value matches the expected type based on unit
                   match value_type {
-                      ValueType::LengthPercent => unit_str == UNIT_PX || unit_str == UNIT_PERCENT,
+                      ValueType::LengthPercent => LENGTH_UNITS.contains(&unit_str.as_str()),
                       ValueType::Length => unit_str == UNIT_PX, // Length only accepts px units, no percentage
-                      ValueType::Time => unit_str == UNIT_S || unit_str == UNIT_MS,
-                      ValueType::Angle => matches!(unit_str.as_str(), UNIT_DEG | UNIT_RAD | UNIT_GRAD | UNIT_TURN),
+                      ValueType::Time => TIME_UNITS.contains(&unit_str.as_str()),
+                      ValueType::Angle => ANGLE_UNITS.contains(&unit_str.as_str()),
                       ValueType::Number => false, // Numbers with units don't match Number type
                       ValueType::Integer => false, // Integers with units don't match Integer type
                       _ => false,
@@ -251,8 +298,36 @@ impl ValueFormat {
     }
 }
 
+/// Syntax of an entry, in brackets if it has several options and `grouped` is set
+fn entry_syntax(entry: &ValueEntry, grouped: bool) -> String {
+    let options = entry.options.iter().map(|option| option.to_string()).collect::<Vec<_>>().join(" | ");
+    if grouped && entry.options.len() > 1 {
+        format!("[ {} ]", options)
+    } else {
+        options
+    }
+}
+
+/// Syntax of a group repeated `min` to `max` times
+fn multiplied(group: &str, min: usize, max: usize) -> String {
+    match (min, max) {
+        (1, 1) => group.to_string(),
+        (min, max) if min == max => format!("{}{{{}}}", group, min),
+        (min, max) => format!("{}{{{},{}}}", group, min, max),
+    }
+}
+
+/// The first value of a value that no format of a [`ValueSpec`] accepts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueMismatch {
+    /// Index of the value
+    pub index: usize,
+    /// Types accepted at the index by the closest formats, empty if none of them has a value there
+    pub expected: Vec<ValueType>,
+}
+
 /// Complete value specification for a property
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ValueSpec {
     /// All possible value formats for this property
     pub formats: Vec<ValueFormat>,
@@ -349,4 +424,146 @@ impl ValueSpec {
     pub(crate) fn is_single_format_and_entry(&self) -> bool {
         self.formats.len() == 1 && self.formats[0].entries.len() == 1
     }
+
+    /// The value definition syntax of the values this spec accepts, e.g. `<length-percentage>{1,4} | auto`
+    ///
+    /// Formats that repeat a single entry are combined into a multiplier, everything else is listed as
+    /// alternatives, so the syntax describes exactly the accepted values but may be longer than a hand-written one.
+    pub fn syntax(&self) -> String {
+        let mut alternatives: Vec<String> = Vec::new();
+        let mut repeated: Vec<&ValueEntry> = Vec::new();
+        for format in &self.formats {
+            let alternative = match format.repeated_entry() {
+                Some(entry) if repeated.contains(&entry) => continue,
+                Some(entry) => {
+                    repeated.push(entry);
+                    let mut counts: Vec<usize> = self
+                        .formats
+                        .iter()
+                        .filter(|other| other.repeated_entry() == Some(entry))
+                        .map(|other| other.entries.len())
+                        .collect();
+                    counts.sort();
+                    counts.dedup();
+                    let group = entry_syntax(entry, true);
+                    let (min, max) = (counts[0], counts[counts.len() - 1]);
+                    if max == 1 {
+                        entry_syntax(entry, false)
+                    } else if max - min + 1 == counts.len() {
+                        multiplied(&group, min, max)
+                    } else {
+                        counts.iter().map(|&count| multiplied(&group, count, count)).collect::<Vec<_>>().join(" | ")
+                    }
+                }
+                None => format.entries.iter().map(|entry| entry_syntax(entry, true)).collect::<Vec<_>>().join(" "),
+            };
+            if !alternatives.contains(&alternative) {
+                alternatives.push(alternative);
+            }
+        }
+
+        let syntax = alternatives.join(" | ");
+        if !self.allows_multiple_values {
+            syntax
+        } else if syntax.contains(' ') {
+            format!("[ {} ]#", syntax)
+        } else {
+            format!("{}#", syntax)
+        }
+    }
+
+    /// Whether a keyword is accepted anywhere in a value
+    pub fn accepts_keyword(&self, keyword: &str) -> bool {
+        self.formats
+            .iter()
+            .flat_map(|format| &format.entries)
+            .flat_map(|entry| &entry.options)
+            .any(|option| matches!(option, ValueType::Keyword(k) if *k == keyword))
+    }
+
+    /// Where a value that matches no format stops matching
+    ///
+    /// The closest formats are the ones that match the most leading values, the value after those is the
+    /// mismatch. `None` if a format matches, if the value has variables (they can stand for any number of values)
+    /// or if the values match the closest formats but some are missing, there is no value to point at then.
+    pub fn first_mismatch(&self, values: &[UssValue], definitions: &UssDefinitions) -> Option<ValueMismatch> {
+        if values.iter().any(|value| matches!(value, UssValue::VariableReference(_)))
+            || self.formats.iter().any(|format| format.is_match(values, definitions))
+        {
+            return None;
+        }
+
+        let prefix_lens: Vec<usize> =
+            self.formats.iter().map(|format| format.matched_prefix_len(values, definitions)).collect();
+        let index = prefix_lens.iter().copied().max()?;
+        if index >= values.len() {
+            return None;
+        }
+
+        let mut expected = Vec::new();
+        for (format, _) in self.formats.iter().zip(&prefix_lens).filter(|(_, len)| **len == index) {
+            for option in format.entries.get(index).map_or(&[][..], |entry| &entry.options) {
+                if !expected.contains(option) {
+                    expected.push(*option);
+                }
+            }
+        }
+        Some(ValueMismatch { index, expected })
+    }
+
+    /// Types accepted at `index` by the formats that have a value there, in spec order without duplicates
+    pub fn types_at(&self, index: usize) -> Vec<ValueType> {
+        let mut types = Vec::new();
+        for entry in self.formats.iter().filter_map(|format| format.entries.get(index)) {
+            for option in &entry.options {
+                if !types.contains(option) {
+                    types.push(*option);
+                }
+            }
+        }
+        types
+    }
+
+    /// Types accepted after `values` by the formats whose leading entries they match, in spec order without duplicates
+    ///
+    /// A variable can stand for any number of values, so with one all the types accepted at that index are returned.
+    pub fn types_after(&self, values: &[UssValue], definitions: &UssDefinitions) -> Vec<ValueType> {
+        if values.iter().any(|value| matches!(value, UssValue::VariableReference(_))) {
+            return self.types_at(values.len());
+        }
+
+        let mut types = Vec::new();
+        for format in self.formats.iter().filter(|format| format.matched_prefix_len(values, definitions) == values.len()) {
+            for option in format.entries.get(values.len()).map_or(&[][..], |entry| &entry.options) {
+                if !types.contains(option) {
+                    types.push(*option);
+                }
+            }
+        }
+        types
+    }
+
+    /// Whether a format has more than `count` values, so a value with `count` values can go on
+    pub fn accepts_more_than(&self, count: usize) -> bool {
+        self.formats.iter().any(|format| format.entries.len() > count)
+    }
+
+    /// Keywords accepted at `index` of a value with `value_count` values, in spec order without duplicates
+    ///
+    /// `None` if no format has that many values, or if the position also accepts other types of values
+    /// (e.g. a length or a color), since a wrong identifier there isn't necessarily a misspelled keyword.
+    pub fn keywords_at(&self, index: usize, value_count: usize) -> Option<Vec<&'static str>> {
+        let mut keywords = Vec::new();
+        for format in self.formats.iter().filter(|format| format.entries.len() == value_count) {
+            for option in &format.entries.get(index)?.options {
+                let ValueType::Keyword(keyword) = option else {
+                    return None;
+                };
+                if !keywords.contains(keyword) {
+                    keywords.push(*keyword);
+                }
+            }
+        }
+        if keywords.is_empty() { None } else { Some(keywords) }
+    }
 }
\ No newline at end of file
@@ -2,7 +2,7 @@
 //!
 //! Contains unit tests for ValueType, ValueEntry, ValueFormat, and ValueSpec.
 
-use crate::uss::value_spec::{ValueFormat, ValueType};
+use crate::uss::value_spec::{ValueEntry, ValueFormat, ValueMismatch, ValueSpec, ValueType};
 use crate::uss::value::UssValue;
 use crate::uss::definitions::UssDefinitions;
 use crate::uss::constants::*;
@@ -332,4 +332,115 @@ fn test_property_name_format() {
     // non existing property
     let values = vec![UssValue::Identifier("non-existing-property".to_string())];
     assert!(!property_format.is_match(&values, &definitions));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_keywords_at() {
+    use crate::uss::value_spec::ValueSpec;
+
+    let spec = ValueSpec::keywords(&["flex", "none", "flex"]);
+    assert_eq!(spec.keywords_at(0, 1), Some(vec!["flex", "none"]));
+    assert_eq!(spec.keywords_at(0, 2), None);
+
+    // a position that also takes lengths is not keyword only
+    let spec = ValueSpec::one_of(vec![ValueType::Keyword("auto"), ValueType::LengthPercent]);
+    assert_eq!(spec.keywords_at(0, 1), None);
+}
+
+#[test]
+fn test_types_at() {
+    use crate::uss::value_spec::ValueSpec;
+
+    // 1 to 4 lengths
+    let spec = ValueSpec::repeat(ValueType::LengthPercent, 1, 4);
+    assert_eq!(spec.types_at(3), vec![ValueType::LengthPercent]);
+    assert!(spec.types_at(4).is_empty());
+    assert!(spec.accepts_more_than(3));
+    assert!(!spec.accepts_more_than(4));
+
+    let spec = ValueSpec::sequence(vec![ValueType::Time, ValueType::Angle]);
+    assert_eq!(spec.types_at(1), vec![ValueType::Angle]);
+    assert_eq!(spec.types_at(1)[0].units(), ANGLE_UNITS);
+    assert!(ValueType::Number.units().is_empty());
+}
+
+#[test]
+fn test_types_after() {
+    let definitions = UssDefinitions::new();
+    let spec = ValueSpec::new(vec![
+        ValueFormat::sequence(vec![ValueType::LengthPercent, ValueType::Color]),
+        ValueFormat::sequence(vec![ValueType::Time, ValueType::Keyword("ease"), ValueType::Time]),
+    ]);
+    let length = UssValue::Numeric { value: 2.0, unit: Some(UNIT_PX.to_string()), has_fractional: false };
+    let time = UssValue::Numeric { value: 1.0, unit: Some(UNIT_S.to_string()), has_fractional: false };
+
+    // only the formats the values before match
+    assert_eq!(spec.types_after(&[length.clone()], &definitions), vec![ValueType::Color]);
+    assert_eq!(spec.types_after(&[time.clone()], &definitions), vec![ValueType::Keyword("ease")]);
+    assert_eq!(spec.types_after(&[], &definitions), vec![ValueType::LengthPercent, ValueType::Time]);
+    assert!(spec.types_after(&[length.clone(), length], &definitions).is_empty());
+
+    // a variable can be any number of values
+    let variable = UssValue::VariableReference("duration".to_string());
+    assert_eq!(spec.types_after(&[variable], &definitions), vec![ValueType::Color, ValueType::Keyword("ease")]);
+}
+
+#[test]
+fn test_first_mismatch() {
+    let definitions = UssDefinitions::new();
+    let spec = ValueSpec::new(vec![
+        ValueFormat::sequence(vec![ValueType::LengthPercent, ValueType::Color]),
+        ValueFormat::sequence(vec![ValueType::LengthPercent, ValueType::LengthPercent, ValueType::Color]),
+        ValueFormat::keywords(&["none"]),
+    ]);
+    let length = UssValue::Numeric { value: 2.0, unit: Some(UNIT_PX.to_string()), has_fractional: false };
+    let keyword = UssValue::Identifier("auto".to_string());
+
+    // the second value fits neither format that accepts the first
+    let mismatch = spec.first_mismatch(&[length.clone(), keyword.clone()], &definitions).unwrap();
+    assert_eq!(mismatch, ValueMismatch { index: 1, expected: vec![ValueType::Color, ValueType::LengthPercent] });
+
+    // the first value fits no format
+    let mismatch = spec.first_mismatch(&[keyword.clone()], &definitions).unwrap();
+    assert_eq!(mismatch.index, 0);
+    assert_eq!(mismatch.expected, vec![ValueType::LengthPercent, ValueType::Keyword("none")]);
+
+    // a value after the longest format
+    let color = UssValue::Identifier("red".to_string());
+    let values = [length.clone(), length.clone(), color.clone(), color.clone()];
+    assert_eq!(spec.first_mismatch(&values, &definitions), Some(ValueMismatch { index: 3, expected: vec![] }));
+
+    // matching, missing values and variables have no value to point at
+    assert_eq!(spec.first_mismatch(&[length.clone(), color], &definitions), None);
+    assert_eq!(spec.first_mismatch(&[length.clone()], &definitions), None);
+    let variable = UssValue::VariableReference("size".to_string());
+    assert_eq!(spec.first_mismatch(&[variable, keyword], &definitions), None);
+}
+
+#[test]
+fn test_syntax() {
+    let repeated = ValueSpec::repeat(ValueType::LengthPercent, 1, 4);
+    assert_eq!(repeated.syntax(), "<length-percentage>{1,4}");
+
+    let mut spec = ValueSpec::new(vec![
+        ValueFormat::keywords(&["none"]),
+        ValueFormat::sequence(vec![ValueType::Number, ValueType::Number]),
+        ValueFormat::one_of(vec![ValueType::LengthPercent, ValueType::Keyword("auto")]),
+        ValueFormat::sequence(vec![ValueType::Asset, ValueType::Integer]),
+        ValueFormat::keywords(&["none"]),
+    ]);
+    assert_eq!(spec.syntax(), "none | <number>{2} | <length-percentage> | auto | url()/resource() <integer>");
+
+    spec.allows_multiple_values = true;
+    assert_eq!(spec.syntax(), "[ none | <number>{2} | <length-percentage> | auto | url()/resource() <integer> ]#");
+    assert_eq!(ValueSpec::new_with_multiple(vec![ValueFormat::single(ValueType::Time)], true).syntax(), "<time>#");
+
+    // counts that aren't a range
+    let spec = ValueSpec::new(vec![
+        ValueFormat::sequence(vec![ValueType::Number]),
+        ValueFormat::sequence(vec![ValueType::Number; 3]),
+        ValueFormat::one_of(vec![ValueType::Color, ValueType::Keyword("none")]),
+        ValueFormat { entries: vec![ValueEntry::options(vec![ValueType::Color, ValueType::Keyword("none")]); 2] },
+    ]);
+    assert_eq!(spec.syntax(), "<number> | <number>{3} | [ <color> | none ]{1,2}");
+}
@@ -0,0 +1,554 @@
+//! CSS Value Definition Syntax
+//!
+//! Parses value definition syntax strings as used in the `format` of property definitions
+//! (e.g. `<length>{1,4}`, `a || b`, `[ x | y ]#`) and generates a `ValueSpec` from them,
+//! so value specs don't have to be built by hand for every property.
+//!
+//! Supported syntax:
+//! - keywords (`auto`), data types (`<length>`, `<length-percentage [0,∞]>`) and property references (`<'flex-grow'>`)
+//! - combinators: juxtaposition, `&&`, `||` and `|`, with the standard CSS precedence
+//! - groups `[ ]`, including the `!` (at least one value) suffix
+//! - multipliers `?`, `{n}`, `{n,m}`, and `#` at the top level (comma-separated values)
+//!
+//! Unbounded multipliers (`*`, `+`, `{n,}`) and literal commas can't be expressed as a finite set of formats
+//! and are reported as errors.
+
+use thiserror::Error;
+
+use crate::uss::property_data::TIMING_FUN;
+use crate::uss::value_spec::{ValueEntry, ValueFormat, ValueSpec, ValueType};
+
+/// Upper bound of formats generated for a single syntax, protects against combinatorial explosion
+const MAX_FORMATS: usize = 4096;
+
+/// Errors that can occur when parsing or expanding a value definition syntax
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ValueSyntaxError {
+    #[error("Unexpected '{found}' at position {position}")]
+    UnexpectedToken { found: String, position: usize },
+    #[error("Unexpected end of syntax")]
+    UnexpectedEnd,
+    #[error("Unknown data type <{0}>")]
+    UnknownDataType(String),
+    #[error("Unknown property reference <'{0}'>")]
+    UnknownProperty(String),
+    #[error("Unsupported syntax: {0}")]
+    Unsupported(String),
+    #[error("Syntax expands to more than {MAX_FORMATS} formats")]
+    TooManyFormats,
+}
+
+/// A node of a parsed value definition syntax
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxNode {
+    /// A literal keyword, eg. `auto`
+    Keyword(&'static str),
+    /// A data type, eg. `length` for `<length>`
+    DataType(&'static str),
+    /// A reference to the syntax of another property, eg. `flex-grow` for `<'flex-grow'>`
+    PropertyRef(&'static str),
+    /// All nodes in this order (juxtaposition)
+    Sequence(Vec<SyntaxNode>),
+    /// All nodes in any order (`&&`)
+    AllOf(Vec<SyntaxNode>),
+    /// One or more of the nodes in any order (`||`)
+    AnyOf(Vec<SyntaxNode>),
+    /// Exactly one of the nodes (`|`)
+    OneOf(Vec<SyntaxNode>),
+    /// The node repeated `min` to `max` times
+    Repeat { node: Box<SyntaxNode>, min: usize, max: usize },
+    /// A group that must produce at least one value (`[ ]!`)
+    NonEmpty(Box<SyntaxNode>),
+    /// Comma-separated list of the node (`#`)
+    CommaList(Box<SyntaxNode>),
+}
+
+impl SyntaxNode {
+    /// Parse a value definition syntax string
+    pub fn parse(syntax: &'static str) -> Result<Self, ValueSyntaxError> {
+        let tokens = tokenize(syntax)?;
+        let mut parser = Parser { tokens, index: 0 };
+        let node = parser.parse_one_of()?;
+        match parser.peek() {
+            None => Ok(node),
+            Some((token, position)) => Err(ValueSyntaxError::UnexpectedToken {
+                found: token.to_string(),
+                position: *position,
+            }),
+        }
+    }
+}
+
+/// Generate a value spec from a value definition syntax string
+///
+/// Property references are not supported, use `value_spec_from_format_with` to resolve them.
+pub fn value_spec_from_format(syntax: &'static str) -> Result<ValueSpec, ValueSyntaxError> {
+    value_spec_from_format_with(syntax, &|_| None)
+}
+
+/// Generate a value spec from a value definition syntax string,
+/// resolving property references (`<'name'>`) to the syntax of that property with `property_format`
+pub fn value_spec_from_format_with(
+    syntax: &'static str,
+    property_format: &dyn Fn(&str) -> Option<&'static str>,
+) -> Result<ValueSpec, ValueSyntaxError> {
+    let node = SyntaxNode::parse(syntax)?;
+    let (node, allows_multiple_values) = match node {
+        SyntaxNode::CommaList(inner) => (*inner, true),
+        node => (node, false),
+    };
+
+    let mut formats: Vec<ValueFormat> = expand(&node, property_format)?
+        .into_iter()
+        .filter(|entries| !entries.is_empty())
+        .map(|entries| ValueFormat { entries })
+        .collect();
+    formats.sort();
+    formats.dedup();
+
+    Ok(ValueSpec::new_with_multiple(formats, allows_multiple_values))
+}
+
+/// Value types accepted by a data type, `None` if the data type is unknown
+pub fn data_type_options(name: &str) -> Option<Vec<ValueType>> {
+    let options = match name {
+        // Unity's <length> accepts percentages as well
+        "length" | "percentage" | "length-percentage" | "x-offset" | "y-offset" | "blur-radius" => {
+            vec![ValueType::LengthPercent]
+        }
+        "number" => vec![ValueType::Number],
+        "integer" => vec![ValueType::Integer],
+        "string" => vec![ValueType::String],
+        "color" => vec![ValueType::Color],
+        "angle" => vec![ValueType::Angle],
+        "time" | "duration" | "delay" => vec![ValueType::Time],
+        "resource" | "url" => vec![ValueType::Asset],
+        "property" => vec![ValueType::PropertyName],
+        "easing-function" | "timing-function" => {
+            TIMING_FUN.iter().map(|&k| ValueType::Keyword(k)).collect()
+        }
+        _ => return None,
+    };
+    Some(options)
+}
+
+type Expansion = Vec<Vec<ValueEntry>>;
+
+/// Expand a syntax node into all the entry sequences it accepts
+fn expand(
+    node: &SyntaxNode,
+    property_format: &dyn Fn(&str) -> Option<&'static str>,
+) -> Result<Expansion, ValueSyntaxError> {
+    let result = match node {
+        SyntaxNode::Keyword(keyword) => vec![vec![ValueEntry::keywords(&[keyword])]],
+        SyntaxNode::DataType(name) => {
+            let options = data_type_options(name)
+                .ok_or_else(|| ValueSyntaxError::UnknownDataType(name.to_string()))?;
+            vec![vec![ValueEntry::options(options)]]
+        }
+        SyntaxNode::PropertyRef(name) => {
+            let syntax = property_format(name)
+                .ok_or_else(|| ValueSyntaxError::UnknownProperty(name.to_string()))?;
+            expand(&SyntaxNode::parse(syntax)?, property_format)?
+        }
+        SyntaxNode::Sequence(nodes) => {
+            let mut result = vec![Vec::new()];
+            for node in nodes {
+                result = product(&result, &expand(node, property_format)?)?;
+            }
+            result
+        }
+        SyntaxNode::AllOf(nodes) => {
+            let expansions = nodes
+                .iter()
+                .map(|n| expand(n, property_format))
+                .collect::<Result<Vec<_>, _>>()?;
+            let all: Vec<usize> = (0..expansions.len()).collect();
+            ordered_products(&expansions, &permutations(&all))?
+        }
+        SyntaxNode::AnyOf(nodes) => {
+            let expansions = nodes
+                .iter()
+                .map(|n| expand(n, property_format))
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut orders = Vec::new();
+            for subset in non_empty_subsets(expansions.len()) {
+                orders.extend(permutations(&subset));
+            }
+            ordered_products(&expansions, &orders)?
+        }
+        SyntaxNode::OneOf(nodes) => {
+            // alternatives that are a single value are merged into one entry,
+            // so `a | b | <length>` becomes one entry with three options
+            let mut merged: Vec<ValueType> = Vec::new();
+            let mut result = Vec::new();
+            for node in nodes {
+                let expansion = expand(node, property_format)?;
+                if expansion.len() == 1 && expansion[0].len() == 1 {
+                    for option in &expansion[0][0].options {
+                        if !merged.contains(option) {
+                            merged.push(*option);
+                        }
+                    }
+                } else {
+                    result.extend(expansion);
+                }
+            }
+            if !merged.is_empty() {
+                result.insert(0, vec![ValueEntry::options(merged)]);
+            }
+            result
+        }
+        SyntaxNode::Repeat { node, min, max } => {
+            let expansion = expand(node, property_format)?;
+            let mut result = Vec::new();
+            let mut current = vec![Vec::new()];
+            for count in 0..=*max {
+                if count >= *min {
+                    result.extend(current.iter().cloned());
+                }
+                if count < *max {
+                    current = product(&current, &expansion)?;
+                }
+            }
+            result
+        }
+        SyntaxNode::NonEmpty(node) => {
+            let mut expansion = expand(node, property_format)?;
+            expansion.retain(|entries| !entries.is_empty());
+            expansion
+        }
+        SyntaxNode::CommaList(_) => {
+            return Err(ValueSyntaxError::Unsupported(
+                "'#' is only supported for the whole syntax".to_string(),
+            ));
+        }
+    };
+
+    if result.len() > MAX_FORMATS {
+        return Err(ValueSyntaxError::TooManyFormats);
+    }
+    Ok(result)
+}
+
+/// Every combination of a sequence from `a` followed by a sequence from `b`
+fn product(a: &Expansion, b: &Expansion) -> Result<Expansion, ValueSyntaxError> {
+    if a.len() * b.len() > MAX_FORMATS {
+        return Err(ValueSyntaxError::TooManyFormats);
+    }
+
+    let mut result = Vec::with_capacity(a.len() * b.len());
+    for first in a {
+        for second in b {
+            let mut entries = first.clone();
+            entries.extend(second.iter().cloned());
+            result.push(entries);
+        }
+    }
+    Ok(result)
+}
+
+/// Products of the expansions in each of the given orders
+fn ordered_products(expansions: &[Expansion], orders: &[Vec<usize>]) -> Result<Expansion, ValueSyntaxError> {
+    let mut result = Vec::new();
+    for order in orders {
+        let mut current = vec![Vec::new()];
+        for &index in order {
+            current = product(&current, &expansions[index])?;
+        }
+        result.extend(current);
+        if result.len() > MAX_FORMATS {
+            return Err(ValueSyntaxError::TooManyFormats);
+        }
+    }
+    Ok(result)
+}
+
+fn permutations(items: &[usize]) -> Vec<Vec<usize>> {
+    if items.len() <= 1 {
+        return vec![items.to_vec()];
+    }
+
+    let mut result = Vec::new();
+    for i in 0..items.len() {
+        let mut rest = items.to_vec();
+        let first = rest.remove(i);
+        for mut permutation in permutations(&rest) {
+            permutation.insert(0, first);
+            result.push(permutation);
+        }
+    }
+    result
+}
+
+fn non_empty_subsets(count: usize) -> Vec<Vec<usize>> {
+    (1..(1usize << count))
+        .map(|mask| (0..count).filter(|i| mask & (1 << i) != 0).collect())
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Keyword(&'static str),
+    DataType(&'static str),
+    PropertyRef(&'static str),
+    OpenBracket,
+    CloseBracket,
+    /// `|`
+    Bar,
+    /// `||`
+    DoubleBar,
+    /// `&&`
+    DoubleAmpersand,
+    Comma,
+    QuestionMark,
+    Asterisk,
+    Plus,
+    Hash,
+    ExclamationMark,
+    /// `{min,max}`, `max` is `None` if unbounded
+    Range(usize, Option<usize>),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Keyword(k) => write!(f, "{}", k),
+            Token::DataType(t) => write!(f, "<{}>", t),
+            Token::PropertyRef(p) => write!(f, "<'{}'>", p),
+            Token::OpenBracket => write!(f, "["),
+            Token::CloseBracket => write!(f, "]"),
+            Token::Bar => write!(f, "|"),
+            Token::DoubleBar => write!(f, "||"),
+            Token::DoubleAmpersand => write!(f, "&&"),
+            Token::Comma => write!(f, ","),
+            Token::QuestionMark => write!(f, "?"),
+            Token::Asterisk => write!(f, "*"),
+            Token::Plus => write!(f, "+"),
+            Token::Hash => write!(f, "#"),
+            Token::ExclamationMark => write!(f, "!"),
+            Token::Range(min, Some(max)) => write!(f, "{{{},{}}}", min, max),
+            Token::Range(min, None) => write!(f, "{{{},}}", min),
+        }
+    }
+}
+
+fn tokenize(syntax: &'static str) -> Result<Vec<(Token, usize)>, ValueSyntaxError> {
+    let bytes = syntax.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        let token = match bytes[i] {
+            b if b.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b'[' => Token::OpenBracket,
+            b']' => Token::CloseBracket,
+            b',' => Token::Comma,
+            b'?' => Token::QuestionMark,
+            b'*' => Token::Asterisk,
+            b'+' => Token::Plus,
+            b'#' => Token::Hash,
+            b'!' => Token::ExclamationMark,
+            b'|' if bytes.get(i + 1) == Some(&b'|') => {
+                i += 1;
+                Token::DoubleBar
+            }
+            b'|' => Token::Bar,
+            b'&' if bytes.get(i + 1) == Some(&b'&') => {
+                i += 1;
+                Token::DoubleAmpersand
+            }
+            b'<' => {
+                let end = syntax[i..].find('>').map(|e| i + e).ok_or(ValueSyntaxError::UnexpectedEnd)?;
+                let inner = syntax[i + 1..end].trim();
+                i = end;
+                if let Some(name) = inner.strip_prefix('\'').and_then(|n| n.strip_suffix('\'')) {
+                    Token::PropertyRef(name)
+                } else {
+                    // drop the range of a data type, eg. `[0,∞]` in `<length [0,∞]>`
+                    Token::DataType(inner.split_whitespace().next().unwrap_or(inner))
+                }
+            }
+            b'{' => {
+                let end = syntax[i..].find('}').map(|e| i + e).ok_or(ValueSyntaxError::UnexpectedEnd)?;
+                let inner = &syntax[i + 1..end];
+                let invalid = || ValueSyntaxError::UnexpectedToken {
+                    found: syntax[start..=end].to_string(),
+                    position: start,
+                };
+                let range = match inner.split_once(',') {
+                    None => {
+                        let count = inner.trim().parse().map_err(|_| invalid())?;
+                        Token::Range(count, Some(count))
+                    }
+                    Some((min, max)) => {
+                        let min = min.trim().parse().map_err(|_| invalid())?;
+                        let max = match max.trim() {
+                            "" => None,
+                            max => Some(max.parse().map_err(|_| invalid())?),
+                        };
+                        Token::Range(min, max)
+                    }
+                };
+                i = end;
+                range
+            }
+            b if b.is_ascii_alphanumeric() || b == b'-' || b == b'_' => {
+                while i + 1 < bytes.len()
+                    && (bytes[i + 1].is_ascii_alphanumeric() || bytes[i + 1] == b'-' || bytes[i + 1] == b'_')
+                {
+                    i += 1;
+                }
+                Token::Keyword(&syntax[start..=i])
+            }
+            _ => {
+                return Err(ValueSyntaxError::UnexpectedToken {
+                    found: syntax[start..].chars().next().map(String::from).unwrap_or_default(),
+                    position: start,
+                });
+            }
+        };
+
+        tokens.push((token, start));
+        i += 1;
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser, from lowest to highest precedence: `|`, `||`, `&&`, juxtaposition
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&(Token, usize)> {
+        self.tokens.get(self.index)
+    }
+
+    fn next_is(&self, token: &Token) -> bool {
+        self.peek().is_some_and(|(t, _)| t == token)
+    }
+
+    fn parse_one_of(&mut self) -> Result<SyntaxNode, ValueSyntaxError> {
+        self.parse_combined(&Token::Bar, SyntaxNode::OneOf, Self::parse_any_of)
+    }
+
+    fn parse_any_of(&mut self) -> Result<SyntaxNode, ValueSyntaxError> {
+        self.parse_combined(&Token::DoubleBar, SyntaxNode::AnyOf, Self::parse_all_of)
+    }
+
+    fn parse_all_of(&mut self) -> Result<SyntaxNode, ValueSyntaxError> {
+        self.parse_combined(&Token::DoubleAmpersand, SyntaxNode::AllOf, Self::parse_sequence)
+    }
+
+    fn parse_combined(
+        &mut self,
+        separator: &Token,
+        combine: fn(Vec<SyntaxNode>) -> SyntaxNode,
+        parse_operand: fn(&mut Self) -> Result<SyntaxNode, ValueSyntaxError>,
+    ) -> Result<SyntaxNode, ValueSyntaxError> {
+        let mut nodes = vec![parse_operand(self)?];
+        while self.next_is(separator) {
+            self.index += 1;
+            nodes.push(parse_operand(self)?);
+        }
+
+        if nodes.len() == 1 {
+            Ok(nodes.pop().unwrap())
+        } else {
+            Ok(combine(nodes))
+        }
+    }
+
+    fn parse_sequence(&mut self) -> Result<SyntaxNode, ValueSyntaxError> {
+        let mut nodes = Vec::new();
+        while let Some((token, _)) = self.peek() {
+            match token {
+                Token::Keyword(_) | Token::DataType(_) | Token::PropertyRef(_) | Token::OpenBracket | Token::Comma => {
+                    nodes.push(self.parse_multiplied()?);
+                }
+                _ => break,
+            }
+        }
+
+        match nodes.len() {
+            0 => match self.peek() {
+                Some((token, position)) => Err(ValueSyntaxError::UnexpectedToken {
+                    found: token.to_string(),
+                    position: *position,
+                }),
+                None => Err(ValueSyntaxError::UnexpectedEnd),
+            },
+            1 => Ok(nodes.pop().unwrap()),
+            _ => Ok(SyntaxNode::Sequence(nodes)),
+        }
+    }
+
+    fn parse_multiplied(&mut self) -> Result<SyntaxNode, ValueSyntaxError> {
+        let mut node = self.parse_term()?;
+
+        while let Some((token, _)) = self.peek() {
+            node = match token {
+                Token::QuestionMark => SyntaxNode::Repeat { node: Box::new(node), min: 0, max: 1 },
+                Token::Range(min, Some(max)) if min <= max => SyntaxNode::Repeat {
+                    node: Box::new(node),
+                    min: *min,
+                    max: *max,
+                },
+                Token::Hash => SyntaxNode::CommaList(Box::new(node)),
+                Token::ExclamationMark => SyntaxNode::NonEmpty(Box::new(node)),
+                Token::Asterisk | Token::Plus | Token::Range(_, _) => {
+                    return Err(ValueSyntaxError::Unsupported(format!(
+                        "unbounded or invalid multiplier '{}'",
+                        token
+                    )));
+                }
+                _ => break,
+            };
+            self.index += 1;
+        }
+
+        Ok(node)
+    }
+
+    fn parse_term(&mut self) -> Result<SyntaxNode, ValueSyntaxError> {
+        let Some((token, position)) = self.peek().cloned() else {
+            return Err(ValueSyntaxError::UnexpectedEnd);
+        };
+        self.index += 1;
+
+        match token {
+            Token::Keyword(keyword) => Ok(SyntaxNode::Keyword(keyword)),
+            Token::DataType(name) => Ok(SyntaxNode::DataType(name)),
+            Token::PropertyRef(name) => Ok(SyntaxNode::PropertyRef(name)),
+            Token::OpenBracket => {
+                let node = self.parse_one_of()?;
+                if !self.next_is(&Token::CloseBracket) {
+                    return match self.peek() {
+                        Some((token, position)) => Err(ValueSyntaxError::UnexpectedToken {
+                            found: token.to_string(),
+                            position: *position,
+                        }),
+                        None => Err(ValueSyntaxError::UnexpectedEnd),
+                    };
+                }
+                self.index += 1;
+                Ok(node)
+            }
+            Token::Comma => Err(ValueSyntaxError::Unsupported(format!(
+                "literal comma at position {}",
+                position
+            ))),
+            token => Err(ValueSyntaxError::UnexpectedToken {
+                found: token.to_string(),
+                position,
+            }),
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! Tests for value definition syntax parsing and value spec generation
+use std::collections::BTreeSet;
+
+use super::property_data::{add_initial_keyword, create_standard_properties, INACCURATE_FORMATS};
+use super::value_spec::{ValueSpec, ValueType};
+use super::value_syntax::*;
+
+/// All concrete type sequences a value spec accepts, used to compare specs by behavior rather than structure
+fn concrete_sequences(spec: &ValueSpec) -> BTreeSet<Vec<ValueType>> {
+    let mut result = BTreeSet::new();
+    for format in &spec.formats {
+        let mut sequences: Vec<Vec<ValueType>> = vec![Vec::new()];
+        for entry in &format.entries {
+            let mut next = Vec::new();
+            for sequence in &sequences {
+                for option in &entry.options {
+                    let mut extended = sequence.clone();
+                    extended.push(*option);
+                    next.push(extended);
+                }
+            }
+            sequences = next;
+        }
+        result.extend(sequences);
+    }
+    result
+}
+
+fn sequences_of(syntax: &'static str) -> BTreeSet<Vec<ValueType>> {
+    concrete_sequences(&value_spec_from_format(syntax).unwrap())
+}
+
+#[test]
+fn test_keywords_are_merged_into_one_entry() {
+    let spec = value_spec_from_format("flex-start | flex-end | <length>").unwrap();
+    assert_eq!(spec.formats.len(), 1);
+    assert_eq!(
+        spec.formats[0].entries[0].options,
+        vec![ValueType::Keyword("flex-start"), ValueType::Keyword("flex-end"), ValueType::LengthPercent]
+    );
+    assert!(!spec.allows_multiple_values);
+}
+
+#[test]
+fn test_multipliers() {
+    let spec = value_spec_from_format("<length>{1,4}").unwrap();
+    let counts: Vec<usize> = spec.formats.iter().map(|f| f.entries.len()).collect();
+    assert_eq!(counts, vec![1, 2, 3, 4]);
+
+    let sequences = sequences_of("<number> <angle>?");
+    assert_eq!(sequences.len(), 2);
+    assert!(sequences.contains(&vec![ValueType::Number]));
+    assert!(sequences.contains(&vec![ValueType::Number, ValueType::Angle]));
+
+    let spec = value_spec_from_format("<time>#").unwrap();
+    assert!(spec.allows_multiple_values);
+    assert_eq!(spec.formats.len(), 1);
+}
+
+#[test]
+fn test_combinators() {
+    // && requires all in any order
+    let sequences = sequences_of("a && <number>");
+    assert_eq!(sequences.len(), 2);
+    assert!(sequences.contains(&vec![ValueType::Number, ValueType::Keyword("a")]));
+
+    // || requires one or more in any order
+    let sequences = sequences_of("a || b");
+    assert_eq!(sequences.len(), 4);
+    assert!(sequences.contains(&vec![ValueType::Keyword("b")]));
+    assert!(sequences.contains(&vec![ValueType::Keyword("b"), ValueType::Keyword("a")]));
+
+    // juxtaposition binds tighter than &&, which binds tighter than ||, which binds tighter than |
+    let sequences = sequences_of("a b && c | d");
+    assert_eq!(sequences.len(), 3);
+    assert!(sequences.contains(&vec![ValueType::Keyword("c"), ValueType::Keyword("a"), ValueType::Keyword("b")]));
+    assert!(sequences.contains(&vec![ValueType::Keyword("d")]));
+}
+
+#[test]
+fn test_non_empty_group() {
+    let sequences = sequences_of("[ a? <length>? ]!");
+    assert_eq!(sequences.len(), 3);
+    assert!(!sequences.contains(&vec![]));
+}
+
+#[test]
+fn test_data_type_range_and_property_reference() {
+    let spec = value_spec_from_format_with("<'flex-basis'> <length-percentage [0,∞]>", &|name| {
+        (name == "flex-basis").then_some("<length> | auto")
+    })
+    .unwrap();
+    assert_eq!(spec.formats.len(), 1);
+    assert_eq!(spec.formats[0].entries.len(), 2);
+
+    assert_eq!(
+        value_spec_from_format("<'flex-basis'>").unwrap_err(),
+        ValueSyntaxError::UnknownProperty("flex-basis".to_string())
+    );
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        value_spec_from_format("<unknown-type>").unwrap_err(),
+        ValueSyntaxError::UnknownDataType("unknown-type".to_string())
+    );
+    assert!(matches!(value_spec_from_format("<length>+"), Err(ValueSyntaxError::Unsupported(_))));
+    assert!(matches!(value_spec_from_format("<length>{1,}"), Err(ValueSyntaxError::Unsupported(_))));
+    assert!(matches!(value_spec_from_format("[ <length>#, a ]"), Err(ValueSyntaxError::Unsupported(_))));
+    assert!(matches!(value_spec_from_format("[ a | b"), Err(ValueSyntaxError::UnexpectedEnd)));
+    assert!(matches!(value_spec_from_format("a | | b"), Err(ValueSyntaxError::UnexpectedToken { .. })));
+    assert!(matches!(value_spec_from_format("a ]"), Err(ValueSyntaxError::UnexpectedToken { .. })));
+    assert!(matches!(
+        value_spec_from_format("[a || b || c || d || e || f || g]{4}"),
+        Err(ValueSyntaxError::TooManyFormats)
+    ));
+}
+
+/// Properties whose value spec is generated from their format, they are checked by
+/// `test_generated_specs_accept_expected_values` instead
+const GENERATED_SPECS: &[&str] = &[
+    "background-position",
+    "background-position-x",
+    "background-position-y",
+    "background-repeat",
+    "background-size",
+    "rotate",
+    "scale",
+];
+
+/// Golden test: value specs generated from the documented format strings must accept
+/// exactly the same values as the hand-built value specs in property_data.
+#[test]
+fn test_generated_specs_match_hand_built_specs() {
+    let properties = create_standard_properties();
+    let property_format = |name: &str| properties.get(name).map(|p| p.format);
+
+    let mut differences = Vec::new();
+    for (name, info) in properties.iter().filter(|(name, _)| !GENERATED_SPECS.contains(name)) {
+        let result = value_spec_from_format_with(info.format, &property_format).map(|mut spec| {
+            add_initial_keyword(&mut spec);
+            spec
+        });
+
+        let is_same = match result {
+            Ok(spec) => {
+                concrete_sequences(&spec) == concrete_sequences(&info.value_spec)
+                    && spec.allows_multiple_values == info.value_spec.allows_multiple_values
+            }
+            Err(_) => false,
+        };
+
+        let is_known = INACCURATE_FORMATS.iter().any(|(known, _)| known == name);
+        if is_same == is_known {
+            differences.push(*name);
+        }
+    }
+    differences.sort();
+
+    assert!(
+        differences.is_empty(),
+        "Generated value specs don't match hand-built ones (or a known difference was fixed, update INACCURATE_FORMATS): {:?}",
+        differences
+    );
+}
+
+/// Value specs generated from formats, compared with the values the formats allow written out by hand
+#[test]
+fn test_generated_specs_accept_expected_values() {
+    use ValueType::{Angle, Keyword, LengthPercent, Number};
+
+    let properties = create_standard_properties();
+    let sequences = |name: &str| concrete_sequences(&properties[name].value_spec);
+
+    let scale = BTreeSet::from([
+        vec![Keyword("none")],
+        vec![Keyword("initial")],
+        vec![Number],
+        vec![Number, Number],
+        vec![Number, Number, Number],
+    ]);
+    assert_eq!(sequences("scale"), scale);
+
+    let mut rotate = BTreeSet::from([
+        vec![Keyword("none")],
+        vec![Keyword("initial")],
+        vec![Angle],
+        vec![Number, Number, Number, Angle],
+        vec![Angle, Number, Number, Number],
+    ]);
+    for axis in ["x", "y", "z"] {
+        rotate.insert(vec![Keyword(axis), Angle]);
+        rotate.insert(vec![Angle, Keyword(axis)]);
+    }
+    assert_eq!(sequences("rotate"), rotate);
+
+    let repeat_keywords = ["repeat", "space", "round", "no-repeat"];
+    let mut background_repeat =
+        BTreeSet::from([vec![Keyword("repeat-x")], vec![Keyword("repeat-y")], vec![Keyword("initial")]]);
+    for first in repeat_keywords {
+        background_repeat.insert(vec![Keyword(first)]);
+        for second in repeat_keywords {
+            background_repeat.insert(vec![Keyword(first), Keyword(second)]);
+        }
+    }
+    assert_eq!(sequences("background-repeat"), background_repeat);
+
+    let size = [LengthPercent, Keyword("auto")];
+    let mut background_size = BTreeSet::from([vec![Keyword("cover")], vec![Keyword("contain")], vec![Keyword("initial")]]);
+    for width in size {
+        background_size.insert(vec![width]);
+        for height in size {
+            background_size.insert(vec![width, height]);
+        }
+    }
+    assert_eq!(sequences("background-size"), background_size);
+
+    let background_position = sequences("background-position");
+    for accepted in [
+        vec![Keyword("left")],
+        vec![LengthPercent, LengthPercent],
+        vec![Keyword("center"), Keyword("top"), LengthPercent],
+        vec![Keyword("right"), LengthPercent, Keyword("bottom"), LengthPercent],
+        vec![Keyword("bottom"), LengthPercent, Keyword("right")],
+    ] {
+        assert!(background_position.contains(&accepted), "{:?}", accepted);
+    }
+    assert!(!background_position.contains(&vec![Keyword("left"), Keyword("right")]));
+    assert!(!background_position.contains(&vec![LengthPercent, LengthPercent, LengthPercent]));
+    assert!(properties["background-position"].value_spec.allows_multiple_values);
+
+    for (name, start, other_axis) in [("background-position-x", "x-start", "top"), ("background-position-y", "y-start", "left")] {
+        let position = sequences(name);
+        assert!(position.contains(&vec![Keyword("center")]), "{}", name);
+        assert!(position.contains(&vec![LengthPercent]), "{}", name);
+        assert!(position.contains(&vec![Keyword(start), LengthPercent]), "{}", name);
+        assert!(!position.contains(&vec![Keyword(other_axis)]), "{}", name);
+        assert!(!position.contains(&vec![]), "{}", name);
+        assert!(properties[name].value_spec.allows_multiple_values, "{}", name);
+    }
+}
@@ -19,9 +19,11 @@
 //! - **Resolution Status**: Variables can be in one of three states:
 //!   - `Resolved`: Successfully resolved to concrete values
 //!   - `Unresolved`: Exists but cannot be resolved due to missing dependencies or circular references
+//!
+//! - **Documentation**: A comment right above a variable declaration documents the variable, e.g.
+//!   `/* Primary brand color */ --brand: #ff0000;`. For variables defined multiple times, the first documented definition wins.
 
 use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
 use tree_sitter::Node;
 use crate::uss::value::UssValue;
 use crate::uss::constants::*;
@@ -44,18 +46,21 @@ pub enum VariableStatus {
 #[derive(Clone, Debug)]
 pub struct VariableResolver {
     variables: HashMap<String, VariableStatus>,
+    /// Documentation comments of variables, by variable name (without `--` prefix)
+    descriptions: HashMap<String, String>,
     /// Temporary storage for parsed values during extraction
     parsed_values: HashMap<String, Vec<UssValue>>,
     resolved: bool,
     /// USS language definitions for value parsing
-    definitions: Arc<UssDefinitions>,
+    definitions: &'static UssDefinitions,
 }
 
 impl VariableResolver {
     /// Create a new variable resolver
-    pub fn new(definitions: Arc<UssDefinitions>) -> Self {
+    pub fn new(definitions: &'static UssDefinitions) -> Self {
         Self {
             variables: HashMap::new(),
+            descriptions: HashMap::new(),
             parsed_values: HashMap::new(),
             resolved: false,
             definitions,
@@ -65,6 +70,7 @@ impl VariableResolver {
     /// Clear all variables and mark as unresolved
     pub fn clear(&mut self) {
         self.variables.clear();
+        self.descriptions.clear();
         self.resolved = false;
     }
 
@@ -76,6 +82,7 @@ impl VariableResolver {
     /// Extract variables from a syntax tree and resolve them with source URL
     pub fn add_variables_from_tree_with_source_url(&mut self, root_node: Node, content: &str, source_url: Option<&url::Url>) {
         self.variables.clear();
+        self.descriptions.clear();
         
         // Extract variables and their values in a single pass
         self.extract_variables_from_node_with_source_url(root_node, content, source_url);
@@ -95,6 +102,39 @@ impl VariableResolver {
         self.variables.get(name)
     }
 
+    /// Get the documentation comment of a variable (name without `--` prefix)
+    pub fn get_description(&self, name: &str) -> Option<&str> {
+        self.descriptions.get(name).map(|d| d.as_str())
+    }
+
+    /// Create markdown documentation for a variable (name without `--` prefix), `None` if it's not defined
+    ///
+    /// Shows the documentation comment of the variable (if any) and its value when it can be resolved.
+    pub fn create_documentation(&self, name: &str) -> Option<String> {
+        let status = self.variables.get(name)?;
+
+        let mut content = format!("### Variable --{}\n", name);
+        if let Some(description) = self.get_description(name) {
+            content.push_str(description);
+            content.push('\n');
+        }
+
+        match status {
+            VariableStatus::Resolved(values) => {
+                let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                content.push_str(&format!("\n**Value:** `{}`", values.join(" ")));
+            }
+            VariableStatus::Ambiguous => {
+                content.push_str("\n*Defined multiple times, the value depends on which definition applies*");
+            }
+            VariableStatus::Unresolved | VariableStatus::Error => {
+                content.push_str("\n*The value can't be resolved*");
+            }
+        }
+
+        Some(content)
+    }
+
     /// Extract variable declarations and their values from a syntax tree in a single pass with source URL
     fn extract_variables_from_node_with_source_url(&mut self, node: Node, content: &str, source_url: Option<&url::Url>) {
         // Look for CSS custom property declarations (--variable-name: value;)
@@ -111,6 +151,12 @@ impl VariableResolver {
             
             if property_text.starts_with("--") {
                 let variable_name = property_text[2..].to_string(); // Remove -- prefix
+
+                if !self.descriptions.contains_key(&variable_name) {
+                    if let Some(description) = Self::doc_comment(node, content) {
+                        self.descriptions.insert(variable_name.clone(), description);
+                    }
+                }
                 
                 // Check if this variable already exists (ambiguous case)
                 if self.variables.contains_key(&variable_name) {
@@ -141,6 +187,36 @@ impl VariableResolver {
         }
     }
 
+    /// Get the documentation comment of a declaration, that is a comment right above it (or before it on the same line)
+    ///
+    /// A comment that trails a previous declaration on the same line belongs to that declaration, not this one.
+    fn doc_comment(declaration_node: Node, content: &str) -> Option<String> {
+        let comment = declaration_node.prev_sibling().filter(|n| n.kind() == NODE_COMMENT)?;
+        if comment.end_position().row + 1 < declaration_node.start_position().row {
+            return None;
+        }
+        if let Some(before) = comment.prev_sibling() {
+            if before.kind() == NODE_DECLARATION && before.end_position().row == comment.start_position().row {
+                return None;
+            }
+        }
+
+        let text = Self::node_text(comment, content);
+        let text = text.trim_start_matches("/*").trim_end_matches("*/");
+        let lines: Vec<&str> = text
+            .lines()
+            .map(|line| line.trim())
+            .map(|line| line.strip_prefix('*').unwrap_or(line).trim())
+            .filter(|line| !line.is_empty())
+            .collect();
+
+        if lines.is_empty() {
+            None
+        } else {
+            Some(lines.join("\n"))
+        }
+    }
+
     /// Get text content of a node with content
     pub fn node_text(node: Node, content: &str) -> String {
         content[node.start_byte()..node.end_byte()].to_string()
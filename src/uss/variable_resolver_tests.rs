@@ -1,5 +1,4 @@
 
-use std::sync::Arc;
 
 use crate::uss::{constants::UNIT_PX, definitions::UssDefinitions, parser::UssParser, value::UssValue, variable_resolver::{VariableResolver, VariableStatus}};
 
@@ -17,7 +16,7 @@ fn test_variable_extraction() {
 }"#;
     
     let tree = create_test_tree(content).unwrap();
-    let mut resolver = VariableResolver::new(Arc::new(UssDefinitions::new()));
+    let mut resolver = VariableResolver::new(UssDefinitions::shared());
     resolver.add_variables_from_tree(tree.root_node(), content);
     
     let variables = resolver.get_variables();
@@ -37,7 +36,7 @@ fn test_variable_resolution_simple() {
         "#;
     
     let tree = create_test_tree(content).unwrap();
-    let mut resolver = VariableResolver::new(Arc::new(UssDefinitions::new()));
+    let mut resolver = VariableResolver::new(UssDefinitions::shared());
     resolver.add_variables_from_tree(tree.root_node(), content);
     
     let variables = resolver.get_variables();
@@ -67,7 +66,7 @@ fn test_variable_resolution_circular() {
         "#;
     
     let tree = create_test_tree(content).unwrap();
-    let mut resolver = VariableResolver::new(Arc::new(UssDefinitions::new()));
+    let mut resolver = VariableResolver::new(UssDefinitions::shared());
     resolver.add_variables_from_tree(tree.root_node(), content);
     
     let color_a = resolver.get_variable("color-a").unwrap();
@@ -90,7 +89,7 @@ fn test_variable_resolution_ambiguous() {
         "#;
     
     let tree = create_test_tree(content).unwrap();
-    let mut resolver = VariableResolver::new(Arc::new(UssDefinitions::new()));
+    let mut resolver = VariableResolver::new(UssDefinitions::shared());
     resolver.add_variables_from_tree(tree.root_node(), content);
     
     let primary_var = resolver.get_variable("primary-color").unwrap();
@@ -111,7 +110,7 @@ fn test_complex_variable_dependencies() {
         "#;
     
     let tree = create_test_tree(content).unwrap();
-    let mut resolver = VariableResolver::new(Arc::new(UssDefinitions::new()));
+    let mut resolver = VariableResolver::new(UssDefinitions::shared());
     resolver.add_variables_from_tree(tree.root_node(), content);
     
     let variables = resolver.get_variables();
@@ -163,7 +162,7 @@ fn test_variable_parsing_errors() {
         "#;
     
     let tree = create_test_tree(content).unwrap();
-    let mut resolver = VariableResolver::new(Arc::new(UssDefinitions::new()));
+    let mut resolver = VariableResolver::new(UssDefinitions::shared());
     resolver.add_variables_from_tree(tree.root_node(), content);
     
     let variables = resolver.get_variables();
@@ -179,3 +178,37 @@ fn test_variable_parsing_errors() {
     let invalid_var = resolver.get_variable("invalid-var").unwrap();
     assert!(matches!(invalid_var, VariableStatus::Error));
 }
+
+#[test]
+fn test_variable_descriptions() {
+    let content = r#":root {
+    /* Primary brand color */ --brand: #ff0000;
+    /*
+     * Spacing between items
+     * in pixels
+     */
+    --spacing: 4px;
+    --plain: 1px; /* trailing comment of plain */
+    --after-trailing: 2px;
+
+    /* separated by a blank line */
+
+    --separated: 3px;
+}
+.other {
+    --brand: #00ff00;
+}"#;
+
+    let tree = create_test_tree(content).unwrap();
+    let mut resolver = VariableResolver::new(UssDefinitions::shared());
+    resolver.add_variables_from_tree(tree.root_node(), content);
+
+    assert_eq!(resolver.get_description("brand"), Some("Primary brand color"));
+    assert_eq!(resolver.get_description("spacing"), Some("Spacing between items\nin pixels"));
+    assert_eq!(resolver.get_description("plain"), None);
+    assert_eq!(resolver.get_description("after-trailing"), None);
+    assert_eq!(resolver.get_description("separated"), None);
+
+    resolver.clear();
+    assert_eq!(resolver.get_description("brand"), None);
+}
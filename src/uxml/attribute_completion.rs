@@ -0,0 +1,330 @@
+//! UXML Attribute Value Completion
+//!
+//! Completion inside attribute values of UXML documents: the values of enumeration attributes and
+//! `true`/`false` for boolean attributes, both from the UXML schema, and the USS classes of the project
+//! for the `class` attribute. UXML documents are edited by the XML support of the editor, which asks
+//! with the custom request [`UXML_ATTRIBUTE_COMPLETION_METHOD`].
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::*;
+use tree_sitter::{Node, Tree};
+
+use crate::language::tree_utils::byte_to_position;
+use crate::uss::constants::{NODE_CLASS_NAME, NODE_CLASS_SELECTOR};
+use crate::uxml_schema_manager::{UxmlAttributeType, VisualElementInfo};
+
+/// Custom request for completion in a UXML attribute value, takes [`UxmlAttributeCompletionParams`] and returns `CompletionList`
+pub const UXML_ATTRIBUTE_COMPLETION_METHOD: &str = "unityCode/uxmlAttributeCompletion";
+
+/// Parameters of [`UXML_ATTRIBUTE_COMPLETION_METHOD`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UxmlAttributeCompletionParams {
+    /// The UXML document
+    pub text_document: TextDocumentIdentifier,
+    /// Content of the document if it has unsaved changes, otherwise it's read from disk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Position of the cursor in the document
+    pub position: Position,
+}
+
+/// The attribute value that contains the cursor
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeValueContext<'a> {
+    /// Element name without namespace prefix, eg. `Button` for `ui:Button`
+    pub tag: &'a str,
+    pub attribute: &'a str,
+    /// The value without quotes, up to the closing quote or the cursor if the value isn't closed
+    pub value: &'a str,
+    /// Byte offset of the start of the value
+    pub value_start: usize,
+    /// Byte offset of the cursor
+    pub cursor: usize,
+}
+
+/// Find the attribute value that contains the byte offset `cursor`
+pub fn attribute_value_at(content: &str, cursor: usize) -> Option<AttributeValueContext<'_>> {
+    let tag_start = content.get(..cursor)?.rfind('<')?;
+    let bytes = content.as_bytes();
+
+    let mut i = tag_start + 1;
+    while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>' && bytes[i] != b'/' {
+        i += 1;
+    }
+    let full_name = &content[tag_start + 1..i];
+    if full_name.is_empty() || full_name.starts_with(['!', '?']) || i >= cursor {
+        return None;
+    }
+    let tag = full_name.rsplit(':').next().unwrap_or(full_name);
+
+    while i < cursor {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        let name_start = i;
+        while i < bytes.len() && !bytes[i].is_ascii_whitespace() && !matches!(bytes[i], b'=' | b'>' | b'/' | b'<') {
+            i += 1;
+        }
+        let name_end = i;
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if name_start == name_end || i >= cursor || bytes[i] != b'=' {
+            return None;
+        }
+        i += 1;
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i >= cursor || (bytes[i] != b'"' && bytes[i] != b'\'') {
+            return None;
+        }
+
+        let quote = bytes[i];
+        let value_start = i + 1;
+        let value_end = bytes[value_start..]
+            .iter()
+            .position(|&b| b == quote || b == b'<')
+            .map_or(bytes.len(), |len| value_start + len);
+        let closed = bytes.get(value_end) == Some(&quote);
+        if cursor <= value_end {
+            return Some(AttributeValueContext {
+                tag,
+                attribute: &content[name_start..name_end],
+                // an unclosed value ends at the cursor
+                value: &content[value_start..if closed { value_end } else { cursor }],
+                value_start,
+                cursor,
+            });
+        }
+        if !closed {
+            return None;
+        }
+        i = value_end + 1;
+    }
+    None
+}
+
+/// Class names of the class selectors of a style sheet
+pub fn collect_class_names(tree: &Tree, content: &str, class_names: &mut HashSet<String>) {
+    collect_class_names_recursive(tree.root_node(), content, class_names);
+}
+
+fn collect_class_names_recursive(node: Node, content: &str, class_names: &mut HashSet<String>) {
+    if node.kind() == NODE_CLASS_NAME && node.parent().is_some_and(|p| p.kind() == NODE_CLASS_SELECTOR) {
+        if let Ok(name) = node.utf8_text(content.as_bytes()) {
+            class_names.insert(name.to_string());
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_class_names_recursive(child, content, class_names);
+    }
+}
+
+/// Completion items for an attribute value
+///
+/// `element` is the schema information of the element, `classes` are the USS classes of the project
+/// with the style sheets that define them, used for the `class` attribute.
+pub fn complete_attribute_value(
+    context: &AttributeValueContext,
+    content: &str,
+    element: Option<&VisualElementInfo>,
+    classes: &BTreeMap<String, Vec<String>>,
+) -> Vec<CompletionItem> {
+    if context.attribute == "class" {
+        return complete_class(context, content, classes);
+    }
+
+    let Some(attribute) = element.and_then(|element| element.attribute(context.attribute)) else {
+        return Vec::new();
+    };
+    let (values, kind) = match &attribute.value_type {
+        UxmlAttributeType::Boolean => (vec!["true".to_string(), "false".to_string()], CompletionItemKind::KEYWORD),
+        UxmlAttributeType::Enumeration(values) => (values.clone(), CompletionItemKind::ENUM_MEMBER),
+        UxmlAttributeType::Other(_) => return Vec::new(),
+    };
+
+    let value_end = context.value_start + context.value.len();
+    let range = Range::new(byte_to_position(context.value_start, content), byte_to_position(value_end, content));
+    values
+        .into_iter()
+        .map(|value| {
+            let is_default = attribute.default_value.as_deref() == Some(value.as_str());
+            let mut documentation = format!("Value of the `{}` attribute of `{}`", attribute.name, context.tag);
+            if let Some(default_value) = attribute.default_value.as_deref().filter(|d| !d.is_empty()) {
+                documentation.push_str(&format!("\n\nDefault: `{}`", default_value));
+            }
+            CompletionItem {
+                label: value.clone(),
+                kind: Some(kind),
+                detail: is_default.then(|| "default".to_string()),
+                documentation: Some(Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: documentation,
+                })),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit { range, new_text: value })),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Classes for the word at the cursor, classes the element already has are skipped
+fn complete_class(
+    context: &AttributeValueContext,
+    content: &str,
+    classes: &BTreeMap<String, Vec<String>>,
+) -> Vec<CompletionItem> {
+    let offset = context.cursor - context.value_start;
+    let word_start = context.value[..offset].rfind(char::is_whitespace).map_or(0, |i| i + 1);
+    let word_end = context.value[offset..].find(char::is_whitespace).map_or(context.value.len(), |i| offset + i);
+    let range = Range::new(
+        byte_to_position(context.value_start + word_start, content),
+        byte_to_position(context.value_start + word_end, content),
+    );
+
+    let existing: HashSet<&str> = context.value[..word_start]
+        .split_whitespace()
+        .chain(context.value[word_end..].split_whitespace())
+        .collect();
+
+    classes
+        .iter()
+        .filter(|(class_name, _)| !existing.contains(class_name.as_str()))
+        .map(|(class_name, style_sheets)| {
+            let files: Vec<String> = style_sheets.iter().map(|file| format!("- `{}`", file)).collect();
+            CompletionItem {
+                label: class_name.clone(),
+                kind: Some(CompletionItemKind::CLASS),
+                documentation: Some(Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: format!("USS class `.{}`, defined in:\n{}", class_name, files.join("\n")),
+                })),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: class_name.clone(),
+                })),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uss::parser::UssParser;
+    use crate::uxml_schema_manager::UxmlAttributeInfo;
+
+    /// Content without the `|` cursor marker, and the offset of the cursor
+    fn with_cursor(text: &str) -> (String, usize) {
+        let cursor = text.find('|').unwrap();
+        (text.replacen('|', "", 1), cursor)
+    }
+
+    fn complete(text: &str, element: Option<&VisualElementInfo>, classes: &BTreeMap<String, Vec<String>>) -> Vec<CompletionItem> {
+        let (content, cursor) = with_cursor(text);
+        let context = attribute_value_at(&content, cursor).unwrap();
+        complete_attribute_value(&context, &content, element, classes)
+    }
+
+    fn toggle() -> VisualElementInfo {
+        VisualElementInfo {
+            name: "Toggle".to_string(),
+            namespace: "UnityEngine.UIElements".to_string(),
+            fully_qualified_name: "UnityEngine.UIElements.Toggle".to_string(),
+            attributes: vec![
+                UxmlAttributeInfo {
+                    name: "focusable".to_string(),
+                    value_type: UxmlAttributeType::Boolean,
+                    default_value: Some("true".to_string()),
+                },
+                UxmlAttributeInfo {
+                    name: "picking-mode".to_string(),
+                    value_type: UxmlAttributeType::Enumeration(vec!["Position".to_string(), "Ignore".to_string()]),
+                    default_value: Some("Position".to_string()),
+                },
+                UxmlAttributeInfo {
+                    name: "text".to_string(),
+                    value_type: UxmlAttributeType::Other("xs:string".to_string()),
+                    default_value: None,
+                },
+            ],
+            base: None,
+        }
+    }
+
+    fn labels(items: &[CompletionItem]) -> Vec<&str> {
+        items.iter().map(|item| item.label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_attribute_value_at() {
+        let (content, cursor) = with_cursor(r#"<ui:UXML><ui:Toggle name="t" picking-mode="Po|s" /></ui:UXML>"#);
+        let context = attribute_value_at(&content, cursor).unwrap();
+        assert_eq!(context.tag, "Toggle");
+        assert_eq!(context.attribute, "picking-mode");
+        assert_eq!(context.value, "Pos");
+
+        // value not closed yet
+        let (content, cursor) = with_cursor("<ui:Toggle focusable=\"t|");
+        assert_eq!(attribute_value_at(&content, cursor).unwrap().value, "t");
+
+        for text in [r#"<ui:Toggle name="t" | />"#, r#"<ui:Tog|gle name="t" />"#, r#"<ui:Toggle name="t" /> te|xt"#] {
+            let (content, cursor) = with_cursor(text);
+            assert!(attribute_value_at(&content, cursor).is_none(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_complete_schema_values() {
+        let element = toggle();
+        let classes = BTreeMap::new();
+
+        let items = complete(r#"<ui:Toggle picking-mode="Po|s" />"#, Some(&element), &classes);
+        assert_eq!(labels(&items), vec!["Position", "Ignore"]);
+        assert_eq!(items[0].detail.as_deref(), Some("default"));
+        // the whole value is replaced
+        let Some(CompletionTextEdit::Edit(edit)) = &items[1].text_edit else {
+            panic!("Expected a text edit");
+        };
+        assert_eq!(edit.range, Range::new(Position::new(0, 25), Position::new(0, 28)));
+
+        let items = complete(r#"<ui:Toggle focusable="|" />"#, Some(&element), &classes);
+        assert_eq!(labels(&items), vec!["true", "false"]);
+
+        assert!(complete(r#"<ui:Toggle text="|" />"#, Some(&element), &classes).is_empty());
+        assert!(complete(r#"<ui:Toggle focusable="|" />"#, None, &classes).is_empty());
+    }
+
+    #[test]
+    fn test_complete_class_names() {
+        let uss = ".primary { color: red; }\n.large:hover, Button.title > .icon { }";
+        let tree = UssParser::new().unwrap().parse(uss, None).unwrap();
+        let mut class_names = HashSet::new();
+        collect_class_names(&tree, uss, &mut class_names);
+        assert_eq!(class_names.len(), 4);
+
+        let classes: BTreeMap<String, Vec<String>> = class_names
+            .into_iter()
+            .map(|name| (name, vec!["Assets/UI/main.uss".to_string()]))
+            .collect();
+        let items = complete(r#"<ui:Button class="primary la| title" />"#, None, &classes);
+        assert_eq!(labels(&items), vec!["icon", "large"]);
+
+        // only the word at the cursor is replaced
+        let Some(CompletionTextEdit::Edit(edit)) = &items[1].text_edit else {
+            panic!("Expected a text edit");
+        };
+        assert_eq!(edit.range, Range::new(Position::new(0, 26), Position::new(0, 28)));
+        let Some(Documentation::MarkupContent(documentation)) = &items[1].documentation else {
+            panic!("Expected documentation");
+        };
+        assert!(documentation.value.contains("Assets/UI/main.uss"));
+    }
+}
@@ -0,0 +1,20 @@
+//! UXML Module
+//!
+//! Lightweight UXML parsing and project-wide indexing of UXML elements,
+//! used by USS features that need to know which elements a selector targets,
+//! completion of attribute values in UXML documents, and resolution of
+//! templates and their instances across UXML documents. Built-in elements
+//! are provided for projects without a generated UXML schema.
+
+pub mod attribute_completion;
+pub mod parser;
+pub mod schema_fallback;
+pub mod templates;
+pub mod usage_index;
+
+#[cfg(test)]
+mod parser_tests;
+#[cfg(test)]
+mod usage_index_tests;
+#[cfg(test)]
+mod templates_tests;
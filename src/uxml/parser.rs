@@ -0,0 +1,258 @@
+//! UXML Parser
+//!
+//! Extracts visual elements from UXML documents, along with the source ranges
+//! of their tag names and the attributes USS selectors can target (`name` and `class`).
+//!
+//! Parsing is lenient: UXML files are often in the middle of being edited,
+//! so elements read before a syntax error are still returned.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::Range;
+
+use crate::language::tree_utils::byte_to_position;
+
+/// An attribute value with its source range (excluding the quotes)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UxmlAttributeValue {
+    pub value: String,
+    pub range: Range,
+}
+
+/// A single element in a UXML document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UxmlElement {
+    /// Element name without namespace prefix, eg. `Button` for `ui:Button`
+    pub tag: String,
+    /// Range of the element name in the start tag, including the namespace prefix
+    pub tag_range: Range,
+    /// Range of the whole element, from the start tag to the end tag
+    ///
+    /// Ends at the start tag if the element isn't closed.
+    pub range: Range,
+    /// The `name` attribute, targeted by `#name` selectors
+    pub name: Option<UxmlAttributeValue>,
+    /// Individual classes of the `class` attribute, targeted by `.class` selectors
+    pub classes: Vec<UxmlAttributeValue>,
+    /// All attributes as (name, value), in source order
+    pub attributes: Vec<(String, UxmlAttributeValue)>,
+    /// Index of the parent element, `None` for the root element
+    pub parent: Option<usize>,
+}
+
+impl UxmlElement {
+    /// Class names of this element
+    pub fn class_names(&self) -> Vec<&str> {
+        self.classes.iter().map(|c| c.value.as_str()).collect()
+    }
+
+    /// Value of the `name` attribute, if any
+    pub fn name_value(&self) -> Option<&str> {
+        self.name.as_ref().map(|n| n.value.as_str())
+    }
+
+    /// Get an attribute by name
+    pub fn attribute(&self, name: &str) -> Option<&UxmlAttributeValue> {
+        self.attributes
+            .iter()
+            .find(|(attr_name, _)| attr_name == name)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Parse all elements of a UXML document, in document order
+pub fn parse_uxml_elements(content: &str) -> Vec<UxmlElement> {
+    let mut reader = Reader::from_str(content);
+    let mut elements: Vec<UxmlElement> = Vec::new();
+    // indices of currently open elements
+    let mut open_elements: Vec<usize> = Vec::new();
+
+    loop {
+        let start = reader.buffer_position() as usize;
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) => {
+                let end = reader.buffer_position() as usize;
+                let element = create_element(e, content, start, end, open_elements.last().copied());
+                elements.push(element);
+                open_elements.push(elements.len() - 1);
+            }
+            Ok(Event::Empty(ref e)) => {
+                let end = reader.buffer_position() as usize;
+                let element = create_element(e, content, start, end, open_elements.last().copied());
+                elements.push(element);
+            }
+            Ok(Event::End(_)) => {
+                if let Some(index) = open_elements.pop() {
+                    elements[index].range.end = byte_to_position(reader.buffer_position() as usize, content);
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => {
+                log::debug!("Stopped parsing UXML at byte {}: {}", start, e);
+                break;
+            }
+        }
+    }
+
+    elements
+}
+
+/// The first XML syntax error of a UXML document, as (byte offset, message)
+///
+/// Elements that are still open at the end of the document are an error too.
+pub fn find_syntax_error(content: &str) -> Option<(usize, String)> {
+    let mut reader = Reader::from_str(content);
+    let mut open_elements: Vec<(usize, String)> = Vec::new();
+
+    loop {
+        let start = reader.buffer_position() as usize;
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) => {
+                open_elements.push((start, String::from_utf8_lossy(e.name().as_ref()).to_string()));
+            }
+            Ok(Event::End(_)) => {
+                open_elements.pop();
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => return Some((reader.error_position() as usize, e.to_string())),
+        }
+    }
+
+    open_elements
+        .pop()
+        .map(|(start, name)| (start, format!("Element '{}' is not closed", name)))
+}
+
+fn create_element(
+    e: &BytesStart,
+    content: &str,
+    start: usize,
+    end: usize,
+    parent: Option<usize>,
+) -> UxmlElement {
+    let full_name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+    let tag = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+
+    // the raw start tag, eg. `<ui:Button class="a b" />`
+    let raw = content.get(start..end).unwrap_or("");
+    let name_start = start + raw.find(full_name.as_str()).unwrap_or(0);
+    let tag_range = byte_range(content, name_start, name_start + full_name.len());
+
+    let mut attributes = Vec::new();
+    let mut classes = Vec::new();
+    for (attr_name, value_start, value_end) in scan_attributes(raw, full_name.len()) {
+        let raw_value = &raw[value_start..value_end];
+        if attr_name == "class" {
+            for (offset, class_name) in split_whitespace_indices(raw_value) {
+                let class_start = start + value_start + offset;
+                classes.push(UxmlAttributeValue {
+                    value: class_name.to_string(),
+                    range: byte_range(content, class_start, class_start + class_name.len()),
+                });
+            }
+        }
+
+        let value = UxmlAttributeValue {
+            value: raw_value.to_string(),
+            range: byte_range(content, start + value_start, start + value_end),
+        };
+        attributes.push((attr_name.to_string(), value));
+    }
+
+    let name = attributes
+        .iter()
+        .find(|(attr_name, _)| attr_name == "name")
+        .map(|(_, value)| value.clone());
+
+    UxmlElement {
+        tag,
+        tag_range,
+        range: byte_range(content, start, end),
+        name,
+        classes,
+        attributes,
+        parent,
+    }
+}
+
+/// Scan the attributes of a raw start tag
+///
+/// Returns (attribute name, value start, value end) with offsets relative to the raw tag.
+/// `name_len` is the length of the element name, which follows the opening `<`.
+fn scan_attributes(raw: &str, name_len: usize) -> Vec<(&str, usize, usize)> {
+    let bytes = raw.as_bytes();
+    let mut attributes = Vec::new();
+    // skip `<` and the element name
+    let mut i = 1 + name_len;
+
+    while i < bytes.len() {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i >= bytes.len() || bytes[i] == b'>' || bytes[i] == b'/' {
+            break;
+        }
+
+        let name_start = i;
+        while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'=' && bytes[i] != b'>' {
+            i += 1;
+        }
+        let name_end = i;
+
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i >= bytes.len() || bytes[i] != b'=' {
+            // attribute without value is not valid XML, stop here
+            break;
+        }
+        i += 1;
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i >= bytes.len() || (bytes[i] != b'"' && bytes[i] != b'\'') {
+            break;
+        }
+
+        let quote = bytes[i];
+        let value_start = i + 1;
+        let Some(len) = bytes[value_start..].iter().position(|&b| b == quote) else {
+            break;
+        };
+        let value_end = value_start + len;
+        attributes.push((&raw[name_start..name_end], value_start, value_end));
+        i = value_end + 1;
+    }
+
+    attributes
+}
+
+/// Split on whitespace, keeping the byte offset of each part
+fn split_whitespace_indices(text: &str) -> Vec<(usize, &str)> {
+    let mut parts = Vec::new();
+    let mut part_start: Option<usize> = None;
+    for (i, ch) in text.char_indices() {
+        match (ch.is_whitespace(), part_start) {
+            (true, Some(s)) => {
+                parts.push((s, &text[s..i]));
+                part_start = None;
+            }
+            (false, None) => part_start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(s) = part_start {
+        parts.push((s, &text[s..]));
+    }
+    parts
+}
+
+fn byte_range(content: &str, start: usize, end: usize) -> Range {
+    Range {
+        start: byte_to_position(start, content),
+        end: byte_to_position(end, content),
+    }
+}
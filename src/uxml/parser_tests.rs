@@ -0,0 +1,64 @@
+use tower_lsp::lsp_types::{Position, Range};
+
+use super::parser::*;
+
+#[test]
+fn test_parse_elements_and_hierarchy() {
+    let content = r#"<ui:UXML xmlns:ui="UnityEngine.UIElements">
+    <ui:VisualElement name="root" class="container">
+        <ui:Button text="Click" class="primary  large" />
+    </ui:VisualElement>
+</ui:UXML>"#;
+
+    let elements = parse_uxml_elements(content);
+    assert_eq!(elements.len(), 3);
+
+    assert_eq!(elements[0].tag, "UXML");
+    assert_eq!(elements[0].parent, None);
+
+    assert_eq!(elements[1].tag, "VisualElement");
+    assert_eq!(elements[1].name_value(), Some("root"));
+    assert_eq!(elements[1].parent, Some(0));
+
+    assert_eq!(elements[2].tag, "Button");
+    assert_eq!(elements[2].class_names(), vec!["primary", "large"]);
+    assert_eq!(elements[2].parent, Some(1));
+    assert_eq!(elements[2].attribute("text").unwrap().value, "Click");
+}
+
+#[test]
+fn test_parse_ranges() {
+    let content = "<ui:UXML>\n  <ui:Label class=\"a title\" />\n</ui:UXML>";
+    let elements = parse_uxml_elements(content);
+    let label = &elements[1];
+
+    assert_eq!(
+        label.tag_range,
+        Range::new(Position::new(1, 3), Position::new(1, 11))
+    );
+    assert_eq!(
+        label.classes[1].range,
+        Range::new(Position::new(1, 21), Position::new(1, 26))
+    );
+    assert_eq!(label.range, Range::new(Position::new(1, 2), Position::new(1, 30)));
+    assert_eq!(elements[0].range, Range::new(Position::new(0, 0), Position::new(2, 10)));
+}
+
+#[test]
+fn test_parse_invalid_document_keeps_previous_elements() {
+    let content = "<ui:UXML>\n  <ui:Label class=\"a\" />\n  <ui:Button class=\n";
+    let elements = parse_uxml_elements(content);
+    assert_eq!(elements.len(), 2);
+    assert_eq!(elements[1].tag, "Label");
+}
+
+#[test]
+fn test_find_syntax_error() {
+    assert_eq!(find_syntax_error("<ui:UXML>\n  <ui:Button />\n</ui:UXML>\n"), None);
+    assert_eq!(find_syntax_error("<ui:UXML>").unwrap(), (0, "Element 'ui:UXML' is not closed".to_string()));
+
+    // the end tag doesn't match the open element
+    let content = "<ui:UXML>\n  <ui:Button>\n</ui:UXML>\n";
+    let (offset, _) = find_syntax_error(content).unwrap();
+    assert!(offset >= content.find("</ui:UXML>").unwrap());
+}
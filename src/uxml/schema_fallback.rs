@@ -0,0 +1,411 @@
+//! Built-in UXML Elements
+//!
+//! Unity only generates the `UIElementsSchema` folder when asked to (from the UXML schema menu, or by some IDE
+//! integrations), so many projects never have it. Without the schema, tag selectors and UXML attributes would
+//! have nothing to complete. This is the fallback: the built-in elements of Unity with their common
+//! attributes, written from the schema Unity 2021.3, 2022.3 and 6000.0 generate.
+//!
+//! Elements are selected by the Unity version of the project, by the version they were added in and the
+//! version fields moved from `UnityEditor.UIElements` to `UnityEngine.UIElements`. Attributes are flattened,
+//! each element has the attributes of its bases, like in the generated schema. Abstract types such as
+//! `BaseField<T>` are not elements, so fields derive from `BindableElement`.
+
+use crate::unity_project_manager::UnityVersion;
+use crate::uxml_schema_manager::{UxmlAttributeInfo, UxmlAttributeType, VisualElementInfo};
+
+/// Namespace of the runtime elements
+pub const ENGINE_NAMESPACE: &str = "UnityEngine.UIElements";
+/// Namespace of the editor only elements
+pub const EDITOR_NAMESPACE: &str = "UnityEditor.UIElements";
+
+/// Version the fields of primitive types moved to the runtime
+const RUNTIME_FIELDS: UnityVersion = UnityVersion::new(2022, 1);
+
+/// Values of an attribute
+#[derive(Debug, Clone, Copy)]
+enum Type {
+    String,
+    Int,
+    Float,
+    Bool,
+    Enum(&'static [&'static str]),
+}
+
+/// Namespace of an element
+#[derive(Debug, Clone, Copy)]
+enum Namespace {
+    Engine,
+    Editor,
+    /// In the editor namespace before the version, in the runtime namespace since
+    EngineSince(UnityVersion),
+}
+
+#[derive(Debug)]
+struct Element {
+    name: &'static str,
+    namespace: Namespace,
+    /// Name of the base element, in this list
+    base: Option<&'static str>,
+    /// Attributes the element adds to the ones of its base
+    attributes: &'static [(&'static str, Type)],
+    /// Version the element was added in, `None` if it's in every supported version
+    since: Option<UnityVersion>,
+}
+
+const fn element(
+    name: &'static str,
+    namespace: Namespace,
+    base: Option<&'static str>,
+    attributes: &'static [(&'static str, Type)],
+) -> Element {
+    Element { name, namespace, base, attributes, since: None }
+}
+
+const fn element_since(
+    name: &'static str,
+    namespace: Namespace,
+    base: Option<&'static str>,
+    attributes: &'static [(&'static str, Type)],
+    since: UnityVersion,
+) -> Element {
+    Element { name, namespace, base, attributes, since: Some(since) }
+}
+
+use Namespace::{Editor, Engine, EngineSince};
+use Type::{Bool, Enum, Float, Int, String as Str};
+
+const DIRECTIONS: &[&str] = &["Horizontal", "Vertical"];
+const SELECTION_TYPES: &[&str] = &["None", "Single", "Multiple"];
+const ALTERNATING_ROWS: &[&str] = &["None", "ContentOnly", "All"];
+const VIRTUALIZATION_METHODS: &[&str] = &["FixedHeight", "DynamicHeight"];
+const SCROLLER_VISIBILITIES: &[&str] = &["Auto", "AlwaysVisible", "Hidden"];
+
+const FIELD: &[(&str, Type)] = &[("label", Str), ("value", Str)];
+const TEXT_INPUT_FIELD: &[(&str, Type)] = &[("label", Str), ("value", Str), ("readonly", Bool), ("is-delayed", Bool)];
+const COLLECTION_VIEW: &[(&str, Type)] = &[
+    ("fixed-item-height", Float),
+    ("virtualization-method", Enum(VIRTUALIZATION_METHODS)),
+    ("show-border", Bool),
+    ("selection-type", Enum(SELECTION_TYPES)),
+    ("show-alternating-row-backgrounds", Enum(ALTERNATING_ROWS)),
+    ("reorderable", Bool),
+    ("horizontal-scrolling", Bool),
+];
+const LIST_VIEW: &[(&str, Type)] = &[
+    ("fixed-item-height", Float),
+    ("virtualization-method", Enum(VIRTUALIZATION_METHODS)),
+    ("show-border", Bool),
+    ("selection-type", Enum(SELECTION_TYPES)),
+    ("show-alternating-row-backgrounds", Enum(ALTERNATING_ROWS)),
+    ("reorderable", Bool),
+    ("reorder-mode", Enum(&["Simple", "Animated"])),
+    ("horizontal-scrolling", Bool),
+    ("show-foldout-header", Bool),
+    ("header-title", Str),
+    ("show-add-remove-footer", Bool),
+    ("show-bound-collection-size", Bool),
+];
+
+static ELEMENTS: &[Element] = &[
+    element(
+        "VisualElement",
+        Engine,
+        None,
+        &[
+            ("name", Str),
+            ("view-data-key", Str),
+            ("picking-mode", Enum(&["Position", "Ignore"])),
+            ("tooltip", Str),
+            ("usage-hints", Enum(&["None", "DynamicTransform", "GroupTransform", "MaskContainer", "DynamicColor"])),
+            ("tabindex", Int),
+            ("focusable", Bool),
+            ("class", Str),
+            ("content-container", Str),
+            ("style", Str),
+        ],
+    ),
+    element("BindableElement", Engine, Some("VisualElement"), &[("binding-path", Str)]),
+    element(
+        "TextElement",
+        Engine,
+        Some("BindableElement"),
+        &[("text", Str), ("enable-rich-text", Bool), ("display-tooltip-when-elided", Bool)],
+    ),
+    element("Label", Engine, Some("TextElement"), &[]),
+    element("Button", Engine, Some("TextElement"), &[]),
+    element("RepeatButton", Engine, Some("TextElement"), &[("delay", Int), ("interval", Int)]),
+    element("Image", Engine, Some("VisualElement"), &[]),
+    element("IMGUIContainer", Engine, Some("VisualElement"), &[]),
+    element("Box", Engine, Some("VisualElement"), &[]),
+    element("GroupBox", Engine, Some("BindableElement"), &[("text", Str)]),
+    element("PopupWindow", Engine, Some("TextElement"), &[]),
+    element("TemplateContainer", Engine, Some("BindableElement"), &[("template", Str)]),
+    element(
+        "ScrollView",
+        Engine,
+        Some("VisualElement"),
+        &[
+            ("mode", Enum(&["Vertical", "Horizontal", "VerticalAndHorizontal"])),
+            ("horizontal-scroller-visibility", Enum(SCROLLER_VISIBILITIES)),
+            ("vertical-scroller-visibility", Enum(SCROLLER_VISIBILITIES)),
+            ("horizontal-page-size", Float),
+            ("vertical-page-size", Float),
+            ("touch-scroll-type", Enum(&["Unrestricted", "Elastic", "Clamped"])),
+            ("scroll-deceleration-rate", Float),
+            ("elasticity", Float),
+        ],
+    ),
+    element(
+        "Scroller",
+        Engine,
+        Some("VisualElement"),
+        &[("low-value", Float), ("high-value", Float), ("direction", Enum(DIRECTIONS)), ("value", Float)],
+    ),
+    element("Foldout", Engine, Some("BindableElement"), &[("text", Str), ("value", Bool)]),
+    element("Toggle", Engine, Some("BindableElement"), &[("label", Str), ("value", Bool), ("text", Str)]),
+    element(
+        "Slider",
+        Engine,
+        Some("BindableElement"),
+        &[
+            ("label", Str),
+            ("value", Float),
+            ("low-value", Float),
+            ("high-value", Float),
+            ("page-size", Float),
+            ("show-input-field", Bool),
+            ("direction", Enum(DIRECTIONS)),
+            ("inverted", Bool),
+        ],
+    ),
+    element(
+        "SliderInt",
+        Engine,
+        Some("BindableElement"),
+        &[
+            ("label", Str),
+            ("value", Int),
+            ("low-value", Int),
+            ("high-value", Int),
+            ("page-size", Float),
+            ("show-input-field", Bool),
+            ("direction", Enum(DIRECTIONS)),
+            ("inverted", Bool),
+        ],
+    ),
+    element(
+        "MinMaxSlider",
+        Engine,
+        Some("BindableElement"),
+        &[("label", Str), ("min-value", Float), ("max-value", Float), ("low-limit", Float), ("high-limit", Float)],
+    ),
+    element(
+        "ProgressBar",
+        Engine,
+        Some("BindableElement"),
+        &[("low-value", Float), ("high-value", Float), ("value", Float), ("title", Str)],
+    ),
+    element(
+        "TextField",
+        Engine,
+        Some("BindableElement"),
+        &[
+            ("label", Str),
+            ("value", Str),
+            ("text", Str),
+            ("max-length", Int),
+            ("password", Bool),
+            ("mask-character", Str),
+            ("multiline", Bool),
+            ("readonly", Bool),
+            ("is-delayed", Bool),
+        ],
+    ),
+    element("DropdownField", Engine, Some("BindableElement"), &[("label", Str), ("index", Int), ("choices", Str), ("value", Str)]),
+    element(
+        "EnumField",
+        Engine,
+        Some("BindableElement"),
+        &[("label", Str), ("type", Str), ("value", Str), ("include-obsolete-values", Bool)],
+    ),
+    element("RadioButton", Engine, Some("BindableElement"), &[("label", Str), ("value", Bool), ("text", Str)]),
+    element("RadioButtonGroup", Engine, Some("BindableElement"), &[("label", Str), ("value", Int), ("choices", Str)]),
+    element("ListView", Engine, Some("BindableElement"), LIST_VIEW),
+    element_since("TreeView", Engine, Some("BindableElement"), COLLECTION_VIEW, UnityVersion::new(2022, 2)),
+    element_since("MultiColumnListView", Engine, Some("BindableElement"), LIST_VIEW, UnityVersion::new(2022, 2)),
+    element_since("MultiColumnTreeView", Engine, Some("BindableElement"), COLLECTION_VIEW, UnityVersion::new(2022, 2)),
+    element_since("Tab", Engine, Some("VisualElement"), &[("label", Str), ("closeable", Bool)], UnityVersion::new(2023, 2)),
+    element_since("TabView", Engine, Some("VisualElement"), &[("reorderable", Bool)], UnityVersion::new(2023, 2)),
+    element_since(
+        "ToggleButtonGroup",
+        Engine,
+        Some("BindableElement"),
+        &[("label", Str), ("is-multiple-selection", Bool), ("allow-empty-selection", Bool)],
+        UnityVersion::new(2023, 2),
+    ),
+    element_since(
+        "TwoPaneSplitView",
+        Engine,
+        Some("VisualElement"),
+        &[("fixed-pane-index", Int), ("fixed-pane-initial-dimension", Float), ("orientation", Enum(DIRECTIONS))],
+        UnityVersion::new(2023, 1),
+    ),
+    element("IntegerField", EngineSince(RUNTIME_FIELDS), Some("BindableElement"), TEXT_INPUT_FIELD),
+    element("LongField", EngineSince(RUNTIME_FIELDS), Some("BindableElement"), TEXT_INPUT_FIELD),
+    element("FloatField", EngineSince(RUNTIME_FIELDS), Some("BindableElement"), TEXT_INPUT_FIELD),
+    element("DoubleField", EngineSince(RUNTIME_FIELDS), Some("BindableElement"), TEXT_INPUT_FIELD),
+    element("Hash128Field", EngineSince(RUNTIME_FIELDS), Some("BindableElement"), TEXT_INPUT_FIELD),
+    element_since("UnsignedIntegerField", Engine, Some("BindableElement"), TEXT_INPUT_FIELD, UnityVersion::new(2022, 3)),
+    element_since("UnsignedLongField", Engine, Some("BindableElement"), TEXT_INPUT_FIELD, UnityVersion::new(2022, 3)),
+    element("Vector2Field", EngineSince(RUNTIME_FIELDS), Some("BindableElement"), FIELD),
+    element("Vector3Field", EngineSince(RUNTIME_FIELDS), Some("BindableElement"), FIELD),
+    element("Vector4Field", EngineSince(RUNTIME_FIELDS), Some("BindableElement"), FIELD),
+    element("Vector2IntField", EngineSince(RUNTIME_FIELDS), Some("BindableElement"), FIELD),
+    element("Vector3IntField", EngineSince(RUNTIME_FIELDS), Some("BindableElement"), FIELD),
+    element("RectField", EngineSince(RUNTIME_FIELDS), Some("BindableElement"), FIELD),
+    element("RectIntField", EngineSince(RUNTIME_FIELDS), Some("BindableElement"), FIELD),
+    element("BoundsField", EngineSince(RUNTIME_FIELDS), Some("BindableElement"), FIELD),
+    element("BoundsIntField", EngineSince(RUNTIME_FIELDS), Some("BindableElement"), FIELD),
+    element("ObjectField", Editor, Some("BindableElement"), &[("label", Str), ("allow-scene-objects", Bool), ("type", Str)]),
+    element(
+        "ColorField",
+        Editor,
+        Some("BindableElement"),
+        &[("label", Str), ("value", Str), ("show-eye-dropper", Bool), ("show-alpha", Bool), ("hdr", Bool)],
+    ),
+    element("CurveField", Editor, Some("BindableElement"), &[("label", Str)]),
+    element("GradientField", Editor, Some("BindableElement"), &[("label", Str)]),
+    element("LayerField", Editor, Some("BindableElement"), &[("label", Str), ("value", Int)]),
+    element("LayerMaskField", Editor, Some("BindableElement"), &[("label", Str), ("value", Int)]),
+    element("MaskField", Editor, Some("BindableElement"), &[("label", Str), ("choices", Str), ("value", Int)]),
+    element("TagField", Editor, Some("BindableElement"), FIELD),
+    element("EnumFlagsField", Editor, Some("BindableElement"), &[("label", Str), ("type", Str), ("value", Str)]),
+    element("PropertyField", Editor, Some("VisualElement"), &[("binding-path", Str), ("label", Str)]),
+    element("InspectorElement", Editor, Some("BindableElement"), &[]),
+    element("Toolbar", Editor, Some("VisualElement"), &[]),
+    element("ToolbarButton", Editor, Some("Button"), &[]),
+    element("ToolbarToggle", Editor, Some("Toggle"), &[]),
+    element("ToolbarMenu", Editor, Some("TextElement"), &[]),
+    element("ToolbarSearchField", Editor, Some("VisualElement"), &[]),
+    element("ToolbarPopupSearchField", Editor, Some("ToolbarSearchField"), &[]),
+    element("ToolbarSpacer", Editor, Some("VisualElement"), &[]),
+    element("ToolbarBreadcrumbs", Editor, Some("VisualElement"), &[]),
+];
+
+/// Built-in elements of a Unity version, of the latest supported version if it isn't known
+pub fn fallback_elements(version: Option<UnityVersion>) -> Vec<VisualElementInfo> {
+    let included: Vec<&Element> = ELEMENTS
+        .iter()
+        .filter(|element| match (element.since, version) {
+            (Some(since), Some(version)) => since <= version,
+            _ => true,
+        })
+        .collect();
+
+    included
+        .iter()
+        .map(|element| {
+            let namespace = namespace(element, version);
+            VisualElementInfo {
+                name: element.name.to_string(),
+                namespace: namespace.to_string(),
+                fully_qualified_name: format!("{}.{}", namespace, element.name),
+                attributes: attributes(element),
+                base: element
+                    .base
+                    .and_then(|base| included.iter().find(|other| other.name == base))
+                    .map(|base| format!("{}.{}", self::namespace(base, version), base.name)),
+            }
+        })
+        .collect()
+}
+
+fn namespace(element: &Element, version: Option<UnityVersion>) -> &'static str {
+    match element.namespace {
+        Engine => ENGINE_NAMESPACE,
+        Editor => EDITOR_NAMESPACE,
+        EngineSince(since) if version.is_some_and(|version| version < since) => EDITOR_NAMESPACE,
+        EngineSince(_) => ENGINE_NAMESPACE,
+    }
+}
+
+/// Attributes of the bases first, then the ones of the element, an attribute the element redeclares keeps its place
+fn attributes(element: &Element) -> Vec<UxmlAttributeInfo> {
+    let mut chain = vec![element];
+    while let Some(base) = chain.last().and_then(|e| e.base).and_then(|base| ELEMENTS.iter().find(|e| e.name == base)) {
+        chain.push(base);
+    }
+
+    let mut attributes: Vec<UxmlAttributeInfo> = Vec::new();
+    for (name, value_type) in chain.iter().rev().flat_map(|element| element.attributes) {
+        let attribute = UxmlAttributeInfo { name: name.to_string(), value_type: value_type.to_attribute_type(), default_value: None };
+        match attributes.iter_mut().find(|existing| existing.name == *name) {
+            Some(existing) => *existing = attribute,
+            None => attributes.push(attribute),
+        }
+    }
+    attributes
+}
+
+impl Type {
+    fn to_attribute_type(self) -> UxmlAttributeType {
+        match self {
+            Type::String => UxmlAttributeType::Other("xs:string".to_string()),
+            Type::Int => UxmlAttributeType::Other("xs:int".to_string()),
+            Type::Float => UxmlAttributeType::Other("xs:float".to_string()),
+            Type::Bool => UxmlAttributeType::Boolean,
+            Type::Enum(values) => UxmlAttributeType::Enumeration(values.iter().map(|v| v.to_string()).collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'a>(elements: &'a [VisualElementInfo], name: &str) -> Option<&'a VisualElementInfo> {
+        elements.iter().find(|element| element.name == name)
+    }
+
+    #[test]
+    fn test_fallback_elements_by_version() {
+        let elements = fallback_elements(Some(UnityVersion::new(2021, 3)));
+        assert!(find(&elements, "TreeView").is_none());
+        assert!(find(&elements, "TabView").is_none());
+        assert_eq!(find(&elements, "IntegerField").unwrap().fully_qualified_name, "UnityEditor.UIElements.IntegerField");
+
+        let elements = fallback_elements(Some(UnityVersion::new(2022, 3)));
+        assert!(find(&elements, "TreeView").is_some());
+        assert!(find(&elements, "TabView").is_none());
+        assert_eq!(find(&elements, "IntegerField").unwrap().fully_qualified_name, "UnityEngine.UIElements.IntegerField");
+
+        let elements = fallback_elements(None);
+        assert!(find(&elements, "TabView").is_some());
+        assert_eq!(find(&elements, "ObjectField").unwrap().namespace, EDITOR_NAMESPACE);
+    }
+
+    #[test]
+    fn test_fallback_attributes_and_bases() {
+        let elements = fallback_elements(Some(UnityVersion::new(6000, 0)));
+        let button = find(&elements, "Button").unwrap();
+        assert_eq!(button.base.as_deref(), Some("UnityEngine.UIElements.TextElement"));
+        let names: Vec<&str> = button.attributes.iter().map(|attribute| attribute.name.as_str()).collect();
+        assert_eq!(&names[..2], &["name", "view-data-key"]);
+        assert!(names.contains(&"binding-path") && names.contains(&"text"));
+
+        let picking_mode = button.attribute("picking-mode").unwrap();
+        assert_eq!(
+            picking_mode.value_type,
+            UxmlAttributeType::Enumeration(vec!["Position".to_string(), "Ignore".to_string()])
+        );
+        assert_eq!(find(&elements, "ToolbarToggle").unwrap().attribute("value").unwrap().value_type, UxmlAttributeType::Boolean);
+
+        // every base is an element of the same version
+        for element in &elements {
+            if let Some(base) = &element.base {
+                assert!(elements.iter().any(|other| &other.fully_qualified_name == base), "{}", base);
+            }
+        }
+        assert!(find(&elements, "VisualElement").unwrap().base.is_none());
+    }
+}
@@ -0,0 +1,219 @@
+//! UXML Templates
+//!
+//! `<Template name="Item" src="Item.uxml" />` declares a template in a UXML document, and
+//! `<Instance template="Item" />` creates an instance of it. `src` is a path relative to the document,
+//! a path from the project root (`/Assets/UI/Item.uxml`) or a project URL written by the UI Builder
+//! (`project://database/Assets/UI/Item.uxml?fileID=...&guid=...&type=3#Item`).
+//!
+//! The editor asks for the template file of an instance with [`UXML_TEMPLATE_DEFINITION_METHOD`] and for
+//! unknown templates and missing template files with [`UXML_TEMPLATE_DIAGNOSTICS_METHOD`].
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::*;
+
+use crate::language::project_packages::ProjectPackages;
+use crate::uxml::parser::{UxmlAttributeValue, UxmlElement};
+
+/// Custom request for the definition of a template, takes [`UxmlTemplateDefinitionParams`] and returns `Location | null`
+pub const UXML_TEMPLATE_DEFINITION_METHOD: &str = "unityCode/uxmlTemplateDefinition";
+
+/// Custom request for diagnostics of the templates of a document, takes [`UxmlTemplateDiagnosticsParams`] and returns `Diagnostic[]`
+pub const UXML_TEMPLATE_DIAGNOSTICS_METHOD: &str = "unityCode/uxmlTemplateDiagnostics";
+
+/// Diagnostic code of instances of templates the document doesn't declare
+pub const UNKNOWN_TEMPLATE_CODE: &str = "unknown-template";
+/// Diagnostic code of templates whose `src` is not a file, or that have no `src`
+pub const MISSING_TEMPLATE_SOURCE_CODE: &str = "missing-template-source";
+/// Diagnostic code of templates declared twice with the same name
+pub const DUPLICATE_TEMPLATE_CODE: &str = "duplicate-template";
+
+/// Parameters of [`UXML_TEMPLATE_DEFINITION_METHOD`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UxmlTemplateDefinitionParams {
+    /// The UXML document
+    pub text_document: TextDocumentIdentifier,
+    /// Content of the document if it has unsaved changes, otherwise it's read from disk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Position in the `template` attribute of an `Instance` or the `src` attribute of a `Template`
+    pub position: Position,
+}
+
+/// Parameters of [`UXML_TEMPLATE_DIAGNOSTICS_METHOD`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UxmlTemplateDiagnosticsParams {
+    /// The UXML document
+    pub text_document: TextDocumentIdentifier,
+    /// Content of the document if it has unsaved changes, otherwise it's read from disk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+/// A `<Template>` of a document
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateDeclaration<'a> {
+    pub element: &'a UxmlElement,
+    pub name: &'a UxmlAttributeValue,
+    /// `None` for templates loaded from `Resources` with a `path` attribute
+    pub src: Option<&'a UxmlAttributeValue>,
+}
+
+/// The `<Template>` elements of a document, in document order
+pub fn template_declarations(elements: &[UxmlElement]) -> Vec<TemplateDeclaration<'_>> {
+    elements
+        .iter()
+        .filter(|element| element.tag == "Template")
+        .filter_map(|element| {
+            Some(TemplateDeclaration {
+                element,
+                name: element.attribute("name")?,
+                src: element.attribute("src"),
+            })
+        })
+        .collect()
+}
+
+/// Path of the file a template `src` refers to, whether it exists or not
+///
+/// `document_path` is the path of the UXML document, relative paths are relative to its folder.
+/// Paths in packages are resolved with `packages`, packages can be elsewhere than in `Packages`.
+pub fn resolve_template_src(
+    src: &str,
+    document_path: &Path,
+    project_root: &Path,
+    packages: Option<&ProjectPackages>,
+) -> Option<PathBuf> {
+    // drop the query (`?fileID=...&guid=...`) and the fragment of project URLs
+    let path = src.split(['?', '#']).next()?.trim();
+    let path = path.strip_prefix("project://database").or_else(|| path.strip_prefix("project:")).unwrap_or(path);
+    let path = urlencoding::decode(path).map(|p| p.into_owned()).unwrap_or_else(|_| path.to_string());
+    if path.is_empty() {
+        return None;
+    }
+
+    match path.strip_prefix('/') {
+        Some(relative) => {
+            let in_project = project_root.join(relative);
+            if !in_project.exists() {
+                if let Some(in_package) = packages.and_then(|packages| packages.resolve(relative)) {
+                    return Some(in_package);
+                }
+            }
+            Some(in_project)
+        }
+        None => Some(document_path.parent()?.join(path)),
+    }
+}
+
+/// Resolves the templates of a UXML document
+pub struct TemplateResolver<'a> {
+    pub document_path: &'a Path,
+    pub project_root: &'a Path,
+    pub packages: Option<&'a ProjectPackages>,
+}
+
+impl TemplateResolver<'_> {
+    fn resolve(&self, src: &str) -> Option<PathBuf> {
+        resolve_template_src(src, self.document_path, self.project_root, self.packages)
+    }
+
+    /// The template file of the `Instance` or `Template` at a position
+    ///
+    /// An instance of a template whose file is missing goes to the declaration of the template.
+    pub fn definition(&self, elements: &[UxmlElement], document_uri: &Url, position: Position) -> Option<Location> {
+        let templates = template_declarations(elements);
+        let contains = |value: &UxmlAttributeValue| value.range.start <= position && position <= value.range.end;
+
+        let declaration = elements.iter().find_map(|element| match element.tag.as_str() {
+            "Instance" => {
+                let template = element.attribute("template").filter(|value| contains(value))?;
+                templates.iter().find(|t| t.name.value == template.value)
+            }
+            "Template" => templates
+                .iter()
+                .find(|t| std::ptr::eq(t.element, element) && t.src.is_some_and(|src| contains(src))),
+            _ => None,
+        })?;
+
+        let file = declaration.src.and_then(|src| self.resolve(&src.value)).filter(|path| path.is_file());
+        match file {
+            Some(path) => Some(Location::new(Url::from_file_path(path).ok()?, Range::default())),
+            None => Some(Location::new(document_uri.clone(), declaration.element.tag_range)),
+        }
+    }
+
+    /// Diagnostics for unknown templates, missing template files and duplicate templates
+    pub fn diagnostics(&self, elements: &[UxmlElement]) -> Vec<Diagnostic> {
+        let templates = template_declarations(elements);
+        let mut diagnostics = Vec::new();
+
+        let mut names: HashMap<&str, usize> = HashMap::new();
+        for template in &templates {
+            let count = names.entry(template.name.value.as_str()).or_default();
+            *count += 1;
+            if *count == 2 {
+                diagnostics.push(diagnostic(
+                    template.name.range,
+                    DiagnosticSeverity::WARNING,
+                    DUPLICATE_TEMPLATE_CODE,
+                    format!("Template '{}' is declared more than once", template.name.value),
+                ));
+            }
+
+            let Some(src) = template.src else {
+                if template.element.attribute("path").is_none() {
+                    diagnostics.push(diagnostic(
+                        template.name.range,
+                        DiagnosticSeverity::ERROR,
+                        MISSING_TEMPLATE_SOURCE_CODE,
+                        format!("Template '{}' needs a src attribute", template.name.value),
+                    ));
+                }
+                continue;
+            };
+            if !self.resolve(&src.value).is_some_and(|path| path.is_file()) {
+                diagnostics.push(diagnostic(
+                    src.range,
+                    DiagnosticSeverity::ERROR,
+                    MISSING_TEMPLATE_SOURCE_CODE,
+                    format!("Template file '{}' doesn't exist", src.value),
+                ));
+            }
+        }
+
+        for element in elements.iter().filter(|element| element.tag == "Instance") {
+            let Some(template) = element.attribute("template") else {
+                continue;
+            };
+            if !names.contains_key(template.value.as_str()) {
+                diagnostics.push(diagnostic(
+                    template.range,
+                    DiagnosticSeverity::ERROR,
+                    UNKNOWN_TEMPLATE_CODE,
+                    format!(
+                        "Unknown template '{}', declare it with <ui:Template name=\"{}\" src=\"...\" />",
+                        template.value, template.value
+                    ),
+                ));
+            }
+        }
+
+        diagnostics
+    }
+}
+
+fn diagnostic(range: Range, severity: DiagnosticSeverity, code: &str, message: String) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        code: Some(NumberOrString::String(code.to_string())),
+        source: Some("uxml".to_string()),
+        message,
+        ..Default::default()
+    }
+}
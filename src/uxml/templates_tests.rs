@@ -0,0 +1,109 @@
+use std::fs;
+
+use tempfile::TempDir;
+use tower_lsp::lsp_types::{DiagnosticSeverity, NumberOrString, Position, Url};
+
+use super::templates::*;
+use crate::uxml::parser::parse_uxml_elements;
+
+const MAIN: &str = r#"<ui:UXML xmlns:ui="UnityEngine.UIElements">
+    <ui:Template name="Item" src="Item.uxml" />
+    <ui:Template name="Header" src="project://database/Assets/Shared/Header%20Bar.uxml?fileID=9197481963319205126&amp;guid=0&amp;type=3#Header" />
+    <ui:Template name="Missing" src="/Assets/UI/Missing.uxml" />
+    <ui:Template name="Icon" path="UI/Icon" />
+    <ui:Instance template="Item" />
+    <ui:Instance template="Header" />
+    <ui:Instance template="Missing" />
+    <ui:Instance template="Footer" />
+</ui:UXML>"#;
+
+fn project() -> TempDir {
+    let project = TempDir::new().unwrap();
+    fs::create_dir_all(project.path().join("Assets/UI")).unwrap();
+    fs::create_dir_all(project.path().join("Assets/Shared")).unwrap();
+    fs::write(project.path().join("Assets/UI/Main.uxml"), MAIN).unwrap();
+    fs::write(project.path().join("Assets/UI/Item.uxml"), "<ui:UXML />").unwrap();
+    fs::write(project.path().join("Assets/Shared/Header Bar.uxml"), "<ui:UXML />").unwrap();
+    project
+}
+
+#[test]
+fn test_resolve_template_src() {
+    let root = std::path::Path::new("/project");
+    let document = root.join("Assets/UI/Main.uxml");
+    let resolve = |src: &str| resolve_template_src(src, &document, root, None);
+
+    assert_eq!(resolve("Item.uxml"), Some(root.join("Assets/UI/Item.uxml")));
+    assert_eq!(resolve("../Shared/Item.uxml"), Some(root.join("Assets/UI/../Shared/Item.uxml")));
+    assert_eq!(resolve("/Assets/Shared/Item.uxml"), Some(root.join("Assets/Shared/Item.uxml")));
+    assert_eq!(
+        resolve("project://database/Assets/My%20UI/Item.uxml?fileID=1&guid=2&type=3#Item"),
+        Some(root.join("Assets/My UI/Item.uxml"))
+    );
+    assert_eq!(resolve("?fileID=1"), None);
+}
+
+#[test]
+fn test_template_diagnostics() {
+    let project = project();
+    let document_path = project.path().join("Assets/UI/Main.uxml");
+    let resolver = TemplateResolver {
+        document_path: &document_path,
+        project_root: project.path(),
+        packages: None,
+    };
+
+    let content = MAIN.replace(
+        "</ui:UXML>",
+        "<ui:Template name=\"Item\" src=\"Item.uxml\" />\n<ui:Template name=\"Broken\" />\n</ui:UXML>",
+    );
+    let diagnostics = resolver.diagnostics(&parse_uxml_elements(&content));
+    let codes: Vec<_> = diagnostics
+        .iter()
+        .map(|d| match &d.code {
+            Some(NumberOrString::String(code)) => (code.as_str(), d.severity.unwrap()),
+            _ => panic!("diagnostic without a code"),
+        })
+        .collect();
+    assert_eq!(
+        codes,
+        vec![
+            (MISSING_TEMPLATE_SOURCE_CODE, DiagnosticSeverity::ERROR),
+            (DUPLICATE_TEMPLATE_CODE, DiagnosticSeverity::WARNING),
+            (MISSING_TEMPLATE_SOURCE_CODE, DiagnosticSeverity::ERROR),
+            (UNKNOWN_TEMPLATE_CODE, DiagnosticSeverity::ERROR),
+        ]
+    );
+    assert!(diagnostics[0].message.contains("/Assets/UI/Missing.uxml"));
+    assert!(diagnostics[2].message.contains("Broken"));
+    assert_eq!(diagnostics[3].range.start, Position::new(8, 27));
+}
+
+#[test]
+fn test_template_definition() {
+    let project = project();
+    let document_path = project.path().join("Assets/UI/Main.uxml");
+    let uri = Url::from_file_path(&document_path).unwrap();
+    let resolver = TemplateResolver {
+        document_path: &document_path,
+        project_root: project.path(),
+        packages: None,
+    };
+    let elements = parse_uxml_elements(MAIN);
+    let definition = |line, character| resolver.definition(&elements, &uri, Position::new(line, character));
+
+    let item = definition(5, 28).unwrap();
+    assert_eq!(item.uri.to_file_path().unwrap(), project.path().join("Assets/UI/Item.uxml"));
+    let header = definition(6, 28).unwrap();
+    assert_eq!(header.uri.to_file_path().unwrap(), project.path().join("Assets/Shared/Header Bar.uxml"));
+    // from the src of a template
+    assert_eq!(definition(1, 36).unwrap(), item);
+
+    // the file of the template is missing, go to its declaration
+    let missing = definition(7, 28).unwrap();
+    assert_eq!(missing.uri, uri);
+    assert_eq!(missing.range.start, Position::new(3, 5));
+
+    assert_eq!(definition(8, 28), None);
+    assert_eq!(definition(5, 10), None);
+}
@@ -0,0 +1,295 @@
+//! UXML Usage Index
+//!
+//! Indexes the elements of all UXML files in a Unity project so that USS selectors
+//! can be matched against them (e.g. to count how many elements a rule applies to).
+//!
+//! The project is [scanned](crate::language::project_scan) again when the index is stale, and the index is
+//! refreshed incrementally: only files whose modification time changed are parsed again.
+//! It can be saved to the [index cache](crate::language::index_cache) to start from in the next session.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Location, Url};
+
+use crate::language::cancellation::{CancellationToken, Cancelled};
+use crate::language::index_cache::{load_index, save_index};
+use crate::language::progress::ProgressCounter;
+use crate::language::project_scan::{find_files, is_in_scanned_directory, ScanTimer};
+use crate::uss::selector::{Combinator, ComplexSelector, CompoundSelector};
+use crate::uxml::parser::{parse_uxml_elements, UxmlElement};
+
+/// Name of the index in the index cache
+const CACHE_NAME: &str = "uxml_elements";
+
+/// Elements of a single indexed UXML file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedUxmlFile {
+    pub last_modified: Option<SystemTime>,
+    pub elements: Vec<UxmlElement>,
+}
+
+/// A UXML element matched by a selector
+#[derive(Debug, Clone)]
+pub struct UxmlElementMatch<'a> {
+    pub path: &'a Path,
+    pub element: &'a UxmlElement,
+}
+
+impl UxmlElementMatch<'_> {
+    /// Location of the element's tag name
+    pub fn location(&self) -> Option<Location> {
+        Some(Location {
+            uri: Url::from_file_path(self.path).ok()?,
+            range: self.element.tag_range,
+        })
+    }
+}
+
+/// Project-wide index of UXML elements
+#[derive(Debug)]
+pub struct UxmlUsageIndex {
+    project_root: PathBuf,
+    files: HashMap<PathBuf, IndexedUxmlFile>,
+    scan: ScanTimer,
+}
+
+impl UxmlUsageIndex {
+    pub fn new(project_root: PathBuf) -> Self {
+        Self {
+            project_root,
+            files: HashMap::new(),
+            scan: ScanTimer::default(),
+        }
+    }
+
+    /// Whether the last scan is older than the refresh interval
+    pub fn is_stale(&self) -> bool {
+        self.scan.is_stale()
+    }
+
+    /// Scan the project for UXML files, parsing new and modified files and dropping deleted ones
+    pub fn refresh(&mut self) {
+        let plan = self.plan_refresh();
+        if let Ok(parsed) = parse_files(&plan.to_parse, &CancellationToken::new(), &ProgressCounter::new()) {
+            self.apply_refresh(plan, parsed);
+        }
+    }
+
+    /// Find the UXML files in the project and the ones that need to be parsed
+    ///
+    /// Parsing is the expensive part of a refresh, it is done separately by [`parse_files`]
+    /// so it can run without access to the index and be cancelled.
+    pub fn plan_refresh(&self) -> RefreshPlan {
+        RefreshPlan::new(&self.project_root, &self.indexed_versions())
+    }
+
+    /// Modification times of the indexed files, to plan a refresh without access to the index
+    pub fn indexed_versions(&self) -> HashMap<PathBuf, Option<SystemTime>> {
+        self.files.iter().map(|(path, file)| (path.clone(), file.last_modified)).collect()
+    }
+
+    /// Complete a refresh with the files parsed for the plan
+    pub fn apply_refresh(&mut self, plan: RefreshPlan, parsed: Vec<(PathBuf, Option<IndexedUxmlFile>)>) {
+        let found: HashSet<&PathBuf> = plan.found.iter().collect();
+        self.files.retain(|path, _| found.contains(path));
+
+        for (path, file) in parsed {
+            match file {
+                Some(file) => {
+                    self.files.insert(path, file);
+                }
+                None => {
+                    self.files.remove(&path);
+                }
+            }
+        }
+
+        self.scan.mark_scanned();
+    }
+
+    /// Read files the editor reported as created, changed or deleted, without scanning the project
+    ///
+    /// Files outside of the scanned directories are ignored. Only the project of the index is used, so
+    /// any index of the project can read them. Reads the files, async code should call it with
+    /// `spawn_blocking` and apply them with [`apply_changed_files`](Self::apply_changed_files).
+    pub fn read_changed_files(&self, paths: &[PathBuf]) -> ChangedUxmlFiles {
+        let (existing, removed): (Vec<PathBuf>, Vec<PathBuf>) = paths
+            .iter()
+            .filter(|path| is_in_scanned_directory(&self.project_root, path))
+            .cloned()
+            .partition(|path| path.is_file());
+
+        let mut files: Vec<(PathBuf, Option<IndexedUxmlFile>)> = removed.into_iter().map(|path| (path, None)).collect();
+        if let Ok(parsed) = parse_files(&existing, &CancellationToken::new(), &ProgressCounter::new()) {
+            files.extend(parsed);
+        }
+        ChangedUxmlFiles { files }
+    }
+
+    /// Index the files read by [`read_changed_files`](Self::read_changed_files), files that no longer exist are dropped
+    pub fn apply_changed_files(&mut self, changed: ChangedUxmlFiles) {
+        for (path, file) in changed.files {
+            match file {
+                Some(file) => {
+                    self.files.insert(path, file);
+                }
+                None => {
+                    self.files.remove(&path);
+                }
+            }
+        }
+    }
+
+    /// Whether the project was scanned at least once
+    pub fn is_indexed(&self) -> bool {
+        self.scan.is_scanned()
+    }
+
+    /// Start from the files indexed in the last session, true if there were any
+    ///
+    /// The index is still stale, the next refresh parses the files that changed since.
+    pub fn load_cache(&mut self) -> bool {
+        let Some(files) = load_index::<HashMap<PathBuf, IndexedUxmlFile>>(&self.project_root, CACHE_NAME) else {
+            return false;
+        };
+        self.files = files;
+        !self.files.is_empty()
+    }
+
+    /// Save the indexed files for the next session
+    pub fn save_cache(&self) -> io::Result<()> {
+        save_index(&self.project_root, CACHE_NAME, &self.files)
+    }
+
+    /// All indexed files
+    pub fn files(&self) -> &HashMap<PathBuf, IndexedUxmlFile> {
+        &self.files
+    }
+
+    /// Find all elements matched by any of the selectors, each element is reported once
+    pub fn find_matching_elements(&self, selectors: &[ComplexSelector]) -> Vec<UxmlElementMatch<'_>> {
+        let mut matches = Vec::new();
+
+        // sort for deterministic results
+        let mut paths: Vec<&PathBuf> = self.files.keys().collect();
+        paths.sort();
+
+        for path in paths {
+            let elements = &self.files[path].elements;
+            for (index, element) in elements.iter().enumerate() {
+                if selectors.iter().any(|s| selector_matches(s, elements, index)) {
+                    matches.push(UxmlElementMatch { path, element });
+                }
+            }
+        }
+
+        matches
+    }
+}
+
+/// UXML files read by [`UxmlUsageIndex::read_changed_files`], `None` for files that were deleted or can't be read
+pub struct ChangedUxmlFiles {
+    files: Vec<(PathBuf, Option<IndexedUxmlFile>)>,
+}
+
+/// UXML files found by a scan, see [`UxmlUsageIndex::plan_refresh`]
+#[derive(Debug, Clone, Default)]
+pub struct RefreshPlan {
+    /// All UXML files in the project
+    pub found: Vec<PathBuf>,
+    /// New and modified files
+    pub to_parse: Vec<PathBuf>,
+}
+
+impl RefreshPlan {
+    /// Find the UXML files in the project and the ones that changed since the versions that are indexed
+    ///
+    /// Walks the project, async code should call it with `spawn_blocking`.
+    pub fn new(project_root: &Path, indexed: &HashMap<PathBuf, Option<SystemTime>>) -> Self {
+        let found = find_files(project_root, "uxml");
+        let to_parse = found
+            .iter()
+            .filter(|path| {
+                let last_modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+                !indexed
+                    .get(*path)
+                    .is_some_and(|indexed| indexed.is_some() && *indexed == last_modified)
+            })
+            .cloned()
+            .collect();
+
+        RefreshPlan { found, to_parse }
+    }
+}
+
+/// Parse UXML files, `None` for files that can't be read
+///
+/// Each parsed file advances `progress`. Stops with `Err(Cancelled)` as soon as the token is cancelled.
+pub fn parse_files(
+    paths: &[PathBuf],
+    token: &CancellationToken,
+    progress: &ProgressCounter,
+) -> Result<Vec<(PathBuf, Option<IndexedUxmlFile>)>, Cancelled> {
+    let mut parsed = Vec::with_capacity(paths.len());
+    progress.add_total(paths.len());
+    for path in paths {
+        token.check()?;
+        progress.advance();
+
+        let last_modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+        let file = match fs::read_to_string(path) {
+            Ok(content) => Some(IndexedUxmlFile {
+                last_modified,
+                elements: parse_uxml_elements(&content),
+            }),
+            Err(e) => {
+                log::warn!("Failed to read UXML file {}: {}", path.display(), e);
+                None
+            }
+        };
+        parsed.push((path.clone(), file));
+    }
+    Ok(parsed)
+}
+
+/// Check if a complex selector matches the element at `index`, taking combinators into account
+pub fn selector_matches(selector: &ComplexSelector, elements: &[UxmlElement], index: usize) -> bool {
+    matches_from(selector, selector.parts.len() - 1, elements, index)
+}
+
+/// Match `selector.parts[..=part]` with `parts[part]` matched against the element at `index`
+fn matches_from(selector: &ComplexSelector, part: usize, elements: &[UxmlElement], index: usize) -> bool {
+    let (combinator, compound) = &selector.parts[part];
+    if !compound_matches(compound, &elements[index]) {
+        return false;
+    }
+
+    let Some(combinator) = combinator else {
+        return true;
+    };
+
+    match combinator {
+        Combinator::Child => elements[index]
+            .parent
+            .is_some_and(|parent| matches_from(selector, part - 1, elements, parent)),
+        Combinator::Descendant => {
+            let mut ancestor = elements[index].parent;
+            while let Some(current) = ancestor {
+                if matches_from(selector, part - 1, elements, current) {
+                    return true;
+                }
+                ancestor = elements[current].parent;
+            }
+            false
+        }
+    }
+}
+
+fn compound_matches(compound: &CompoundSelector, element: &UxmlElement) -> bool {
+    compound.matches_element(&element.tag, element.name_value(), &element.class_names())
+}
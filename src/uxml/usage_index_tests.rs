@@ -0,0 +1,140 @@
+use std::fs;
+
+use tempfile::TempDir;
+
+use super::usage_index::*;
+use crate::language::cancellation::{CancellationToken, Cancelled};
+use crate::language::progress::ProgressCounter;
+use crate::test_utils::get_unity_project_root;
+use crate::uss::selector::ComplexSelector;
+use crate::uxml::parser::parse_uxml_elements;
+
+fn selectors(texts: &[&str]) -> Vec<ComplexSelector> {
+    texts.iter().map(|t| ComplexSelector::parse(t).unwrap()).collect()
+}
+
+#[test]
+fn test_find_matching_elements_in_project() {
+    let mut index = UxmlUsageIndex::new(get_unity_project_root());
+    index.refresh();
+
+    let matches = index.find_matching_elements(&selectors(&[".primary-button"]));
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].element.tag, "Button");
+    assert!(matches[0].location().is_some());
+
+    let matches = index.find_matching_elements(&selectors(&[".container > Label", ".container Button"]));
+    assert_eq!(matches.len(), 2);
+
+    let matches = index.find_matching_elements(&selectors(&[".not-used-anywhere"]));
+    assert!(matches.is_empty());
+}
+
+#[test]
+fn test_selector_matches_combinators() {
+    let content = r#"<UXML>
+    <VisualElement class="a">
+        <VisualElement class="b">
+            <Label class="c" />
+        </VisualElement>
+    </VisualElement>
+</UXML>"#;
+    let elements = parse_uxml_elements(content);
+    let label = 3;
+
+    let matches = |text: &str| selector_matches(&ComplexSelector::parse(text).unwrap(), &elements, label);
+    assert!(matches(".a .c"));
+    assert!(matches(".a .b > .c"));
+    assert!(matches(".b > Label"));
+    assert!(!matches(".a > .c"));
+    assert!(!matches(".c .a"));
+}
+
+#[test]
+fn test_refresh_picks_up_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    let ui_dir = temp_dir.path().join("Assets").join("UI");
+    fs::create_dir_all(&ui_dir).unwrap();
+    let file = ui_dir.join("test.uxml");
+    fs::write(&file, r#"<UXML><Label class="x" /></UXML>"#).unwrap();
+
+    let mut index = UxmlUsageIndex::new(temp_dir.path().to_path_buf());
+    index.refresh();
+    assert_eq!(index.find_matching_elements(&selectors(&[".x"])).len(), 1);
+
+    fs::remove_file(&file).unwrap();
+    index.refresh();
+    assert!(index.files().is_empty());
+}
+
+#[test]
+fn test_changed_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let ui_dir = temp_dir.path().join("Assets").join("UI");
+    fs::create_dir_all(&ui_dir).unwrap();
+    let old = ui_dir.join("old.uxml");
+    fs::write(&old, r#"<UXML><Label class="x" /></UXML>"#).unwrap();
+    let mut index = UxmlUsageIndex::new(temp_dir.path().to_path_buf());
+    index.refresh();
+
+    // renamed, read by another index of the project
+    let new = ui_dir.join("new.uxml");
+    fs::rename(&old, &new).unwrap();
+    let outside = temp_dir.path().join("Temp.uxml");
+    fs::write(&outside, r#"<UXML><Label class="x" /></UXML>"#).unwrap();
+    let changed = UxmlUsageIndex::new(temp_dir.path().to_path_buf()).read_changed_files(&[old, new.clone(), outside]);
+    index.apply_changed_files(changed);
+
+    let paths: Vec<&std::path::PathBuf> = index.files().keys().collect();
+    assert_eq!(paths, vec![&new]);
+}
+
+#[test]
+fn test_cancelled_refresh_keeps_index() {
+    let temp_dir = TempDir::new().unwrap();
+    let ui_dir = temp_dir.path().join("Assets").join("UI");
+    fs::create_dir_all(&ui_dir).unwrap();
+    fs::write(ui_dir.join("test.uxml"), r#"<UXML><Label class="x" /></UXML>"#).unwrap();
+
+    let mut index = UxmlUsageIndex::new(temp_dir.path().to_path_buf());
+    let plan = index.plan_refresh();
+    assert_eq!(plan.to_parse.len(), 1);
+
+    let token = CancellationToken::new();
+    token.cancel();
+    assert_eq!(parse_files(&plan.to_parse, &token, &ProgressCounter::new()).err(), Some(Cancelled));
+    assert!(index.is_stale());
+
+    let parsed = parse_files(&plan.to_parse, &CancellationToken::new(), &ProgressCounter::new()).unwrap();
+    index.apply_refresh(plan, parsed);
+    assert!(!index.is_stale());
+    assert_eq!(index.find_matching_elements(&selectors(&[".x"])).len(), 1);
+    assert!(index.plan_refresh().to_parse.is_empty());
+}
+
+#[test]
+fn test_index_cache() {
+    let temp_dir = TempDir::new().unwrap();
+    let ui_dir = temp_dir.path().join("Assets").join("UI");
+    fs::create_dir_all(&ui_dir).unwrap();
+    fs::write(ui_dir.join("a.uxml"), r#"<UXML><Label class="x" /></UXML>"#).unwrap();
+    fs::write(ui_dir.join("b.uxml"), r#"<UXML><Button class="y" /></UXML>"#).unwrap();
+
+    let mut index = UxmlUsageIndex::new(temp_dir.path().to_path_buf());
+    index.refresh();
+    index.save_cache().unwrap();
+
+    // a restarted server only parses the files that changed since
+    let mut restarted = UxmlUsageIndex::new(temp_dir.path().to_path_buf());
+    assert!(restarted.load_cache());
+    assert_eq!(restarted.find_matching_elements(&selectors(&[".x"])).len(), 1);
+    assert!(restarted.plan_refresh().to_parse.is_empty());
+
+    let changed = ui_dir.join("b.uxml");
+    fs::write(&changed, r#"<UXML><Button class="z" /></UXML>"#).unwrap();
+    let modified = fs::metadata(&changed).unwrap().modified().unwrap() + std::time::Duration::from_secs(1);
+    fs::File::options().write(true).open(&changed).unwrap().set_modified(modified).unwrap();
+    let plan = restarted.plan_refresh();
+    assert_eq!(plan.to_parse.len(), 1);
+    assert_eq!(plan.to_parse, vec![changed]);
+}
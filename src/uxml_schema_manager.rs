@@ -8,7 +8,10 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tokio::time::sleep;
 use tokio::fs;
+use crate::cs::custom_elements::CustomElementScanner;
 use crate::dir_changed::{DirChanged};
+use crate::unity_project_manager::UnityVersion;
+use crate::uxml::schema_fallback::fallback_elements;
 
 /// Errors that can occur during UXML schema processing
 #[derive(Error, Debug)]
@@ -33,13 +36,77 @@ pub struct VisualElementInfo {
     pub namespace: String,
     /// The fully qualified name combining namespace and element name (e.g., "UnityEngine.UIElements.Button")
     pub fully_qualified_name: String,
+    /// The UXML attributes of the element, in schema order
+    #[serde(default)]
+    pub attributes: Vec<UxmlAttributeInfo>,
+    /// Fully qualified name of the element this one derives from, `None` for a root such as `VisualElement`
+    ///
+    /// From the base of the element's type (`xs:extension` or `xs:restriction`), or its substitution group
+    /// if no element has the base type.
+    #[serde(default)]
+    pub base: Option<String>,
+}
+
+impl VisualElementInfo {
+    /// Looks up a UXML attribute of the element by name
+    pub fn attribute(&self, name: &str) -> Option<&UxmlAttributeInfo> {
+        self.attributes.iter().find(|attribute| attribute.name == name)
+    }
+}
+
+/// A UXML attribute of a visual element, as declared in the schema
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UxmlAttributeInfo {
+    /// The attribute name (e.g., "picking-mode")
+    pub name: String,
+    /// The values the attribute accepts
+    pub value_type: UxmlAttributeType,
+    /// The default value declared in the schema, if any
+    pub default_value: Option<String>,
+}
+
+/// The values a UXML attribute accepts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum UxmlAttributeType {
+    /// `true` or `false`
+    Boolean,
+    /// One of the listed values, from an `xs:enumeration` restriction
+    Enumeration(Vec<String>),
+    /// Any other type, by its qualified name (e.g., "xs:string" or "xs:float")
+    Other(String),
+}
+
+/// An element declared by a schema
+#[derive(Debug, Clone, PartialEq)]
+struct SchemaElement {
+    name: String,
+    /// Qualified name of the element's type
+    type_name: Option<String>,
+    /// Qualified name of the element it can substitute, e.g. `UnityEngine.UIElements.VisualElement`
+    substitution_group: Option<String>,
 }
 
 #[derive(Debug)]
 struct SchemaFileInfo {
     last_modified: SystemTime,
     namespace: String,
-    elements: Vec<String>,
+    elements: Vec<SchemaElement>,
+    /// Attributes of complex types, by qualified type name
+    complex_types: HashMap<String, Vec<UxmlAttributeInfo>>,
+    /// Qualified base type of complex types, by qualified type name
+    type_bases: HashMap<String, String>,
+    /// Enumeration values of simple types, by qualified type name
+    simple_types: HashMap<String, Vec<String>>,
+}
+
+/// Content of a schema file
+#[derive(Debug, Default)]
+struct ParsedSchema {
+    namespace: String,
+    elements: Vec<SchemaElement>,
+    complex_types: HashMap<String, Vec<UxmlAttributeInfo>>,
+    type_bases: HashMap<String, String>,
+    simple_types: HashMap<String, Vec<String>>,
 }
 
 /// Core data structure containing visual elements and providing lookup functionality
@@ -50,6 +117,8 @@ pub struct VisualElementsData {
     visual_elements: HashMap<String, VisualElementInfo>,
     /// Visual element name to fully qualified name(there might be name collisions, new names will override old name)
     name_to_full_name: HashMap<String, String>,
+    /// Whether the elements are the built-in elements, without the ones of packages and user code
+    incomplete: bool,
 }
 
 impl VisualElementsData {
@@ -58,6 +127,7 @@ impl VisualElementsData {
         Self {
             visual_elements: HashMap::new(),
             name_to_full_name: HashMap::new(),
+            incomplete: false,
         }
     }
 
@@ -108,6 +178,7 @@ impl VisualElementsData {
     pub fn clear(&mut self) {
         self.visual_elements.clear();
         self.name_to_full_name.clear();
+        self.incomplete = false;
     }
 
     /// Inserts a visual element into the collection
@@ -121,6 +192,48 @@ impl VisualElementsData {
     pub fn is_empty(&self) -> bool {
         self.visual_elements.is_empty()
     }
+
+    /// Whether only the built-in elements are known, because the project has no schema, so an unknown
+    /// element may still exist
+    pub fn is_incomplete(&self) -> bool {
+        self.incomplete
+    }
+
+    /// The elements a visual element derives from, nearest first, e.g. `BaseField`, `BindableElement` and
+    /// `VisualElement` for a `TextField`
+    ///
+    /// Bases that are not loaded end the chain.
+    pub fn ancestors(&self, fully_qualified_name: &str) -> Vec<&VisualElementInfo> {
+        let mut ancestors: Vec<&VisualElementInfo> = Vec::new();
+        let mut current = self.lookup(fully_qualified_name);
+        while let Some(base) = current.and_then(|element| element.base.as_deref()).and_then(|base| self.lookup(base)) {
+            // a schema can declare a cycle, don't follow it forever
+            if base.fully_qualified_name == fully_qualified_name || ancestors.iter().any(|a| a.fully_qualified_name == base.fully_qualified_name) {
+                break;
+            }
+            ancestors.push(base);
+            current = Some(base);
+        }
+        ancestors
+    }
+
+    /// Whether a visual element derives from another one, directly or not (an element doesn't derive from itself)
+    ///
+    /// Both are fully qualified names, e.g. `UnityEngine.UIElements.ScrollView` and `UnityEngine.UIElements.VisualElement`.
+    pub fn derives_from(&self, fully_qualified_name: &str, base: &str) -> bool {
+        self.ancestors(fully_qualified_name).iter().any(|ancestor| ancestor.fully_qualified_name == base)
+    }
+
+    /// All visual elements deriving from an element, directly or not, sorted by fully qualified name
+    pub fn derived_elements(&self, base: &str) -> Vec<&VisualElementInfo> {
+        let mut derived: Vec<&VisualElementInfo> = self
+            .visual_elements
+            .values()
+            .filter(|element| self.derives_from(&element.fully_qualified_name, base))
+            .collect();
+        derived.sort_by(|a, b| a.fully_qualified_name.cmp(&b.fully_qualified_name));
+        derived
+    }
 }
 
 /// Manages Unity UXML schema files and provides lookup functionality for UI elements
@@ -134,6 +247,12 @@ pub struct UxmlSchemaManager {
     visual_elements_data: Arc<Mutex<VisualElementsData>>,
     dir_changed: DirChanged,
     last_scan_timestamp: u64,
+    /// Custom elements of user code that may not be in the schema yet, see [`UxmlSchemaManager::set_custom_elements`]
+    custom_elements: Option<CustomElementScanner>,
+    /// Unity version of the project, selects the built-in elements used without a schema directory
+    unity_version: Option<UnityVersion>,
+    /// Whether the built-in elements are used, because the schema directory doesn't exist
+    using_fallback: bool,
 }
 
 impl UxmlSchemaManager {
@@ -160,6 +279,9 @@ impl UxmlSchemaManager {
             visual_elements_data: Arc::new(Mutex::new(VisualElementsData::new())),
             dir_changed,
             last_scan_timestamp: 0,
+            custom_elements: None,
+            unity_version: None,
+            using_fallback: false,
         }
     }
 
@@ -168,6 +290,38 @@ impl UxmlSchemaManager {
         Arc::clone(&self.visual_elements_data)
     }
 
+    /// Also provide the custom elements declared in C#, until Unity adds them to the schema
+    ///
+    /// Elements of the schema win over custom elements with the same name.
+    pub(crate) fn set_custom_elements(&mut self, scanner: CustomElementScanner) {
+        self.custom_elements = Some(scanner);
+    }
+
+    /// Set the Unity version of the project, used to select the built-in elements if the schema directory
+    /// doesn't exist, see [`crate::uxml::schema_fallback`]
+    pub(crate) fn set_unity_version(&mut self, version: Option<UnityVersion>) {
+        if self.unity_version != version {
+            self.unity_version = version;
+            // built again with the elements of the version on the next update
+            self.using_fallback = false;
+        }
+    }
+
+    /// Whether the built-in elements are used because the schema directory doesn't exist
+    pub fn is_using_fallback(&self) -> bool {
+        self.using_fallback
+    }
+
+    /// Parse all schema files and scan all custom elements again on the next update
+    pub fn invalidate(&mut self) {
+        self.schema_files.clear();
+        self.last_scan_timestamp = 0;
+        self.using_fallback = false;
+        if let Some(scanner) = &mut self.custom_elements {
+            scanner.invalidate();
+        }
+    }
+
     pub async fn some(&mut self) -> (){
         sleep(Duration::from_millis(1000)).await;
     }
@@ -176,24 +330,53 @@ impl UxmlSchemaManager {
     /// 
     /// Only performs directory scanning if changes have been detected by the file watcher.
     /// This optimization avoids expensive file system operations when no changes occurred.
+    /// Custom elements are scanned again when C# sources changed. While the schema directory doesn't
+    /// exist, the built-in elements of the project's Unity version are used instead.
     /// 
     /// ## Returns
     /// 
     /// * `Ok(())` if the update completed successfully
     /// * `Err(UxmlSchemaError)` if file I/O or XML parsing failed
-    pub async fn update(&mut self) -> Result<(), UxmlSchemaError> {        
+    pub async fn update(&mut self) -> Result<(), UxmlSchemaError> {
+        let schema_changed = self.scan_schema_files().await?;
+        let custom_elements_changed = match &mut self.custom_elements {
+            Some(scanner) => scanner.refresh().await,
+            None => false,
+        };
+
+        let use_fallback = !self.schema_directory.exists();
+        let fallback_changed = use_fallback != self.using_fallback;
+        if fallback_changed {
+            self.using_fallback = use_fallback;
+            if use_fallback {
+                log::info!(
+                    "Schema directory {} doesn't exist, using the built-in elements of Unity {}",
+                    self.schema_directory.display(),
+                    self.unity_version.map_or("(latest)".to_string(), |version| version.to_string())
+                );
+            }
+        }
+
+        if schema_changed || custom_elements_changed || fallback_changed {
+            self.rebuild_visual_elements();
+        }
+        Ok(())
+    }
+
+    /// Scan the schema directory if it changed, returns whether any schema file changed
+    async fn scan_schema_files(&mut self) -> Result<bool, UxmlSchemaError> {
         // Check if directory has changed since last scan
         let current_timestamp = self.dir_changed.last_change_timestamp();
         
         // Skip scan if no changes detected since last scan
         if current_timestamp <= self.last_scan_timestamp {
-            return Ok(());
+            return Ok(false);
         }
 
         // Make sure directory exists, other wise, we should not do anything
         // Not even logging, because repeated updates can cause lots of logs flooding our debug log 
         if !self.schema_directory.exists() {
-            return Ok(());
+            return Ok(false);
         }
 
         log::info!("Schema directory changed scanning for .xsd files in: {}", 
@@ -243,11 +426,6 @@ impl UxmlSchemaManager {
             any_changes = true;
         }
         
-        // If any changes occurred, rebuild the visual_elements from all cached files
-        if any_changes {
-            self.rebuild_visual_elements();
-        }
-        
         // Update last scan timestamp
         self.last_scan_timestamp = current_timestamp;
         
@@ -255,18 +433,21 @@ impl UxmlSchemaManager {
         log::info!("Schema update completed in {:.2}ms (found {} files, processed {} files, changes detected: {})", 
                    duration.as_secs_f64() * 1000.0, current_files.len(), processed_files_count, any_changes);
         
-        Ok(())
+        Ok(any_changes)
     }
 
     async fn process_schema_file(&mut self, path: &Path, last_modified: SystemTime) -> Result<(), UxmlSchemaError> {
         let content = fs::read_to_string(path).await?;
-        let (namespace, elements) = self.parse_schema_content(&content)?;
+        let parsed = self.parse_schema_content(&content)?;
         
         // Update file info cache
         let file_info = SchemaFileInfo {
             last_modified,
-            namespace,
-            elements,
+            namespace: parsed.namespace,
+            elements: parsed.elements,
+            complex_types: parsed.complex_types,
+            type_bases: parsed.type_bases,
+            simple_types: parsed.simple_types,
         };
         self.schema_files.insert(path.to_path_buf(), file_info);
         
@@ -274,17 +455,68 @@ impl UxmlSchemaManager {
     }
     
     /// Rebuilds the visual_elements HashMap from all cached schema files
+    ///
+    /// Types are resolved across all files, since elements often use types of other namespaces.
     fn rebuild_visual_elements(&mut self) {
+        let simple_types: HashMap<&String, &Vec<String>> = self.schema_files.values()
+            .flat_map(|file_info| file_info.simple_types.iter())
+            .collect();
+        let complex_types: HashMap<&String, &Vec<UxmlAttributeInfo>> = self.schema_files.values()
+            .flat_map(|file_info| file_info.complex_types.iter())
+            .collect();
+        let type_bases: HashMap<&String, &String> = self.schema_files.values()
+            .flat_map(|file_info| file_info.type_bases.iter())
+            .collect();
+        // the element of each type, to find the element of a base type
+        let type_elements: HashMap<&String, String> = self.schema_files.values()
+            .flat_map(|file_info| file_info.elements.iter().map(move |element| (element, &file_info.namespace)))
+            .filter_map(|(element, namespace)| Some((element.type_name.as_ref()?, format!("{}.{}", namespace, element.name))))
+            .collect();
+
         if let Ok(mut data) = self.visual_elements_data.lock() {
             data.clear();
+
+            // inserted first, so elements of the schema with the same name replace them
+            let custom_elements = self.custom_elements.iter().flat_map(|scanner| scanner.elements());
+            for element in custom_elements {
+                let fqn = element.fully_qualified_name();
+                data.insert(fqn.clone(), VisualElementInfo {
+                    name: element.name.clone(),
+                    namespace: element.namespace.clone(),
+                    fully_qualified_name: fqn,
+                    attributes: Vec::new(),
+                    base: None,
+                });
+            }
+
+            // the built-in elements stand in for the schema, so they replace custom elements too
+            if self.using_fallback {
+                data.incomplete = true;
+                for element in fallback_elements(self.unity_version) {
+                    data.insert(element.fully_qualified_name.clone(), element);
+                }
+            }
             
             for file_info in self.schema_files.values() {
-                for element_name in &file_info.elements {
-                    let fqn = format!("{}.{}", file_info.namespace, element_name);
+                for element in &file_info.elements {
+                    let attributes = element.type_name.as_ref()
+                        .and_then(|element_type| complex_types.get(element_type))
+                        .map(|attributes| attributes.iter().map(|attribute| resolve_attribute(attribute, &simple_types)).collect())
+                        .unwrap_or_default();
+
+                    let fqn = format!("{}.{}", file_info.namespace, element.name);
+                    let base = element.type_name.as_ref()
+                        .and_then(|element_type| type_bases.get(element_type))
+                        .and_then(|base_type| type_elements.get(base_type))
+                        .or(element.substitution_group.as_ref())
+                        .filter(|base| **base != fqn)
+                        .cloned();
                     let element_info = VisualElementInfo {
-                        name: element_name.clone(),
+                        name: element.name.clone(),
                         namespace: file_info.namespace.clone(),
                         fully_qualified_name: fqn.clone(),
+                        attributes,
+                        base,
                     };
                     data.insert(fqn, element_info);
                 }
@@ -294,63 +526,177 @@ impl UxmlSchemaManager {
         }
     } 
 
-    fn parse_schema_content(&self, content: &str) -> Result<(String, Vec<String>), UxmlSchemaError> {
+    fn parse_schema_content(&self, content: &str) -> Result<ParsedSchema, UxmlSchemaError> {
         let mut reader = Reader::from_str(content);
         
-        let mut namespace = String::new();
-        let mut elements = Vec::new();
+        let mut parsed = ParsedSchema::default();
+        // namespace prefixes declared on the schema element
+        let mut prefixes: HashMap<String, String> = HashMap::new();
+        // the named complex type being read
+        let mut complex_type: Option<String> = None;
+        // the named simple type being read
+        let mut simple_type: Option<String> = None;
+        // an attribute with nested content (an anonymous simple type) being read
+        let mut attribute: Option<UxmlAttributeInfo> = None;
+        let mut enumeration: Vec<String> = Vec::new();
         let mut buf = Vec::new();
         
         loop {
-            match reader.read_event_into(&mut buf)? {
-                Event::Start(ref e) | Event::Empty(ref e) => {
+            let event = reader.read_event_into(&mut buf)?;
+            match &event {
+                Event::Start(e) | Event::Empty(e) => {
+                    let is_empty = matches!(event, Event::Empty(_));
+                    let attributes = read_attributes(e);
+                    let get = |key: &str| attributes.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone());
+
                     match e.name().as_ref() {
                         b"xs:schema" => {
                             // Extract targetNamespace attribute
-                            for attr in e.attributes() {
-                                match attr {
-                                    Ok(attr) => {
-                                        if attr.key.as_ref() == b"targetNamespace" {
-                                            match std::str::from_utf8(&attr.value) {
-                                                Ok(value) => namespace = value.to_string(),
-                                                Err(_) => continue,
-                                            }
-                                        }
-                                    }
-                                    Err(_) => continue,
-                                }
+                            if let Some(value) = get("targetNamespace") {
+                                parsed.namespace = value;
                             }
+                            prefixes = namespace_prefixes(&attributes, &HashMap::new());
                         }
                         b"xs:element" => {
-                            // Extract element name attribute
-                            for attr in e.attributes() {
-                                match attr {
-                                    Ok(attr) => {
-                                        if attr.key.as_ref() == b"name" {
-                                            match std::str::from_utf8(&attr.value) {
-                                                Ok(value) => {
-                                                    elements.push(value.to_string());
-                                                    break;
-                                                }
-                                                Err(_) => continue,
-                                            }
-                                        }
-                                    }
-                                    Err(_) => continue,
+                            // Extract element name attribute, elements of a sequence only have a ref
+                            if let Some(name) = get("name") {
+                                let element_prefixes = namespace_prefixes(&attributes, &prefixes);
+                                parsed.elements.push(SchemaElement {
+                                    name,
+                                    type_name: get("type").map(|t| qualify(&t, &element_prefixes, &parsed.namespace)),
+                                    substitution_group: get("substitutionGroup")
+                                        .map(|group| qualify(&group, &element_prefixes, &parsed.namespace)),
+                                });
+                            }
+                        }
+                        b"xs:complexType" => {
+                            if let Some(name) = get("name") {
+                                let name = qualify(&name, &prefixes, &parsed.namespace);
+                                parsed.complex_types.entry(name.clone()).or_default();
+                                if !is_empty {
+                                    complex_type = Some(name);
                                 }
                             }
                         }
+                        b"xs:attribute" => {
+                            // attributes outside of complex types (e.g. attribute groups) are not used by elements
+                            if let (Some(type_name), Some(name)) = (complex_type.clone(), get("name")) {
+                                let value_type = match get("type") {
+                                    Some(t) if t == "xs:boolean" => UxmlAttributeType::Boolean,
+                                    Some(t) => UxmlAttributeType::Other(qualify(
+                                        &t,
+                                        &namespace_prefixes(&attributes, &prefixes),
+                                        &parsed.namespace,
+                                    )),
+                                    None => UxmlAttributeType::Other("xs:string".to_string()),
+                                };
+                                let info = UxmlAttributeInfo {
+                                    name,
+                                    value_type,
+                                    default_value: get("default"),
+                                };
+                                if is_empty {
+                                    parsed.complex_types.entry(type_name).or_default().push(info);
+                                } else {
+                                    attribute = Some(info);
+                                }
+                            }
+                        }
+                        b"xs:extension" | b"xs:restriction" => {
+                            // restrictions of simple types are not type hierarchy
+                            if let (Some(type_name), None, None) = (&complex_type, &simple_type, &attribute) {
+                                if let Some(base) = get("base") {
+                                    let base = qualify(&base, &namespace_prefixes(&attributes, &prefixes), &parsed.namespace);
+                                    parsed.type_bases.insert(type_name.clone(), base);
+                                }
+                            }
+                        }
+                        b"xs:simpleType" => {
+                            if attribute.is_none() && !is_empty {
+                                simple_type = get("name").map(|name| qualify(&name, &prefixes, &parsed.namespace));
+                            }
+                            enumeration.clear();
+                        }
+                        b"xs:enumeration" => {
+                            if let Some(value) = get("value") {
+                                enumeration.push(value);
+                            }
+                        }
                         _ => {}
                     }
                 }
+                Event::End(e) => match e.name().as_ref() {
+                    b"xs:complexType" => complex_type = None,
+                    b"xs:simpleType" => {
+                        if let Some(name) = simple_type.take() {
+                            parsed.simple_types.insert(name, std::mem::take(&mut enumeration));
+                        } else if let Some(info) = attribute.as_mut().filter(|_| !enumeration.is_empty()) {
+                            info.value_type = UxmlAttributeType::Enumeration(std::mem::take(&mut enumeration));
+                        }
+                    }
+                    b"xs:attribute" => {
+                        if let (Some(info), Some(type_name)) = (attribute.take(), complex_type.as_ref()) {
+                            parsed.complex_types.entry(type_name.clone()).or_default().push(info);
+                        }
+                    }
+                    _ => {}
+                },
                 Event::Eof => break,
                 _ => {}
             }
             buf.clear();
         }
         
-        Ok((namespace, elements))
+        Ok(parsed)
+    }
+}
+
+/// Attributes of an XML element as (key, value), attributes that fail to parse are skipped
+fn read_attributes(e: &quick_xml::events::BytesStart) -> Vec<(String, String)> {
+    e.attributes()
+        .filter_map(|attr| attr.ok())
+        .filter_map(|attr| {
+            let key = std::str::from_utf8(attr.key.as_ref()).ok()?.to_string();
+            let value = std::str::from_utf8(&attr.value).ok()?.to_string();
+            Some((key, value))
+        })
+        .collect()
+}
+
+/// Namespace prefixes declared by `xmlns:prefix` attributes, added to the `inherited` ones
+fn namespace_prefixes(attributes: &[(String, String)], inherited: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut prefixes = inherited.clone();
+    for (key, value) in attributes {
+        if let Some(prefix) = key.strip_prefix("xmlns:") {
+            prefixes.insert(prefix.to_string(), value.clone());
+        }
+    }
+    prefixes
+}
+
+/// Qualified name of a type, e.g. `q1:picking-mode_Type` becomes `UnityEngine.UIElements.picking-mode_Type`
+///
+/// Built-in XML schema types keep their `xs:` prefix, unprefixed names are in the target namespace.
+fn qualify(name: &str, prefixes: &HashMap<String, String>, target_namespace: &str) -> String {
+    match name.split_once(':') {
+        Some(("xs", _)) => name.to_string(),
+        Some((prefix, local)) => match prefixes.get(prefix) {
+            Some(namespace) => format!("{}.{}", namespace, local),
+            None => name.to_string(),
+        },
+        None => format!("{}.{}", target_namespace, name),
+    }
+}
+
+/// Resolve the simple type of an attribute to its enumeration values
+fn resolve_attribute(attribute: &UxmlAttributeInfo, simple_types: &HashMap<&String, &Vec<String>>) -> UxmlAttributeInfo {
+    let mut attribute = attribute.clone();
+    if let UxmlAttributeType::Other(type_name) = &attribute.value_type {
+        if let Some(values) = simple_types.get(type_name).filter(|values| !values.is_empty()) {
+            attribute.value_type = UxmlAttributeType::Enumeration((*values).clone());
+        }
     }
+    attribute
 }
 
 #[cfg(test)]
@@ -126,4 +126,210 @@ async fn test_namespace_extraction_from_real_files() {
             println!("✓ Found {} in correct namespace", expected);
         }
     }
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_attribute_metadata() {
+    let temp_dir = TempDir::new().unwrap();
+    let engine = r#"<?xml version="1.0" encoding="utf-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:engine="UnityEngine.UIElements" targetNamespace="UnityEngine.UIElements" elementFormDefault="qualified">
+  <xs:simpleType name="VisualElement_picking-mode_Type">
+    <xs:restriction base="xs:string">
+      <xs:enumeration value="Position" />
+      <xs:enumeration value="Ignore" />
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:complexType name="ToggleType">
+    <xs:complexContent mixed="false">
+      <xs:restriction base="engine:VisualElementType">
+        <xs:attribute default="" name="name" type="xs:string" use="optional" />
+        <xs:attribute default="true" name="focusable" type="xs:boolean" use="optional" />
+        <xs:attribute default="Position" name="picking-mode" xmlns:q1="UnityEngine.UIElements" type="q1:VisualElement_picking-mode_Type" use="optional" />
+        <xs:attribute default="Left" name="alignment" use="optional">
+          <xs:simpleType>
+            <xs:restriction base="xs:string">
+              <xs:enumeration value="Left" />
+              <xs:enumeration value="Right" />
+            </xs:restriction>
+          </xs:simpleType>
+        </xs:attribute>
+      </xs:restriction>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:element name="Toggle" substitutionGroup="engine:VisualElement" xmlns:q2="UnityEngine.UIElements" type="q2:ToggleType" />
+</xs:schema>"#;
+    // an element of another namespace that uses a simple type of the engine namespace
+    let editor = r#"<?xml version="1.0" encoding="utf-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="UnityEditor.UIElements" elementFormDefault="qualified">
+  <xs:complexType name="ToolbarType">
+    <xs:attribute default="Ignore" name="picking-mode" xmlns:q1="UnityEngine.UIElements" type="q1:VisualElement_picking-mode_Type" use="optional" />
+  </xs:complexType>
+  <xs:element name="Toolbar" type="ToolbarType" />
+</xs:schema>"#;
+    fs::write(temp_dir.path().join("UnityEngine.UIElements.xsd"), engine).unwrap();
+    fs::write(temp_dir.path().join("UnityEditor.UIElements.xsd"), editor).unwrap();
+
+    let mut manager = UxmlSchemaManager::new(temp_dir.path().to_path_buf());
+    manager.update().await.unwrap();
+    let visual_elements_data = manager.get_visual_elements_data();
+    let data = visual_elements_data.lock().unwrap();
+
+    let toggle = data.lookup("UnityEngine.UIElements.Toggle").unwrap();
+    assert_eq!(toggle.attributes.len(), 4);
+    assert_eq!(toggle.attribute("name").unwrap().value_type, UxmlAttributeType::Other("xs:string".to_string()));
+    let focusable = toggle.attribute("focusable").unwrap();
+    assert_eq!(focusable.value_type, UxmlAttributeType::Boolean);
+    assert_eq!(focusable.default_value.as_deref(), Some("true"));
+    assert_eq!(
+        toggle.attribute("picking-mode").unwrap().value_type,
+        UxmlAttributeType::Enumeration(vec!["Position".to_string(), "Ignore".to_string()])
+    );
+    assert_eq!(
+        toggle.attribute("alignment").unwrap().value_type,
+        UxmlAttributeType::Enumeration(vec!["Left".to_string(), "Right".to_string()])
+    );
+
+    let toolbar = data.lookup("UnityEditor.UIElements.Toolbar").unwrap();
+    assert!(matches!(toolbar.attribute("picking-mode").unwrap().value_type, UxmlAttributeType::Enumeration(_)));
+}
+
+#[tokio::test]
+async fn test_custom_elements_merged_with_schema() {
+    let project = TempDir::new().unwrap();
+    let schema_dir = project.path().join("UIElementsSchema");
+    fs::create_dir_all(&schema_dir).unwrap();
+    let schema = r#"<?xml version="1.0" encoding="utf-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="MyGame.UI" elementFormDefault="qualified">
+  <xs:complexType name="ManaBarType">
+    <xs:attribute default="" name="name" type="xs:string" use="optional" />
+  </xs:complexType>
+  <xs:element name="ManaBar" type="ManaBarType" />
+</xs:schema>"#;
+    fs::write(schema_dir.join("MyGame.UI.xsd"), schema).unwrap();
+
+    let scripts = project.path().join("Assets");
+    fs::create_dir_all(&scripts).unwrap();
+    let source = "namespace MyGame.UI {\n[UxmlElement] partial class HealthBar : VisualElement { }\n[UxmlElement] partial class ManaBar : VisualElement { }\n}";
+    fs::write(scripts.join("Bars.cs"), source).unwrap();
+    fs::write(
+        project.path().join("Assembly-CSharp.csproj"),
+        r#"<Project><AssemblyName>Assembly-CSharp</AssemblyName><Compile Include="Assets/Bars.cs" /></Project>"#,
+    )
+    .unwrap();
+
+    let mut manager = UxmlSchemaManager::new(schema_dir);
+    manager.set_custom_elements(CustomElementScanner::new(project.path().to_path_buf()));
+    manager.update().await.unwrap();
+    let visual_elements_data = manager.get_visual_elements_data();
+    let data = visual_elements_data.lock().unwrap();
+
+    // not in the schema yet
+    let health_bar = data.lookup_by_name("HealthBar").unwrap();
+    assert_eq!(health_bar.fully_qualified_name, "MyGame.UI.HealthBar");
+    assert!(health_bar.attributes.is_empty());
+    // the schema has its attributes
+    assert_eq!(data.lookup_by_name("ManaBar").unwrap().attributes.len(), 1);
+}
+
+#[tokio::test]
+async fn test_element_hierarchy() {
+    let temp_dir = TempDir::new().unwrap();
+    let schema = r#"<?xml version="1.0" encoding="utf-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:engine="UnityEngine.UIElements" targetNamespace="UnityEngine.UIElements" elementFormDefault="qualified">
+  <xs:complexType name="VisualElementType">
+    <xs:attribute default="" name="name" type="xs:string" use="optional" />
+  </xs:complexType>
+  <xs:element name="VisualElement" type="engine:VisualElementType" />
+  <xs:complexType name="BindableElementType">
+    <xs:complexContent mixed="false">
+      <xs:extension base="engine:VisualElementType">
+        <xs:attribute default="" name="binding-path" type="xs:string" use="optional" />
+      </xs:extension>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:element name="BindableElement" type="engine:BindableElementType" />
+  <xs:complexType name="TextFieldType">
+    <xs:complexContent mixed="false">
+      <xs:extension base="engine:BindableElementType">
+        <xs:attribute default="" name="value" type="xs:string" use="optional" />
+      </xs:extension>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:element name="TextField" substitutionGroup="engine:VisualElement" type="engine:TextFieldType" />
+  <xs:complexType name="ScrollViewType">
+    <xs:complexContent mixed="false">
+      <xs:restriction base="engine:VisualElementType">
+        <xs:attribute default="" name="name" type="xs:string" use="optional" />
+      </xs:restriction>
+    </xs:complexContent>
+  </xs:complexType>
+  <xs:element name="ScrollView" substitutionGroup="engine:VisualElement" type="engine:ScrollViewType" />
+  <xs:complexType name="LabelType">
+    <xs:attribute default="" name="text" type="xs:string" use="optional" />
+  </xs:complexType>
+  <xs:element name="Label" substitutionGroup="engine:BindableElement" type="engine:LabelType" />
+</xs:schema>"#;
+    fs::write(temp_dir.path().join("UnityEngine.UIElements.xsd"), schema).unwrap();
+
+    let mut manager = UxmlSchemaManager::new(temp_dir.path().to_path_buf());
+    manager.update().await.unwrap();
+    let visual_elements_data = manager.get_visual_elements_data();
+    let data = visual_elements_data.lock().unwrap();
+
+    let name = |element: &VisualElementInfo| element.name.clone();
+    assert_eq!(data.lookup("UnityEngine.UIElements.VisualElement").unwrap().base, None);
+    // the base of the type wins over the substitution group
+    assert_eq!(
+        data.ancestors("UnityEngine.UIElements.TextField").into_iter().map(name).collect::<Vec<_>>(),
+        vec!["BindableElement", "VisualElement"]
+    );
+    // without a base type the substitution group is the base
+    assert_eq!(
+        data.lookup("UnityEngine.UIElements.Label").unwrap().base.as_deref(),
+        Some("UnityEngine.UIElements.BindableElement")
+    );
+
+    assert!(data.derives_from("UnityEngine.UIElements.ScrollView", "UnityEngine.UIElements.VisualElement"));
+    assert!(!data.derives_from("UnityEngine.UIElements.ScrollView", "UnityEngine.UIElements.BindableElement"));
+    assert!(!data.derives_from("UnityEngine.UIElements.VisualElement", "UnityEngine.UIElements.VisualElement"));
+    assert_eq!(
+        data.derived_elements("UnityEngine.UIElements.BindableElement").into_iter().map(name).collect::<Vec<_>>(),
+        vec!["Label", "TextField"]
+    );
+}
+
+#[tokio::test]
+async fn test_built_in_elements_without_schema() {
+    use crate::test_utils::{UnityProjectBuilder, ENGINE_SCHEMA};
+    use crate::unity_project_manager::UnityVersion;
+
+    let project = UnityProjectBuilder::new().unity_version("2021.3.5f1").build().unwrap();
+    let mut manager = UxmlSchemaManager::new(project.join("UIElementsSchema"));
+    manager.set_unity_version(Some(UnityVersion::new(2021, 3)));
+    manager.update().await.unwrap();
+    assert!(manager.is_using_fallback());
+    {
+        let data = manager.get_visual_elements_data();
+        let data = data.lock().unwrap();
+        assert!(data.is_incomplete());
+        assert!(data.lookup_by_name("Button").is_some());
+        assert!(data.lookup("UnityEditor.UIElements.FloatField").is_some());
+        assert!(data.lookup_by_name("TreeView").is_none());
+        assert!(data.derives_from("UnityEngine.UIElements.Button", "UnityEngine.UIElements.VisualElement"));
+    }
+
+    // another version selects other elements
+    manager.set_unity_version(Some(UnityVersion::new(6000, 0)));
+    manager.update().await.unwrap();
+    assert!(manager.get_visual_elements_data().lock().unwrap().lookup("UnityEngine.UIElements.TabView").is_some());
+
+    // the generated schema replaces them
+    project.write("UIElementsSchema/UnityEngine.UIElements.xsd", ENGINE_SCHEMA).unwrap();
+    manager.update().await.unwrap();
+    assert!(!manager.is_using_fallback());
+    let data = manager.get_visual_elements_data();
+    let data = data.lock().unwrap();
+    assert!(!data.is_incomplete());
+    assert!(data.lookup("UnityEngine.UIElements.Toggle").is_some());
+    assert!(data.lookup_by_name("TabView").is_none());
+}